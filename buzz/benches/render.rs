@@ -0,0 +1,36 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+use buzz::{render_pass, scenes, Framebuffer, RenderConfig};
+
+fn samples_per_second(c: &mut Criterion) {
+    let (width, height, samples) = (64, 64, 4);
+    let config = RenderConfig {
+        width,
+        height,
+        samples,
+        seed: Some(0),
+        ..Default::default()
+    };
+
+    let mut group = c.benchmark_group("buzz_render");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(u64::from(width * height * samples)));
+
+    for (name, (scene, camera)) in [
+        ("cornell_box", scenes::cornell_box()),
+        ("veach_mis", scenes::veach_mis()),
+        ("furnace", scenes::furnace(0.8)),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut fb = Framebuffer::new(width, height);
+                render_pass(&camera, &scene, &config, &mut fb, samples, 42);
+                fb
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, samples_per_second);
+criterion_main!(benches);
@@ -1,7 +1,6 @@
-use geo::{v3, Vec3};
 use sketch_utils::opener;
 
-use buzz::*;
+use buzz::prelude::*;
 
 pub fn main() -> opener::Result<()> {
     let target = v3(0.0, 0.0, -1.0);
@@ -33,13 +32,12 @@ pub fn main() -> opener::Result<()> {
         &RenderConfig {
             width: 400,
             height: 200,
-            samples: 10,
-            max_bounces: 5,
             direct_lighting: false,
             soft_shadows: false,
+            ..Default::default()
         },
     );
-    img.save("basic.ppm").expect("cannot save output image");
+    img.save_png("basic.png").expect("cannot save output image");
 
-    opener::open("basic.ppm")
+    opener::open("basic.png")
 }
@@ -41,19 +41,8 @@ pub fn main() -> opener::Result<()> {
 
     let camera = Camera::look_at(v3(-3.0, 0.0, 1.0), Vec3::zero(), v3(0, 0, 1), 35.0);
 
-    let img = parallel_render(
-        &camera,
-        &scene,
-        &RenderConfig {
-            width: 1920,
-            height: 1080,
-            max_bounces: 5,
-            samples: 10,
-            direct_lighting: true,
-            soft_shadows: true,
-        },
-    );
-    img.save("csg.ppm").expect("cannot save output image");
+    let img = parallel_render(&camera, &scene, &RenderConfig::default());
+    img.save_png("csg.png").expect("cannot save output image");
 
-    opener::open("csg.ppm")
+    opener::open("csg.png")
 }
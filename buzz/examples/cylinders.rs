@@ -34,19 +34,9 @@ pub fn main() -> opener::Result<()> {
 
     let camera = Camera::look_at(v3(0, 3, 1), v3(0, 0, 1), v3(0, 0, 1), 50.0);
 
-    let img = parallel_render(
-        &camera,
-        &scene,
-        &RenderConfig {
-            width: 1920,
-            height: 1080,
-            max_bounces: 5,
-            samples: 10,
-            direct_lighting: true,
-            soft_shadows: true,
-        },
-    );
-    img.save("cylinders.ppm").expect("cannot save output image");
+    let img = parallel_render(&camera, &scene, &RenderConfig::default());
+    img.save_png("cylinders.png")
+        .expect("cannot save output image");
 
-    opener::open("cylinders.ppm")
+    opener::open("cylinders.png")
 }
@@ -1,7 +1,6 @@
-use geo::{v3, Vec3};
 use sketch_utils::opener;
 
-use buzz::*;
+use buzz::prelude::*;
 
 pub fn main() -> opener::Result<()> {
     let mut objects = SceneObjects::new();
@@ -22,19 +21,8 @@ pub fn main() -> opener::Result<()> {
 
     let camera = Camera::look_at(v3(3, 3, 3), v3(0, 0, 0.5), v3(0, 0, 1), 50.0);
 
-    let img = render(
-        &camera,
-        &scene,
-        &RenderConfig {
-            width: 1920,
-            height: 1080,
-            max_bounces: 5,
-            samples: 10,
-            direct_lighting: true,
-            soft_shadows: true,
-        },
-    );
-    img.save("hello.ppm").expect("cannot save output image");
+    let img = render(&camera, &scene, &RenderConfig::default());
+    img.save_png("hello.png").expect("cannot save output image");
 
-    opener::open("hello.ppm")
+    opener::open("hello.png")
 }
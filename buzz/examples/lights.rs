@@ -33,13 +33,11 @@ fn main() -> opener::Result<()> {
         &RenderConfig {
             width: 400,
             height: 200,
-            samples: 10,
-            max_bounces: 5,
-            direct_lighting: true,
-            soft_shadows: true,
+            ..Default::default()
         },
     );
-    img.save("lights.ppm").expect("cannot save output image");
+    img.save_png("lights.png")
+        .expect("cannot save output image");
 
-    opener::open("lights.ppm")
+    opener::open("lights.png")
 }
@@ -60,11 +60,12 @@ pub fn main() -> opener::Result<()> {
             height: 4096,
             samples: 20,
             max_bounces: 10,
-            direct_lighting: true,
             soft_shadows: false,
+            ..Default::default()
         },
     );
-    img.save("particles.ppm").expect("cannot save output image");
+    img.save_png("particles.png")
+        .expect("cannot save output image");
 
-    opener::open("particles.ppm")
+    opener::open("particles.png")
 }
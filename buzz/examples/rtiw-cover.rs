@@ -70,13 +70,15 @@ pub fn main() -> opener::Result<()> {
             width: 1200,
             height: 800,
             max_bounces: 50,
+            throughput_threshold: 0.05,
             samples: 50,
             direct_lighting: false,
             soft_shadows: false,
+            ..Default::default()
         },
     );
-    img.save("ray-tracing-in-a-weekend-cover.ppm")
+    img.save_png("ray-tracing-in-a-weekend-cover.png")
         .expect("cannot save output image");
 
-    opener::open("ray-tracing-in-a-weekend-cover.ppm")
+    opener::open("ray-tracing-in-a-weekend-cover.png")
 }
@@ -0,0 +1,126 @@
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use rand::prelude::*;
+use sketch_utils::{args::Cli, opener};
+
+use buzz::{
+    checkpoint::{self, Checkpoint},
+    *,
+};
+
+/// How often to save the partial render to disk.
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Samples per pixel rendered between two checkpoints checks.
+const PASS_SAMPLES: u32 = 4;
+
+pub fn main() -> opener::Result<()> {
+    let args = Cli::new("scene_file", "Render a scene file.")
+        .with_positional("SCENE", "the scene file to render, suzanne by default")
+        .with_flag("--resume", "resume the render from the last checkpoint")
+        .with_flag("--denoise", "denoise the final image")
+        .parse();
+
+    let resume = args.flag("--resume");
+    let denoise = args.flag("--denoise");
+
+    let path = args
+        .positional(0)
+        .expect("invalid SCENE")
+        .unwrap_or_else(|| {
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("..")
+                .join("data")
+                .join("scenes")
+                .join("suzanne.toml")
+                .display()
+                .to_string()
+        });
+
+    let scene_file::SceneFile {
+        camera,
+        scene,
+        mut config,
+        meshes,
+    } = scene_file::load(&path).expect("cannot load scene file");
+
+    // the command line takes precedence over the scene file
+    config.width = args.width.unwrap_or(config.width);
+    config.height = args.height.unwrap_or(config.height);
+    config.samples = args.samples.unwrap_or(config.samples);
+    config.seed = args.seed.or(config.seed);
+
+    let checkpoint_path = "scene.ckpt";
+    // editing a mesh must invalidate the checkpoint as much as editing the
+    // scene file
+    let mut scene_data = fs::read(&path).expect("cannot read scene file");
+    for mesh in &meshes {
+        scene_data.extend(fs::read(mesh).expect("cannot read mesh file"));
+    }
+    let scene_hash = checkpoint::hash_bytes(&scene_data);
+
+    let mut checkpoint = if resume {
+        Checkpoint::resume(checkpoint_path, scene_hash, config.width, config.height)
+            .expect("cannot resume render")
+    } else {
+        Checkpoint::new(
+            scene_hash,
+            config.seed.unwrap_or_else(|| thread_rng().gen()),
+            Framebuffer::new(config.width, config.height),
+        )
+    };
+
+    let mut last_save = Instant::now();
+    while checkpoint.framebuffer.samples() < config.samples {
+        let samples = PASS_SAMPLES.min(config.samples - checkpoint.framebuffer.samples());
+        render_pass(
+            &camera,
+            &scene,
+            &config,
+            &mut checkpoint.framebuffer,
+            samples,
+            checkpoint.seed,
+        );
+
+        if last_save.elapsed() >= CHECKPOINT_INTERVAL {
+            checkpoint
+                .save(checkpoint_path)
+                .expect("cannot save checkpoint");
+            last_save = Instant::now();
+        }
+    }
+
+    let output = args.output_or("scene.png");
+    let framebuffer = checkpoint.framebuffer.with_srgb(config.srgb);
+    if denoise {
+        let aovs = render_aovs(&camera, &scene, &config, 4);
+        framebuffer
+            .to_denoised_image(&BilateralFilter::default(), &aovs)
+            .save_png(&output)
+    } else if config.transparent_background {
+        framebuffer.save_rgba_png(&output)
+    } else {
+        framebuffer.save_png(&output)
+    }
+    .expect("cannot save output image");
+
+    // keep the linear colors too for further processing
+    let exr = Path::new(&output).with_extension("exr");
+    framebuffer
+        .save_exr(&exr.to_string_lossy())
+        .expect("cannot save output image");
+
+    if Path::new(checkpoint_path).exists() {
+        fs::remove_file(checkpoint_path).expect("cannot remove checkpoint");
+    }
+
+    if args.open {
+        opener::open(&output)?;
+    }
+
+    Ok(())
+}
@@ -41,16 +41,13 @@ pub fn main() -> opener::Result<()> {
         &camera,
         &scene,
         &RenderConfig {
-            width: 1920,
-            height: 1080,
-            max_bounces: 5,
             samples: 25,
-            direct_lighting: true,
-            soft_shadows: true,
+            ..Default::default()
         },
     );
 
-    img.save("teapot.ppm").expect("cannot save output image");
+    img.save_png("teapot.png")
+        .expect("cannot save output image");
 
-    opener::open("teapot.ppm")
+    opener::open("teapot.png")
 }
@@ -6,6 +6,8 @@ use rand::Rng;
 
 use geo::{ray::Ray, Vec3};
 
+use crate::RayDifferential;
+
 /// A `Camera` is an object that allows to cast rays towards a 3D point in world
 /// space that is calculated from a 2D point in screen space.
 #[derive(Debug, Clone, PartialEq)]
@@ -121,6 +123,30 @@ impl Camera {
             None => Ray::new(self.position, rd),
         }
     }
+
+    /// Return the `RayDifferential` of a `ray` cast by `cast_ray` for an image
+    /// with the given dimensions, that is the rays going through the points
+    /// one pixel to the right and one pixel below. The lens is ignored and the
+    /// offset rays start from the origin of `ray`.
+    pub fn ray_differential(&self, ray: &Ray, (width, height): (u32, u32)) -> RayDifferential {
+        let width = f64::from(width);
+        let height = f64::from(height);
+        let aspect = width / height;
+
+        // scale the direction back to the one built by `cast_ray` before
+        // normalizing it, then move by one pixel in NDC
+        let d = ray.dir.normalized();
+        let rd = d * (self.m / d.dot(self.w));
+        let dx = self.u * (2.0 * aspect / (width - 1.0));
+        let dy = self.v * (-2.0 / (height - 1.0));
+
+        RayDifferential {
+            rx_origin: ray.origin,
+            rx_dir: (rd + dx).normalized(),
+            ry_origin: ray.origin,
+            ry_dir: (rd + dy).normalized(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -175,6 +201,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_ray_differential() {
+        let c = Camera::look_at(v3(0, 0, 5), v3(0, 0, 0), v3(0, 1, 0), 90.0);
+        let ray = Ray::new(c.position, v3(0, 0, -1));
+
+        // the offset rays go through the neighbouring pixels on the plane at
+        // unit distance which is 2 units wide and tall
+        let d = c.ray_differential(&ray, (101, 101));
+        let (px, py) = (d.rx_dir / -d.rx_dir.z, d.ry_dir / -d.ry_dir.z);
+        assert!(px.dist(v3(0.02, 0, -1)) < 1e-9, "{px:?}");
+        assert!(py.dist(v3(0, -0.02, -1)) < 1e-9, "{py:?}");
+
+        assert!((d.footprint(v3(0, 0, 0), v3(0, 0, 1)) - 0.1).abs() < 1e-9);
+    }
+
     #[test]
     fn test_cast_ray_with_focus() {
         let mut rng = XorShiftRng::seed_from_u64(0);
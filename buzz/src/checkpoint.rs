@@ -0,0 +1,285 @@
+//! Save the state of a progressive render to disk so that it can be resumed
+//! later on, for example after a crash or a restart of the machine.
+//!
+//! A checkpoint stores the accumulated samples of the `Framebuffer`, the seed
+//! of the random number generator and a hash of the scene being rendered, so
+//! that a render is never resumed with a different scene by mistake.
+//!
+//! The checkpoints saved before the `Framebuffer` had an alpha channel can
+//! still be loaded, their pixels are all opaque.
+
+use std::{
+    fmt,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use geo::Vec3;
+
+use crate::Framebuffer;
+
+const MAGIC: &[u8; 8] = b"BUZZCKP2";
+const MAGIC_NO_ALPHA: &[u8; 8] = b"BUZZCKP1";
+
+/// Size of the magic number, the hash, the seed, the dimensions and the
+/// samples.
+const HEADER_SIZE: u64 = 36;
+
+/// Result type returned by `Checkpoint::load`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Possible errors while loading a checkpoint.
+#[derive(Debug)]
+pub enum Error {
+    /// IO error.
+    IoError(io::Error),
+
+    /// The file is not a checkpoint or it's truncated.
+    BadFormat,
+
+    /// The checkpoint was saved while rendering a different scene.
+    SceneMismatch,
+}
+
+/// A snapshot of a progressive render.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    /// Hash of the scene being rendered, see `hash_bytes`.
+    pub scene_hash: u64,
+
+    /// Seed passed to every `render_pass`.
+    pub seed: u64,
+
+    pub framebuffer: Framebuffer,
+}
+
+impl Checkpoint {
+    pub fn new(scene_hash: u64, seed: u64, framebuffer: Framebuffer) -> Self {
+        Self {
+            scene_hash,
+            seed,
+            framebuffer,
+        }
+    }
+
+    /// Load the checkpoint at `path` making sure it belongs to the scene with
+    /// the given hash and that it has the expected dimensions.
+    pub fn resume(
+        path: impl AsRef<Path>,
+        scene_hash: u64,
+        width: u32,
+        height: u32,
+    ) -> Result<Self> {
+        let checkpoint = Self::load(path)?;
+
+        let fb = &checkpoint.framebuffer;
+        if checkpoint.scene_hash != scene_hash || (fb.width(), fb.height()) != (width, height) {
+            return Err(Error::SceneMismatch);
+        }
+
+        Ok(checkpoint)
+    }
+
+    /// Load the checkpoint saved at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let f = File::open(path)?;
+        let file_size = f.metadata()?.len();
+        let mut r = BufReader::new(f);
+
+        let mut magic = [0; 8];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC && &magic != MAGIC_NO_ALPHA {
+            return Err(Error::BadFormat);
+        }
+
+        let scene_hash = read_u64(&mut r)?;
+        let seed = read_u64(&mut r)?;
+        let width = read_u32(&mut r)?;
+        let height = read_u32(&mut r)?;
+        let samples = read_u32(&mut r)?;
+
+        // the dimensions come from the file itself, make sure it really
+        // contains that many pixels before allocating room for them
+        let pixel_size = if &magic == MAGIC { 4 * 8 } else { 3 * 8 };
+        let n = u64::from(width) * u64::from(height);
+        let expected_size = n
+            .checked_mul(pixel_size)
+            .and_then(|s| s.checked_add(HEADER_SIZE));
+        if expected_size != Some(file_size) {
+            return Err(Error::BadFormat);
+        }
+        let n = usize::try_from(n).map_err(|_| Error::BadFormat)?;
+
+        let mut data = Vec::with_capacity(n);
+        for _ in 0..n {
+            data.push(Vec3::new(
+                read_f64(&mut r)?,
+                read_f64(&mut r)?,
+                read_f64(&mut r)?,
+            ));
+        }
+
+        let mut fb = Framebuffer::from_raw(width, height, samples, data);
+        if &magic == MAGIC {
+            let mut alpha = Vec::with_capacity(n);
+            for _ in 0..n {
+                alpha.push(read_f64(&mut r)?);
+            }
+            fb = fb.with_alpha(alpha);
+        }
+
+        Ok(Self::new(scene_hash, seed, fb))
+    }
+
+    /// Save the checkpoint to `path`.
+    ///
+    /// The checkpoint is first written to a temporary file that then replaces
+    /// `path` so that a crash while saving never corrupts the previous
+    /// checkpoint.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+
+        {
+            let fb = &self.framebuffer;
+            let mut w = BufWriter::new(File::create(&tmp)?);
+
+            w.write_all(MAGIC)?;
+            w.write_all(&self.scene_hash.to_le_bytes())?;
+            w.write_all(&self.seed.to_le_bytes())?;
+            w.write_all(&fb.width().to_le_bytes())?;
+            w.write_all(&fb.height().to_le_bytes())?;
+            w.write_all(&fb.samples().to_le_bytes())?;
+
+            for c in fb.data() {
+                w.write_all(&c.x.to_le_bytes())?;
+                w.write_all(&c.y.to_le_bytes())?;
+                w.write_all(&c.z.to_le_bytes())?;
+            }
+            for a in fb.alpha() {
+                w.write_all(&a.to_le_bytes())?;
+            }
+
+            w.into_inner()?.sync_all()?;
+        }
+
+        fs::rename(tmp, path)
+    }
+}
+
+/// Hash the given bytes with FNV-1a.
+///
+/// Unlike the hashers in std the result is stable across runs and Rust
+/// versions, therefore it can be used to identify a scene in a `Checkpoint`.
+pub fn hash_bytes(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |h, b| {
+        (h ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn read_u32(r: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(r: &mut impl Read) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_f64(r: &mut impl Read) -> io::Result<f64> {
+    let mut buf = [0; 8];
+    r.read_exact(&mut buf)?;
+    Ok(f64::from_le_bytes(buf))
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            Error::BadFormat
+        } else {
+            Error::IoError(e)
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::IoError(e) => write!(f, "io error: {e}"),
+            Error::BadFormat => write!(f, "not a valid checkpoint"),
+            Error::SceneMismatch => write!(f, "the checkpoint belongs to a different scene"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use geo::v3;
+
+    #[test]
+    fn test_save_load() {
+        let fb = Framebuffer::from_raw(2, 1, 7, vec![v3(1.0, 2.0, 3.0), v3(0.5, 0.25, f64::MAX)])
+            .with_alpha(vec![7.0, 2.5]);
+        let checkpoint = Checkpoint::new(hash_bytes(b"scene"), 42, fb);
+
+        let path = temp_path("save-load");
+        checkpoint.save(&path).unwrap();
+
+        assert_eq!(Checkpoint::load(&path).unwrap(), checkpoint);
+        assert!(Checkpoint::resume(&path, hash_bytes(b"scene"), 2, 1).is_ok());
+        assert!(matches!(
+            Checkpoint::resume(&path, hash_bytes(b"other"), 2, 1),
+            Err(Error::SceneMismatch)
+        ));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_bad_size() {
+        let fb = Framebuffer::from_raw(2, 2, 1, vec![v3(1, 1, 1); 4]);
+        let path = temp_path("bad-size");
+        Checkpoint::new(1, 2, fb).save(&path).unwrap();
+        let data = fs::read(&path).unwrap();
+
+        // truncated or with trailing garbage
+        for len in [HEADER_SIZE as usize, data.len() - 1] {
+            fs::write(&path, &data[..len]).unwrap();
+            assert!(matches!(Checkpoint::load(&path), Err(Error::BadFormat)));
+        }
+        fs::write(&path, [&data[..], &[0; 8]].concat()).unwrap();
+        assert!(matches!(Checkpoint::load(&path), Err(Error::BadFormat)));
+
+        // huge dimensions must not be trusted
+        let mut huge = data.clone();
+        huge[24..32].copy_from_slice(&[0xff; 8]);
+        fs::write(&path, &huge).unwrap();
+        assert!(matches!(Checkpoint::load(&path), Err(Error::BadFormat)));
+
+        // the old format without alpha has smaller pixels
+        let mut no_alpha = data[..data.len() - 4 * 8].to_vec();
+        no_alpha[..8].copy_from_slice(MAGIC_NO_ALPHA);
+        fs::write(&path, &no_alpha).unwrap();
+        let checkpoint = Checkpoint::load(&path).unwrap();
+        assert_eq!(checkpoint.framebuffer.alpha(), &[1.0; 4][..]);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    /// A path in the temporary directory that doesn't clash with the other
+    /// tests, not even the ones of other processes running at the same time.
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "buzz-test-checkpoint-{name}-{}.ckpt",
+            std::process::id()
+        ))
+    }
+}
@@ -0,0 +1,151 @@
+//! Remove the noise from low sample renders.
+//!
+//! A `Denoiser` receives the linear colors of a render after all the samples
+//! have been accumulated along with the `Aovs` of the scene that it can use to
+//! preserve the edges of the objects.
+
+use geo::Vec3;
+use rayon::prelude::*;
+
+use crate::Aovs;
+
+/// A `Denoiser` filters the noise of a rendered image.
+pub trait Denoiser: Sync {
+    /// Return the denoised version of `color`, the linear color of each pixel
+    /// row by row, using the given `Aovs` as a guide.
+    fn denoise(&self, color: &[Vec3], aovs: &Aovs) -> Vec<Vec3>;
+}
+
+/// An edge aware [bilateral filter][0].
+///
+/// Each pixel is replaced by the weighted average of its neighbors where the
+/// weight decreases with both the distance between the pixels and how much
+/// their colors, normals and albedos differ. Therefore, noise is smoothed out
+/// on flat surfaces while the edges between objects are kept sharp.
+///
+/// [0]: https://en.wikipedia.org/wiki/Bilateral_filter
+#[derive(Debug, Clone, PartialEq)]
+pub struct BilateralFilter {
+    /// half size of the window of neighbors to consider.
+    pub radius: u32,
+
+    /// standard deviations of the gaussians weighting the distance between
+    /// the pixels and the differences of their colors, normals and albedos.
+    pub sigma_spatial: f64,
+    pub sigma_color: f64,
+    pub sigma_normal: f64,
+    pub sigma_albedo: f64,
+}
+
+impl BilateralFilter {
+    pub fn new(radius: u32) -> Self {
+        Self {
+            radius,
+            sigma_spatial: f64::from(radius.max(1)) / 2.0,
+            sigma_color: 0.5,
+            sigma_normal: 0.2,
+            sigma_albedo: 0.1,
+        }
+    }
+
+    pub fn with_sigma_spatial(mut self, sigma: f64) -> Self {
+        self.sigma_spatial = sigma;
+        self
+    }
+
+    pub fn with_sigma_color(mut self, sigma: f64) -> Self {
+        self.sigma_color = sigma;
+        self
+    }
+
+    pub fn with_sigma_normal(mut self, sigma: f64) -> Self {
+        self.sigma_normal = sigma;
+        self
+    }
+
+    pub fn with_sigma_albedo(mut self, sigma: f64) -> Self {
+        self.sigma_albedo = sigma;
+        self
+    }
+}
+
+impl Default for BilateralFilter {
+    fn default() -> Self {
+        Self::new(5)
+    }
+}
+
+impl Denoiser for BilateralFilter {
+    fn denoise(&self, color: &[Vec3], aovs: &Aovs) -> Vec<Vec3> {
+        let (w, h) = (aovs.width as usize, aovs.height as usize);
+        assert_eq!(color.len(), w * h);
+
+        let r = self.radius as usize;
+        let weight = |d2: f64, sigma: f64| (-d2 / (2.0 * sigma * sigma)).exp();
+
+        (0..w * h)
+            .into_par_iter()
+            .map(|i| {
+                let (x, y) = (i % w, i / w);
+
+                let mut sum = Vec3::zero();
+                let mut total = 0.0;
+
+                for qy in y.saturating_sub(r)..(y + r + 1).min(h) {
+                    for qx in x.saturating_sub(r)..(x + r + 1).min(w) {
+                        let j = qy * w + qx;
+
+                        let (dx, dy) = (qx.abs_diff(x) as f64, qy.abs_diff(y) as f64);
+                        let wt = weight(dx * dx + dy * dy, self.sigma_spatial)
+                            * weight(color[i].dist2(color[j]), self.sigma_color)
+                            * weight(aovs.normal[i].dist2(aovs.normal[j]), self.sigma_normal)
+                            * weight(aovs.albedo[i].dist2(aovs.albedo[j]), self.sigma_albedo);
+
+                        sum += color[j] * wt;
+                        total += wt;
+                    }
+                }
+
+                // the weight of the pixel itself is always 1, hence total is
+                // never zero
+                sum / total
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use geo::v3;
+
+    #[test]
+    fn test_bilateral_preserves_edges() {
+        let (w, h) = (6, 4);
+
+        // left half red and right half blue with a different albedo so the
+        // filter must not blend them together
+        let color = (0..w * h)
+            .map(|i| {
+                if i % w < w / 2 {
+                    v3(1, 0, 0)
+                } else {
+                    v3(0, 0, 1)
+                }
+            })
+            .collect::<Vec<_>>();
+        let aovs = Aovs {
+            width: w as u32,
+            height: h as u32,
+            normal: vec![v3(0, 0, 1); w * h],
+            albedo: color.clone(),
+        };
+
+        let denoised = BilateralFilter::new(2).denoise(&color, &aovs);
+
+        for (d, c) in denoised.iter().zip(&color) {
+            assert!(d.dist(*c) < 1e-6, "{d:?} != {c:?}");
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Ray differentials estimate how big the footprint of a pixel is on the
+//! surfaces it sees so that the textures can be filtered over it.
+//!
+//! Point sampling a texture whose details are smaller than a pixel, like a
+//! checkerboard seen at a grazing angle, aliases badly and no amount of
+//! samples per pixel makes the moiré patterns go away quickly. The
+//! differentials follow the camera rays through the mirror-like reflections
+//! and refractions while the diffuse and glossy bounces drop them since they
+//! blur the textures anyway.
+//!
+//! See [Tracing Ray Differentials][0] by Homan Igehy.
+//!
+//! [0]: https://graphics.stanford.edu/papers/trd/
+
+use geo::{ray::Ray, Vec3};
+
+/// The rays through the points one pixel to the right and one pixel below the
+/// point a camera ray goes through, see `Camera::ray_differential`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayDifferential {
+    pub rx_origin: Vec3,
+    pub rx_dir: Vec3,
+    pub ry_origin: Vec3,
+    pub ry_dir: Vec3,
+}
+
+impl RayDifferential {
+    /// The width of the footprint of the pixel on the surface hit at `p` whose
+    /// normal is `n`, that is the distance from `p` of the points where the
+    /// offset rays hit the plane tangent to the surface.
+    ///
+    /// The footprint is 0 if the offset rays don't hit the plane.
+    pub fn footprint(&self, p: Vec3, n: Vec3) -> f64 {
+        self.tangent_hits(p, n)
+            .map_or(0.0, |(px, py)| px.dist(p).max(py.dist(p)))
+    }
+
+    /// The differential of the ray reflected around `n` at `p` by a perfect
+    /// mirror.
+    pub fn reflected(&self, p: Vec3, n: Vec3) -> Option<Self> {
+        let (px, py) = self.tangent_hits(p, n)?;
+        let n = n.normalized();
+
+        Some(Self {
+            rx_origin: px,
+            rx_dir: Ray::new(self.rx_dir, n).reflect(),
+            ry_origin: py,
+            ry_dir: Ray::new(self.ry_dir, n).reflect(),
+        })
+    }
+
+    /// The differential of the ray refracted at `p` into or out of a
+    /// dielectric with the given `refraction_index` whose outward normal is
+    /// `n`, see `dielectric_bounce`.
+    ///
+    /// Return None if any of the offset rays is totally reflected.
+    pub fn refracted(&self, p: Vec3, n: Vec3, refraction_index: f64) -> Option<Self> {
+        let (px, py) = self.tangent_hits(p, n)?;
+        let n = n.normalized();
+
+        let refract = |d: Vec3| {
+            if d.dot(n) > 0.0 {
+                Ray::new(d, -n).refract(refraction_index)
+            } else {
+                Ray::new(d, n).refract(1.0 / refraction_index)
+            }
+        };
+
+        Some(Self {
+            rx_origin: px,
+            rx_dir: refract(self.rx_dir)?,
+            ry_origin: py,
+            ry_dir: refract(self.ry_dir)?,
+        })
+    }
+
+    /// The points where the offset rays hit the plane through `p` with normal
+    /// `n`, if any.
+    fn tangent_hits(&self, p: Vec3, n: Vec3) -> Option<(Vec3, Vec3)> {
+        let hit = |o: Vec3, d: Vec3| {
+            let den = n.dot(d);
+            if den.abs() < 1e-12 {
+                return None;
+            }
+
+            let t = n.dot(p - o) / den;
+            (t >= 0.0).then(|| o + d * t)
+        };
+
+        Some((
+            hit(self.rx_origin, self.rx_dir)?,
+            hit(self.ry_origin, self.ry_dir)?,
+        ))
+    }
+}
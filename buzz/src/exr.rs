@@ -0,0 +1,68 @@
+//! Save linear renders as [OpenEXR][0] images.
+//!
+//! Unlike the 8 bit formats, an EXR stores the colors as floats without any
+//! clamping or gamma correction so that the full dynamic range of a render is
+//! kept for compositing and tonemapping.
+//!
+//! [0]: https://openexr.com/en/latest/OpenEXRFileLayout.html
+
+use std::io;
+
+use geo::Vec3;
+
+/// Save the given linear colors, row by row, as an EXR image with 32 bit
+/// float RGB channels.
+pub fn save(path: &str, width: u32, height: u32, color: &[Vec3]) -> io::Result<()> {
+    assert_eq!(color.len(), width as usize * height as usize);
+
+    let width = width as usize;
+    ::exr::prelude::write_rgb_file(path, width, height as usize, |x, y| {
+        let c = color[y * width + x];
+        (c.x as f32, c.y as f32, c.z as f32)
+    })
+    .map_err(|e| match e {
+        ::exr::error::Error::Io(e) => e,
+        e => io::Error::other(e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ::exr::prelude::read_first_rgba_layer_from_file;
+    use geo::v3;
+
+    use super::*;
+
+    #[test]
+    fn test_save() {
+        let color = vec![
+            v3(0.0, 0.5, 1.0),
+            v3(2.0, 0.25, 0.0),
+            v3(0.1, 0.2, 0.3),
+            v3(4, 5, 6),
+            v3(-1.0, 100.0, 0.75),
+            v3(0.0, 0.0, 0.0),
+        ];
+
+        let path = std::env::temp_dir().join(format!("buzz-exr-{}.exr", std::process::id()));
+        save(path.to_str().unwrap(), 2, 3, &color).unwrap();
+
+        let image = read_first_rgba_layer_from_file(
+            &path,
+            |resolution, _| vec![vec![[0.0_f32; 3]; resolution.width()]; resolution.height()],
+            |pixels, pos, (r, g, b, _): (f32, f32, f32, f32)| pixels[pos.y()][pos.x()] = [r, g, b],
+        )
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let pixels = image.layer_data.channel_data.pixels;
+        assert_eq!(pixels.len(), 3);
+        for (y, row) in pixels.iter().enumerate() {
+            assert_eq!(row.len(), 2);
+            for (x, px) in row.iter().enumerate() {
+                let c = color[y * 2 + x];
+                assert_eq!(*px, [c.x as f32, c.y as f32, c.z as f32]);
+            }
+        }
+    }
+}
@@ -1,9 +1,20 @@
 #![allow(clippy::useless_let_if_seq)]
 
 pub mod camera;
+pub mod checkpoint;
+pub mod denoise;
+pub mod differential;
+pub mod exr;
+pub mod light_tree;
 pub mod material;
 pub mod object;
 pub mod objectgeo;
+pub mod portal;
+pub mod prelude;
+pub mod scene_file;
+pub mod scenes;
+pub mod sky;
+pub mod texture;
 
 mod renderer;
 
@@ -11,15 +22,21 @@ use std::sync::Arc;
 
 use geo::{
     ray::Ray,
-    spatial_index::{Bvh, Intersection, Shape},
+    spatial_index::{Bvh, Shape},
     Vec3,
 };
 
 pub use camera::Camera;
+pub use denoise::{BilateralFilter, Denoiser};
+pub use differential::RayDifferential;
+pub use light_tree::LightTree;
 pub use material::Material;
 pub use object::*;
 pub use objectgeo::*;
+pub use portal::Portal;
 pub use renderer::*;
+pub use sky::Sky;
+pub use texture::Texture;
 
 /// A `Scene` is a collection of objects that can be rendered.
 #[derive(Debug)]
@@ -27,6 +44,7 @@ pub struct Scene {
     objects: SceneObjects,
     objects_index: Bvh<Arc<dyn Object>>,
     environment: Environment,
+    portals: Vec<Portal>,
 }
 
 #[derive(Debug)]
@@ -43,6 +61,10 @@ pub enum Environment {
 
     /// The `Environment` is a simple linear gradient between two RGB colors.
     LinearGradient(Vec3, Vec3),
+
+    /// The `Environment` is a physically based daylight sky whose sun is
+    /// sampled as a light, see the `sky` module.
+    Sky(Sky),
 }
 
 impl Scene {
@@ -54,24 +76,61 @@ impl Scene {
             objects,
             objects_index,
             environment,
+            portals: vec![],
         }
     }
 
+    /// Replace the objects of the `Scene` rebuilding its index.
+    ///
+    /// Only the top level index over the objects is rebuilt, the `Bvh`s of the
+    /// `TriangleMesh`es referenced by `MeshInstance`s are shared, therefore
+    /// moving the instances around, e.g. for each frame of an animation, is
+    /// cheap.
+    pub fn set_objects(&mut self, objects: SceneObjects) {
+        self.objects_index = objects.iter().cloned().collect();
+        self.objects = objects;
+    }
+
+    /// Sample the `Environment` through the given `Portal`s, see the `portal`
+    /// module.
+    pub fn with_portals(mut self, portals: impl IntoIterator<Item = Portal>) -> Self {
+        self.portals = portals.into_iter().collect();
+        self
+    }
+
     /// Calculate the intersection between a `Ray` and all the objects in the
     /// scene returning the closest object (along with its intersection result)
     /// to the ray.
-    pub fn intersection(&self, ray: &Ray) -> Option<(&dyn Object, Hit)> {
+    ///
+    /// Only the objects visible to rays of the given kind are considered, see
+    /// `Visibility`.
+    pub fn intersection(&self, ray: &Ray, kind: RayKind) -> Option<(&dyn Object, Hit)> {
         self.objects_index
-            .intersections(ray)
-            .min_by(|(_, t0), (_, t1)| t0.t().total_cmp(&t1.t()))
+            .closest_intersection_where(ray, |o| o.visibility().hit_by(kind))
             .map(|(s, t)| (s.as_ref(), t))
     }
 
+    /// Check whether the `Ray` hits any object closer than `t_max` to its
+    /// origin. It's faster than `intersection` since it stops at the first
+    /// hit found, use it for shadow rays.
+    ///
+    /// Only the objects visible to rays of the given kind are considered, see
+    /// `Visibility`.
+    pub fn intersects_before(&self, ray: &Ray, t_max: f64, kind: RayKind) -> bool {
+        self.objects_index
+            .intersects_before_where(ray, t_max, |o| o.visibility().hit_by(kind))
+    }
+
     /// Get the `Surface` with the given id.
     pub fn surface(&self, id: usize) -> &dyn Object {
         self.objects[id].as_ref()
     }
 
+    /// Return the `Portal`s of the `Scene`.
+    pub fn portals(&self) -> &[Portal] {
+        &self.portals
+    }
+
     /// Return an iterator over all the lights in the `Scene`.
     pub fn lights(&self) -> impl Iterator<Item = &dyn Object> {
         self.objects
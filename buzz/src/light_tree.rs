@@ -0,0 +1,214 @@
+//! Pick a single light per sample in scenes with a lot of lights.
+//!
+//! Instead of sampling every light at each bounce, a `LightTree` picks one of
+//! them with a probability proportional to its estimated contribution to the
+//! point being shaded and the sampled light is then divided by such
+//! probability. The lights are organized in a bounding volume hierarchy so
+//! that picking a light only takes a logarithmic number of steps.
+
+use geo::{Aabb, Vec3};
+use rand::Rng;
+
+use crate::{Material, Object};
+
+/// A bounding volume hierarchy over the lights of a `Scene`.
+#[derive(Debug)]
+pub struct LightTree<'s> {
+    lights: Vec<&'s dyn Object>,
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug)]
+struct Node {
+    bbox: Aabb,
+    power: f64,
+    kind: NodeKind,
+}
+
+#[derive(Debug)]
+enum NodeKind {
+    Leaf(usize),
+    Inner(usize, usize),
+}
+
+impl<'s> LightTree<'s> {
+    /// Build a new `LightTree` over the given lights.
+    pub fn new(lights: impl IntoIterator<Item = &'s dyn Object>) -> Self {
+        let lights = lights.into_iter().collect::<Vec<_>>();
+
+        let mut tree = LightTree {
+            nodes: Vec::with_capacity(2 * lights.len()),
+            lights: vec![],
+        };
+
+        if !lights.is_empty() {
+            let mut indices = (0..lights.len()).collect::<Vec<_>>();
+            tree.build(&lights, &mut indices);
+        }
+
+        tree.lights = lights;
+        tree
+    }
+
+    /// Return whether there are no lights in the tree.
+    pub fn is_empty(&self) -> bool {
+        self.lights.is_empty()
+    }
+
+    /// Return the number of lights in the tree.
+    pub fn len(&self) -> usize {
+        self.lights.len()
+    }
+
+    /// Pick a light to sample from the point `p` along with the probability
+    /// of having picked it.
+    pub fn sample(&self, p: Vec3, rng: &mut impl Rng) -> Option<(&'s dyn Object, f64)> {
+        self.pick(p, rng.gen())
+    }
+
+    /// Walk down the tree using the uniform random number `u` to choose which
+    /// child to visit, `u` is then rescaled to be uniform again in the chosen
+    /// child.
+    fn pick(&self, p: Vec3, mut u: f64) -> Option<(&'s dyn Object, f64)> {
+        let mut node = self.nodes.first()?;
+        let mut pdf = 1.0;
+
+        loop {
+            match node.kind {
+                NodeKind::Leaf(l) => return Some((self.lights[l], pdf)),
+                NodeKind::Inner(l, r) => {
+                    let (il, ir) = (self.nodes[l].importance(p), self.nodes[r].importance(p));
+
+                    let pl = if il + ir > 0.0 { il / (il + ir) } else { 0.5 };
+
+                    if u < pl {
+                        u /= pl;
+                        pdf *= pl;
+                        node = &self.nodes[l];
+                    } else {
+                        u = (u - pl) / (1.0 - pl);
+                        pdf *= 1.0 - pl;
+                        node = &self.nodes[r];
+                    }
+                }
+            }
+        }
+    }
+
+    /// Recursively build the subtree over the given lights returning the index
+    /// of its root. The lights are split in half along the longest axis of
+    /// their bounding box.
+    fn build(&mut self, lights: &[&dyn Object], indices: &mut [usize]) -> usize {
+        let id = self.nodes.len();
+
+        if let [l] = indices {
+            let (center, radius) = lights[*l].bounding_sphere();
+            let r = Vec3::new(radius, radius, radius);
+
+            self.nodes.push(Node {
+                bbox: Aabb::new(center - r).expanded(center + r),
+                power: power(lights[*l].material(), radius),
+                kind: NodeKind::Leaf(*l),
+            });
+            return id;
+        }
+
+        let centers =
+            Aabb::from_points(indices.iter().map(|&l| lights[l].bounding_sphere().0)).unwrap();
+        let d = centers.dimensions();
+        let axis = if d.x >= d.y && d.x >= d.z {
+            geo::Axis::X
+        } else if d.y >= d.z {
+            geo::Axis::Y
+        } else {
+            geo::Axis::Z
+        };
+
+        indices.sort_by(|&a, &b| {
+            let (ca, cb) = (lights[a].bounding_sphere().0, lights[b].bounding_sphere().0);
+            ca[axis].total_cmp(&cb[axis])
+        });
+
+        // reserve the slot of this node before building the children
+        self.nodes.push(Node {
+            bbox: centers,
+            power: 0.0,
+            kind: NodeKind::Leaf(0),
+        });
+
+        let (left, right) = indices.split_at_mut(indices.len() / 2);
+        let l = self.build(lights, left);
+        let r = self.build(lights, right);
+
+        self.nodes[id] = Node {
+            bbox: self.nodes[l].bbox.union(&self.nodes[r].bbox),
+            power: self.nodes[l].power + self.nodes[r].power,
+            kind: NodeKind::Inner(l, r),
+        };
+
+        id
+    }
+}
+
+impl Node {
+    /// Estimate of how much light the lights in this node send to `p`: their
+    /// power divided by the squared distance, which is clamped to the size
+    /// of the node so that points close to or inside it don't blow up.
+    fn importance(&self, p: Vec3) -> f64 {
+        let d = self.bbox.dimensions();
+        let d2 = self.bbox.center().dist2(p).max(d.norm2() / 4.0).max(1e-6);
+
+        self.power / d2
+    }
+}
+
+/// Estimate the power of a light as its average emittance times its area
+/// which is approximated from its bounding sphere.
+fn power(material: &Material, radius: f64) -> f64 {
+    let Material::Light { emittance, .. } = *material else {
+        return 0.0;
+    };
+
+    (emittance.x + emittance.y + emittance.z) / 3.0 * radius * radius
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::v3;
+
+    use crate::{SimpleObject, SphereGeometry};
+
+    use super::*;
+
+    #[test]
+    fn test_pick() {
+        let lights = [
+            SimpleObject::new(
+                SphereGeometry::new(v3(0, 0, 0), 1.0),
+                Material::light(v3(1, 1, 1)),
+            ),
+            SimpleObject::new(
+                SphereGeometry::new(v3(10, 0, 0), 1.0),
+                Material::light(v3(4, 4, 4)),
+            ),
+        ];
+        let tree = LightTree::new(lights.iter().map(|l| l as &dyn Object));
+        assert_eq!(tree.len(), 2);
+
+        // equidistant from both lights, the brightest one is picked 4 times
+        // as often
+        let p = v3(5, 3, 0);
+        let (l0, pdf0) = tree.pick(p, 0.1).unwrap();
+        let (l1, pdf1) = tree.pick(p, 0.9).unwrap();
+
+        assert_eq!(l0.bounding_sphere().0, v3(0, 0, 0));
+        assert_eq!(l1.bounding_sphere().0, v3(10, 0, 0));
+        assert!((pdf0 - 0.2).abs() < 1e-9 && (pdf1 - 0.8).abs() < 1e-9);
+
+        // right next to the dim light it becomes the most likely
+        let (_, pdf) = tree.pick(v3(-1.5, 0.0, 0.0), 0.0).unwrap();
+        assert!(pdf > 0.9);
+
+        assert!(LightTree::new(vec![]).pick(p, 0.5).is_none());
+    }
+}
@@ -1,16 +1,39 @@
+use std::f64::consts::PI;
+
 use rand::Rng;
 
-use geo::{ray::Ray, Vec3};
+use geo::{plane::orthonormal_basis, ray::Ray, Vec2, Vec3};
+
+use crate::texture::Texture;
 
 /// Enum over all the supported `Material`s. Each variant dictates how light
 /// interacts(reflects, refracts, etc..) with them. They're mainly composed of
 /// an `albedo` field which is the intrinsic color of the material.
 #[derive(Debug, PartialEq, Clone)]
 pub enum Material {
-    Lambertian { albedo: Vec3 },
-    Metal { albedo: Vec3, fuzziness: f64 },
-    Dielectric { refraction_index: f64 },
-    Light { emittance: Vec3 },
+    Lambertian {
+        albedo: Vec3,
+    },
+    Metal {
+        albedo: Vec3,
+        fuzziness: f64,
+    },
+    Dielectric {
+        refraction_index: f64,
+        dispersion: f64,
+        absorption: Vec3,
+    },
+    Pbr {
+        base_color: Texture,
+        metallic: Texture,
+        roughness: Texture,
+    },
+    Light {
+        emittance: Vec3,
+        falloff: f64,
+        one_sided: bool,
+    },
+    ShadowCatcher,
 }
 
 impl Material {
@@ -35,13 +58,167 @@ impl Material {
     /// identified by a refraction index. For example, glass has a refraction
     /// index in [1.3, 1.7] while diamond is 2.4.
     pub const fn dielectric(refraction_index: f64) -> Self {
-        Material::Dielectric { refraction_index }
+        Material::dispersive_dielectric(refraction_index, 0.0)
+    }
+
+    /// A dielectric whose refraction index changes with the wavelength of the
+    /// light, splitting white light into its colors like a prism does.
+    ///
+    /// `dispersion` is the difference between the refraction index of the blue
+    /// and green light, the red one is bent less by the same amount. For
+    /// example, it's around 0.01 for glass and 0.02 for diamond.
+    pub const fn dispersive_dielectric(refraction_index: f64, dispersion: f64) -> Self {
+        Material::Dielectric {
+            refraction_index,
+            dispersion,
+            absorption: Vec3::zero(),
+        }
+    }
+
+    /// Make a dielectric absorb the light traveling inside it according to the
+    /// [Beer–Lambert law][0] like colored glass or deep water do.
+    ///
+    /// `color` is the color of white light after going through `distance`
+    /// units of the material, the deeper the light goes the darker and more
+    /// saturated it gets.
+    ///
+    /// The channels of `color` are clamped to a tiny positive value so that a
+    /// black channel absorbs almost all the light instead of an infinite amount
+    /// of it. Only dielectrics can absorb light, the other materials are
+    /// returned unchanged.
+    ///
+    /// Note that the objects made of an absorbing dielectric must be closed and
+    /// must not overlap with each other.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/Beer%E2%80%93Lambert_law
+    pub fn with_absorption(self, color: Vec3, distance: f64) -> Self {
+        assert!(distance > 0.0, "absorption distance must be positive");
+
+        let absorption = |c: f64| -c.max(1e-6).ln() / distance;
+
+        match self {
+            Material::Dielectric {
+                refraction_index,
+                dispersion,
+                ..
+            } => Material::Dielectric {
+                refraction_index,
+                dispersion,
+                absorption: Vec3::new(
+                    absorption(color.x),
+                    absorption(color.y),
+                    absorption(color.z),
+                ),
+            },
+            m => m,
+        }
+    }
+
+    /// The fraction of each channel of the light that survives after traveling
+    /// `distance` units inside the material, only dielectrics absorb light.
+    pub fn transmittance(&self, distance: f64) -> Vec3 {
+        match *self {
+            Material::Dielectric { absorption, .. } => Vec3::new(
+                (-absorption.x * distance).exp(),
+                (-absorption.y * distance).exp(),
+                (-absorption.z * distance).exp(),
+            ),
+            _ => Vec3::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    /// A physically based material in the metallic-roughness workflow that
+    /// reflects light according to the [GGX microfacet model][0] on top of a
+    /// diffuse base.
+    ///
+    /// `metallic` blends between a dielectric, whose diffuse color is the
+    /// `base_color` and whose reflections are white, and a metal that tints its
+    /// reflections with the `base_color`. `roughness` goes from a perfect
+    /// mirror at 0 to a very blurry reflection at 1. Both are in [0, 1] and,
+    /// like the `base_color`, they can vary across the surface.
+    ///
+    /// [0]: https://www.cs.cornell.edu/~srm/publications/EGSR07-btdf.pdf
+    pub fn pbr(
+        base_color: impl Into<Texture>,
+        metallic: impl Into<Texture>,
+        roughness: impl Into<Texture>,
+    ) -> Self {
+        Material::Pbr {
+            base_color: base_color.into(),
+            metallic: metallic.into(),
+            roughness: roughness.into(),
+        }
     }
 
     /// A light material is a material that does not reflect rays, but always
     /// emits the given light.
     pub const fn light(emittance: Vec3) -> Self {
-        Material::Light { emittance }
+        Material::Light {
+            emittance,
+            falloff: 0.0,
+            one_sided: false,
+        }
+    }
+
+    /// A light that doesn't emit the same amount of light in all directions.
+    ///
+    /// The emitted light is scaled by the cosine of the angle between the
+    /// surface normal and the outgoing direction raised to `falloff`, so that
+    /// an higher `falloff` concentrates the light around the normal much like
+    /// a spot light does. A `falloff` of 0 emits uniformly. If `one_sided` is
+    /// true then no light is emitted from the back of the surface.
+    pub const fn spot_light(emittance: Vec3, falloff: f64, one_sided: bool) -> Self {
+        Material::Light {
+            emittance,
+            falloff,
+            one_sided,
+        }
+    }
+
+    /// A material that only shows the shadows cast on it by the other objects
+    /// so that they can be composited over a photo or a flat background.
+    ///
+    /// Where it's lit it shows the `Environment` behind it, and it darkens it
+    /// where the light is blocked. The camera samples hitting it have an alpha
+    /// equal to how much of the light is blocked, see `Framebuffer::alpha`.
+    pub const fn shadow_catcher() -> Self {
+        Material::ShadowCatcher
+    }
+
+    /// The intrinsic color of the material at the point `p` with texture
+    /// coordinates `uv`. Dielectrics and shadow catchers are colorless hence
+    /// their albedo is white while lights use their emittance.
+    pub fn albedo(&self, p: Vec3, uv: Option<Vec2>) -> Vec3 {
+        match *self {
+            Material::Lambertian { albedo } | Material::Metal { albedo, .. } => albedo,
+            Material::Dielectric { .. } | Material::ShadowCatcher => Vec3::new(1.0, 1.0, 1.0),
+            Material::Pbr { ref base_color, .. } => base_color.value(p, uv),
+            Material::Light { emittance, .. } => emittance,
+        }
+    }
+
+    /// The light emitted towards `-dir` by a surface with normal `n` made of
+    /// this material. It's always black for materials that are not lights.
+    pub fn emitted(&self, dir: Vec3, n: Vec3) -> Vec3 {
+        let Material::Light {
+            emittance,
+            falloff,
+            one_sided,
+        } = *self
+        else {
+            return Vec3::zero();
+        };
+
+        let mut cos = -dir.normalized().dot(n.normalized());
+        if !one_sided {
+            cos = cos.abs();
+        }
+
+        if cos <= 0.0 {
+            return Vec3::zero();
+        }
+
+        emittance * cos.powf(falloff)
     }
 }
 
@@ -72,6 +249,112 @@ pub fn metal_bounce(
         intersection,
         Ray::new(ray.dir.normalized(), n).reflect() + Vec3::random_unit(rng) * fuzziness,
     )
+    .with_time(ray.time)
+}
+
+/// Calculate the bouncing of a ray coming to `intersection` on a rough
+/// specular surface modeled with the GGX microfacet distribution.
+///
+/// The microfacet normal is importance sampled from the distribution given
+/// the `roughness` and the ray is reflected around it. Alongside the bounced
+/// `Ray` the weight of the sample is returned, that is the BRDF times the
+/// cosine term divided by the pdf, given the reflectance at normal incidence
+/// `f0`. `None` is returned if the reflected ray goes below the surface.
+pub fn ggx_bounce(
+    ray: &Ray,
+    intersection: Vec3,
+    n: Vec3,
+    f0: Vec3,
+    roughness: f64,
+    rng: &mut impl Rng,
+) -> Option<(Ray, Vec3)> {
+    let alpha = ggx_alpha(roughness);
+    let wo = -ray.dir.normalized();
+    let n = n.normalized();
+
+    // sample the microfacet normal proportionally to D(h) * cos(h)
+    let (u1, u2) = (rng.gen::<f64>(), rng.gen::<f64>());
+    let cos_h = ((1.0 - u1) / (1.0 + (alpha * alpha - 1.0) * u1)).sqrt();
+    let sin_h = (1.0 - cos_h * cos_h).max(0.0).sqrt();
+    let phi = 2.0 * PI * u2;
+
+    let (t, b) = orthonormal_basis(n);
+    let h = (t * (sin_h * phi.cos()) + b * (sin_h * phi.sin()) + n * cos_h).normalized();
+
+    let wo_h = wo.dot(h);
+    let wi = h * (2.0 * wo_h) - wo;
+
+    let (cos_o, cos_i) = (n.dot(wo), n.dot(wi));
+    if cos_o <= 0.0 || cos_i <= 0.0 || wo_h <= 0.0 {
+        return None;
+    }
+
+    // with this pdf the distribution term cancels out
+    let g = smith_g1(cos_o, alpha) * smith_g1(cos_i, alpha);
+    let weight = fresnel_schlick(f0, wo_h) * (g * wo_h / (cos_o * cos_h));
+
+    Some((Ray::new(intersection, wi).with_time(ray.time), weight))
+}
+
+/// Evaluate the GGX microfacet BRDF, without the cosine term, for light
+/// coming from `wi` and leaving towards `wo` on a surface with normal `n`.
+///
+/// It's the counterpart of `ggx_bounce`, useful when the directions are not
+/// sampled from the distribution itself.
+pub fn ggx_brdf(n: Vec3, wo: Vec3, wi: Vec3, f0: Vec3, roughness: f64) -> Vec3 {
+    let alpha = ggx_alpha(roughness);
+    let (n, wo, wi) = (n.normalized(), wo.normalized(), wi.normalized());
+
+    let (cos_o, cos_i) = (n.dot(wo), n.dot(wi));
+    if cos_o <= 0.0 || cos_i <= 0.0 {
+        return Vec3::zero();
+    }
+
+    let h = (wo + wi).normalized();
+    let d = ggx_d(n.dot(h), alpha);
+    let g = smith_g1(cos_o, alpha) * smith_g1(cos_i, alpha);
+
+    fresnel_schlick(f0, wo.dot(h)) * (d * g / (4.0 * cos_o * cos_i))
+}
+
+/// The reflectance at normal incidence of a `Pbr` material with the given
+/// `base_color` and `metallic` factor. Dielectrics reflect about 4% of the
+/// light regardless of their color.
+pub fn pbr_f0(base_color: Vec3, metallic: f64) -> Vec3 {
+    Vec3::new(0.04, 0.04, 0.04) * (1.0 - metallic) + base_color * metallic
+}
+
+/// [Schlick's approximation][0] of the Fresnel reflectance given the
+/// reflectance `f0` at normal incidence and the cosine of the angle between
+/// the incoming light and the normal.
+///
+/// [0]: https://en.wikipedia.org/wiki/Schlick's_approximation
+pub fn fresnel_schlick(f0: Vec3, cos: f64) -> Vec3 {
+    let k = (1.0 - cos.clamp(0.0, 1.0)).powi(5);
+
+    f0 + (Vec3::new(1.0, 1.0, 1.0) - f0) * k
+}
+
+/// The GGX alpha parameter for the given perceptual `roughness`, clamped to
+/// avoid the degenerate perfect mirror.
+fn ggx_alpha(roughness: f64) -> f64 {
+    roughness.clamp(0.0, 1.0).powi(2).max(1e-3)
+}
+
+/// The GGX normal distribution function, that is the density of the
+/// microfacets oriented at an angle whose cosine is `cos_h` from the normal.
+fn ggx_d(cos_h: f64, alpha: f64) -> f64 {
+    let a2 = alpha * alpha;
+    let k = cos_h * cos_h * (a2 - 1.0) + 1.0;
+
+    a2 / (PI * k * k)
+}
+
+/// Smith masking function for the GGX distribution.
+fn smith_g1(cos: f64, alpha: f64) -> f64 {
+    let a2 = alpha * alpha;
+
+    2.0 * cos / (cos + (a2 + (1.0 - a2) * cos * cos).sqrt())
 }
 
 /// Calculate the bouncing of a ray coming to `intersection` on a dielectric
@@ -117,7 +400,15 @@ pub fn dielectric_bounce(
         None => Ray::new(ray.dir, n).reflect(),
     };
 
-    Ray::new(intersection, dir)
+    Ray::new(intersection, dir).with_time(ray.time)
+}
+
+/// Return the refraction index of the given RGB channel of a dielectric with
+/// the given dispersion.
+pub fn dispersed_refraction_index(refraction_index: f64, dispersion: f64, band: usize) -> f64 {
+    // red, green and blue light in this order, shorter wavelengths are bent
+    // more
+    refraction_index + dispersion * [-1.0, 0.0, 1.0][band]
 }
 
 /// Approximate the [Fresnel factor][1] that is the factor or refracted light
@@ -130,3 +421,209 @@ fn schlick(cos: f64, refraction_index: f64) -> f64 {
 
     r0 + (1.0 - r0) * (1.0 - cos).powi(5)
 }
+
+#[cfg(test)]
+mod tests {
+    use geo::v3;
+    use rand::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    use super::*;
+
+    const SAMPLES: usize = 100_000;
+
+    /// A random direction in the hemisphere around +Z, uniformly distributed
+    /// over the solid angle.
+    fn random_hemisphere(rng: &mut impl Rng) -> Vec3 {
+        let z = rng.gen::<f64>();
+        let r = (1.0 - z * z).sqrt();
+        let phi = 2.0 * PI * rng.gen::<f64>();
+
+        v3(r * phi.cos(), r * phi.sin(), z)
+    }
+
+    /// The fraction of the light coming from `wo` that `ggx_bounce` reflects,
+    /// estimated by averaging the weights of many samples.
+    fn ggx_albedo(wo: Vec3, f0: Vec3, roughness: f64, rng: &mut impl Rng) -> Vec3 {
+        let n = v3(0, 0, 1);
+        let ray = Ray::new(wo, -wo);
+
+        (0..SAMPLES)
+            .filter_map(|_| ggx_bounce(&ray, Vec3::zero(), n, f0, roughness, rng))
+            .map(|(_, w)| w)
+            .sum::<Vec3>()
+            / SAMPLES as f64
+    }
+
+    #[test]
+    fn test_lambertian_white_furnace() {
+        let mut rng = XorShiftRng::seed_from_u64(0);
+        let n = v3(1, 2, 3).normalized();
+
+        // inside a uniformly white environment the light reflected by a
+        // Lambertian surface is its albedo, the bounces going through the
+        // surface would hit the object itself and they're absorbed instead
+        for albedo in [v3(1, 1, 1), v3(0.5, 0.25, 0.0)] {
+            let material = Material::lambertian(albedo);
+
+            let throughput = (0..SAMPLES)
+                .map(|_| {
+                    let r = lambertian_bounce(Vec3::zero(), n, &mut rng);
+                    if r.dir.dot(n) > 0.0 {
+                        material.albedo(r.origin, None)
+                    } else {
+                        Vec3::zero()
+                    }
+                })
+                .sum::<Vec3>()
+                / SAMPLES as f64;
+
+            assert!(throughput.dist(albedo) < 1e-3, "{throughput:?}");
+        }
+    }
+
+    #[test]
+    fn test_metal_reciprocity() {
+        let mut rng = XorShiftRng::seed_from_u64(0);
+        let n = v3(0, 0, 1);
+
+        // a perfect mirror sends the reflected ray back where it came from
+        for _ in 0..100 {
+            let wi = random_hemisphere(&mut rng);
+            let ray = Ray::new(wi, -wi);
+
+            let wo = metal_bounce(&ray, Vec3::zero(), n, 0.0, &mut rng).dir;
+            assert!((wo.norm() - 1.0).abs() < 1e-9 && (wo.z - wi.z).abs() < 1e-9);
+
+            let back = metal_bounce(&Ray::new(wo, -wo), Vec3::zero(), n, 0.0, &mut rng).dir;
+            assert!(back.dist(wi) < 1e-9, "{back:?} vs {wi:?}");
+        }
+    }
+
+    #[test]
+    fn test_dielectric_reciprocity() {
+        let mut rng = XorShiftRng::seed_from_u64(0);
+        let n = v3(0, 0, 1);
+
+        for _ in 0..1000 {
+            let wi = random_hemisphere(&mut rng);
+            let ray = Ray::new(wi, -wi);
+
+            // no light is absorbed, every ray is either reflected or
+            // refracted
+            let wo = dielectric_bounce(&ray, Vec3::zero(), n, 1.5, &mut rng).dir;
+            if wo.z > 0.0 {
+                assert!((wo.z / wo.norm() - wi.z).abs() < 1e-9);
+                continue;
+            }
+
+            // a refracted ray traced backwards refracts along the original
+            // ray, unless it's reflected this time
+            let back = dielectric_bounce(&Ray::new(wo, -wo), Vec3::zero(), n, 1.5, &mut rng).dir;
+            if back.z > 0.0 {
+                assert!(back.normalized().dist(wi) < 1e-6, "{back:?} vs {wi:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_ggx_reciprocity() {
+        let mut rng = XorShiftRng::seed_from_u64(0);
+        let n = v3(0, 0, 1);
+        let f0 = v3(0.9, 0.6, 0.3);
+
+        for _ in 0..1000 {
+            let wo = random_hemisphere(&mut rng);
+            let wi = random_hemisphere(&mut rng);
+            let roughness = rng.gen::<f64>();
+
+            let f = ggx_brdf(n, wo, wi, f0, roughness);
+            let b = ggx_brdf(n, wi, wo, f0, roughness);
+            assert!(f.dist(b) <= 1e-9 * f.norm(), "{f:?} vs {b:?}");
+        }
+
+        assert_eq!(
+            ggx_brdf(n, v3(0, 0, 1), v3(0, 0, -1), f0, 0.5),
+            Vec3::zero()
+        );
+    }
+
+    #[test]
+    fn test_ggx_white_furnace() {
+        let mut rng = XorShiftRng::seed_from_u64(0);
+        let white = v3(1, 1, 1);
+
+        // a white mirror can't reflect more light than it receives and, apart
+        // from the light lost by the single scattering model at high
+        // roughness, it reflects all of it
+        for roughness in [0.0, 0.1, 0.3, 0.6, 1.0] {
+            for cos_o in [1.0_f64, 0.7, 0.3] {
+                let wo = v3((1.0 - cos_o * cos_o).sqrt(), 0.0, cos_o);
+                let albedo = ggx_albedo(wo, white, roughness, &mut rng).x;
+
+                assert!(
+                    albedo <= 1.0 + 1e-3,
+                    "roughness {roughness} cos {cos_o}: {albedo}"
+                );
+                if roughness <= 0.3 {
+                    assert!(albedo > 0.9, "roughness {roughness} cos {cos_o}: {albedo}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_ggx_sampling_matches_brdf() {
+        let mut rng = XorShiftRng::seed_from_u64(0);
+        let n = v3(0, 0, 1);
+        let f0 = v3(0.04, 0.5, 1.0);
+
+        // the importance sampled weights must integrate the same BRDF that is
+        // evaluated explicitly, here estimated by sampling the hemisphere
+        // uniformly
+        for roughness in [0.4, 0.8] {
+            let wo = v3(0.6, 0.0, 0.8);
+            let sampled = ggx_albedo(wo, f0, roughness, &mut rng);
+
+            let uniform = (0..SAMPLES * 10)
+                .map(|_| {
+                    let wi = random_hemisphere(&mut rng);
+                    ggx_brdf(n, wo, wi, f0, roughness) * wi.z
+                })
+                .sum::<Vec3>()
+                * (2.0 * PI / (SAMPLES * 10) as f64);
+
+            assert!(
+                sampled.dist(uniform) < 0.02 * uniform.norm(),
+                "roughness {roughness}: {sampled:?} vs {uniform:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_absorption() {
+        let color = v3(0.2, 0.5, 1.0);
+        let glass = Material::dielectric(1.5).with_absorption(color, 2.0);
+
+        assert_eq!(glass.transmittance(0.0), v3(1, 1, 1));
+        assert!(glass.transmittance(2.0).dist(color) < 1e-9);
+        assert!(glass.transmittance(4.0).dist(color * color) < 1e-9);
+
+        assert_eq!(Material::dielectric(1.5).transmittance(10.0), v3(1, 1, 1));
+        assert_eq!(Material::lambertian(color).transmittance(10.0), v3(1, 1, 1));
+
+        // black channels absorb almost everything without ever going NaN
+        let black = Material::dielectric(1.5).with_absorption(v3(0, 0.5, -1), 1.0);
+        assert_eq!(black.transmittance(0.0), v3(1, 1, 1));
+        let t = black.transmittance(1.0);
+        assert!(
+            t.x < 1e-5 && (t.y - 0.5).abs() < 1e-9 && t.z < 1e-5,
+            "{t:?}"
+        );
+
+        // the other materials don't absorb light
+        let metal = Material::metal(color, 0.1).with_absorption(color, 2.0);
+        assert_eq!(metal.transmittance(10.0), v3(1, 1, 1));
+        assert!(matches!(metal, Material::Metal { fuzziness, .. } if fuzziness == 0.1));
+    }
+}
@@ -5,7 +5,7 @@ use std::ops::{Deref, DerefMut};
 
 use geo::{
     spatial_index::{Intersection, Shape},
-    Vec3,
+    Vec2, Vec3,
 };
 
 pub use facet::Facet;
@@ -22,6 +22,58 @@ pub trait Object: Shape<Intersection = Hit> + Surface + Sync + Send {
     /// returned in the `surface_id` field of `Hit` when this `Object` is
     /// intersected.
     fn set_surface_id(&mut self, id: usize);
+
+    /// Which kinds of rays can hit the `Object`. By default it's visible to
+    /// all of them.
+    fn visibility(&self) -> Visibility {
+        Visibility::default()
+    }
+}
+
+/// The kind of a `Ray` traced through a `Scene`, see `Visibility`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RayKind {
+    /// A ray cast from the camera.
+    Camera,
+
+    /// A ray checking whether a point is lit, e.g. by a light or the sun.
+    Shadow,
+
+    /// A ray bouncing off a surface to collect the indirect lighting, the
+    /// reflections and the refractions.
+    Bounce,
+}
+
+/// Which kinds of rays can hit an `Object`.
+///
+/// For example, a light can be invisible to the camera but still light the
+/// `Scene` or an object can appear in the image without casting shadows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Visibility {
+    pub camera: bool,
+    pub shadow: bool,
+    pub bounce: bool,
+}
+
+impl Visibility {
+    /// Whether a ray of the given kind can hit the `Object`.
+    pub fn hit_by(&self, kind: RayKind) -> bool {
+        match kind {
+            RayKind::Camera => self.camera,
+            RayKind::Shadow => self.shadow,
+            RayKind::Bounce => self.bounce,
+        }
+    }
+}
+
+impl Default for Visibility {
+    fn default() -> Self {
+        Self {
+            camera: true,
+            shadow: true,
+            bounce: true,
+        }
+    }
 }
 
 /// A `Surface` is an object that can be shaded.
@@ -45,6 +97,11 @@ pub struct Hit {
     /// set, but in case they were already calculated as part of the
     /// intersection check a recalculation is avoided this way.
     pub point_and_normal: Option<(Vec3, Vec3)>,
+
+    /// texture coordinates of the point at the given `t`, if the shape
+    /// provides a parameterization of its surface. They're usually, but not
+    /// necessarily, in [0, 1].
+    pub uv: Option<Vec2>,
 }
 
 impl Hit {
@@ -53,8 +110,15 @@ impl Hit {
             t,
             point_and_normal,
             surface_id: 0,
+            uv: None,
         }
     }
+
+    /// Set the texture coordinates of the `Hit`.
+    pub fn with_uv(mut self, uv: Vec2) -> Self {
+        self.uv = Some(uv);
+        self
+    }
 }
 
 impl Intersection for Hit {
@@ -74,6 +138,10 @@ where
     fn set_surface_id(&mut self, id: usize) {
         self.deref_mut().set_surface_id(id)
     }
+
+    fn visibility(&self) -> Visibility {
+        self.deref().visibility()
+    }
 }
 
 impl<T> Surface for Box<T>
@@ -1,12 +1,13 @@
 use geo::{ray::Ray, spatial_index::Shape, Aabb, Vec3};
 
-use crate::{material::Material, Hit, Object, Surface};
+use crate::{material::Material, Hit, Object, Surface, Visibility};
 
 #[derive(Debug)]
 pub struct SimpleObject<S> {
     geom: S,
     material: Material,
     surface_id: usize,
+    visibility: Visibility,
 }
 
 impl<G> SimpleObject<G> {
@@ -15,8 +16,15 @@ impl<G> SimpleObject<G> {
             geom,
             material,
             surface_id: 0,
+            visibility: Visibility::default(),
         }
     }
+
+    /// Set which kinds of rays can hit the object.
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
 }
 
 impl<S> Object for SimpleObject<S>
@@ -30,6 +38,10 @@ where
     fn set_surface_id(&mut self, id: usize) {
         self.surface_id = id;
     }
+
+    fn visibility(&self) -> Visibility {
+        self.visibility
+    }
 }
 
 impl<S> Surface for SimpleObject<S>
@@ -2,18 +2,33 @@
 //!
 //! [0]: https://en.wikipedia.org/wiki/Constructive_solid_geometry
 
-use geo::{ray::Ray, sdf::Sdf, spatial_index::Shape, Aabb, Vec3};
+use geo::{
+    ray::Ray,
+    sdf::{Sdf, SphereTracing},
+    spatial_index::Shape,
+    Aabb, Vec3,
+};
 
 use crate::{Hit, Surface};
 
 #[derive(Debug)]
 pub struct SdfGeometry {
     sdf: Sdf,
+    tracing: SphereTracing,
 }
 
 impl SdfGeometry {
     pub fn new(sdf: Sdf) -> Self {
-        SdfGeometry { sdf }
+        SdfGeometry {
+            sdf,
+            tracing: SphereTracing::new(1000),
+        }
+    }
+
+    /// Change the parameters used to find the intersections with the Sdf.
+    pub fn with_sphere_tracing(mut self, tracing: SphereTracing) -> Self {
+        self.tracing = tracing;
+        self
     }
 }
 
@@ -31,7 +46,7 @@ impl Shape for SdfGeometry {
     }
 
     fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
-        let t = self.sdf.ray_march(ray, 1000)?;
-        Some(Hit::new(t, None))
+        let hit = self.sdf.sphere_trace(ray, &self.tracing)?;
+        Some(Hit::new(hit.t, None))
     }
 }
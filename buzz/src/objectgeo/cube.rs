@@ -1,4 +1,4 @@
-use geo::{v3, Aabb};
+use geo::{v2, v3, Aabb};
 
 use crate::{Hit, Ray, Shape, Surface, Vec3};
 
@@ -18,7 +18,22 @@ impl Shape for CubeGeometry {
 
     fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
         let (tmin, tmax) = self.bbox.ray_intersection(ray)?;
-        Some(Hit::new(tmin.min(tmax), None))
+        let t = tmin.min(tmax);
+
+        // project the point onto the face it lies on, each face is mapped to
+        // the whole [0, 1] square
+        let p = ray.point_at(t);
+        let n = self.normal_at(p);
+        let uvw = (p - self.bbox.min()) / self.bbox.dimensions();
+        let uv = if n.x != 0.0 {
+            v2(uvw.y, uvw.z)
+        } else if n.y != 0.0 {
+            v2(uvw.x, uvw.z)
+        } else {
+            v2(uvw.x, uvw.y)
+        };
+
+        Some(Hit::new(t, None).with_uv(uv))
     }
 
     fn bbox(&self) -> Aabb {
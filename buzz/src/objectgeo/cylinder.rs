@@ -1,4 +1,6 @@
-use geo::{v3, Aabb};
+use std::f64::consts::PI;
+
+use geo::{v2, v3, Aabb};
 
 use crate::{Hit, Ray, Shape, Surface, Vec3};
 
@@ -44,17 +46,18 @@ impl Shape for CylinderGeometry {
             std::mem::swap(&mut t0, &mut t1);
         }
 
-        let z0 = ray.origin.z + t0 * ray.dir.z;
-        if t0 > EPS && self.zmin < z0 && self.zmax > z0 {
-            return Some(Hit::new(t0, None));
-        }
+        [t0, t1].into_iter().find_map(|t| {
+            let p = ray.point_at(t);
+            if t <= EPS || self.zmin >= p.z || self.zmax <= p.z {
+                return None;
+            }
 
-        let z1 = ray.origin.z + t1 * ray.dir.z;
-        if t1 > EPS && self.zmin < z1 && self.zmax > z1 {
-            return Some(Hit::new(t1, None));
-        }
+            // the angle around the Z axis and the height along it
+            let u = 0.5 + p.y.atan2(p.x) / (2.0 * PI);
+            let v = (p.z - self.zmin) / (self.zmax - self.zmin);
 
-        None
+            Some(Hit::new(t, None).with_uv(v2(u, v)))
+        })
     }
 
     fn bbox(&self) -> Aabb {
@@ -39,12 +39,9 @@ impl Surface for FacetGeometry {
         } else {
             let bary = self.tri.barycentric(&pt).expect("point outside triangle");
 
-            let mut n = self.tri.a * bary.x;
-            n += self.tri.b * bary.y;
-            n += self.tri.c * bary.z;
-            n.normalize();
-
-            n
+            self.tri
+                .interpolate(bary, self.tri.a, self.tri.b, self.tri.c)
+                .normalized()
         }
     }
 }
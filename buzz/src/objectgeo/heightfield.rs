@@ -0,0 +1,34 @@
+use geo::{ray::Ray, spatial_index::Shape, Aabb, Heightfield, Vec3};
+
+use crate::{Hit, Surface};
+
+/// A terrain described by a `Heightfield` whose heights are along the Z axis.
+#[derive(Debug, PartialEq, Clone)]
+pub struct HeightfieldGeometry {
+    heightfield: Heightfield,
+}
+
+impl HeightfieldGeometry {
+    pub fn new(heightfield: Heightfield) -> Self {
+        HeightfieldGeometry { heightfield }
+    }
+}
+
+impl Shape for HeightfieldGeometry {
+    type Intersection = Hit;
+
+    fn bbox(&self) -> Aabb {
+        self.heightfield.bbox()
+    }
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        let t = self.heightfield.intersection(ray)?;
+        Some(Hit::new(t, None))
+    }
+}
+
+impl Surface for HeightfieldGeometry {
+    fn normal_at(&self, p: Vec3) -> Vec3 {
+        self.heightfield.normal_at(p)
+    }
+}
@@ -0,0 +1,200 @@
+//! Two level acceleration structure for scenes with meshes.
+//!
+//! The triangles of a mesh are indexed once in the bottom level `Bvh` of a
+//! `TriangleMesh` that can then be shared, via an `Arc`, among any number of
+//! `MeshInstance`s each placing the mesh in the scene with its own transform.
+//! The `Scene` index is the top level that only stores the instances so that
+//! moving an instance, e.g. between the frames of an animation, only requires
+//! to rebuild the cheap top level, see `Scene::set_objects`, while the
+//! triangles are never indexed again.
+
+use std::sync::Arc;
+
+use geo::{mat4::Mat4, ray::Ray, spatial_index::Bvh, Aabb, Triangle, Vec3};
+
+use crate::{FacetGeometry, Hit, Shape, Surface};
+
+/// A triangle mesh indexed by its own `Bvh`, the bottom level of the
+/// acceleration structure.
+#[derive(Debug)]
+pub struct TriangleMesh {
+    facets: Bvh<FacetGeometry>,
+    bbox: Aabb,
+}
+
+/// An instance of a shared `TriangleMesh` placed in the scene by the given
+/// transform.
+#[derive(Debug, Clone)]
+pub struct MeshInstance {
+    mesh: Arc<TriangleMesh>,
+    trans: Mat4,
+    inverse_trans: Mat4,
+}
+
+impl TriangleMesh {
+    /// Build the `TriangleMesh` made of the given triangles.
+    pub fn new(triangles: impl IntoIterator<Item = Triangle>, flat_shading: bool) -> Self {
+        let facets = triangles
+            .into_iter()
+            .map(|t| FacetGeometry::new(t, flat_shading))
+            .collect::<Bvh<_>>();
+
+        let bbox = facets.bbox().unwrap_or_else(|| Aabb::new(Vec3::zero()));
+
+        Self { facets, bbox }
+    }
+
+    /// Return the number of triangles in the `TriangleMesh`.
+    pub fn len(&self) -> usize {
+        self.facets.iter().count()
+    }
+
+    /// Return whether the `TriangleMesh` has no triangles.
+    pub fn is_empty(&self) -> bool {
+        self.facets.iter().next().is_none()
+    }
+}
+
+impl Shape for TriangleMesh {
+    type Intersection = Hit;
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        // the normal must be calculated here because only now the hit facet
+        // is known
+        let (facet, hit) = self.facets.closest_intersection(ray)?;
+
+        let p = ray.point_at(hit.t);
+        Some(Hit::new(hit.t, Some((p, facet.normal_at(p)))))
+    }
+
+    fn bbox(&self) -> Aabb {
+        self.bbox.clone()
+    }
+}
+
+impl Surface for TriangleMesh {
+    fn normal_at(&self, _p: Vec3) -> Vec3 {
+        unreachable!()
+    }
+}
+
+impl MeshInstance {
+    pub fn new(mesh: Arc<TriangleMesh>, trans: Mat4) -> Self {
+        let inverse_trans = trans.inverse();
+
+        Self {
+            mesh,
+            trans,
+            inverse_trans,
+        }
+    }
+
+    /// Return a copy of this `MeshInstance` placed by the given transform
+    /// that shares the same `TriangleMesh`.
+    pub fn with_transform(&self, trans: Mat4) -> Self {
+        Self::new(Arc::clone(&self.mesh), trans)
+    }
+
+    /// The `TriangleMesh` this `MeshInstance` refers to.
+    pub fn mesh(&self) -> &Arc<TriangleMesh> {
+        &self.mesh
+    }
+}
+
+impl Shape for MeshInstance {
+    type Intersection = Hit;
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        let transformed_ray = ray.clone() * &self.inverse_trans;
+        let hit = self.mesh.intersection(&transformed_ray)?;
+        let (p, n) = hit.point_and_normal?;
+
+        let intersection = p * &self.trans;
+        let tn = self.inverse_trans.transform_normal(&n);
+
+        let t = (intersection - ray.origin).dot(ray.dir) / ray.dir.norm2();
+        Some(Hit {
+            uv: hit.uv,
+            ..Hit::new(t, Some((intersection, tn)))
+        })
+    }
+
+    fn bbox(&self) -> Aabb {
+        self.mesh.bbox() * &self.trans
+    }
+}
+
+impl Surface for MeshInstance {
+    fn normal_at(&self, _p: Vec3) -> Vec3 {
+        unreachable!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::v3;
+
+    use super::*;
+
+    #[test]
+    fn test_instances_share_mesh() {
+        let mesh = Arc::new(TriangleMesh::new(
+            [
+                Triangle::new(v3(-1, -1, 0), v3(1, -1, 0), v3(1, 1, 0)),
+                Triangle::new(v3(-1, -1, 0), v3(1, 1, 0), v3(-1, 1, 0)),
+            ],
+            true,
+        ));
+        assert_eq!(mesh.len(), 2);
+
+        let a = MeshInstance::new(Arc::clone(&mesh), Mat4::identity());
+        let b = a.with_transform(Mat4::translate(v3(0, 0, 5)));
+        assert!(Arc::ptr_eq(a.mesh(), b.mesh()));
+
+        let ray = Ray::new(v3(0, 0, 10), v3(0.0, 0.0, -1.0));
+
+        let hit = a.intersection(&ray).unwrap();
+        assert!((hit.t - 10.0).abs() < 1e-9);
+
+        let hit = b.intersection(&ray).unwrap();
+        assert!((hit.t - 5.0).abs() < 1e-9);
+        let (p, n) = hit.point_and_normal.unwrap();
+        assert!(p.dist(v3(0, 0, 5)) < 1e-9);
+        assert!(n.dist(v3(0, 0, 1)) < 1e-9);
+
+        assert_eq!(b.bbox(), Aabb::new(v3(-1, -1, 5)).expanded(v3(1, 1, 5)));
+        assert!(b
+            .intersection(&Ray::new(v3(3, 0, 10), v3(0.0, 0.0, -1.0)))
+            .is_none());
+    }
+
+    #[test]
+    fn test_scaled_instance() {
+        let mesh = Arc::new(TriangleMesh::new(
+            [
+                Triangle::new(v3(-1, -1, 0), v3(1, -1, 0), v3(1, 1, 0)),
+                Triangle::new(v3(-1, -1, 0), v3(1, 1, 0), v3(-1, 1, 0)),
+            ],
+            true,
+        ));
+        let instance = MeshInstance::new(
+            mesh,
+            Mat4::translate(v3(0, 0, 5)) * &Mat4::scale(v3(3, 3, 3)),
+        );
+
+        // the direction is not normalized, t must still be expressed in terms
+        // of the original ray and not be the distance from its origin
+        let ray = Ray::new(v3(2, 0, 10), v3(0, 0, -2));
+        let hit = instance.intersection(&ray).unwrap();
+        assert!((hit.t - 2.5).abs() < 1e-9, "{}", hit.t);
+
+        let (p, n) = hit.point_and_normal.unwrap();
+        assert!(p.dist(ray.point_at(hit.t)) < 1e-9);
+        assert!(p.dist(v3(2, 0, 5)) < 1e-9);
+        assert!(n.dist(v3(0, 0, 1)) < 1e-9);
+
+        assert!(instance
+            .intersection(&Ray::new(v3(4, 0, 10), v3(0, 0, -2)))
+            .is_none());
+    }
+}
@@ -2,6 +2,9 @@ mod csg;
 mod cube;
 mod cylinder;
 mod facet;
+mod heightfield;
+mod mesh;
+mod moving;
 mod plane;
 mod sphere;
 mod transformed;
@@ -10,6 +13,9 @@ pub use csg::SdfGeometry;
 pub use cube::CubeGeometry;
 pub use cylinder::CylinderGeometry;
 pub use facet::FacetGeometry;
+pub use heightfield::HeightfieldGeometry;
+pub use mesh::{MeshInstance, TriangleMesh};
+pub use moving::{Keyframe, MovingGeometry};
 pub use plane::PlaneGeometry;
 pub use sphere::SphereGeometry;
 pub use transformed::TransformedGeometry;
@@ -0,0 +1,190 @@
+use geo::{mat4::Mat4, quat::Quat, Aabb};
+
+use crate::{Hit, Ray, Shape, Surface, Vec3};
+
+/// Number of instants the motion is sampled at to compute the bounding box.
+const BBOX_SAMPLES: u32 = 16;
+
+/// A transformation made of a scaling, a rotation and a translation applied in
+/// this order.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Keyframe {
+    pub scale: Vec3,
+    pub rotation: Quat,
+    pub translation: Vec3,
+}
+
+/// A shape that moves from one `Keyframe` at time 0 to another at time 1.
+///
+/// The transformation is interpolated at the time of each `Ray`, hence
+/// casting rays at different instants renders the shape with motion blur.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MovingGeometry<S> {
+    shape: S,
+    from: Keyframe,
+    to: Keyframe,
+}
+
+impl Keyframe {
+    /// The `Keyframe` that doesn't transform the shape at all.
+    pub fn identity() -> Self {
+        Keyframe {
+            scale: Vec3::new(1.0, 1.0, 1.0),
+            rotation: Quat::identity(),
+            translation: Vec3::zero(),
+        }
+    }
+
+    pub fn with_scale(mut self, scale: Vec3) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: Quat) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_translation(mut self, translation: Vec3) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    /// Interpolate between `self` and `to`, the rotation is interpolated with
+    /// `Quat::slerp` so that it takes the shortest path.
+    pub fn lerp(&self, to: &Keyframe, t: f64) -> Keyframe {
+        Keyframe {
+            scale: self.scale.lerp(to.scale, t),
+            rotation: self.rotation.slerp(to.rotation, t),
+            translation: self.translation.lerp(to.translation, t),
+        }
+    }
+
+    /// Return the transformation matrix of this `Keyframe`.
+    pub fn to_mat4(&self) -> Mat4 {
+        Mat4::translate(self.translation) * &(self.rotation.to_mat4() * &Mat4::scale(self.scale))
+    }
+
+    /// Return the inverse transformation matrix of this `Keyframe`.
+    pub fn to_inverse_mat4(&self) -> Mat4 {
+        let inv_scale = Vec3::new(1.0 / self.scale.x, 1.0 / self.scale.y, 1.0 / self.scale.z);
+
+        Mat4::scale(inv_scale)
+            * &(self.rotation.conjugate().to_mat4() * &Mat4::translate(-self.translation))
+    }
+}
+
+impl Default for Keyframe {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl<S> MovingGeometry<S> {
+    pub fn new(shape: S, from: Keyframe, to: Keyframe) -> Self {
+        MovingGeometry { shape, from, to }
+    }
+
+    /// Return the `Keyframe` at the given time which is clamped to [0, 1].
+    pub fn keyframe_at(&self, time: f64) -> Keyframe {
+        self.from.lerp(&self.to, time.clamp(0.0, 1.0))
+    }
+}
+
+impl<S> Shape for MovingGeometry<S>
+where
+    S: Shape<Intersection = Hit> + Surface,
+{
+    type Intersection = Hit;
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        let keyframe = self.keyframe_at(ray.time);
+        let (trans, inverse_trans) = (keyframe.to_mat4(), keyframe.to_inverse_mat4());
+
+        let transformed_ray = ray.clone() * &inverse_trans;
+        let hit = self.shape.intersection(&transformed_ray)?;
+
+        let p = transformed_ray.point_at(hit.t);
+        let n = self.shape.normal_at(p);
+
+        let intersection = p * &trans;
+        let tn = inverse_trans.transform_normal(&n);
+
+        let t = (intersection - ray.origin).dot(ray.dir) / ray.dir.norm2();
+        Some(Hit {
+            uv: hit.uv,
+            ..Hit::new(t, Some((intersection, tn)))
+        })
+    }
+
+    fn bbox(&self) -> Aabb {
+        // the bounding sphere of the shape contains it no matter how it's
+        // rotated, therefore it's enough to track how its center moves. The
+        // motion is only sampled so pad the result by the maximum distance the
+        // center travels between two samples.
+        let (center, radius) = self.shape.bounding_sphere();
+
+        let spheres = (0..=BBOX_SAMPLES)
+            .map(|i| {
+                let k = self.keyframe_at(f64::from(i) / f64::from(BBOX_SAMPLES));
+                let s = k.scale;
+
+                (
+                    center * &k.to_mat4(),
+                    radius * s.x.abs().max(s.y.abs()).max(s.z.abs()),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let step = spheres
+            .windows(2)
+            .map(|w| w[0].0.dist(w[1].0))
+            .fold(0.0, f64::max);
+
+        let mut bbox = Aabb::new(spheres[0].0);
+        for (c, r) in spheres {
+            let r = Vec3::new(r + step, r + step, r + step);
+            bbox.expand(c - r);
+            bbox.expand(c + r);
+        }
+
+        bbox
+    }
+}
+
+impl<S> Surface for MovingGeometry<S>
+where
+    S: Surface,
+{
+    fn normal_at(&self, _p: Vec3) -> Vec3 {
+        unreachable!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::v3;
+
+    use crate::SphereGeometry;
+
+    use super::*;
+
+    #[test]
+    fn test_intersection_at_time() {
+        let sphere = MovingGeometry::new(
+            SphereGeometry::new(Vec3::zero(), 1.0),
+            Keyframe::identity(),
+            Keyframe::identity().with_translation(v3(10, 0, 0)),
+        );
+
+        let ray = Ray::new(v3(0, 0, -5), v3(0, 0, 1));
+        assert_eq!(sphere.intersection(&ray).map(|h| h.t), Some(4.0));
+        assert!(sphere.intersection(&ray.clone().with_time(1.0)).is_none());
+
+        let ray = Ray::new(v3(5, 0, -5), v3(0, 0, 1)).with_time(0.5);
+        assert_eq!(sphere.intersection(&ray).map(|h| h.t), Some(4.0));
+
+        let bbox = sphere.bbox();
+        assert!(bbox.min().x <= -1.0 && bbox.max().x >= 11.0);
+    }
+}
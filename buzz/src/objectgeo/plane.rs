@@ -1,22 +1,23 @@
-use geo::{plane, ray::Ray, spatial_index::Shape, Aabb, Vec3};
+use geo::{ray::Ray, spatial_index::Shape, v2, Aabb, Plane, Vec3};
 
 use crate::{Hit, Surface};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct PlaneGeometry {
-    pub origin: Vec3,
-    pub normal: Vec3,
+    pub plane: Plane,
 }
 
 impl PlaneGeometry {
     pub fn new(origin: Vec3, normal: Vec3) -> Self {
-        PlaneGeometry { origin, normal }
+        PlaneGeometry {
+            plane: Plane::new(origin, normal),
+        }
     }
 }
 
 impl Surface for PlaneGeometry {
     fn normal_at(&self, _pt: Vec3) -> Vec3 {
-        self.normal
+        self.plane.normal
     }
 }
 
@@ -24,15 +25,20 @@ impl Shape for PlaneGeometry {
     type Intersection = Hit;
 
     fn bbox(&self) -> Aabb {
-        plane::bbox()
+        self.plane.bbox()
     }
 
     fn bounding_sphere(&self) -> (Vec3, f64) {
-        (self.origin, f64::INFINITY)
+        self.plane.bounding_sphere()
     }
 
     fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
-        let t = plane::intersection(self.origin, self.normal, ray)?;
-        Some(Hit::new(t, None))
+        let t = self.plane.intersection(ray)?;
+
+        // the coordinates on the plane wrt its origin, they're not bounded
+        let (bu, bv) = self.plane.basis();
+        let d = ray.point_at(t) - self.plane.origin;
+
+        Some(Hit::new(t, None).with_uv(v2(d.dot(bu), d.dot(bv))))
     }
 }
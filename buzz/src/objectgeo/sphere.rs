@@ -1,4 +1,6 @@
-use geo::{ray::Ray, spatial_index::Shape, sphere, Aabb, Vec3};
+use std::f64::consts::PI;
+
+use geo::{ray::Ray, spatial_index::Shape, v2, Aabb, Sphere, Vec3};
 
 use crate::{Hit, Surface};
 
@@ -12,27 +14,63 @@ impl SphereGeometry {
     pub fn new(center: Vec3, radius: f64) -> Self {
         SphereGeometry { center, radius }
     }
+
+    /// The `Sphere` this geometry is made of.
+    pub fn sphere(&self) -> Sphere {
+        Sphere::new(self.center, self.radius)
+    }
+}
+
+impl From<Sphere> for SphereGeometry {
+    fn from(s: Sphere) -> Self {
+        SphereGeometry::new(s.center, s.radius)
+    }
 }
 
 impl Shape for SphereGeometry {
     type Intersection = Hit;
 
     fn bbox(&self) -> Aabb {
-        sphere::bounding_box(self.center, self.radius)
+        self.sphere().bbox()
     }
 
     fn bounding_sphere(&self) -> (Vec3, f64) {
-        (self.center, self.radius)
+        self.sphere().bounding_sphere()
     }
 
     fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
-        let t = sphere::ray_intersection(self.center, self.radius, ray)?;
-        Some(Hit::new(t, None))
+        let t = self.sphere().intersection(ray)?;
+
+        // spherical coordinates around the Z axis, v goes from the south to
+        // the north pole
+        let d = (ray.point_at(t) - self.center) / self.radius;
+        let u = 0.5 + d.y.atan2(d.x) / (2.0 * PI);
+        let v = 0.5 + d.z.clamp(-1.0, 1.0).asin() / PI;
+
+        Some(Hit::new(t, None).with_uv(v2(u, v)))
     }
 }
 
 impl Surface for SphereGeometry {
     fn normal_at(&self, pt: Vec3) -> Vec3 {
-        sphere::normal(self.center, pt)
+        self.sphere().normal_at(pt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::{v2, v3};
+
+    use super::*;
+
+    #[test]
+    fn test_uv() {
+        let sphere = SphereGeometry::new(v3(0, 0, 1), 2.0);
+
+        let uv = |origin: Vec3, dir: Vec3| sphere.intersection(&Ray::new(origin, dir))?.uv;
+
+        assert_eq!(uv(v3(0, 0, 10), v3(0, 0, -1)), Some(v2(0.5, 1.0)));
+        assert_eq!(uv(v3(10, 0, 1), v3(-1, 0, 0)), Some(v2(0.5, 0.5)));
+        assert_eq!(uv(v3(0, -10, 1), v3(0, 1, 0)), Some(v2(0.25, 0.5)));
     }
 }
@@ -37,7 +37,10 @@ where
         let intersection = p * &self.trans;
         let tn = self.inverse_trans.transform_normal(&n);
 
-        Some(Hit::new((p - ray.origin).norm(), Some((intersection, tn))))
+        Some(Hit {
+            uv: hit.uv,
+            ..Hit::new((p - ray.origin).norm(), Some((intersection, tn)))
+        })
     }
 
     fn bbox(&self) -> Aabb {
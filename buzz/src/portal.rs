@@ -0,0 +1,98 @@
+//! Light portals guide the sampling of the `Environment` in interior scenes.
+//!
+//! When a room is only lit by the environment through a few small openings,
+//! almost none of the random bounces escape the room and the render converges
+//! very slowly. Placing a `Portal` over each opening allows the renderer to
+//! explicitly sample the environment through it, as if it were an area light.
+
+use geo::{ray::Ray, Vec3};
+use rand::Rng;
+
+/// A parallelogram through which the `Environment` is visible.
+///
+/// Note that all the light coming from the `Environment` is assumed to come
+/// through the portals, so they must cover all the openings of the scene.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Portal {
+    corner: Vec3,
+    u: Vec3,
+    v: Vec3,
+    normal: Vec3,
+    area: f64,
+}
+
+impl Portal {
+    /// Create a new `Portal` with a corner at `corner` and the two sides `u`
+    /// and `v` starting from it.
+    pub fn new(corner: Vec3, u: Vec3, v: Vec3) -> Self {
+        let n = u.cross(v);
+        let area = n.norm();
+        assert!(area > 0.0, "portal sides must not be parallel");
+
+        Self {
+            corner,
+            u,
+            v,
+            normal: n / area,
+            area,
+        }
+    }
+
+    pub fn normal(&self) -> Vec3 {
+        self.normal
+    }
+
+    pub fn area(&self) -> f64 {
+        self.area
+    }
+
+    /// Return a random point uniformly distributed on the `Portal`.
+    pub fn sample_point(&self, rng: &mut impl Rng) -> Vec3 {
+        self.corner + self.u * rng.gen::<f64>() + self.v * rng.gen::<f64>()
+    }
+
+    /// Return the t parameter of the point where the `Ray` goes through the
+    /// `Portal`, if any.
+    pub fn intersection(&self, ray: &Ray) -> Option<f64> {
+        let d = self.normal.dot(ray.dir);
+        if d.abs() < 1e-9 {
+            return None;
+        }
+
+        let t = self.normal.dot(self.corner - ray.origin) / d;
+        if t < 1e-9 {
+            return None;
+        }
+
+        // solve for the coordinates of the point along the two sides
+        let p = ray.point_at(t) - self.corner;
+        let a = p.cross(self.v).dot(self.normal) / self.area;
+        let b = self.u.cross(p).dot(self.normal) / self.area;
+
+        ((0.0..=1.0).contains(&a) && (0.0..=1.0).contains(&b)).then_some(t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::v3;
+
+    use super::*;
+
+    #[test]
+    fn test_intersection() {
+        let portal = Portal::new(v3(0, 0, 2), v3(2, 0, 0), v3(0, 1, 0));
+
+        assert_eq!(portal.area(), 2.0);
+        assert_eq!(portal.normal(), v3(0, 0, 1));
+
+        let ray = |x, y| Ray::new(v3(x, y, 0.0), v3(0, 0, 2));
+        assert_eq!(portal.intersection(&ray(1.5, 0.5)), Some(1.0));
+        assert_eq!(portal.intersection(&ray(0.0, 0.0)), Some(1.0));
+        assert_eq!(portal.intersection(&ray(2.5, 0.5)), None);
+        assert_eq!(portal.intersection(&ray(1.0, -0.1)), None);
+
+        let away = Ray::new(v3(1.0, 0.5, 0.0), v3(0, 0, -1));
+        assert_eq!(portal.intersection(&away), None);
+    }
+}
@@ -0,0 +1,30 @@
+//! Glob import this module to fill a path traced `Scene` with geometries and
+//! their `Material`s, light it with an `Environment` and render it from a
+//! `Camera`. The vectors and shapes of `geo::prelude` are re-exported too.
+//!
+//! ```no_run
+//! use buzz::prelude::*;
+//!
+//! let mut objects = SceneObjects::new();
+//! objects.push(SimpleObject::new(
+//!     SphereGeometry::new(Vec3::zero(), 1.0),
+//!     Material::lambertian(v3(0.8, 0.2, 0.2)),
+//! ));
+//! let scene = Scene::new(objects, Environment::Color(v3(1, 1, 1)));
+//!
+//! let camera = Camera::look_at(v3(3, 3, 3), Vec3::zero(), v3(0, 0, 1), 50.0);
+//! let config = RenderConfig {
+//!     width: 320,
+//!     height: 240,
+//!     ..Default::default()
+//! };
+//! render(&camera, &scene, &config).save_png("sphere.png")?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+pub use geo::prelude::*;
+
+pub use crate::{
+    camera::Camera, material::Material, object::*, objectgeo::*, renderer::*, sky::Sky,
+    texture::Texture, Environment, Scene, SceneObjects,
+};
@@ -1,14 +1,20 @@
-use geo::{ray::Ray, spatial_index::Intersection, util::image::Image, Vec3};
+use geo::{
+    plane::orthonormal_basis, ray::Ray, spatial_index::Intersection, util::image::Image, Axis, Vec3,
+};
 
-use std::convert::TryFrom;
+use std::{convert::TryFrom, f64::consts::PI, io};
 
 use rand::prelude::*;
 use rand_xorshift::XorShiftRng;
 use rayon::prelude::*;
 
 use crate::{
-    material::{dielectric_bounce, lambertian_bounce, metal_bounce, Material},
-    Camera, Environment, Object, Scene,
+    exr,
+    material::{
+        dielectric_bounce, dispersed_refraction_index, fresnel_schlick, ggx_bounce,
+        lambertian_bounce, metal_bounce, pbr_f0, Material,
+    },
+    Camera, Denoiser, Environment, LightTree, Object, Portal, RayDifferential, RayKind, Scene,
 };
 
 /// Simple struct to hold rendering params together.
@@ -23,6 +29,14 @@ pub struct RenderConfig {
     /// better results in scenes with a lot of reflective objects.
     pub max_bounces: u32,
 
+    /// paths whose throughput, that is the fraction of light they can still
+    /// carry, falls below this threshold are randomly terminated via Russian
+    /// roulette and the surviving ones are boosted to compensate so that the
+    /// result stays unbiased. This allows a high `max_bounces` for deep glass
+    /// or mirror scenes without wasting time on negligible diffuse paths. 0
+    /// disables it.
+    pub throughput_threshold: f64,
+
     /// whether to calculate direct lighting for each intersection. This is
     /// useful because calculating only indirect lighting in a scene is
     /// particularly resource hungry if a lot of details is needed. On the other
@@ -36,21 +50,149 @@ pub struct RenderConfig {
     /// width and height of the rendered image.
     pub width: u32,
     pub height: u32,
+
+    /// the interval of time the shutter of the camera stays open. Each camera
+    /// ray is cast at a random instant in this interval and therefore objects
+    /// moving in the meantime look blurred.
+    pub shutter_open: f64,
+    pub shutter_close: f64,
+
+    /// the seed of all the random numbers used while rendering, two renders
+    /// with the same seed and config produce exactly the same image. A random
+    /// seed is picked at each render if it's not set.
+    pub seed: Option<u64>,
+
+    /// the rays bouncing off a surface start slightly above it, along its
+    /// normal, so that they don't hit it again because of floating point
+    /// errors, i.e. shadow acne. The offset is this relative epsilon scaled
+    /// by the magnitude of the coordinates of the hit point and by its
+    /// distance from the origin of the ray since the errors grow with both,
+    /// see `DEFAULT_RAY_EPSILON`.
+    pub ray_epsilon: f64,
+
+    /// how to shade the surfaces, the debug modes ignore most of the other
+    /// settings and are a lot faster than a full render.
+    pub shading: ShadingMode,
+
+    /// optional rectangle of pixels, as its top left corner followed by its
+    /// width and height, to limit the rendering to. The image keeps its full
+    /// dimensions and the camera rays are the same of a full render, but the
+    /// pixels outside the rectangle are left black. Handy to inspect a detail
+    /// at full quality without waiting for the whole frame.
+    pub crop: Option<(u32, u32, u32, u32)>,
+
+    /// whether the environment seen directly by the camera, also behind the
+    /// shadow catchers, is left out of the image. Since the camera rays that
+    /// don't hit anything have no alpha the colors are then premultiplied by
+    /// the alpha, which is what `Framebuffer::to_rgba_image` expects, and the
+    /// render can be composited over another background. The environment
+    /// still lights the scene and shows up in reflections.
+    pub transparent_background: bool,
+
+    /// optional fog fading the surfaces seen by the camera into its color the
+    /// farther they are. It's a cheap way to give a sense of depth to large
+    /// scenes without tracing the light through a participating medium.
+    pub fog: Option<Fog>,
+
+    /// whether the rendered images are encoded with the sRGB transfer
+    /// function, which is what image viewers expect, instead of the plain
+    /// square root gamma correction. It's off by default to keep the colors
+    /// of the existing renders.
+    pub srgb: bool,
+}
+
+/// A `RenderConfig::ray_epsilon` that works well for most scenes.
+pub const DEFAULT_RAY_EPSILON: f64 = 1e-6;
+
+impl Default for RenderConfig {
+    /// Path trace a Full HD image with a random seed, like the scene files do
+    /// when the render settings are omitted.
+    fn default() -> Self {
+        Self {
+            samples: 10,
+            max_bounces: 5,
+            throughput_threshold: 0.0,
+            direct_lighting: true,
+            soft_shadows: true,
+            width: 1920,
+            height: 1080,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
+            seed: None,
+            ray_epsilon: DEFAULT_RAY_EPSILON,
+            shading: ShadingMode::PathTracing,
+            crop: None,
+            transparent_background: false,
+            fog: None,
+            srgb: false,
+        }
+    }
+}
+
+/// How `RenderConfig` shades the surfaces hit by the camera rays.
+///
+/// All the modes but `PathTracing` are meant to quickly debug the setup of a
+/// scene without touching its materials.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum ShadingMode {
+    /// Full path tracing.
+    #[default]
+    PathTracing,
+
+    /// Color each surface by its normal mapped from [-1, 1] to [0, 1], which
+    /// makes flipped or missing mesh normals stand out.
+    Normals,
+
+    /// Grayscale distance of the surfaces from the camera, white up close and
+    /// black at `far` and beyond.
+    Depth { far: f64 },
+
+    /// Diffuse surfaces are lit only by the lights and the portals, without
+    /// any indirect bounce. Reflections and refractions are still traced.
+    DirectOnly,
+
+    /// Clay-like shading that only depends on the angle between each surface
+    /// and the view direction, ignoring the materials and the lights.
+    Matcap,
+}
+
+/// Exponential distance fog, see `RenderConfig::fog`.
+///
+/// Only the distance of the first surface hit by each camera ray counts,
+/// hence reflections and refractions are not fogged any further and the
+/// environment seen directly is entirely covered by the fog.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Fog {
+    pub color: Vec3,
+
+    /// how thick the fog is, the fraction of the light of a surface that
+    /// reaches the camera is `exp(-density * distance)`.
+    pub density: f64,
+}
+
+impl Fog {
+    /// Fade the `color` of a surface at the given distance from the camera
+    /// into the fog. `coverage` scales the color of the fog, it's the alpha of
+    /// the surface when the colors are premultiplied.
+    pub fn apply(&self, color: Vec3, distance: f64, coverage: f64) -> Vec3 {
+        let t = (-self.density * distance).exp();
+        color * t + self.color * (coverage * (1.0 - t))
+    }
 }
 
 /// Render a `Scene` from a `Camera` to a new `RgbImage` of the given
 /// dimensions.
 pub fn render(camera: &Camera, scene: &Scene, config: &RenderConfig) -> Image<3> {
-    let lights = if config.direct_lighting {
-        scene.lights().collect::<Vec<_>>()
-    } else {
-        vec![]
-    };
+    let lights = light_tree(scene, config);
 
-    let mut rng = XorShiftRng::seed_from_u64(thread_rng().gen());
+    let mut rng = XorShiftRng::seed_from_u64(config.seed());
     let mut img = Image::rgb(config.width, config.height);
 
     for (x, y, pix) in img.pixels_mut() {
+        if !config.in_crop(x, y) {
+            continue;
+        }
+
         pix.copy_from_slice(&render_pixel(
             (x, y),
             camera,
@@ -67,129 +209,956 @@ pub fn render(camera: &Camera, scene: &Scene, config: &RenderConfig) -> Image<3>
 /// Render a `Scene` from a `Camera` to a new `RgbImage` of the given dimensions
 /// concurrently.
 pub fn parallel_render(camera: &Camera, scene: &Scene, config: &RenderConfig) -> Image<3> {
-    let lights = if config.direct_lighting {
-        scene.lights().collect::<Vec<_>>()
-    } else {
-        vec![]
+    let mut fb = Framebuffer::new(config.width, config.height).with_srgb(config.srgb);
+    render_pass(
+        camera,
+        scene,
+        config,
+        &mut fb,
+        config.samples,
+        config.seed(),
+    );
+    fb.to_image()
+}
+
+impl RenderConfig {
+    /// Return the seed of the random numbers to use for a render, either the
+    /// configured one or a random one.
+    fn seed(&self) -> u64 {
+        self.seed.unwrap_or_else(|| thread_rng().gen())
+    }
+
+    /// Decide whether a path with the given throughput continues returning
+    /// the factor to scale its contribution by, see `throughput_threshold`.
+    fn roulette(&self, throughput: Vec3, rng: &mut impl Rng) -> Option<f64> {
+        let m = throughput.x.max(throughput.y).max(throughput.z);
+        if self.throughput_threshold <= 0.0 || m >= self.throughput_threshold {
+            return Some(1.0);
+        }
+
+        let p = m / self.throughput_threshold;
+        (rng.gen::<f64>() < p).then(|| 1.0 / p)
+    }
+
+    /// Whether the pixel at the given coordinates is inside the `crop`
+    /// rectangle, if any.
+    fn in_crop(&self, x: u32, y: u32) -> bool {
+        self.crop.is_none_or(|(cx, cy, w, h)| {
+            (cx..cx.saturating_add(w)).contains(&x) && (cy..cy.saturating_add(h)).contains(&y)
+        })
+    }
+
+    /// Whether the diffuse surfaces sample the lights directly.
+    fn direct_lighting(&self) -> bool {
+        self.direct_lighting || self.shading == ShadingMode::DirectOnly
+    }
+
+    /// Move the origin of `bounce`, a ray spawned from the surface hit by
+    /// `ray` whose normal is `n`, off the surface on the side `bounce` goes
+    /// to.
+    fn offset_ray(&self, ray: &Ray, n: Vec3, mut bounce: Ray) -> Ray {
+        let p = bounce.origin;
+        let scale = 1.0 + p.x.abs().max(p.y.abs()).max(p.z.abs()) + p.dist(ray.origin);
+        let offset = n.normalized() * (self.ray_epsilon * scale);
+
+        if bounce.dir.dot(n) >= 0.0 {
+            bounce.origin += offset;
+        } else {
+            bounce.origin -= offset;
+        }
+
+        bounce
+    }
+}
+
+/// Accumulation buffer that stores the sum of all the samples taken so far
+/// for each pixel.
+///
+/// It allows to render an image progressively in multiple passes, each one
+/// refining the previous ones, and to save the partial result at any time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Framebuffer {
+    width: u32,
+    height: u32,
+    samples: u32,
+    data: Vec<Vec3>,
+    alpha: Vec<f64>,
+    srgb: bool,
+}
+
+impl Framebuffer {
+    /// Create a new empty `Framebuffer` with the given dimensions.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self::from_raw(width, height, 0, vec![Vec3::zero(); pixels(width, height)])
+    }
+
+    /// Create a `Framebuffer` from the per pixel sums of `samples` samples.
+    ///
+    /// All the pixels are fully opaque, use `with_alpha` to set their alpha.
+    pub fn from_raw(width: u32, height: u32, samples: u32, data: Vec<Vec3>) -> Self {
+        assert_eq!(data.len(), pixels(width, height));
+
+        Self {
+            width,
+            height,
+            samples,
+            alpha: vec![f64::from(samples); data.len()],
+            data,
+            srgb: false,
+        }
+    }
+
+    /// Replace the per pixel sums of the alpha of the samples.
+    pub fn with_alpha(mut self, alpha: Vec<f64>) -> Self {
+        assert_eq!(alpha.len(), self.data.len());
+
+        self.alpha = alpha;
+        self
+    }
+
+    /// Encode the images with the sRGB transfer function instead of the plain
+    /// gamma correction, see `RenderConfig::srgb`.
+    pub fn with_srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Number of samples per pixel accumulated so far.
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// The sum of all the samples of each pixel, row by row.
+    pub fn data(&self) -> &[Vec3] {
+        &self.data
+    }
+
+    /// The sum of the alpha of all the samples of each pixel, row by row.
+    ///
+    /// The alpha of a sample is 0 when the camera ray sees the `Environment`
+    /// and 1 when it hits an object, but for shadow catchers whose alpha is
+    /// how much they're in shadow. This allows to composite the render over
+    /// another background.
+    pub fn alpha(&self) -> &[f64] {
+        &self.alpha
+    }
+
+    /// Average the accumulated samples and convert them to a gamma corrected
+    /// image.
+    pub fn to_image(&self) -> Image<3> {
+        let mut img = Image::rgb(self.width, self.height);
+        let n = f64::from(self.samples.max(1));
+
+        for (pix, c) in img.data_mut().chunks_mut(3).zip(&self.data) {
+            pix.copy_from_slice(&to_rgb(*c / n, self.srgb));
+        }
+
+        img
+    }
+
+    /// Average the accumulated samples and save them as a PNG.
+    pub fn save_png(&self, path: &str) -> io::Result<()> {
+        self.to_image().save_png(path)
+    }
+
+    /// Average the accumulated samples and their alpha and convert them to a
+    /// gamma corrected image with transparency.
+    ///
+    /// The colors are assumed to be premultiplied by the alpha, that is
+    /// rendered with `RenderConfig::transparent_background`.
+    pub fn to_rgba_image(&self) -> Image<4> {
+        let mut img = Image::rgba(self.width, self.height);
+        let n = f64::from(self.samples.max(1));
+
+        for ((pix, c), a) in img
+            .data_mut()
+            .chunks_mut(4)
+            .zip(&self.data)
+            .zip(&self.alpha)
+        {
+            let a = (a / n).clamp(0.0, 1.0);
+            let c = if a > 0.0 { *c / (n * a) } else { Vec3::zero() };
+
+            pix[..3].copy_from_slice(&to_rgb(c, self.srgb));
+            pix[3] = (a * 255.0).round() as u8;
+        }
+
+        img
+    }
+
+    /// Average the accumulated samples and their alpha and save them as a PNG
+    /// with transparency, see `to_rgba_image`.
+    pub fn save_rgba_png(&self, path: &str) -> io::Result<()> {
+        self.to_rgba_image().save_png(path)
+    }
+
+    /// Average the accumulated samples and save them as a JPEG of the given
+    /// `quality`, from 1 to 100.
+    pub fn save_jpeg(&self, path: &str, quality: u8) -> io::Result<()> {
+        self.to_image().save_jpeg(path, quality)
+    }
+
+    /// Average the accumulated samples and save them as an OpenEXR image
+    /// without any conversion so that the full dynamic range is preserved.
+    pub fn save_exr(&self, path: &str) -> io::Result<()> {
+        let n = f64::from(self.samples.max(1));
+        let color = self.data.iter().map(|c| *c / n).collect::<Vec<_>>();
+
+        exr::save(path, self.width, self.height, &color)
+    }
+
+    /// Average the accumulated samples, run them through the given `Denoiser`
+    /// and convert the result to a gamma corrected image.
+    pub fn to_denoised_image(&self, denoiser: &dyn Denoiser, aovs: &Aovs) -> Image<3> {
+        assert_eq!((self.width, self.height), (aovs.width, aovs.height));
+
+        let n = f64::from(self.samples.max(1));
+        let color = self.data.iter().map(|c| *c / n).collect::<Vec<_>>();
+
+        let denoised = denoiser.denoise(&color, aovs);
+
+        let mut img = Image::rgb(self.width, self.height);
+        for (pix, c) in img.data_mut().chunks_mut(3).zip(denoised) {
+            pix.copy_from_slice(&to_rgb(c, self.srgb));
+        }
+
+        img
+    }
+}
+
+/// Arbitrary output variables, that is the auxiliary per pixel buffers that
+/// describe the surfaces directly visible from the camera.
+///
+/// They're noise free and therefore they're useful to guide a `Denoiser`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aovs {
+    pub width: u32,
+    pub height: u32,
+
+    /// Average normal of the first surface hit by the camera rays of each
+    /// pixel. It's zero where only the environment is visible.
+    pub normal: Vec<Vec3>,
+
+    /// Average albedo of the first surface hit by the camera rays of each
+    /// pixel. It's the color of the environment where no object is hit.
+    pub albedo: Vec<Vec3>,
+}
+
+/// Render the `Aovs` of a `Scene` from a `Camera` concurrently, averaging
+/// `samples` camera rays for each pixel.
+pub fn render_aovs(camera: &Camera, scene: &Scene, config: &RenderConfig, samples: u32) -> Aovs {
+    let mut aovs = Aovs {
+        width: config.width,
+        height: config.height,
+        normal: vec![Vec3::zero(); pixels(config.width, config.height)],
+        albedo: vec![Vec3::zero(); pixels(config.width, config.height)],
     };
 
+    let w = usize::try_from(config.width).unwrap();
+    let n = f64::from(samples.max(1));
+    let seed = config.seed();
+
+    aovs.normal
+        .par_chunks_mut(w)
+        .zip(aovs.albedo.par_chunks_mut(w))
+        .zip((0_u32..config.height).into_par_iter())
+        .for_each(|((normals, albedos), y)| {
+            let mut rng = XorShiftRng::seed_from_u64(seed ^ u64::from(y));
+
+            for ((normal, albedo), x) in normals.iter_mut().zip(albedos).zip(0..) {
+                if !config.in_crop(x, y) {
+                    continue;
+                }
+
+                for _ in 0..samples {
+                    let ray = camera.cast_ray((x, y), (config.width, config.height), &mut rng);
+
+                    match scene.intersection(&ray, RayKind::Camera) {
+                        None => *albedo += sample_environment(scene, &ray, false),
+                        Some((s, hit)) => {
+                            let n = hit.point_and_normal.map_or_else(
+                                || {
+                                    scene
+                                        .surface(hit.surface_id)
+                                        .normal_at(ray.point_at(hit.t()))
+                                },
+                                |(_, n)| n,
+                            );
+
+                            *normal += n.normalized();
+                            *albedo += s.material().albedo(ray.point_at(hit.t()), hit.uv);
+                        }
+                    }
+                }
+
+                *normal /= n;
+                *albedo /= n;
+            }
+        });
+
+    aovs
+}
+
+/// Render a "clay" image of a `Scene` from a `Camera` concurrently where the
+/// materials and the lights are ignored and each surface is only shaded by its
+/// [ambient occlusion][0].
+///
+/// The occlusion is estimated from `config.samples` rays per pixel, each one
+/// shooting a single ray in the hemisphere around the visible surface that
+/// counts as occluded if it hits another object closer than `radius`. It's a
+/// lot faster than a full render which makes it handy to preview the
+/// composition of a scene.
+///
+/// [0]: https://en.wikipedia.org/wiki/Ambient_occlusion
+pub fn render_ao(camera: &Camera, scene: &Scene, config: &RenderConfig, radius: f64) -> Image<3> {
     let mut img = Image::rgb(config.width, config.height);
+    let w = usize::try_from(config.width).unwrap();
+    let samples = config.samples.max(1);
+    let seed = config.seed();
 
     img.data_mut()
-        .par_chunks_mut(3 * usize::try_from(config.width).unwrap())
+        .par_chunks_mut(w * 3)
         .zip((0_u32..config.height).into_par_iter())
         .for_each(|(row, y)| {
-            let mut rng = XorShiftRng::seed_from_u64(thread_rng().gen());
+            let mut rng = XorShiftRng::seed_from_u64(seed ^ u64::from(y));
 
             for (pix, x) in row.chunks_mut(3).zip(0..) {
-                pix.copy_from_slice(&render_pixel(
-                    (x, y),
-                    camera,
-                    scene,
-                    &lights,
-                    &mut rng,
-                    config,
-                ));
+                if !config.in_crop(x, y) {
+                    continue;
+                }
+
+                let visible = (0..samples)
+                    .filter(|_| {
+                        let time = config.shutter_open
+                            + (config.shutter_close - config.shutter_open) * rng.gen::<f64>();
+                        let ray = camera
+                            .cast_ray((x, y), (config.width, config.height), &mut rng)
+                            .with_time(time);
+
+                        !is_occluded(scene, &ray, radius, config, &mut rng)
+                    })
+                    .count();
+
+                let c = f64::from(u32::try_from(visible).unwrap()) / f64::from(samples);
+                pix.copy_from_slice(&to_rgb(Vec3::new(c, c, c), config.srgb));
             }
         });
 
     img
 }
 
+/// Return whether a random ray in the hemisphere around the first surface hit
+/// by `ray` hits another object within `radius`. Rays that don't hit anything
+/// are never occluded.
+fn is_occluded(
+    scene: &Scene,
+    ray: &Ray,
+    radius: f64,
+    config: &RenderConfig,
+    rng: &mut impl Rng,
+) -> bool {
+    let Some((_, hit)) = scene.intersection(ray, RayKind::Camera) else {
+        return false;
+    };
+
+    let (intersection, mut n) = hit.point_and_normal.unwrap_or_else(|| {
+        let intersection = ray.point_at(hit.t());
+        (
+            intersection,
+            scene.surface(hit.surface_id).normal_at(intersection),
+        )
+    });
+
+    // shade both sides of the surfaces
+    if n.dot(ray.dir) > 0.0 {
+        n = -n;
+    }
+
+    let bounce = lambertian_bounce(intersection, n, rng);
+    let occluder = config.offset_ray(
+        ray,
+        n,
+        Ray::new(intersection, bounce.dir.normalized()).with_time(ray.time),
+    );
+
+    scene.intersects_before(&occluder, radius, RayKind::Shadow)
+}
+
+/// Render `samples` more samples for each pixel and add them to the given
+/// `Framebuffer` concurrently.
+///
+/// The random numbers only depend on `seed` and on the number of samples
+/// already in the `Framebuffer` so that a render can be resumed from a
+/// checkpoint as if it was never interrupted.
+pub fn render_pass(
+    camera: &Camera,
+    scene: &Scene,
+    config: &RenderConfig,
+    fb: &mut Framebuffer,
+    samples: u32,
+    seed: u64,
+) {
+    assert_eq!((fb.width, fb.height), (config.width, config.height));
+
+    let lights = light_tree(scene, config);
+
+    let pass = u64::from(fb.samples) << 32;
+    let w = usize::try_from(config.width).unwrap();
+
+    fb.data
+        .par_chunks_mut(w)
+        .zip(fb.alpha.par_chunks_mut(w))
+        .zip((0_u32..config.height).into_par_iter())
+        .for_each(|((row, alphas), y)| {
+            let mut rng = XorShiftRng::seed_from_u64(seed ^ (pass | u64::from(y)));
+
+            for ((pix, alpha), x) in row.iter_mut().zip(alphas).zip(0..) {
+                if !config.in_crop(x, y) {
+                    continue;
+                }
+
+                let (c, a) =
+                    sample_pixel((x, y), camera, scene, &lights, &mut rng, config, samples);
+                *pix += c;
+                *alpha += a;
+            }
+        });
+
+    fb.samples += samples;
+}
+
 /// Render a single pixel of an image from a `Scene` and `Camera`.
 pub fn render_pixel(
     (x, y): (u32, u32),
     camera: &Camera,
     scene: &Scene,
-    lights: &[&dyn Object],
+    lights: &LightTree,
     rng: &mut impl Rng,
     config: &RenderConfig,
 ) -> [u8; 3] {
-    let mut c = (0..config.samples)
+    let (c, _) = sample_pixel((x, y), camera, scene, lights, rng, config, config.samples);
+    to_rgb(c / f64::from(config.samples), config.srgb)
+}
+
+/// Return the `LightTree` over the lights of the `Scene` or an empty one if
+/// direct lighting is disabled.
+fn light_tree<'s>(scene: &'s Scene, config: &RenderConfig) -> LightTree<'s> {
+    if config.direct_lighting() {
+        LightTree::new(scene.lights())
+    } else {
+        LightTree::new(vec![])
+    }
+}
+
+/// Return the sum of `samples` samples of the pixel at the given coordinates
+/// along with the sum of their alpha.
+fn sample_pixel(
+    (x, y): (u32, u32),
+    camera: &Camera,
+    scene: &Scene,
+    lights: &LightTree,
+    rng: &mut impl Rng,
+    config: &RenderConfig,
+    samples: u32,
+) -> (Vec3, f64) {
+    (0..samples)
         .map(|_| {
-            let r = camera.cast_ray((x, y), (config.width, config.height), rng);
-            sample(scene, lights, &r, 0, rng, config)
+            let time = config.shutter_open
+                + (config.shutter_close - config.shutter_open) * rng.gen::<f64>();
+            let r = camera
+                .cast_ray((x, y), (config.width, config.height), rng)
+                .with_time(time);
+
+            match config.shading {
+                ShadingMode::PathTracing | ShadingMode::DirectOnly => {
+                    let differential = camera.ray_differential(&r, (config.width, config.height));
+                    sample_camera(scene, lights, &r, differential, rng, config)
+                }
+                mode => sample_debug(scene, &r, mode),
+            }
         })
-        .sum::<Vec3>()
-        / f64::from(config.samples);
+        .fold((Vec3::zero(), 0.0), |(c, a), (sc, sa)| (c + sc, a + sa))
+}
+
+/// Shade the first surface hit by `ray` according to one of the debug
+/// `ShadingMode`s along with its alpha. Rays that don't hit anything are black
+/// and transparent.
+fn sample_debug(scene: &Scene, ray: &Ray, mode: ShadingMode) -> (Vec3, f64) {
+    let Some((_, hit)) = scene.intersection(ray, RayKind::Camera) else {
+        return (Vec3::zero(), 0.0);
+    };
+
+    let n = match hit.point_and_normal {
+        Some((_, n)) => n,
+        None => scene
+            .surface(hit.surface_id)
+            .normal_at(ray.point_at(hit.t())),
+    }
+    .normalized();
+
+    let c = match mode {
+        ShadingMode::Normals => (n + Vec3::new(1.0, 1.0, 1.0)) * 0.5,
+        ShadingMode::Depth { far } => {
+            let c = 1.0 - (hit.t() * ray.dir.norm() / far).clamp(0.0, 1.0);
+            Vec3::new(c, c, c)
+        }
+        ShadingMode::Matcap => {
+            // a soft key light from the camera plus a rim on the silhouettes
+            // on top of a warm clay color
+            let facing = n.dot(-ray.dir.normalized()).abs();
+            let rim = (1.0 - facing).powi(3);
+
+            Vec3::new(0.8, 0.7, 0.6) * (0.2 + 0.8 * facing) + Vec3::new(rim, rim, rim) * 0.3
+        }
+        ShadingMode::PathTracing | ShadingMode::DirectOnly => {
+            unreachable!("not a debug shading mode")
+        }
+    };
+
+    (c, 1.0)
+}
+
+/// Gamma correct the given linear color and convert it to RGB, encoding it as
+/// sRGB if `srgb` is set.
+fn to_rgb(c: Vec3, srgb: bool) -> [u8; 3] {
+    if !srgb {
+        return [c.x, c.y, c.z].map(|c| (c.sqrt() * 255.0) as u8);
+    }
+
+    [c.x, c.y, c.z].map(|c| {
+        let c = c.clamp(0.0, 1.0);
+
+        // the sRGB transfer function is linear near black and roughly a 2.4
+        // gamma curve elsewhere
+        let c = if c <= 0.003_130_8 {
+            12.92 * c
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        };
+
+        (c * 255.0).round() as u8
+    })
+}
+
+fn pixels(width: u32, height: u32) -> usize {
+    usize::try_from(width).unwrap() * usize::try_from(height).unwrap()
+}
 
-    // gamma correct pixels
-    c.x = c.x.sqrt();
-    c.y = c.y.sqrt();
-    c.z = c.z.sqrt();
+/// The state of a path being traced.
+#[derive(Debug, Clone, Copy)]
+struct Path {
+    /// number of bounces done so far.
+    depth: u32,
+
+    /// the RGB channel the path carries once it went through a dispersive
+    /// material, in that case only that channel is sampled.
+    band: Option<usize>,
+
+    /// product of the attenuations of all the bounces done so far.
+    throughput: Vec3,
+
+    /// whether the sun was explicitly sampled at the last bounce, in which
+    /// case hitting it must not count its light twice.
+    sun_sampled: bool,
+
+    /// the differential of the ray the path goes along, it's only kept along
+    /// the mirror-like bounces, see `RayDifferential`.
+    differential: Option<RayDifferential>,
+}
 
-    [
-        (c.x * 255.0) as u8,
-        (c.y * 255.0) as u8,
-        (c.z * 255.0) as u8,
-    ]
+impl Path {
+    /// A new path starting from the camera.
+    fn camera() -> Self {
+        Self {
+            depth: 0,
+            band: None,
+            throughput: Vec3::new(1.0, 1.0, 1.0),
+            sun_sampled: false,
+            differential: None,
+        }
+    }
+
+    /// The path continuing after a bounce attenuated by `attenuation`.
+    fn bounce(self, attenuation: Vec3) -> Self {
+        Self {
+            depth: self.depth + 1,
+            band: self.band,
+            throughput: self.throughput * attenuation,
+            sun_sampled: false,
+            differential: None,
+        }
+    }
 }
 
+/// Sample the light coming along `ray` reaching the surface the given `Path`
+/// bounced off.
 fn sample(
     scene: &Scene,
-    lights: &[&dyn Object],
+    lights: &LightTree,
     ray: &Ray,
-    depth: u32,
+    path: Path,
     rng: &mut impl Rng,
     config: &RenderConfig,
 ) -> Vec3 {
-    match scene.intersection(ray) {
-        // doesn't intersect any object, just sample the environment
-        None => sample_environment(scene, ray),
+    // the rays of the paths that bounced at least once are bounce rays
+    let kind = if path.depth == 0 {
+        RayKind::Camera
+    } else {
+        RayKind::Bounce
+    };
 
-        // intersected the scene too many times, bail out
-        Some(_) if depth >= config.max_bounces => Vec3::zero(),
+    match scene.intersection(ray, kind) {
+        // doesn't intersect any object, just sample the environment
+        None => sample_environment(scene, ray, !path.sun_sampled),
 
         // hits an object, sample its material
-        Some((s, hit)) => {
-            let (intersection, n) = hit.point_and_normal.unwrap_or_else(|| {
-                let intersection = ray.point_at(hit.t());
-                let n = scene.surface(hit.surface_id).normal_at(intersection);
-
-                (intersection, n)
-            });
-
-            match *s.material() {
-                Material::Lambertian { albedo } => {
-                    let indirect = sample(
-                        scene,
-                        lights,
-                        &lambertian_bounce(intersection, n, rng),
-                        depth + 1,
-                        rng,
-                        config,
-                    );
-
-                    let direct = lights
-                        .iter()
-                        .map(|l| sample_light(scene, *l, ray, intersection, n, config, rng))
-                        .sum::<Vec3>();
-
-                    albedo * (direct + indirect)
-                }
-                Material::Metal { albedo, fuzziness } => {
-                    let r = metal_bounce(ray, intersection, n, fuzziness, rng);
+        Some(hit) => shade(scene, lights, ray, hit, path, rng, config).0,
+    }
+}
 
-                    if r.dir.dot(n) < 0.0 {
-                        return Vec3::zero();
-                    }
+/// Sample the light coming along a camera `ray` along with its alpha, that is
+/// how much the objects cover the `Environment` behind them, see
+/// `Framebuffer::alpha`.
+fn sample_camera(
+    scene: &Scene,
+    lights: &LightTree,
+    ray: &Ray,
+    differential: RayDifferential,
+    rng: &mut impl Rng,
+    config: &RenderConfig,
+) -> (Vec3, f64) {
+    let path = Path {
+        differential: Some(differential),
+        ..Path::camera()
+    };
 
-                    albedo * sample(scene, lights, &r, depth + 1, rng, config)
+    match scene.intersection(ray, RayKind::Camera) {
+        None if config.transparent_background => (Vec3::zero(), 0.0),
+        None => match config.fog {
+            Some(fog) => (fog.color, 0.0),
+            None => (sample_environment(scene, ray, true), 0.0),
+        },
+        Some(hit) => {
+            let distance = hit.1.t() * ray.dir.norm();
+            let (c, alpha) = shade(scene, lights, ray, hit, path, rng, config);
+
+            match config.fog {
+                None => (c, alpha),
+                Some(fog) => {
+                    let coverage = if config.transparent_background {
+                        alpha
+                    } else {
+                        1.0
+                    };
+                    (fog.apply(c, distance, coverage), alpha)
                 }
-                Material::Dielectric { refraction_index } => sample(
-                    scene,
-                    lights,
-                    &dielectric_bounce(ray, intersection, n, refraction_index, rng),
-                    depth + 1,
-                    rng,
-                    config,
-                ),
-                Material::Light { emittance } => emittance,
             }
         }
     }
 }
 
+/// Sample the light leaving the object hit by `ray` towards the origin of the
+/// ray according to its material, along with the alpha of the object.
+///
+/// The alpha is always 1 but for shadow catchers, see
+/// `Material::shadow_catcher`.
+fn shade(
+    scene: &Scene,
+    lights: &LightTree,
+    ray: &Ray,
+    (s, hit): (&dyn Object, Hit),
+    path: Path,
+    rng: &mut impl Rng,
+    config: &RenderConfig,
+) -> (Vec3, f64) {
+    // intersected the scene too many times, bail out
+    if path.depth >= config.max_bounces {
+        return (Vec3::zero(), 1.0);
+    }
+
+    let (intersection, n) = hit.point_and_normal.unwrap_or_else(|| {
+        let intersection = ray.point_at(hit.t());
+        let n = scene.surface(hit.surface_id).normal_at(intersection);
+
+        (intersection, n)
+    });
+
+    // the textures are filtered over the footprint of the pixel, if known
+    let footprint = path
+        .differential
+        .map_or(0.0, |d| d.footprint(intersection, n));
+
+    // the differential of `r`, either the reflection or the refraction of
+    // `ray` through a dielectric with the given refraction index
+    let dielectric_differential = |r: &Ray, refraction_index: f64| {
+        let d = path.differential?;
+        if r.dir.dot(n) * ray.dir.dot(n) < 0.0 {
+            d.reflected(intersection, n)
+        } else {
+            d.refracted(intersection, n, refraction_index)
+        }
+    };
+
+    // continue the path along `r` if it survives the Russian roulette
+    let trace = |r: &Ray, path: Path, rng: &mut _| match config.roulette(path.throughput, rng) {
+        None => Vec3::zero(),
+        Some(k) => sample(scene, lights, r, path, rng, config) * k,
+    };
+
+    // light a diffuse surface with normal `n` and color `albedo`
+    let diffuse = |albedo: Vec3, n: Vec3, rng: &mut _| {
+        let bounce = config.offset_ray(
+            ray,
+            n,
+            lambertian_bounce(intersection, n, rng).with_time(ray.time),
+        );
+
+        // the environment seen through a portal is already part of
+        // the direct lighting, don't count it twice
+        let through_portal = config.direct_lighting()
+            && scene
+                .portals()
+                .iter()
+                .any(|p| p.intersection(&bounce).is_some())
+            && !scene.intersects_before(&bounce, f64::INFINITY, RayKind::Bounce);
+
+        let indirect = if through_portal || config.shading == ShadingMode::DirectOnly {
+            Vec3::zero()
+        } else {
+            let path = Path {
+                sun_sampled: config.direct_lighting(),
+                ..path.bounce(albedo)
+            };
+            trace(&bounce, path, rng)
+        };
+
+        let (_, direct) = direct_light(scene, lights, ray, intersection, n, config, rng);
+
+        albedo * (direct + indirect)
+    };
+
+    let c = match *s.material() {
+        Material::Lambertian { albedo } => diffuse(albedo, n, rng),
+        Material::Metal { albedo, fuzziness } => {
+            let r = config.offset_ray(ray, n, metal_bounce(ray, intersection, n, fuzziness, rng));
+
+            if r.dir.dot(n) < 0.0 {
+                return (Vec3::zero(), 1.0);
+            }
+
+            let path = Path {
+                differential: path
+                    .differential
+                    .filter(|_| fuzziness == 0.0)
+                    .and_then(|d| d.reflected(intersection, n)),
+                ..path.bounce(albedo)
+            };
+            albedo * trace(&r, path, rng)
+        }
+        Material::Dielectric {
+            refraction_index,
+            dispersion,
+            ..
+        } => {
+            // a ray hitting the surface from the inside traveled all the way
+            // from where it entered the dielectric, absorbing the light along
+            // the way
+            let transmittance = if ray.dir.dot(n) > 0.0 {
+                s.material().transmittance(ray.origin.dist(intersection))
+            } else {
+                Vec3::new(1.0, 1.0, 1.0)
+            };
+
+            if dispersion == 0.0 {
+                let r = config.offset_ray(
+                    ray,
+                    n,
+                    dielectric_bounce(ray, intersection, n, refraction_index, rng),
+                );
+                let path = Path {
+                    differential: dielectric_differential(&r, refraction_index),
+                    ..path.bounce(transmittance)
+                };
+                return (transmittance * trace(&r, path, rng), 1.0);
+            }
+
+            // pick the wavelength of the path at the first dispersive
+            // material and stick to it, the chosen channel is then
+            // scaled to compensate for the ones that are not sampled
+            let (b, scale) = match path.band {
+                Some(b) => (b, 1.0),
+                None => (rng.gen_range(0..3), 3.0),
+            };
+
+            let refraction_index = dispersed_refraction_index(refraction_index, dispersion, b);
+            let r = config.offset_ray(
+                ray,
+                n,
+                dielectric_bounce(ray, intersection, n, refraction_index, rng),
+            );
+
+            let axis = [Axis::X, Axis::Y, Axis::Z][b];
+            let mut attenuation = Vec3::zero();
+            attenuation[axis] = scale * transmittance[axis];
+
+            let path = Path {
+                band: Some(b),
+                differential: dielectric_differential(&r, refraction_index),
+                ..path.bounce(attenuation)
+            };
+            let c = trace(&r, path, rng);
+
+            let mut out = Vec3::zero();
+            out[axis] = c[axis] * attenuation[axis];
+            out
+        }
+        Material::Pbr {
+            ref base_color,
+            ref metallic,
+            ref roughness,
+        } => {
+            let n = if ray.dir.dot(n) > 0.0 { -n } else { n };
+            let base_color = base_color.filtered(intersection, hit.uv, footprint);
+            let metallic = metallic
+                .filtered_scalar(intersection, hit.uv, footprint)
+                .clamp(0.0, 1.0);
+            let f0 = pbr_f0(base_color, metallic);
+
+            // pick either the specular or the diffuse lobe and
+            // compensate for the other, metals have no diffuse at all
+            let p_specular = 0.5 + 0.5 * metallic;
+            if rng.gen::<f64>() < p_specular {
+                let roughness = roughness.filtered_scalar(intersection, hit.uv, footprint);
+                let Some((r, weight)) = ggx_bounce(ray, intersection, n, f0, roughness, rng) else {
+                    return (Vec3::zero(), 1.0);
+                };
+
+                let r = config.offset_ray(ray, n, r);
+                let weight = weight / p_specular;
+                weight * trace(&r, path.bounce(weight), rng)
+            } else {
+                // the light reflected by the specular layer doesn't
+                // reach the diffuse base
+                let cos = n.dot(-ray.dir.normalized());
+                let transmitted = Vec3::new(1.0, 1.0, 1.0) - fresnel_schlick(f0, cos);
+                let albedo = base_color * transmitted * ((1.0 - metallic) / (1.0 - p_specular));
+
+                diffuse(albedo, n, rng)
+            }
+        }
+        Material::Light { .. } => s.material().emitted(ray.dir, n),
+        Material::ShadowCatcher => {
+            let shadow = catcher_shadow(scene, lights, ray, intersection, n, config, rng);
+            let behind = if path.depth == 0 && config.transparent_background {
+                Vec3::zero()
+            } else {
+                sample_environment(scene, ray, !path.sun_sampled)
+            };
+
+            return (behind * (1.0 - shadow), shadow);
+        }
+    };
+
+    (c, 1.0)
+}
+
+/// Estimate how much of the light reaching `intersection` on a shadow catcher
+/// with normal `n` is blocked by the other objects, from 0 when fully lit to 1
+/// when in full shadow.
+///
+/// Both the direct lighting and the light of the `Environment` coming from a
+/// random direction are sampled once, considering and ignoring the objects in
+/// between.
+fn catcher_shadow(
+    scene: &Scene,
+    lights: &LightTree,
+    ray: &Ray,
+    intersection: Vec3,
+    n: Vec3,
+    config: &RenderConfig,
+    rng: &mut impl Rng,
+) -> f64 {
+    let n = if ray.dir.dot(n) > 0.0 { -n } else { n };
+
+    let (mut unoccluded, mut lit) = direct_light(scene, lights, ray, intersection, n, config, rng);
+
+    let bounce = config.offset_ray(
+        ray,
+        n,
+        lambertian_bounce(intersection, n, rng).with_time(ray.time),
+    );
+    let env = sample_environment(scene, &bounce, !config.direct_lighting());
+    unoccluded += env;
+    if !scene.intersects_before(&bounce, f64::INFINITY, RayKind::Shadow) {
+        lit += env;
+    }
+
+    let (unoccluded, lit) = (
+        unoccluded.x + unoccluded.y + unoccluded.z,
+        lit.x + lit.y + lit.z,
+    );
+    if unoccluded <= 0.0 {
+        return 0.0;
+    }
+
+    (1.0 - lit / unoccluded).clamp(0.0, 1.0)
+}
+
+/// Sample the direct lighting reaching `intersection` on a diffuse surface
+/// with normal `n`, that is the light coming from a single light, picked
+/// according to how much it likely contributes, from the portals and from
+/// the sun.
+///
+/// Return the light ignoring the objects in between followed by the light
+/// that actually reaches the surface.
+fn direct_light(
+    scene: &Scene,
+    lights: &LightTree,
+    ray: &Ray,
+    intersection: Vec3,
+    n: Vec3,
+    config: &RenderConfig,
+    rng: &mut impl Rng,
+) -> (Vec3, Vec3) {
+    let (mut unoccluded, mut lit) = (Vec3::zero(), Vec3::zero());
+    let mut add = |(c, occluded): (Vec3, bool)| {
+        unoccluded += c;
+        if !occluded {
+            lit += c;
+        }
+    };
+
+    // compensate for the lights that are not sampled
+    if let Some((l, pdf)) = lights.sample(intersection, rng) {
+        let (c, occluded) = sample_light(scene, l, ray, intersection, n, config, rng);
+        add((c / pdf, occluded));
+    }
+
+    if config.direct_lighting() {
+        for p in scene.portals() {
+            add(sample_portal(scene, p, ray, intersection, n, config, rng));
+        }
+
+        add(sample_sun(scene, ray, intersection, n, config, rng));
+    }
+
+    (unoccluded, lit)
+}
+
+/// Sample the given light reaching `intersection` on a diffuse surface with
+/// normal `n`.
+///
+/// Return the light as if nothing was in between and whether it's actually
+/// occluded by other objects, the same goes for `sample_portal` and
+/// `sample_sun`.
 fn sample_light(
     scene: &Scene,
     light: &dyn Object,
@@ -198,7 +1167,7 @@ fn sample_light(
     n: Vec3,
     config: &RenderConfig,
     rng: &mut impl Rng,
-) -> Vec3 {
+) -> (Vec3, bool) {
     let (mut light_pos, light_radius) = light.bounding_sphere();
 
     if config.soft_shadows {
@@ -208,40 +1177,126 @@ fn sample_light(
 
             if x.powi(2) + y.powi(2) <= 1.0 {
                 let l = (light_pos - ray.origin).normalized();
-                let u = l.cross(Vec3::random_unit(rng)).normalized();
-                let v = l.cross(u);
+                let (u, v) = orthonormal_basis(l);
 
                 break light_pos + (u * (x * light_radius)) + (v * (y * light_radius));
             }
         };
     }
 
-    let light_ray = Ray::new(intersection, (light_pos - intersection).normalized());
+    let light_ray = config.offset_ray(
+        ray,
+        n,
+        Ray::new(intersection, (light_pos - intersection).normalized()).with_time(ray.time),
+    );
 
     // if `light_ray` goes in the opposite direction wrt `n` then it doesn't
     // reach the light for sure
     let diffuse = light_ray.dir.dot(n);
     if diffuse <= 0.0 {
-        return Vec3::zero();
+        return (Vec3::zero(), false);
     }
 
-    // check if `intersection` is in the shadow of another object or reaches
-    // a light
-    if let Some((o, _t)) = scene.intersection(&light_ray) {
-        if let Material::Light { emittance } = o.material() {
-            return *emittance * diffuse;
+    // when the ray reaches the sampled light it's enough to check that nothing
+    // lies in between, otherwise the sampled point might still be on another
+    // light, e.g. the other facet of a quad, and the closest hit is needed
+    let (light, hit, occluded) = match light.intersection(&light_ray) {
+        Some(hit) => {
+            let occluded =
+                scene.intersects_before(&light_ray, hit.t() * (1.0 - 1e-9), RayKind::Shadow);
+            (light, hit, occluded)
         }
+        None => match scene.intersection(&light_ray, RayKind::Shadow) {
+            Some((o, hit)) if matches!(o.material(), Material::Light { .. }) => (o, hit, false),
+            _ => return (Vec3::zero(), false),
+        },
+    };
+
+    let (_, light_n) = hit.point_and_normal.unwrap_or_else(|| {
+        let p = light_ray.point_at(hit.t());
+        (p, light.normal_at(p))
+    });
+
+    (
+        light.material().emitted(light_ray.dir, light_n) * diffuse,
+        occluded,
+    )
+}
+
+/// Sample the `Environment` light reaching `intersection` through the given
+/// `Portal` by treating it as an area light.
+fn sample_portal(
+    scene: &Scene,
+    portal: &Portal,
+    ray: &Ray,
+    intersection: Vec3,
+    n: Vec3,
+    config: &RenderConfig,
+    rng: &mut impl Rng,
+) -> (Vec3, bool) {
+    let d = portal.sample_point(rng) - intersection;
+    let dist2 = d.norm2();
+    let dir = d / dist2.sqrt();
+
+    // discard the portals behind the surface or seen edge-on before tracing
+    // any shadow ray
+    let diffuse = dir.dot(n);
+    let cos_portal = dir.dot(portal.normal()).abs();
+    if diffuse <= 0.0 || cos_portal <= 0.0 {
+        return (Vec3::zero(), false);
     }
 
-    Vec3::zero()
+    let light_ray = config.offset_ray(ray, n, Ray::new(intersection, dir).with_time(ray.time));
+    let occluded = scene.intersects_before(&light_ray, f64::INFINITY, RayKind::Shadow);
+
+    // convert the uniform sampling of the portal area to solid angle, the 1/PI
+    // is the normalization of the lambertian BRDF
+    let c = sample_environment(scene, &light_ray, false)
+        * (diffuse * cos_portal * portal.area() / (PI * dist2));
+
+    (c, occluded)
 }
 
-fn sample_environment(scene: &Scene, ray: &Ray) -> Vec3 {
+/// Sample the `Environment` along the given ray, including the sun of a `Sky`
+/// only if `sun` is true.
+fn sample_environment(scene: &Scene, ray: &Ray, sun: bool) -> Vec3 {
     match scene.environment {
         Environment::Color(c) => c,
         Environment::LinearGradient(a, b) => {
             let t = 0.5 * (ray.dir.y / ray.dir.norm() + 1.0);
             Vec3::lerp(a, b, t)
         }
+        Environment::Sky(ref sky) if sun => sky.radiance(ray.dir),
+        Environment::Sky(ref sky) => sky.sky_radiance(ray.dir),
+    }
+}
+
+/// Sample the sun of the `Sky` of the `Scene`, if any, reaching the
+/// `intersection` on a diffuse surface with normal `n`.
+fn sample_sun(
+    scene: &Scene,
+    ray: &Ray,
+    intersection: Vec3,
+    n: Vec3,
+    config: &RenderConfig,
+    rng: &mut impl Rng,
+) -> (Vec3, bool) {
+    let Environment::Sky(ref sky) = scene.environment else {
+        return (Vec3::zero(), false);
+    };
+    let Some((dir, solid_angle, radiance)) = sky.sample_sun(rng) else {
+        return (Vec3::zero(), false);
+    };
+
+    let cos = dir.dot(n.normalized());
+    if cos <= 0.0 {
+        return (Vec3::zero(), false);
     }
+
+    let sun_ray = config.offset_ray(ray, n, Ray::new(intersection, dir).with_time(ray.time));
+    let occluded = scene.intersects_before(&sun_ray, f64::INFINITY, RayKind::Shadow);
+
+    // the sun is sampled uniformly over its solid angle, the 1/PI is the
+    // normalization of the lambertian BRDF
+    (radiance * (cos * solid_angle / PI), occluded)
 }
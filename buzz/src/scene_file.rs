@@ -0,0 +1,821 @@
+//! Load a `Scene` along with its `Camera` and `RenderConfig` from a JSON or
+//! TOML scene description file.
+//!
+//! A minimal scene in TOML looks like the following.
+//!
+//! ```toml
+//! [camera]
+//! position = [0.0, -4.0, 1.0]
+//! target = [0.0, 0.0, 0.0]
+//! fovy = 35.0
+//!
+//! [render]
+//! width = 800
+//! height = 600
+//!
+//! [materials]
+//! red = { type = "lambertian", albedo = [0.8, 0.1, 0.1] }
+//!
+//! [[objects]]
+//! shape = "sphere"
+//! center = [0.0, 0.0, 0.0]
+//! radius = 1.0
+//! material = "red"
+//!
+//! [[objects]]
+//! shape = "mesh"
+//! path = "suzanne.stl"
+//! material = { type = "metal", albedo = [0.9, 0.9, 0.9], fuzziness = 0.1 }
+//! transform = [{ scale = [0.5, 0.5, 0.5] }, { translate = [2.0, 0.0, 0.0] }]
+//! ```
+//!
+//! Objects can either reference one of the named `materials` or define their
+//! own inline. Paths to mesh files are relative to the scene file.
+//!
+//! Physically based materials take their properties either as constants or as
+//! textures, e.g. `{ type = "pbr", base_color = { even = [1.0, 1.0, 1.0], odd
+//! = [0.1, 0.1, 0.1], size = 0.5 }, metallic = 0.0, roughness = 0.3 }`. A
+//! checkerboard over the texture coordinates of the surface uses `cells`
+//! instead of `size`.
+//!
+//! Colored glass absorbs the light going through it with e.g. `{ type =
+//! "dielectric", refraction_index = 1.5, absorption = { color = [0.2, 0.6,
+//! 0.9], distance = 1.0 } }`, see `Material::with_absorption`.
+//!
+//! A ground that only shows the shadows cast on it, to composite the render
+//! over a photo, uses `{ type = "shadow_catcher" }`, see
+//! `Material::shadow_catcher`.
+//!
+//! The surfaces can be shaded by one of the debug `ShadingMode`s instead of
+//! being path traced with e.g. `shading = { type = "normals" }` or
+//! `shading = { type = "depth", far = 10.0 }` in the `render` table.
+//!
+//! Only a rectangle of the image is rendered with e.g. `crop = [100, 50, 64,
+//! 64]` in the `render` table, that is its top left corner followed by its
+//! width and height.
+//!
+//! The environment seen directly by the camera is left out of the image with
+//! `transparent_background = true` in the `render` table so that the render
+//! can be saved with transparency, see `Framebuffer::to_rgba_image`.
+//!
+//! Large scenes fade into the distance with e.g. `fog = { color = [0.7, 0.75,
+//! 0.8], density = 0.05 }` in the `render` table, see `Fog`.
+//!
+//! The image is encoded as sRGB with `srgb = true` in the `render` table, see
+//! `RenderConfig::srgb`.
+//!
+//! Outdoor scenes can be lit by a physical sky with e.g. `environment = {
+//! type = "sky", sun_direction = [1.0, 0.5, 0.8], turbidity = 3.0 }`, see the
+//! `sky` module.
+//!
+//! Objects can be hidden from some kinds of rays, e.g. a light that doesn't
+//! show up in the image but still lights the scene has `visibility = { camera
+//! = false }`, see `Visibility`.
+//!
+//! Interior scenes lit by the environment through small openings can list
+//! them as `portals = [{ corner = [..], u = [..], v = [..] }]`, see the
+//! `portal` module.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::Deserialize;
+use sketch_utils::loader;
+
+pub use sketch_utils::loader::Format;
+
+use geo::{mat4::Mat4, mesh::load_mesh, Aabb};
+
+use crate::{
+    Camera, CubeGeometry, CylinderGeometry, Environment, Fog, Hit, Material, MeshInstance,
+    PlaneGeometry, Portal, RenderConfig, Scene, SceneObjects, ShadingMode, Shape, SimpleObject,
+    Sky, SphereGeometry, Surface, Texture, TransformedGeometry, TriangleMesh, Visibility,
+    DEFAULT_RAY_EPSILON,
+};
+
+/// Result type returned by the scene file loaders.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Possible errors while loading a scene file.
+#[derive(Debug)]
+pub enum Error {
+    /// The scene file could not be read or it doesn't describe a scene.
+    Load(loader::Error),
+
+    /// An object references a material that is not defined.
+    UnknownMaterial(String),
+
+    /// A mesh referenced by the scene could not be loaded.
+    Mesh(PathBuf, geo::mesh::Error),
+}
+
+/// A fully loaded scene file, ready to be rendered.
+#[derive(Debug)]
+pub struct SceneFile {
+    pub camera: Camera,
+    pub scene: Scene,
+    pub config: RenderConfig,
+
+    /// the mesh files the scene is made of, in the order they're first
+    /// referenced. Along with the scene file itself they tell whether a
+    /// render of the scene is still up to date.
+    pub meshes: Vec<PathBuf>,
+}
+
+type V3 = [f64; 3];
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneDesc {
+    camera: CameraDesc,
+
+    #[serde(default)]
+    render: RenderDesc,
+
+    #[serde(default)]
+    environment: EnvironmentDesc,
+
+    #[serde(default)]
+    portals: Vec<PortalDesc>,
+
+    #[serde(default)]
+    materials: HashMap<String, MaterialDesc>,
+
+    #[serde(default)]
+    objects: Vec<ObjectDesc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CameraDesc {
+    position: V3,
+    target: V3,
+    #[serde(default = "default_up")]
+    up: V3,
+    fovy: f64,
+    focus: Option<FocusDesc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FocusDesc {
+    point: V3,
+    aperture: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RenderDesc {
+    width: u32,
+    height: u32,
+    samples: u32,
+    max_bounces: u32,
+    throughput_threshold: f64,
+    direct_lighting: bool,
+    soft_shadows: bool,
+    seed: Option<u64>,
+    ray_epsilon: f64,
+    shading: ShadingDesc,
+
+    /// x, y, width and height of the rectangle of pixels to render
+    crop: Option<[u32; 4]>,
+    transparent_background: bool,
+    fog: Option<FogDesc>,
+    srgb: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FogDesc {
+    color: V3,
+    density: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum ShadingDesc {
+    PathTracing,
+    Normals,
+    Depth { far: f64 },
+    DirectOnly,
+    Matcap,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum EnvironmentDesc {
+    Color {
+        color: V3,
+    },
+    LinearGradient {
+        from: V3,
+        to: V3,
+    },
+    Sky {
+        sun_direction: V3,
+        #[serde(default = "default_turbidity")]
+        turbidity: f64,
+        intensity: Option<f64>,
+        sun_irradiance: Option<f64>,
+        sun_radius: Option<f64>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PortalDesc {
+    corner: V3,
+    u: V3,
+    v: V3,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum MaterialDesc {
+    Lambertian {
+        albedo: V3,
+    },
+    Metal {
+        albedo: V3,
+        fuzziness: f64,
+    },
+    Dielectric {
+        refraction_index: f64,
+        #[serde(default)]
+        dispersion: f64,
+        absorption: Option<AbsorptionDesc>,
+    },
+    Pbr {
+        base_color: TextureDesc,
+        #[serde(default = "default_metallic")]
+        metallic: TextureDesc,
+        #[serde(default = "default_roughness")]
+        roughness: TextureDesc,
+    },
+    Light {
+        emittance: V3,
+        #[serde(default)]
+        falloff: f64,
+        #[serde(default)]
+        one_sided: bool,
+    },
+    ShadowCatcher,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AbsorptionDesc {
+    color: V3,
+    #[serde(default = "default_absorption_distance")]
+    distance: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum TextureDesc {
+    Scalar(f64),
+    Color(V3),
+    Checker { even: V3, odd: V3, size: f64 },
+    UvChecker { even: V3, odd: V3, cells: f64 },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum MaterialRef {
+    Named(String),
+    Inline(MaterialDesc),
+}
+
+#[derive(Debug, Deserialize)]
+struct ObjectDesc {
+    #[serde(flatten)]
+    shape: ShapeDesc,
+
+    material: MaterialRef,
+
+    /// transformations applied in order
+    #[serde(default)]
+    transform: Vec<TransformDesc>,
+
+    #[serde(default)]
+    visibility: VisibilityDesc,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct VisibilityDesc {
+    camera: bool,
+    shadow: bool,
+    bounce: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+enum ShapeDesc {
+    Sphere {
+        center: V3,
+        radius: f64,
+    },
+    Cube {
+        min: V3,
+        max: V3,
+    },
+    Cylinder {
+        radius: f64,
+        zmin: f64,
+        zmax: f64,
+    },
+    Plane {
+        origin: V3,
+        normal: V3,
+    },
+    Mesh {
+        path: PathBuf,
+        #[serde(default)]
+        flat_shading: bool,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum TransformDesc {
+    Translate(V3),
+    Scale(V3),
+
+    /// rotation around the given axis by the given angle in degrees
+    Rotate {
+        axis: V3,
+        angle: f64,
+    },
+}
+
+/// Load the scene file at `path` guessing its format by the file extension.
+///
+/// Relative mesh paths are resolved against the directory containing the
+/// scene file.
+pub fn load(path: impl AsRef<Path>) -> Result<SceneFile> {
+    let path = path.as_ref();
+    let desc: SceneDesc = loader::load(path)?;
+    desc.build(loader::base_dir(path))
+}
+
+/// Parse a scene from the given document written in the given `Format`.
+/// Relative mesh paths are resolved against `base_dir`.
+pub fn from_str(data: &str, format: Format, base_dir: impl AsRef<Path>) -> Result<SceneFile> {
+    let desc: SceneDesc = loader::from_str(data, format)?;
+    desc.build(base_dir.as_ref())
+}
+
+impl SceneDesc {
+    fn build(self, base_dir: &Path) -> Result<SceneFile> {
+        let mut camera = Camera::look_at(
+            self.camera.position.into(),
+            self.camera.target.into(),
+            self.camera.up.into(),
+            self.camera.fovy,
+        );
+        if let Some(focus) = self.camera.focus {
+            camera = camera.with_focus(focus.point.into(), focus.aperture);
+        }
+
+        // the same mesh file is loaded and indexed only once and then shared
+        // among all the objects that reference it
+        let mut meshes: HashMap<(PathBuf, bool), Arc<TriangleMesh>> = HashMap::new();
+        let mut mesh_paths = vec![];
+
+        let mut objects = SceneObjects::new();
+        for obj in self.objects {
+            let material = match obj.material {
+                MaterialRef::Inline(m) => m,
+                MaterialRef::Named(name) => self
+                    .materials
+                    .get(&name)
+                    .cloned()
+                    .ok_or(Error::UnknownMaterial(name))?,
+            }
+            .into();
+
+            let transform = obj.transform.iter().fold(Mat4::identity(), |m, t| {
+                let t = match *t {
+                    TransformDesc::Translate(v) => Mat4::translate(v.into()),
+                    TransformDesc::Scale(v) => Mat4::scale(v.into()),
+                    TransformDesc::Rotate { axis, angle } => {
+                        Mat4::rotate(axis.into(), angle.to_radians())
+                    }
+                };
+                t * &m
+            });
+            let transform = (!obj.transform.is_empty()).then_some(transform);
+            let visibility = Visibility {
+                camera: obj.visibility.camera,
+                shadow: obj.visibility.shadow,
+                bounce: obj.visibility.bounce,
+            };
+
+            match obj.shape {
+                ShapeDesc::Sphere { center, radius } => push(
+                    &mut objects,
+                    SphereGeometry::new(center.into(), radius),
+                    material,
+                    transform,
+                    visibility,
+                ),
+                ShapeDesc::Cube { min, max } => push(
+                    &mut objects,
+                    CubeGeometry::new(Aabb::new(min.into()).expanded(max.into())),
+                    material,
+                    transform,
+                    visibility,
+                ),
+                ShapeDesc::Cylinder { radius, zmin, zmax } => push(
+                    &mut objects,
+                    CylinderGeometry::new(radius, (zmin, zmax)),
+                    material,
+                    transform,
+                    visibility,
+                ),
+                ShapeDesc::Plane { origin, normal } => push(
+                    &mut objects,
+                    PlaneGeometry::new(origin.into(), normal.into()),
+                    material,
+                    transform,
+                    visibility,
+                ),
+                ShapeDesc::Mesh { path, flat_shading } => {
+                    let path = base_dir.join(path);
+                    let mesh = match meshes.get(&(path.clone(), flat_shading)) {
+                        Some(mesh) => Arc::clone(mesh),
+                        None => {
+                            let mesh =
+                                load_mesh(&path).map_err(|e| Error::Mesh(path.clone(), e))?;
+                            let mesh = Arc::new(TriangleMesh::new(mesh.triangles(), flat_shading));
+                            if !mesh_paths.contains(&path) {
+                                mesh_paths.push(path.clone());
+                            }
+                            meshes.insert((path, flat_shading), Arc::clone(&mesh));
+                            mesh
+                        }
+                    };
+
+                    objects.push(
+                        SimpleObject::new(
+                            MeshInstance::new(mesh, transform.unwrap_or_else(Mat4::identity)),
+                            material,
+                        )
+                        .with_visibility(visibility),
+                    );
+                }
+            }
+        }
+
+        let environment = match self.environment {
+            EnvironmentDesc::Color { color } => Environment::Color(color.into()),
+            EnvironmentDesc::LinearGradient { from, to } => {
+                Environment::LinearGradient(from.into(), to.into())
+            }
+            EnvironmentDesc::Sky {
+                sun_direction,
+                turbidity,
+                intensity,
+                sun_irradiance,
+                sun_radius,
+            } => {
+                let mut sky = Sky::new(sun_direction.into(), turbidity);
+                if let Some(intensity) = intensity {
+                    sky = sky.with_intensity(intensity);
+                }
+                if let Some(irradiance) = sun_irradiance {
+                    sky = sky.with_sun_irradiance(irradiance);
+                }
+                if let Some(radius) = sun_radius {
+                    sky = sky.with_sun_radius(radius);
+                }
+                Environment::Sky(sky)
+            }
+        };
+
+        let r = self.render;
+        Ok(SceneFile {
+            camera,
+            scene: Scene::new(objects, environment).with_portals(
+                self.portals
+                    .into_iter()
+                    .map(|p| Portal::new(p.corner.into(), p.u.into(), p.v.into())),
+            ),
+            config: RenderConfig {
+                width: r.width,
+                height: r.height,
+                samples: r.samples,
+                max_bounces: r.max_bounces,
+                throughput_threshold: r.throughput_threshold,
+                direct_lighting: r.direct_lighting,
+                soft_shadows: r.soft_shadows,
+                shutter_open: 0.0,
+                shutter_close: 0.0,
+                seed: r.seed,
+                ray_epsilon: r.ray_epsilon,
+                shading: match r.shading {
+                    ShadingDesc::PathTracing => ShadingMode::PathTracing,
+                    ShadingDesc::Normals => ShadingMode::Normals,
+                    ShadingDesc::Depth { far } => ShadingMode::Depth { far },
+                    ShadingDesc::DirectOnly => ShadingMode::DirectOnly,
+                    ShadingDesc::Matcap => ShadingMode::Matcap,
+                },
+                crop: r.crop.map(|[x, y, w, h]| (x, y, w, h)),
+                transparent_background: r.transparent_background,
+                fog: r.fog.map(|f| Fog {
+                    color: f.color.into(),
+                    density: f.density,
+                }),
+                srgb: r.srgb,
+            },
+            meshes: mesh_paths,
+        })
+    }
+}
+
+fn push<G>(
+    objects: &mut SceneObjects,
+    geom: G,
+    material: Material,
+    transform: Option<Mat4>,
+    visibility: Visibility,
+) where
+    G: Shape<Intersection = Hit> + Surface + Send + Sync + 'static,
+{
+    match transform {
+        None => objects.push(SimpleObject::new(geom, material).with_visibility(visibility)),
+        Some(m) => objects.push(
+            SimpleObject::new(TransformedGeometry::new(geom, m), material)
+                .with_visibility(visibility),
+        ),
+    }
+}
+
+fn default_up() -> V3 {
+    [0.0, 0.0, 1.0]
+}
+
+fn default_turbidity() -> f64 {
+    3.0
+}
+
+fn default_absorption_distance() -> f64 {
+    1.0
+}
+
+fn default_metallic() -> TextureDesc {
+    TextureDesc::Scalar(0.0)
+}
+
+fn default_roughness() -> TextureDesc {
+    TextureDesc::Scalar(0.5)
+}
+
+impl Default for RenderDesc {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            samples: 10,
+            max_bounces: 5,
+            throughput_threshold: 0.0,
+            direct_lighting: true,
+            soft_shadows: true,
+            seed: None,
+            ray_epsilon: DEFAULT_RAY_EPSILON,
+            shading: ShadingDesc::PathTracing,
+            crop: None,
+            transparent_background: false,
+            fog: None,
+            srgb: false,
+        }
+    }
+}
+
+impl Default for VisibilityDesc {
+    fn default() -> Self {
+        Self {
+            camera: true,
+            shadow: true,
+            bounce: true,
+        }
+    }
+}
+
+impl Default for EnvironmentDesc {
+    fn default() -> Self {
+        EnvironmentDesc::Color { color: [0.0; 3] }
+    }
+}
+
+impl From<MaterialDesc> for Material {
+    fn from(m: MaterialDesc) -> Self {
+        match m {
+            MaterialDesc::Lambertian { albedo } => Material::lambertian(albedo.into()),
+            MaterialDesc::Metal { albedo, fuzziness } => Material::metal(albedo.into(), fuzziness),
+            MaterialDesc::Dielectric {
+                refraction_index,
+                dispersion,
+                absorption,
+            } => {
+                let m = Material::dispersive_dielectric(refraction_index, dispersion);
+                match absorption {
+                    Some(a) => m.with_absorption(a.color.into(), a.distance),
+                    None => m,
+                }
+            }
+            MaterialDesc::Pbr {
+                base_color,
+                metallic,
+                roughness,
+            } => Material::pbr(base_color, metallic, roughness),
+            MaterialDesc::Light {
+                emittance,
+                falloff,
+                one_sided,
+            } => Material::spot_light(emittance.into(), falloff, one_sided),
+            MaterialDesc::ShadowCatcher => Material::shadow_catcher(),
+        }
+    }
+}
+
+impl From<TextureDesc> for Texture {
+    fn from(t: TextureDesc) -> Self {
+        match t {
+            TextureDesc::Scalar(v) => Texture::from(v),
+            TextureDesc::Color(c) => Texture::constant(c.into()),
+            TextureDesc::Checker { even, odd, size } => {
+                Texture::checker(even.into(), odd.into(), size)
+            }
+            TextureDesc::UvChecker { even, odd, cells } => {
+                Texture::uv_checker(even.into(), odd.into(), cells)
+            }
+        }
+    }
+}
+
+impl From<loader::Error> for Error {
+    fn from(e: loader::Error) -> Self {
+        Error::Load(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Load(e) => write!(f, "{e}"),
+            Error::UnknownMaterial(name) => write!(f, "unknown material {name}"),
+            Error::Mesh(path, e) => write!(f, "cannot load mesh {}: {e}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Load(e) => Some(e),
+            Error::Mesh(_, e) => Some(e),
+            Error::UnknownMaterial(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    const TOML: &str = r#"
+[camera]
+position = [0.0, -4.0, 1.0]
+target = [0.0, 0.0, 0.0]
+fovy = 35.0
+
+[render]
+width = 80
+height = 60
+samples = 4
+fog = { color = [0.7, 0.75, 0.8], density = 0.05 }
+
+[materials]
+red = { type = "lambertian", albedo = [0.8, 0.1, 0.1] }
+
+[[objects]]
+shape = "sphere"
+center = [0.0, 0.0, 0.0]
+radius = 1.0
+material = "red"
+
+[[objects]]
+shape = "cube"
+min = [-1.0, -1.0, -1.0]
+max = [1.0, 1.0, 1.0]
+material = { type = "metal", albedo = [0.9, 0.9, 0.9], fuzziness = 0.1 }
+transform = [{ scale = [0.5, 0.5, 0.5] }, { translate = [2.0, 0.0, 0.0] }]
+"#;
+
+    const JSON: &str = r#"{
+  "camera": { "position": [0, -4, 1], "target": [0, 0, 0], "fovy": 35 },
+  "render": { "width": 80, "height": 60, "samples": 4, "fog": { "color": [0.7, 0.75, 0.8], "density": 0.05 } },
+  "materials": { "red": { "type": "lambertian", "albedo": [0.8, 0.1, 0.1] } },
+  "objects": [
+    { "shape": "sphere", "center": [0, 0, 0], "radius": 1, "material": "red" },
+    {
+      "shape": "cube", "min": [-1, -1, -1], "max": [1, 1, 1],
+      "material": { "type": "metal", "albedo": [0.9, 0.9, 0.9], "fuzziness": 0.1 },
+      "transform": [{ "scale": [0.5, 0.5, 0.5] }, { "translate": [2, 0, 0] }]
+    }
+  ]
+}"#;
+
+    #[test]
+    fn test_parse() {
+        for (data, format) in [(TOML, Format::Toml), (JSON, Format::Json)] {
+            let file = from_str(data, format, "").unwrap();
+
+            assert_eq!((file.config.width, file.config.height), (80, 60));
+            assert_eq!(file.config.samples, 4);
+            assert_eq!(file.config.max_bounces, 5);
+            assert_eq!(file.config.fog.map(|f| f.density), Some(0.05));
+            assert_eq!(file.scene.objects.iter().count(), 2);
+            assert!(file.meshes.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_meshes() {
+        let dir = std::env::temp_dir().join(format!("buzz-scene-file-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+        fs::write(dir.join("a.obj"), obj).unwrap();
+        fs::write(dir.join("b.obj"), obj).unwrap();
+
+        let scene = TOML.replace(
+            "shape = \"sphere\"\ncenter = [0.0, 0.0, 0.0]\nradius = 1.0",
+            "shape = \"mesh\"\npath = \"b.obj\"",
+        ) + r#"
+[[objects]]
+shape = "mesh"
+path = "a.obj"
+material = "red"
+
+[[objects]]
+shape = "mesh"
+path = "b.obj"
+flat_shading = true
+material = "red"
+"#;
+        let file = from_str(&scene, Format::Toml, &dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let file = file.unwrap();
+        assert_eq!(file.scene.objects.iter().count(), 4);
+        assert_eq!(file.meshes, vec![dir.join("b.obj"), dir.join("a.obj")]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let unknown_material = TOML.replace("material = \"red\"", "material = \"blue\"");
+        assert!(matches!(
+            from_str(&unknown_material, Format::Toml, ""),
+            Err(Error::UnknownMaterial(name)) if name == "blue"
+        ));
+
+        let missing_mesh = TOML.replace(
+            "shape = \"sphere\"\ncenter = [0.0, 0.0, 0.0]\nradius = 1.0",
+            "shape = \"mesh\"\npath = \"missing.stl\"",
+        );
+        let err = from_str(&missing_mesh, Format::Toml, "meshes").unwrap_err();
+        assert!(matches!(
+            &err,
+            Error::Mesh(path, geo::mesh::Error::IoError(_)) if path == &Path::new("meshes").join("missing.stl")
+        ));
+        assert!(err.to_string().starts_with("cannot load mesh meshes"));
+        assert!(!err.to_string().contains("IoError"));
+        assert!(std::error::Error::source(&err).is_some());
+
+        let unknown_field = TOML.replace("samples = 4", "samplez = 4");
+        let err = from_str(&unknown_field, Format::Toml, "").unwrap_err();
+        assert!(matches!(err, Error::Load(loader::Error::Toml(_))));
+        assert!(std::error::Error::source(&err).is_some());
+
+        assert!(matches!(
+            from_str(&JSON[1..], Format::Json, ""),
+            Err(Error::Load(loader::Error::Json(_)))
+        ));
+        assert!(matches!(
+            from_str(JSON, Format::Toml, ""),
+            Err(Error::Load(loader::Error::Toml(_)))
+        ));
+        assert!(matches!(
+            load("scene.yaml"),
+            Err(Error::Load(loader::Error::UnknownFormat(_)))
+        ));
+    }
+}
@@ -0,0 +1,181 @@
+//! Standard scenes to compare renders against known results.
+//!
+//! These scenes are well known in the rendering literature and they're mostly
+//! useful to check that changes to the sampling code don't introduce bias and
+//! to benchmark the renderer on comparable workloads. Each generator returns
+//! the `Scene` along with a `Camera` that frames it.
+
+use geo::{v3, Triangle, Vec3};
+
+use crate::{
+    Camera, Environment, FacetGeometry, Material, Scene, SceneObjects, SimpleObject, SphereGeometry,
+};
+
+/// The [Cornell box][0]: a white room with a red wall on the left, a green
+/// wall on the right, a light in the ceiling and two white boxes inside.
+///
+/// The room spans [-1, 1] along X and Y and [0, 2] along Z and its front side
+/// is open towards the camera.
+///
+/// [0]: https://www.graphics.cornell.edu/online/box/
+pub fn cornell_box() -> (Scene, Camera) {
+    let white = Material::lambertian(v3(0.73, 0.73, 0.73));
+    let red = Material::lambertian(v3(0.65, 0.05, 0.05));
+    let green = Material::lambertian(v3(0.12, 0.45, 0.15));
+
+    let mut objects = SceneObjects::new();
+
+    // all the walls face the inside of the room
+    let floor = v3(-1, -1, 0);
+    quad(&mut objects, floor, v3(2, 0, 0), v3(0, 2, 0), &white);
+    quad(
+        &mut objects,
+        v3(-1, -1, 2),
+        v3(0, 2, 0),
+        v3(2, 0, 0),
+        &white,
+    );
+    quad(&mut objects, v3(-1, 1, 0), v3(2, 0, 0), v3(0, 0, 2), &white);
+    quad(&mut objects, floor, v3(0, 2, 0), v3(0, 0, 2), &red);
+    quad(&mut objects, v3(1, -1, 0), v3(0, 0, 2), v3(0, 2, 0), &green);
+
+    cuboid(
+        &mut objects,
+        v3(0.35, -0.3, 0.3),
+        v3(0.3, 0.3, 0.3),
+        -18.0,
+        &white,
+    );
+    cuboid(
+        &mut objects,
+        v3(-0.35, 0.3, 0.6),
+        v3(0.3, 0.3, 0.6),
+        15.0,
+        &white,
+    );
+
+    // slightly below the ceiling so that it doesn't overlap with it
+    quad(
+        &mut objects,
+        v3(-0.25, -0.2, 1.99),
+        v3(0.0, 0.4, 0.0),
+        v3(0.5, 0.0, 0.0),
+        &Material::light(v3(1.7, 1.5, 1.2)),
+    );
+
+    let scene = Scene::new(objects, Environment::Color(Vec3::zero()));
+    let camera = Camera::look_at(v3(0.0, -3.9, 1.0), v3(0, 0, 1), v3(0, 0, 1), 40.0);
+
+    (scene, camera)
+}
+
+/// The multiple importance sampling scene from [Eric Veach's thesis][0]: four
+/// metal plates, from rough to polished, reflecting four spherical lights of
+/// increasing size that all emit the same power.
+///
+/// Sampling only the lights or only the materials produces a lot of noise on
+/// either the polished plates or the big lights making it a good stress test
+/// for the sampling strategies.
+///
+/// [0]: https://graphics.stanford.edu/papers/veach_thesis/
+pub fn veach_mis() -> (Scene, Camera) {
+    let camera_pos = v3(0, -8, 4);
+    let lights_center = v3(0, 3, 3);
+
+    let mut objects = SceneObjects::new();
+
+    quad(
+        &mut objects,
+        v3(-10, -10, -1),
+        v3(20, 0, 0),
+        v3(0, 20, 0),
+        &Material::lambertian(v3(0.4, 0.4, 0.4)),
+    );
+    quad(
+        &mut objects,
+        v3(-10, 6, -1),
+        v3(20, 0, 0),
+        v3(0, 0, 12),
+        &Material::lambertian(v3(0.4, 0.4, 0.4)),
+    );
+
+    // each plate is tilted so that it reflects the lights towards the camera
+    for (i, fuzziness) in [0.2, 0.08, 0.03, 0.005].into_iter().enumerate() {
+        let center = v3(0.0, -1.5 + i as f64, i as f64 * 0.35);
+        let n = ((camera_pos - center).normalized() + (lights_center - center).normalized())
+            .normalized();
+        let along = n.cross(v3(1, 0, 0)).normalized() * 0.45;
+        let across = v3(2, 0, 0);
+
+        quad(
+            &mut objects,
+            center - across - along,
+            across * 2.0,
+            along * 2.0,
+            &Material::metal(v3(0.9, 0.9, 0.9), fuzziness),
+        );
+    }
+
+    for (i, radius) in [0.03, 0.1, 0.3, 0.9].into_iter().enumerate() {
+        let c = v3(-3.75 + i as f64 * 2.5, 0.0, 0.0);
+        let emittance = 0.1 / (radius * radius);
+
+        objects.push(SimpleObject::new(
+            SphereGeometry::new(lights_center + c, radius),
+            Material::light(v3(emittance, emittance, emittance)),
+        ));
+    }
+
+    let scene = Scene::new(objects, Environment::Color(Vec3::zero()));
+    let camera = Camera::look_at(camera_pos, v3(0.0, 0.0, 0.6), v3(0, 0, 1), 40.0);
+
+    (scene, camera)
+}
+
+/// The white furnace test: a diffuse sphere with the given `albedo` lit by a
+/// uniform white `Environment`.
+///
+/// Every point of the sphere receives the same light from the environment
+/// hence, in an unbiased renderer, the sphere converges to its albedo and
+/// blends with the background when the albedo is 1.
+pub fn furnace(albedo: f64) -> (Scene, Camera) {
+    let mut objects = SceneObjects::new();
+    objects.push(SimpleObject::new(
+        SphereGeometry::new(Vec3::zero(), 1.0),
+        Material::lambertian(v3(albedo, albedo, albedo)),
+    ));
+
+    let scene = Scene::new(objects, Environment::Color(v3(1, 1, 1)));
+    let camera = Camera::look_at(v3(0, -4, 0), Vec3::zero(), v3(0, 0, 1), 15.0);
+
+    (scene, camera)
+}
+
+/// Add the parallelogram with a corner in `o` and sides `u` and `v` as two
+/// flat shaded triangles facing towards `u x v`.
+fn quad(objects: &mut SceneObjects, o: Vec3, u: Vec3, v: Vec3, material: &Material) {
+    for tri in [
+        Triangle::new(o, o + u, o + u + v),
+        Triangle::new(o, o + u + v, o + v),
+    ] {
+        objects.push(SimpleObject::new(
+            FacetGeometry::new(tri, true),
+            material.clone(),
+        ));
+    }
+}
+
+/// Add a box with the given center and half sizes rotated around the Z axis
+/// by `angle` degrees.
+fn cuboid(objects: &mut SceneObjects, center: Vec3, half: Vec3, angle: f64, material: &Material) {
+    let (s, c) = angle.to_radians().sin_cos();
+    let x = v3(c, s, 0.0) * half.x;
+    let y = v3(-s, c, 0.0) * half.y;
+    let z = v3(0.0, 0.0, half.z);
+
+    // one face for each axis on each side, all facing outwards
+    for (u, v, w) in [(x, y, z), (y, z, x), (z, x, y)] {
+        quad(objects, center + w - u - v, u * 2.0, v * 2.0, material);
+        quad(objects, center - w - v - u, v * 2.0, u * 2.0, material);
+    }
+}
@@ -0,0 +1,283 @@
+//! Analytic daylight sky based on the [Preetham model][0].
+//!
+//! The model describes the color of a clear sky given the position of the sun
+//! and the turbidity of the atmosphere, that is how hazy it is. A turbidity of
+//! 2 is a very clear sky while 10 is a hazy one.
+//!
+//! The sun itself is a small, very bright, disc that is sampled explicitly as
+//! a light by the diffuse surfaces, see `RenderConfig::direct_lighting`.
+//!
+//! [0]: https://www2.cs.duke.edu/courses/cps124/spring08/assign/07_papers/p91-preetham.pdf
+
+use std::f64::consts::PI;
+
+use rand::Rng;
+
+use geo::{plane::orthonormal_basis, Vec3};
+
+/// A clear sky lit by the sun, the zenith is towards +Z.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sky {
+    sun_dir: Vec3,
+    turbidity: f64,
+    intensity: f64,
+    sun_irradiance: f64,
+    sun_radius: f64,
+
+    // precomputed from the parameters above
+    perez: [[f64; 5]; 3],
+    zenith: [f64; 3],
+    sun_radiance: Vec3,
+}
+
+impl Sky {
+    /// Create a `Sky` where the sun is in the `sun_dir` direction, which
+    /// doesn't need to be normalized, and with the given turbidity which is
+    /// clamped to [1.7, 10] where the model is valid.
+    ///
+    /// When the sun is below the horizon the sky is dark and there's no sun
+    /// light.
+    pub fn new(sun_dir: Vec3, turbidity: f64) -> Self {
+        let mut sky = Self {
+            sun_dir: sun_dir.normalized(),
+            turbidity: turbidity.clamp(1.7, 10.0),
+            intensity: 0.05,
+            sun_irradiance: 5.0,
+            sun_radius: 0.265_f64.to_radians(),
+            perez: [[0.0; 5]; 3],
+            zenith: [0.0; 3],
+            sun_radiance: Vec3::zero(),
+        };
+        sky.precompute();
+        sky
+    }
+
+    /// Scale the luminance of the sky, which is in kcd/m² in the model, by
+    /// the given factor.
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Set the irradiance of the sun on a surface perpendicular to it before
+    /// being attenuated by the atmosphere.
+    pub fn with_sun_irradiance(mut self, irradiance: f64) -> Self {
+        self.sun_irradiance = irradiance;
+        self.precompute();
+        self
+    }
+
+    /// Set the angular radius of the sun disc in degrees, the bigger the
+    /// softer the shadows.
+    pub fn with_sun_radius(mut self, degrees: f64) -> Self {
+        self.sun_radius = degrees.to_radians().max(1e-4);
+        self.precompute();
+        self
+    }
+
+    /// The normalized direction towards the sun.
+    pub fn sun_dir(&self) -> Vec3 {
+        self.sun_dir
+    }
+
+    /// The color of the sky, sun excluded, in the given direction.
+    pub fn sky_radiance(&self, dir: Vec3) -> Vec3 {
+        if self.sun_dir.z <= 0.0 {
+            return Vec3::zero();
+        }
+
+        // the model is not defined below the horizon, just extend the horizon
+        // down
+        let dir = dir.normalized();
+        let cos_theta = dir.z.max(1e-3);
+        let gamma = dir.dot(self.sun_dir).clamp(-1.0, 1.0).acos();
+        let theta_sun = self.sun_dir.z.clamp(-1.0, 1.0).acos();
+
+        let [y, x, yy] = [0, 1, 2].map(|i| {
+            self.zenith[i] * perez(self.perez[i], cos_theta, gamma)
+                / perez(self.perez[i], 1.0, theta_sun)
+        });
+
+        xyy_to_rgb(x, yy, y * self.intensity)
+    }
+
+    /// The color of the sun disc, it's black outside of it.
+    pub fn sun_radiance(&self, dir: Vec3) -> Vec3 {
+        if dir.normalized().dot(self.sun_dir) < self.sun_radius.cos() {
+            return Vec3::zero();
+        }
+
+        self.sun_radiance
+    }
+
+    /// The color of the sky in the given direction, sun included.
+    pub fn radiance(&self, dir: Vec3) -> Vec3 {
+        self.sky_radiance(dir) + self.sun_radiance(dir)
+    }
+
+    /// Sample a direction towards the sun disc uniformly, along with the solid
+    /// angle the disc covers and its radiance. `None` is returned when the
+    /// sun is below the horizon.
+    pub fn sample_sun(&self, rng: &mut impl Rng) -> Option<(Vec3, f64, Vec3)> {
+        if self.sun_radiance == Vec3::zero() {
+            return None;
+        }
+
+        let cos_max = self.sun_radius.cos();
+        let cos = 1.0 - rng.gen::<f64>() * (1.0 - cos_max);
+        let sin = (1.0 - cos * cos).max(0.0).sqrt();
+        let phi = 2.0 * PI * rng.gen::<f64>();
+
+        let (u, v) = orthonormal_basis(self.sun_dir);
+        let dir = u * (sin * phi.cos()) + v * (sin * phi.sin()) + self.sun_dir * cos;
+
+        Some((dir, self.sun_solid_angle(), self.sun_radiance))
+    }
+
+    /// The irradiance of the sun, once attenuated by the atmosphere, on a
+    /// surface perpendicular to it.
+    pub fn sun_irradiance(&self) -> Vec3 {
+        self.sun_radiance * self.sun_solid_angle()
+    }
+
+    fn sun_solid_angle(&self) -> f64 {
+        2.0 * PI * (1.0 - self.sun_radius.cos())
+    }
+
+    fn precompute(&mut self) {
+        let t = self.turbidity;
+
+        self.perez = [
+            [
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703,
+            ],
+            [
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452,
+            ],
+            [
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529,
+            ],
+        ];
+
+        let theta = self.sun_dir.z.clamp(-1.0, 1.0).acos();
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta);
+        let zenith_y = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+
+        let chromaticity = |m: [[f64; 4]; 3]| {
+            let th = [theta.powi(3), theta.powi(2), theta, 1.0];
+            let row = |r: [f64; 4]| r.iter().zip(th).map(|(a, b)| a * b).sum::<f64>();
+
+            t * t * row(m[0]) + t * row(m[1]) + row(m[2])
+        };
+        let zenith_x = chromaticity([
+            [0.00166, -0.00375, 0.00209, 0.0],
+            [-0.02903, 0.06377, -0.03202, 0.00394],
+            [0.11693, -0.21196, 0.06052, 0.25886],
+        ]);
+        let zenith_yy = chromaticity([
+            [0.00275, -0.00610, 0.00317, 0.0],
+            [-0.04214, 0.08970, -0.04153, 0.00516],
+            [0.15346, -0.26756, 0.06670, 0.26688],
+        ]);
+
+        self.zenith = [zenith_y.max(0.0), zenith_x, zenith_yy];
+
+        self.sun_radiance = if self.sun_dir.z <= 0.0 {
+            Vec3::zero()
+        } else {
+            sun_transmittance(theta, t) * (self.sun_irradiance / self.sun_solid_angle())
+        };
+    }
+}
+
+/// The Perez function modeling the distribution of the sky luminance given
+/// the cosine of the zenith angle and the angle from the sun.
+fn perez([a, b, c, d, e]: [f64; 5], cos_theta: f64, gamma: f64) -> f64 {
+    (1.0 + a * (b / cos_theta).exp()) * (1.0 + c * (d * gamma).exp() + e * gamma.cos().powi(2))
+}
+
+/// The fraction of the sun light that reaches the ground through the
+/// atmosphere for each RGB channel due to the Rayleigh scattering and the
+/// aerosols, given the zenith angle of the sun and the turbidity.
+fn sun_transmittance(theta: f64, turbidity: f64) -> Vec3 {
+    // relative optical mass of the atmosphere, the longer the path the light
+    // travels the more it's attenuated
+    let m = 1.0 / (theta.cos() + 0.15 * (93.885 - theta.to_degrees()).powf(-1.253));
+
+    // Ångström's turbidity formula for the aerosols
+    let beta = 0.04608 * turbidity - 0.04586;
+
+    // representative wavelengths of the RGB channels in micrometers
+    let [r, g, b] = [0.680, 0.550, 0.440].map(|l: f64| {
+        let rayleigh = 0.008735 * l.powf(-4.08);
+        let aerosol = beta * l.powf(-1.3);
+
+        (-m * (rayleigh + aerosol)).exp()
+    });
+
+    Vec3::new(r, g, b)
+}
+
+/// Convert a color in the CIE xyY color space to linear sRGB.
+fn xyy_to_rgb(x: f64, y: f64, lum: f64) -> Vec3 {
+    if y <= 0.0 {
+        return Vec3::zero();
+    }
+
+    let cx = x * lum / y;
+    let cz = (1.0 - x - y) * lum / y;
+
+    Vec3::new(
+        3.2406 * cx - 1.5372 * lum - 0.4986 * cz,
+        -0.9689 * cx + 1.8758 * lum + 0.0415 * cz,
+        0.0557 * cx - 0.2040 * lum + 1.0570 * cz,
+    )
+    .max(Vec3::zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::v3;
+    use rand::SeedableRng;
+    use rand_xorshift::XorShiftRng;
+
+    use super::*;
+
+    #[test]
+    fn test_sky() {
+        let sky = Sky::new(v3(0.0, 1.0, 0.5), 3.0);
+
+        // the sky is brighter around the sun and blue overhead
+        let zenith = sky.sky_radiance(v3(0, 0, 1));
+        assert!(zenith.z > zenith.x, "{zenith:?}");
+        assert!(sky.sky_radiance(v3(0.0, 1.0, 0.6)).y > sky.sky_radiance(v3(0.0, -1.0, 0.6)).y);
+
+        assert_eq!(sky.sun_radiance(v3(0, 0, 1)), Vec3::zero());
+        let sun = sky.radiance(sky.sun_dir());
+        assert!(sun.x > zenith.x * 1000.0 && sun.x > sun.z, "{sun:?}");
+
+        let mut rng = XorShiftRng::seed_from_u64(0);
+        let (dir, solid_angle, radiance) = sky.sample_sun(&mut rng).unwrap();
+        assert_eq!(sky.sun_radiance(dir), radiance);
+        assert!(
+            (radiance.y * solid_angle - 5.0 * sun_transmittance(sky.sun_dir.z.acos(), 3.0).y).abs()
+                < 1e-9
+        );
+
+        let night = Sky::new(v3(0.0, 1.0, -0.1), 3.0);
+        assert_eq!(night.radiance(v3(0, 0, 1)), Vec3::zero());
+        assert!(night.sample_sun(&mut rng).is_none());
+    }
+}
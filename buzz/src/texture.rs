@@ -0,0 +1,168 @@
+use geo::{Vec2, Vec3};
+
+/// A `Texture` describes how a property of a `Material`, like its color or its
+/// roughness, varies across the surface of an object.
+///
+/// Scalar properties only use the first channel of the texture.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Texture {
+    /// The same value everywhere.
+    Constant(Vec3),
+
+    /// A 3D checkerboard made of cubes of side `size` alternating between the
+    /// `even` and `odd` values.
+    Checker { even: Vec3, odd: Vec3, size: f64 },
+
+    /// A checkerboard in texture space with `cells` squares per unit along
+    /// both the u and v directions. Surfaces without texture coordinates are
+    /// `even` everywhere.
+    UvChecker { even: Vec3, odd: Vec3, cells: f64 },
+}
+
+impl Texture {
+    /// Create a `Texture` that is the same value everywhere.
+    pub const fn constant(v: Vec3) -> Self {
+        Texture::Constant(v)
+    }
+
+    /// Create a `Texture` that alternates between `even` and `odd` in a 3D
+    /// checkerboard pattern made of cubes of side `size`.
+    pub const fn checker(even: Vec3, odd: Vec3, size: f64) -> Self {
+        Texture::Checker { even, odd, size }
+    }
+
+    /// Create a `Texture` that alternates between `even` and `odd` in a
+    /// checkerboard pattern over the texture coordinates of the surface with
+    /// `cells` squares per unit.
+    pub const fn uv_checker(even: Vec3, odd: Vec3, cells: f64) -> Self {
+        Texture::UvChecker { even, odd, cells }
+    }
+
+    /// The value of the texture at the given point `p` in world space whose
+    /// texture coordinates on the surface are `uv`, if any.
+    pub fn value(&self, p: Vec3, uv: Option<Vec2>) -> Vec3 {
+        match *self {
+            Texture::Constant(v) => v,
+            Texture::Checker { even, odd, size } => {
+                let cell = |c: f64| (c / size).floor() as i64;
+
+                if (cell(p.x) + cell(p.y) + cell(p.z)).rem_euclid(2) == 0 {
+                    even
+                } else {
+                    odd
+                }
+            }
+            Texture::UvChecker { even, odd, cells } => {
+                let Some(uv) = uv else {
+                    return even;
+                };
+                let cell = |c: f64| (c * cells).floor() as i64;
+
+                if (cell(uv.x) + cell(uv.y)).rem_euclid(2) == 0 {
+                    even
+                } else {
+                    odd
+                }
+            }
+        }
+    }
+
+    /// The value of a scalar texture at the given point `p` in world space
+    /// whose texture coordinates are `uv`, see `value`.
+    pub fn scalar(&self, p: Vec3, uv: Option<Vec2>) -> f64 {
+        self.value(p, uv).x
+    }
+
+    /// The average value of the texture over a box of side `width` around the
+    /// point `p` in world space, e.g. the footprint of a pixel estimated by a
+    /// `RayDifferential`, to avoid aliasing.
+    ///
+    /// Only the textures defined in world space are filtered, the others are
+    /// point sampled at `p` and `uv` like `value` does.
+    pub fn filtered(&self, p: Vec3, uv: Option<Vec2>, width: f64) -> Vec3 {
+        match *self {
+            Texture::Checker { even, odd, size } if width > 0.0 => {
+                // the checkerboard is the product of a square wave along each
+                // axis and the box filter is separable
+                let s = [p.x, p.y, p.z]
+                    .into_iter()
+                    .map(|c| filtered_square_wave(c / size, width / size))
+                    .product::<f64>();
+
+                odd.lerp(even, 0.5 + 0.5 * s)
+            }
+            _ => self.value(p, uv),
+        }
+    }
+
+    /// The filtered value of a scalar texture, see `filtered`.
+    pub fn filtered_scalar(&self, p: Vec3, uv: Option<Vec2>, width: f64) -> f64 {
+        self.filtered(p, uv, width).x
+    }
+}
+
+/// The average of the square wave that is 1 on the even cells and -1 on the
+/// odd ones over the interval of length `width` centered at `x`.
+fn filtered_square_wave(x: f64, width: f64) -> f64 {
+    // the integral of the square wave is a triangle wave
+    let integral = |x: f64| 1.0 - (x.rem_euclid(2.0) - 1.0).abs();
+
+    ((integral(x + width / 2.0) - integral(x - width / 2.0)) / width).clamp(-1.0, 1.0)
+}
+
+impl From<Vec3> for Texture {
+    fn from(v: Vec3) -> Self {
+        Texture::Constant(v)
+    }
+}
+
+impl From<f64> for Texture {
+    fn from(v: f64) -> Self {
+        Texture::Constant(Vec3::new(v, v, v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::{v2, v3};
+
+    use super::*;
+
+    #[test]
+    fn test_checker() {
+        let t = Texture::checker(v3(1, 1, 1), Vec3::zero(), 0.5);
+
+        assert_eq!(t.value(v3(0.1, 0.1, 0.1), None), v3(1, 1, 1));
+        assert_eq!(t.value(v3(0.6, 0.1, 0.1), None), Vec3::zero());
+        assert_eq!(t.value(v3(-0.1, 0.1, 0.1), None), Vec3::zero());
+        assert_eq!(t.value(v3(0.6, 0.6, 0.1), None), v3(1, 1, 1));
+
+        assert_eq!(Texture::from(0.3).scalar(v3(4, 5, 6), None), 0.3);
+    }
+
+    #[test]
+    fn test_filtered_checker() {
+        let t = Texture::checker(v3(1, 1, 1), Vec3::zero(), 0.5);
+        let gray = v3(0.5, 0.5, 0.5);
+
+        let p = v3(0.25, 0.25, 0.6);
+        assert_eq!(t.filtered(p, None, 0.0), t.value(p, None));
+        assert!(t.filtered(p, None, 0.1).dist(Vec3::zero()) < 1e-9);
+
+        // half of the footprint is on each side of the edge between two cells
+        assert!(t.filtered(v3(0, 0.25, 0.25), None, 0.1).dist(gray) < 1e-9);
+
+        // a footprint covering lots of cells averages them
+        assert!(t.filtered(v3(0.1, 0.1, 0.1), None, 50.0).dist(gray) < 1e-9);
+    }
+
+    #[test]
+    fn test_uv_checker() {
+        let t = Texture::uv_checker(v3(1, 1, 1), Vec3::zero(), 4.0);
+
+        assert_eq!(t.value(Vec3::zero(), Some(v2(0.1, 0.1))), v3(1, 1, 1));
+        assert_eq!(t.value(Vec3::zero(), Some(v2(0.3, 0.1))), Vec3::zero());
+        assert_eq!(t.value(Vec3::zero(), Some(v2(0.3, 0.3))), v3(1, 1, 1));
+        assert_eq!(t.value(v3(0.3, 0, 0), None), v3(1, 1, 1));
+    }
+}
@@ -0,0 +1,41 @@
+use std::path::{Path, PathBuf};
+
+use buzz::{parallel_render, scenes, Camera, RenderConfig, Scene};
+use sketch_utils::golden::assert_ppm_matches;
+
+fn check(name: &str, (scene, camera): (Scene, Camera)) {
+    let config = RenderConfig {
+        width: 24,
+        height: 24,
+        samples: 128,
+        max_bounces: 4,
+        seed: Some(0),
+        ..Default::default()
+    };
+
+    let output = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("buzz-{name}.ppm"));
+    parallel_render(&camera, &scene, &config)
+        .save(output.to_str().unwrap())
+        .expect("cannot save render");
+
+    // the blocks average enough samples to hide the noise, but not the
+    // changes to the sampling code that introduce bias
+    assert_ppm_matches(output, golden(&format!("{name}.ppm")), 6, 0.05);
+}
+
+fn golden(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(name)
+}
+
+#[test]
+fn test_golden_cornell_box() {
+    check("cornell_box", scenes::cornell_box());
+}
+
+#[test]
+fn test_golden_furnace() {
+    check("furnace", scenes::furnace(0.5));
+}
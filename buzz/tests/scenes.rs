@@ -0,0 +1,421 @@
+use geo::{v3, Vec3};
+
+use buzz::{
+    parallel_render, render, render_ao, render_pass, scenes, Camera, Environment, Fog, Framebuffer,
+    Material, PlaneGeometry, RenderConfig, Scene, SceneObjects, ShadingMode, SimpleObject, Sky,
+    SphereGeometry, Texture, Visibility,
+};
+
+fn render_linear(
+    scene: &Scene,
+    camera: &Camera,
+    (width, height): (u32, u32),
+    samples: u32,
+) -> Framebuffer {
+    let config = RenderConfig {
+        width,
+        height,
+        samples,
+        ..Default::default()
+    };
+
+    let mut fb = Framebuffer::new(width, height);
+    render_pass(camera, scene, &config, &mut fb, samples, 42);
+    fb
+}
+
+fn mean(pixels: impl IntoIterator<Item = Vec3>) -> Vec3 {
+    let (sum, n) = pixels
+        .into_iter()
+        .fold((Vec3::zero(), 0.0), |(s, n), p| (s + p, n + 1.0));
+    sum / n
+}
+
+#[test]
+fn test_furnace_converges_to_albedo() {
+    for albedo in [0.2, 0.5, 0.8, 1.0] {
+        let (scene, camera) = scenes::furnace(albedo);
+        let fb = render_linear(&scene, &camera, (8, 8), 32);
+
+        let c = mean(fb.data().iter().map(|&p| p / f64::from(fb.samples())));
+        for v in [c.x, c.y, c.z] {
+            assert!(
+                (v - albedo).abs() < 0.01,
+                "albedo {albedo} rendered as {c:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_pbr_furnace_conserves_energy() {
+    let checker = Texture::checker(v3(1, 1, 1), v3(0.5, 0.5, 0.5), 0.25);
+
+    // white dielectrics and smooth white metals reflect all the light they
+    // receive, either diffusely or specularly
+    for (base_color, metallic, roughness, expected) in [
+        (Texture::from(1.0), 0.0, 0.5, 1.0),
+        (Texture::from(1.0), 1.0, 0.2, 1.0),
+        (Texture::from(0.5), 1.0, 0.0, 0.5),
+        (checker, 0.0, 0.3, 0.75),
+    ] {
+        let mut objects = SceneObjects::new();
+        objects.push(SimpleObject::new(
+            SphereGeometry::new(Vec3::zero(), 1.0),
+            Material::pbr(base_color, metallic, roughness),
+        ));
+        let scene = Scene::new(objects, Environment::Color(v3(1, 1, 1)));
+        let camera = Camera::look_at(v3(0, -4, 0), Vec3::zero(), v3(0, 0, 1), 15.0);
+
+        let fb = render_linear(&scene, &camera, (16, 16), 64);
+
+        let c = mean(fb.data().iter().map(|&p| p / f64::from(fb.samples())));
+        for v in [c.x, c.y, c.z] {
+            assert!(
+                (v - expected).abs() < 0.05,
+                "metallic {metallic} roughness {roughness} rendered as {c:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_sun_lights_diffuse_surfaces() {
+    // only the sun lights the ground, therefore all the light comes straight
+    // from it
+    let sky = Sky::new(v3(0, -1, 1), 3.0).with_intensity(0.0);
+    let irradiance = sky.sun_irradiance();
+
+    let mut objects = SceneObjects::new();
+    objects.push(SimpleObject::new(
+        PlaneGeometry::new(Vec3::zero(), v3(0, 0, 1)),
+        Material::lambertian(v3(0.5, 0.5, 0.5)),
+    ));
+    let scene = Scene::new(objects, Environment::Sky(sky));
+    let camera = Camera::look_at(v3(0, 0, 4), Vec3::zero(), v3(0, 1, 0), 30.0);
+
+    let fb = render_linear(&scene, &camera, (8, 8), 16);
+    let c = fb.data().iter().copied().sum::<Vec3>() / f64::from(fb.samples() * 64);
+
+    // the sun is 45 degrees above the horizon
+    let expected = irradiance * (0.5 / std::f64::consts::PI * 0.5_f64.sqrt());
+    assert!(
+        c.dist(expected) < 0.02 * expected.norm(),
+        "{c:?} vs {expected:?}"
+    );
+}
+
+#[test]
+fn test_cornell_box_color_bleeding() {
+    let (scene, camera) = scenes::cornell_box();
+    assert_eq!(scene.lights().count(), 2);
+
+    let (w, h) = (16, 16);
+    let fb = render_linear(&scene, &camera, (w, h), 64);
+    let half = |left: bool| {
+        mean(
+            fb.data()
+                .chunks(w as usize)
+                .flat_map(|row| if left { &row[..8] } else { &row[8..] })
+                .copied(),
+        )
+    };
+
+    let (left, right) = (half(true), half(false));
+    assert!(left.x > 0.0 && right.y > 0.0);
+    assert!(left.x / left.y > right.x / right.y, "{left:?} {right:?}");
+}
+
+#[test]
+fn test_veach_mis() {
+    let (scene, camera) = scenes::veach_mis();
+    assert_eq!(scene.lights().count(), 4);
+
+    let fb = render_linear(&scene, &camera, (16, 12), 16);
+    assert!(fb
+        .data()
+        .iter()
+        .all(|p| p.x.is_finite() && p.y.is_finite() && p.z.is_finite()));
+    assert!(fb.data().iter().any(|p| p.x > 0.0));
+}
+
+#[test]
+fn test_seeded_renders_are_reproducible() {
+    let (scene, camera) = scenes::cornell_box();
+    let config = RenderConfig {
+        width: 8,
+        height: 8,
+        samples: 4,
+        max_bounces: 3,
+        seed: Some(7),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        render(&camera, &scene, &config).data(),
+        render(&camera, &scene, &config).data()
+    );
+    assert_eq!(
+        parallel_render(&camera, &scene, &config).data(),
+        parallel_render(&camera, &scene, &config).data()
+    );
+    assert_eq!(
+        render_ao(&camera, &scene, &config, 0.5).data(),
+        render_ao(&camera, &scene, &config, 0.5).data()
+    );
+}
+
+#[test]
+fn test_debug_shading_modes() {
+    let (scene, camera) = scenes::furnace(0.5);
+    let shade = |shading| {
+        let config = RenderConfig {
+            width: 9,
+            height: 9,
+            samples: 1,
+            seed: Some(7),
+            shading,
+            ..Default::default()
+        };
+
+        let mut fb = Framebuffer::new(9, 9);
+        render_pass(&camera, &scene, &config, &mut fb, 1, 42);
+        fb.data()[4 * 9 + 4]
+    };
+
+    // the center of the sphere faces the camera along -y at distance 3
+    let n = shade(ShadingMode::Normals);
+    assert!(n.dist(Vec3::new(0.5, 0.0, 0.5)) < 0.1, "{n:?}");
+
+    let d = shade(ShadingMode::Depth { far: 4.0 });
+    assert!((d.x - 0.25).abs() < 0.02, "{d:?}");
+
+    let m = shade(ShadingMode::Matcap);
+    assert!(m.x > 0.0 && m.x <= 1.0, "{m:?}");
+
+    // the furnace is only lit by the environment which is indirect light
+    assert_eq!(shade(ShadingMode::DirectOnly), Vec3::zero());
+}
+
+#[test]
+fn test_camera_invisible_objects() {
+    let depth = |visibility| {
+        let mut objects = SceneObjects::new();
+        objects.push(SimpleObject::new(
+            SphereGeometry::new(Vec3::zero(), 1.0),
+            Material::lambertian(v3(0.5, 0.5, 0.5)),
+        ));
+        objects.push(
+            SimpleObject::new(
+                SphereGeometry::new(v3(0.0, -2.5, 0.0), 0.5),
+                Material::lambertian(v3(0.5, 0.5, 0.5)),
+            )
+            .with_visibility(visibility),
+        );
+        let scene = Scene::new(objects, Environment::Color(v3(1, 1, 1)));
+        let camera = Camera::look_at(v3(0, -4, 0), Vec3::zero(), v3(0, 0, 1), 15.0);
+
+        let config = RenderConfig {
+            width: 9,
+            height: 9,
+            samples: 1,
+            seed: Some(7),
+            shading: ShadingMode::Depth { far: 4.0 },
+            ..Default::default()
+        };
+
+        let mut fb = Framebuffer::new(9, 9);
+        render_pass(&camera, &scene, &config, &mut fb, 1, 42);
+        fb.data()[4 * 9 + 4].x
+    };
+
+    // the small sphere hides the center of the big one unless the camera
+    // rays go through it
+    let d = depth(Visibility::default());
+    assert!((d - 0.75).abs() < 0.02, "{d}");
+
+    let d = depth(Visibility {
+        camera: false,
+        ..Visibility::default()
+    });
+    assert!((d - 0.25).abs() < 0.02, "{d}");
+}
+
+#[test]
+fn test_shadow_catcher_alpha() {
+    // the sun is straight above a sphere hidden from the camera that casts its
+    // shadow on the center of the catcher
+    let sky = Sky::new(v3(0, 0, 1), 3.0).with_intensity(0.0);
+
+    let mut objects = SceneObjects::new();
+    objects.push(SimpleObject::new(
+        PlaneGeometry::new(Vec3::zero(), v3(0, 0, 1)),
+        Material::shadow_catcher(),
+    ));
+    objects.push(
+        SimpleObject::new(
+            SphereGeometry::new(v3(0, 0, 1), 0.5),
+            Material::lambertian(v3(0.5, 0.5, 0.5)),
+        )
+        .with_visibility(Visibility {
+            camera: false,
+            ..Visibility::default()
+        }),
+    );
+    let scene = Scene::new(objects, Environment::Sky(sky));
+    let camera = Camera::look_at(v3(0, 0, 10), Vec3::zero(), v3(0, 1, 0), 30.0);
+
+    let fb = render_linear(&scene, &camera, (9, 9), 8);
+    let alpha = |x: usize, y: usize| fb.alpha()[y * 9 + x] / f64::from(fb.samples());
+
+    assert!(alpha(4, 4) > 0.95, "{}", alpha(4, 4));
+    for (x, y) in [(0, 0), (8, 0), (0, 8), (8, 8)] {
+        assert!(alpha(x, y) < 0.05, "({x}, {y}) {}", alpha(x, y));
+    }
+
+    // the environment seen directly has no alpha
+    let scene = Scene::new(SceneObjects::new(), Environment::Color(v3(1, 1, 1)));
+    let fb = render_linear(&scene, &camera, (9, 9), 2);
+    assert!(fb.alpha().iter().all(|&a| a == 0.0));
+}
+
+#[test]
+fn test_transparent_background() {
+    let mut objects = SceneObjects::new();
+    objects.push(SimpleObject::new(
+        SphereGeometry::new(Vec3::zero(), 1.0),
+        Material::lambertian(v3(0.5, 0.5, 0.5)),
+    ));
+    let scene = Scene::new(objects, Environment::Color(v3(1, 1, 1)));
+    let camera = Camera::look_at(v3(0, -4, 0), Vec3::zero(), v3(0, 0, 1), 40.0);
+
+    let config = RenderConfig {
+        width: 9,
+        height: 9,
+        samples: 4,
+        seed: Some(7),
+        transparent_background: true,
+        ..Default::default()
+    };
+
+    let mut fb = Framebuffer::new(9, 9);
+    render_pass(&camera, &scene, &config, &mut fb, 4, 42);
+
+    // the corners only see the environment, the center only the sphere lit by
+    // it
+    assert_eq!(fb.data()[0], Vec3::zero());
+    assert_eq!(fb.alpha()[0], 0.0);
+    assert!(fb.data()[4 * 9 + 4].x > 0.0);
+    assert_eq!(fb.alpha()[4 * 9 + 4], 4.0);
+
+    let img = fb.to_rgba_image();
+    assert_eq!(img.data()[3], 0);
+    assert_eq!(img.data()[(4 * 9 + 4) * 4 + 3], 255);
+}
+
+#[test]
+fn test_fog() {
+    let mut objects = SceneObjects::new();
+    objects.push(SimpleObject::new(
+        SphereGeometry::new(Vec3::zero(), 1.0),
+        Material::lambertian(Vec3::zero()),
+    ));
+    let scene = Scene::new(objects, Environment::Color(v3(1, 1, 1)));
+    let camera = Camera::look_at(v3(0, -4, 0), Vec3::zero(), v3(0, 0, 1), 40.0);
+
+    // the closest point of the sphere is 3 units away, half of its light goes
+    // through the fog
+    let fog = Fog {
+        color: v3(0, 0, 1),
+        density: 2_f64.ln() / 3.0,
+    };
+    let config = RenderConfig {
+        width: 9,
+        height: 9,
+        samples: 4,
+        seed: Some(7),
+        fog: Some(fog),
+        ..Default::default()
+    };
+
+    let mut fb = Framebuffer::new(9, 9);
+    render_pass(&camera, &scene, &config, &mut fb, 4, 42);
+    let pixel = |x: usize, y: usize| fb.data()[y * 9 + x] / f64::from(fb.samples());
+
+    // the environment is entirely covered by the fog
+    assert!(pixel(0, 0).dist(fog.color) < 1e-9, "{:?}", pixel(0, 0));
+    assert!(
+        pixel(4, 4).dist(v3(0.0, 0.0, 0.5)) < 0.05,
+        "{:?}",
+        pixel(4, 4)
+    );
+}
+
+#[test]
+fn test_crop() {
+    let (scene, camera) = scenes::furnace(0.5);
+    let render = |crop| {
+        let config = RenderConfig {
+            width: 8,
+            height: 8,
+            samples: 1,
+            seed: Some(7),
+            shading: ShadingMode::Normals,
+            crop,
+            ..Default::default()
+        };
+
+        let mut fb = Framebuffer::new(8, 8);
+        render_pass(&camera, &scene, &config, &mut fb, 4, 42);
+        fb
+    };
+
+    let full = render(None);
+    let cropped = render(Some((2, 3, 4, 2)));
+    assert_eq!((cropped.width(), cropped.height()), (8, 8));
+
+    // the pixels in the rectangle see the same surfaces of the full render
+    for (i, (c, f)) in cropped.data().iter().zip(full.data()).enumerate() {
+        let (x, y) = (i % 8, i / 8);
+        if (2..6).contains(&x) && (3..5).contains(&y) {
+            assert!(c.dist(*f) < 0.2, "({x}, {y}): {c:?} vs {f:?}");
+        } else {
+            assert_eq!(*c, Vec3::zero(), "({x}, {y})");
+        }
+    }
+}
+
+#[test]
+fn test_russian_roulette_is_unbiased() {
+    let albedo = 0.5;
+    let (scene, camera) = scenes::furnace(albedo);
+    let config = RenderConfig {
+        width: 8,
+        height: 8,
+        samples: 64,
+        max_bounces: 64,
+        throughput_threshold: 0.5,
+        ..Default::default()
+    };
+
+    let mut fb = Framebuffer::new(8, 8);
+    render_pass(&camera, &scene, &config, &mut fb, 64, 42);
+
+    let c = mean(fb.data().iter().map(|&p| p / f64::from(fb.samples())));
+    for v in [c.x, c.y, c.z] {
+        assert!((v - albedo).abs() < 0.01, "rendered as {c:?}");
+    }
+}
+
+#[test]
+fn test_srgb() {
+    let fb = Framebuffer::from_raw(2, 1, 2, vec![v3(0.5, 0.5, 0.5), v3(0.002, 4.0, 0.0)]);
+
+    // plain square root gamma correction by default
+    assert!(!RenderConfig::default().srgb);
+    assert_eq!(fb.to_image().data(), &[127, 127, 127, 8, 255, 0]);
+
+    // the sRGB transfer function is linear near black
+    let fb = fb.with_srgb(true);
+    assert_eq!(fb.to_image().data(), &[137, 137, 137, 3, 255, 0]);
+}
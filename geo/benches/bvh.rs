@@ -0,0 +1,96 @@
+mod common;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use geo::{
+    ray::Ray,
+    spatial_index::{Bvh, BvhBuilder, SplitMethod},
+    Triangle,
+};
+
+use common::{camera_rays, mesh_scene, model_rays, subdivided_model};
+
+fn closest_hits(bvh: &Bvh<Triangle>, rays: &[Ray]) -> f64 {
+    rays.iter()
+        .filter_map(|r| bvh.closest_intersection(r).map(|(_, t)| t))
+        .sum()
+}
+
+fn closest_hits_batch(bvh: &Bvh<Triangle>, rays: &[Ray], packet_size: usize) -> f64 {
+    let mut closest = vec![f64::INFINITY; rays.len()];
+
+    for (packet, closest) in rays
+        .chunks(packet_size)
+        .zip(closest.chunks_mut(packet_size))
+    {
+        bvh.intersections_batch(packet, |i, _, t| closest[i] = closest[i].min(t));
+    }
+
+    closest.into_iter().filter(|t| t.is_finite()).sum()
+}
+
+fn bvh_build(c: &mut Criterion) {
+    let triangles = mesh_scene();
+
+    let mut group = c.benchmark_group("bvh_build");
+    for (name, split_method) in [
+        ("median", SplitMethod::Median),
+        ("sah", SplitMethod::Sah { bins: 16 }),
+    ] {
+        let builder = BvhBuilder::new().with_split_method(split_method);
+        group.bench_function(name, |b| {
+            b.iter(|| builder.build(black_box(triangles.clone())))
+        });
+    }
+    group.finish();
+}
+
+fn bvh_rays(c: &mut Criterion) {
+    let suzanne = subdivided_model("suzanne.stl", 2);
+    let datasets = [
+        ("terrain", mesh_scene(), camera_rays()),
+        ("suzanne", suzanne.clone(), model_rays(&suzanne)),
+    ];
+
+    let mut group = c.benchmark_group("bvh_rays");
+    for (dataset, triangles, rays) in &datasets {
+        group.throughput(Throughput::Elements(rays.len() as u64));
+
+        for (name, split_method) in [
+            ("median", SplitMethod::Median),
+            ("sah", SplitMethod::Sah { bins: 16 }),
+        ] {
+            let bvh = BvhBuilder::new()
+                .with_split_method(split_method)
+                .build(triangles.iter().cloned());
+
+            group.bench_function(format!("{dataset}/{name}"), |b| {
+                b.iter(|| closest_hits(&bvh, black_box(rays)))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bvh_ray_packets(c: &mut Criterion) {
+    let triangles = mesh_scene();
+    let rays = camera_rays();
+
+    let bvh = BvhBuilder::new()
+        .with_split_method(SplitMethod::Sah { bins: 16 })
+        .build(triangles);
+
+    let mut group = c.benchmark_group("bvh_ray_packets");
+    group.bench_function("per_ray", |b| {
+        b.iter(|| closest_hits(&bvh, black_box(&rays)))
+    });
+    for packet_size in [16, 64, 256] {
+        group.bench_function(format!("batch_{packet_size}"), |b| {
+            b.iter(|| closest_hits_batch(&bvh, black_box(&rays), packet_size))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bvh_build, bvh_rays, bvh_ray_packets);
+criterion_main!(benches);
@@ -0,0 +1,91 @@
+//! Datasets shared by the benchmarks.
+
+use geo::{
+    mesh::{halfedge::HalfEdgeMesh, load_mesh, subdivision::loop_subdivision, Mesh},
+    ray::Ray,
+    v3, Aabb, Triangle,
+};
+
+/// An uneven mesh scene made of a dense bumpy patch of tiny triangles lying on
+/// a couple of huge ground triangles, which is where median splits struggle.
+pub fn mesh_scene() -> Vec<Triangle> {
+    let n = 200;
+    let size = 0.05;
+
+    let height = |x: f64, y: f64| (x * 3.0).sin() * (y * 2.0).cos() * 0.3 + 0.5;
+
+    let mut triangles = vec![];
+    for j in 0..n {
+        for i in 0..n {
+            let (x, y) = (f64::from(i) * size, f64::from(j) * size);
+
+            let a = v3(x, y, height(x, y));
+            let b = v3(x + size, y, height(x + size, y));
+            let c = v3(x + size, y + size, height(x + size, y + size));
+            let d = v3(x, y + size, height(x, y + size));
+
+            triangles.push(Triangle::new(a, b, c));
+            triangles.push(Triangle::new(a, c, d));
+        }
+    }
+
+    triangles.push(Triangle::new(
+        v3(-1000, -1000, 0),
+        v3(1000, -1000, 0),
+        v3(1000, 1000, 0),
+    ));
+    triangles.push(Triangle::new(
+        v3(-1000, -1000, 0),
+        v3(1000, 1000, 0),
+        v3(-1000, 1000, 0),
+    ));
+
+    triangles
+}
+
+/// Camera rays on a grid looking down at the mesh.
+pub fn camera_rays() -> Vec<Ray> {
+    let n = 64;
+    let eye = v3(5, -10, 10);
+
+    (0..n * n)
+        .map(|i| {
+            let (x, y) = (f64::from(i % n), f64::from(i / n));
+            let target = v3(
+                x / f64::from(n) * 12.0 - 1.0,
+                y / f64::from(n) * 12.0 - 1.0,
+                0,
+            );
+            Ray::new(eye, (target - eye).normalized())
+        })
+        .collect()
+}
+
+/// A model from the `data` directory subdivided `levels` times so that it has
+/// a realistic, but configurable, number of small triangles.
+pub fn subdivided_model(name: &str, levels: u32) -> Vec<Triangle> {
+    let path = format!("{}/../data/{name}", env!("CARGO_MANIFEST_DIR"));
+    let mesh = load_mesh(&path).unwrap_or_else(|e| panic!("cannot load {path}: {e:?}"));
+
+    let mesh = HalfEdgeMesh::from_triangles(mesh.triangles());
+    loop_subdivision(&mesh, levels).triangles().collect()
+}
+
+/// Rays on a grid from a point in front of the given triangles towards them.
+pub fn model_rays(triangles: &[Triangle]) -> Vec<Ray> {
+    let n = 64;
+    let bbox = Aabb::from_points(triangles.iter().flat_map(|t| [t.a, t.b, t.c])).unwrap();
+    let (center, d) = (bbox.center(), bbox.dimensions());
+    let eye = center + v3(0.5, -2.0, 1.0) * d.norm();
+
+    (0..n * n)
+        .map(|i| {
+            let (x, y) = (
+                f64::from(i % n) / f64::from(n),
+                f64::from(i / n) / f64::from(n),
+            );
+            let target = bbox.min() + v3(x * d.x, 0.5 * d.y, y * d.z);
+            Ray::new(eye, (target - eye).normalized())
+        })
+        .collect()
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use geo::{ray::Ray, spatial_index::KdTree, Triangle};
+
+use common::{camera_rays, mesh_scene, model_rays, subdivided_model};
+
+fn closest_hits(kd: &KdTree<Triangle>, rays: &[Ray]) -> f64 {
+    rays.iter()
+        .filter_map(|r| kd.intersection(r).map(|(_, t)| t))
+        .sum()
+}
+
+fn kdtree_build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("kdtree_build");
+    group.sample_size(10);
+    for (dataset, triangles) in [
+        ("terrain", mesh_scene()),
+        ("suzanne", subdivided_model("suzanne.stl", 2)),
+    ] {
+        group.bench_function(dataset, |b| {
+            b.iter(|| KdTree::new(black_box(triangles.clone())))
+        });
+    }
+    group.finish();
+}
+
+fn kdtree_rays(c: &mut Criterion) {
+    let suzanne = subdivided_model("suzanne.stl", 2);
+    let datasets = [
+        ("terrain", mesh_scene(), camera_rays()),
+        ("suzanne", suzanne.clone(), model_rays(&suzanne)),
+    ];
+
+    let mut group = c.benchmark_group("kdtree_rays");
+    for (dataset, triangles, rays) in datasets {
+        let kd = KdTree::new(triangles);
+
+        group.throughput(Throughput::Elements(rays.len() as u64));
+        group.bench_function(dataset, |b| b.iter(|| closest_hits(&kd, black_box(&rays))));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, kdtree_build, kdtree_rays);
+criterion_main!(benches);
@@ -1,13 +1,22 @@
 pub mod mesh;
+pub mod prelude;
 pub mod primitive;
 pub mod sdf;
+pub mod sdf2;
 pub mod spatial_index;
 pub mod util;
 
 pub use primitive::{
     aabb::Aabb,
-    mat4, plane, ray, sphere,
+    heightfield::{self, Heightfield},
+    mat4,
+    plane::{self, Plane},
+    polyline2::{self, Polyline2},
+    quat::{self, Quat},
+    ray,
+    sphere::{self, Sphere},
     triangle::{self, Triangle},
+    vec2::{self, v2, Vec2},
     vec3::{self, v3, Vec3},
     Axis,
 };
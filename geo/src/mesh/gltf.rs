@@ -0,0 +1,534 @@
+//! This module contains functions to load the geometry of [glTF 2.0][0] files,
+//! both the JSON `.gltf` and the binary `.glb` flavors.
+//!
+//! Only the positions and the indices of the triangle primitives are read
+//! along with the transforms of the nodes of the default scene. Materials,
+//! textures, animations, skins and morph targets are all ignored.
+//!
+//! [0]: https://registry.khronos.org/glTF/specs/2.0/glTF-2.0.html
+
+use std::{collections::HashMap, fs, io::Read, path::Path};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use percent_encoding::percent_decode_str;
+use serde::Deserialize;
+
+use crate::{mat4::Mat4, v3, Triangle, Vec3};
+
+use super::{Error, Mesh, Result};
+
+/// Mesh read from a glTF or GLB file.
+pub struct Gltf {
+    triangles: Vec<Triangle>,
+}
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_JSON_CHUNK: u32 = 0x4e4f_534a;
+const GLB_BIN_CHUNK: u32 = 0x004e_4942;
+
+const MODE_TRIANGLES: usize = 4;
+
+const COMPONENT_UNSIGNED_BYTE: usize = 5121;
+const COMPONENT_UNSIGNED_SHORT: usize = 5123;
+const COMPONENT_UNSIGNED_INT: usize = 5125;
+const COMPONENT_FLOAT: usize = 5126;
+
+/// The subset of the glTF document needed to read the geometry.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Document {
+    scene: Option<usize>,
+    #[serde(default)]
+    scenes: Vec<SceneDesc>,
+    #[serde(default)]
+    nodes: Vec<NodeDesc>,
+    #[serde(default)]
+    meshes: Vec<MeshDesc>,
+    #[serde(default)]
+    buffers: Vec<BufferDesc>,
+    #[serde(default)]
+    buffer_views: Vec<BufferViewDesc>,
+    #[serde(default)]
+    accessors: Vec<AccessorDesc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SceneDesc {
+    #[serde(default)]
+    nodes: Vec<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NodeDesc {
+    mesh: Option<usize>,
+    #[serde(default)]
+    children: Vec<usize>,
+    matrix: Option<[f64; 16]>,
+    translation: Option<[f64; 3]>,
+    rotation: Option<[f64; 4]>,
+    scale: Option<[f64; 3]>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MeshDesc {
+    #[serde(default)]
+    primitives: Vec<PrimitiveDesc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PrimitiveDesc {
+    #[serde(default)]
+    attributes: HashMap<String, usize>,
+    indices: Option<usize>,
+    #[serde(default = "default_mode")]
+    mode: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct BufferDesc {
+    uri: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BufferViewDesc {
+    buffer: usize,
+    #[serde(default)]
+    byte_offset: usize,
+    byte_length: usize,
+    byte_stride: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccessorDesc {
+    buffer_view: Option<usize>,
+    #[serde(default)]
+    byte_offset: usize,
+    component_type: usize,
+    count: usize,
+    #[serde(rename = "type")]
+    ty: String,
+}
+
+fn default_mode() -> usize {
+    MODE_TRIANGLES
+}
+
+impl Gltf {
+    /// Try to load a `Gltf` from the given reader, guessing whether it's a
+    /// GLB or a glTF by its contents. External buffers are resolved relative
+    /// to `base_dir`.
+    pub fn load(mut r: impl Read, base_dir: impl AsRef<Path>) -> Result<Gltf> {
+        let mut data = vec![];
+        r.read_to_end(&mut data)?;
+
+        let (json, bin) = if data.starts_with(GLB_MAGIC) {
+            parse_glb(&data)?
+        } else {
+            (&data[..], None)
+        };
+
+        let doc: Document = serde_json::from_slice(json).map_err(|_| Error::BadFormat)?;
+
+        let mut buffers = Vec::with_capacity(doc.buffers.len());
+        for (i, buffer) in doc.buffers.iter().enumerate() {
+            let data = match &buffer.uri {
+                None if i == 0 => bin.ok_or(Error::BadFormat)?.to_vec(),
+                None => return Err(Error::BadFormat),
+                Some(uri) => match uri.strip_prefix("data:") {
+                    Some(data_uri) => {
+                        let (_, b64) = data_uri.split_once(";base64,").ok_or(Error::BadFormat)?;
+                        BASE64.decode(b64).map_err(|_| Error::BadFormat)?
+                    }
+                    None => {
+                        let path = percent_decode_str(uri).decode_utf8_lossy();
+                        fs::read(base_dir.as_ref().join(path.as_ref()))?
+                    }
+                },
+            };
+            buffers.push(data);
+        }
+
+        let loader = Loader { doc: &doc, buffers };
+
+        Ok(Gltf {
+            triangles: loader.load()?,
+        })
+    }
+}
+
+impl Mesh for Gltf {
+    fn triangles(&self) -> Box<dyn Iterator<Item = Triangle> + '_> {
+        Box::new(self.triangles.iter().cloned())
+    }
+}
+
+/// Split a GLB container into its JSON and optional binary chunks.
+fn parse_glb(data: &[u8]) -> Result<(&[u8], Option<&[u8]>)> {
+    let u32_at = |i: usize| -> Result<u32> {
+        let b = data.get(i..i + 4).ok_or(Error::BadFormat)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+
+    if u32_at(4)? != 2 {
+        return Err(Error::BadFormat);
+    }
+    let len = (u32_at(8)? as usize).min(data.len());
+
+    let mut json = None;
+    let mut bin = None;
+
+    let mut i = 12;
+    while i + 8 <= len {
+        let chunk_len = u32_at(i)? as usize;
+        let ty = u32_at(i + 4)?;
+        let end = (i + 8).checked_add(chunk_len).ok_or(Error::BadFormat)?;
+        let chunk = data.get(i + 8..end).ok_or(Error::BadFormat)?;
+
+        match ty {
+            GLB_JSON_CHUNK if json.is_none() => json = Some(chunk),
+            GLB_BIN_CHUNK if bin.is_none() => bin = Some(chunk),
+            _ => {}
+        }
+
+        i = end;
+    }
+
+    Ok((json.ok_or(Error::BadFormat)?, bin))
+}
+
+struct Loader<'a> {
+    doc: &'a Document,
+    buffers: Vec<Vec<u8>>,
+}
+
+impl Loader<'_> {
+    fn load(self) -> Result<Vec<Triangle>> {
+        let mut triangles = vec![];
+
+        match self.doc.scenes.get(self.doc.scene.unwrap_or(0)) {
+            Some(scene) => {
+                for &node in &scene.nodes {
+                    self.load_node(node, &Mat4::identity(), 0, &mut triangles)?;
+                }
+            }
+            None => {
+                // without scenes there are no nodes to place the meshes hence
+                // just load all of them as they are
+                for mesh in &self.doc.meshes {
+                    self.load_mesh(mesh, &Mat4::identity(), &mut triangles)?;
+                }
+            }
+        }
+
+        Ok(triangles)
+    }
+
+    fn load_node(
+        &self,
+        node: usize,
+        parent: &Mat4,
+        depth: usize,
+        triangles: &mut Vec<Triangle>,
+    ) -> Result<()> {
+        // the nodes must form a tree, a path longer than the number of nodes
+        // means there's a cycle
+        if depth > self.doc.nodes.len() {
+            return Err(Error::BadFormat);
+        }
+        let node = self.doc.nodes.get(node).ok_or(Error::BadFormat)?;
+
+        let transform = parent.clone() * &node_transform(node);
+
+        if let Some(mesh) = node.mesh {
+            let mesh = self.doc.meshes.get(mesh).ok_or(Error::BadFormat)?;
+            self.load_mesh(mesh, &transform, triangles)?;
+        }
+
+        for &child in &node.children {
+            self.load_node(child, &transform, depth + 1, triangles)?;
+        }
+
+        Ok(())
+    }
+
+    fn load_mesh(
+        &self,
+        mesh: &MeshDesc,
+        transform: &Mat4,
+        triangles: &mut Vec<Triangle>,
+    ) -> Result<()> {
+        for primitive in &mesh.primitives {
+            if primitive.mode != MODE_TRIANGLES {
+                continue;
+            }
+
+            let Some(&positions) = primitive.attributes.get("POSITION") else {
+                continue;
+            };
+            let positions = self
+                .read_accessor(positions)?
+                .chunks_exact(3)
+                .map(|c| v3(c[0], c[1], c[2]) * transform)
+                .collect::<Vec<Vec3>>();
+
+            let indices = match primitive.indices {
+                Some(indices) => self
+                    .read_accessor(indices)?
+                    .into_iter()
+                    .map(|i| i as usize)
+                    .collect::<Vec<_>>(),
+                None => (0..positions.len()).collect(),
+            };
+
+            for t in indices.chunks_exact(3) {
+                let get = |i: usize| positions.get(t[i]).copied().ok_or(Error::BadFormat);
+                triangles.push(Triangle::new(get(0)?, get(1)?, get(2)?));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read all the components of all the elements of the given accessor.
+    ///
+    /// All the sizes and offsets come straight from the file, hence they're
+    /// validated against the actual length of the buffer before allocating
+    /// anything.
+    fn read_accessor(&self, accessor: usize) -> Result<Vec<f64>> {
+        let accessor = self.doc.accessors.get(accessor).ok_or(Error::BadFormat)?;
+
+        let components = match accessor.ty.as_str() {
+            "SCALAR" => 1,
+            "VEC2" => 2,
+            "VEC3" => 3,
+            "VEC4" => 4,
+            _ => return Err(Error::BadFormat),
+        };
+        let component_size = match accessor.component_type {
+            COMPONENT_UNSIGNED_BYTE => 1,
+            COMPONENT_UNSIGNED_SHORT => 2,
+            COMPONENT_UNSIGNED_INT | COMPONENT_FLOAT => 4,
+            _ => return Err(Error::BadFormat),
+        };
+
+        // accessors without a buffer view are all zeros unless overridden by
+        // sparse values, which are not supported, so they'd only produce
+        // degenerate triangles. Reject them instead of trusting their count.
+        let view = accessor.buffer_view.ok_or(Error::BadFormat)?;
+        let view = self.doc.buffer_views.get(view).ok_or(Error::BadFormat)?;
+
+        let buffer = self.buffers.get(view.buffer).ok_or(Error::BadFormat)?;
+        let view_end = view
+            .byte_offset
+            .checked_add(view.byte_length)
+            .ok_or(Error::BadFormat)?;
+        let bytes = buffer
+            .get(view.byte_offset..view_end)
+            .ok_or(Error::BadFormat)?;
+
+        let element_size = components * component_size;
+        let stride = view.byte_stride.unwrap_or(element_size);
+
+        if accessor.count == 0 {
+            return Ok(vec![]);
+        }
+
+        // the last element must fit in the view
+        let end = (accessor.count - 1)
+            .checked_mul(stride)
+            .and_then(|o| o.checked_add(accessor.byte_offset))
+            .and_then(|o| o.checked_add(element_size))
+            .ok_or(Error::BadFormat)?;
+        if end > bytes.len() {
+            return Err(Error::BadFormat);
+        }
+
+        let mut out = Vec::with_capacity(accessor.count * components);
+        for e in 0..accessor.count {
+            let start = accessor.byte_offset + e * stride;
+            let element = &bytes[start..start + element_size];
+
+            for c in element.chunks_exact(component_size) {
+                out.push(match accessor.component_type {
+                    COMPONENT_UNSIGNED_BYTE => f64::from(c[0]),
+                    COMPONENT_UNSIGNED_SHORT => f64::from(u16::from_le_bytes([c[0], c[1]])),
+                    COMPONENT_UNSIGNED_INT => {
+                        f64::from(u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+                    }
+                    _ => f64::from(f32::from_le_bytes([c[0], c[1], c[2], c[3]])),
+                });
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+/// The local transform of a node either given as a matrix or as translation,
+/// rotation and scale.
+fn node_transform(node: &NodeDesc) -> Mat4 {
+    if let Some(m) = node.matrix {
+        // glTF matrices are stored in column major order
+        let mut data = [[0.0; 4]; 4];
+        for (i, v) in m.into_iter().enumerate() {
+            data[i % 4][i / 4] = v;
+        }
+        return Mat4 { data };
+    }
+
+    let mut transform = Mat4::identity();
+
+    if let Some([x, y, z]) = node.translation {
+        transform = transform * &Mat4::translate(v3(x, y, z));
+    }
+
+    if let Some([x, y, z, w]) = node.rotation {
+        let rotation = Mat4 {
+            data: [
+                [
+                    1.0 - 2.0 * (y * y + z * z),
+                    2.0 * (x * y - z * w),
+                    2.0 * (x * z + y * w),
+                    0.0,
+                ],
+                [
+                    2.0 * (x * y + z * w),
+                    1.0 - 2.0 * (x * x + z * z),
+                    2.0 * (y * z - x * w),
+                    0.0,
+                ],
+                [
+                    2.0 * (x * z - y * w),
+                    2.0 * (y * z + x * w),
+                    1.0 - 2.0 * (x * x + y * y),
+                    0.0,
+                ],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        };
+        transform = transform * &rotation;
+    }
+
+    if let Some([x, y, z]) = node.scale {
+        transform = transform * &Mat4::scale(v3(x, y, z));
+    }
+
+    transform
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A single triangle scaled by 2 by its node, with u16 indices followed by
+    /// the positions in the same buffer.
+    fn triangle_buffer() -> Vec<u8> {
+        let mut buf = vec![];
+        for i in [0_u16, 1, 2, 0] {
+            buf.extend_from_slice(&i.to_le_bytes());
+        }
+        for c in [0.0_f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            buf.extend_from_slice(&c.to_le_bytes());
+        }
+        buf
+    }
+
+    fn triangle_json(uri: Option<&str>) -> String {
+        let uri = uri.map_or(String::new(), |u| format!(r#""uri": "{u}","#));
+        format!(
+            r#"{{
+  "asset": {{ "version": "2.0" }},
+  "scene": 0,
+  "scenes": [{{ "nodes": [0] }}],
+  "nodes": [{{ "mesh": 0, "scale": [2, 2, 2], "translation": [0, 0, 1] }}],
+  "meshes": [{{ "primitives": [{{ "attributes": {{ "POSITION": 1 }}, "indices": 0 }}] }}],
+  "buffers": [{{ {uri} "byteLength": 44 }}],
+  "bufferViews": [
+    {{ "buffer": 0, "byteOffset": 0, "byteLength": 6 }},
+    {{ "buffer": 0, "byteOffset": 8, "byteLength": 36 }}
+  ],
+  "accessors": [
+    {{ "bufferView": 0, "componentType": 5123, "count": 3, "type": "SCALAR" }},
+    {{ "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC3" }}
+  ]
+}}"#
+        )
+    }
+
+    fn expected() -> Vec<Triangle> {
+        vec![Triangle::new(v3(0, 0, 1), v3(2, 0, 1), v3(0, 2, 1))]
+    }
+
+    #[test]
+    fn test_load_gltf() {
+        let b64 = BASE64.encode(triangle_buffer());
+
+        let json = triangle_json(Some(&format!("data:application/octet-stream;base64,{b64}")));
+        let gltf = Gltf::load(json.as_bytes(), ".").unwrap();
+
+        assert_eq!(gltf.triangles().collect::<Vec<_>>(), expected());
+    }
+
+    #[test]
+    fn test_load_glb() {
+        let mut json = triangle_json(None).into_bytes();
+        while !json.len().is_multiple_of(4) {
+            json.push(b' ');
+        }
+        let bin = triangle_buffer();
+
+        let mut glb = GLB_MAGIC.to_vec();
+        glb.extend_from_slice(&2_u32.to_le_bytes());
+        glb.extend_from_slice(&((12 + 8 + json.len() + 8 + bin.len()) as u32).to_le_bytes());
+        glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        glb.extend_from_slice(&GLB_JSON_CHUNK.to_le_bytes());
+        glb.extend_from_slice(&json);
+        glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        glb.extend_from_slice(&GLB_BIN_CHUNK.to_le_bytes());
+        glb.extend_from_slice(&bin);
+
+        let gltf = Gltf::load(&glb[..], ".").unwrap();
+        assert_eq!(gltf.triangles().collect::<Vec<_>>(), expected());
+    }
+
+    #[test]
+    fn test_load_malformed() {
+        let load = |json: String| {
+            let b64 = BASE64.encode(triangle_buffer());
+            let json = json.replace(
+                "URI",
+                &format!("data:application/octet-stream;base64,{b64}"),
+            );
+            Gltf::load(json.as_bytes(), ".")
+        };
+
+        let json = triangle_json(Some("URI"));
+        assert!(load(json.clone()).is_ok());
+
+        // counts way bigger than the buffer
+        assert!(load(json.replace(
+            r#""count": 3, "type": "VEC3""#,
+            r#""count": 1000000000000, "type": "VEC3""#
+        ))
+        .is_err());
+
+        // offsets that overflow
+        assert!(load(json.replace(
+            r#""byteOffset": 8, "byteLength": 36"#,
+            &format!(r#""byteOffset": {}, "byteLength": 36"#, usize::MAX - 1)
+        ))
+        .is_err());
+        assert!(load(json.replace(
+            r#""bufferView": 1,"#,
+            &format!(r#""bufferView": 1, "byteOffset": {},"#, usize::MAX - 1)
+        ))
+        .is_err());
+
+        // accessors without views
+        assert!(load(json.replace(r#""bufferView": 1,"#, "")).is_err());
+
+        assert!(Gltf::load("{ \"nodes\": 1 ".as_bytes(), ".").is_err());
+    }
+}
@@ -0,0 +1,336 @@
+//! Indexed triangle mesh with adjacency information.
+//!
+//! `Mesh::triangles` only returns a triangle soup which is enough for
+//! rendering, but algorithms like silhouette detection, smooth normals or
+//! decimation need to know which triangles and vertices are next to each
+//! other. `HalfEdgeMesh` stores the triangles as indices into a list of unique
+//! vertices along with the [half edges][0] connecting them.
+//!
+//! [0]: https://en.wikipedia.org/wiki/Doubly_connected_edge_list
+
+use std::collections::HashMap;
+
+use super::Mesh;
+use crate::{Triangle, Vec3};
+
+/// A triangle mesh where each face is split into three directed half edges
+/// that know the half edge going in the opposite direction in the adjacent
+/// face, if any.
+///
+/// Half edges are identified by an index where the half edge `3 * f + i` goes
+/// from the `i`-th vertex to the next vertex of face `f`. Edges shared by more
+/// than two faces are treated as boundary edges.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HalfEdgeMesh {
+    vertices: Vec<Vec3>,
+    faces: Vec<[usize; 3]>,
+
+    // the opposite half edge of each half edge
+    twins: Vec<Option<usize>>,
+
+    // an outgoing half edge for each vertex, for vertices on the boundary
+    // it's the one with no twin so that walking around the vertex in
+    // counterclockwise order visits all the faces
+    outgoing: Vec<Option<usize>>,
+}
+
+impl HalfEdgeMesh {
+    /// Create a new `HalfEdgeMesh` from the given vertices and faces whose
+    /// vertices are in counterclockwise order.
+    pub fn new(vertices: Vec<Vec3>, faces: Vec<[usize; 3]>) -> Self {
+        let mut directed = HashMap::with_capacity(faces.len() * 3);
+        let mut twins = vec![None; faces.len() * 3];
+        let mut outgoing = vec![None; vertices.len()];
+
+        for (fi, f) in faces.iter().enumerate() {
+            for i in 0..3 {
+                let h = 3 * fi + i;
+                let (a, b) = (f[i], f[(i + 1) % 3]);
+                assert!(a < vertices.len() && b < vertices.len());
+
+                outgoing[a] = Some(h);
+
+                // edges used more than once in the same direction are non
+                // manifold, leave them unpaired
+                if directed.insert((a, b), Some(h)).is_some() {
+                    directed.insert((a, b), None);
+                }
+            }
+        }
+
+        for (&(a, b), &h) in &directed {
+            let (Some(h), Some(&Some(t))) = (h, directed.get(&(b, a))) else {
+                continue;
+            };
+
+            twins[h] = Some(t);
+        }
+
+        let mut mesh = HalfEdgeMesh {
+            vertices,
+            faces,
+            twins,
+            outgoing,
+        };
+
+        for h in 0..mesh.twins.len() {
+            if mesh.twins[h].is_none() {
+                let v = mesh.origin(h);
+                mesh.outgoing[v] = Some(h);
+            }
+        }
+
+        mesh
+    }
+
+    /// Create a new `HalfEdgeMesh` from the given triangles merging the
+    /// vertices with exactly the same coordinates.
+    pub fn from_triangles(triangles: impl IntoIterator<Item = Triangle>) -> Self {
+        let mut vertices = vec![];
+        let mut ids = HashMap::new();
+
+        let faces = triangles
+            .into_iter()
+            .map(|t| {
+                [t.a, t.b, t.c].map(|p| {
+                    *ids.entry((p.x.to_bits(), p.y.to_bits(), p.z.to_bits()))
+                        .or_insert_with(|| {
+                            vertices.push(p);
+                            vertices.len() - 1
+                        })
+                })
+            })
+            .collect();
+
+        Self::new(vertices, faces)
+    }
+
+    /// Return the vertices of the mesh.
+    pub fn vertices(&self) -> &[Vec3] {
+        &self.vertices
+    }
+
+    /// Return the faces of the mesh as indices into `vertices`.
+    pub fn faces(&self) -> &[[usize; 3]] {
+        &self.faces
+    }
+
+    /// Return the triangle of the given face.
+    pub fn triangle(&self, f: usize) -> Triangle {
+        let [a, b, c] = self.faces[f].map(|v| self.vertices[v]);
+        Triangle::new(a, b, c)
+    }
+
+    /// Return the total number of half edges.
+    pub fn halfedges_len(&self) -> usize {
+        self.twins.len()
+    }
+
+    /// Return the vertex the given half edge starts from.
+    pub fn origin(&self, h: usize) -> usize {
+        self.faces[h / 3][h % 3]
+    }
+
+    /// Return the vertex the given half edge points to.
+    pub fn destination(&self, h: usize) -> usize {
+        self.origin(self.next(h))
+    }
+
+    /// Return the face the given half edge belongs to.
+    pub fn face(&self, h: usize) -> usize {
+        h / 3
+    }
+
+    /// Return the half edge following the given one in its face.
+    pub fn next(&self, h: usize) -> usize {
+        h - h % 3 + (h + 1) % 3
+    }
+
+    /// Return the half edge preceding the given one in its face.
+    pub fn prev(&self, h: usize) -> usize {
+        h - h % 3 + (h + 2) % 3
+    }
+
+    /// Return the half edge going in the opposite direction in the adjacent
+    /// face or `None` if the edge is on the boundary.
+    pub fn twin(&self, h: usize) -> Option<usize> {
+        self.twins[h]
+    }
+
+    /// Return each edge of the mesh once as one of its half edges.
+    pub fn edges(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.twins.len()).filter(|&h| self.twins[h].is_none_or(|t| h < t))
+    }
+
+    /// Return the half edges on the boundary of the mesh, i.e. the ones without
+    /// a twin.
+    pub fn boundary_halfedges(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.twins.len()).filter(|&h| self.twins[h].is_none())
+    }
+
+    /// Return whether the mesh is closed, i.e. every edge is shared by exactly
+    /// two faces.
+    pub fn is_closed(&self) -> bool {
+        self.twins.iter().all(Option::is_some)
+    }
+
+    /// Return whether the given vertex is on the boundary of the mesh.
+    pub fn is_boundary_vertex(&self, v: usize) -> bool {
+        self.outgoing[v].is_none_or(|h| self.twins[h].is_none())
+    }
+
+    /// Return the half edges starting from the given vertex in
+    /// counterclockwise order.
+    pub fn outgoing_halfedges(&self, v: usize) -> Vec<usize> {
+        let Some(start) = self.outgoing[v] else {
+            return vec![];
+        };
+
+        let mut hs = vec![start];
+        let mut h = start;
+        while let Some(t) = self.twins[self.prev(h)] {
+            if t == start || hs.len() >= self.twins.len() {
+                return hs;
+            }
+
+            hs.push(t);
+            h = t;
+        }
+
+        // the fan is open, but the starting half edge is not necessarily the
+        // first one if the vertex is non manifold, walk the other way too
+        let mut h = start;
+        while let Some(t) = self.twins[h] {
+            h = self.next(t);
+            if h == start || hs.len() >= self.twins.len() {
+                break;
+            }
+
+            hs.insert(0, h);
+        }
+
+        hs
+    }
+
+    /// Return the faces around the given vertex in counterclockwise order.
+    pub fn vertex_faces(&self, v: usize) -> Vec<usize> {
+        self.outgoing_halfedges(v)
+            .into_iter()
+            .map(|h| self.face(h))
+            .collect()
+    }
+
+    /// Return the vertices connected to the given vertex by an edge in
+    /// counterclockwise order.
+    pub fn vertex_neighbors(&self, v: usize) -> Vec<usize> {
+        let hs = self.outgoing_halfedges(v);
+
+        let mut neighbors = hs.iter().map(|&h| self.destination(h)).collect::<Vec<_>>();
+
+        // the last face of an open fan has one more neighbor
+        if let Some(&last) = hs.last() {
+            let p = self.prev(last);
+            if self.twins[p].is_none() {
+                neighbors.push(self.origin(p));
+            }
+        }
+
+        neighbors
+    }
+
+    /// Return the faces sharing an edge with the given face, in the same order
+    /// as its edges.
+    pub fn face_neighbors(&self, f: usize) -> [Option<usize>; 3] {
+        [0, 1, 2].map(|i| self.twins[3 * f + i].map(|t| self.face(t)))
+    }
+
+    /// Return the normal of the given face.
+    pub fn face_normal(&self, f: usize) -> Vec3 {
+        self.triangle(f).normal()
+    }
+
+    /// Return the normal of the given vertex as the average of the normals of
+    /// the faces around it weighted by their area.
+    pub fn vertex_normal(&self, v: usize) -> Vec3 {
+        self.vertex_faces(v)
+            .into_iter()
+            .map(|f| {
+                let t = self.triangle(f);
+                (t.b - t.a).cross(t.c - t.a)
+            })
+            .sum::<Vec3>()
+            .normalized()
+    }
+}
+
+impl Mesh for HalfEdgeMesh {
+    fn triangles(&self) -> Box<dyn Iterator<Item = Triangle> + '_> {
+        Box::new((0..self.faces.len()).map(|f| self.triangle(f)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::v3;
+
+    fn tetrahedron() -> HalfEdgeMesh {
+        HalfEdgeMesh::new(
+            vec![v3(0, 0, 0), v3(1, 0, 0), v3(0, 1, 0), v3(0, 0, 1)],
+            vec![[0, 2, 1], [0, 1, 3], [1, 2, 3], [0, 3, 2]],
+        )
+    }
+
+    #[test]
+    fn test_closed() {
+        let mesh = tetrahedron();
+
+        assert!(mesh.is_closed());
+        assert_eq!(mesh.edges().count(), 6);
+        assert_eq!(mesh.boundary_halfedges().count(), 0);
+
+        for h in 0..mesh.halfedges_len() {
+            let t = mesh.twin(h).unwrap();
+            assert_eq!(mesh.twin(t), Some(h));
+            assert_eq!(mesh.origin(t), mesh.destination(h));
+            assert_eq!(mesh.next(mesh.prev(h)), h);
+        }
+
+        let mut faces = mesh.vertex_faces(0);
+        faces.sort_unstable();
+        assert_eq!(faces, vec![0, 1, 3]);
+
+        let mut neighbors = mesh.vertex_neighbors(3);
+        neighbors.sort_unstable();
+        assert_eq!(neighbors, vec![0, 1, 2]);
+
+        assert_eq!(mesh.face_neighbors(0), [Some(3), Some(2), Some(1)]);
+        assert_eq!(mesh.face_normal(0), v3(0, 0, -1));
+
+        let n = mesh.vertex_normal(0);
+        assert!((n - v3(-1, -1, -1).normalized()).norm() < 1e-9);
+    }
+
+    #[test]
+    fn test_open() {
+        // a square made of two triangles
+        let mesh = HalfEdgeMesh::from_triangles([
+            Triangle::new(v3(0, 0, 0), v3(1, 0, 0), v3(1, 1, 0)),
+            Triangle::new(v3(0, 0, 0), v3(1, 1, 0), v3(0, 1, 0)),
+        ]);
+
+        assert_eq!(mesh.vertices().len(), 4);
+        assert!(!mesh.is_closed());
+        assert_eq!(mesh.edges().count(), 5);
+        assert_eq!(mesh.boundary_halfedges().count(), 4);
+
+        assert!(mesh.is_boundary_vertex(0));
+        assert_eq!(mesh.vertex_faces(0), vec![0, 1]);
+        assert_eq!(mesh.vertex_neighbors(0), vec![1, 2, 3]);
+        assert_eq!(mesh.vertex_neighbors(2), vec![3, 0, 1]);
+        assert_eq!(mesh.face_neighbors(1), [Some(0), None, None]);
+
+        assert_eq!(mesh.triangles().count(), 2);
+    }
+}
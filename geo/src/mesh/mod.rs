@@ -1,7 +1,14 @@
+#[cfg(feature = "gltf")]
+pub mod gltf;
+pub mod halfedge;
 pub mod obj;
+pub mod off;
+pub mod repair;
 pub mod stl;
+pub mod subdivision;
 
 use std::{
+    fmt,
     fs::File,
     io::{self, BufReader},
     num,
@@ -52,13 +59,39 @@ pub trait Mesh {
 
         Some(aabb)
     }
+
+    /// Return a hash of the triangles of this mesh that is stable across runs
+    /// and platforms, useful to check whether data derived from the mesh like
+    /// a serialized `KdTree` is still valid.
+    ///
+    /// [FNV-1a][0] is used to hash the bit patterns of the coordinates.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/Fowler%E2%80%93Noll%E2%80%93Vo_hash_function
+    fn content_hash(&self) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+
+        for t in self.triangles() {
+            for p in [t.a, t.b, t.c] {
+                for c in [p.x, p.y, p.z] {
+                    for b in c.to_bits().to_le_bytes() {
+                        hash ^= u64::from(b);
+                        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+                    }
+                }
+            }
+        }
+
+        hash
+    }
 }
 
 /// Load the mesh at `path` trying to guess the format by the file extension.
 ///
-/// STL and OBJ are the only supported formats as of now.
+/// STL, OBJ, OFF, glTF and GLB are the supported formats as of now, the
+/// latter two only with the `gltf` feature enabled.
 pub fn load_mesh(path: impl AsRef<Path>) -> Result<Box<dyn Mesh>> {
-    let ext = path.as_ref().extension().ok_or(Error::BadFormat)?;
+    let path = path.as_ref();
+    let ext = path.extension().ok_or(Error::BadFormat)?;
 
     if ext == "obj" {
         let f = File::open(path)?;
@@ -72,6 +105,20 @@ pub fn load_mesh(path: impl AsRef<Path>) -> Result<Box<dyn Mesh>> {
         return Ok(Box::new(stl::Stl::load(reader)?));
     }
 
+    if ext == "off" {
+        let f = File::open(path)?;
+        let reader = BufReader::new(f);
+        return Ok(Box::new(off::Off::load(reader)?));
+    }
+
+    #[cfg(feature = "gltf")]
+    if ext == "gltf" || ext == "glb" {
+        let f = File::open(path)?;
+        let reader = BufReader::new(f);
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+        return Ok(Box::new(gltf::Gltf::load(reader, base_dir)?));
+    }
+
     Err(Error::BadFormat)
 }
 
@@ -92,3 +139,40 @@ impl From<num::ParseIntError> for Error {
         Error::InvalidNumber
     }
 }
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::BadFormat => write!(f, "malformed or unsupported mesh file"),
+            Error::InvalidNumber => write!(f, "invalid number"),
+            Error::IoError(e) => write!(f, "io error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::IoError(e) => Some(e),
+            Error::BadFormat | Error::InvalidNumber => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_mesh_errors() {
+        let err = load_mesh("mesh.ply").err().unwrap();
+        assert!(matches!(err, Error::BadFormat));
+        assert_eq!(err.to_string(), "malformed or unsupported mesh file");
+        assert!(std::error::Error::source(&err).is_none());
+
+        let err = load_mesh("missing-mesh.stl").err().unwrap();
+        assert!(matches!(err, Error::IoError(_)));
+        assert!(err.to_string().starts_with("io error: "));
+        assert!(std::error::Error::source(&err).is_some());
+    }
+}
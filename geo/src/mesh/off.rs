@@ -0,0 +1,148 @@
+//! This module contains functions to load meshes in the [OFF][0] format.
+//!
+//! [0]: https://segeval.cs.princeton.edu/public/off_format.html
+
+use std::io::BufRead;
+
+use crate::{v3, Triangle, Vec3};
+
+use super::{Error, Mesh, Result};
+
+/// Mesh read from an OFF file.
+///
+/// Only the vertices and the faces are read, any color or normal is ignored.
+/// Faces with more than three vertices are triangulated as fans.
+pub struct Off {
+    vertices: Vec<Vec3>,
+    faces: Vec<[usize; 3]>,
+}
+
+impl Off {
+    /// Try to load an `Off` from the given reader.
+    pub fn load(r: impl BufRead) -> Result<Off> {
+        let mut lines = r
+            .lines()
+            .map(|l| {
+                l.map(|mut l| {
+                    if let Some(i) = l.find('#') {
+                        l.truncate(i);
+                    }
+                    l
+                })
+            })
+            .filter(|l| l.as_ref().map_or(true, |l| !l.trim().is_empty()));
+
+        let header = lines.next().ok_or(Error::BadFormat)??;
+        let mut tokens = header.split_whitespace();
+
+        // the keyword can be prefixed by letters describing additional vertex
+        // data like colors or normals, but higher dimensional meshes are not
+        // supported
+        let keyword = tokens.next().ok_or(Error::BadFormat)?;
+        if !keyword.ends_with("OFF") || keyword.contains('n') || keyword.contains('4') {
+            return Err(Error::BadFormat);
+        }
+
+        // the counts can be either on the same line of the keyword or on the
+        // next one
+        let mut counts = tokens.map(str::to_string).collect::<Vec<_>>();
+        if counts.is_empty() {
+            let l = lines.next().ok_or(Error::BadFormat)??;
+            counts = l.split_whitespace().map(str::to_string).collect();
+        }
+        if counts.len() < 2 {
+            return Err(Error::BadFormat);
+        }
+        let nvertices: usize = counts[0].parse()?;
+        let nfaces: usize = counts[1].parse()?;
+
+        // the counts can't be trusted to preallocate, the vectors grow only as
+        // long as there actually are lines to read
+        let mut mesh = Off {
+            vertices: vec![],
+            faces: vec![],
+        };
+
+        for _ in 0..nvertices {
+            let l = lines.next().ok_or(Error::BadFormat)??;
+            let mut tokens = l.split_whitespace();
+
+            let mut coord =
+                || -> Result<f64> { Ok(tokens.next().ok_or(Error::BadFormat)?.parse()?) };
+            let (x, y, z) = (coord()?, coord()?, coord()?);
+
+            mesh.vertices.push(v3(x, y, z));
+        }
+
+        for _ in 0..nfaces {
+            let l = lines.next().ok_or(Error::BadFormat)??;
+            let mut tokens = l.split_whitespace();
+
+            let n: usize = tokens.next().ok_or(Error::BadFormat)?.parse()?;
+            let face = tokens
+                .take(n)
+                .map(|t| {
+                    let i: usize = t.parse()?;
+                    if i >= mesh.vertices.len() {
+                        return Err(Error::BadFormat);
+                    }
+                    Ok(i)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            if face.len() != n || n < 3 {
+                return Err(Error::BadFormat);
+            }
+
+            for i in 1..n - 1 {
+                mesh.faces.push([face[0], face[i], face[i + 1]]);
+            }
+        }
+
+        Ok(mesh)
+    }
+}
+
+impl Mesh for Off {
+    fn triangles(&self) -> Box<dyn Iterator<Item = Triangle> + '_> {
+        Box::new(self.faces.iter().map(move |&[a, b, c]| {
+            Triangle::new(self.vertices[a], self.vertices[b], self.vertices[c])
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let data = "OFF
+# a unit square made of a single quad and a triangle
+5 2 0
+0 0 0
+1 0 0
+1 1 0
+0 1 0
+0.5 0.5 1 # apex
+4 0 1 2 3
+3 0 1 4 255 0 0
+";
+
+        let off = Off::load(data.as_bytes()).unwrap();
+        let tris = off.triangles().collect::<Vec<_>>();
+
+        assert_eq!(
+            tris,
+            vec![
+                Triangle::new(v3(0, 0, 0), v3(1, 0, 0), v3(1, 1, 0)),
+                Triangle::new(v3(0, 0, 0), v3(1, 1, 0), v3(0, 1, 0)),
+                Triangle::new(v3(0, 0, 0), v3(1, 0, 0), v3(0.5, 0.5, 1.0)),
+            ]
+        );
+
+        assert!(Off::load("OFF 3 1 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 3\n".as_bytes()).is_err());
+        assert!(Off::load("4OFF\n".as_bytes()).is_err());
+        assert!(Off::load(format!("OFF {} {} 0\n", usize::MAX, usize::MAX).as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,426 @@
+//! Find and fix the most common defects of meshes found in the wild.
+//!
+//! Operations like voxelization or deciding whether a point is inside a mesh
+//! assume the mesh is closed and consistently oriented, but plenty of STLs
+//! have degenerate triangles, vertices that are only almost shared between
+//! triangles or triangles facing the wrong way. `validate` reports such
+//! defects while `repair` fixes the ones that can be fixed automatically.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::{Triangle, Vec3};
+
+/// The defects found in a mesh by `validate`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Report {
+    /// Indices of the triangles with (almost) zero area or that collapse once
+    /// their vertices are welded.
+    pub degenerate_triangles: Vec<usize>,
+
+    /// Number of distinct vertices that are closer than the tolerance to
+    /// another vertex and therefore should be the same vertex.
+    pub duplicate_vertices: usize,
+
+    /// Number of edges shared by two triangles that traverse it in the same
+    /// direction, i.e. one of the two triangles has its normal flipped.
+    pub inconsistent_edges: usize,
+
+    /// Number of closed parts of the mesh whose normals point inside.
+    pub inverted_components: usize,
+
+    /// Edges that are used by only one triangle, they're the holes of the
+    /// mesh.
+    pub boundary_edges: Vec<(Vec3, Vec3)>,
+
+    /// Edges that are shared by more than two triangles.
+    pub non_manifold_edges: Vec<(Vec3, Vec3)>,
+}
+
+impl Report {
+    /// Return whether the mesh is a closed, consistently oriented, manifold
+    /// without any degenerate triangles or duplicate vertices.
+    pub fn is_valid(&self) -> bool {
+        self.degenerate_triangles.is_empty()
+            && self.duplicate_vertices == 0
+            && self.inconsistent_edges == 0
+            && self.inverted_components == 0
+            && self.boundary_edges.is_empty()
+            && self.non_manifold_edges.is_empty()
+    }
+}
+
+/// Check the given triangles for defects. Vertices closer than `eps` are
+/// considered to be the same vertex.
+pub fn validate(triangles: &[Triangle], eps: f64) -> Report {
+    let welded = Welded::new(triangles, eps);
+
+    let mut report = Report {
+        duplicate_vertices: welded.duplicates,
+        ..Report::default()
+    };
+
+    for (i, t) in triangles.iter().enumerate() {
+        if is_degenerate(t, &welded.faces[i], eps) {
+            report.degenerate_triangles.push(i);
+        }
+    }
+
+    let faces = welded
+        .faces
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| report.degenerate_triangles.binary_search(i).is_err())
+        .map(|(_, f)| *f)
+        .collect::<Vec<_>>();
+
+    let edges = edges(&faces);
+    for (&(a, b), uses) in &edges {
+        let e = (welded.vertices[a], welded.vertices[b]);
+
+        match uses.as_slice() {
+            [_] => report.boundary_edges.push(e),
+            [(_, d0), (_, d1)] => {
+                if d0 == d1 {
+                    report.inconsistent_edges += 1;
+                }
+            }
+            _ => report.non_manifold_edges.push(e),
+        }
+    }
+
+    let mut faces = faces;
+    orient(&mut faces, &edges);
+    for component in components(&faces, &edges) {
+        if is_closed(&component, &faces, &edges) && volume(&component, &faces, &welded) < 0.0 {
+            report.inverted_components += 1;
+        }
+    }
+
+    report
+}
+
+/// Fix the defects of the given triangles that can be fixed automatically:
+/// vertices closer than `eps` are welded together, degenerate triangles are
+/// removed and the triangles are flipped so that they're consistently oriented
+/// with the normals of closed parts pointing outside.
+///
+/// Holes and non manifold edges are left as is.
+pub fn repair(triangles: &[Triangle], eps: f64) -> Vec<Triangle> {
+    let welded = Welded::new(triangles, eps);
+
+    let mut faces = welded
+        .faces
+        .iter()
+        .zip(triangles)
+        .filter(|(f, t)| !is_degenerate(t, f, eps))
+        .map(|(f, _)| *f)
+        .collect::<Vec<_>>();
+
+    let edges = edges(&faces);
+    orient(&mut faces, &edges);
+
+    for component in components(&faces, &edges) {
+        if is_closed(&component, &faces, &edges) && volume(&component, &faces, &welded) < 0.0 {
+            for &f in &component {
+                faces[f].swap(1, 2);
+            }
+        }
+    }
+
+    faces
+        .iter()
+        .map(|[a, b, c]| {
+            Triangle::new(
+                welded.vertices[*a],
+                welded.vertices[*b],
+                welded.vertices[*c],
+            )
+        })
+        .collect()
+}
+
+/// The triangles of a mesh as indices into a list of unique vertices.
+struct Welded {
+    vertices: Vec<Vec3>,
+    faces: Vec<[usize; 3]>,
+    duplicates: usize,
+}
+
+impl Welded {
+    /// Merge the vertices closer than `eps` by bucketing them in a grid of
+    /// cells of size `eps` so that only the neighboring cells must be checked.
+    fn new(triangles: &[Triangle], eps: f64) -> Self {
+        let cell = |p: Vec3| {
+            let eps = eps.max(f64::EPSILON);
+            (
+                (p.x / eps).floor() as i64,
+                (p.y / eps).floor() as i64,
+                (p.z / eps).floor() as i64,
+            )
+        };
+
+        let mut vertices: Vec<Vec3> = vec![];
+        let mut grid: HashMap<_, Vec<usize>> = HashMap::new();
+        let mut exact = HashMap::new();
+
+        let mut faces = Vec::with_capacity(triangles.len());
+        for t in triangles {
+            let mut face = [0; 3];
+
+            for (f, p) in face.iter_mut().zip([t.a, t.b, t.c]) {
+                let key = (p.x.to_bits(), p.y.to_bits(), p.z.to_bits());
+                if let Some(&i) = exact.get(&key) {
+                    *f = i;
+                    continue;
+                }
+
+                let (cx, cy, cz) = cell(p);
+                let close = (-1..=1)
+                    .flat_map(|dx| {
+                        (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (dx, dy, dz)))
+                    })
+                    .filter_map(|(dx, dy, dz)| grid.get(&(cx + dx, cy + dy, cz + dz)))
+                    .flatten()
+                    .copied()
+                    .find(|&i| vertices[i].dist(p) <= eps);
+
+                *f = close.unwrap_or_else(|| {
+                    vertices.push(p);
+                    grid.entry((cx, cy, cz))
+                        .or_default()
+                        .push(vertices.len() - 1);
+                    vertices.len() - 1
+                });
+                exact.insert(key, *f);
+            }
+
+            faces.push(face);
+        }
+
+        Welded {
+            duplicates: exact.len() - vertices.len(),
+            vertices,
+            faces,
+        }
+    }
+}
+
+fn is_degenerate(t: &Triangle, [a, b, c]: &[usize; 3], eps: f64) -> bool {
+    a == b || b == c || a == c || t.area() <= eps * eps
+}
+
+/// For each undirected edge, identified by its sorted vertices, the faces
+/// using it along with whether they traverse it from the smaller vertex to
+/// the bigger one.
+type Edges = HashMap<(usize, usize), Vec<(usize, bool)>>;
+
+fn edges(faces: &[[usize; 3]]) -> Edges {
+    let mut edges = Edges::new();
+
+    for (fi, f) in faces.iter().enumerate() {
+        for i in 0..3 {
+            let (a, b) = (f[i], f[(i + 1) % 3]);
+            edges
+                .entry((a.min(b), a.max(b)))
+                .or_default()
+                .push((fi, a < b));
+        }
+    }
+
+    edges
+}
+
+/// Return the faces sharing a manifold edge with the given face.
+fn neighbors<'a>(
+    face: &'a [usize; 3],
+    fi: usize,
+    edges: &'a Edges,
+) -> impl Iterator<Item = usize> + 'a {
+    (0..3).filter_map(move |i| {
+        let (a, b) = (face[i], face[(i + 1) % 3]);
+        match edges[&(a.min(b), a.max(b))].as_slice() {
+            [(f0, _), (f1, _)] => Some(if *f0 == fi { *f1 } else { *f0 }),
+            _ => None,
+        }
+    })
+}
+
+/// Flip the faces so that the ones sharing a manifold edge traverse it in
+/// opposite directions, starting from the first face of each connected
+/// component.
+fn orient(faces: &mut [[usize; 3]], edges: &Edges) {
+    let mut visited = vec![false; faces.len()];
+    let mut queue = VecDeque::new();
+
+    for start in 0..faces.len() {
+        if visited[start] {
+            continue;
+        }
+
+        visited[start] = true;
+        queue.push_back(start);
+
+        while let Some(fi) = queue.pop_front() {
+            let face = faces[fi];
+
+            for ni in neighbors(&face, fi, edges).collect::<Vec<_>>() {
+                if visited[ni] {
+                    continue;
+                }
+                visited[ni] = true;
+
+                // the shared edge must be traversed in opposite directions
+                let shared = (0..3)
+                    .map(|i| (face[i], face[(i + 1) % 3]))
+                    .find(|(a, b)| faces[ni].contains(a) && faces[ni].contains(b))
+                    .unwrap();
+                let n = faces[ni];
+                if (0..3).any(|i| (n[i], n[(i + 1) % 3]) == shared) {
+                    faces[ni].swap(1, 2);
+                }
+
+                queue.push_back(ni);
+            }
+        }
+    }
+}
+
+/// Group the faces connected by manifold edges.
+fn components(faces: &[[usize; 3]], edges: &Edges) -> Vec<Vec<usize>> {
+    let mut visited = vec![false; faces.len()];
+    let mut components = vec![];
+
+    for start in 0..faces.len() {
+        if visited[start] {
+            continue;
+        }
+
+        visited[start] = true;
+        let mut component = vec![];
+        let mut stack = vec![start];
+
+        while let Some(fi) = stack.pop() {
+            component.push(fi);
+
+            for ni in neighbors(&faces[fi], fi, edges) {
+                if !visited[ni] {
+                    visited[ni] = true;
+                    stack.push(ni);
+                }
+            }
+        }
+
+        components.push(component);
+    }
+
+    components
+}
+
+fn is_closed(component: &[usize], faces: &[[usize; 3]], edges: &Edges) -> bool {
+    component.iter().all(|&fi| {
+        let f = &faces[fi];
+        (0..3).all(|i| {
+            let (a, b) = (f[i], f[(i + 1) % 3]);
+            edges[&(a.min(b), a.max(b))].len() == 2
+        })
+    })
+}
+
+/// Signed volume enclosed by the given closed component, it's negative if the
+/// normals point inside.
+fn volume(component: &[usize], faces: &[[usize; 3]], welded: &Welded) -> f64 {
+    component
+        .iter()
+        .map(|&fi| {
+            let [a, b, c] = faces[fi].map(|i| welded.vertices[i]);
+            a.dot(b.cross(c))
+        })
+        .sum::<f64>()
+        / 6.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::v3;
+
+    fn cube() -> Vec<Triangle> {
+        let v = |i: usize| {
+            v3(
+                (i & 1) as f64 * 2.0 - 1.0,
+                ((i >> 1) & 1) as f64 * 2.0 - 1.0,
+                ((i >> 2) & 1) as f64 * 2.0 - 1.0,
+            )
+        };
+
+        [
+            [0, 2, 1],
+            [1, 2, 3],
+            [4, 5, 6],
+            [5, 7, 6],
+            [0, 1, 4],
+            [1, 5, 4],
+            [2, 6, 3],
+            [3, 6, 7],
+            [0, 4, 2],
+            [2, 4, 6],
+            [1, 3, 5],
+            [3, 7, 5],
+        ]
+        .iter()
+        .map(|&[a, b, c]| Triangle::new(v(a), v(b), v(c)))
+        .collect()
+    }
+
+    #[test]
+    fn test_valid_cube() {
+        let cube = cube();
+        assert!(validate(&cube, 1e-9).is_valid());
+        assert_eq!(repair(&cube, 1e-9), cube);
+    }
+
+    #[test]
+    fn test_repair() {
+        let mut mesh = cube();
+
+        // flip a triangle, move a vertex slightly off and add a degenerate
+        // triangle
+        let t = &mut mesh[3];
+        *t = Triangle::new(t.a, t.c, t.b);
+        mesh[7].a.x += 1e-7;
+        mesh.push(Triangle::new(v3(1, 1, 1), v3(1, 1, 1), v3(-1, 1, 1)));
+
+        let report = validate(&mesh, 1e-6);
+        assert_eq!(report.degenerate_triangles, vec![12]);
+        assert_eq!(report.duplicate_vertices, 1);
+        assert_eq!(report.inconsistent_edges, 3);
+        assert_eq!(report.inverted_components, 0);
+        assert!(report.boundary_edges.is_empty());
+        assert!(report.non_manifold_edges.is_empty());
+
+        let repaired = repair(&mesh, 1e-6);
+        assert_eq!(repaired.len(), 12);
+        assert!(validate(&repaired, 1e-6).is_valid());
+
+        // a cube inside out is turned the right way
+        let inverted = cube()
+            .into_iter()
+            .map(|t| Triangle::new(t.a, t.c, t.b))
+            .collect::<Vec<_>>();
+        assert_eq!(validate(&inverted, 1e-9).inverted_components, 1);
+        assert_eq!(repair(&inverted, 1e-9), cube());
+    }
+
+    #[test]
+    fn test_open_and_non_manifold() {
+        let mut mesh = cube();
+        mesh.pop();
+        mesh.push(Triangle::new(v3(-1, -1, -1), v3(1, -1, -1), v3(0, -3, 0)));
+
+        let report = validate(&mesh, 1e-9);
+        assert_eq!(report.boundary_edges.len(), 3 + 2);
+        assert_eq!(report.non_manifold_edges.len(), 1);
+        assert!(!report.is_valid());
+    }
+}
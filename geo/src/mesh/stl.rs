@@ -1,11 +1,12 @@
-//! This module contains functions to load [binary and ascii STL].
+//! This module contains functions to load [binary and ascii STL] and to save
+//! binary STL.
 
 use std::{
     convert::TryFrom,
-    io::{BufRead, Seek},
+    io::{self, BufRead, Seek, Write},
 };
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 
 use super::{Error, Mesh, Result};
 use crate::{v3, Triangle, Vec3};
@@ -133,6 +134,30 @@ pub fn load_binary_stl<R: BufRead>(mut r: R) -> Result<([u8; 80], Vec<StlTriangl
     Ok((header, triangles))
 }
 
+/// Write the given triangles as a [binary STL][0] with an empty header. The
+/// normal of each triangle is computed from its vertices.
+///
+/// [0]: https://en.wikipedia.org/wiki/STL_(file_format)#Binary_STL
+pub fn write_binary_stl(mut w: impl Write, triangles: &[Triangle]) -> io::Result<()> {
+    let ntriangles = u32::try_from(triangles.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too many triangles"))?;
+
+    w.write_all(&[0; 80])?;
+    w.write_u32::<LittleEndian>(ntriangles)?;
+
+    for t in triangles {
+        for v in [t.normal(), t.a, t.b, t.c] {
+            w.write_f32::<LittleEndian>(v.x as f32)?;
+            w.write_f32::<LittleEndian>(v.y as f32)?;
+            w.write_f32::<LittleEndian>(v.z as f32)?;
+        }
+
+        w.write_u16::<LittleEndian>(0)?;
+    }
+
+    Ok(())
+}
+
 /// Load a [ASCII STL][0] from a given string. Return a tuple composed of the
 /// solid name and the triangles of the STL.
 ///
@@ -329,6 +354,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_write_binary_stl() {
+        let tris = vec![
+            Triangle::new(Vec3::zero(), v3(1, 0, 0), v3(0, 1, 0)),
+            Triangle::new(v3(0, 0, 1), v3(0, 1, 0), v3(1, 0, 0)),
+        ];
+
+        let mut out = vec![];
+        write_binary_stl(&mut out, &tris).unwrap();
+        assert_eq!(out.len(), 84 + 50 * tris.len());
+
+        let (_, loaded) = load_binary_stl(BufReader::new(Cursor::new(out))).unwrap();
+        assert_eq!(
+            loaded.into_iter().map(|t| t.triangle).collect::<Vec<_>>(),
+            tris
+        );
+    }
+
     #[test]
     fn test_load_ascii_stl() {
         let stl = r"solid cube_corner
@@ -0,0 +1,133 @@
+//! Smooth triangle meshes with [Loop subdivision][0].
+//!
+//! Each step splits every triangle into four and moves the vertices towards a
+//! weighted average of their neighbors so that, by repeating it, a coarse
+//! control cage converges to a smooth surface.
+//!
+//! [0]: https://en.wikipedia.org/wiki/Loop_subdivision_surface
+
+use std::collections::HashMap;
+
+use super::halfedge::HalfEdgeMesh;
+use crate::Vec3;
+
+/// Subdivide the given mesh `levels` times with Loop subdivision. Boundary
+/// edges are kept as boundaries and smoothed as curves on their own.
+pub fn loop_subdivision(mesh: &HalfEdgeMesh, levels: u32) -> HalfEdgeMesh {
+    let mut mesh = mesh.clone();
+    for _ in 0..levels {
+        mesh = loop_step(&mesh);
+    }
+    mesh
+}
+
+fn loop_step(mesh: &HalfEdgeMesh) -> HalfEdgeMesh {
+    let vs = mesh.vertices();
+
+    let mut vertices = (0..vs.len())
+        .map(|v| {
+            let neighbors = mesh.vertex_neighbors(v);
+
+            if neighbors.is_empty() {
+                return vs[v];
+            }
+
+            if mesh.is_boundary_vertex(v) {
+                let (first, last) = (neighbors[0], neighbors[neighbors.len() - 1]);
+                return vs[v] * 0.75 + (vs[first] + vs[last]) * 0.125;
+            }
+
+            let n = neighbors.len() as f64;
+            let beta = if neighbors.len() == 3 {
+                3.0 / 16.0
+            } else {
+                3.0 / (8.0 * n)
+            };
+
+            vs[v] * (1.0 - n * beta) + neighbors.iter().map(|&u| vs[u]).sum::<Vec3>() * beta
+        })
+        .collect::<Vec<_>>();
+
+    // a new vertex for each edge shared by the half edges on it
+    let mut edge_vertices = HashMap::new();
+    for h in mesh.edges() {
+        let (a, b) = (vs[mesh.origin(h)], vs[mesh.destination(h)]);
+
+        let p = match mesh.twin(h) {
+            None => (a + b) * 0.5,
+            Some(t) => {
+                let c = vs[mesh.origin(mesh.prev(h))];
+                let d = vs[mesh.origin(mesh.prev(t))];
+                (a + b) * 0.375 + (c + d) * 0.125
+            }
+        };
+
+        vertices.push(p);
+        edge_vertices.insert(h, vertices.len() - 1);
+        if let Some(t) = mesh.twin(h) {
+            edge_vertices.insert(t, vertices.len() - 1);
+        }
+    }
+
+    let mut faces = Vec::with_capacity(mesh.faces().len() * 4);
+    for (f, &[a, b, c]) in mesh.faces().iter().enumerate() {
+        let [ab, bc, ca] = [0, 1, 2].map(|i| edge_vertices[&(3 * f + i)]);
+
+        faces.push([a, ab, ca]);
+        faces.push([b, bc, ab]);
+        faces.push([c, ca, bc]);
+        faces.push([ab, bc, ca]);
+    }
+
+    HalfEdgeMesh::new(vertices, faces)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{v3, Triangle};
+
+    #[test]
+    fn test_closed() {
+        let cage = HalfEdgeMesh::new(
+            vec![v3(0, 0, 0), v3(1, 0, 0), v3(0, 1, 0), v3(0, 0, 1)],
+            vec![[0, 2, 1], [0, 1, 3], [1, 2, 3], [0, 3, 2]],
+        );
+
+        let mesh = loop_subdivision(&cage, 2);
+        assert_eq!(mesh.faces().len(), 4 * 16);
+        assert_eq!(mesh.vertices().len(), 4 + 6 + 24);
+        assert!(mesh.is_closed());
+
+        // the surface shrinks inside the cage, but it keeps facing outside
+        let center = v3(0.25, 0.25, 0.25);
+        for f in 0..mesh.faces().len() {
+            let t = mesh.triangle(f);
+            assert!(t.normal().dot(t.centroid() - center) > 0.0);
+        }
+
+        // the original vertices are moved towards their neighbors
+        let mesh = loop_subdivision(&cage, 1);
+        assert_eq!(mesh.vertices()[0], v3(0.1875, 0.1875, 0.1875));
+    }
+
+    #[test]
+    fn test_boundary() {
+        let cage = HalfEdgeMesh::from_triangles([
+            Triangle::new(v3(0, 0, 0), v3(1, 0, 0), v3(1, 1, 0)),
+            Triangle::new(v3(0, 0, 0), v3(1, 1, 0), v3(0, 1, 0)),
+        ]);
+
+        let mesh = loop_subdivision(&cage, 1);
+        assert_eq!(mesh.faces().len(), 8);
+        assert_eq!(mesh.boundary_halfedges().count(), 8);
+
+        assert_eq!(mesh.vertices()[0], v3(0.125, 0.125, 0.0));
+        assert!(mesh.vertices().iter().all(|v| v.z == 0.0));
+
+        // boundary edges are split at their midpoint
+        assert!(mesh.vertices().contains(&v3(0.5, 0.0, 0.0)));
+        assert!(mesh.vertices().contains(&v3(0.5, 0.5, 0.0)));
+    }
+}
@@ -0,0 +1,11 @@
+//! The types and functions used by most sketches so that they can be brought
+//! in scope with a single `use geo::prelude::*`.
+
+pub use crate::{
+    mat4::Mat4,
+    primitive::polyline::Polyline,
+    ray::Ray,
+    sdf::{self, Sdf},
+    spatial_index::Shape,
+    v2, v3, Aabb, Axis, Plane, Polyline2, Quat, Sphere, Triangle, Vec2, Vec3,
+};
@@ -1,6 +1,6 @@
 use std::ops::Mul;
 
-use crate::{mat4::Mat4, ray::Ray, v3, Vec3};
+use crate::{mat4::Mat4, ray::Ray, v3, Axis, Vec3};
 
 /// An [Axis aligned bounding box][0] useful for approximating the boundary of
 /// shapes.
@@ -68,6 +68,45 @@ impl Aabb {
         self.max - self.min
     }
 
+    /// Return the surface area of the bounding box.
+    pub fn surface_area(&self) -> f64 {
+        let d = self.dimensions();
+        2.0 * (d.x * d.y + d.x * d.z + d.y * d.z)
+    }
+
+    /// Return the volume of the bounding box.
+    pub fn volume(&self) -> f64 {
+        let d = self.dimensions();
+        d.x * d.y * d.z
+    }
+
+    /// Return an iterator over the 8 corners of the bounding box.
+    ///
+    /// The corners are sorted so that the i-th corner takes the x coordinate
+    /// from `max` if the first bit of i is set and from `min` otherwise and
+    /// the same goes for y and the second bit and z and the third bit.
+    pub fn corners(&self) -> impl Iterator<Item = Vec3> {
+        let (min, max) = (self.min, self.max);
+
+        (0..8).map(move |i| {
+            v3(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            )
+        })
+    }
+
+    /// Return a new bounding box enlarged by the given margin in all the
+    /// directions. A negative margin shrinks the bounding box instead, but
+    /// it's up to the caller to ensure it doesn't shrink past its center.
+    pub fn padded(&self, margin: f64) -> Self {
+        Self {
+            min: self.min - margin,
+            max: self.max + margin,
+        }
+    }
+
     /// Expand the bounding box so that it covers the given point too.
     pub fn expand(&mut self, p: Vec3) {
         if p.x < self.min.x {
@@ -148,42 +187,47 @@ impl Aabb {
     /// of the first and last point of intersections. Note that a negative t
     /// means that the intersection is behind the ray's origin.
     pub fn ray_intersection(&self, ray: &Ray) -> Option<(f64, f64)> {
-        let max = self.max;
-        let min = self.min;
-
-        let mut tmin = (min.x - ray.origin.x) / ray.dir.x;
-        let mut tmax = (max.x - ray.origin.x) / ray.dir.x;
-        if tmin > tmax {
-            std::mem::swap(&mut tmin, &mut tmax);
-        }
-
-        let mut tymin = (min.y - ray.origin.y) / ray.dir.y;
-        let mut tymax = (max.y - ray.origin.y) / ray.dir.y;
-        if tymin > tymax {
-            std::mem::swap(&mut tymin, &mut tymax);
-        }
+        self.ray_intersection_inv(ray.origin, ray.inv_dir())
+    }
 
-        if tmin > tymax || tymin > tmax {
-            return None;
-        }
+    /// Like `ray_intersection`, but for a ray given by its origin and by the
+    /// inverse of its direction as returned by `Ray::inv_dir`, so that it can
+    /// be computed once per ray when checking many boxes.
+    ///
+    /// Rays parallel to an axis, i.e. whose inverse direction is infinite
+    /// along it, are handled explicitly instead of producing NaNs and rays
+    /// with NaN components never intersect.
+    pub fn ray_intersection_inv(&self, origin: Vec3, inv_dir: Vec3) -> Option<(f64, f64)> {
+        let mut tmin = f64::NEG_INFINITY;
+        let mut tmax = f64::INFINITY;
+
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            let (o, inv) = (origin[axis], inv_dir[axis]);
+            let (min, max) = (self.min[axis], self.max[axis]);
+
+            if o.is_nan() || inv.is_nan() {
+                return None;
+            }
 
-        tmin = tmin.max(tymin);
-        tmax = tmax.min(tymax);
+            // the ray is parallel to the slab, either it's always inside it
+            // or it never is
+            if inv.is_infinite() {
+                if o < min || o > max {
+                    return None;
+                }
+                continue;
+            }
 
-        let mut tzmin = (min.z - ray.origin.z) / ray.dir.z;
-        let mut tzmax = (max.z - ray.origin.z) / ray.dir.z;
-        if tzmin > tzmax {
-            std::mem::swap(&mut tzmin, &mut tzmax);
+            let (t0, t1) = ((min - o) * inv, (max - o) * inv);
+            tmin = tmin.max(t0.min(t1));
+            tmax = tmax.min(t0.max(t1));
         }
 
-        if tmin > tzmax || tzmin > tmax {
-            return None;
+        if tmin <= tmax {
+            Some((tmin, tmax))
+        } else {
+            None
         }
-
-        tmin = tmin.max(tzmin);
-        tmax = tmax.min(tzmax);
-
-        Some((tmin, tmax))
     }
 
     /// Get the bounding sphere of this `Aabb`.
@@ -235,6 +279,8 @@ impl Mul<&Mat4> for Aabb {
 mod tests {
     use super::*;
 
+    use proptest::prelude::*;
+
     #[test]
     fn test_basic_operations() {
         let mut aabb = Aabb::new(Vec3::zero());
@@ -338,6 +384,51 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_measures() {
+        let aabb = Aabb::with_dimensions(v3(-1.0, 0.0, 2.0), v3(1, 2, 3));
+
+        assert_eq!(aabb.surface_area(), 22.0);
+        assert_eq!(aabb.volume(), 6.0);
+
+        assert_eq!(Aabb::new(v3(1, 2, 3)).surface_area(), 0.0);
+        assert_eq!(Aabb::new(v3(1, 2, 3)).volume(), 0.0);
+    }
+
+    #[test]
+    fn test_corners() {
+        let aabb = Aabb::with_dimensions(Vec3::zero(), v3(1, 2, 3));
+
+        assert_eq!(
+            aabb.corners().collect::<Vec<_>>(),
+            vec![
+                v3(0, 0, 0),
+                v3(1, 0, 0),
+                v3(0, 2, 0),
+                v3(1, 2, 0),
+                v3(0, 0, 3),
+                v3(1, 0, 3),
+                v3(0, 2, 3),
+                v3(1, 2, 3),
+            ]
+        );
+        assert_eq!(Aabb::from_points(aabb.corners()), Some(aabb));
+    }
+
+    #[test]
+    fn test_padded() {
+        let aabb = Aabb::with_dimensions(Vec3::zero(), v3(1, 2, 3));
+
+        assert_eq!(
+            aabb.padded(0.5),
+            Aabb {
+                min: v3(-0.5, -0.5, -0.5),
+                max: v3(1.5, 2.5, 3.5),
+            }
+        );
+        assert_eq!(aabb.padded(1.0).padded(-1.0), aabb);
+    }
+
     #[test]
     fn test_contains() {
         let aabb = Aabb::from_points(vec![Vec3::zero(), v3(-10.0, 2.0, 3.0)]).unwrap();
@@ -388,4 +479,120 @@ mod tests {
             Some((-1.0, 0.0))
         );
     }
+
+    #[test]
+    fn test_ray_intersection_axis_parallel() {
+        let aabb = Aabb::from_points(vec![Vec3::zero(), v3(1, 1, 1)]).unwrap();
+
+        // along the faces and the edges of the box
+        assert_eq!(
+            aabb.ray_intersection(&Ray::new(v3(-1, 0, 0), v3(1, 0, 0))),
+            Some((1.0, 2.0))
+        );
+        assert_eq!(
+            aabb.ray_intersection(&Ray::new(v3(-1.0, 1.0, 0.5), v3(1.0, -0.0, 0.0))),
+            Some((1.0, 2.0))
+        );
+        assert_eq!(
+            aabb.ray_intersection(&Ray::new(v3(0.5, 0.5, 3.0), v3(-0.0, 0.0, -2.0))),
+            Some((1.0, 1.5))
+        );
+
+        // parallel, but outside of the slab
+        assert_eq!(
+            aabb.ray_intersection(&Ray::new(v3(-1.0, 1.5, 0.5), v3(1, 0, 0))),
+            None
+        );
+
+        // flat boxes
+        let flat = Aabb::from_points(vec![Vec3::zero(), v3(1, 1, 0)]).unwrap();
+        assert_eq!(
+            flat.ray_intersection(&Ray::new(v3(0.5, 0.5, 1.0), v3(0, 0, -1))),
+            Some((1.0, 1.0))
+        );
+        assert_eq!(
+            flat.ray_intersection(&Ray::new(v3(-1.0, 0.5, 0.0), v3(1, 0, 0))),
+            Some((1.0, 2.0))
+        );
+
+        // degenerate rays
+        assert_eq!(
+            aabb.ray_intersection(&Ray::new(v3(0.5, 0.5, 0.5), Vec3::zero())),
+            Some((f64::NEG_INFINITY, f64::INFINITY))
+        );
+        assert_eq!(
+            aabb.ray_intersection(&Ray::new(v3(0.5, f64::NAN, 0.5), v3(1, 1, 1))),
+            None
+        );
+        assert_eq!(
+            aabb.ray_intersection(&Ray::new(v3(-1, 0, 0), v3(1.0, f64::NAN, 0.0))),
+            None
+        );
+    }
+
+    /// Reference implementation of the slab method dividing by each component
+    /// of the direction and checking the components that are zero separately.
+    fn reference_ray_intersection(aabb: &Aabb, ray: &Ray) -> Option<(f64, f64)> {
+        let mut range = (f64::NEG_INFINITY, f64::INFINITY);
+
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            let (o, d) = (ray.origin[axis], ray.dir[axis]);
+            let (min, max) = (aabb.min[axis], aabb.max[axis]);
+
+            if d == 0.0 {
+                if o < min || o > max {
+                    return None;
+                }
+                continue;
+            }
+
+            let (t0, t1) = ((min - o) / d, (max - o) / d);
+            let (t0, t1) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+
+            range = (range.0.max(t0), range.1.min(t1));
+        }
+
+        Some(range).filter(|(t0, t1)| t0 <= t1)
+    }
+
+    fn coord() -> impl Strategy<Value = f64> {
+        prop_oneof![Just(0.0), Just(-0.0), Just(1.0), Just(-1.0), -100.0..100.0,]
+    }
+
+    fn point() -> impl Strategy<Value = Vec3> {
+        (coord(), coord(), coord()).prop_map(|(x, y, z)| v3(x, y, z))
+    }
+
+    proptest! {
+        #[test]
+        fn prop_ray_intersection_matches_reference(
+            a in point(),
+            b in point(),
+            origin in point(),
+            dir in point(),
+        ) {
+            let aabb = Aabb::new(a).expanded(b);
+            let ray = Ray::new(origin, dir);
+
+            let expected = reference_ray_intersection(&aabb, &ray);
+            let actual = aabb.ray_intersection(&ray);
+
+            let close = |a: f64, b: f64| a == b || (a - b).abs() <= 1e-9 * a.abs().max(1.0);
+
+            match (expected, actual) {
+                (Some((e0, e1)), Some((a0, a1))) => {
+                    prop_assert!(close(e0, a0) && close(e1, a1), "{expected:?} {actual:?}");
+                }
+                // rays grazing the box might disagree because of rounding
+                (Some((t0, t1)), None) | (None, Some((t0, t1))) => {
+                    prop_assert!(close(t0, t1), "{expected:?} {actual:?}");
+                }
+                (None, None) => {}
+            }
+
+            if let Some((t0, t1)) = actual {
+                prop_assert!(!t0.is_nan() && !t1.is_nan());
+            }
+        }
+    }
 }
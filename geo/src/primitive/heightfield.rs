@@ -0,0 +1,308 @@
+use crate::{ray::Ray, spatial_index::Shape, v3, Aabb, Vec2, Vec3};
+
+/// A `Heightfield` is a surface defined by a regular grid of heights over the
+/// XY plane, the heights are along the Z axis.
+///
+/// The surface between the samples is bilinearly interpolated and it's
+/// intersected exactly without converting it to triangles first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heightfield {
+    min: Vec2,
+    max: Vec2,
+    width: usize,
+    height: usize,
+    heights: Vec<f64>,
+    zrange: (f64, f64),
+}
+
+impl Heightfield {
+    /// Create a new `Heightfield` spanning the rectangle from `min` to `max`
+    /// with `width` x `height` samples given row by row.
+    pub fn new(min: Vec2, max: Vec2, width: usize, height: usize, heights: Vec<f64>) -> Self {
+        assert!(width >= 2 && height >= 2);
+        assert_eq!(heights.len(), width * height);
+        assert!(min.x < max.x && min.y < max.y);
+
+        let zrange = heights
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &z| {
+                (lo.min(z), hi.max(z))
+            });
+
+        Self {
+            min,
+            max,
+            width,
+            height,
+            heights,
+            zrange,
+        }
+    }
+
+    /// Create a new `Heightfield` by sampling the given function at `width` x
+    /// `height` points evenly spaced between `min` and `max`.
+    pub fn from_fn(
+        min: Vec2,
+        max: Vec2,
+        (width, height): (usize, usize),
+        f: impl Fn(f64, f64) -> f64,
+    ) -> Self {
+        assert!(width >= 2 && height >= 2);
+
+        let cell = cell_size(min, max, width, height);
+
+        let mut heights = Vec::with_capacity(width * height);
+        for j in 0..height {
+            for i in 0..width {
+                heights.push(f(min.x + cell.x * i as f64, min.y + cell.y * j as f64));
+            }
+        }
+
+        Self::new(min, max, width, height, heights)
+    }
+
+    /// Return the number of samples along the X and Y axis.
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Return the point of the sample at the given column and row.
+    pub fn vertex(&self, i: usize, j: usize) -> Vec3 {
+        let cell = self.cell_size();
+        v3(
+            self.min.x + cell.x * i as f64,
+            self.min.y + cell.y * j as f64,
+            self.heights[j * self.width + i],
+        )
+    }
+
+    /// Return the interpolated height at the given point which is clamped to
+    /// the extent of the `Heightfield`.
+    pub fn height_at(&self, x: f64, y: f64) -> f64 {
+        let (i, j, u, v) = self.locate(x, y);
+        let [h00, h10, h01, h11] = self.cell_heights(i, j);
+
+        h00 * (1.0 - u) * (1.0 - v) + h10 * u * (1.0 - v) + h01 * (1.0 - u) * v + h11 * u * v
+    }
+
+    /// Return the normal of the surface at the given point, only its X and Y
+    /// coordinates are considered.
+    pub fn normal_at(&self, p: Vec3) -> Vec3 {
+        let (i, j, u, v) = self.locate(p.x, p.y);
+        let [h00, h10, h01, h11] = self.cell_heights(i, j);
+        let cell = self.cell_size();
+
+        let c = h00 - h10 - h01 + h11;
+        let dx = (h10 - h00 + c * v) / cell.x;
+        let dy = (h01 - h00 + c * u) / cell.y;
+
+        v3(-dx, -dy, 1.0).normalized()
+    }
+
+    fn cell_size(&self) -> Vec2 {
+        cell_size(self.min, self.max, self.width, self.height)
+    }
+
+    fn cell_heights(&self, i: usize, j: usize) -> [f64; 4] {
+        let w = self.width;
+        [
+            self.heights[j * w + i],
+            self.heights[j * w + i + 1],
+            self.heights[(j + 1) * w + i],
+            self.heights[(j + 1) * w + i + 1],
+        ]
+    }
+
+    /// Return the cell containing the given point along with the local
+    /// coordinates of the point inside it.
+    fn locate(&self, x: f64, y: f64) -> (usize, usize, f64, f64) {
+        let cell = self.cell_size();
+        let fx = ((x - self.min.x) / cell.x).clamp(0.0, (self.width - 1) as f64);
+        let fy = ((y - self.min.y) / cell.y).clamp(0.0, (self.height - 1) as f64);
+
+        let i = (fx.floor() as usize).min(self.width - 2);
+        let j = (fy.floor() as usize).min(self.height - 2);
+
+        (i, j, fx - i as f64, fy - j as f64)
+    }
+
+    /// Intersect the bilinear patch of the given cell with the ray in the [t0,
+    /// t1] interval.
+    fn cell_intersection(&self, ray: &Ray, i: usize, j: usize, t0: f64, t1: f64) -> Option<f64> {
+        let [h00, h10, h01, h11] = self.cell_heights(i, j);
+
+        // quickly discard the cell if the ray is always above or below it
+        let (z0, z1) = (ray.point_at(t0).z, ray.point_at(t1).z);
+        let (lo, hi) = (
+            h00.min(h10).min(h01).min(h11),
+            h00.max(h10).max(h01).max(h11),
+        );
+        if z0.min(z1) > hi || z0.max(z1) < lo {
+            return None;
+        }
+
+        // the local coordinates of the ray inside the cell are linear in t,
+        // hence the difference between the ray height and the patch height is
+        // a quadratic in t
+        let cell = self.cell_size();
+        let origin = self.vertex(i, j);
+        let (u0, du) = ((ray.origin.x - origin.x) / cell.x, ray.dir.x / cell.x);
+        let (v0, dv) = ((ray.origin.y - origin.y) / cell.y, ray.dir.y / cell.y);
+
+        let (a, b, c) = (h10 - h00, h01 - h00, h00 - h10 - h01 + h11);
+
+        let qa = -c * du * dv;
+        let qb = ray.dir.z - a * du - b * dv - c * (u0 * dv + v0 * du);
+        let qc = ray.origin.z - h00 - a * u0 - b * v0 - c * u0 * v0;
+
+        const EPS: f64 = 1e-9;
+        let valid = |t: f64| t > EPS && t >= t0 - EPS && t <= t1 + EPS;
+
+        if qa.abs() < EPS {
+            if qb.abs() < EPS {
+                return None;
+            }
+
+            return Some(-qc / qb).filter(|&t| valid(t));
+        }
+
+        let disc = qb * qb - 4.0 * qa * qc;
+        if disc < 0.0 {
+            return None;
+        }
+
+        let sq = disc.sqrt();
+        let (r0, r1) = ((-qb - sq) / (2.0 * qa), (-qb + sq) / (2.0 * qa));
+
+        [r0.min(r1), r0.max(r1)].into_iter().find(|&t| valid(t))
+    }
+}
+
+impl Shape for Heightfield {
+    type Intersection = f64;
+
+    /// Walk the cells crossed by the ray in order using a 2D DDA and return the
+    /// first intersection with the surface.
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        let (tmin, tmax) = self.bbox().ray_intersection(ray)?;
+        if tmax < 0.0 {
+            return None;
+        }
+
+        let t_start = tmin.max(0.0);
+        let cell = self.cell_size();
+
+        let p = ray.point_at(t_start);
+        let (mut i, mut j, _, _) = self.locate(p.x, p.y);
+
+        // t parameter of the next cell boundary and how much it changes
+        // between boundaries along each axis
+        let axis = |dir: f64, o: f64, start: f64, size: f64, k: usize| {
+            if dir == 0.0 {
+                return (f64::INFINITY, f64::INFINITY);
+            }
+
+            let boundary = start + size * (k + usize::from(dir > 0.0)) as f64;
+            ((boundary - o) / dir, size / dir.abs())
+        };
+        let (mut tx, dtx) = axis(ray.dir.x, ray.origin.x, self.min.x, cell.x, i);
+        let (mut ty, dty) = axis(ray.dir.y, ray.origin.y, self.min.y, cell.y, j);
+
+        let mut t = t_start;
+        loop {
+            let t_exit = tx.min(ty).min(tmax);
+            if let Some(hit) = self.cell_intersection(ray, i, j, t, t_exit) {
+                return Some(hit);
+            }
+
+            if t_exit >= tmax {
+                return None;
+            }
+
+            t = t_exit;
+            if tx < ty {
+                if ray.dir.x > 0.0 {
+                    i += 1;
+                } else {
+                    i = i.checked_sub(1)?;
+                }
+                tx += dtx;
+            } else {
+                if ray.dir.y > 0.0 {
+                    j += 1;
+                } else {
+                    j = j.checked_sub(1)?;
+                }
+                ty += dty;
+            }
+
+            if i >= self.width - 1 || j >= self.height - 1 {
+                return None;
+            }
+        }
+    }
+
+    fn bbox(&self) -> Aabb {
+        Aabb::new(v3(self.min.x, self.min.y, self.zrange.0)).expanded(v3(
+            self.max.x,
+            self.max.y,
+            self.zrange.1,
+        ))
+    }
+}
+
+fn cell_size(min: Vec2, max: Vec2, width: usize, height: usize) -> Vec2 {
+    (max - min) / Vec2::new((width - 1) as f64, (height - 1) as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::v2;
+
+    #[test]
+    fn test_height_at() {
+        let hf = Heightfield::new(v2(0, 0), v2(2, 1), 3, 2, vec![0.0, 1.0, 0.0, 2.0, 3.0, 2.0]);
+
+        assert_eq!(hf.height_at(0.0, 0.0), 0.0);
+        assert_eq!(hf.height_at(1.0, 1.0), 3.0);
+        assert_eq!(hf.height_at(0.5, 0.5), 1.5);
+        assert_eq!(hf.height_at(1.5, 0.0), 0.5);
+        assert_eq!(hf.height_at(10.0, -5.0), 0.0);
+
+        assert_eq!(hf.vertex(1, 1), v3(1, 1, 3));
+        assert_eq!(
+            hf.normal_at(v3(0.5, 0.2, 0.0)),
+            v3(-1.0, -2.0, 1.0).normalized()
+        );
+    }
+
+    #[test]
+    fn test_intersection() {
+        let hf = Heightfield::from_fn(v2(-5, -5), v2(5, 5), (21, 21), |x, y| {
+            (x * 0.7).sin() + (y * 0.3).cos()
+        });
+
+        // straight down
+        let ray = Ray::new(v3(1.2, -3.4, 10.0), v3(0, 0, -1));
+        let t = hf.intersection(&ray).unwrap();
+        assert!((t - (10.0 - hf.height_at(1.2, -3.4))).abs() < 1e-9);
+
+        // slanted rays crossing many cells must hit the surface exactly
+        for dir in [v3(1.0, 0.3, -0.2), v3(-0.5, -1.0, -0.1), v3(0.0, 1.0, -0.3)] {
+            let target = v3(0.1, 0.2, hf.height_at(0.1, 0.2));
+            let ray = Ray::new(target - dir * 5.0, dir);
+
+            let t = hf.intersection(&ray).unwrap();
+            assert!(t <= 5.0 + 1e-9);
+
+            let p = ray.point_at(t);
+            assert!((p.z - hf.height_at(p.x, p.y)).abs() < 1e-6, "{p:?}");
+        }
+
+        // above the surface and outside of it
+        assert_eq!(hf.intersection(&Ray::new(v3(0, 0, 10), v3(1, 0, 0))), None);
+        assert_eq!(hf.intersection(&Ray::new(v3(6, 0, 10), v3(0, 0, -1))), None);
+    }
+}
@@ -1,4 +1,4 @@
-use std::ops::Mul;
+use std::{f64::consts::PI, ops::Mul};
 
 use crate::{v3, Vec3};
 
@@ -80,6 +80,101 @@ impl Mat4 {
         }
     }
 
+    /// Create a view matrix that transforms points from world space to the
+    /// space of a camera positioned at `eye` looking at `target`. `up` is the
+    /// direction that will point upwards in the camera space.
+    ///
+    /// The camera looks towards the negative z axis of its space.
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        let f = (target - eye).normalized();
+        let s = f.cross(up).normalized();
+        let u = s.cross(f).normalized();
+
+        Mat4 {
+            data: [
+                [s.x, s.y, s.z, -s.dot(eye)],
+                [u.x, u.y, u.z, -u.dot(eye)],
+                [-f.x, -f.y, -f.z, f.dot(eye)],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    /// Create a [perspective projection][0] matrix given the vertical field of
+    /// view in degrees, the aspect ratio and the distance of the near and far
+    /// planes.
+    ///
+    /// The visible points in camera space are mapped into the cube from
+    /// (-1,-1,-1) to (1,1,1) once `Mat4::project_point` performs the
+    /// perspective divide.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/3D_projection#Perspective_projection
+    #[rustfmt::skip]
+    pub fn perspective(fovy: f64, aspect: f64, near: f64, far: f64) -> Self {
+        let ymax = near * (fovy * PI / 360.0).tan();
+        let xmax = ymax * aspect;
+
+        let t1 = 2.0 * near;
+        let t2 = 2.0 * xmax;
+        let t3 = 2.0 * ymax;
+        let t4 = far - near;
+
+        Mat4 {
+            data: [
+                [t1 / t2, 0.0,     0.0,                0.0],
+                [0.0,     t1 / t3, 0.0,                0.0],
+                [0.0,     0.0,     (-far - near) / t4, -t1 * far / t4],
+                [0.0,     0.0,     -1.0,               0.0],
+            ],
+        }
+    }
+
+    /// Create an [orthographic projection][0] matrix that maps the box with
+    /// the given bounds in camera space into the cube from (-1,-1,-1) to
+    /// (1,1,1).
+    ///
+    /// Like `Mat4::perspective` the near and far planes are the distances
+    /// along the negative z axis.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/Orthographic_projection
+    #[rustfmt::skip]
+    pub fn orthographic(
+        left: f64,
+        right: f64,
+        bottom: f64,
+        top: f64,
+        near: f64,
+        far: f64,
+    ) -> Self {
+        let w = right - left;
+        let h = top - bottom;
+        let d = far - near;
+
+        Mat4 {
+            data: [
+                [2.0 / w, 0.0,     0.0,      -(right + left) / w],
+                [0.0,     2.0 / h, 0.0,      -(top + bottom) / h],
+                [0.0,     0.0,     -2.0 / d, -(far + near) / d],
+                [0.0,     0.0,     0.0,      1.0],
+            ],
+        }
+    }
+
+    /// Transform the given point by this matrix including the homogeneous
+    /// divide which is required by projection matrices, see
+    /// `Mat4::perspective`.
+    pub fn project_point(&self, p: Vec3) -> Vec3 {
+        (p * self) / self.homogeneous_w(p)
+    }
+
+    /// Return the homogeneous coordinate of the given point once transformed
+    /// by this matrix, it's not positive for the points behind the camera of a
+    /// projection matrix.
+    pub fn homogeneous_w(&self, p: Vec3) -> f64 {
+        let m = &self.data;
+        m[3][0] * p.x + m[3][1] * p.y + m[3][2] * p.z + m[3][3]
+    }
+
     /// Return the transpose of the matrix.
     #[allow(clippy::needless_range_loop)]
     pub fn transpose(&self) -> Self {
@@ -181,3 +276,42 @@ impl Mul<&Mat4> for Vec3 {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_approx_eq(a: Vec3, b: Vec3) {
+        assert!(a.dist(b) < 1e-9, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn test_look_at() {
+        let eye = v3(1, 2, 3);
+        let view = Mat4::look_at(eye, v3(1, 2, -7), v3(0, 1, 0));
+
+        assert_approx_eq(eye * &view, Vec3::zero());
+        assert_approx_eq(v3(1, 2, -7) * &view, v3(0, 0, -10));
+        assert_approx_eq(v3(1, 3, 3) * &view, v3(0, 1, 0));
+        assert_approx_eq(v3(2, 2, 3) * &view, v3(1, 0, 0));
+    }
+
+    #[test]
+    fn test_perspective() {
+        let proj = Mat4::perspective(90.0, 2.0, 1.0, 10.0);
+
+        assert_approx_eq(proj.project_point(v3(0, 0, -1)), v3(0, 0, -1));
+        assert_approx_eq(proj.project_point(v3(0, 0, -10)), v3(0, 0, 1));
+        assert_approx_eq(proj.project_point(v3(2, 1, -1)), v3(1, 1, -1));
+        assert_approx_eq(proj.project_point(v3(-20, 10, -10)), v3(-1, 1, 1));
+    }
+
+    #[test]
+    fn test_orthographic() {
+        let proj = Mat4::orthographic(-2.0, 4.0, 0.0, 1.0, 1.0, 3.0);
+
+        assert_approx_eq(proj.project_point(v3(-2, 0, -1)), v3(-1, -1, -1));
+        assert_approx_eq(proj.project_point(v3(4, 1, -3)), v3(1, 1, 1));
+        assert_approx_eq(proj.project_point(v3(1.0, 0.5, -2.0)), Vec3::zero());
+    }
+}
@@ -1,16 +1,25 @@
 pub mod aabb;
+pub mod heightfield;
 pub mod mat4;
 pub mod plane;
 pub mod polyline;
+pub mod polyline2;
+pub mod quat;
 pub mod ray;
 pub mod sphere;
 pub mod triangle;
+pub mod vec2;
 pub mod vec3;
 
 pub use vec3::Vec3;
 
 /// An enum over the X, Y and Z axis.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum Axis {
     X,
     Y,
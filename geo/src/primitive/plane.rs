@@ -1,6 +1,100 @@
 use crate::ray::Ray;
+use crate::spatial_index::Shape;
 use crate::{v3, Aabb, Vec3};
 
+/// An infinite plane passing through `origin` whose normal is `normal`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub origin: Vec3,
+    pub normal: Vec3,
+}
+
+impl Plane {
+    /// Create a new `Plane` passing through `origin` and perpendicular to
+    /// `normal`, which is normalized.
+    pub fn new(origin: Vec3, normal: Vec3) -> Self {
+        Self {
+            origin,
+            normal: normal.normalized(),
+        }
+    }
+
+    /// Create the `Plane` passing through the given three points whose normal
+    /// follows the right hand rule, if the points are not collinear.
+    pub fn from_points(a: Vec3, b: Vec3, c: Vec3) -> Option<Self> {
+        let n = (b - a).cross(c - a);
+        let len = n.norm();
+        if len < 1e-12 {
+            return None;
+        }
+
+        Some(Self {
+            origin: a,
+            normal: n / len,
+        })
+    }
+
+    /// Calculate the signed distance of the given point from the `Plane`. The
+    /// distance is positive on the side the normal points to and negative on
+    /// the other.
+    pub fn signed_distance(&self, p: Vec3) -> f64 {
+        (p - self.origin).dot(self.normal)
+    }
+
+    /// Project the given point onto the `Plane`.
+    pub fn project(&self, p: Vec3) -> Vec3 {
+        p - self.normal * self.signed_distance(p)
+    }
+
+    /// Return two unit vectors `(u, v)` lying on the `Plane` that together with
+    /// the normal form a right handed orthonormal basis.
+    pub fn basis(&self) -> (Vec3, Vec3) {
+        orthonormal_basis(self.normal)
+    }
+
+    /// Return the point at the given coordinates in the 2D coordinate system
+    /// of the `Plane` centered at `origin` and with the axes returned by
+    /// `basis`.
+    pub fn point_at(&self, u: f64, v: f64) -> Vec3 {
+        let (bu, bv) = self.basis();
+        self.origin + bu * u + bv * v
+    }
+}
+
+impl Shape for Plane {
+    type Intersection = f64;
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        intersection(self.origin, self.normal, ray)
+    }
+
+    fn bbox(&self) -> Aabb {
+        bbox()
+    }
+
+    fn bounding_sphere(&self) -> (Vec3, f64) {
+        (self.origin, f64::INFINITY)
+    }
+}
+
+/// Build two unit vectors perpendicular to each other and to the unit vector
+/// `n` such that `(u, v, n)` is a right handed orthonormal basis.
+///
+/// The basis changes continuously with `n` except when crossing the `z = 0`
+/// plane, see [Building an Orthonormal Basis, Revisited][0].
+///
+/// [0]: https://graphics.pixar.com/library/OrthonormalB/paper.pdf
+pub fn orthonormal_basis(n: Vec3) -> (Vec3, Vec3) {
+    let sign = 1.0_f64.copysign(n.z);
+    let a = -1.0 / (sign + n.z);
+    let b = n.x * n.y * a;
+
+    let u = v3(1.0 + sign * n.x * n.x * a, sign * b, -sign * n.x);
+    let v = v3(b, sign + n.y * n.y * a, -n.y);
+
+    (u, v)
+}
+
 /// Return the infinite bounding box of an infinite plane.
 pub fn bbox() -> Aabb {
     Aabb::new(v3(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY)).expanded(v3(
@@ -33,6 +127,52 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_plane() {
+        let plane = Plane::new(v3(0, 0, 2), v3(0, 0, 3));
+        assert_eq!(plane.normal, v3(0, 0, 1));
+
+        assert_eq!(plane.signed_distance(v3(4, 5, 7)), 5.0);
+        assert_eq!(plane.signed_distance(v3(4, 5, -1)), -3.0);
+        assert_eq!(plane.project(v3(4, 5, 7)), v3(4, 5, 2));
+
+        assert_eq!(
+            plane.intersection(&Ray::new(v3(1, 1, 5), v3(0.0, 0.0, -1.0))),
+            Some(3.0)
+        );
+        assert_eq!(plane.point_at(1.0, 2.0), v3(1, 2, 2));
+
+        assert_eq!(
+            Plane::from_points(Vec3::zero(), v3(1, 0, 0), v3(0, 1, 0)),
+            Some(Plane::new(Vec3::zero(), v3(0, 0, 1)))
+        );
+        assert_eq!(
+            Plane::from_points(Vec3::zero(), v3(1, 0, 0), v3(2, 0, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_orthonormal_basis() {
+        for n in [
+            v3(0, 0, 1),
+            v3(0.0, 0.0, -1.0),
+            v3(1, 0, 0),
+            v3(0, 1, 0),
+            v3(1.0, -2.0, 3.0).normalized(),
+            v3(-0.3, 0.1, -0.9).normalized(),
+        ] {
+            let (u, v) = orthonormal_basis(n);
+
+            assert!((u.norm() - 1.0).abs() < 1e-9);
+            assert!((v.norm() - 1.0).abs() < 1e-9);
+            assert!(u.dot(v).abs() < 1e-9);
+            assert!(u.dot(n).abs() < 1e-9);
+            assert!(v.dot(n).abs() < 1e-9);
+            assert!(u.cross(v).dist(n) < 1e-9);
+        }
+    }
+
     #[test]
     fn test_intersection() {
         assert_eq!(
@@ -1,6 +1,6 @@
 use std::iter;
 
-use crate::Vec3;
+use crate::{mat4::Mat4, Aabb, Vec2, Vec3};
 
 /// A `Polyline` object represented by a series of points.
 #[derive(Debug, Clone, PartialEq)]
@@ -35,6 +35,31 @@ impl Polyline {
         self.points.iter().cloned()
     }
 
+    /// Return a new `Polyline` with all the points transformed by the given
+    /// matrix.
+    pub fn transformed(&self, m: &Mat4) -> Self {
+        self.iter().map(|p| p * m).collect()
+    }
+
+    /// Project all the points by the given projection matrix, e.g. the one of
+    /// a camera, including the homogeneous divide.
+    ///
+    /// Each point comes along with whether it's clipped, that is whether it's
+    /// outside the clip volume from (-1,-1,-1) to (1,1,1) or behind the
+    /// camera.
+    pub fn project(&self, m: &Mat4) -> Vec<(Vec3, bool)> {
+        let clip_box = Aabb::cuboid(Vec3::zero(), 2.0);
+
+        self.iter()
+            .map(|p| {
+                let w = m.homogeneous_w(p);
+                let projected = (p * m) / w;
+
+                (projected, w <= 0.0 || !clip_box.contains(&projected))
+            })
+            .collect()
+    }
+
     /// Return the total length of the `Polyline`.
     pub fn norm(&self) -> f64 {
         self.points
@@ -44,6 +69,23 @@ impl Polyline {
             .sum()
     }
 
+    /// Return the point at the given distance from the start along the
+    /// `Polyline` or None if it's empty. Distances outside of [0, norm()] are
+    /// clamped to the endpoints.
+    pub fn point_at_length(&self, t: f64) -> Option<Vec3> {
+        point_at_length(&self.points, t)
+    }
+
+    /// Return a new `Polyline` whose points are evenly spaced `step` apart
+    /// along the original path, unlike `chop` the spacing is kept across the
+    /// corners. The last point is always kept so that the path ends where the
+    /// original one did.
+    pub fn resampled(&self, step: f64) -> Self {
+        Self {
+            points: resampled(&self.points, step),
+        }
+    }
+
     /// Return a new `Polyline` where every point is `eps` distance from the
     /// previous point.
     pub fn chop(&self, eps: f64) -> Self {
@@ -66,42 +108,52 @@ impl Polyline {
 
     /// Simplify the Polyline up to the given precision using the
     /// Ramer-Douglas-Peucker algorithm.
-    pub fn simplified(mut self, eps: f64) -> Self {
+    pub fn simplified(self, eps: f64) -> Self {
+        self.simplified_with(|_, _| eps)
+    }
+
+    /// Simplify the Polyline using the Ramer-Douglas-Peucker algorithm where
+    /// the precision can change from point to point.
+    ///
+    /// `eps` is called with the index and the value of a point and it must
+    /// return the maximum distance the point is allowed to be from the
+    /// simplified line. This is useful to simplify more aggressively the
+    /// points that are far away from the camera, for example.
+    pub fn simplified_with(self, eps: impl Fn(usize, Vec3) -> f64) -> Self {
         if self.len() < 3 {
             return self;
         }
 
-        let a = self.points[0];
-        let b = *self.points.last().unwrap();
-
-        let mut index = 0;
-        let mut maxd = 0.0;
-        for (i, p) in self
-            .points
-            .iter()
-            .enumerate()
-            .take(self.points.len() - 2)
-            .skip(1)
-        {
-            let d = p.segment_dist(a, b);
-            if d > maxd {
-                maxd = d;
-                index = i;
-            }
-        }
+        let mut keep = vec![false; self.len()];
+        keep[0] = true;
+        keep[self.len() - 1] = true;
 
-        if maxd > eps {
-            let right = self.points.split_off(index);
-            self.points.push(right[0]);
+        let mut stack = vec![(0, self.len() - 1)];
+        while let Some((s, e)) = stack.pop() {
+            let (a, b) = (self.points[s], self.points[e]);
 
-            let mut path1 = self.simplified(eps);
-            let path2 = Self::from(right).simplified(eps);
+            let mut index = s;
+            let mut maxd = 0.0;
+            for (i, p) in self.points.iter().enumerate().take(e).skip(s + 1) {
+                let d = p.segment_dist(a, b);
+                if d > maxd {
+                    maxd = d;
+                    index = i;
+                }
+            }
 
-            path1.points.extend_from_slice(&path2.points[1..]);
-            return path1;
+            if index != s && maxd > eps(index, self.points[index]) {
+                keep[index] = true;
+                stack.push((s, index));
+                stack.push((index, e));
+            }
         }
 
-        Self::from(vec![a, b])
+        self.points
+            .into_iter()
+            .zip(keep)
+            .filter_map(|(p, k)| k.then_some(p))
+            .collect()
     }
 }
 
@@ -130,3 +182,173 @@ impl iter::Extend<Vec3> for Polyline {
         self.points.extend(iter)
     }
 }
+
+/// The operations on the points that the algorithms shared by `Polyline`
+/// and `Polyline2` need.
+pub(crate) trait PathPoint: Copy + PartialEq {
+    fn dist(&self, other: Self) -> f64;
+    fn lerp(self, other: Self, t: f64) -> Self;
+}
+
+impl PathPoint for Vec3 {
+    fn dist(&self, other: Self) -> f64 {
+        Vec3::dist(self, other)
+    }
+
+    fn lerp(self, other: Self, t: f64) -> Self {
+        Vec3::lerp(self, other, t)
+    }
+}
+
+impl PathPoint for Vec2 {
+    fn dist(&self, other: Self) -> f64 {
+        Vec2::dist(self, other)
+    }
+
+    fn lerp(self, other: Self, t: f64) -> Self {
+        Vec2::lerp(self, other, t)
+    }
+}
+
+/// Return the point at distance `t` along the path through `points`, see
+/// `Polyline::point_at_length`.
+pub(crate) fn point_at_length<P: PathPoint>(points: &[P], t: f64) -> Option<P> {
+    let mut left = t.max(0.0);
+    for w in points.windows(2) {
+        let l = w[0].dist(w[1]);
+        if left <= l {
+            return Some(if l == 0.0 {
+                w[0]
+            } else {
+                w[0].lerp(w[1], left / l)
+            });
+        }
+        left -= l;
+    }
+
+    points.last().copied()
+}
+
+/// Return the points evenly spaced `step` apart along the path through
+/// `points`, see `Polyline::resampled`.
+pub(crate) fn resampled<P: PathPoint>(points: &[P], step: f64) -> Vec<P> {
+    assert!(step > 0.0, "resampling step must be positive");
+
+    let (first, last) = match (points.first(), points.last()) {
+        (Some(f), Some(l)) => (*f, *l),
+        _ => return vec![],
+    };
+
+    let mut out = vec![first];
+
+    // distance left to walk before emitting the next point
+    let mut left = step;
+    for w in points.windows(2) {
+        let (s, e) = (w[0], w[1]);
+        let l = s.dist(e);
+
+        let mut t = left;
+        while t < l {
+            out.push(s.lerp(e, t / l));
+            t += step;
+        }
+        left = t - l;
+    }
+
+    if out.last() != Some(&last) {
+        out.push(last);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{v2, v3};
+
+    #[test]
+    fn test_arc_length() {
+        let path = Polyline::from(vec![v3(0, 0, 0), v3(1.5, 0, 0), v3(1.5, 2, 0)]);
+
+        assert_eq!(path.norm(), 3.5);
+        assert_eq!(path.point_at_length(-1.0), Some(v3(0, 0, 0)));
+        assert_eq!(path.point_at_length(0.5), Some(v3(0.5, 0, 0)));
+        assert_eq!(path.point_at_length(2.0), Some(v3(1.5, 0.5, 0)));
+        assert_eq!(path.point_at_length(10.0), Some(v3(1.5, 2, 0)));
+        assert_eq!(Polyline::new().point_at_length(1.0), None);
+
+        assert_eq!(
+            path.resampled(1.0).points,
+            vec![
+                v3(0, 0, 0),
+                v3(1, 0, 0),
+                v3(1.5, 0.5, 0),
+                v3(1.5, 1.5, 0),
+                v3(1.5, 2, 0)
+            ]
+        );
+        assert_eq!(path.resampled(5.0).points, vec![v3(0, 0, 0), v3(1.5, 2, 0)]);
+        assert!(Polyline::new().resampled(1.0).is_empty());
+    }
+
+    #[test]
+    fn test_repeated_points() {
+        // zero length segments are skipped, whatever the kind of point
+        let path = [v3(0, 0, 0), v3(0, 0, 0), v3(0, 0, 2)];
+        assert_eq!(point_at_length(&path, 0.0), Some(v3(0, 0, 0)));
+        assert_eq!(point_at_length(&path, 1.0), Some(v3(0, 0, 1)));
+        assert_eq!(
+            resampled(&path, 1.0),
+            vec![v3(0, 0, 0), v3(0, 0, 1), v3(0, 0, 2)]
+        );
+
+        let path = [v2(1, 1), v2(2, 1), v2(2, 1)];
+        assert_eq!(point_at_length(&path, 0.5), Some(v2(1.5, 1)));
+        assert_eq!(point_at_length(&path, 3.0), Some(v2(2, 1)));
+        assert_eq!(resampled(&path, 0.5), vec![v2(1, 1), v2(1.5, 1), v2(2, 1)]);
+    }
+
+    #[test]
+    fn test_transform_and_project() {
+        let path = Polyline::from(vec![v3(0, 0, -1), v3(0.5, 0.5, -2), v3(0, 0, 1)]);
+
+        assert_eq!(
+            path.transformed(&Mat4::translate(v3(1, 2, 3))).points,
+            vec![v3(1, 2, 2), v3(1.5, 2.5, 1), v3(1, 2, 4)]
+        );
+
+        let proj = Mat4::perspective(90.0, 1.0, 0.5, 10.0);
+        let projected = path.project(&proj);
+        assert_eq!(
+            projected.iter().map(|(_, c)| *c).collect::<Vec<_>>(),
+            vec![false, false, true]
+        );
+        assert!(projected[1].0.dist(proj.project_point(path.points[1])) < 1e-9);
+        assert!((projected[1].0.x - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_simplified_with() {
+        let path = Polyline::from(vec![
+            v3(0, 0, 0),
+            v3(1, 0.1, 0),
+            v3(2, 0, 0),
+            v3(3, 0.1, 0),
+            v3(4, 0, 0),
+        ]);
+
+        assert_eq!(path.clone().simplified(0.01), path);
+        assert_eq!(
+            path.clone().simplified(0.5).points,
+            vec![v3(0, 0, 0), v3(4, 0, 0)]
+        );
+
+        // only the points before x=2 need to be preserved precisely
+        assert_eq!(
+            path.simplified_with(|_, p| if p.x < 2.0 { 0.01 } else { 0.5 })
+                .points,
+            vec![v3(0, 0, 0), v3(1, 0.1, 0), v3(4, 0, 0)]
+        );
+    }
+}
@@ -0,0 +1,378 @@
+use std::{f64::consts::PI, iter};
+
+use crate::{primitive::polyline, Vec2};
+
+/// Maximum angle between consecutive points of a round join.
+const ROUND_JOIN_STEP: f64 = PI / 16.0;
+
+/// How the offset segments are connected on the outer side of a corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineJoin {
+    /// Extend the segments until they meet unless the resulting spike is
+    /// longer than `limit` times the offset distance, in which case the corner
+    /// is cut.
+    Miter { limit: f64 },
+
+    /// Connect the segments with a circular arc.
+    Round,
+}
+
+/// A 2D `Polyline2` object represented by a series of points, usually the
+/// output of one of the line renderers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polyline2 {
+    /// The points that make this `Polyline2` object.
+    pub points: Vec<Vec2>,
+}
+
+impl Polyline2 {
+    /// Create a new empty `Polyline2`.
+    pub fn new() -> Self {
+        Self { points: vec![] }
+    }
+
+    /// Return the number of points the `Polyline2` is made of.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Return whether the the `Polyline2` is empty or not.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Add a new point to the `Polyline2`.
+    pub fn push(&mut self, v: Vec2) {
+        self.points.push(v);
+    }
+
+    /// Iterator over all the points.
+    pub fn iter(&self) -> impl Iterator<Item = Vec2> + '_ {
+        self.points.iter().cloned()
+    }
+
+    /// Return the total length of the `Polyline2`.
+    pub fn norm(&self) -> f64 {
+        self.points.windows(2).map(|w| w[0].dist(w[1])).sum()
+    }
+
+    /// Return the bounding box of the `Polyline2` as a pair of min and max
+    /// points or None if it's empty.
+    pub fn bbox(&self) -> Option<(Vec2, Vec2)> {
+        bbox(self.iter())
+    }
+
+    /// Return the point at the given distance from the start along the
+    /// `Polyline2` or None if it's empty. Distances outside of [0, norm()] are
+    /// clamped to the endpoints.
+    pub fn point_at_length(&self, t: f64) -> Option<Vec2> {
+        polyline::point_at_length(&self.points, t)
+    }
+
+    /// Return a new `Polyline2` whose points are evenly spaced `step` apart
+    /// along the original path. The last point is always kept so that the
+    /// path ends where the original one did.
+    pub fn resampled(&self, step: f64) -> Self {
+        Self {
+            points: polyline::resampled(&self.points, step),
+        }
+    }
+
+    /// Simplify the `Polyline2` up to the given precision using the
+    /// [Ramer-Douglas-Peucker][0] algorithm.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/Ramer%E2%80%93Douglas%E2%80%93Peucker_algorithm
+    pub fn simplified(&self, eps: f64) -> Self {
+        if self.len() < 3 {
+            return self.clone();
+        }
+
+        let mut keep = vec![false; self.len()];
+        keep[0] = true;
+        keep[self.len() - 1] = true;
+
+        let mut stack = vec![(0, self.len() - 1)];
+        while let Some((s, e)) = stack.pop() {
+            let (a, b) = (self.points[s], self.points[e]);
+
+            let mut index = s;
+            let mut maxd = 0.0;
+            for (i, p) in self.points.iter().enumerate().take(e).skip(s + 1) {
+                let d = p.segment_dist(a, b);
+                if d > maxd {
+                    maxd = d;
+                    index = i;
+                }
+            }
+
+            if maxd > eps {
+                keep[index] = true;
+                stack.push((s, index));
+                stack.push((index, e));
+            }
+        }
+
+        self.iter()
+            .zip(keep)
+            .filter_map(|(p, k)| k.then_some(p))
+            .collect()
+    }
+
+    /// Return a new `Polyline2` parallel to this one at the given distance,
+    /// positive distances offset to the left of the path and negative ones to
+    /// the right. If the first and last points match the path is considered
+    /// closed and so is the offset one.
+    ///
+    /// Note that the offset path can intersect itself when the distance is
+    /// larger than the radius of curvature of the path.
+    pub fn offset(&self, distance: f64, join: LineJoin) -> Self {
+        let mut pts = self.points.clone();
+        pts.dedup();
+
+        if pts.len() < 2 {
+            return pts.into();
+        }
+
+        let closed = pts.len() > 2 && pts.first() == pts.last();
+
+        let normal = |a: Vec2, b: Vec2| (b - a).normalized().perp() * distance;
+        let segments = pts
+            .windows(2)
+            .map(|w| {
+                let n = normal(w[0], w[1]);
+                (w[0] + n, w[1] + n)
+            })
+            .collect::<Vec<_>>();
+
+        let mut out = Self::new();
+        if !closed {
+            out.push(segments[0].0);
+        }
+
+        for (i, v) in pts.iter().enumerate().skip(1) {
+            let prev = segments[i - 1];
+            let next = match segments.get(i) {
+                Some(s) => *s,
+                None if closed => segments[0],
+                None => {
+                    out.push(prev.1);
+                    break;
+                }
+            };
+
+            join_segments(&mut out, *v, prev, next, distance, join);
+        }
+
+        if closed {
+            out.push(out.points[0]);
+        }
+
+        out
+    }
+
+    /// Expand the `Polyline2` into parallel passes of a pen of the given width
+    /// so that together they cover a stroke `width` wide centered on the path.
+    pub fn stroke_passes(&self, width: f64, pen_width: f64, join: LineJoin) -> Vec<Self> {
+        assert!(pen_width > 0.0, "pen width must be positive");
+
+        let n = (width / pen_width).ceil().max(1.0) as usize;
+        if n == 1 {
+            return vec![self.clone()];
+        }
+
+        // the outermost passes are half a pen width inside the stroke
+        let span = width - pen_width;
+        (0..n)
+            .map(|i| {
+                let d = -span / 2.0 + span * i as f64 / (n - 1) as f64;
+                self.offset(d, join)
+            })
+            .collect()
+    }
+}
+
+/// Connect the end of the `prev` offset segment to the start of the `next` one
+/// around the corner `v` of the original path.
+fn join_segments(
+    out: &mut Polyline2,
+    v: Vec2,
+    prev: (Vec2, Vec2),
+    next: (Vec2, Vec2),
+    distance: f64,
+    join: LineJoin,
+) {
+    let (d0, d1) = (prev.1 - prev.0, next.1 - next.0);
+    let turn = d0.cross(d1);
+
+    // straight line, the two segments already meet
+    if turn.abs() < 1e-12 && d0.dot(d1) > 0.0 {
+        out.push(prev.1);
+        return;
+    }
+
+    // on the inner side of the corner the segments overlap, cut them where
+    // they cross
+    let inner = turn * distance > 0.0;
+    let crossing = line_intersection(prev.0, d0, next.0, d1);
+
+    if inner {
+        out.push(crossing.unwrap_or(prev.1));
+        return;
+    }
+
+    match join {
+        LineJoin::Miter { limit } => match crossing {
+            Some(p) if p.dist(v) <= limit * distance.abs() => out.push(p),
+            _ => {
+                out.push(prev.1);
+                out.push(next.0);
+            }
+        },
+        LineJoin::Round => {
+            let (a0, a1) = (prev.1 - v, next.0 - v);
+            let angle = a0.cross(a1).atan2(a0.dot(a1));
+            let steps = (angle.abs() / ROUND_JOIN_STEP).ceil().max(1.0) as usize;
+
+            for i in 0..=steps {
+                let (s, c) = (angle * i as f64 / steps as f64).sin_cos();
+                out.push(v + Vec2::new(a0.x * c - a0.y * s, a0.x * s + a0.y * c));
+            }
+        }
+    }
+}
+
+/// Return the intersection between the line through `a` with direction `da`
+/// and the one through `b` with direction `db`, if they're not parallel.
+fn line_intersection(a: Vec2, da: Vec2, b: Vec2, db: Vec2) -> Option<Vec2> {
+    let den = da.cross(db);
+    if den.abs() < 1e-12 {
+        return None;
+    }
+
+    Some(a + da * ((b - a).cross(db) / den))
+}
+
+/// Return the bounding box of the given points as a pair of min and max points
+/// or None if there are no points.
+pub fn bbox(pts: impl IntoIterator<Item = Vec2>) -> Option<(Vec2, Vec2)> {
+    let mut pts = pts.into_iter();
+    let p = pts.next()?;
+
+    Some(pts.fold((p, p), |(min, max), p| (min.min(p), max.max(p))))
+}
+
+impl Default for Polyline2 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::convert::From<Vec<Vec2>> for Polyline2 {
+    fn from(v: Vec<Vec2>) -> Self {
+        Polyline2 { points: v }
+    }
+}
+
+impl iter::FromIterator<Vec2> for Polyline2 {
+    fn from_iter<T: IntoIterator<Item = Vec2>>(iter: T) -> Self {
+        Polyline2 {
+            points: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl iter::Extend<Vec2> for Polyline2 {
+    fn extend<T: IntoIterator<Item = Vec2>>(&mut self, iter: T) {
+        self.points.extend(iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2;
+
+    #[test]
+    fn test_norm_bbox() {
+        let path = Polyline2::from(vec![v2(0, 0), v2(3, 4), v2(3, -2)]);
+
+        assert_eq!(path.norm(), 11.0);
+        assert_eq!(path.bbox(), Some((v2(0, -2), v2(3, 4))));
+        assert_eq!(Polyline2::new().bbox(), None);
+    }
+
+    #[test]
+    fn test_resampled() {
+        let path = Polyline2::from(vec![v2(0, 0), v2(1.5, 0), v2(1.5, 2)]);
+
+        assert_eq!(
+            path.resampled(1.0).points,
+            vec![v2(0, 0), v2(1, 0), v2(1.5, 0.5), v2(1.5, 1.5), v2(1.5, 2)]
+        );
+        assert_eq!(path.resampled(5.0).points, vec![v2(0, 0), v2(1.5, 2)]);
+        assert_eq!(path.point_at_length(2.5), Some(v2(1.5, 1)));
+        assert!(Polyline2::new().resampled(1.0).is_empty());
+    }
+
+    #[test]
+    fn test_offset() {
+        let path = Polyline2::from(vec![v2(0, 0), v2(2, 0), v2(2, 2)]);
+
+        // left turn, the left side is the inner one
+        assert_eq!(
+            path.offset(1.0, LineJoin::Round).points,
+            vec![v2(0, 1), v2(1, 1), v2(1, 2)]
+        );
+
+        let miter = path.offset(-1.0, LineJoin::Miter { limit: 2.0 });
+        assert_eq!(miter.points, vec![v2(0, -1), v2(3, -1), v2(3, 2)]);
+
+        let bevel = path.offset(-1.0, LineJoin::Miter { limit: 1.0 });
+        assert_eq!(bevel.points, vec![v2(0, -1), v2(2, -1), v2(3, 0), v2(3, 2)]);
+
+        let round = path.offset(-1.0, LineJoin::Round);
+        assert_eq!(round.points.first(), Some(&v2(0, -1)));
+        assert_eq!(round.points.last(), Some(&v2(3, 2)));
+        for p in &round.points[1..round.len() - 1] {
+            assert!((p.dist(v2(2, 0)) - 1.0).abs() < 1e-9, "{p:?}");
+        }
+
+        // closed counterclockwise square offset outwards
+        let square = Polyline2::from(vec![v2(0, 0), v2(2, 0), v2(2, 2), v2(0, 2), v2(0, 0)]);
+        assert_eq!(
+            square.offset(-1.0, LineJoin::Miter { limit: 2.0 }).points,
+            vec![v2(3, -1), v2(3, 3), v2(-1, 3), v2(-1, -1), v2(3, -1)]
+        );
+    }
+
+    #[test]
+    fn test_stroke_passes() {
+        let path = Polyline2::from(vec![v2(0, 0), v2(4, 0)]);
+
+        let passes = path.stroke_passes(1.0, 0.25, LineJoin::Round);
+        assert_eq!(passes.len(), 4);
+        assert_eq!(passes[0].points, vec![v2(0, -0.375), v2(4, -0.375)]);
+        assert_eq!(passes[3].points, vec![v2(0, 0.375), v2(4, 0.375)]);
+
+        assert_eq!(path.stroke_passes(0.1, 0.25, LineJoin::Round), vec![path]);
+    }
+
+    #[test]
+    fn test_simplified() {
+        let path = Polyline2::from(vec![
+            v2(0, 0),
+            v2(1, 0.01),
+            v2(2, -0.01),
+            v2(3, 0),
+            v2(3, 1),
+            v2(3.01, 2),
+            v2(3, 3),
+        ]);
+
+        assert_eq!(
+            path.simplified(0.1).points,
+            vec![v2(0, 0), v2(3, 0), v2(3, 3)]
+        );
+        assert_eq!(path.simplified(0.001).len(), path.len());
+    }
+}
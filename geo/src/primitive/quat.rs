@@ -0,0 +1,296 @@
+use std::ops::Mul;
+
+use crate::{mat4::Mat4, v3, Vec3};
+
+/// A [Quaternion][0] representing a rotation in 3D space.
+///
+/// Unlike rotation matrices and Euler angles quaternions can be smoothly
+/// interpolated with `Quat::slerp` without suffering from [gimbal lock][1].
+///
+/// [0]: https://en.wikipedia.org/wiki/Quaternions_and_spatial_rotation
+/// [1]: https://en.wikipedia.org/wiki/Gimbal_lock
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quat {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Quat {
+    /// Create a new `Quat` with the given components.
+    pub const fn new(w: f64, x: f64, y: f64, z: f64) -> Self {
+        Quat { w, x, y, z }
+    }
+
+    /// The `Quat` representing no rotation at all.
+    pub const fn identity() -> Self {
+        Quat::new(1.0, 0.0, 0.0, 0.0)
+    }
+
+    /// Create a `Quat` that rotates by the given angle in radians around the
+    /// given axis.
+    ///
+    /// Positive angles rotate counterclockwise when looking from the tip of
+    /// the axis towards the origin.
+    pub fn from_axis_angle(axis: Vec3, angle: f64) -> Self {
+        let axis = axis.normalized();
+        let (s, c) = (angle / 2.0).sin_cos();
+
+        Quat::new(c, axis.x * s, axis.y * s, axis.z * s)
+    }
+
+    /// Create a `Quat` from the given Euler angles in radians. The rotation
+    /// around the x axis is applied first, then the one around the y axis and
+    /// finally the one around the z axis.
+    pub fn from_euler(x: f64, y: f64, z: f64) -> Self {
+        Quat::from_axis_angle(v3(0, 0, 1), z)
+            * Quat::from_axis_angle(v3(0, 1, 0), y)
+            * Quat::from_axis_angle(v3(1, 0, 0), x)
+    }
+
+    /// Create a `Quat` from the rotation part of the given transformation
+    /// matrix which must not contain any scaling.
+    pub fn from_mat4(m: &Mat4) -> Self {
+        let m = &m.data;
+        let trace = m[0][0] + m[1][1] + m[2][2];
+
+        let q = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Quat::new(
+                s / 4.0,
+                (m[2][1] - m[1][2]) / s,
+                (m[0][2] - m[2][0]) / s,
+                (m[1][0] - m[0][1]) / s,
+            )
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+            Quat::new(
+                (m[2][1] - m[1][2]) / s,
+                s / 4.0,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+            )
+        } else if m[1][1] > m[2][2] {
+            let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+            Quat::new(
+                (m[0][2] - m[2][0]) / s,
+                (m[0][1] + m[1][0]) / s,
+                s / 4.0,
+                (m[1][2] + m[2][1]) / s,
+            )
+        } else {
+            let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+            Quat::new(
+                (m[1][0] - m[0][1]) / s,
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                s / 4.0,
+            )
+        };
+
+        q.normalized()
+    }
+
+    /// Return the rotation matrix equivalent to this `Quat`.
+    pub fn to_mat4(&self) -> Mat4 {
+        let Quat { w, x, y, z } = self.normalized();
+
+        Mat4 {
+            data: [
+                [
+                    1.0 - 2.0 * (y * y + z * z),
+                    2.0 * (x * y - w * z),
+                    2.0 * (x * z + w * y),
+                    0.0,
+                ],
+                [
+                    2.0 * (x * y + w * z),
+                    1.0 - 2.0 * (x * x + z * z),
+                    2.0 * (y * z - w * x),
+                    0.0,
+                ],
+                [
+                    2.0 * (x * z - w * y),
+                    2.0 * (y * z + w * x),
+                    1.0 - 2.0 * (x * x + y * y),
+                    0.0,
+                ],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    /// Return the axis and the angle in radians of the rotation.
+    ///
+    /// The axis of the identity rotation is arbitrarily chosen to be the x
+    /// axis.
+    pub fn to_axis_angle(&self) -> (Vec3, f64) {
+        let q = self.normalized();
+
+        let angle = 2.0 * q.w.clamp(-1.0, 1.0).acos();
+        let s = (1.0 - q.w * q.w).max(0.0).sqrt();
+        if s < 1e-9 {
+            return (v3(1, 0, 0), angle);
+        }
+
+        (v3(q.x / s, q.y / s, q.z / s), angle)
+    }
+
+    /// Rotate the given vector by this `Quat`.
+    pub fn rotate(&self, v: Vec3) -> Vec3 {
+        let q = self.normalized();
+        let u = v3(q.x, q.y, q.z);
+
+        let t = u.cross(v) * 2.0;
+        v + t * q.w + u.cross(t)
+    }
+
+    /// Return the norm or length of this `Quat`.
+    pub fn norm(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    /// Return a new copy of this `Quat` with norm 1.
+    pub fn normalized(&self) -> Self {
+        let n = self.norm();
+        Quat::new(self.w / n, self.x / n, self.y / n, self.z / n)
+    }
+
+    /// Return the conjugate of this `Quat` which for unit quaternions is the
+    /// inverse rotation.
+    pub fn conjugate(&self) -> Self {
+        Quat::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    /// Calculate the dot product between two `Quat`.
+    pub fn dot(&self, q: &Quat) -> f64 {
+        self.w * q.w + self.x * q.x + self.y * q.y + self.z * q.z
+    }
+
+    /// [Spherical linear interpolation][0] between two rotations that always
+    /// takes the shortest path.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/Slerp
+    pub fn slerp(self, b: Quat, t: f64) -> Quat {
+        let a = self.normalized();
+        let mut b = b.normalized();
+
+        // q and -q represent the same rotation, pick the closest one
+        let mut d = a.dot(&b);
+        if d < 0.0 {
+            b = Quat::new(-b.w, -b.x, -b.y, -b.z);
+            d = -d;
+        }
+
+        // the quaternions are so close that slerp is numerically unstable and
+        // a normalized lerp is indistinguishable from it
+        if d > 0.9995 {
+            return Quat::new(
+                a.w + (b.w - a.w) * t,
+                a.x + (b.x - a.x) * t,
+                a.y + (b.y - a.y) * t,
+                a.z + (b.z - a.z) * t,
+            )
+            .normalized();
+        }
+
+        let theta = d.acos();
+        let sa = ((1.0 - t) * theta).sin() / theta.sin();
+        let sb = (t * theta).sin() / theta.sin();
+
+        Quat::new(
+            a.w * sa + b.w * sb,
+            a.x * sa + b.x * sb,
+            a.y * sa + b.y * sb,
+            a.z * sa + b.z * sb,
+        )
+    }
+}
+
+impl Mul for Quat {
+    type Output = Quat;
+
+    /// Compose two rotations, the resulting `Quat` applies `q` first and then
+    /// `self`.
+    fn mul(self, q: Quat) -> Self::Output {
+        Quat::new(
+            self.w * q.w - self.x * q.x - self.y * q.y - self.z * q.z,
+            self.w * q.x + self.x * q.w + self.y * q.z - self.z * q.y,
+            self.w * q.y - self.x * q.z + self.y * q.w + self.z * q.x,
+            self.w * q.z + self.x * q.y - self.y * q.x + self.z * q.w,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    use super::*;
+
+    fn assert_approx_eq(a: Vec3, b: Vec3) {
+        assert!(a.dist(b) < 1e-9, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn test_axis_angle() {
+        let q = Quat::from_axis_angle(v3(0, 0, 2), FRAC_PI_2);
+
+        assert_approx_eq(q.rotate(v3(1, 0, 0)), v3(0, 1, 0));
+        assert_approx_eq(q.rotate(v3(0, 1, 5)), v3(-1, 0, 5));
+        assert_approx_eq(q.conjugate().rotate(v3(0, 1, 0)), v3(1, 0, 0));
+
+        let (axis, angle) = q.to_axis_angle();
+        assert_approx_eq(axis, v3(0, 0, 1));
+        assert!((angle - FRAC_PI_2).abs() < 1e-9);
+
+        assert_eq!(Quat::identity().to_axis_angle(), (v3(1, 0, 0), 0.0));
+    }
+
+    #[test]
+    fn test_euler() {
+        let q = Quat::from_euler(FRAC_PI_2, 0.0, FRAC_PI_2);
+
+        // x rotation brings y to z and then the z rotation leaves it there
+        assert_approx_eq(q.rotate(v3(0, 1, 0)), v3(0, 0, 1));
+        // x rotation leaves x untouched and then the z rotation brings it to y
+        assert_approx_eq(q.rotate(v3(1, 0, 0)), v3(0, 1, 0));
+    }
+
+    #[test]
+    fn test_mat4() {
+        for q in [
+            Quat::identity(),
+            Quat::from_axis_angle(v3(1, 2, 3), 0.7),
+            Quat::from_axis_angle(v3(0, 1, 0), PI),
+            Quat::from_axis_angle(v3(1, 0, 0), PI),
+            Quat::from_axis_angle(v3(0, 0, 1), PI),
+            Quat::from_euler(0.3, -2.0, 1.1),
+        ] {
+            let m = q.to_mat4();
+            for p in [v3(1, 0, 0), v3(0, 1, 0), v3(0, 0, 1), v3(-1, 2, 3)] {
+                assert_approx_eq(p * &m, q.rotate(p));
+            }
+
+            let q2 = Quat::from_mat4(&m);
+            assert!((q.dot(&q2).abs() - 1.0).abs() < 1e-9, "{q:?} != {q2:?}");
+        }
+    }
+
+    #[test]
+    fn test_slerp() {
+        let a = Quat::identity();
+        let b = Quat::from_axis_angle(v3(0, 1, 0), FRAC_PI_2);
+
+        assert_approx_eq(a.slerp(b, 0.0).rotate(v3(1, 0, 0)), v3(1, 0, 0));
+        assert_approx_eq(a.slerp(b, 1.0).rotate(v3(1, 0, 0)), v3(0, 0, -1));
+
+        let half = Quat::from_axis_angle(v3(0, 1, 0), FRAC_PI_2 / 2.0);
+        assert!((a.slerp(b, 0.5).dot(&half) - 1.0).abs() < 1e-9);
+
+        // shortest path even when the quaternions are in opposite hemispheres
+        let c = Quat::new(-b.w, -b.x, -b.y, -b.z);
+        assert!((a.slerp(c, 0.5).dot(&half).abs() - 1.0).abs() < 1e-9);
+    }
+}
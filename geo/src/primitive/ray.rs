@@ -11,13 +11,34 @@ pub struct Ray {
 
     /// The direction, possibly not normalized, of the `Ray`.
     pub dir: Vec3,
+
+    /// The instant in time the `Ray` was cast at. It's used to render moving
+    /// objects and it's 0 by default.
+    pub time: f64,
 }
 
 impl Ray {
     /// Create a new `Ray` with the given origin and direction. The direction
     /// doesn't have to be normalized.
     pub fn new(origin: Vec3, dir: Vec3) -> Self {
-        Ray { origin, dir }
+        Ray {
+            origin,
+            dir,
+            time: 0.0,
+        }
+    }
+
+    /// Change the instant in time the `Ray` was cast at.
+    pub fn with_time(mut self, time: f64) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Return the inverse of the direction of the `Ray` on each axis. The
+    /// components of the direction that are zero, or negative zero, become
+    /// infinite with the same sign.
+    pub fn inv_dir(&self) -> Vec3 {
+        Vec3::new(1.0 / self.dir.x, 1.0 / self.dir.y, 1.0 / self.dir.z)
     }
 
     /// Get the point on a `Ray` at the given parameter `t`.
@@ -84,7 +105,7 @@ impl Mul<&Mat4> for Ray {
     type Output = Ray;
 
     fn mul(self, mat: &Mat4) -> Self::Output {
-        Ray::new(self.origin * mat, mat.transform_normal(&self.dir))
+        Ray::new(self.origin * mat, mat.transform_normal(&self.dir)).with_time(self.time)
     }
 }
 
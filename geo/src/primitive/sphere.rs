@@ -1,5 +1,63 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+
 use crate::ray::Ray;
-use crate::{Aabb, Vec3};
+use crate::spatial_index::Shape;
+use crate::{v3, Aabb, Vec3};
+
+/// A `Sphere` defined by its center and radius.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sphere {
+    pub center: Vec3,
+    pub radius: f64,
+}
+
+impl Sphere {
+    /// Create a new `Sphere` centered at `center` with the given `radius`.
+    pub fn new(center: Vec3, radius: f64) -> Self {
+        Self { center, radius }
+    }
+
+    /// Calculate the signed distance of the given point from the surface of
+    /// the `Sphere`. The distance is negative inside the sphere.
+    pub fn signed_distance(&self, p: Vec3) -> f64 {
+        p.dist(self.center) - self.radius
+    }
+
+    /// Calculate the normal of the `Sphere` at the given point, see `normal`.
+    pub fn normal_at(&self, p: Vec3) -> Vec3 {
+        normal(self.center, p)
+    }
+
+    /// Generate a random point uniformly distributed on the surface of the
+    /// `Sphere`.
+    pub fn random_pt(&self, rng: &mut impl Rng) -> Vec3 {
+        // the area of the slices of a sphere between two planes perpendicular
+        // to an axis only depends on their distance, hence z is uniform
+        let z = rng.gen_range(-1.0..=1.0);
+        let phi = rng.gen_range(0.0..2.0 * PI);
+        let r = (1.0 - z * z).sqrt();
+
+        self.center + v3(r * phi.cos(), r * phi.sin(), z) * self.radius
+    }
+}
+
+impl Shape for Sphere {
+    type Intersection = f64;
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        ray_intersection(self.center, self.radius, ray)
+    }
+
+    fn bbox(&self) -> Aabb {
+        bounding_box(self.center, self.radius)
+    }
+
+    fn bounding_sphere(&self) -> (Vec3, f64) {
+        (self.center, self.radius)
+    }
+}
 
 /// Check if a sphere defined by `center` and `radius` intersects a `Ray`. If so
 /// return the parameter of the intersection point closest to `ray.origin`.
@@ -46,7 +104,7 @@ pub fn bounding_box(center: Vec3, radius: f64) -> Aabb {
 
 #[cfg(test)]
 mod tests {
-    use crate::v3;
+    use rand::{rngs::StdRng, SeedableRng};
 
     use super::*;
 
@@ -99,4 +157,37 @@ mod tests {
         assert_eq!(bbox.min(), v3(-9.0, -12.0, -7.0));
         assert_eq!(bbox.max(), v3(11, 8, 13));
     }
+
+    #[test]
+    fn test_sphere() {
+        let s = Sphere::new(v3(1, 2, 3), 2.0);
+
+        assert_eq!(s.signed_distance(v3(1, 2, 3)), -2.0);
+        assert_eq!(s.signed_distance(v3(1, 2, 8)), 3.0);
+        assert_eq!(s.normal_at(v3(1, 2, 8)), v3(0, 0, 1));
+
+        assert_eq!(
+            s.intersection(&Ray::new(v3(1, 2, 10), v3(0, 0, -1))),
+            Some(5.0)
+        );
+        assert_eq!(s.bbox(), bounding_box(v3(1, 2, 3), 2.0));
+        assert_eq!(s.bounding_sphere(), (v3(1, 2, 3), 2.0));
+    }
+
+    #[test]
+    fn test_random_pt() {
+        let s = Sphere::new(v3(1, 2, 3), 2.0);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let n = 10_000;
+        let mut mean = Vec3::zero();
+        for _ in 0..n {
+            let p = s.random_pt(&mut rng);
+            assert!(s.signed_distance(p).abs() < 1e-9);
+            mean = mean + p / f64::from(n);
+        }
+
+        // the points are spread evenly around the center
+        assert!(mean.dist(s.center) < 0.05, "{mean:?}");
+    }
 }
@@ -1,8 +1,10 @@
+use std::ops::{Add, Mul};
+
 use rand::Rng;
 
-use crate::v3;
 use crate::{primitive::polyline::Polyline, spatial_index::Shape, Vec3};
 use crate::{ray::Ray, Aabb};
+use crate::{v3, Axis};
 
 /// A `Triangle` defined by three vertices.
 #[derive(Debug, Clone, PartialEq)]
@@ -79,6 +81,17 @@ impl Triangle {
         }
     }
 
+    /// Interpolate the attributes `a_attr`, `b_attr` and `c_attr`, like
+    /// normals, texture coordinates or colors, of the vertices `a`, `b` and
+    /// `c` respectively at the point whose barycentric coordinates wrt this
+    /// triangle are `bary`, as returned by `barycentric`.
+    pub fn interpolate<T>(&self, bary: Vec3, a_attr: T, b_attr: T, c_attr: T) -> T
+    where
+        T: Add<Output = T> + Mul<f64, Output = T>,
+    {
+        barycentric_interpolate(bary, a_attr, b_attr, c_attr)
+    }
+
     /// Return the closed boundary of the triangle.
     pub fn boundary(&self) -> Polyline {
         vec![self.a, self.b, self.c, self.a].into()
@@ -100,44 +113,76 @@ impl Triangle {
     }
 }
 
+/// Interpolate the three values `a`, `b` and `c` with the given barycentric
+/// coordinates, that is calculate `a * bary.x + b * bary.y + c * bary.z`.
+pub fn barycentric_interpolate<T>(bary: Vec3, a: T, b: T, c: T) -> T
+where
+    T: Add<Output = T> + Mul<f64, Output = T>,
+{
+    a * bary.x + b * bary.y + c * bary.z
+}
+
 impl Shape for Triangle {
     type Intersection = f64;
 
     /// Return the parameter t of the intersection between the ray and a
     /// triangle if any.
+    ///
+    /// This is the [watertight algorithm][0] by Woop, Benthin and Wald: the
+    /// triangle is transformed in a space where the ray starts at the origin
+    /// and goes along +Z and then the edge functions are evaluated in 2D. The
+    /// edge functions of an edge shared by two triangles are exactly the
+    /// same, but with opposite sign, hence rays hitting it are never lost
+    /// between the triangles.
+    ///
+    /// [0]: https://jcgt.org/published/0002/01/05/
     fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
-        let e1 = self.b - self.a;
-        let e2 = self.c - self.a;
+        // permute the axes so that Z is the dominant axis of the ray and swap
+        // X and Y to preserve the winding of the triangle
+        let d = ray.dir;
+        let kz = if d.x.abs() >= d.y.abs() && d.x.abs() >= d.z.abs() {
+            Axis::X
+        } else if d.y.abs() >= d.z.abs() {
+            Axis::Y
+        } else {
+            Axis::Z
+        };
+        let (mut kx, mut ky) = match kz {
+            Axis::X => (Axis::Y, Axis::Z),
+            Axis::Y => (Axis::Z, Axis::X),
+            Axis::Z => (Axis::X, Axis::Y),
+        };
+        if d[kz] < 0.0 {
+            std::mem::swap(&mut kx, &mut ky);
+        }
 
-        let px = ray.dir.y * e2.z - ray.dir.z * e2.y;
-        let py = ray.dir.z * e2.x - ray.dir.x * e2.z;
-        let pz = ray.dir.x * e2.y - ray.dir.y * e2.x;
+        // shear the vertices relative to the origin of the ray so that the
+        // ray goes along +Z
+        let (sx, sy, sz) = (d[kx] / d[kz], d[ky] / d[kz], 1.0 / d[kz]);
+        let [a, b, c] = [self.a, self.b, self.c].map(|p| {
+            let p = p - ray.origin;
+            (p[kx] - sx * p[kz], p[ky] - sy * p[kz], sz * p[kz])
+        });
 
-        let det = e1.x * px + e1.y * py + e1.z * pz;
-        if det.abs() < 1e-9 {
-            return None;
-        }
+        // edge functions, a point is inside iff they all have the same sign
+        let u = c.0 * b.1 - c.1 * b.0;
+        let v = a.0 * c.1 - a.1 * c.0;
+        let w = b.0 * a.1 - b.1 * a.0;
 
-        let inv = 1.0 / det;
-        let t = ray.origin - self.a;
-        let u = (t.x * px + t.y * py + t.z * pz) * inv;
-        if !(0.0..=1.0).contains(&u) {
+        if (u < 0.0 || v < 0.0 || w < 0.0) && (u > 0.0 || v > 0.0 || w > 0.0) {
             return None;
         }
 
-        let qx = t.y * e1.z - t.z * e1.y;
-        let qy = t.z * e1.x - t.x * e1.z;
-        let qz = t.x * e1.y - t.y * e1.x;
-        let v = (ray.dir.x * qx + ray.dir.y * qy + ray.dir.z * qz) * inv;
-        if v < 0.0 || u + v > 1.0 {
+        let det = u + v + w;
+        if det == 0.0 {
             return None;
         }
 
-        let d = (e2.x * qx + e2.y * qy + e2.z * qz) * inv;
-        if d < 1e-9 {
+        let t = (u * a.2 + v * b.2 + w * c.2) / det;
+        if t < 1e-9 {
             None
         } else {
-            Some(d)
+            Some(t)
         }
     }
 
@@ -152,6 +197,8 @@ impl Shape for Triangle {
 
 #[cfg(test)]
 mod tests {
+    use crate::Vec2;
+
     use super::*;
 
     #[test]
@@ -167,6 +214,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_triangle_interpolate() {
+        let t = Triangle::new(v3(0, 0, 0), v3(4, 0, 0), v3(0, 4, 0));
+
+        let p = v3(1, 2, 0);
+        let bary = t.barycentric(&p).unwrap();
+        assert_eq!(t.interpolate(bary, t.a, t.b, t.c), p);
+
+        let uv = t.interpolate(
+            bary,
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 1.0),
+        );
+        assert_eq!(uv, Vec2::new(0.25, 0.5));
+
+        assert_eq!(
+            barycentric_interpolate(v3(0.5, 0.25, 0.25), 2.0, 4.0, 8.0),
+            4.0
+        );
+    }
+
     #[test]
     fn test_triangle_normal() {
         assert_eq!(
@@ -217,4 +286,57 @@ mod tests {
             None
         );
     }
+
+    #[test]
+    fn test_triangle_intersection() {
+        let tri = Triangle::new(v3(0, 0, 0), v3(1, 0, 0), v3(0, 1, 0));
+
+        assert_eq!(
+            tri.intersection(&Ray::new(v3(0.25, 0.25, 2.0), v3(0, 0, -1))),
+            Some(2.0)
+        );
+        assert_eq!(
+            tri.intersection(&Ray::new(v3(0.25, 0.25, -2.0), v3(0, 0, 1))),
+            Some(2.0)
+        );
+        assert_eq!(
+            tri.intersection(&Ray::new(v3(0.75, 0.75, 2.0), v3(0, 0, -1))),
+            None
+        );
+        assert_eq!(
+            tri.intersection(&Ray::new(v3(0.25, 0.25, -2.0), v3(0, 0, -1))),
+            None
+        );
+        assert_eq!(
+            tri.intersection(&Ray::new(v3(-1.0, 0.25, 0.0), v3(1, 0, 0))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_triangle_intersection_watertight() {
+        // a fan of thin triangles around the origin, every ray going through
+        // one of the shared edges must hit at least one of them
+        let n = 37;
+        let rim = (0..n)
+            .map(|i| {
+                let a = i as f64 / n as f64 * std::f64::consts::TAU;
+                v3(a.cos() * 3.0, a.sin() * 3.0, 0.7)
+            })
+            .collect::<Vec<_>>();
+        let fan = (0..n)
+            .map(|i| Triangle::new(v3(0.1, 0.2, 0.3), rim[i], rim[(i + 1) % n]))
+            .collect::<Vec<_>>();
+
+        let origin = v3(0.3, -0.2, 5.0);
+        for tri in &fan {
+            for k in 1..50 {
+                let k = k as f64 / 50.0;
+                let p = tri.a + (tri.b - tri.a) * k;
+                let ray = Ray::new(origin, (p - origin).normalized());
+
+                assert!(fan.iter().any(|t| t.intersection(&ray).is_some()));
+            }
+        }
+    }
 }
@@ -0,0 +1,210 @@
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// A simple 2D vector, mostly useful to manipulate the output of the line
+/// renderers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vec2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Tiny alias for `Vec2::new()`.
+pub fn v2(x: impl Into<f64>, y: impl Into<f64>) -> Vec2 {
+    Vec2::new(x.into(), y.into())
+}
+
+impl Vec2 {
+    /// Create a new `Vec2` with the given coordinates.
+    pub const fn new(x: f64, y: f64) -> Self {
+        Vec2 { x, y }
+    }
+
+    /// `Vec2` with everything set to 0.
+    pub const fn zero() -> Self {
+        Vec2::new(0.0, 0.0)
+    }
+
+    /// Calculate the distance between two `Vec2`.
+    pub fn dist(&self, other: Vec2) -> f64 {
+        self.dist2(other).sqrt()
+    }
+
+    /// Calculate the squared distance between two `Vec2`.
+    pub fn dist2(&self, other: Vec2) -> f64 {
+        (self.x - other.x).powi(2) + (self.y - other.y).powi(2)
+    }
+
+    /// Calculate the norm or length of this `Vec2`.
+    pub fn norm(&self) -> f64 {
+        self.norm2().sqrt()
+    }
+
+    /// Calculate the squared norm or length of this `Vec2`.
+    pub fn norm2(&self) -> f64 {
+        self.x.powi(2) + self.y.powi(2)
+    }
+
+    /// Normalize this `Vec2` so that its norm is 1.
+    pub fn normalize(&mut self) {
+        *self /= self.norm();
+    }
+
+    /// Return a new normalized copy of this `Vec2`.
+    pub fn normalized(mut self) -> Self {
+        self.normalize();
+        self
+    }
+
+    /// Calculate the [dot product][0] between two `Vec2`.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/Dot_product
+    pub fn dot(&self, v: Vec2) -> f64 {
+        self.x * v.x + self.y * v.y
+    }
+
+    /// Calculate the z component of the cross product between two `Vec2`
+    /// lying on the z=0 plane.
+    ///
+    /// It's positive when `v` is counterclockwise from `self`.
+    pub fn cross(&self, v: Vec2) -> f64 {
+        self.x * v.y - self.y * v.x
+    }
+
+    /// Return the `Vec2` rotated by 90 degrees counterclockwise.
+    pub fn perp(&self) -> Self {
+        Vec2::new(-self.y, self.x)
+    }
+
+    /// Return the component-wise minimum between two `Vec2`.
+    pub fn min(&self, v: Vec2) -> Self {
+        Vec2::new(self.x.min(v.x), self.y.min(v.y))
+    }
+
+    /// Return the component-wise maximum between two `Vec2`.
+    pub fn max(&self, v: Vec2) -> Self {
+        Vec2::new(self.x.max(v.x), self.y.max(v.y))
+    }
+
+    /// Linear interpolation between two `Vec2`.
+    ///
+    /// ```rust
+    /// # use geo::{Vec2, v2};
+    ///
+    /// let o = Vec2::zero();
+    /// let p = v2(2.0, -6.0);
+    ///
+    /// assert_eq!(Vec2::lerp(o, p, 0.0), o);
+    /// assert_eq!(Vec2::lerp(o, p, 1.0), p);
+    /// assert_eq!(Vec2::lerp(o, p, 0.5), v2(1.0, -3.0));
+    /// ```
+    pub fn lerp(self, b: Vec2, t: f64) -> Vec2 {
+        self * (1.0 - t) + b * t
+    }
+
+    /// Check whether a `Vec2` is not NaN or infinite.
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite()
+    }
+
+    /// Return the distance from a `Vec2` to a line segment from `v` to `w`.
+    pub fn segment_dist(&self, v: Vec2, w: Vec2) -> f64 {
+        let l2 = v.dist2(w);
+        if l2 == 0.0 {
+            return self.dist(v);
+        }
+
+        let t = ((*self - v).dot(w - v) / l2).clamp(0.0, 1.0);
+        (v + (w - v) * t).dist(*self)
+    }
+}
+
+macro_rules! impl_num_op {
+    ($tr:ident, $fn:ident, $op:tt, $assign_tr:ident, $assign_fn:ident) => {
+        impl $tr for Vec2 {
+            type Output = Vec2;
+
+            fn $fn(self, v: Vec2) -> Self::Output {
+                Vec2::new(self.x $op v.x, self.y $op v.y)
+            }
+        }
+
+        impl $tr<f64> for Vec2 {
+            type Output = Vec2;
+
+            fn $fn(self, s: f64) -> Self::Output {
+                Vec2::new(self.x $op s, self.y $op s)
+            }
+        }
+
+        impl $assign_tr for Vec2 {
+            fn $assign_fn(&mut self, v: Vec2) {
+                self.x.$assign_fn(v.x);
+                self.y.$assign_fn(v.y);
+            }
+        }
+
+        impl $assign_tr<f64> for Vec2 {
+            fn $assign_fn(&mut self, s: f64) {
+                self.x.$assign_fn(s);
+                self.y.$assign_fn(s);
+            }
+        }
+    };
+}
+
+impl_num_op!(Add, add, +, AddAssign, add_assign);
+impl_num_op!(Sub, sub, -, SubAssign, sub_assign);
+impl_num_op!(Mul, mul, *, MulAssign, mul_assign);
+impl_num_op!(Div, div, /, DivAssign, div_assign);
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Self::Output {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+impl Sum for Vec2 {
+    fn sum<I: Iterator<Item = Vec2>>(iter: I) -> Vec2 {
+        iter.fold(Vec2::zero(), Add::add)
+    }
+}
+
+impl From<(f64, f64)> for Vec2 {
+    fn from((x, y): (f64, f64)) -> Self {
+        Vec2::new(x, y)
+    }
+}
+
+impl From<Vec2> for (f64, f64) {
+    fn from(v: Vec2) -> Self {
+        (v.x, v.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_dist() {
+        let (a, b) = (v2(0, 0), v2(4, 0));
+
+        assert_eq!(v2(2, 3).segment_dist(a, b), 3.0);
+        assert_eq!(v2(-3, 4).segment_dist(a, b), 5.0);
+        assert_eq!(v2(7, -4).segment_dist(a, b), 5.0);
+        assert_eq!(v2(1, 1).segment_dist(a, a), 2_f64.sqrt());
+    }
+
+    #[test]
+    fn test_cross_perp() {
+        let v = v2(1, 0);
+
+        assert_eq!(v.perp(), v2(0, 1));
+        assert_eq!(v.cross(v.perp()), 1.0);
+        assert_eq!(v.perp().cross(v), -1.0);
+        assert_eq!(v.dot(v.perp()), 0.0);
+    }
+}
@@ -113,6 +113,16 @@ impl Vec3 {
         self * (1.0 - t) + b * t
     }
 
+    /// Return the component-wise minimum between two `Vec3`.
+    pub fn min(&self, v: Vec3) -> Self {
+        Vec3::new(self.x.min(v.x), self.y.min(v.y), self.z.min(v.z))
+    }
+
+    /// Return the component-wise maximum between two `Vec3`.
+    pub fn max(&self, v: Vec3) -> Self {
+        Vec3::new(self.x.max(v.x), self.y.max(v.y), self.z.max(v.z))
+    }
+
     /// Check whether a `Vec3` is not NaN or infinite.
     ///
     /// ```rust
@@ -228,6 +238,12 @@ impl Product for Vec3 {
     }
 }
 
+impl From<[f64; 3]> for Vec3 {
+    fn from([x, y, z]: [f64; 3]) -> Self {
+        Vec3::new(x, y, z)
+    }
+}
+
 impl Distribution<Vec3> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3 {
         let (x, y, z) = rng.gen();
@@ -0,0 +1,255 @@
+//! Extract a triangle mesh from an `Sdf` using [dual contouring][0].
+//!
+//! [0]: https://www.cs.rice.edu/~jwarren/papers/dualcontour.pdf
+
+use std::collections::HashMap;
+
+use crate::{v3, Triangle, Vec3};
+
+use super::Sdf;
+
+/// Weight of the bias towards the mass point used to place each vertex, it
+/// keeps the vertices stable on flat regions where the planes are parallel.
+const QEF_BIAS: f64 = 1e-3;
+
+/// Convert the given `Sdf` to a closed triangle mesh.
+///
+/// The bounding box of the `Sdf` is split into cubic cells so that there are
+/// `resolution` cells along its longest side. Each cell the surface passes
+/// through gets a vertex placed where the tangent planes at the surface
+/// crossings on the cell edges meet, therefore sharp edges and corners are
+/// preserved unlike marching cubes. The triangles are oriented so that their
+/// normals point outside.
+pub fn to_mesh(sdf: &Sdf, resolution: u32) -> Vec<Triangle> {
+    assert!(resolution > 0);
+
+    let bbox = sdf.bbox();
+    let d = bbox.dimensions();
+    let step = d.x.max(d.y).max(d.z) / f64::from(resolution);
+    if step <= 0.0 {
+        return vec![];
+    }
+
+    // pad the grid by a cell on each side so that the mesh is closed even if
+    // the surface touches the bounding box
+    let origin = bbox.min() - step;
+    let [nx, ny, nz] = [d.x, d.y, d.z].map(|d| (d / step).ceil() as usize + 3);
+
+    let grid = Grid {
+        origin,
+        step,
+        dims: [nx, ny, nz],
+        dists: sample(sdf, origin, step, [nx, ny, nz]),
+    };
+
+    let mut vertices = HashMap::new();
+    let mut triangles = vec![];
+
+    // each edge of the grid crossing the surface is shared by 4 cells whose
+    // vertices form a quad
+    for z in 0..nz {
+        for y in 0..ny {
+            for x in 0..nx {
+                let d0 = grid.dist(x, y, z);
+
+                // the edges along each axis starting from the current point
+                // with the cells around them, in order around the edge
+                for ((dx, dy, dz), cells) in [
+                    ((1, 0, 0), [(0, 0, 0), (0, 1, 0), (0, 1, 1), (0, 0, 1)]),
+                    ((0, 1, 0), [(0, 0, 0), (0, 0, 1), (1, 0, 1), (1, 0, 0)]),
+                    ((0, 0, 1), [(0, 0, 0), (1, 0, 0), (1, 1, 0), (0, 1, 0)]),
+                ] {
+                    let (x1, y1, z1) = (x + dx, y + dy, z + dz);
+                    if x1 >= nx || y1 >= ny || z1 >= nz {
+                        continue;
+                    }
+
+                    let d1 = grid.dist(x1, y1, z1);
+                    if (d0 < 0.0) == (d1 < 0.0) {
+                        continue;
+                    }
+
+                    let mut quad = [Vec3::zero(); 4];
+                    let mut valid = true;
+                    for (q, (cx, cy, cz)) in quad.iter_mut().zip(cells) {
+                        // the cells are on the negative side of the edge
+                        if x < cx || y < cy || z < cz {
+                            valid = false;
+                            break;
+                        }
+
+                        let cell = (x - cx, y - cy, z - cz);
+                        *q = *vertices
+                            .entry(cell)
+                            .or_insert_with(|| grid.cell_vertex(sdf, cell));
+                    }
+
+                    if !valid {
+                        continue;
+                    }
+
+                    // the normal must point from the inside to the outside
+                    let sign = if d0 < 0.0 { 1.0 } else { -1.0 };
+                    let dir = v3(dx as f64, dy as f64, dz as f64) * sign;
+
+                    for t in [
+                        Triangle::new(quad[0], quad[1], quad[2]),
+                        Triangle::new(quad[0], quad[2], quad[3]),
+                    ] {
+                        if t.area() <= 0.0 {
+                            continue;
+                        }
+
+                        if t.normal().dot(dir) < 0.0 {
+                            triangles.push(Triangle::new(t.a, t.c, t.b));
+                        } else {
+                            triangles.push(t);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    triangles
+}
+
+struct Grid {
+    origin: Vec3,
+    step: f64,
+    dims: [usize; 3],
+    dists: Vec<f64>,
+}
+
+impl Grid {
+    fn dist(&self, x: usize, y: usize, z: usize) -> f64 {
+        self.dists[(z * self.dims[1] + y) * self.dims[0] + x]
+    }
+
+    fn point(&self, x: usize, y: usize, z: usize) -> Vec3 {
+        self.origin + v3(x as f64, y as f64, z as f64) * self.step
+    }
+
+    /// Find the vertex of the given cell minimizing the distance from the
+    /// tangent planes of the surface at the crossings on the cell edges.
+    fn cell_vertex(&self, sdf: &Sdf, (x, y, z): (usize, usize, usize)) -> Vec3 {
+        let corners = [
+            (0, 0, 0),
+            (1, 0, 0),
+            (0, 1, 0),
+            (1, 1, 0),
+            (0, 0, 1),
+            (1, 0, 1),
+            (0, 1, 1),
+            (1, 1, 1),
+        ]
+        .map(|(dx, dy, dz)| (x + dx, y + dy, z + dz));
+
+        let mut planes = vec![];
+        for (i, &(ax, ay, az)) in corners.iter().enumerate() {
+            for &(bx, by, bz) in &corners[i + 1..] {
+                // only the 12 edges of the cube, i.e. corners differing in
+                // exactly one coordinate
+                if usize::from(ax != bx) + usize::from(ay != by) + usize::from(az != bz) != 1 {
+                    continue;
+                }
+
+                let (da, db) = (self.dist(ax, ay, az), self.dist(bx, by, bz));
+                if (da < 0.0) == (db < 0.0) {
+                    continue;
+                }
+
+                let t = da / (da - db);
+                let p = self.point(ax, ay, az).lerp(self.point(bx, by, bz), t);
+                planes.push((p, sdf.normal_at(p)));
+            }
+        }
+
+        let (min, max) = (self.point(x, y, z), self.point(x + 1, y + 1, z + 1));
+
+        // minimize the sum of the squared distances from the planes plus a
+        // small bias towards the mass point by solving the normal equations
+        // relative to the mass point with Cramer's rule
+        let mass = planes.iter().map(|(p, _)| *p).sum::<Vec3>() / planes.len() as f64;
+
+        let mut cols = [v3(QEF_BIAS, 0, 0), v3(0, QEF_BIAS, 0), v3(0, 0, QEF_BIAS)];
+        let mut b = Vec3::zero();
+        for (p, n) in planes.iter().filter(|(_, n)| n.is_finite()) {
+            cols[0] += *n * n.x;
+            cols[1] += *n * n.y;
+            cols[2] += *n * n.z;
+            b += *n * n.dot(*p - mass);
+        }
+
+        let [c0, c1, c2] = cols;
+        let det = c0.dot(c1.cross(c2));
+        let offset = v3(
+            b.dot(c1.cross(c2)),
+            c0.dot(b.cross(c2)),
+            c0.dot(c1.cross(b)),
+        ) / det;
+
+        (mass + offset).max(min).min(max)
+    }
+}
+
+/// Evaluate the `Sdf` at each point of the grid one layer at a time.
+fn sample(sdf: &Sdf, origin: Vec3, step: f64, [nx, ny, nz]: [usize; 3]) -> Vec<f64> {
+    let mut dists = vec![0.0; nx * ny * nz];
+    let mut pts = Vec::with_capacity(nx * ny);
+
+    for (z, layer) in dists.chunks_mut(nx * ny).enumerate() {
+        pts.clear();
+        for y in 0..ny {
+            pts.extend((0..nx).map(|x| origin + v3(x as f64, y as f64, z as f64) * step));
+        }
+
+        sdf.dist_many(&pts, layer);
+    }
+
+    dists
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::sdf::{cuboid, sphere};
+
+    #[test]
+    fn test_sphere() {
+        let mesh = to_mesh(&sphere(5.0), 20);
+        assert!(!mesh.is_empty());
+
+        for t in &mesh {
+            for p in [t.a, t.b, t.c] {
+                assert!((p.norm() - 5.0).abs() < 0.1, "{p:?}");
+            }
+
+            // outward normals
+            assert!(t.normal().dot(t.centroid()) > 0.0);
+        }
+
+        let area = mesh.iter().map(Triangle::area).sum::<f64>();
+        let expected = 4.0 * std::f64::consts::PI * 25.0;
+        assert!(
+            (area - expected).abs() / expected < 0.05,
+            "{area} {expected}"
+        );
+    }
+
+    #[test]
+    fn test_sharp_edges() {
+        let mesh = to_mesh(&cuboid(v3(4, 4, 4)), 7);
+
+        let area = mesh.iter().map(Triangle::area).sum::<f64>();
+        assert!((area - 96.0).abs() < 0.05, "{area}");
+
+        // the corners must be preserved
+        for c in [v3(-2, -2, -2), v3(2, 2, 2), v3(-2, 2, -2)] {
+            assert!(mesh
+                .iter()
+                .any(|t| [t.a, t.b, t.c].iter().any(|p| p.dist(c) < 1e-2)));
+        }
+    }
+}
@@ -0,0 +1,290 @@
+//! Declarative description of an `Sdf` that can be deserialized from scene
+//! files.
+
+use serde::Deserialize;
+
+use crate::{mat4::Mat4, sdf::*, sdf2::Sdf2Desc};
+
+/// A serializable description of an `Sdf` built out of the primitives and the
+/// boolean operations in this module.
+///
+/// In TOML a sphere with a hole looks like
+///
+/// ```toml
+/// type = "difference"
+/// from = { type = "sphere", radius = 10.0 }
+/// sub = [{ type = "cylinder", radius = 3.0, height = 30.0 }]
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum SdfDesc {
+    Sphere {
+        radius: f64,
+    },
+    Cuboid {
+        size: [f64; 3],
+    },
+    Cylinder {
+        radius: f64,
+        height: f64,
+    },
+    RoundedCuboid {
+        size: [f64; 3],
+        radius: f64,
+    },
+    RoundedCylinder {
+        radius: f64,
+        height: f64,
+        rounding: f64,
+    },
+    Torus {
+        r1: f64,
+        r2: f64,
+    },
+    Capsule {
+        a: [f64; 3],
+        b: [f64; 3],
+        radius: f64,
+    },
+    Octahedron {
+        radius: f64,
+    },
+    CappedCone {
+        height: f64,
+        bottom_radius: f64,
+        top_radius: f64,
+    },
+    Pyramid {
+        base: f64,
+        height: f64,
+    },
+    HexPrism {
+        radius: f64,
+        height: f64,
+    },
+    Helix {
+        radius: f64,
+        pitch: f64,
+        thickness: f64,
+        height: f64,
+    },
+    Gyroid {
+        size: [f64; 3],
+        period: f64,
+        thickness: f64,
+    },
+    SchwarzP {
+        size: [f64; 3],
+        period: f64,
+        thickness: f64,
+    },
+
+    /// a 2D profile in the xy plane extruded along the z axis
+    Extrude {
+        profile: Sdf2Desc,
+        height: f64,
+    },
+
+    /// a 2D profile revolved around the y axis
+    Revolve {
+        profile: Sdf2Desc,
+        #[serde(default)]
+        offset: f64,
+    },
+
+    Union {
+        of: Vec<SdfDesc>,
+    },
+    Intersection {
+        of: Vec<SdfDesc>,
+    },
+    Difference {
+        from: Box<SdfDesc>,
+        sub: Vec<SdfDesc>,
+    },
+    SmoothUnion {
+        of: Vec<SdfDesc>,
+        k: f64,
+    },
+
+    Translate {
+        by: [f64; 3],
+        sdf: Box<SdfDesc>,
+    },
+    Scale {
+        by: f64,
+        sdf: Box<SdfDesc>,
+    },
+
+    /// rotation around `axis` by `angle` degrees
+    Rotate {
+        axis: [f64; 3],
+        angle: f64,
+        sdf: Box<SdfDesc>,
+    },
+    Round {
+        radius: f64,
+        sdf: Box<SdfDesc>,
+    },
+    Shell {
+        thickness: f64,
+        sdf: Box<SdfDesc>,
+    },
+}
+
+impl SdfDesc {
+    /// Build the `Sdf` this `SdfDesc` describes.
+    ///
+    /// Return None if any union or intersection has no operands, including the
+    /// ones of the 2D profiles.
+    pub fn build(&self) -> Option<Sdf> {
+        let sdf = match self {
+            SdfDesc::Sphere { radius } => sphere(*radius),
+            SdfDesc::Cuboid { size } => cuboid((*size).into()),
+            SdfDesc::Cylinder { radius, height } => cylinder(*radius, *height),
+            SdfDesc::RoundedCuboid { size, radius } => rounded_cuboid((*size).into(), *radius),
+            SdfDesc::RoundedCylinder {
+                radius,
+                height,
+                rounding,
+            } => rounded_cylinder(*radius, *height, *rounding),
+            SdfDesc::Torus { r1, r2 } => torus(*r1, *r2),
+            SdfDesc::Capsule { a, b, radius } => capsule((*a).into(), (*b).into(), *radius),
+            SdfDesc::Octahedron { radius } => octahedron(*radius),
+            SdfDesc::CappedCone {
+                height,
+                bottom_radius,
+                top_radius,
+            } => capped_cone(*height, *bottom_radius, *top_radius),
+            SdfDesc::Pyramid { base, height } => pyramid(*base, *height),
+            SdfDesc::HexPrism { radius, height } => hex_prism(*radius, *height),
+            SdfDesc::Helix {
+                radius,
+                pitch,
+                thickness,
+                height,
+            } => helix(*radius, *pitch, *thickness, *height),
+            SdfDesc::Gyroid {
+                size,
+                period,
+                thickness,
+            } => gyroid((*size).into(), *period, *thickness),
+            SdfDesc::SchwarzP {
+                size,
+                period,
+                thickness,
+            } => schwarz_p((*size).into(), *period, *thickness),
+
+            SdfDesc::Extrude { profile, height } => extrude(profile.build()?, *height),
+            SdfDesc::Revolve { profile, offset } => revolve(profile.build()?, *offset),
+
+            SdfDesc::Union { of } => fold(of, |a, b| a | b)?,
+            SdfDesc::Intersection { of } => fold(of, |a, b| a & b)?,
+            SdfDesc::SmoothUnion { of, k } => fold(of, |a, b| a.smooth_union(b, *k))?,
+            SdfDesc::Difference { from, sub } => {
+                let mut sdf = from.build()?;
+                for s in sub {
+                    sdf = sdf - s.build()?;
+                }
+                sdf
+            }
+
+            SdfDesc::Translate { by, sdf } => sdf.build()? + Vec3::from(*by),
+            SdfDesc::Scale { by, sdf } => sdf.build()? * *by,
+            SdfDesc::Rotate { axis, angle, sdf } => {
+                sdf.build()? * Mat4::rotate((*axis).into(), angle.to_radians())
+            }
+            SdfDesc::Round { radius, sdf } => sdf.build()?.round(*radius),
+            SdfDesc::Shell { thickness, sdf } => sdf.build()?.shell(*thickness),
+        };
+
+        Some(sdf)
+    }
+}
+
+fn fold(descs: &[SdfDesc], f: impl Fn(Sdf, Sdf) -> Sdf) -> Option<Sdf> {
+    let mut sdfs = descs.iter().map(SdfDesc::build);
+
+    let first = sdfs.next()??;
+    sdfs.try_fold(first, |acc, s| Some(f(acc, s?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v3;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let desc: SdfDesc = toml::from_str(
+            r#"
+type = "difference"
+from = { type = "sphere", radius = 10.0 }
+sub = [{ type = "cylinder", radius = 3.0, height = 30.0 }]
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            desc,
+            SdfDesc::Difference {
+                from: Box::new(SdfDesc::Sphere { radius: 10.0 }),
+                sub: vec![SdfDesc::Cylinder {
+                    radius: 3.0,
+                    height: 30.0
+                }],
+            }
+        );
+
+        let json: SdfDesc = serde_json::from_str(
+            r#"{
+  "type": "difference",
+  "from": { "type": "sphere", "radius": 10 },
+  "sub": [{ "type": "cylinder", "radius": 3, "height": 30 }]
+}"#,
+        )
+        .unwrap();
+        assert_eq!(json, desc);
+
+        let sdf = desc.build().unwrap();
+        assert!(sdf.dist(&v3(0, 0, 0)) > 0.0);
+        assert!(sdf.dist(&v3(4, 4, 0)) < 0.0);
+        assert!(sdf.dist(&v3(0, 0, 11)) > 0.0);
+
+        let translated: SdfDesc = toml::from_str(
+            r#"
+type = "translate"
+by = [5.0, 0.0, 0.0]
+sdf = { type = "sphere", radius = 1.0 }
+"#,
+        )
+        .unwrap();
+        let sdf = translated.build().unwrap();
+        assert!((sdf.dist(&v3(5, 0, 0)) + 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_errors() {
+        for data in [
+            r#"type = "hyperboloid""#,
+            r#"radius = 1.0"#,
+            "type = \"sphere\"\nradius = 1.0\ncolor = \"red\"",
+            r#"type = "cylinder""#,
+            "type = \"translate\"\nby = [1.0, 2.0]\nsdf = { type = \"sphere\", radius = 1.0 }",
+        ] {
+            assert!(toml::from_str::<SdfDesc>(data).is_err(), "{data}");
+        }
+
+        // boolean operations without operands are well formed but cannot be
+        // built, not even when nested
+        for data in [
+            "type = \"union\"\nof = []",
+            "type = \"scale\"\nby = 2.0\nsdf = { type = \"intersection\", of = [] }",
+            "type = \"extrude\"\nheight = 1.0\nprofile = { type = \"union\", of = [] }",
+        ] {
+            let desc = toml::from_str::<SdfDesc>(data).unwrap();
+            assert!(desc.build().is_none(), "{data}");
+        }
+    }
+}
@@ -11,7 +11,15 @@ use std::{
 
 use crate::{mat4::Mat4, ray::Ray, v3, Aabb, Vec3};
 
+mod contour;
+#[cfg(feature = "serde")]
+pub mod desc;
 pub mod primitives;
+
+pub use contour::to_mesh;
+
+#[cfg(feature = "serde")]
+pub use desc::SdfDesc;
 pub use primitives::*;
 
 /// An SDF is a function that when called with a point it returns the distance
@@ -31,18 +39,77 @@ pub struct Sdf {
     /// - negative if the point is inside the object
     /// - positive if the point is outside the object
     /// - 0 (or almost 0) if the point is exactly on the boundary of the object
-    dist: Arc<dyn Fn(&Vec3) -> f64 + Send + Sync + 'static>,
+    dist: Arc<DistFn>,
+
+    /// Calculate the distances of many points at once, see `Sdf::dist_many`.
+    dist_many: Arc<DistManyFn>,
 
     /// The bounding box of the Sdf.
     bbox: Aabb,
 }
 
+/// Parameters of `Sdf::sphere_trace`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SphereTracing {
+    /// maximum number of evaluations of the Sdf before giving up.
+    pub max_steps: usize,
+
+    /// distance below which a point is considered on the surface.
+    pub epsilon: f64,
+
+    /// how much to lengthen each step, it must be in [1, 2). 1 disables
+    /// over-relaxation.
+    pub relaxation: f64,
+
+    /// number of bisection steps to use to refine the intersection when the
+    /// ray ends up inside the Sdf.
+    pub bisection_steps: usize,
+}
+
+/// The intersection found by `Sdf::sphere_trace`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceHit {
+    /// t parameter of the intersection along the `Ray`.
+    pub t: f64,
+
+    /// how many steps it took to find the intersection, useful to shade
+    /// areas that are hard to trace or for debugging.
+    pub steps: usize,
+}
+
+type DistFn = dyn Fn(&Vec3) -> f64 + Send + Sync + 'static;
+type DistManyFn = dyn Fn(&[Vec3], &mut [f64]) + Send + Sync + 'static;
+
 impl Sdf {
     /// Create the Sdf from the given function and with the given visibility
     /// bounding box.
     pub fn from_fn(bbox: Aabb, dist: impl Fn(&Vec3) -> f64 + Send + Sync + 'static) -> Self {
+        let dist = Arc::new(dist);
+
+        Self {
+            dist: dist.clone(),
+            dist_many: Arc::new(move |pts: &[Vec3], out: &mut [f64]| {
+                for (p, d) in pts.iter().zip(out) {
+                    *d = dist(p);
+                }
+            }),
+            bbox,
+        }
+    }
+
+    /// Create the Sdf from a function that calculates the distance of a single
+    /// point and one that calculates the distances of many points at once.
+    ///
+    /// The two functions must agree on the distances, the latter is just an
+    /// opportunity to amortize the cost of the evaluation over many points.
+    pub fn from_fns(
+        bbox: Aabb,
+        dist: impl Fn(&Vec3) -> f64 + Send + Sync + 'static,
+        dist_many: impl Fn(&[Vec3], &mut [f64]) + Send + Sync + 'static,
+    ) -> Self {
         Self {
             dist: Arc::new(dist),
+            dist_many: Arc::new(dist_many),
             bbox,
         }
     }
@@ -52,6 +119,17 @@ impl Sdf {
         (self.dist)(p)
     }
 
+    /// Calculate the distances of all the given points from the Sdf and store
+    /// them in `out`, which must be as long as `pts`.
+    ///
+    /// The combinators evaluate their operands over the whole batch at once
+    /// instead of point by point, hence this is much faster than calling
+    /// `Sdf::dist` for each point when evaluating an Sdf over a grid.
+    pub fn dist_many(&self, pts: &[Vec3], out: &mut [f64]) {
+        assert_eq!(pts.len(), out.len());
+        (self.dist_many)(pts, out)
+    }
+
     /// Return the bounding box of the Sdf
     pub fn bbox(&self) -> Aabb {
         self.bbox.clone()
@@ -62,8 +140,7 @@ impl Sdf {
     /// This is useful when the SDF is manually altered or created in a way
     /// that's not automatically tracked by the system.
     pub fn pad_bbox(mut self, p: f64) -> Self {
-        self.bbox.expand(self.bbox.min() - p);
-        self.bbox.expand(self.bbox.max() + p);
+        self.bbox = self.bbox.padded(p);
         self
     }
 
@@ -93,71 +170,189 @@ impl Sdf {
     /// before giving up and returning a no intersection.
     ///
     /// Returns either the t parameter of the intersection or none if no
-    /// intersection was found.
+    /// intersection was found. See `Sdf::sphere_trace` for more control.
     ///
     /// [0]: https://en.wikipedia.org/wiki/Volume_ray_casting#Ray_Marching
     pub fn ray_march(&self, ray: &Ray, steps: usize) -> Option<f64> {
-        let epsilon = 0.00001;
+        self.sphere_trace(ray, &SphereTracing::new(steps))
+            .map(|h| h.t)
+    }
 
+    /// Calculate the intersection between a given Ray and an Sdf using
+    /// [over-relaxed sphere tracing][0].
+    ///
+    /// Each step is made longer than the distance to the surface by the
+    /// relaxation factor which greatly reduces the number of steps needed
+    /// when the ray grazes a surface. If a step turns out to be too long, the
+    /// marching restarts from the last point known to be outside of the Sdf
+    /// and continues with plain sphere tracing, so that it never skips a
+    /// surface nor moves backwards past that point. Besides,
+    /// when the ray ends up inside the Sdf, because of a non exact distance
+    /// for example, the exact crossing is found by bisection so that thin
+    /// features are not missed and no surface is detected too far inside.
+    ///
+    /// [0]: https://erleuchtet.org/~cupe/permanent/enhanced_sphere_tracing.pdf
+    pub fn sphere_trace(&self, ray: &Ray, opts: &SphereTracing) -> Option<TraceHit> {
         let (t1, t2) = self.bbox.ray_intersection(ray)?;
         if t2 < t1 || t2 < 0.0 {
             return None;
         }
 
+        // distances are in world units while t is in units of the direction
+        let dir_norm = ray.dir.norm();
+
         let mut t = t1.max(0.0001);
+        let mut outside_t = t;
+        let mut omega = opts.relaxation;
+        let mut prev_d = 0.0;
+        let mut step = 0.0;
 
-        // ray marching
-        for _ in 0..steps {
+        for steps in 1..=opts.max_steps {
             let d = self.dist(&ray.point_at(t));
-            if d < epsilon {
-                return Some(t);
+
+            if d < 0.0 && steps > 1 {
+                let t = self.bisect(ray, outside_t, t, opts.bisection_steps);
+                return Some(TraceHit { t, steps });
             }
 
-            t += d;
+            // the unbounding spheres of two consecutive steps don't overlap,
+            // hence the surface could be between them: restart from the last
+            // safe point with a plain step, which is inside its unbounding
+            // sphere, and disable over-relaxation
+            if omega > 1.0 && (d + prev_d) / dir_norm < step {
+                omega = 1.0;
+                step = prev_d / dir_norm;
+                t = outside_t + step;
+                continue;
+            }
+
+            if d < opts.epsilon {
+                return Some(TraceHit { t, steps });
+            }
+
+            outside_t = t;
+            prev_d = d;
+            step = d * omega / dir_norm;
+            t += step;
 
             if t > t2 {
-                break;
+                if omega <= 1.0 {
+                    break;
+                }
+
+                // the relaxed step might have jumped over a surface right
+                // before leaving the bbox, check it with a plain step
+                omega = 1.0;
+                step = d / dir_norm;
+                t = outside_t + step;
+                if t > t2 {
+                    break;
+                }
             }
         }
 
         None
     }
 
+    /// Find the point where the Sdf crosses the surface between `outside`
+    /// and `inside` along the `Ray` by bisection.
+    fn bisect(&self, ray: &Ray, mut outside: f64, mut inside: f64, steps: usize) -> f64 {
+        for _ in 0..steps {
+            let mid = (outside + inside) / 2.0;
+            if self.dist(&ray.point_at(mid)) < 0.0 {
+                inside = mid;
+            } else {
+                outside = mid;
+            }
+        }
+
+        (outside + inside) / 2.0
+    }
+
     /// Return a shape that's the surface enlarged by the given thickness.
     ///
     /// All interiors beyond thickness are removed.
     pub fn shell(self, thickness: f64) -> Self {
-        let b = Aabb::new(self.bbox.min() - thickness).expanded(self.bbox.max() + thickness);
-        Self::from_fn(b, move |p| self.dist(p).abs() - thickness)
+        let b = self.bbox.padded(thickness);
+        self.map(b, |p| p, move |d| d.abs() - thickness)
     }
 
     /// Round the SDF with the given radius.
     pub fn round(self, radius: f64) -> Self {
-        Self::from_fn(self.bbox.clone(), move |p| self.dist(p) - radius)
+        let b = self.bbox.clone();
+        self.map(b, |p| p, move |d| d - radius)
     }
 
     /// Smoothly merge two SDFs together using the given blend factor.
     pub fn smooth_union(self, other: Sdf, k: f64) -> Self {
-        Self::from_fn(self.bbox.union(&other.bbox), move |p| {
-            smooth_union(self.dist(p), other.dist(p), k)
-        })
+        let b = self.bbox.union(&other.bbox);
+        self.combine(other, b, move |d1, d2| smooth_union(d1, d2, k))
     }
 
     /// Smoothly subtract the latter SDF from the former together using the
     /// given blend factor.
     pub fn smooth_sub(self, other: Sdf, k: f64) -> Self {
-        Self::from_fn(self.bbox.clone(), move |p| {
-            smooth_sub(self.dist(p), other.dist(p), k)
-        })
+        let b = self.bbox.clone();
+        self.combine(other, b, move |d1, d2| smooth_sub(d1, d2, k))
     }
 
     /// Smoothly intersect two SDFs using the given blend factor.
     pub fn smooth_and(self, other: Sdf, k: f64) -> Self {
-        Self::from_fn(
-            self.bbox
-                .intersection(&other.bbox)
-                .unwrap_or_else(|| Aabb::new(Vec3::zero())),
-            move |p| smooth_and(self.dist(p), other.dist(p), k),
+        let b = self
+            .bbox
+            .intersection(&other.bbox)
+            .unwrap_or_else(|| Aabb::new(Vec3::zero()));
+        self.combine(other, b, move |d1, d2| smooth_and(d1, d2, k))
+    }
+
+    /// Return a new Sdf with the given bbox that evaluates `self` at the
+    /// points transformed by `fp` and then transforms the distances with `fd`.
+    fn map(
+        self,
+        bbox: Aabb,
+        fp: impl Fn(Vec3) -> Vec3 + Send + Sync + 'static,
+        fd: impl Fn(f64) -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        let (fp, fd) = (Arc::new(fp), Arc::new(fd));
+        let sdf = self.clone();
+        let (fp2, fd2) = (fp.clone(), fd.clone());
+
+        Self::from_fns(
+            bbox,
+            move |p| fd(sdf.dist(&fp(*p))),
+            move |pts, out| {
+                let pts = pts.iter().map(|p| fp2(*p)).collect::<Vec<_>>();
+                self.dist_many(&pts, out);
+
+                for d in out {
+                    *d = fd2(*d);
+                }
+            },
+        )
+    }
+
+    /// Return a new Sdf with the given bbox that combines the distances of
+    /// `self` and `other` with the given function.
+    fn combine(
+        self,
+        other: Sdf,
+        bbox: Aabb,
+        f: impl Fn(f64, f64) -> f64 + Copy + Send + Sync + 'static,
+    ) -> Self {
+        let (a, b) = (self.clone(), other.clone());
+
+        Self::from_fns(
+            bbox,
+            move |p| f(a.dist(p), b.dist(p)),
+            move |pts, out| {
+                let mut tmp = vec![0.0; pts.len()];
+                self.dist_many(pts, out);
+                other.dist_many(pts, &mut tmp);
+
+                for (d, d2) in out.iter_mut().zip(tmp) {
+                    *d = f(*d, d2);
+                }
+            },
         )
     }
 }
@@ -166,9 +361,8 @@ impl Add<Vec3> for Sdf {
     type Output = Self;
 
     fn add(self, delta: Vec3) -> Self::Output {
-        Self::from_fn(self.bbox.translated(delta), move |p| {
-            self.dist(&(*p - delta))
-        })
+        let b = self.bbox.translated(delta);
+        self.map(b, move |p| p - delta, |d| d)
     }
 }
 
@@ -176,11 +370,8 @@ impl BitOr<Sdf> for Sdf {
     type Output = Self;
 
     fn bitor(self, rhs: Sdf) -> Self::Output {
-        Self::from_fn(self.bbox.union(&rhs.bbox), move |p| {
-            let ld = self.dist(p);
-            let rd = rhs.dist(p);
-            f64::min(ld, rd)
-        })
+        let b = self.bbox.union(&rhs.bbox);
+        self.combine(rhs, b, f64::min)
     }
 }
 
@@ -188,16 +379,11 @@ impl BitAnd<Sdf> for Sdf {
     type Output = Self;
 
     fn bitand(self, rhs: Sdf) -> Self::Output {
-        Self::from_fn(
-            self.bbox
-                .intersection(&rhs.bbox)
-                .unwrap_or_else(|| Aabb::new(Vec3::zero())),
-            move |p| {
-                let ld = self.dist(p);
-                let rd = rhs.dist(p);
-                f64::max(ld, rd)
-            },
-        )
+        let b = self
+            .bbox
+            .intersection(&rhs.bbox)
+            .unwrap_or_else(|| Aabb::new(Vec3::zero()));
+        self.combine(rhs, b, f64::max)
     }
 }
 
@@ -205,12 +391,8 @@ impl Sub<Self> for Sdf {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        Self::from_fn(self.bbox.clone(), move |p| {
-            let ld = self.dist(p);
-            let rd = rhs.dist(p);
-
-            f64::max(ld, -rd)
-        })
+        let b = self.bbox.clone();
+        self.combine(rhs, b, |ld, rd| f64::max(ld, -rd))
     }
 }
 
@@ -220,10 +402,7 @@ impl Mul<Mat4> for Sdf {
     fn mul(self, mat: Mat4) -> Self::Output {
         let inverse_matrix = mat.inverse();
         let bbox = self.bbox.clone() * &mat;
-        Sdf::from_fn(bbox, move |&p| {
-            let q = p * &inverse_matrix;
-            self.dist(&q)
-        })
+        self.map(bbox, move |p| p * &inverse_matrix, |d| d)
     }
 }
 
@@ -234,7 +413,34 @@ impl Mul<f64> for Sdf {
         let mut b = Aabb::new(self.bbox.min() * s);
         b.expand(self.bbox.max() * s);
 
-        Sdf::from_fn(b, move |p| self.dist(&(*p / s)) * s)
+        self.map(b, move |p| p / s, move |d| d * s)
+    }
+}
+
+impl SphereTracing {
+    pub fn new(max_steps: usize) -> Self {
+        Self {
+            max_steps,
+            epsilon: 0.00001,
+            relaxation: 1.2,
+            bisection_steps: 16,
+        }
+    }
+
+    pub fn with_epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    pub fn with_relaxation(mut self, relaxation: f64) -> Self {
+        assert!((1.0..2.0).contains(&relaxation));
+        self.relaxation = relaxation;
+        self
+    }
+
+    pub fn with_bisection_steps(mut self, bisection_steps: usize) -> Self {
+        self.bisection_steps = bisection_steps;
+        self
     }
 }
 
@@ -258,3 +464,103 @@ pub fn smooth_and(d1: f64, d2: f64, k: f64) -> f64 {
     let h = f64::clamp(0.5 - 0.5 * (d2 - d1) / k, 0.0, 1.0);
     d2 + (d1 - d2) * h + k * h * (1.0 - h)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sphere_trace() {
+        let sdf = sphere(1.0);
+        let ray = Ray::new(v3(0.5, 0.0, -5.0), v3(0, 0, 2));
+
+        let hit = sdf.sphere_trace(&ray, &SphereTracing::new(100)).unwrap();
+        assert!((hit.t - (5.0 - f64::sqrt(0.75)) / 2.0).abs() < 1e-5);
+        assert!(hit.steps > 1);
+
+        let plain = sdf
+            .sphere_trace(&ray, &SphereTracing::new(100).with_relaxation(1.0))
+            .unwrap();
+        assert!((plain.t - hit.t).abs() < 1e-5);
+
+        assert_eq!(sdf.sphere_trace(&ray, &SphereTracing::new(1)), None);
+        assert_eq!(
+            sdf.sphere_trace(
+                &Ray::new(v3(0, 3, -5), v3(0, 0, 1)),
+                &SphereTracing::new(100)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_sphere_trace_relaxation() {
+        // the bbox of the plane contains the origins of the rays so that the
+        // tracing starts right from them
+        let bbox = Aabb::new(v3(-20, -20, -1)).expanded(v3(20, 20, 10));
+        let plane = Sdf::from_fn(bbox, |p| p.z);
+
+        // rays from shallow to steep angles, where the relaxed steps overshoot
+        // and must be retried, must hit where plain sphere tracing does
+        for (sdf, target) in [(sphere(1.0), v3(0.3, 0.2, 0.0)), (plane, v3(0.0, 0.0, 0.0))] {
+            for i in 1..90 {
+                let angle = f64::from(i).to_radians();
+                let origin = target + v3(-5.0 * angle.cos(), 0.0, 5.0 * angle.sin());
+                let ray = Ray::new(origin, target - origin);
+
+                let plain = sdf
+                    .sphere_trace(&ray, &SphereTracing::new(1000).with_relaxation(1.0))
+                    .unwrap();
+
+                for relaxation in [1.2, 1.6, 1.9] {
+                    let opts = SphereTracing::new(1000).with_relaxation(relaxation);
+                    let hit = sdf.sphere_trace(&ray, &opts).unwrap();
+                    assert!(
+                        (hit.t - plain.t).abs() < 1e-3,
+                        "angle {i} relaxation {relaxation}: {hit:?} vs {plain:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sphere_trace_bisection() {
+        // the distance overestimates the real one by a lot, so the ray ends up
+        // inside the plate which must be found by bisection
+        let plate = cuboid(v3(10.0, 10.0, 0.1));
+        let sdf = Sdf::from_fn(plate.bbox(), move |p| plate.dist(p) * 10.0);
+
+        let ray = Ray::new(v3(0, 0, -5), v3(0, 0, 1));
+        let hit = sdf.sphere_trace(&ray, &SphereTracing::new(100)).unwrap();
+        assert!((hit.t - 4.95).abs() < 1e-3, "{hit:?}");
+    }
+
+    #[test]
+    fn test_dist_many() {
+        let sdf = ((sphere(5.0) + v3(1, 2, 3)) | cuboid(v3(4, 4, 4)))
+            .smooth_union(torus(1.0, 4.0) * Mat4::rotate(v3(1, 0, 0), 1.0), 0.5)
+            - capsule(v3(-3, 0, 0), v3(3, 0, 0), 1.0)
+                .shell(0.2)
+                .round(0.1)
+                * 2.0;
+
+        let pts = (0..1000)
+            .map(|i| {
+                let i = f64::from(i);
+                v3(
+                    (i * 0.37).sin() * 8.0,
+                    (i * 0.11).cos() * 8.0,
+                    i / 100.0 - 5.0,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut dists = vec![0.0; pts.len()];
+        sdf.dist_many(&pts, &mut dists);
+
+        for (p, d) in pts.iter().zip(dists) {
+            assert_eq!(d, sdf.dist(p));
+        }
+    }
+}
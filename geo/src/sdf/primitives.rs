@@ -1,11 +1,10 @@
-use crate::{sphere, v3, Aabb, Vec3};
+use crate::{sdf2::Sdf2, spatial_index::Shape, v2, v3, Aabb, Sphere, Vec3};
 
 use super::Sdf;
 
 pub fn sphere(radius: f64) -> Sdf {
-    Sdf::from_fn(sphere::bounding_box(Vec3::zero(), radius), move |p| {
-        p.norm() - radius
-    })
+    let s = Sphere::new(Vec3::zero(), radius);
+    Sdf::from_fn(s.bbox(), move |p| s.signed_distance(p))
 }
 
 pub fn cuboid(size: Vec3) -> Sdf {
@@ -54,6 +53,35 @@ pub fn cylinder(radius: f64, height: f64) -> Sdf {
     })
 }
 
+/// A box of the given size centered at the origin whose edges and corners are
+/// rounded by `radius`, the rounding doesn't change the size of the box.
+pub fn rounded_cuboid(size: Vec3, radius: f64) -> Sdf {
+    let h = size / 2.0;
+    let r = radius.clamp(0.0, h.x.min(h.y).min(h.z));
+
+    Sdf::from_fn(Aabb::new(-h).expanded(h), move |p| {
+        let q = v3(p.x.abs(), p.y.abs(), p.z.abs()) - h + r;
+        let outside = v3(q.x.max(0.0), q.y.max(0.0), q.z.max(0.0)).norm();
+
+        outside + q.x.max(q.y).max(q.z).min(0.0) - r
+    })
+}
+
+/// A cylinder along the y axis, like `cylinder`, whose caps are rounded by
+/// `rounding` without changing its size.
+pub fn rounded_cylinder(radius: f64, height: f64, rounding: f64) -> Sdf {
+    let r = rounding.clamp(0.0, radius.min(height / 2.0));
+    let bbox =
+        Aabb::new(v3(-radius, -height / 2.0, -radius)).expanded(v3(radius, height / 2.0, radius));
+
+    Sdf::from_fn(bbox, move |p| {
+        let dx = v3(p.x, 0.0, p.z).norm() - radius + r;
+        let dy = p.y.abs() - height / 2.0 + r;
+
+        dx.max(dy).min(0.0) + v2(dx.max(0.0), dy.max(0.0)).norm() - r
+    })
+}
+
 pub fn torus(r1: f64, r2: f64) -> Sdf {
     let a = r1;
     let b = r1 + r2;
@@ -87,3 +115,352 @@ pub fn octahedron(r: f64) -> Sdf {
         m * tan_30
     })
 }
+
+/// A cone along the y axis, centered at the origin, with the given bottom and
+/// top radii. Either radius can be 0 to get a pointy cone.
+pub fn capped_cone(height: f64, bottom_radius: f64, top_radius: f64) -> Sdf {
+    let h = height / 2.0;
+    let r = bottom_radius.max(top_radius);
+    let bbox = Aabb::new(v3(-r, -h, -r)).expanded(v3(r, h, r));
+
+    let (r1, r2) = (bottom_radius, top_radius);
+    Sdf::from_fn(bbox, move |p| {
+        let q = (v3(p.x, 0.0, p.z).norm(), p.y);
+        let k1 = (r2, h);
+        let k2 = (r2 - r1, 2.0 * h);
+
+        let ca = (
+            q.0 - q.0.min(if q.1 < 0.0 { r1 } else { r2 }),
+            q.1.abs() - h,
+        );
+
+        let t = ((k1.0 - q.0) * k2.0 + (k1.1 - q.1) * k2.1) / (k2.0.powi(2) + k2.1.powi(2));
+        let t = t.clamp(0.0, 1.0);
+        let cb = (q.0 - k1.0 + k2.0 * t, q.1 - k1.1 + k2.1 * t);
+
+        let s = if cb.0 < 0.0 && ca.1 < 0.0 { -1.0 } else { 1.0 };
+        let d2 = f64::min(ca.0.powi(2) + ca.1.powi(2), cb.0.powi(2) + cb.1.powi(2));
+
+        s * d2.sqrt()
+    })
+}
+
+/// A pyramid with a square base of the given size lying on the xz plane and
+/// with its apex at the given height on the y axis.
+pub fn pyramid(base: f64, height: f64) -> Sdf {
+    let b = base / 2.0;
+    let bbox = Aabb::new(v3(-b, 0, -b)).expanded(v3(b, height, b));
+
+    // the distance is calculated for a pyramid with a unit base and then
+    // scaled back
+    let h = height / base;
+    let m2 = h * h + 0.25;
+
+    Sdf::from_fn(bbox, move |p| {
+        let p = *p / base;
+
+        // below the base the closest point always lies on the base itself
+        if p.y < 0.0 {
+            let dx = f64::max(p.x.abs() - 0.5, 0.0);
+            let dz = f64::max(p.z.abs() - 0.5, 0.0);
+            return (dx * dx + p.y * p.y + dz * dz).sqrt() * base;
+        }
+
+        let (mut x, mut z) = (p.x.abs(), p.z.abs());
+        if z > x {
+            std::mem::swap(&mut x, &mut z);
+        }
+        x -= 0.5;
+        z -= 0.5;
+
+        let q = v3(z, h * p.y - 0.5 * x, h * x + 0.5 * p.y);
+
+        let s = f64::max(-q.x, 0.0);
+        let t = f64::clamp((q.y - 0.5 * z) / (m2 + 0.25), 0.0, 1.0);
+
+        let a = m2 * (q.x + s).powi(2) + q.y.powi(2);
+        let b = m2 * (q.x + 0.5 * t).powi(2) + (q.y - m2 * t).powi(2);
+
+        let d2 = if f64::min(q.y, -q.x * m2 - q.y * 0.5) > 0.0 {
+            0.0
+        } else {
+            a.min(b)
+        };
+
+        ((d2 + q.z * q.z) / m2).sqrt() * f64::max(q.z, -p.y).signum() * base
+    })
+}
+
+/// A prism along the y axis, centered at the origin, whose base is a regular
+/// hexagon with the given circumradius and two of its vertices on the x axis.
+pub fn hex_prism(radius: f64, height: f64) -> Sdf {
+    let (kx, ky, kz) = (-f64::sqrt(3.0) / 2.0, 0.5, 1.0 / f64::sqrt(3.0));
+    let apothem = radius * f64::sqrt(3.0) / 2.0;
+    let h = height / 2.0;
+
+    let bbox = Aabb::new(v3(-radius, -h, -apothem)).expanded(v3(radius, h, apothem));
+
+    Sdf::from_fn(bbox, move |p| {
+        let (mut x, mut y) = (p.x.abs(), p.z.abs());
+
+        let d = 2.0 * f64::min(kx * x + ky * y, 0.0);
+        x -= d * kx;
+        y -= d * ky;
+
+        let cx = x.clamp(-kz * apothem, kz * apothem);
+        let dx = ((x - cx).powi(2) + (y - apothem).powi(2)).sqrt() * (y - apothem).signum();
+        let dy = p.y.abs() - h;
+
+        f64::min(f64::max(dx, dy), 0.0) + (dx.max(0.0).powi(2) + dy.max(0.0).powi(2)).sqrt()
+    })
+}
+
+/// A spring, that is a tube of the given thickness that winds around the y
+/// axis at the given distance from it climbing by `pitch` each turn.
+///
+/// The spring is centered at the origin, it's `height` tall and it's cut flat
+/// at both ends. The distance is only an approximation that gets worse as the
+/// pitch grows.
+pub fn helix(radius: f64, pitch: f64, thickness: f64, height: f64) -> Sdf {
+    let r = radius + thickness;
+    let h = height / 2.0;
+    let bbox = Aabb::new(v3(-r, -h, -r)).expanded(v3(r, h, r));
+
+    // the tube is slanted, project the vertical distance on the normal of the
+    // helix to get a closer estimate
+    let cos = f64::cos(f64::atan(pitch / (2.0 * std::f64::consts::PI * radius)));
+
+    Sdf::from_fn(bbox, move |p| {
+        let turn = p.z.atan2(p.x) / (2.0 * std::f64::consts::PI);
+
+        let dy = (p.y - turn * pitch).rem_euclid(pitch);
+        let dy = if dy > pitch / 2.0 { dy - pitch } else { dy };
+
+        let dr = v3(p.x, 0.0, p.z).norm() - radius;
+        let d = (dr.powi(2) + (dy * cos).powi(2)).sqrt() - thickness;
+
+        d.max(p.y.abs() - h)
+    })
+}
+
+/// A [gyroid][0] surface with the given thickness that repeats every `period`
+/// units, trimmed to a box of the given size centered at the origin.
+///
+/// [0]: https://en.wikipedia.org/wiki/Gyroid
+pub fn gyroid(size: Vec3, period: f64, thickness: f64) -> Sdf {
+    // 1.5 is an estimate of the maximum gradient of the gyroid function
+    tpms(size, period, thickness, 1.5, |x, y, z| {
+        x.sin() * y.cos() + y.sin() * z.cos() + z.sin() * x.cos()
+    })
+}
+
+/// A [Schwarz P][0] surface with the given thickness that repeats every
+/// `period` units, trimmed to a box of the given size centered at the origin.
+///
+/// [0]: https://en.wikipedia.org/wiki/Schwarz_minimal_surface
+pub fn schwarz_p(size: Vec3, period: f64, thickness: f64) -> Sdf {
+    tpms(size, period, thickness, f64::sqrt(3.0), |x, y, z| {
+        x.cos() + y.cos() + z.cos()
+    })
+}
+
+/// Extrude the given 2D profile, which lies in the xy plane, along the z axis
+/// by `height` symmetrically around the origin.
+pub fn extrude(profile: Sdf2, height: f64) -> Sdf {
+    let h = height / 2.0;
+    let (min, max) = profile.bbox();
+    let bbox = Aabb::new(v3(min.x, min.y, -h)).expanded(v3(max.x, max.y, h));
+
+    Sdf::from_fn(bbox, move |p| {
+        let dxy = profile.dist(&v2(p.x, p.y));
+        let dz = p.z.abs() - h;
+
+        dxy.max(dz).min(0.0) + v2(dxy.max(0.0), dz.max(0.0)).norm()
+    })
+}
+
+/// Revolve the given 2D profile around the y axis like a lathe does. The x
+/// coordinate of the profile is the distance from the axis minus `offset`, so
+/// that the same profile can be used to make rings of different radii, while
+/// the y one is the height.
+///
+/// The part of the profile that ends up on the other side of the axis is
+/// ignored.
+pub fn revolve(profile: Sdf2, offset: f64) -> Sdf {
+    let (min, max) = profile.bbox();
+    let r = (max.x + offset).max(0.0);
+    let bbox = Aabb::new(v3(-r, min.y, -r)).expanded(v3(r, max.y, r));
+
+    Sdf::from_fn(bbox, move |p| {
+        profile.dist(&v2(v3(p.x, 0.0, p.z).norm() - offset, p.y))
+    })
+}
+
+/// Turn the implicit function of a [triply periodic minimal surface][0] into a
+/// shell of the given thickness bounded by a box of the given size.
+///
+/// Since the implicit function is not a distance it's scaled by the period and
+/// its maximum gradient to get an approximation of the distance.
+///
+/// [0]: https://en.wikipedia.org/wiki/Triply_periodic_minimal_surface
+fn tpms(
+    size: Vec3,
+    period: f64,
+    thickness: f64,
+    max_gradient: f64,
+    f: impl Fn(f64, f64, f64) -> f64 + Send + Sync + 'static,
+) -> Sdf {
+    let scale = 2.0 * std::f64::consts::PI / period;
+    let bounds = cuboid(size);
+
+    Sdf::from_fn(bounds.bbox(), move |p| {
+        let q = *p * scale;
+        let d = f(q.x, q.y, q.z).abs() / (scale * max_gradient) - thickness / 2.0;
+
+        d.max(bounds.dist(p))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check the surface of the sdf never lies outside its bounding box and
+    /// that the bounding box is tight.
+    fn assert_bbox(sdf: &Sdf) {
+        let bbox = sdf.bbox();
+        let (min, max) = (bbox.min() - 1.0, bbox.max() + 1.0);
+        let n = 40;
+
+        let mut inside = Aabb::new(bbox.center());
+        for i in 0..=n {
+            for j in 0..=n {
+                for k in 0..=n {
+                    let t = v3(i, j, k) / f64::from(n);
+                    let p = v3(
+                        min.x + (max.x - min.x) * t.x,
+                        min.y + (max.y - min.y) * t.y,
+                        min.z + (max.z - min.z) * t.z,
+                    );
+
+                    if sdf.dist(&p) <= 0.0 {
+                        assert!(bbox.padded(1e-9).contains(&p), "{p:?} outside {bbox:?}");
+                        inside.expand(p);
+                    }
+                }
+            }
+        }
+
+        let step = (max - min) / f64::from(n);
+        assert!(inside.min().dist(bbox.min()) <= step.norm());
+        assert!(inside.max().dist(bbox.max()) <= step.norm());
+    }
+
+    #[test]
+    fn test_rounded_cuboid() {
+        let b = rounded_cuboid(v3(4, 2, 2), 0.5);
+        assert!((b.dist(&v3(0, 0, 0)) + 1.0).abs() < 1e-9);
+        assert!((b.dist(&v3(3, 0, 0)) - 1.0).abs() < 1e-9);
+
+        // the corners are carved by a sphere of the rounding radius
+        let corner = v3(1.5, 0.5, 0.5);
+        let d = b.dist(&(corner + v3(1, 1, 1).normalized()));
+        assert!((d - 0.5).abs() < 1e-9, "{d}");
+
+        let sharp = cuboid(v3(4, 2, 2));
+        assert!(
+            (rounded_cuboid(v3(4, 2, 2), 0.0).dist(&v3(3, 2, 1)) - sharp.dist(&v3(3, 2, 1))).abs()
+                < 1e-9
+        );
+        assert_bbox(&b);
+    }
+
+    #[test]
+    fn test_rounded_cylinder() {
+        let c = rounded_cylinder(2.0, 4.0, 0.5);
+        assert!((c.dist(&v3(0, 0, 0)) + 2.0).abs() < 1e-9);
+        assert!(c.dist(&v3(2, 0, 0)).abs() < 1e-9);
+        assert!(c.dist(&v3(0, 2, 0)).abs() < 1e-9);
+        assert!((c.dist(&v3(2, 2, 0)) - (f64::sqrt(0.5) - 0.5)).abs() < 1e-9);
+        assert_bbox(&c);
+    }
+
+    #[test]
+    fn test_extrude() {
+        let e = extrude(crate::sdf2::rect(v2(2, 4)), 6.0);
+        let c = cuboid(v3(2, 4, 6));
+
+        for p in [v3(0, 0, 0), v3(3, 1, 0), v3(2, 3, 4), v3(0.5, -1.0, 3.0)] {
+            assert!((e.dist(&p) - c.dist(&p)).abs() < 1e-9, "{p:?}");
+        }
+        assert_bbox(&e);
+    }
+
+    #[test]
+    fn test_revolve() {
+        // a circle revolved around an axis far from it is a torus
+        let r = revolve(crate::sdf2::circle(1.0), 3.0);
+        assert!((r.dist(&v3(3, 0, 0)) + 1.0).abs() < 1e-9);
+        assert!((r.dist(&v3(0, 0, -4)).abs()) < 1e-9);
+        assert!((r.dist(&v3(0, 0, 0)) - 2.0).abs() < 1e-9);
+        assert!((r.dist(&v3(0, 2, 3)) - 1.0).abs() < 1e-9);
+        assert_bbox(&r);
+
+        // while a rectangle touching the axis is a cylinder
+        let r = revolve(crate::sdf2::rect(v2(4, 4)), 0.0);
+        let c = cylinder(2.0, 4.0);
+        for p in [v3(0, 0, 0), v3(1, 3, 1), v3(3, 0, 0), v3(-1, 1, 0.5)] {
+            assert!((r.dist(&p) - c.dist(&p)).abs() < 1e-9, "{p:?}");
+        }
+    }
+
+    #[test]
+    fn test_capped_cone() {
+        let cone = capped_cone(4.0, 2.0, 1.0);
+        assert!(cone.dist(&v3(1.5, 0, 0)).abs() < 1e-9);
+        assert!((cone.dist(&v3(0, 0, 0)) + 6.0 / f64::sqrt(17.0)).abs() < 1e-9);
+        assert!((cone.dist(&v3(0, 3, 0)) - 1.0).abs() < 1e-9);
+        assert!((cone.dist(&v3(0, -2, 0))).abs() < 1e-9);
+        assert_bbox(&cone);
+    }
+
+    #[test]
+    fn test_pyramid() {
+        let p = pyramid(2.0, 3.0);
+        assert!(p.dist(&v3(0, 1, 0)) < 0.0);
+        assert!(p.dist(&v3(0, 3, 0)).abs() < 1e-9);
+        assert!((p.dist(&v3(0, -1, 0)) - 1.0).abs() < 1e-9);
+        assert_bbox(&p);
+    }
+
+    #[test]
+    fn test_hex_prism() {
+        let h = hex_prism(2.0, 2.0);
+        assert!(h.dist(&v3(2, 0, 0)).abs() < 1e-9);
+        assert!((h.dist(&v3(0, 0, 0)) + 1.0).abs() < 1e-9);
+        assert!((h.dist(&v3(0, 0, 3)) - (3.0 - f64::sqrt(3.0))).abs() < 1e-9);
+        assert_bbox(&h);
+    }
+
+    #[test]
+    fn test_helix() {
+        let h = helix(5.0, 4.0, 1.0, 20.0);
+        assert!(h.dist(&v3(5, 0, 0)) < 0.0);
+        assert!(h.dist(&v3(5, 2, 0)) > 0.0);
+        assert!(h.dist(&v3(0, 0, 0)) > 0.0);
+        assert_bbox(&h);
+    }
+
+    #[test]
+    fn test_tpms() {
+        let g = gyroid(v3(10, 10, 10), 5.0, 0.5);
+        assert!(g.dist(&v3(0, 0, 0)) < 0.0);
+        assert!(g.dist(&v3(6, 0, 0)) > 0.0);
+        assert_bbox(&g);
+
+        let s = schwarz_p(v3(10, 10, 10), 4.0, 0.5);
+        assert!(s.dist(&v3(1, 1, 1)) < 0.0);
+        assert!(s.dist(&v3(0, 0, 0)) > 0.0);
+        assert_bbox(&s);
+    }
+}
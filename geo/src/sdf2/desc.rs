@@ -0,0 +1,121 @@
+//! Declarative description of an `Sdf2` that can be deserialized from scene
+//! files.
+
+use serde::Deserialize;
+
+use crate::{sdf2::*, Vec2};
+
+/// A serializable description of an `Sdf2` built out of the primitives and
+/// the boolean operations in this module, it mirrors `SdfDesc`.
+///
+/// In TOML a ring looks like
+///
+/// ```toml
+/// type = "difference"
+/// from = { type = "circle", radius = 10.0 }
+/// sub = [{ type = "circle", radius = 8.0 }]
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Sdf2Desc {
+    Circle {
+        radius: f64,
+    },
+    Rect {
+        size: [f64; 2],
+    },
+    Segment {
+        a: [f64; 2],
+        b: [f64; 2],
+        radius: f64,
+    },
+    Polygon {
+        vertices: Vec<[f64; 2]>,
+    },
+
+    Union {
+        of: Vec<Sdf2Desc>,
+    },
+    Intersection {
+        of: Vec<Sdf2Desc>,
+    },
+    Difference {
+        from: Box<Sdf2Desc>,
+        sub: Vec<Sdf2Desc>,
+    },
+    SmoothUnion {
+        of: Vec<Sdf2Desc>,
+        k: f64,
+    },
+
+    Translate {
+        by: [f64; 2],
+        sdf: Box<Sdf2Desc>,
+    },
+    Scale {
+        by: f64,
+        sdf: Box<Sdf2Desc>,
+    },
+
+    /// rotation around the origin by `angle` degrees
+    Rotate {
+        angle: f64,
+        sdf: Box<Sdf2Desc>,
+    },
+    Offset {
+        by: f64,
+        sdf: Box<Sdf2Desc>,
+    },
+    Shell {
+        thickness: f64,
+        sdf: Box<Sdf2Desc>,
+    },
+}
+
+impl Sdf2Desc {
+    /// Build the `Sdf2` this `Sdf2Desc` describes.
+    ///
+    /// Return None if any union or intersection has no operands or if a
+    /// polygon has no vertices.
+    pub fn build(&self) -> Option<Sdf2> {
+        let v = |[x, y]: [f64; 2]| Vec2::new(x, y);
+
+        let sdf = match self {
+            Sdf2Desc::Circle { radius } => circle(*radius),
+            Sdf2Desc::Rect { size } => rect(v(*size)),
+            Sdf2Desc::Segment { a, b, radius } => segment(v(*a), v(*b), *radius),
+            Sdf2Desc::Polygon { vertices } => {
+                if vertices.is_empty() {
+                    return None;
+                }
+                polygon(vertices.iter().copied().map(v))
+            }
+
+            Sdf2Desc::Union { of } => fold(of, |a, b| a | b)?,
+            Sdf2Desc::Intersection { of } => fold(of, |a, b| a & b)?,
+            Sdf2Desc::SmoothUnion { of, k } => fold(of, |a, b| a.smooth_union(b, *k))?,
+            Sdf2Desc::Difference { from, sub } => {
+                let mut sdf = from.build()?;
+                for s in sub {
+                    sdf = sdf - s.build()?;
+                }
+                sdf
+            }
+
+            Sdf2Desc::Translate { by, sdf } => sdf.build()? + v(*by),
+            Sdf2Desc::Scale { by, sdf } => sdf.build()? * *by,
+            Sdf2Desc::Rotate { angle, sdf } => sdf.build()?.rotate(angle.to_radians()),
+            Sdf2Desc::Offset { by, sdf } => sdf.build()?.offset(*by),
+            Sdf2Desc::Shell { thickness, sdf } => sdf.build()?.shell(*thickness),
+        };
+
+        Some(sdf)
+    }
+}
+
+fn fold(descs: &[Sdf2Desc], f: impl Fn(Sdf2, Sdf2) -> Sdf2) -> Option<Sdf2> {
+    let mut sdfs = descs.iter().map(Sdf2Desc::build);
+
+    let first = sdfs.next()??;
+    sdfs.try_fold(first, |acc, s| Some(f(acc, s?)))
+}
@@ -0,0 +1,321 @@
+//! [Signed Distance Functions][0] in the plane.
+//!
+//! They can be turned into solids with `sdf::extrude` and `sdf::revolve` or
+//! contoured directly to get 2D paths.
+//!
+//! [0]: https://iquilezles.org/articles/distfunctions2d/
+
+use std::{
+    fmt::Debug,
+    ops::{Add, BitAnd, BitOr, Mul, Sub},
+    sync::Arc,
+};
+
+use crate::{
+    sdf::{smooth_and, smooth_sub, smooth_union},
+    v2, Vec2,
+};
+
+#[cfg(feature = "serde")]
+pub mod desc;
+pub mod primitives;
+
+#[cfg(feature = "serde")]
+pub use desc::Sdf2Desc;
+pub use primitives::*;
+
+/// A 2D SDF, that is a function that returns the signed distance from a point
+/// to the boundary of a shape in the plane. Like `Sdf`, the distance is
+/// negative inside the shape and positive outside.
+///
+/// The same boolean operations of `Sdf` are available.
+#[derive(Clone)]
+pub struct Sdf2 {
+    dist: Arc<DistFn>,
+
+    /// The bounding box of the Sdf2 as its min and max corners.
+    bbox: (Vec2, Vec2),
+}
+
+type DistFn = dyn Fn(&Vec2) -> f64 + Send + Sync + 'static;
+
+impl Sdf2 {
+    /// Create the Sdf2 from the given function and with the given bounding
+    /// box.
+    pub fn from_fn(
+        bbox: (Vec2, Vec2),
+        dist: impl Fn(&Vec2) -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            dist: Arc::new(dist),
+            bbox,
+        }
+    }
+
+    /// Calculate the distance to the given point from the Sdf2.
+    pub fn dist(&self, p: &Vec2) -> f64 {
+        (self.dist)(p)
+    }
+
+    /// Return the bounding box of the Sdf2 as its min and max corners.
+    pub fn bbox(&self) -> (Vec2, Vec2) {
+        self.bbox
+    }
+
+    /// Pad the bbox of the Sdf2 by the given amount in all dimensions, see
+    /// `Sdf::pad_bbox`.
+    pub fn pad_bbox(mut self, p: f64) -> Self {
+        self.bbox = pad(self.bbox, p);
+        self
+    }
+
+    /// Calculate the normal at the given point on the boundary of the Sdf2.
+    pub fn normal_at(&self, p: Vec2) -> Vec2 {
+        let e = 0.000001;
+        let n = v2(
+            self.dist(&v2(p.x + e, p.y)) - self.dist(&v2(p.x - e, p.y)),
+            self.dist(&v2(p.x, p.y + e)) - self.dist(&v2(p.x, p.y - e)),
+        );
+        n.normalized()
+    }
+
+    /// Find the t parameter of the first intersection between the ray that
+    /// starts at `origin` and goes towards `dir` and the boundary of the Sdf2
+    /// by sphere tracing it like `Sdf::ray_march` does.
+    pub fn ray_march(&self, origin: Vec2, dir: Vec2, steps: usize) -> Option<f64> {
+        let (t1, t2) = bbox_ray_intersection(self.bbox, origin, dir)?;
+        if t2 < t1 || t2 < 0.0 {
+            return None;
+        }
+
+        let dir_norm = dir.norm();
+        let mut t = t1.max(0.0001);
+
+        for _ in 0..steps {
+            let d = self.dist(&(origin + dir * t));
+            if d < 0.00001 {
+                return Some(t);
+            }
+
+            t += d / dir_norm;
+            if t > t2 {
+                break;
+            }
+        }
+
+        None
+    }
+
+    /// Return a shape that's the boundary enlarged by the given thickness.
+    ///
+    /// All interiors beyond thickness are removed.
+    pub fn shell(self, thickness: f64) -> Self {
+        let b = pad(self.bbox, thickness);
+        self.map(b, |p| p, move |d| d.abs() - thickness)
+    }
+
+    /// Grow the shape by the given distance, rounding its corners, or shrink
+    /// it if the distance is negative.
+    pub fn offset(self, d: f64) -> Self {
+        let b = pad(self.bbox, d.max(0.0));
+        self.map(b, |p| p, move |dist| dist - d)
+    }
+
+    /// Rotate the shape around the origin by the given angle in radians.
+    pub fn rotate(self, angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        let rot = move |p: Vec2, sin: f64| v2(p.x * cos - p.y * sin, p.x * sin + p.y * cos);
+
+        let (min, max) = self.bbox;
+        let corners = [min, v2(max.x, min.y), max, v2(min.x, max.y)].map(|c| rot(c, sin));
+        let b = corners[1..]
+            .iter()
+            .fold((corners[0], corners[0]), |(l, h), c| (l.min(*c), h.max(*c)));
+
+        self.map(b, move |p| rot(p, -sin), |d| d)
+    }
+
+    /// Smoothly merge two Sdf2s together using the given blend factor.
+    pub fn smooth_union(self, other: Sdf2, k: f64) -> Self {
+        let b = union(self.bbox, other.bbox);
+        self.combine(other, b, move |d1, d2| smooth_union(d1, d2, k))
+    }
+
+    /// Smoothly subtract the latter Sdf2 from the former using the given blend
+    /// factor.
+    pub fn smooth_sub(self, other: Sdf2, k: f64) -> Self {
+        let b = self.bbox;
+        self.combine(other, b, move |d1, d2| smooth_sub(d1, d2, k))
+    }
+
+    /// Smoothly intersect two Sdf2s using the given blend factor.
+    pub fn smooth_and(self, other: Sdf2, k: f64) -> Self {
+        let b = intersection(self.bbox, other.bbox);
+        self.combine(other, b, move |d1, d2| smooth_and(d1, d2, k))
+    }
+
+    /// Return a new Sdf2 with the given bbox that evaluates `self` at the
+    /// points transformed by `fp` and then transforms the distances with `fd`.
+    fn map(
+        self,
+        bbox: (Vec2, Vec2),
+        fp: impl Fn(Vec2) -> Vec2 + Send + Sync + 'static,
+        fd: impl Fn(f64) -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        Self::from_fn(bbox, move |p| fd(self.dist(&fp(*p))))
+    }
+
+    /// Return a new Sdf2 with the given bbox that combines the distances of
+    /// `self` and `other` with the given function.
+    fn combine(
+        self,
+        other: Sdf2,
+        bbox: (Vec2, Vec2),
+        f: impl Fn(f64, f64) -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        Self::from_fn(bbox, move |p| f(self.dist(p), other.dist(p)))
+    }
+}
+
+impl Add<Vec2> for Sdf2 {
+    type Output = Self;
+
+    fn add(self, delta: Vec2) -> Self::Output {
+        let (min, max) = self.bbox;
+        self.map((min + delta, max + delta), move |p| p - delta, |d| d)
+    }
+}
+
+impl BitOr<Sdf2> for Sdf2 {
+    type Output = Self;
+
+    fn bitor(self, rhs: Sdf2) -> Self::Output {
+        let b = union(self.bbox, rhs.bbox);
+        self.combine(rhs, b, f64::min)
+    }
+}
+
+impl BitAnd<Sdf2> for Sdf2 {
+    type Output = Self;
+
+    fn bitand(self, rhs: Sdf2) -> Self::Output {
+        let b = intersection(self.bbox, rhs.bbox);
+        self.combine(rhs, b, f64::max)
+    }
+}
+
+impl Sub<Self> for Sdf2 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        let b = self.bbox;
+        self.combine(rhs, b, |ld, rd| f64::max(ld, -rd))
+    }
+}
+
+impl Mul<f64> for Sdf2 {
+    type Output = Self;
+
+    fn mul(self, s: f64) -> Self::Output {
+        let (min, max) = self.bbox;
+        let b = ((min * s).min(max * s), (min * s).max(max * s));
+
+        self.map(b, move |p| p / s, move |d| d * s)
+    }
+}
+
+impl Debug for Sdf2 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sdf2").field("bbox", &self.bbox).finish()
+    }
+}
+
+fn pad((min, max): (Vec2, Vec2), p: f64) -> (Vec2, Vec2) {
+    (min - p, max + p)
+}
+
+fn union(a: (Vec2, Vec2), b: (Vec2, Vec2)) -> (Vec2, Vec2) {
+    (a.0.min(b.0), a.1.max(b.1))
+}
+
+/// The intersection of the two bounding boxes or an empty box at the origin if
+/// they're disjoint.
+fn intersection(a: (Vec2, Vec2), b: (Vec2, Vec2)) -> (Vec2, Vec2) {
+    let (min, max) = (a.0.max(b.0), a.1.min(b.1));
+    if min.x > max.x || min.y > max.y {
+        return (Vec2::zero(), Vec2::zero());
+    }
+
+    (min, max)
+}
+
+/// Intersect the ray with the bounding box using the slab method returning the
+/// t parameters where the ray enters and exits it.
+fn bbox_ray_intersection((min, max): (Vec2, Vec2), origin: Vec2, dir: Vec2) -> Option<(f64, f64)> {
+    let mut t1 = f64::NEG_INFINITY;
+    let mut t2 = f64::INFINITY;
+
+    for (o, d, lo, hi) in [
+        (origin.x, dir.x, min.x, max.x),
+        (origin.y, dir.y, min.y, max.y),
+    ] {
+        if d == 0.0 {
+            if o < lo || o > hi {
+                return None;
+            }
+            continue;
+        }
+
+        let (a, b) = ((lo - o) / d, (hi - o) / d);
+        t1 = t1.max(a.min(b));
+        t2 = t2.min(a.max(b));
+    }
+
+    Some((t1, t2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ops() {
+        let sdf = (circle(2.0) | rect(v2(6, 1))) - circle(0.5);
+        assert!((sdf.dist(&v2(0, 0)) - 0.5).abs() < 1e-9);
+        assert!((sdf.dist(&v2(4, 0)) - 1.0).abs() < 1e-9);
+        assert!((sdf.dist(&v2(0, 1)) + 0.5).abs() < 1e-9);
+        assert_eq!(sdf.bbox(), (v2(-3, -2), v2(3, 2)));
+
+        let sdf = (circle(1.0) + v2(2, 0)) * 2.0;
+        assert!((sdf.dist(&v2(4, 0)) + 2.0).abs() < 1e-9);
+        assert_eq!(sdf.bbox(), (v2(2, -2), v2(6, 2)));
+
+        let sdf = rect(v2(4, 2)).rotate(std::f64::consts::FRAC_PI_2);
+        assert!((sdf.dist(&v2(0, 2))).abs() < 1e-9);
+        assert!((sdf.bbox().1 - v2(1, 2)).norm() < 1e-9);
+
+        let sdf = circle(1.0).offset(1.0) & circle(1.0).shell(0.5);
+        assert!((sdf.dist(&v2(1, 0)) + 0.5).abs() < 1e-9);
+        assert!((sdf.dist(&v2(0, 0)) - 0.5).abs() < 1e-9);
+        assert_eq!(sdf.bbox(), (v2(-1.5, -1.5), v2(1.5, 1.5)));
+
+        let sdf = circle(1.0).smooth_union(circle(1.0) + v2(1.5, 0), 0.5);
+        assert!(sdf.dist(&v2(0.75, 0.7)) < 0.0);
+        assert!(
+            sdf.dist(&v2(0.75, 0.7))
+                < (circle(1.0) | (circle(1.0) + v2(1.5, 0))).dist(&v2(0.75, 0.7))
+        );
+    }
+
+    #[test]
+    fn test_ray_march() {
+        let sdf = circle(1.0) + v2(5, 0);
+
+        let t = sdf.ray_march(v2(0, 0), v2(2, 0), 100).unwrap();
+        assert!((t - 2.0).abs() < 1e-4);
+
+        assert_eq!(sdf.ray_march(v2(0, 0), v2(0, 1), 100), None);
+        assert_eq!(sdf.ray_march(v2(0, 0), v2(-1, 0), 100), None);
+        assert!((sdf.normal_at(v2(4, 0)) - v2(-1, 0)).norm() < 1e-6);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{polyline2, v2, Vec2};
+
+use super::Sdf2;
+
+pub fn circle(radius: f64) -> Sdf2 {
+    Sdf2::from_fn((v2(-radius, -radius), v2(radius, radius)), move |p| {
+        p.norm() - radius
+    })
+}
+
+/// A rectangle of the given size centered at the origin.
+pub fn rect(size: Vec2) -> Sdf2 {
+    let h = size / 2.0;
+
+    Sdf2::from_fn((-h, h), move |p| {
+        let dx = p.x.abs() - h.x;
+        let dy = p.y.abs() - h.y;
+
+        v2(dx.max(0.0), dy.max(0.0)).norm() + dx.max(dy).min(0.0)
+    })
+}
+
+/// The segment from `a` to `b` thickened by `r` in all directions, that is a
+/// 2D capsule.
+pub fn segment(a: Vec2, b: Vec2, r: f64) -> Sdf2 {
+    let bbox = (a.min(b) - r, a.max(b) + r);
+
+    Sdf2::from_fn(bbox, move |p| p.segment_dist(a, b) - r)
+}
+
+/// A polygon with the given vertices, which can be either clockwise or
+/// counter-clockwise and don't need to be convex. The polygon is implicitly
+/// closed.
+///
+/// Panics if there are no vertices.
+pub fn polygon(vertices: impl IntoIterator<Item = Vec2>) -> Sdf2 {
+    let vertices = vertices.into_iter().collect::<Vec<_>>();
+    let bbox = polyline2::bbox(vertices.iter().copied()).expect("empty polygon");
+
+    Sdf2::from_fn(bbox, move |&p| {
+        let mut d = p.dist2(vertices[0]);
+        let mut inside = false;
+
+        let mut prev = vertices[vertices.len() - 1];
+        for &v in &vertices {
+            let e = prev - v;
+            let w = p - v;
+            let h = (w.dot(e) / e.norm2()).clamp(0.0, 1.0);
+            d = d.min((w - e * h).norm2());
+
+            // count the crossings of the horizontal ray going right from p
+            let crosses = (p.y >= v.y) != (p.y >= prev.y);
+            if crosses && (e.x * w.y > e.y * w.x) == (prev.y > v.y) {
+                inside = !inside;
+            }
+
+            prev = v;
+        }
+
+        if inside {
+            -d.sqrt()
+        } else {
+            d.sqrt()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_polygon() {
+        // a concave L shape
+        let p = polygon([v2(0, 0), v2(4, 0), v2(4, 2), v2(2, 2), v2(2, 4), v2(0, 4)]);
+        assert!((p.dist(&v2(1, 1)) + 1.0).abs() < 1e-9);
+        assert!((p.dist(&v2(3, 3)) - 1.0).abs() < 1e-9);
+        assert!((p.dist(&v2(5, 1)) - 1.0).abs() < 1e-9);
+        assert!((p.dist(&v2(-1, -1)) - f64::sqrt(2.0)).abs() < 1e-9);
+        assert_eq!(p.bbox(), (v2(0, 0), v2(4, 4)));
+
+        let tri = polygon([v2(0, 0), v2(0, 4), v2(4, 0)]);
+        assert!(tri.dist(&v2(1, 1)) < 0.0);
+        assert!((tri.dist(&v2(3, 3)) - f64::sqrt(2.0)).abs() < 1e-9);
+
+        let square = polygon([v2(-1, -1), v2(1, -1), v2(1, 1), v2(-1, 1)]);
+        let r = rect(v2(2, 2));
+        for q in [v2(0, 0), v2(0.5, -0.2), v2(3, 1), v2(-2, 4), v2(1, 0)] {
+            assert!((square.dist(&q) - r.dist(&q)).abs() < 1e-9, "{q:?}");
+        }
+    }
+
+    #[test]
+    fn test_segment() {
+        let s = segment(v2(0, 0), v2(4, 0), 1.0);
+        assert!((s.dist(&v2(2, 0)) + 1.0).abs() < 1e-9);
+        assert!((s.dist(&v2(2, 3)) - 2.0).abs() < 1e-9);
+        assert!((s.dist(&v2(7, 0)) - 2.0).abs() < 1e-9);
+        assert_eq!(s.bbox(), (v2(-1, -1), v2(5, 1)));
+    }
+}
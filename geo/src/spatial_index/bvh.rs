@@ -2,15 +2,22 @@ use std::iter::FromIterator;
 
 use crate::ray::Ray;
 use crate::spatial_index::{Intersection, Shape};
+use crate::util::ksmallest_by;
 use crate::{Aabb, Axis, Vec3};
 
 /// A [Bounding volume hierarchy][0] is a tree data structure for collecting a
 /// set of shapes that allows for quick intersection checking by pruning the
 /// tree by looking at the `Aabb` of each shape.
 ///
-/// The current implementation partitions the shapes by recursively splitting
-/// the shapes by the axis that has the biggest change in coordinates between
-/// all the centers of the bounding boxes.
+/// By default, the shapes are partitioned by recursively splitting them at the
+/// median along the axis that has the biggest change in coordinates between
+/// all the centers of the bounding boxes. Use `BvhBuilder` to pick a different
+/// `SplitMethod`.
+///
+/// Shapes can also be added after the `Bvh` has been built with `Bvh::insert`
+/// and shapes that have moved can be accounted for with `Bvh::refit` which are
+/// both much cheaper than rebuilding the whole tree, at the cost of a slightly
+/// worse tree.
 ///
 /// [0]: https://en.wikipedia.org/wiki/Bounding_volume_hierarchy
 #[derive(Debug, Clone, PartialEq)]
@@ -19,6 +26,33 @@ pub struct Bvh<T> {
     infinite_objects: Vec<T>,
 }
 
+/// Builder to customize how a `Bvh` is built.
+///
+/// Collecting an iterator into a `Bvh` is the same as building it with the
+/// default `BvhBuilder`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BvhBuilder {
+    split_method: SplitMethod,
+}
+
+/// How the shapes are partitioned at each level of a `Bvh`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SplitMethod {
+    /// Split the shapes in two halves at the median center along the axis with
+    /// the biggest change in coordinates. It's fast to build, but it produces
+    /// poor trees when the shapes are unevenly distributed.
+    #[default]
+    Median,
+
+    /// Split the shapes where the [Surface Area Heuristic][0] estimates the
+    /// lowest cost of traversing the tree. Only `bins` evenly spaced split
+    /// positions are evaluated for each axis. It's slower to build, but the
+    /// resulting tree is usually much faster to query.
+    ///
+    /// [0]: https://pbr-book.org/3ed-2018/Primitives_and_Intersection_Acceleration/Bounding_Volume_Hierarchies#TheSurfaceAreaHeuristic
+    Sah { bins: usize },
+}
+
 #[derive(Debug, Clone, PartialEq)]
 enum Node<T> {
     Branch {
@@ -26,11 +60,15 @@ enum Node<T> {
         left: Box<Node<T>>,
         right: Box<Node<T>>,
     },
-    Leaf {
-        data: T,
-    },
+    /// Leaves store a few shapes so that insertions don't need to restructure
+    /// the tree every time.
+    Leaf { data: Vec<T> },
 }
 
+/// The maximum number of shapes a leaf can store before being split when
+/// inserting new shapes.
+const LEAF_CAPACITY: usize = 4;
+
 impl<T> Bvh<T>
 where
     T: Shape,
@@ -38,10 +76,7 @@ where
     /// Return the boundinx box for all the elements contained in the `Bvh`.
     /// Return `None` if empty.
     pub fn bbox(&self) -> Option<Aabb> {
-        let nodes_bbox = self.root.as_ref().map(|n| match n {
-            Node::Branch { bbox, .. } => bbox.clone(),
-            Node::Leaf { data } => data.bbox(),
-        });
+        let nodes_bbox = self.root.as_ref().map(Node::bbox);
 
         let infinite_bbox = if self.infinite_objects.is_empty() {
             None
@@ -80,9 +115,71 @@ where
 
             None
         })
+        .flatten()
         .chain(&self.infinite_objects)
     }
 
+    /// Iterator over mutable references to all the elements.
+    ///
+    /// Remember to call `Bvh::refit` if the bounding box of any element
+    /// changes.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        let mut stack = vec![];
+        if let Some(n) = self.root.as_mut() {
+            stack.push(n);
+        }
+
+        std::iter::from_fn(move || {
+            while let Some(n) = stack.pop() {
+                match n {
+                    Node::Leaf { data } => return Some(data),
+                    Node::Branch { left, right, .. } => {
+                        stack.push(right);
+                        stack.push(left);
+                    }
+                }
+            }
+
+            None
+        })
+        .flatten()
+        .chain(&mut self.infinite_objects)
+    }
+
+    /// Insert a new element in the `Bvh` without rebuilding it.
+    ///
+    /// The element is added to the leaf whose bounding box grows the least
+    /// and the leaf is split once it's full. Inserting many elements this way
+    /// produces a worse tree than building it from scratch, but it's a lot
+    /// faster when only a few elements are added at a time.
+    pub fn insert(&mut self, elem: T) {
+        let bbox = elem.bbox();
+        if !bbox.min().is_finite() || !bbox.max().is_finite() {
+            self.infinite_objects.push(elem);
+            return;
+        }
+
+        match &mut self.root {
+            None => self.root = Some(Node::Leaf { data: vec![elem] }),
+            Some(root) => root.insert(elem, &bbox),
+        }
+    }
+
+    /// Update the bounding boxes of the tree after the elements have moved,
+    /// see `Bvh::iter_mut`.
+    ///
+    /// The structure of the tree is left untouched, hence it's a lot faster
+    /// than building a new `Bvh`, but the tree quality degrades as the
+    /// elements move far away from their original positions.
+    ///
+    /// Note that elements whose bounding box became infinite or finite are not
+    /// moved between the tree and the infinite objects.
+    pub fn refit(&mut self) {
+        if let Some(root) = &mut self.root {
+            root.refit();
+        }
+    }
+
     /// Returns all the objects that intersect the given `Aabb`.
     pub fn bbox_intersections(&self, aabb: Aabb) -> impl Iterator<Item = &T> {
         let mut stack = vec![];
@@ -91,16 +188,11 @@ where
         }
 
         {
-            let aabb = aabb.clone();
+            let (aabb, leaf_aabb) = (aabb.clone(), aabb.clone());
             std::iter::from_fn(move || {
                 while let Some(n) = stack.pop() {
                     match n {
-                        Node::Leaf { data } => {
-                            if data.bbox().intersection(&aabb).is_none() {
-                                continue;
-                            }
-                            return Some(data);
-                        }
+                        Node::Leaf { data } => return Some(data),
                         Node::Branch { left, right, bbox } => {
                             if bbox.intersection(&aabb).is_some() {
                                 stack.push(right);
@@ -112,6 +204,8 @@ where
 
                 None
             })
+            .flatten()
+            .filter(move |data| data.bbox().intersection(&leaf_aabb).is_some())
         }
         .chain(
             self.infinite_objects
@@ -131,6 +225,8 @@ where
         let ray = ray.clone();
         Intersections {
             stack,
+            leaf: [].iter(),
+            inv_dir: ray.inv_dir(),
             ray: ray.clone(),
         }
         .chain(self.infinite_objects.iter().filter_map(move |obj| {
@@ -142,11 +238,232 @@ where
             }
         }))
     }
+
+    /// Return the object closest to the origin of the ray that intersects it
+    /// along with its intersection.
+    ///
+    /// This is equivalent to taking the intersection with the minimum t among
+    /// the ones returned by `intersections`, but it's much faster because the
+    /// children of each node are visited from the nearest to the farthest and
+    /// the nodes that start farther than the closest intersection found so far
+    /// are skipped.
+    pub fn closest_intersection(&self, ray: &Ray) -> Option<(&T, T::Intersection)> {
+        self.closest_intersection_where(ray, |_| true)
+    }
+
+    /// Return the object closest to the origin of the ray that intersects it
+    /// along with its intersection like `closest_intersection` does, but
+    /// ignoring the objects for which `keep` returns false.
+    pub fn closest_intersection_where(
+        &self,
+        ray: &Ray,
+        keep: impl Fn(&T) -> bool,
+    ) -> Option<(&T, T::Intersection)> {
+        let inv_dir = ray.inv_dir();
+
+        // leaves have no bounding box of their own, therefore they're entered
+        // at the same distance of their parent
+        let entry = |n: &Node<T>, parent_t: f64| match n {
+            Node::Leaf { .. } => Some(parent_t),
+            Node::Branch { bbox, .. } => match bbox.ray_intersection_inv(ray.origin, inv_dir) {
+                Some((t1, t2)) if t1 <= t2 && t2 >= 0.0 => Some(t1.max(0.0)),
+                _ => None,
+            },
+        };
+
+        fn check<'s, T: Shape>(
+            data: &'s [T],
+            ray: &Ray,
+            keep: &impl Fn(&T) -> bool,
+            closest: &mut Option<(&'s T, T::Intersection)>,
+        ) {
+            for d in data.iter().filter(|d| keep(d)) {
+                let Some(inter) = d.intersection(ray) else {
+                    continue;
+                };
+
+                let t = inter.t();
+                if t >= 0.0 && closest.as_ref().is_none_or(|(_, c)| t < c.t()) {
+                    *closest = Some((d, inter));
+                }
+            }
+        }
+
+        let mut closest: Option<(&T, T::Intersection)> = None;
+        let mut stack = vec![];
+        if let Some(root) = self.root.as_ref() {
+            if let Some(t) = entry(root, 0.0) {
+                stack.push((root, t));
+            }
+        }
+
+        while let Some((n, t)) = stack.pop() {
+            if closest.as_ref().is_some_and(|(_, c)| t > c.t()) {
+                continue;
+            }
+
+            match n {
+                Node::Leaf { data } => check(data, ray, &keep, &mut closest),
+                Node::Branch { left, right, .. } => {
+                    let children = [(left, entry(left, t)), (right, entry(right, t))];
+
+                    // push the farthest child first so that the nearest one is
+                    // visited first
+                    let [(near, near_t), (far, far_t)] = match children {
+                        [(l, Some(lt)), (r, Some(rt))] if rt < lt => [(r, Some(rt)), (l, Some(lt))],
+                        children => children,
+                    };
+
+                    if let Some(far_t) = far_t {
+                        stack.push((far, far_t));
+                    }
+                    if let Some(near_t) = near_t {
+                        stack.push((near, near_t));
+                    }
+                }
+            }
+        }
+
+        check(&self.infinite_objects, ray, &keep, &mut closest);
+
+        closest
+    }
+
+    /// Check whether the ray intersects any object closer than `t_max` to its
+    /// origin.
+    ///
+    /// This is meant for shadow rays where it doesn't matter which object is
+    /// hit: the traversal stops as soon as any intersection is found and the
+    /// nodes that start beyond `t_max` are skipped.
+    pub fn intersects_before(&self, ray: &Ray, t_max: f64) -> bool {
+        self.intersects_before_where(ray, t_max, |_| true)
+    }
+
+    /// Check whether the ray intersects any object closer than `t_max` to its
+    /// origin like `intersects_before` does, but ignoring the objects for
+    /// which `keep` returns false.
+    pub fn intersects_before_where(
+        &self,
+        ray: &Ray,
+        t_max: f64,
+        keep: impl Fn(&T) -> bool,
+    ) -> bool {
+        let hits = |d: &T| {
+            keep(d)
+                && d.intersection(ray)
+                    .is_some_and(|inter| inter.t() >= 0.0 && inter.t() < t_max)
+        };
+
+        let inv_dir = ray.inv_dir();
+
+        let mut stack = vec![];
+        if let Some(root) = self.root.as_ref() {
+            stack.push(root);
+        }
+
+        while let Some(n) = stack.pop() {
+            match n {
+                Node::Leaf { data } => {
+                    if data.iter().any(hits) {
+                        return true;
+                    }
+                }
+                Node::Branch { bbox, left, right } => {
+                    match bbox.ray_intersection_inv(ray.origin, inv_dir) {
+                        Some((t1, t2)) if t1 <= t2 && t2 >= 0.0 && t1 < t_max => {
+                            stack.push(right);
+                            stack.push(left);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        self.infinite_objects.iter().any(hits)
+    }
+
+    /// Find all the intersections between the objects and all the given rays
+    /// calling `f` with the index of the ray, the object and the intersection
+    /// for each one of them.
+    ///
+    /// This is equivalent to calling `Bvh::intersections` for each ray, but the
+    /// tree is traversed only once for the whole batch by keeping track of
+    /// which rays hit each node. It's faster when the rays are coherent, like
+    /// neighboring camera rays, because the traversal overhead is shared among
+    /// all the rays. The intersections are not reported in any particular
+    /// order.
+    pub fn intersections_batch<'s>(
+        &'s self,
+        rays: &[Ray],
+        mut f: impl FnMut(usize, &'s T, T::Intersection),
+    ) {
+        let mut report = |i: usize, data: &'s T| {
+            if let Some(inter) = data.intersection(&rays[i]) {
+                if inter.t() >= 0.0 {
+                    f(i, data, inter);
+                }
+            }
+        };
+
+        // the indices of the rays that are still active at each node are
+        // stored in a single buffer and each node on the stack refers to a
+        // range of it. Since the ranges are allocated in the same order the
+        // nodes are pushed, once a node is popped everything after its range
+        // is not needed anymore.
+        let mut active = (0..rays.len()).collect::<Vec<_>>();
+        let inv_dirs = rays.iter().map(Ray::inv_dir).collect::<Vec<_>>();
+        let mut stack = vec![];
+        if let Some(n) = self.root.as_ref() {
+            stack.push((n, 0, active.len()));
+        }
+
+        while let Some((n, start, end)) = stack.pop() {
+            active.truncate(end);
+
+            match n {
+                Node::Leaf { data } => {
+                    for d in data {
+                        for &i in &active[start..end] {
+                            report(i, d);
+                        }
+                    }
+                }
+                Node::Branch { bbox, left, right } => {
+                    let children_start = active.len();
+                    for k in start..end {
+                        let i = active[k];
+                        if let Some((t1, t2)) =
+                            bbox.ray_intersection_inv(rays[i].origin, inv_dirs[i])
+                        {
+                            if t1 <= t2 && t2 >= 0.0 {
+                                active.push(i);
+                            }
+                        }
+                    }
+
+                    let children_end = active.len();
+                    if children_start < children_end {
+                        stack.push((right, children_start, children_end));
+                        stack.push((left, children_start, children_end));
+                    }
+                }
+            }
+        }
+
+        for obj in &self.infinite_objects {
+            for i in 0..rays.len() {
+                report(i, obj);
+            }
+        }
+    }
 }
 
 pub struct Intersections<'s, T> {
     stack: Vec<&'s Node<T>>,
+    leaf: std::slice::Iter<'s, T>,
     ray: Ray,
+    inv_dir: Vec3,
 }
 
 impl<'s, T> std::iter::Iterator for Intersections<'s, T>
@@ -156,35 +473,59 @@ where
     type Item = (&'s T, T::Intersection);
 
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some(n) = self.stack.pop() {
-            match n {
-                Node::Leaf { data } => {
-                    if let Some(inter) = data.intersection(&self.ray) {
-                        if inter.t() >= 0.0 {
-                            return Some((data, inter));
-                        }
+        loop {
+            for data in self.leaf.by_ref() {
+                if let Some(inter) = data.intersection(&self.ray) {
+                    if inter.t() >= 0.0 {
+                        return Some((data, inter));
                     }
                 }
+            }
+
+            let n = self.stack.pop()?;
+            match n {
+                Node::Leaf { data } => self.leaf = data.iter(),
 
-                Node::Branch { bbox, left, right } => match bbox.ray_intersection(&self.ray) {
-                    Some((t1, t2)) if t1 <= t2 && t2 >= 0.0 => {
-                        self.stack.push(right);
-                        self.stack.push(left);
+                Node::Branch { bbox, left, right } => {
+                    match bbox.ray_intersection_inv(self.ray.origin, self.inv_dir) {
+                        Some((t1, t2)) if t1 <= t2 && t2 >= 0.0 => {
+                            self.stack.push(right);
+                            self.stack.push(left);
+                        }
+                        _ => {}
                     }
-                    _ => {}
-                },
+                }
             }
         }
-
-        None
     }
 }
 
-impl<'s, T: 's> FromIterator<T> for Bvh<T>
+impl<T> FromIterator<T> for Bvh<T>
 where
     T: Shape,
 {
     fn from_iter<I: IntoIterator<Item = T>>(it: I) -> Self {
+        BvhBuilder::new().build(it)
+    }
+}
+
+impl BvhBuilder {
+    /// Create a new `BvhBuilder` that uses `SplitMethod::Median`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_split_method(mut self, split_method: SplitMethod) -> Self {
+        if let SplitMethod::Sah { bins } = split_method {
+            assert!(bins >= 2, "SAH needs at least 2 bins");
+        }
+
+        self.split_method = split_method;
+        self
+    }
+
+    /// Build a `Bvh` containing all the given shapes.
+    pub fn build<T: Shape>(&self, it: impl IntoIterator<Item = T>) -> Bvh<T> {
         let mut elems = vec![];
         let mut infinite_objects = vec![];
 
@@ -202,7 +543,7 @@ where
             root: if elems.is_empty() {
                 None
             } else {
-                Some(Node::new(elems))
+                Some(Node::new(elems, self.split_method))
             },
         }
     }
@@ -212,36 +553,181 @@ impl<T> Node<T>
 where
     T: Shape,
 {
-    fn new(mut elems: Vec<(T, Aabb)>) -> Self {
+    fn new(mut elems: Vec<(T, Aabb)>, split_method: SplitMethod) -> Self {
         assert!(!elems.is_empty());
 
         if elems.len() == 1 {
             return Node::Leaf {
-                data: elems.pop().unwrap().0,
+                data: vec![elems.pop().unwrap().0],
             };
         }
 
         let (partition_axis, bbox) = Node::preprocess_elems(&elems);
 
+        let right_elems = match split_method {
+            SplitMethod::Median => None,
+            SplitMethod::Sah { bins } => Node::sah_split(&mut elems, bins),
+        }
+        .unwrap_or_else(|| Node::median_split(&mut elems, partition_axis));
+
+        Node::Branch {
+            bbox,
+            left: Box::new(Node::new(elems, split_method)),
+            right: Box::new(Node::new(right_elems, split_method)),
+        }
+    }
+
+    /// Return the bounding box of all the elements of the node.
+    fn bbox(&self) -> Aabb {
+        match self {
+            Node::Branch { bbox, .. } => bbox.clone(),
+            Node::Leaf { data } => {
+                let mut bbox = data[0].bbox();
+                for d in &data[1..] {
+                    bbox = bbox.union(&d.bbox());
+                }
+                bbox
+            }
+        }
+    }
+
+    fn insert(&mut self, elem: T, elem_bbox: &Aabb) {
+        match self {
+            Node::Branch { bbox, left, right } => {
+                *bbox = bbox.union(elem_bbox);
+
+                // pick the child whose surface area grows the least since it's
+                // proportional to the probability of a ray hitting it
+                let growth = |n: &Node<T>| {
+                    let bbox = n.bbox();
+                    bbox.union(elem_bbox).surface_area() - bbox.surface_area()
+                };
+
+                if growth(left) <= growth(right) {
+                    left.insert(elem, elem_bbox);
+                } else {
+                    right.insert(elem, elem_bbox);
+                }
+            }
+            Node::Leaf { data } => {
+                data.push(elem);
+
+                if data.len() > LEAF_CAPACITY {
+                    let elems = data
+                        .drain(..)
+                        .map(|e| {
+                            let bbox = e.bbox();
+                            (e, bbox)
+                        })
+                        .collect();
+
+                    *self = Node::new(elems, SplitMethod::Median);
+                }
+            }
+        }
+    }
+
+    fn refit(&mut self) -> Aabb {
+        match self {
+            Node::Branch { bbox, left, right } => {
+                *bbox = left.refit().union(&right.refit());
+                bbox.clone()
+            }
+            Node::Leaf { .. } => self.bbox(),
+        }
+    }
+
+    /// Split the elements at the median center along the given axis returning
+    /// the elements in the right half.
+    fn median_split(elems: &mut Vec<(T, Aabb)>, axis: Axis) -> Vec<(T, Aabb)> {
         let pivot = elems.len() / 2;
 
-        // select_nth actually partitions the elems so that bboxes before pivot
-        // have a smaller dimensions than the median dimension and bboxes after
-        // pivot have a greater dimension.
-        elems.select_nth_unstable_by(pivot, |(_, b1), (_, b2)| {
-            let c1 = b1.center()[partition_axis];
-            let c2 = b2.center()[partition_axis];
+        // the elements before pivot are the ones with the smallest centers
+        // along the axis, all the others have a greater one
+        ksmallest_by(elems, pivot, |(_, b1), (_, b2)| {
+            let c1 = b1.center()[axis];
+            let c2 = b2.center()[axis];
 
             c1.total_cmp(&c2)
         });
 
-        let right_elems = elems.split_off(pivot);
+        elems.split_off(pivot)
+    }
 
-        Node::Branch {
-            bbox,
-            left: Box::new(Node::new(elems)),
-            right: Box::new(Node::new(right_elems)),
+    /// Split the elements where the binned Surface Area Heuristic estimates
+    /// the lowest cost returning the elements on the right side.
+    ///
+    /// The centers of the elements are assigned to `bins` evenly spaced bins
+    /// along each axis and the split is chosen among the bins boundaries as the
+    /// one that minimizes the sum of the surface area of each side multiplied
+    /// by the number of its elements.
+    ///
+    /// Return `None` if no split is possible because all the elements share
+    /// the same center.
+    fn sah_split(elems: &mut Vec<(T, Aabb)>, bins: usize) -> Option<Vec<(T, Aabb)>> {
+        let centers = Aabb::from_points(elems.iter().map(|(_, b)| b.center()))?;
+
+        let bin_of = |b: &Aabb, axis: Axis| {
+            let (lo, hi) = (centers.min()[axis], centers.max()[axis]);
+            let i = (b.center()[axis] - lo) / (hi - lo) * bins as f64;
+            (i as usize).min(bins - 1)
+        };
+
+        let union = |a: Option<Aabb>, b: &Option<Aabb>| match (a, b) {
+            (Some(a), Some(b)) => Some(a.union(b)),
+            (a, b) => a.or_else(|| b.clone()),
+        };
+        let area = |a: &Option<Aabb>| a.as_ref().map_or(0.0, Aabb::surface_area);
+
+        // cost, axis and index of the last bin on the left side of the best
+        // split found so far
+        let mut best: Option<(f64, Axis, usize)> = None;
+
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            if centers.min()[axis] >= centers.max()[axis] {
+                continue;
+            }
+
+            let mut bins_bbox = vec![None; bins];
+            let mut bins_count = vec![0; bins];
+            for (_, b) in elems.iter() {
+                let i = bin_of(b, axis);
+                bins_bbox[i] = union(bins_bbox[i].take(), &Some(b.clone()));
+                bins_count[i] += 1;
+            }
+
+            // sweep from the right to know the cost of the right side of
+            // each split, then sweep from the left to evaluate all the splits
+            let mut right_cost = vec![0.0; bins];
+            let (mut bbox, mut count) = (None, 0);
+            for i in (1..bins).rev() {
+                bbox = union(bbox, &bins_bbox[i]);
+                count += bins_count[i];
+                right_cost[i - 1] = area(&bbox) * count as f64;
+            }
+
+            let (mut bbox, mut count) = (None, 0);
+            for i in 0..bins - 1 {
+                bbox = union(bbox, &bins_bbox[i]);
+                count += bins_count[i];
+
+                if count == 0 || count == elems.len() {
+                    continue;
+                }
+
+                let cost = area(&bbox) * count as f64 + right_cost[i];
+                if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                    best = Some((cost, axis, i));
+                }
+            }
         }
+
+        let (_, axis, split) = best?;
+
+        let (left, right) = elems.drain(..).partition(|(_, b)| bin_of(b, axis) <= split);
+        *elems = left;
+
+        Some(right)
     }
 
     /// Gather some information about the input shapes and return the axis that
@@ -295,15 +781,19 @@ mod tests {
                 root: Some(Node::Branch {
                     bbox: Aabb::from_points(vec![v3(0.0, -1.0, 0.0), v3(10, 2, 7)]).unwrap(),
 
-                    left: Box::new(Node::Leaf { data: v3(0, 2, 0) }),
+                    left: Box::new(Node::Leaf {
+                        data: vec![v3(0, 2, 0)]
+                    }),
 
                     right: Box::new(Node::Branch {
                         bbox: Aabb::from_points(vec![v3(8.0, -1.0, 4.0), v3(10, 1, 7)]).unwrap(),
 
-                        left: Box::new(Node::Leaf { data: v3(8, 1, 4) }),
+                        left: Box::new(Node::Leaf {
+                            data: vec![v3(8, 1, 4)]
+                        }),
 
                         right: Box::new(Node::Leaf {
-                            data: v3(10.0, -1.0, 7.0)
+                            data: vec![v3(10.0, -1.0, 7.0)]
                         })
                     })
                 })
@@ -316,6 +806,151 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sah_build() {
+        let pts = (0..50)
+            .map(|i| v3(f64::from(i % 7) * 0.1, f64::from(i % 3), f64::from(i)))
+            .chain(vec![v3(100, 100, 100), v3(-100, 0, 0)])
+            .collect::<Vec<_>>();
+
+        let median: Bvh<Vec3> = pts.iter().copied().collect();
+        let sah = BvhBuilder::new()
+            .with_split_method(SplitMethod::Sah { bins: 8 })
+            .build(pts.iter().copied());
+
+        let cmp = |a: &Vec3, b: &Vec3| a.z.total_cmp(&b.z).then(a.x.total_cmp(&b.x));
+
+        let mut elems = sah.iter().copied().collect::<Vec<_>>();
+        elems.sort_by(cmp);
+        let mut expected = pts.clone();
+        expected.sort_by(cmp);
+        assert_eq!(elems, expected);
+
+        assert_eq!(sah.bbox(), median.bbox());
+
+        for ray in [
+            Ray::new(Vec3::zero(), v3(0, 0, 1)),
+            Ray::new(v3(-200, 0, 0), v3(1, 0, 0)),
+            Ray::new(Vec3::zero(), v3(1, 1, 1)),
+        ] {
+            let mut expected = median.intersections(&ray).collect::<Vec<_>>();
+            let mut inters = sah.intersections(&ray).collect::<Vec<_>>();
+            expected.sort_by(|a, b| a.1.total_cmp(&b.1));
+            inters.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+            assert_eq!(inters, expected);
+        }
+    }
+
+    #[test]
+    fn test_insert_refit() {
+        let mut bvh: Bvh<Vec3> = vec![v3(0, 0, 0), v3(10, 0, 0)].into_iter().collect();
+
+        for i in 1..10 {
+            bvh.insert(v3(i, 0, 0));
+        }
+        bvh.insert(v3(f64::INFINITY, 0.0, 0.0));
+
+        assert_eq!(bvh.iter().count(), 12);
+        assert_eq!(bvh.infinite_objects, vec![v3(f64::INFINITY, 0.0, 0.0)]);
+        assert_eq!(
+            bvh.bbox(),
+            Aabb::from_points(vec![v3(0, 0, 0), v3(f64::INFINITY, 0.0, 0.0)])
+        );
+
+        let ray = Ray::new(v3(7, -1, 0), v3(0, 1, 0));
+        assert_eq!(
+            bvh.intersections(&ray).collect::<Vec<_>>(),
+            vec![(&v3(7, 0, 0), 1.0)]
+        );
+
+        for p in bvh.iter_mut() {
+            if p.x.is_finite() {
+                p.z += 5.0;
+            }
+        }
+        bvh.refit();
+
+        assert_eq!(bvh.intersections(&ray).collect::<Vec<_>>(), vec![]);
+        assert_eq!(
+            bvh.intersections(&Ray::new(v3(7, -1, 5), v3(0, 1, 0)))
+                .collect::<Vec<_>>(),
+            vec![(&v3(7, 0, 5), 1.0)]
+        );
+    }
+
+    #[test]
+    fn test_intersections_batch() {
+        let bvh: Bvh<Vec3> = (0..20)
+            .map(|i| v3(f64::from(i % 5), f64::from(i / 5), 0.0))
+            .chain(vec![v3(f64::INFINITY, 0.0, 0.0)])
+            .collect();
+
+        let rays = (0..10)
+            .map(|i| Ray::new(v3(f64::from(i % 5), -1.0, 0.0), v3(0, 1, 0)))
+            .chain(vec![Ray::new(v3(-10, -10, 0), v3(1, 1, 0))])
+            .collect::<Vec<_>>();
+
+        let mut batch = vec![];
+        bvh.intersections_batch(&rays, |i, p, t| batch.push((i, p, t)));
+
+        let mut expected = rays
+            .iter()
+            .enumerate()
+            .flat_map(|(i, r)| bvh.intersections(r).map(move |(p, t)| (i, p, t)))
+            .collect::<Vec<_>>();
+
+        let key = |(i, _, t): &(usize, &Vec3, f64)| (*i, t.to_bits());
+        batch.sort_by_key(key);
+        expected.sort_by_key(key);
+
+        assert_eq!(batch.len(), 10 * 4 + 4);
+        assert_eq!(batch, expected);
+    }
+
+    #[test]
+    fn test_closest_intersection() {
+        let pts = (0..200)
+            .map(|i| v3(f64::from(i % 10), f64::from(i / 10 % 4), f64::from(i / 40)))
+            .chain(vec![v3(f64::INFINITY, 0.0, 0.0)])
+            .collect::<Vec<_>>();
+
+        for bvh in [
+            pts.iter().copied().collect::<Bvh<_>>(),
+            BvhBuilder::new()
+                .with_split_method(SplitMethod::Sah { bins: 4 })
+                .build(pts.iter().copied()),
+        ] {
+            for ray in [
+                Ray::new(v3(-1, 0, 0), v3(1, 0, 0)),
+                Ray::new(v3(20, 2, 3), v3(-1, 0, 0)),
+                Ray::new(v3(3, 3, 10), v3(0.0, 0.0, -1.0)),
+                Ray::new(v3(-1, -1, -1), v3(1, 1, 1)),
+                Ray::new(v3(-5, -5, -5), v3(0, 1, 0)),
+            ] {
+                let expected = bvh
+                    .intersections(&ray)
+                    .min_by(|(_, t0), (_, t1)| t0.total_cmp(t1));
+
+                assert_eq!(bvh.closest_intersection(&ray), expected);
+
+                for t_max in [0.5, 2.5, 7.0, f64::INFINITY] {
+                    assert_eq!(
+                        bvh.intersects_before(&ray, t_max),
+                        expected.is_some_and(|(_, t)| t < t_max)
+                    );
+                }
+            }
+        }
+
+        let empty: Bvh<Vec3> = vec![].into_iter().collect();
+        assert!(!empty.intersects_before(&Ray::new(Vec3::zero(), v3(1, 0, 0)), f64::INFINITY));
+        assert_eq!(
+            empty.closest_intersection(&Ray::new(Vec3::zero(), v3(1, 0, 0))),
+            None
+        );
+    }
+
     #[test]
     fn test_intersections() {
         let bvh: Bvh<Vec3> = vec![
@@ -1,6 +1,12 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
 use std::ops::Deref;
+use std::path::Path;
 use std::sync::Arc;
 
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
 use crate::ray::Ray;
 use crate::spatial_index::{Intersection, Shape};
 use crate::{Aabb, Axis};
@@ -8,6 +14,15 @@ use crate::{Aabb, Axis};
 /// maximum number of elements each leaf can contain.
 const LEAF_SIZE: usize = 8;
 
+/// magic bytes at the start of a serialized `KdTree`, the last byte is the
+/// version of the format.
+const MAGIC: &[u8; 4] = b"KDT\x01";
+
+/// maximum depth of a serialized `KdTree` that can be read back. The trees
+/// built over real scenes are way shallower, this only prevents malformed data
+/// from overflowing the stack while reading it.
+const MAX_DEPTH: usize = 256;
+
 /// A [K-d tree][0] is a space partitioning data structure for organizing points
 /// in a k-dimensional space. In our case, `KdTree` is actually a kdtree with
 /// `k=3`.
@@ -16,10 +31,18 @@ const LEAF_SIZE: usize = 8;
 /// partitioned according to the axis that best splits the center of the shapes
 /// into two collections.
 ///
+/// Building a `KdTree` over big meshes can be slow, therefore it can be saved
+/// with `KdTree::write` and loaded back with `KdTree::read` which only
+/// restores the structure of the tree, the shapes must be provided again.
+///
 /// [0]: https://en.wikipedia.org/wiki/K-d_tree
 #[derive(Debug, Clone, PartialEq)]
 pub struct KdTree<T> {
     root: Node<T>,
+
+    // the shapes in the order they were given, used to refer to them by index
+    // when serializing the tree
+    shapes: Vec<Arc<T>>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -44,16 +67,103 @@ where
     /// Create a new `KdTree` that contains all the given shapes.
     pub fn new(shapes: Vec<T>) -> Self {
         let bboxes = shapes.iter().map(|s| s.bbox()).collect();
+        let shapes = shapes.into_iter().map(Arc::new).collect::<Vec<_>>();
+
+        KdTree {
+            root: Node::new(shapes.clone(), bboxes),
+            shapes,
+        }
+    }
+
+    /// Load the `KdTree` saved at `path` if it was built from shapes with the
+    /// given `hash`, otherwise build a new one over `shapes` and save it at
+    /// `path` for the next time.
+    ///
+    /// The `hash` must identify the shapes, for meshes `Mesh::content_hash` can
+    /// be used.
+    pub fn load_or_build(path: impl AsRef<Path>, shapes: Vec<T>, hash: u64) -> io::Result<Self> {
+        let path = path.as_ref();
+
+        let layout =
+            File::open(path).and_then(|f| Layout::read(&mut BufReader::new(f), shapes.len(), hash));
+
+        if let Ok(layout) = layout {
+            return Ok(Self::from_layout(layout, shapes));
+        }
+
+        let tree = KdTree::new(shapes);
+        tree.write(BufWriter::new(File::create(path)?), hash)?;
+        Ok(tree)
+    }
+
+    /// Load a `KdTree` previously saved with `KdTree::write` over the given
+    /// shapes which must be the same ones, in the same order, the tree was
+    /// originally built with.
+    ///
+    /// It fails with `io::ErrorKind::InvalidData` if the data is malformed or
+    /// if it was saved with a different `hash` or number of shapes.
+    pub fn read(mut r: impl Read, shapes: Vec<T>, hash: u64) -> io::Result<Self> {
+        let layout = Layout::read(&mut r, shapes.len(), hash)?;
+        Ok(Self::from_layout(layout, shapes))
+    }
+
+    /// Save the structure of the `KdTree` in a compact binary format along
+    /// with the `hash` of the shapes it was built from. The shapes themselves
+    /// are not saved, but referenced by their index.
+    pub fn write(&self, mut w: impl Write, hash: u64) -> io::Result<()> {
+        let indices = self
+            .shapes
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (Arc::as_ptr(s), i as u32))
+            .collect::<HashMap<_, _>>();
+
+        w.write_all(MAGIC)?;
+        w.write_u64::<LittleEndian>(hash)?;
+        w.write_u64::<LittleEndian>(self.shapes.len() as u64)?;
+
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            match node {
+                Node::Leaf { data } => {
+                    w.write_u8(0)?;
+                    w.write_u32::<LittleEndian>(data.len() as u32)?;
+                    for s in data {
+                        w.write_u32::<LittleEndian>(indices[&Arc::as_ptr(s)])?;
+                    }
+                }
+                Node::Branch {
+                    left,
+                    right,
+                    split_value,
+                    split_axis,
+                } => {
+                    w.write_u8(1)?;
+                    w.write_u8(*split_axis as u8)?;
+                    w.write_f64::<LittleEndian>(*split_value)?;
+
+                    stack.push(right);
+                    stack.push(left);
+                }
+            }
+        }
+
+        w.flush()
+    }
+
+    fn from_layout(layout: Layout, shapes: Vec<T>) -> Self {
+        let shapes = shapes.into_iter().map(Arc::new).collect::<Vec<_>>();
 
         KdTree {
-            root: Node::new(shapes.into_iter().map(Arc::new).collect(), bboxes),
+            root: Node::from_layout(layout, &shapes),
+            shapes,
         }
     }
 
     /// Find the intersection, if any, between the objects in the `KdTree` and a
     /// given `Ray`. The intersection is defined by the shape and its t
     /// parameter with respect to the ray.
-    pub fn intersection(&self, ray: &Ray) -> Option<(&T, <T as Shape>::Intersection)> {
+    pub fn intersection(&self, ray: &Ray) -> Option<(&T, T::Intersection)> {
         self.intersections(ray).next()
     }
 
@@ -61,10 +171,7 @@ where
     /// given ray. Each intersection is defined by the shape and its t parameter
     /// with respect to the ray. The intersections are sorted by their t
     /// parameter.
-    pub fn intersections(
-        &self,
-        ray: &Ray,
-    ) -> impl Iterator<Item = (&T, <T as Shape>::Intersection)> {
+    pub fn intersections(&self, ray: &Ray) -> impl Iterator<Item = (&T, T::Intersection)> {
         self.root.intersections(ray, 0.0, f64::INFINITY)
     }
 }
@@ -99,11 +206,10 @@ where
         ray: &Ray,
         tmin: f64,
         tmax: f64,
-    ) -> impl Iterator<Item = (&T, <T as Shape>::Intersection)> + '_ {
+    ) -> impl Iterator<Item = (&T, T::Intersection)> + '_ {
         let mut node_stack = vec![(self, tmin, tmax)];
 
-        let mut current_intersections: Vec<(&T, <T as Shape>::Intersection)> =
-            Vec::with_capacity(LEAF_SIZE);
+        let mut current_intersections: Vec<(&T, T::Intersection)> = Vec::with_capacity(LEAF_SIZE);
 
         let ray = ray.clone();
         std::iter::from_fn(move || {
@@ -187,6 +293,105 @@ where
     }
 }
 
+impl<T> Node<T> {
+    fn from_layout(layout: Layout, shapes: &[Arc<T>]) -> Self {
+        match layout {
+            Layout::Leaf(indices) => Node::Leaf {
+                data: indices.into_iter().map(|i| shapes[i].clone()).collect(),
+            },
+            Layout::Branch {
+                left,
+                right,
+                split_value,
+                split_axis,
+            } => Node::Branch {
+                left: Box::new(Node::from_layout(*left, shapes)),
+                right: Box::new(Node::from_layout(*right, shapes)),
+                split_value,
+                split_axis,
+            },
+        }
+    }
+}
+
+/// The structure of a serialized `KdTree` where the shapes are referred to by
+/// index.
+enum Layout {
+    Leaf(Vec<usize>),
+    Branch {
+        left: Box<Layout>,
+        right: Box<Layout>,
+        split_value: f64,
+        split_axis: Axis,
+    },
+}
+
+impl Layout {
+    fn read(r: &mut impl Read, nshapes: usize, hash: u64) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a serialized KdTree"));
+        }
+
+        if r.read_u64::<LittleEndian>()? != hash {
+            return Err(invalid_data("hash mismatch"));
+        }
+        if r.read_u64::<LittleEndian>()? != nshapes as u64 {
+            return Err(invalid_data("number of shapes mismatch"));
+        }
+
+        Self::read_node(r, nshapes, 0)
+    }
+
+    fn read_node(r: &mut impl Read, nshapes: usize, depth: usize) -> io::Result<Self> {
+        match r.read_u8()? {
+            0 => {
+                let n = r.read_u32::<LittleEndian>()?;
+
+                let mut indices = Vec::with_capacity((n as usize).min(nshapes));
+                for _ in 0..n {
+                    let i = r.read_u32::<LittleEndian>()? as usize;
+                    if i >= nshapes {
+                        return Err(invalid_data("shape index out of bounds"));
+                    }
+                    indices.push(i);
+                }
+
+                Ok(Layout::Leaf(indices))
+            }
+            1 => {
+                if depth >= MAX_DEPTH {
+                    return Err(invalid_data("tree too deep"));
+                }
+
+                let split_axis = match r.read_u8()? {
+                    0 => Axis::X,
+                    1 => Axis::Y,
+                    2 => Axis::Z,
+                    _ => return Err(invalid_data("invalid split axis")),
+                };
+                let split_value = r.read_f64::<LittleEndian>()?;
+
+                let left = Self::read_node(r, nshapes, depth + 1)?;
+                let right = Self::read_node(r, nshapes, depth + 1)?;
+
+                Ok(Layout::Branch {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                    split_value,
+                    split_axis,
+                })
+            }
+            _ => Err(invalid_data("invalid node")),
+        }
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
 /// Check where the bounding box lies wrt to the given axis and value. In
 /// particular, it returns:
 /// - (true, false) when the bbox is completely to the left
@@ -304,15 +509,13 @@ mod tests {
         let kd = KdTree::new(vec![Vec3::zero(), v3(-1.0, 2.0, 0.0), v3(8.0, 6.0, -1.0)]);
 
         assert_eq!(
-            kd,
-            KdTree {
-                root: Node::Leaf {
-                    data: vec![
-                        Arc::new(Vec3::zero()),
-                        Arc::new(v3(-1.0, 2.0, 0.0)),
-                        Arc::new(v3(8.0, 6.0, -1.0)),
-                    ]
-                }
+            kd.root,
+            Node::Leaf {
+                data: vec![
+                    Arc::new(Vec3::zero()),
+                    Arc::new(v3(-1.0, 2.0, 0.0)),
+                    Arc::new(v3(8.0, 6.0, -1.0)),
+                ]
             }
         );
 
@@ -331,38 +534,98 @@ mod tests {
         ]);
 
         assert_eq!(
-            kd,
-            KdTree {
-                root: Node::Branch {
-                    split_value: 0.0,
-                    split_axis: Axis::Y,
-
-                    left: Box::new(Node::Leaf {
-                        data: vec![
-                            Arc::new(v3(1.0, -3.0, 6.0)),
-                            Arc::new(v3(-3.0, -3.0, 6.0)),
-                            Arc::new(v3(0.0, -6.0, 2.0)),
-                            Arc::new(v3(-9.0, -3.0, -3.0)),
-                            Arc::new(v3(0, 0, 1)),
-                            Arc::new(v3(-1.0, -3.0, 2.0)),
-                            Arc::new(v3(0, 0, 0))
-                        ]
-                    }),
-                    right: Box::new(Node::Leaf {
-                        data: vec![
-                            Arc::new(v3(0.0, 5.0, -1.0)),
-                            Arc::new(v3(10.0, 1.0, -4.0)),
-                            Arc::new(v3(0, 0, 1)),
-                            Arc::new(v3(8.0, 6.0, -1.0)),
-                            Arc::new(v3(-1.0, 2.0, 0.0)),
-                            Arc::new(v3(0, 0, 0)),
-                        ]
-                    }),
-                }
+            kd.root,
+            Node::Branch {
+                split_value: 0.0,
+                split_axis: Axis::Y,
+
+                left: Box::new(Node::Leaf {
+                    data: vec![
+                        Arc::new(v3(1.0, -3.0, 6.0)),
+                        Arc::new(v3(-3.0, -3.0, 6.0)),
+                        Arc::new(v3(0.0, -6.0, 2.0)),
+                        Arc::new(v3(-9.0, -3.0, -3.0)),
+                        Arc::new(v3(0, 0, 1)),
+                        Arc::new(v3(-1.0, -3.0, 2.0)),
+                        Arc::new(v3(0, 0, 0))
+                    ]
+                }),
+                right: Box::new(Node::Leaf {
+                    data: vec![
+                        Arc::new(v3(0.0, 5.0, -1.0)),
+                        Arc::new(v3(10.0, 1.0, -4.0)),
+                        Arc::new(v3(0, 0, 1)),
+                        Arc::new(v3(8.0, 6.0, -1.0)),
+                        Arc::new(v3(-1.0, 2.0, 0.0)),
+                        Arc::new(v3(0, 0, 0)),
+                    ]
+                }),
             }
         );
     }
 
+    #[test]
+    fn test_write_read() {
+        let pts = (0..50)
+            .map(|i| {
+                let i = f64::from(i);
+                v3(i.sin() * 10.0, (i * 0.7).cos() * 10.0, i)
+            })
+            .collect::<Vec<_>>();
+        let tree = KdTree::new(pts.clone());
+
+        let mut buf = vec![];
+        tree.write(&mut buf, 42).unwrap();
+
+        assert_eq!(KdTree::read(&buf[..], pts.clone(), 42).unwrap(), tree);
+
+        for (shapes, hash) in [(pts.clone(), 7), (pts[1..].to_vec(), 42)] {
+            let err = KdTree::read(&buf[..], shapes, hash).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+        assert!(KdTree::read(&buf[..buf.len() - 1], pts, 42).is_err());
+    }
+
+    #[test]
+    fn test_read_too_deep() {
+        let pts = vec![v3(0, 0, 0), v3(1, 1, 1)];
+
+        // a chain of branches whose left child is always another branch and
+        // whose right one is an empty leaf
+        let tree = |depth: usize| {
+            let mut buf = MAGIC.to_vec();
+            buf.extend(42_u64.to_le_bytes());
+            buf.extend(2_u64.to_le_bytes());
+            for _ in 0..depth {
+                buf.push(1);
+                buf.push(0);
+                buf.extend(0.5_f64.to_le_bytes());
+            }
+            buf.extend([0, 1, 0, 0, 0, 0, 0, 0, 0]);
+            for _ in 0..depth {
+                buf.extend([0, 0, 0, 0, 0]);
+            }
+            buf
+        };
+
+        assert!(KdTree::read(&tree(MAX_DEPTH)[..], pts.clone(), 42).is_ok());
+
+        for depth in [MAX_DEPTH + 1, 1_000_000] {
+            let err = KdTree::read(&tree(depth)[..], pts.clone(), 42).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+
+        // a malformed file is simply replaced by a new tree
+        let path = std::env::temp_dir().join(format!("geo-kdtree-{}.kdt", std::process::id()));
+        std::fs::write(&path, tree(1_000_000)).unwrap();
+        let loaded = KdTree::load_or_build(&path, pts.clone(), 42).unwrap();
+        assert_eq!(loaded, KdTree::new(pts.clone()));
+
+        let saved = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(KdTree::read(&saved[..], pts, 42).unwrap(), loaded);
+    }
+
     #[test]
     fn test_best_partitioning() {
         assert_eq!(
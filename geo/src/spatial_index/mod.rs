@@ -1,7 +1,7 @@
 pub mod bvh;
 pub mod kdtree;
 
-pub use bvh::Bvh;
+pub use bvh::{Bvh, BvhBuilder, SplitMethod};
 pub use kdtree::KdTree;
 
 use crate::ray::Ray;
@@ -0,0 +1,241 @@
+//! Clip 2D polylines against a frame, a very common post-processing step
+//! before sending a drawing to a plotter.
+//!
+//! Each segment is split at every point where it crosses the boundary of the
+//! region and the pieces are kept or discarded depending on which side of the
+//! boundary they lie on. Unlike [Sutherland–Hodgman][0] this works with
+//! concave polygons too.
+//!
+//! [0]: https://en.wikipedia.org/wiki/Sutherland%E2%80%93Hodgman_algorithm
+
+use crate::{Polyline2, Vec2};
+
+/// A closed region of the plane polylines can be clipped against.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClipRegion {
+    /// A simple polygon, convex or concave, whose last point is implicitly
+    /// connected to the first one.
+    Polygon(Vec<Vec2>),
+
+    /// A circle with the given center and radius.
+    Circle { center: Vec2, radius: f64 },
+}
+
+impl ClipRegion {
+    /// Create a polygonal `ClipRegion` from the given points.
+    pub fn polygon(pts: impl IntoIterator<Item = Vec2>) -> Self {
+        let pts: Vec<_> = pts.into_iter().collect();
+        assert!(pts.len() >= 3, "a polygon needs at least 3 points");
+
+        ClipRegion::Polygon(pts)
+    }
+
+    /// Create an axis aligned rectangular `ClipRegion` from its min and max
+    /// corners.
+    pub fn rect(min: Vec2, max: Vec2) -> Self {
+        ClipRegion::polygon([min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)])
+    }
+
+    /// Create a circular `ClipRegion`.
+    pub fn circle(center: Vec2, radius: f64) -> Self {
+        ClipRegion::Circle { center, radius }
+    }
+
+    /// Check whether the given point lies inside the region.
+    pub fn contains(&self, p: Vec2) -> bool {
+        match self {
+            ClipRegion::Circle { center, radius } => p.dist2(*center) <= radius * radius,
+            ClipRegion::Polygon(pts) => {
+                // even-odd rule
+                let mut inside = false;
+                for (a, b) in edges(pts) {
+                    if (a.y > p.y) != (b.y > p.y)
+                        && p.x < a.x + (b.x - a.x) * (p.y - a.y) / (b.y - a.y)
+                    {
+                        inside = !inside;
+                    }
+                }
+                inside
+            }
+        }
+    }
+
+    /// Return the pieces of the given path that lie inside the region.
+    pub fn clip(&self, path: &Polyline2) -> Vec<Polyline2> {
+        self.split(path, true)
+    }
+
+    /// Return the pieces of the given path that lie outside the region.
+    pub fn clip_outside(&self, path: &Polyline2) -> Vec<Polyline2> {
+        self.split(path, false)
+    }
+
+    fn split(&self, path: &Polyline2, keep_inside: bool) -> Vec<Polyline2> {
+        let mut out = vec![];
+
+        if path.len() == 1 {
+            if self.contains(path.points[0]) == keep_inside {
+                out.push(path.clone());
+            }
+            return out;
+        }
+
+        let mut cur = Polyline2::new();
+        let mut ts = vec![];
+        for w in path.points.windows(2) {
+            let (a, b) = (w[0], w[1]);
+
+            ts.clear();
+            ts.push(0.0);
+            self.crossings(a, b, &mut ts);
+            ts.push(1.0);
+            ts.sort_by(|t0, t1| t0.partial_cmp(t1).unwrap());
+
+            for t in ts.windows(2) {
+                let (t0, t1) = (t[0], t[1]);
+                if t1 - t0 <= f64::EPSILON {
+                    continue;
+                }
+
+                // the piece is entirely on one side of the boundary, hence
+                // checking its midpoint is enough
+                let keep = self.contains(a.lerp(b, (t0 + t1) / 2.0)) == keep_inside;
+                if !keep {
+                    if cur.len() > 1 {
+                        out.push(cur);
+                    }
+                    cur = Polyline2::new();
+                    continue;
+                }
+
+                let (p0, p1) = (a.lerp(b, t0), a.lerp(b, t1));
+                if cur.is_empty() {
+                    cur.push(p0);
+                }
+                cur.push(p1);
+            }
+        }
+
+        if cur.len() > 1 {
+            out.push(cur);
+        }
+
+        out
+    }
+
+    /// Push the parameters in (0, 1) where the segment from `a` to `b` crosses
+    /// the boundary of the region.
+    fn crossings(&self, a: Vec2, b: Vec2, ts: &mut Vec<f64>) {
+        let d = b - a;
+        let in_range = |t: f64| t > 0.0 && t < 1.0;
+
+        match self {
+            ClipRegion::Circle { center, radius } => {
+                let f = a - *center;
+                let qa = d.norm2();
+                let qb = 2.0 * f.dot(d);
+                let qc = f.norm2() - radius * radius;
+
+                let disc = qb * qb - 4.0 * qa * qc;
+                if qa == 0.0 || disc < 0.0 {
+                    return;
+                }
+
+                let disc = disc.sqrt();
+                ts.extend(
+                    [(-qb - disc) / (2.0 * qa), (-qb + disc) / (2.0 * qa)]
+                        .into_iter()
+                        .filter(|&t| in_range(t)),
+                );
+            }
+            ClipRegion::Polygon(pts) => {
+                for (p, q) in edges(pts) {
+                    let e = q - p;
+                    let den = d.cross(e);
+                    if den == 0.0 {
+                        continue;
+                    }
+
+                    let ap = p - a;
+                    let t = ap.cross(e) / den;
+                    let u = ap.cross(d) / den;
+                    if in_range(t) && (0.0..=1.0).contains(&u) {
+                        ts.push(t);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn edges(pts: &[Vec2]) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+    pts.iter()
+        .zip(pts.iter().cycle().skip(1))
+        .map(|(a, b)| (*a, *b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2;
+
+    #[test]
+    fn test_clip_rect() {
+        let frame = ClipRegion::rect(v2(0, 0), v2(10, 10));
+        let path = Polyline2::from(vec![v2(-5, 5), v2(5, 5), v2(5, 15), v2(8, 15), v2(8, 5)]);
+
+        assert_eq!(
+            frame.clip(&path),
+            vec![
+                Polyline2::from(vec![v2(0, 5), v2(5, 5), v2(5, 10)]),
+                Polyline2::from(vec![v2(8, 10), v2(8, 5)]),
+            ]
+        );
+
+        assert_eq!(
+            frame.clip_outside(&path),
+            vec![
+                Polyline2::from(vec![v2(-5, 5), v2(0, 5)]),
+                Polyline2::from(vec![v2(5, 10), v2(5, 15), v2(8, 15), v2(8, 10)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clip_concave() {
+        // U shaped polygon, the horizontal line crosses the notch
+        let frame = ClipRegion::polygon([
+            v2(0, 0),
+            v2(9, 0),
+            v2(9, 9),
+            v2(6, 9),
+            v2(6, 3),
+            v2(3, 3),
+            v2(3, 9),
+            v2(0, 9),
+        ]);
+        let path = Polyline2::from(vec![v2(-2, 6), v2(14, 6)]);
+
+        assert_eq!(
+            frame.clip(&path),
+            vec![
+                Polyline2::from(vec![v2(0, 6), v2(3, 6)]),
+                Polyline2::from(vec![v2(6, 6), v2(9, 6)]),
+            ]
+        );
+        assert!(frame.contains(v2(1, 1)));
+        assert!(!frame.contains(v2(4, 6)));
+    }
+
+    #[test]
+    fn test_clip_circle() {
+        let frame = ClipRegion::circle(v2(0, 0), 5.0);
+
+        let clipped = frame.clip(&Polyline2::from(vec![v2(-10, 0), v2(10, 0)]));
+        assert_eq!(clipped, vec![Polyline2::from(vec![v2(-5, 0), v2(5, 0)])]);
+
+        let inside = Polyline2::from(vec![v2(-1, 0), v2(1, 1)]);
+        assert_eq!(frame.clip(&inside), vec![inside.clone()]);
+        assert!(frame.clip_outside(&inside).is_empty());
+    }
+}
@@ -1,8 +1,22 @@
+//! Minimal in memory image that can be saved as [PNM][0], [PNG][1] or
+//! [JPEG][2], the latter two only with the `image` feature enabled. The alpha
+//! channel is dropped when saving as JPEG and images with an alpha channel
+//! cannot be saved as PNM at all.
+//!
+//! [0]: https://en.wikipedia.org/wiki/Netpbm
+//! [1]: https://www.w3.org/TR/png/
+//! [2]: https://www.w3.org/Graphics/JPEG/
+
 use std::{
     fs::File,
     io::{self, BufWriter, Write},
 };
 
+#[cfg(feature = "image")]
+use jpeg_encoder::ColorType as JpegColor;
+#[cfg(feature = "image")]
+use png::ColorType as PngColor;
+
 pub struct Image<const PIXELS: usize> {
     data: Vec<u8>,
     width: u32,
@@ -20,6 +34,17 @@ impl Image<3> {
     }
 }
 
+impl Image<4> {
+    pub fn rgba(w: u32, h: u32) -> Self {
+        let d = usize::try_from(w).unwrap() * usize::try_from(h).unwrap();
+        Self {
+            data: vec![0; d * 4],
+            width: w,
+            height: h,
+        }
+    }
+}
+
 impl Image<1> {
     pub fn grayscale(w: u32, h: u32) -> Self {
         let d = usize::try_from(w).unwrap() * usize::try_from(h).unwrap();
@@ -40,6 +65,24 @@ impl<const PIXELS: usize> Image<PIXELS> {
         &mut self.data
     }
 
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Return the channels of the pixel at the given coordinates.
+    pub fn pixel_mut(&mut self, x: u32, y: u32) -> &mut [u8] {
+        assert!(x < self.width && y < self.height);
+
+        let i = (usize::try_from(y).unwrap() * usize::try_from(self.width).unwrap()
+            + usize::try_from(x).unwrap())
+            * PIXELS;
+        &mut self.data[i..i + PIXELS]
+    }
+
     pub fn pixels_mut(&mut self) -> impl Iterator<Item = (u32, u32, &mut [u8])> {
         let w = usize::try_from(self.width).unwrap();
         self.data
@@ -48,15 +91,23 @@ impl<const PIXELS: usize> Image<PIXELS> {
             .map(move |(i, p)| ((i % w) as u32, (i / w) as u32, p))
     }
 
+    /// Save the image to the given path. The format is chosen by the
+    /// extension, which must be either .png, .jpg/.jpeg, with the `image`
+    /// feature enabled, or .pgm/.ppm depending on the number of channels.
     pub fn save(&self, f: &str) -> io::Result<()> {
-        let header = if PIXELS == 1 {
-            assert!(f.ends_with(".pgm"));
-            "P5"
-        } else if PIXELS == 3 {
-            assert!(f.ends_with(".ppm"));
-            "P6"
-        } else {
-            unimplemented!();
+        #[cfg(feature = "image")]
+        if f.ends_with(".png") {
+            return self.save_png(f);
+        }
+        #[cfg(feature = "image")]
+        if f.ends_with(".jpg") || f.ends_with(".jpeg") {
+            return self.save_jpeg(f, 90);
+        }
+
+        let header = match (PIXELS, f.ends_with(".pgm"), f.ends_with(".ppm")) {
+            (1, true, _) => "P5",
+            (3, _, true) => "P6",
+            _ => return Err(unsupported(PIXELS, f)),
         };
 
         let mut out = BufWriter::new(File::create(f)?);
@@ -69,4 +120,189 @@ impl<const PIXELS: usize> Image<PIXELS> {
 
         Ok(())
     }
+
+    /// Save the image as a PNG to the given path.
+    #[cfg(feature = "image")]
+    pub fn save_png(&self, f: &str) -> io::Result<()> {
+        let color = match PIXELS {
+            1 => PngColor::Grayscale,
+            3 => PngColor::Rgb,
+            4 => PngColor::Rgba,
+            _ => return Err(unsupported(PIXELS, f)),
+        };
+
+        let mut encoder =
+            png::Encoder::new(BufWriter::new(File::create(f)?), self.width, self.height);
+        encoder.set_color(color);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer
+            .write_image_data(&self.data)
+            .map_err(io::Error::other)?;
+        writer.finish().map_err(io::Error::other)
+    }
+
+    /// Save the image as a JPEG of the given `quality`, from 1 to 100, to
+    /// the given path.
+    #[cfg(feature = "image")]
+    pub fn save_jpeg(&self, f: &str, quality: u8) -> io::Result<()> {
+        let color = match PIXELS {
+            1 => JpegColor::Luma,
+            3 => JpegColor::Rgb,
+            4 => JpegColor::Rgba,
+            _ => return Err(unsupported(PIXELS, f)),
+        };
+
+        let (Ok(w), Ok(h)) = (u16::try_from(self.width), u16::try_from(self.height)) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "JPEG images cannot be larger than 65535x65535",
+            ));
+        };
+
+        let mut out = BufWriter::new(File::create(f)?);
+        jpeg_encoder::Encoder::new(&mut out, quality)
+            .encode(&self.data, w, h, color)
+            .map_err(io::Error::other)?;
+        out.flush()
+    }
+}
+
+fn unsupported(channels: usize, f: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("cannot save an image with {channels} channels as {f}"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io::BufReader, path::PathBuf};
+
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("geo-image-{}-{name}", std::process::id()))
+    }
+
+    /// A smooth gradient, easy on the JPEG compression.
+    fn gradient<const PIXELS: usize>() -> Image<PIXELS> {
+        let mut img = Image {
+            data: vec![0; 32 * 24 * PIXELS],
+            width: 32,
+            height: 24,
+        };
+        for (x, y, p) in img.pixels_mut() {
+            for (c, v) in p.iter_mut().enumerate() {
+                *v = (x * 4 + y * 2 + c as u32 * 16) as u8;
+            }
+        }
+        img
+    }
+
+    #[cfg(feature = "image")]
+    fn decode_png(path: &PathBuf) -> (png::OutputInfo, Vec<u8>) {
+        let decoder = png::Decoder::new(BufReader::new(File::open(path).unwrap()));
+        let mut reader = decoder.read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        buf.truncate(info.buffer_size());
+        (info, buf)
+    }
+
+    #[cfg(feature = "image")]
+    fn decode_jpeg(path: &PathBuf) -> (jpeg_decoder::ImageInfo, Vec<u8>) {
+        let mut decoder = jpeg_decoder::Decoder::new(BufReader::new(File::open(path).unwrap()));
+        let data = decoder.decode().unwrap();
+        (decoder.info().unwrap(), data)
+    }
+
+    #[cfg(feature = "image")]
+    fn assert_close(a: impl IntoIterator<Item = u8>, b: impl IntoIterator<Item = u8>) {
+        let mut n = 0;
+        for (a, b) in a.into_iter().zip(b) {
+            assert!(a.abs_diff(b) <= 8, "{a} != {b}");
+            n += 1;
+        }
+        assert!(n > 0);
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn test_png_roundtrip() {
+        let path = temp_path("roundtrip.png");
+
+        let gray = gradient::<1>();
+        gray.save(path.to_str().unwrap()).unwrap();
+        let (info, data) = decode_png(&path);
+        assert_eq!((info.width, info.height), (32, 24));
+        assert_eq!(info.color_type, png::ColorType::Grayscale);
+        assert_eq!(data, gray.data());
+
+        let rgb = gradient::<3>();
+        rgb.save(path.to_str().unwrap()).unwrap();
+        let (info, data) = decode_png(&path);
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        assert_eq!(data, rgb.data());
+
+        let rgba = gradient::<4>();
+        rgba.save(path.to_str().unwrap()).unwrap();
+        let (info, data) = decode_png(&path);
+        assert_eq!(info.color_type, png::ColorType::Rgba);
+        assert_eq!(data, rgba.data());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn test_jpeg_roundtrip() {
+        let path = temp_path("roundtrip.jpg");
+
+        let gray = gradient::<1>();
+        gray.save_jpeg(path.to_str().unwrap(), 100).unwrap();
+        let (info, data) = decode_jpeg(&path);
+        assert_eq!((info.width, info.height), (32, 24));
+        assert_eq!(info.pixel_format, jpeg_decoder::PixelFormat::L8);
+        assert_close(data, gray.data().iter().copied());
+
+        let rgb = gradient::<3>();
+        rgb.save_jpeg(path.to_str().unwrap(), 100).unwrap();
+        let (info, data) = decode_jpeg(&path);
+        assert_eq!(info.pixel_format, jpeg_decoder::PixelFormat::RGB24);
+        assert_close(data, rgb.data().iter().copied());
+
+        // the alpha channel is dropped
+        let rgba = gradient::<4>();
+        rgba.save_jpeg(path.to_str().unwrap(), 100).unwrap();
+        let (info, data) = decode_jpeg(&path);
+        assert_eq!(info.pixel_format, jpeg_decoder::PixelFormat::RGB24);
+        assert_close(
+            data,
+            rgba.data().chunks_exact(4).flat_map(|p| p[..3].to_vec()),
+        );
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let path = temp_path("unsupported.ppm");
+        let err = gradient::<4>().save(path.to_str().unwrap()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        let err = gradient::<1>().save(path.to_str().unwrap()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        #[cfg(feature = "image")]
+        {
+            let err = gradient::<2>()
+                .save_png(path.to_str().unwrap())
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+
+        assert!(!path.exists());
+    }
 }
@@ -1,31 +1,115 @@
+pub mod clip;
 pub mod image;
+pub mod select;
 
-pub fn arange(s: f64, e: f64, step: f64) -> impl Iterator<Item = f64> {
-    Arange { s, e, step }
+pub use select::{
+    argmax_by_key, argmin_by_key, chunk_ranges, ksmallest_by, ksmallest_by_key, partition,
+};
+
+/// Return the values from `start` to `end`, excluded, `step` apart.
+///
+/// Each value is computed as `start + i * step` rather than by repeatedly
+/// adding `step` so that the error doesn't accumulate over long ranges. An
+/// `end` that is a multiple of `step` away from `start`, up to floating point
+/// errors, is never included, see `arange_inclusive` for that.
+pub fn arange(start: f64, end: f64, step: f64) -> impl ExactSizeIterator<Item = f64> + Clone {
+    let n = steps(start, end, step).map_or(0, |n| {
+        let k = n.round();
+        if (n - k).abs() <= STEPS_EPSILON * k.max(1.0) {
+            k as usize
+        } else {
+            n.ceil() as usize
+        }
+    });
+
+    (0..n).map(move |i| start + step * i as f64)
 }
 
-#[derive(Debug, Clone)]
-struct Arange {
-    s: f64,
-    e: f64,
+/// Return the values from `start` to `end`, included if it's a multiple of
+/// `step` away from `start` up to floating point errors, `step` apart. See
+/// `arange`.
+pub fn arange_inclusive(
+    start: f64,
+    end: f64,
     step: f64,
-}
+) -> impl ExactSizeIterator<Item = f64> + Clone {
+    let n = steps(start, end, step)
+        .map_or(0, |n| (n + STEPS_EPSILON * n.max(1.0)).floor() as usize + 1);
 
-impl Iterator for Arange {
-    type Item = f64;
+    (0..n).map(move |i| start + step * i as f64)
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.s > self.e {
-            return None;
+/// Return `n` evenly spaced values from `start` to `end`, both included. The
+/// last value is exactly `end` while a single value is just `start`.
+pub fn linspace(start: f64, end: f64, n: usize) -> impl ExactSizeIterator<Item = f64> + Clone {
+    (0..n).map(move |i| {
+        if n <= 1 {
+            start
+        } else if i + 1 == n {
+            end
+        } else {
+            start + (end - start) * (i as f64 / (n - 1) as f64)
         }
+    })
+}
+
+/// The relative tolerance used to decide whether `end` lies on a step.
+const STEPS_EPSILON: f64 = 1e-9;
 
-        let v = self.s;
-        self.s += self.step;
-        Some(v)
+/// Return how many steps fit between `start` and `end`, `None` if `step`
+/// goes the wrong way or if there are infinite steps.
+fn steps(start: f64, end: f64, step: f64) -> Option<f64> {
+    let n = (end - start) / step;
+    (n.is_finite() && n >= 0.0).then_some(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arange() {
+        assert_eq!(
+            arange(0.0, 3.0, 1.0).collect::<Vec<_>>(),
+            vec![0.0, 1.0, 2.0]
+        );
+        assert_eq!(
+            arange_inclusive(0.0, 3.0, 1.0).collect::<Vec<_>>(),
+            vec![0.0, 1.0, 2.0, 3.0]
+        );
+        assert_eq!(arange(0.0, 2.5, 1.0).len(), 3);
+        assert_eq!(arange_inclusive(0.0, 2.5, 1.0).len(), 3);
+        assert_eq!(
+            arange(3.0, 0.0, -1.0).collect::<Vec<_>>(),
+            vec![3.0, 2.0, 1.0]
+        );
+
+        assert_eq!(arange(0.0, 1.0, -0.1).len(), 0);
+        assert_eq!(arange(0.0, 1.0, 0.0).len(), 0);
+        assert_eq!(
+            arange_inclusive(1.0, 1.0, 0.5).collect::<Vec<_>>(),
+            vec![1.0]
+        );
+
+        // 0.1 is not exactly representable, yet the end is handled consistently
+        // and the values don't drift
+        assert_eq!(arange(0.0, 1.0, 0.1).len(), 10);
+        assert_eq!(arange_inclusive(0.0, 1.0, 0.1).len(), 11);
+
+        let last = arange(0.0, 2000.0, 0.01).last().unwrap();
+        assert!((last - 1999.99).abs() < 1e-9, "{last}");
     }
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        let n = f64::ceil((self.e - self.s) / self.step) as u64;
-        (0, usize::try_from(n).ok())
+    #[test]
+    fn test_linspace() {
+        assert_eq!(
+            linspace(0.0, 1.0, 5).collect::<Vec<_>>(),
+            vec![0.0, 0.25, 0.5, 0.75, 1.0]
+        );
+        assert_eq!(linspace(2.0, 1.0, 1).collect::<Vec<_>>(), vec![2.0]);
+        assert_eq!(linspace(0.0, 1.0, 0).len(), 0);
+
+        let v = linspace(0.0, 0.3, 4).collect::<Vec<_>>();
+        assert_eq!(v.last(), Some(&0.3));
     }
 }
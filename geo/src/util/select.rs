@@ -0,0 +1,189 @@
+//! Selection and partitioning helpers over slices and iterators.
+
+use std::{cmp::Ordering, ops::Range};
+
+/// Reorder `v` so that its first `k` elements are the `k` smallest ones
+/// according to `cmp`, in no particular order, and return them.
+///
+/// It runs in linear time on average, unlike sorting the whole slice. If `k`
+/// is greater than the length of `v` the whole slice is returned.
+pub fn ksmallest_by<T>(v: &mut [T], k: usize, mut cmp: impl FnMut(&T, &T) -> Ordering) -> &mut [T] {
+    let k = k.min(v.len());
+
+    if k > 0 && k < v.len() {
+        v.select_nth_unstable_by(k - 1, &mut cmp);
+    }
+
+    &mut v[..k]
+}
+
+/// Reorder `v` so that its first `k` elements are the `k` ones with the
+/// smallest keys, in no particular order, and return them. See
+/// `ksmallest_by`.
+pub fn ksmallest_by_key<T, K: Ord>(
+    v: &mut [T],
+    k: usize,
+    mut key: impl FnMut(&T) -> K,
+) -> &mut [T] {
+    ksmallest_by(v, k, |a, b| key(a).cmp(&key(b)))
+}
+
+/// Return the index of the first element with the smallest key, if any.
+///
+/// Keys that cannot be compared, like `f64::NAN`, are never picked unless
+/// they come first.
+pub fn argmin_by_key<T, K: PartialOrd>(
+    it: impl IntoIterator<Item = T>,
+    key: impl FnMut(&T) -> K,
+) -> Option<usize> {
+    arg_best_by_key(it, key, Ordering::Less)
+}
+
+/// Return the index of the first element with the greatest key, if any.
+///
+/// Keys that cannot be compared, like `f64::NAN`, are never picked unless
+/// they come first.
+pub fn argmax_by_key<T, K: PartialOrd>(
+    it: impl IntoIterator<Item = T>,
+    key: impl FnMut(&T) -> K,
+) -> Option<usize> {
+    arg_best_by_key(it, key, Ordering::Greater)
+}
+
+fn arg_best_by_key<T, K: PartialOrd>(
+    it: impl IntoIterator<Item = T>,
+    mut key: impl FnMut(&T) -> K,
+    better: Ordering,
+) -> Option<usize> {
+    let mut best: Option<(usize, K)> = None;
+
+    for (i, e) in it.into_iter().enumerate() {
+        let k = key(&e);
+
+        match &best {
+            Some((_, b)) if k.partial_cmp(b) != Some(better) => {}
+            _ => best = Some((i, k)),
+        }
+    }
+
+    best.map(|(i, _)| i)
+}
+
+/// Move all the elements of `v` that satisfy `pred` before the ones that
+/// don't, returning how many they are. The relative order of the elements is
+/// not preserved.
+pub fn partition<T>(v: &mut [T], mut pred: impl FnMut(&T) -> bool) -> usize {
+    let mut i = 0;
+    let mut j = v.len();
+
+    while i < j {
+        if pred(&v[i]) {
+            i += 1;
+        } else {
+            j -= 1;
+            v.swap(i, j);
+        }
+    }
+
+    i
+}
+
+/// Split `0..len` into `n` contiguous ranges whose lengths differ by at most
+/// one, e.g. to distribute some work among `n` workers. Empty ranges are
+/// skipped hence fewer than `n` ranges are returned when `len < n`.
+pub fn chunk_ranges(len: usize, n: usize) -> impl Iterator<Item = Range<usize>> {
+    let n = n.max(1).min(len.max(1));
+    let (size, rem) = (len / n, len % n);
+
+    (0..n)
+        .map(move |i| {
+            // the first `rem` ranges take one of the remaining elements each
+            let start = i * size + i.min(rem);
+            let end = start + size + usize::from(i < rem);
+            start..end
+        })
+        .filter(|r| !r.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_ksmallest() {
+        let mut v = vec![5, 1, 4, 2, 3];
+
+        let mut smallest = ksmallest_by(&mut v, 2, |a, b| a.cmp(b)).to_vec();
+        smallest.sort_unstable();
+        assert_eq!(smallest, vec![1, 2]);
+
+        assert!(ksmallest_by_key(&mut v, 0, |x| *x).is_empty());
+        assert_eq!(ksmallest_by_key(&mut v, 10, |x| *x).len(), 5);
+
+        let mut largest = ksmallest_by_key(&mut v, 3, |x| std::cmp::Reverse(*x)).to_vec();
+        largest.sort_unstable();
+        assert_eq!(largest, vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_argmin_argmax() {
+        let v = [3.0, 1.0, 4.0, 1.0, 5.0, f64::NAN];
+
+        assert_eq!(argmin_by_key(&v, |x| **x), Some(1));
+        assert_eq!(argmax_by_key(&v, |x| **x), Some(4));
+        assert_eq!(argmin_by_key(Vec::<f64>::new(), |x| *x), None);
+        assert_eq!(argmax_by_key(["a", "ccc", "bb"], |s| s.len()), Some(1));
+    }
+
+    #[test]
+    fn test_partition() {
+        let mut v = vec![1, 2, 3, 4, 5, 6, 7];
+        let n = partition(&mut v, |x| x % 3 == 0);
+
+        assert_eq!(n, 2);
+        assert!(v[..n].iter().all(|x| x % 3 == 0));
+        assert!(v[n..].iter().all(|x| x % 3 != 0));
+
+        assert_eq!(partition(&mut [] as &mut [i32], |_| true), 0);
+    }
+
+    #[test]
+    fn test_chunk_ranges() {
+        assert_eq!(
+            chunk_ranges(10, 3).collect::<Vec<_>>(),
+            vec![0..4, 4..7, 7..10]
+        );
+        assert_eq!(chunk_ranges(2, 4).collect::<Vec<_>>(), vec![0..1, 1..2]);
+        assert_eq!(chunk_ranges(5, 0).collect::<Vec<_>>(), vec![0..5]);
+        assert_eq!(chunk_ranges(0, 3).count(), 0);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_ksmallest_matches_sort(mut v in prop::collection::vec(any::<i32>(), 0..64), k in 0_usize..80) {
+            let mut sorted = v.clone();
+            sorted.sort_unstable();
+
+            let mut smallest = ksmallest_by_key(&mut v, k, |x| *x).to_vec();
+            smallest.sort_unstable();
+
+            prop_assert_eq!(&smallest[..], &sorted[..k.min(sorted.len())]);
+        }
+
+        #[test]
+        fn prop_chunk_ranges_cover(len in 0_usize..1000, n in 0_usize..50) {
+            let ranges = chunk_ranges(len, n).collect::<Vec<_>>();
+
+            prop_assert_eq!(ranges.iter().map(|r| r.len()).sum::<usize>(), len);
+            prop_assert!(ranges.windows(2).all(|w| w[0].end == w[1].start));
+
+            let lens = ranges.iter().map(|r| r.len());
+            let (min, max) = (lens.clone().min(), lens.max());
+            if let (Some(min), Some(max)) = (min, max) {
+                prop_assert!(max - min <= 1);
+            }
+        }
+    }
+}
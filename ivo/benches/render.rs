@@ -0,0 +1,61 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use ivo::*;
+
+/// A ball with the given number of millions of voxels.
+fn ball(millions: f64) -> Scene {
+    let r = (millions * 1e6 * 3.0 / (4.0 * std::f64::consts::PI)).cbrt() as i32;
+
+    let mut scene = Scene::new();
+    scene.sphere((0, 0, 0), r);
+    scene
+}
+
+/// A bumpy terrain made of columns of voxels on a square grid with the given
+/// side, which has a lot more visible faces than a ball of the same size.
+fn terrain(side: i32) -> Scene {
+    let mut scene = Scene::with_dimensions_hint(side, side, side / 4);
+
+    for y in 0..side {
+        for x in 0..side {
+            let (fx, fy) = (f64::from(x) / 16.0, f64::from(y) / 16.0);
+            let h = ((fx.sin() * fy.cos() + 1.0) * f64::from(side) / 16.0) as i32 + 1;
+
+            for z in 0..h {
+                scene.add(x, y, z);
+            }
+        }
+    }
+
+    scene
+}
+
+fn render(c: &mut Criterion) {
+    let datasets = [
+        ("ball_1m", ball(1.0)),
+        ("ball_4m", ball(4.0)),
+        ("terrain_512", terrain(512)),
+    ];
+
+    let mut group = c.benchmark_group("ivo_render");
+    group.sample_size(10);
+    for (name, scene) in &datasets {
+        group.throughput(Throughput::Elements(scene.voxels().count() as u64));
+
+        group.bench_function(format!("{name}/outlines"), |b| {
+            b.iter(|| render_outlines(black_box(scene)))
+        });
+        group.bench_function(format!("{name}/triangles"), |b| {
+            b.iter(|| render_triangles(black_box(scene)))
+        });
+
+        #[cfg(feature = "parallel")]
+        group.bench_function(format!("{name}/par_outlines"), |b| {
+            b.iter(|| par_render_outlines(black_box(scene)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, render);
+criterion_main!(benches);
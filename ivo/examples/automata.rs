@@ -1,6 +1,6 @@
 use std::f64::consts::TAU;
 
-use sketch_utils::{opener, sketch_output_path};
+use sketch_utils::{opener, OutputSession};
 
 use ivo::*;
 
@@ -9,9 +9,14 @@ use rand::prelude::*;
 pub fn main() {
     let mut scene = Scene::new();
 
-    let mut rng = rand::thread_rng();
+    let seed = rand::thread_rng().gen();
+    let mut rng = StdRng::seed_from_u64(seed);
 
     let n = rng.gen::<u8>();
+    let session = OutputSession::new("automata.svg")
+        .unwrap()
+        .with_seed(seed)
+        .with_param("rule", n);
 
     let mut cells: Vec<u8> = vec![0; 360];
     for _ in 0..rng.gen_range(1..=10) {
@@ -45,10 +50,8 @@ pub fn main() {
 
     let triangles = render_outlines(&scene);
 
-    let path = sketch_output_path("automata.svg").unwrap();
-
     dump_outlines_svg(
-        &path,
+        session.path(),
         &triangles,
         // &SvgSettings::new(744.0, 1052.0)
         &SvgSettings::new(744.0 * 3.0, 1052.0 * 3.0)
@@ -57,5 +60,7 @@ pub fn main() {
     )
     .unwrap();
 
+    let path = session.finish().expect("cannot save metadata");
+
     opener::open(&path).expect("cannot open automata.svg");
 }
@@ -1,4 +1,4 @@
-use sketch_utils::opener;
+use sketch_utils::{args::Cli, opener};
 
 use rand::prelude::*;
 
@@ -34,16 +34,20 @@ pub fn cube(rng: &mut impl Rng, n: usize, perfect_grid: bool) -> Vec<Blob> {
 }
 
 pub fn main() {
-    let mut scene = Scene::new();
+    let args = Cli::new("blocks", "Render a grid of blocks made of random blobs.")
+        .with_positional("N", "number of blobs per block, 10 by default")
+        .with_positional("SIZE", "number of blocks per side, 10 by default")
+        .with_flag("--perfect-grid", "keep the blobs inside their block")
+        .with_flag("--invert", "invert the scene after adding each blob")
+        .parse();
+    let n = args.positional(0).expect("invalid N").unwrap_or(10);
+    let size = args.positional(1).expect("invalid SIZE").unwrap_or(10);
+    let perfect_grid = args.flag("--perfect-grid");
+    let invert = args.flag("--invert");
 
-    let mut args = std::env::args().skip(1);
-    let mut next_arg = |def| args.next().map(|l| l.parse().unwrap()).unwrap_or(def);
-    let n = next_arg(10);
-    let perfect_grid = next_arg(0) != 0;
-    let invert = next_arg(0) != 0;
-    let size = next_arg(10) as i32;
+    let mut scene = Scene::new();
 
-    let mut rng = rand::thread_rng();
+    let mut rng = StdRng::seed_from_u64(args.seed());
     for i in 0..size {
         let bx = i * 20;
 
@@ -72,8 +76,16 @@ pub fn main() {
 
     let triangles = render_outlines(&scene);
 
-    dump_outlines_svg("blocks.svg", &triangles, &SvgSettings::new(2048.0, 2048.0))
-        .expect("cannot save blocks.svg");
-
-    opener::open("blocks.svg").expect("cannot open blocks.svg");
+    let output = args.output_or("blocks.svg");
+    let (width, height) = args.dimensions_or(2048, 2048);
+    dump_outlines_svg(
+        &output,
+        &triangles,
+        &SvgSettings::new(f64::from(width), f64::from(height)),
+    )
+    .expect("cannot save output");
+
+    if args.open {
+        opener::open(&output).expect("cannot open output");
+    }
 }
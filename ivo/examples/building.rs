@@ -1,5 +1,5 @@
 use geo::{sdf::*, v3};
-use sketch_utils::{opener, sketch_output_path};
+use sketch_utils::{opener, OutputSession};
 
 use rand::prelude::*;
 
@@ -8,13 +8,14 @@ use ivo::*;
 pub fn main() {
     let mut scene = Scene::new();
 
-    let path = sketch_output_path("building.svg").unwrap();
+    let seed = thread_rng().gen();
+    let session = OutputSession::new("building.svg").unwrap().with_seed(seed);
 
     scene.aabb((0, 0, 0), (100, 100, 100));
 
     scene.invert();
 
-    let mut rng = thread_rng();
+    let mut rng = StdRng::seed_from_u64(seed);
     for octave in 0..=6 {
         let n = 2 << octave;
         let r = 100 / (1 << octave);
@@ -36,7 +37,9 @@ pub fn main() {
         .with_padding(20.0);
 
     let triangles = render_outlines(&scene);
-    dump_outlines_svg(&path, &triangles, &settings).unwrap();
+    dump_outlines_svg(session.path(), &triangles, &settings).unwrap();
+
+    let path = session.finish().expect("cannot save metadata");
 
     opener::open(&path).expect("cannot open building.svg");
 }
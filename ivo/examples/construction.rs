@@ -0,0 +1,21 @@
+use ivo::*;
+use sketch_utils::opener;
+
+pub fn main() {
+    let mut scene = Scene::new();
+
+    // a tower on a base, partially hidden by a wall in front of it
+    scene.aabb((0, 0, 0), (8, 8, 0));
+    scene.aabb((-4, -4, 7), (2, 2, 6));
+    scene.aabb((0, 6, 4), (8, 1, 3));
+
+    dump_construction_svg(
+        "construction.svg",
+        &render_outlines(&scene),
+        &render_hidden_outlines(&scene),
+        &SvgSettings::new(1920.0, 1080.0).with_hidden_dasharray("6 4"),
+    )
+    .expect("cannot save construction.svg");
+
+    opener::open("construction.svg").expect("cannot open construction.svg");
+}
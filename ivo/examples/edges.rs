@@ -0,0 +1,22 @@
+use ivo::*;
+use sketch_utils::opener;
+
+pub fn main() {
+    let mut scene = Scene::new();
+    scene.sphere((0, 0, 0), 16);
+
+    // thick silhouette and a different pen for the edges of each orientation,
+    // the creases are thicker than the steps between faces
+    let settings = SvgSettings::new(1920.0, 1080.0)
+        .with_silhouette_stroke("black", 3.0)
+        .with_orientation_stroke(Orientation::Top, "orange", 1.0)
+        .with_orientation_stroke(Orientation::Left, "steelblue", 1.0)
+        .with_orientation_stroke(Orientation::Right, "seagreen", 1.0)
+        .with_edge_width(EdgeKind::Crease, 1.5)
+        .with_edge_width(EdgeKind::Face, 0.5);
+
+    dump_tagged_outlines_svg("edges.svg", &render_tagged_outlines(&scene), &settings)
+        .expect("cannot save edges.svg");
+
+    opener::open("edges.svg").expect("cannot open edges.svg");
+}
@@ -0,0 +1,32 @@
+use ivo::*;
+use sketch_utils::opener;
+
+pub fn main() {
+    let mut scene = Scene::new();
+
+    // a stack of plates of decreasing size with a pin through them
+    for (i, hw) in [12, 10, 8, 6].into_iter().enumerate() {
+        scene.aabb((0, 0, 3 * i as i32), (hw, hw, 1));
+    }
+    scene.cylinder((0, 0, -2), 2, 16);
+
+    // each plate is a part of the assembly, the pin is another one
+    let part = |(x, y, z): Voxel| {
+        if x * x + y * y <= 4 {
+            -1
+        } else {
+            (z + 1).div_euclid(3)
+        }
+    };
+
+    let exploded = scene.exploded(part, 3.0, Explode::Along(geo::Axis::Z));
+
+    dump_outlines_svg(
+        "exploded.svg",
+        &render_outlines(&exploded),
+        &SvgSettings::new(1920.0, 1080.0),
+    )
+    .expect("cannot save exploded.svg");
+
+    opener::open("exploded.svg").expect("cannot open exploded.svg");
+}
@@ -0,0 +1,36 @@
+use geo::{sdf::*, v3};
+use sketch_utils::opener;
+
+use ivo::*;
+
+pub fn main() {
+    let n = 12;
+
+    // each frame only adds a torus on top of the previous ones, so only the
+    // parts of the scene around it need to be rendered again
+    let mut renderer = IncrementalRenderer::new(Scene::with_dimensions_hint(200, 200, 200), 32);
+
+    let frames = (0..n)
+        .map(|i| {
+            // stack more and more tori on top of each other
+            let t = f64::from(i) / f64::from(n - 1);
+            renderer
+                .scene_mut()
+                .sdf(&(torus(3.0, 20.0 + 40.0 * t) + v3(0.0, 0.0, 160.0 * t)));
+
+            renderer.render_outlines()
+        })
+        .collect::<Vec<_>>();
+
+    dump_animation_svg(
+        "growing.svg",
+        &frames,
+        &SvgSettings::new(1080.0, 1080.0).with_background("white"),
+        AnimationOutput::Smil {
+            frame_duration: 0.25,
+        },
+    )
+    .expect("cannot save growing.svg");
+
+    opener::open("growing.svg").expect("cannot open growing.svg");
+}
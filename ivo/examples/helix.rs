@@ -1,7 +1,7 @@
-use std::{env, f64::consts::TAU};
+use std::f64::consts::TAU;
 
-use geo::util::arange;
-use sketch_utils::{opener, sketch_output_path};
+use geo::util::arange_inclusive;
+use sketch_utils::{args::Cli, opener, OutputSession};
 
 use ivo::*;
 
@@ -15,25 +15,33 @@ enum Mode {
 }
 
 pub fn main() {
-    let mut scene = Scene::new();
-
-    let _fill = ["true", "1"].contains(
-        &env::args()
-            .nth(1)
-            .unwrap_or_default()
-            .trim()
-            .to_lowercase()
-            .as_str(),
-    );
-    let mode = if env::args().nth(2).unwrap_or_default().trim().to_lowercase() == "cone" {
+    let args = Cli::new("helix", "Render random helixes of voxels.")
+        .with_flag("--fill", "fill the faces instead of drawing their outlines")
+        .with_flag(
+            "--cone",
+            "shape the helixes as cones instead of using noise",
+        )
+        .parse();
+
+    let fill = args.flag("--fill");
+    let mode = if args.flag("--cone") {
         Mode::Cone
     } else {
         Mode::Noise
     };
 
-    let fill = false;
+    let seed = args.seed();
+    let mut session = match &args.output {
+        Some(output) => OutputSession::at(output),
+        None => OutputSession::new("helix.svg").expect("cannot create output directory"),
+    }
+    .with_seed(seed)
+    .with_param("fill", fill)
+    .with_param("mode", format!("{mode:?}"));
+
+    let mut scene = Scene::new();
 
-    let mut rng = thread_rng();
+    let mut rng = StdRng::seed_from_u64(seed);
 
     let freq = rng.gen_range(0.3..=5.0);
     let freq2 = rng.gen_range(0.3..=3.0);
@@ -48,6 +56,12 @@ pub fn main() {
 
     let noise = Perlin::new(rng.gen());
 
+    session.set_param("freq", freq);
+    session.set_param("freq2", freq2);
+    session.set_param("freq3", freq3);
+    session.set_param("nseeds", nseeds);
+    session.set_param("radius", radius);
+
     let mut seeds = vec![];
     for i in 1..=nseeds {
         let a = match mode {
@@ -58,7 +72,7 @@ pub fn main() {
     }
 
     let height = 200.0;
-    for zz in arange(0.0, height, 0.01) {
+    for zz in arange_inclusive(0.0, height, 0.01) {
         let z = zz.round() as i32;
 
         let zt = zz / height;
@@ -86,10 +100,9 @@ pub fn main() {
         }
     }
 
-    let path = sketch_output_path("helix.svg").unwrap();
-
-    let a4_multiplier = 3.0;
-    let settings = SvgSettings::new(744.0 * a4_multiplier, 1052.0 * a4_multiplier)
+    let a4_multiplier = 3;
+    let (width, height) = args.dimensions_or(744 * a4_multiplier, 1052 * a4_multiplier);
+    let settings = SvgSettings::new(f64::from(width), f64::from(height))
         .with_stroke_width(2.0)
         .with_padding(10.0)
         .with_fill_color(Orientation::Top, "#ff0000")
@@ -104,11 +117,15 @@ pub fn main() {
 
     if fill {
         let triangles = render_triangles(&scene);
-        dump_triangles_svg(&path, &triangles, &settings).unwrap();
+        dump_triangles_svg(session.path(), &triangles, &settings).unwrap();
     } else {
         let triangles = render_outlines(&scene);
-        dump_outlines_svg(&path, &triangles, &settings).unwrap();
+        dump_outlines_svg(session.path(), &triangles, &settings).unwrap();
     }
 
-    opener::open(&path).expect("cannot open helix.svg");
+    let path = session.finish().expect("cannot save metadata");
+
+    if args.open {
+        opener::open(&path).expect("cannot open helix.svg");
+    }
 }
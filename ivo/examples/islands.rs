@@ -4,7 +4,7 @@ use noise::{HybridMulti, MultiFractal, NoiseFn, Perlin};
 use rand::prelude::*;
 
 use ivo::*;
-use sketch_utils::{opener, sketch_output_path};
+use sketch_utils::{opener, OutputSession};
 
 fn landscape(scene: &mut Scene, noise: impl NoiseFn<f64, 2>) {
     let mut minh = f64::INFINITY;
@@ -56,7 +56,10 @@ fn landscape(scene: &mut Scene, noise: impl NoiseFn<f64, 2>) {
 pub fn main() {
     let mut scene = Scene::new();
 
-    let seed = rand::thread_rng().gen();
+    let seed: u32 = rand::thread_rng().gen();
+    let session = OutputSession::new("islands.svg")
+        .unwrap()
+        .with_seed(u64::from(seed));
 
     let noise = HybridMulti::<Perlin>::new(seed)
         .set_frequency(0.5)
@@ -66,10 +69,8 @@ pub fn main() {
 
     let triangles = render_outlines(&scene);
 
-    let path = sketch_output_path("islands.svg").unwrap();
-
     dump_outlines_svg(
-        &path,
+        session.path(),
         &triangles,
         // &SvgSettings::new(2048.0, 2048.0)
         &SvgSettings::new(1052.0, 744.0)
@@ -78,5 +79,7 @@ pub fn main() {
     )
     .expect("cannot save output image");
 
+    let path = session.finish().expect("cannot save metadata");
+
     opener::open(path).expect("cannot open output image");
 }
@@ -0,0 +1,26 @@
+use ivo::*;
+use sketch_utils::opener;
+
+pub fn main() {
+    let mut scene = Scene::new();
+
+    // a few towers of different heights on top of a base
+    scene.aabb((0, 0, 0), (16, 16, 1));
+    for (i, h) in [4, 8, 12, 6].into_iter().enumerate() {
+        let (x, y) = ((i as i32 % 2) * 16 - 8, (i as i32 / 2) * 16 - 8);
+        scene.aabb((x, y, h + 1), (2, 2, h));
+    }
+
+    // the light comes from the back left
+    let shadows = render_shadows(&scene, (1, 1));
+
+    dump_shadowed_outlines_svg(
+        "shadows.svg",
+        &render_outlines(&scene),
+        &shadows,
+        &SvgSettings::new(1920.0, 1080.0).with_shadows(ShadowStyle::Hatch { spacing: 0.3 }),
+    )
+    .expect("cannot save shadows.svg");
+
+    opener::open("shadows.svg").expect("cannot open shadows.svg");
+}
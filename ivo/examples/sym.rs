@@ -1,3 +1,4 @@
+use geo::Axis;
 use sketch_utils::opener;
 
 use ivo::*;
@@ -13,10 +14,11 @@ pub fn main() {
             let h = 150.0 + 300.0 * t.powi(6);
 
             scene.zslab((x + 25, y + 25, 0), (20, 20, h as i32));
-            scene.zslab((x + 25, y + 25, -h as i32), (20, 20, h as i32));
         }
     }
 
+    scene.union(&scene.mirrored(Axis::Z));
+
     let triangles = render_outlines(&scene);
 
     dump_outlines_svg("sym.svg", &triangles, &SvgSettings::new(1080.0, 1920.0))
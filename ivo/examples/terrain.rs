@@ -0,0 +1,42 @@
+use rand::prelude::*;
+
+use ivo::{procgen::ValueNoise, *};
+use sketch_utils::{opener, OutputSession};
+
+pub fn main() {
+    let seed = rand::thread_rng().gen();
+    let session = OutputSession::new("terrain.svg").unwrap().with_seed(seed);
+
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let (w, h) = (80, 80);
+    let noise = ValueNoise::new(rng.gen()).with_octaves(4);
+
+    let heights = (0..w * h)
+        .map(|i| {
+            let (x, y) = (f64::from(i % w), f64::from(i / w));
+            noise.get(x / 30.0, y / 30.0, 0.0) * 0.5 + 0.5
+        })
+        .collect::<Vec<_>>();
+
+    let mut scene = Scene::new();
+    scene.heightmap(&heights, usize::try_from(w).unwrap(), 30.0);
+
+    // carve some caves
+    let caves = ValueNoise::new(rng.gen()).with_octaves(2);
+    scene.invert();
+    scene.noise_fill((0, 0, 0), (w, h, 30), &caves, 0.08, 0.4);
+
+    let triangles = render_outlines(&scene);
+
+    dump_outlines_svg(
+        session.path(),
+        &triangles,
+        &SvgSettings::new(1920.0, 1080.0).with_padding(10.0),
+    )
+    .expect("cannot save output image");
+
+    let path = session.finish().expect("cannot save metadata");
+
+    opener::open(path).expect("cannot open output image");
+}
@@ -1,17 +1,18 @@
-use sketch_utils::opener;
+use sketch_utils::{args::Cli, opener};
 
 use rand::prelude::*;
 
 use ivo::*;
 
 pub fn main() {
-    let mut scene = Scene::new();
+    let args = Cli::new("tower", "Render a tower of random slabs.")
+        .with_positional("N", "number of slabs, 10 by default")
+        .parse();
+    let n = args.positional(0).expect("invalid N").unwrap_or(10);
 
-    let mut args = std::env::args().skip(1);
-    let mut next_arg = |def| args.next().map(|l| l.parse().unwrap()).unwrap_or(def);
-    let n = next_arg(10);
+    let mut scene = Scene::new();
 
-    let mut rng = rand::thread_rng();
+    let mut rng = StdRng::seed_from_u64(args.seed());
 
     let mut bz = 2;
 
@@ -39,14 +40,18 @@ pub fn main() {
 
     let triangles = render_outlines(&scene);
 
+    let output = args.output_or("tower.svg");
+    let (width, height) = args.dimensions_or(2048, 2048);
     dump_outlines_svg(
-        "tower.svg",
+        &output,
         &triangles,
-        &SvgSettings::new(2048.0, 2048.0)
+        &SvgSettings::new(f64::from(width), f64::from(height))
             .with_padding(10.0)
             .with_stroke_width(2.0),
     )
-    .expect("cannot save tower.svg");
+    .expect("cannot save output");
 
-    opener::open("tower.svg").expect("cannot open tower.svg");
+    if args.open {
+        opener::open(&output).expect("cannot open output");
+    }
 }
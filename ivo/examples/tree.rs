@@ -0,0 +1,34 @@
+use sketch_utils::opener;
+
+use ivo::*;
+
+pub fn main() {
+    let mut scene = Scene::new();
+
+    scene.cylinder((0, 0, 0), 3, 30);
+
+    for (dx, dy) in [(1, 0), (0, 1), (-1, 0), (0, -1)] {
+        scene.bezier(
+            (0, 0, 20),
+            (dx * 5, dy * 5, 30),
+            (dx * 15, dy * 15, 30),
+            (dx * 25, dy * 25, 45),
+            1,
+        );
+        scene.sphere((dx * 25, dy * 25, 45), 6);
+    }
+
+    scene.line((0, 0, 30), (0, 0, 55), 2);
+    scene.sphere((0, 0, 55), 10);
+
+    let triangles = render_outlines(&scene);
+
+    dump_outlines_svg(
+        "tree.svg",
+        &triangles,
+        &SvgSettings::new(1920.0, 1080.0).with_padding(10.0),
+    )
+    .expect("cannot save tree.svg");
+
+    opener::open("tree.svg").expect("cannot open tree.svg");
+}
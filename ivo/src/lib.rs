@@ -20,6 +20,18 @@
 //! Where o is the origin and each axis grows positevely along the direction
 //! shown above.
 //!
+//! Scenes can also be described in TOML or JSON files, see the `scene_file`
+//! module, and rendered to SVG with the `ivo` binary. MagicaVoxel `.vox` files
+//! can be loaded and saved as well, see the `vox` module.
+//!
+//! The optional `parallel` feature enables `par_render_outlines` and
+//! `par_render_triangles` which split the rendering across multiple threads
+//! using [rayon](https://docs.rs/rayon).
+//!
+//! Scenes that are edited and rendered over and over can be rendered with an
+//! `IncrementalRenderer` which only renders again the parts of the Scene
+//! affected by the edits.
+//!
 //! ## Renderer implementation
 //!
 //! The renderer works by first triangulating the faces of each Voxel into a set
@@ -100,12 +112,25 @@
 //! ```
 //!
 
+pub mod prelude;
+#[cfg(feature = "procgen")]
+pub mod procgen;
 mod renderer;
+pub mod scene_file;
 mod spatial_index;
+pub mod vox;
 
 pub use renderer::*;
 
-use geo::{sdf::Sdf, util::arange, v3};
+use std::hash::Hash;
+
+use geo::{
+    sdf::Sdf,
+    util::{arange_inclusive, linspace},
+    v3, Axis, Triangle,
+};
+use renderer::project_ij;
+use rustc_hash::{FxHashMap, FxHashSet};
 
 /// A Voxel identified by its x, y, z coordinates.
 pub type Voxel = (i32, i32, i32);
@@ -119,6 +144,17 @@ pub type XY = (f64, f64);
 /// A line in the cartesian plane.
 pub type Line = Vec<XY>;
 
+/// How the clusters of voxels are moved apart by `Scene::exploded`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Explode {
+    /// Move the clusters away from the center of the Scene in any direction.
+    Radial,
+
+    /// Move the clusters away from the center of the Scene only along the
+    /// given axis.
+    Along(Axis),
+}
+
 /// A Scene that can be rendered.
 ///
 /// It's just a collection of Voxels.
@@ -126,6 +162,10 @@ pub type Line = Vec<XY>;
 pub struct Scene {
     voxels: spatial_index::Index,
     add: bool,
+
+    /// The cells in IJ space of the voxels added or removed since the last
+    /// call to `take_changes`, if changes are being tracked.
+    changes: Option<FxHashSet<IJ>>,
 }
 
 impl Scene {
@@ -134,6 +174,7 @@ impl Scene {
         Self {
             voxels: spatial_index::Index::new(),
             add: true,
+            changes: None,
         }
     }
 
@@ -149,6 +190,7 @@ impl Scene {
         Self {
             voxels: spatial_index::Index::with_bbox_hint(min, max),
             add: true,
+            changes: None,
         }
     }
 
@@ -184,12 +226,193 @@ impl Scene {
         })
     }
 
+    /// Return the voxels that are at least partially visible once the Scene
+    /// is rendered, that is the ones that are not hidden by the voxels in
+    /// front of them, sorted from the closest to the farthest.
+    ///
+    /// It's useful to only decorate the visible surfaces of a Scene.
+    pub fn visible_voxels(&self) -> Vec<Voxel> {
+        renderer::visible_voxels(self)
+    }
+
     /// Add the given voxel to the scene.
     pub fn add(&mut self, x: i32, y: i32, z: i32) {
-        if self.add {
-            self.voxels.add(x, y, z);
-        } else {
-            self.voxels.remove(x, y, z);
+        if !self.add {
+            self.remove(x, y, z);
+            return;
+        }
+
+        if let Some(changes) = &mut self.changes {
+            if !self.voxels.is_set(x, y, z) {
+                changes.insert(project_ij((x, y, z)));
+            }
+        }
+        self.voxels.add(x, y, z);
+    }
+
+    /// Remove the given voxel from the scene.
+    ///
+    /// Unlike `Scene::add` the current insertion mode is ignored.
+    pub fn remove(&mut self, x: i32, y: i32, z: i32) {
+        if let Some(changes) = &mut self.changes {
+            if self.voxels.is_set(x, y, z) {
+                changes.insert(project_ij((x, y, z)));
+            }
+        }
+        self.voxels.remove(x, y, z);
+    }
+
+    /// Add all the voxels of the `other` Scene to this one.
+    ///
+    /// Unlike `Scene::add` the current insertion mode is ignored.
+    pub fn union(&mut self, other: &Scene) {
+        if let Some(changes) = &mut self.changes {
+            changes.extend(
+                other
+                    .voxels()
+                    .filter(|&(x, y, z)| !self.voxels.is_set(x, y, z))
+                    .map(project_ij),
+            );
+        }
+        self.voxels.union(&other.voxels);
+    }
+
+    /// Keep only the voxels that are also in the `other` Scene.
+    pub fn intersect(&mut self, other: &Scene) {
+        if let Some(changes) = &mut self.changes {
+            changes.extend(
+                self.voxels
+                    .iter()
+                    .filter(|&(x, y, z)| !other.is_set(x, y, z))
+                    .map(project_ij),
+            );
+        }
+        self.voxels.intersect(&other.voxels);
+    }
+
+    /// Remove all the voxels of the `other` Scene from this one.
+    ///
+    /// Unlike `Scene::add` the current insertion mode is ignored.
+    pub fn subtract(&mut self, other: &Scene) {
+        if let Some(changes) = &mut self.changes {
+            changes.extend(
+                other
+                    .voxels()
+                    .filter(|&(x, y, z)| self.voxels.is_set(x, y, z))
+                    .map(project_ij),
+            );
+        }
+        self.voxels.subtract(&other.voxels);
+    }
+
+    /// Start or stop keeping track of the cells in IJ space whose voxels are
+    /// added or removed, see `IncrementalRenderer`.
+    pub(crate) fn track_changes(&mut self, track: bool) {
+        self.changes = track.then(FxHashSet::default);
+    }
+
+    /// Return the cells in IJ space whose voxels were added or removed since
+    /// the last call to this function or None if the changes are not being
+    /// tracked.
+    pub(crate) fn take_changes(&mut self) -> Option<FxHashSet<IJ>> {
+        self.changes.as_mut().map(std::mem::take)
+    }
+
+    /// Return a new Scene with all the voxels translated by the given amount.
+    pub fn translated(&self, dx: i32, dy: i32, dz: i32) -> Scene {
+        self.mapped(|(x, y, z)| (x + dx, y + dy, z + dz))
+    }
+
+    /// Return a new Scene rotated by `n` times 90 degrees counter-clockwise
+    /// around the z axis.
+    ///
+    /// Negative values of `n` rotate the Scene clockwise.
+    pub fn rotated_z90(&self, n: i32) -> Scene {
+        match n.rem_euclid(4) {
+            0 => self.mapped(|v| v),
+            1 => self.mapped(|(x, y, z)| (-y, x, z)),
+            2 => self.mapped(|(x, y, z)| (-x, -y, z)),
+            _ => self.mapped(|(x, y, z)| (y, -x, z)),
+        }
+    }
+
+    /// Return a new Scene mirrored along the given axis, that is the
+    /// coordinate of the voxels on that axis is negated.
+    pub fn mirrored(&self, axis: Axis) -> Scene {
+        match axis {
+            Axis::X => self.mapped(|(x, y, z)| (-x, y, z)),
+            Axis::Y => self.mapped(|(x, y, z)| (x, -y, z)),
+            Axis::Z => self.mapped(|(x, y, z)| (x, y, -z)),
+        }
+    }
+
+    /// Return a new "exploded" Scene where the voxels are grouped in clusters
+    /// by the `cluster` function and each cluster is moved away from the
+    /// center of the Scene by `factor` times the distance between its center
+    /// and the center of the Scene, as in exploded assembly drawings.
+    ///
+    /// The clusters can be moved in any direction or only along an axis
+    /// depending on `mode`. Since the voxels stay on the grid, the offsets are
+    /// rounded to the closest integer.
+    pub fn exploded<K: Hash + Eq>(
+        &self,
+        cluster: impl Fn(Voxel) -> K,
+        factor: f64,
+        mode: Explode,
+    ) -> Scene {
+        let mut centers: FxHashMap<K, ((f64, f64, f64), f64)> = FxHashMap::default();
+        let (mut center, mut n) = ((0.0, 0.0, 0.0), 0.0);
+
+        for v @ (x, y, z) in self.voxels() {
+            let p = (f64::from(x), f64::from(y), f64::from(z));
+
+            let (c, cn) = centers.entry(cluster(v)).or_default();
+            *c = (c.0 + p.0, c.1 + p.1, c.2 + p.2);
+            *cn += 1.0;
+
+            center = (center.0 + p.0, center.1 + p.1, center.2 + p.2);
+            n += 1.0;
+        }
+
+        let center = (center.0 / n, center.1 / n, center.2 / n);
+        let offsets = centers
+            .into_iter()
+            .map(|(k, ((x, y, z), cn))| {
+                let d = [x / cn - center.0, y / cn - center.1, z / cn - center.2];
+                let [dx, dy, dz] = match mode {
+                    Explode::Radial => d,
+                    Explode::Along(axis) => {
+                        let mut masked = [0.0; 3];
+                        masked[axis as usize] = d[axis as usize];
+                        masked
+                    }
+                }
+                .map(|d| (d * factor).round() as i32);
+
+                (k, (dx, dy, dz))
+            })
+            .collect::<FxHashMap<_, _>>();
+
+        self.mapped(|v @ (x, y, z)| {
+            let (dx, dy, dz) = offsets[&cluster(v)];
+            (x + dx, y + dy, z + dz)
+        })
+    }
+
+    /// Return a new Scene where every voxel is the result of calling `f` on
+    /// the voxels of this Scene.
+    ///
+    /// The insertion mode of the new Scene is the same as this one.
+    pub fn mapped(&self, f: impl Fn(Voxel) -> Voxel) -> Scene {
+        let mut voxels = spatial_index::Index::new();
+        for (x, y, z) in self.voxels().map(f) {
+            voxels.add(x, y, z);
+        }
+
+        Scene {
+            voxels,
+            add: self.add,
+            changes: None,
         }
     }
 
@@ -240,33 +463,211 @@ impl Scene {
         }
     }
 
+    /// Add all the voxels inside the sphere centered at the given point with
+    /// the given radius.
+    pub fn sphere(&mut self, (x, y, z): Voxel, r: i32) {
+        for dz in -r..=r {
+            for dy in -r..=r {
+                for dx in -r..=r {
+                    if dx * dx + dy * dy + dz * dz <= r * r {
+                        self.add(x + dx, y + dy, z + dz);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Add a cylinder parallel to the z axis with the given radius and height.
+    ///
+    /// Like slabs, the cylinder starts at the given point, which is the center
+    /// of its bottom face.
+    pub fn cylinder(&mut self, (x, y, z): Voxel, r: i32, h: i32) {
+        for dz in 0..=h {
+            for dy in -r..=r {
+                for dx in -r..=r {
+                    if dx * dx + dy * dy <= r * r {
+                        self.add(x + dx, y + dy, z + dz);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Add a line from `a` to `b` using the 3D version of the [Bresenham's line
+    /// algorithm][0].
+    ///
+    /// Each voxel on the line is expanded to a sphere of radius `thickness`,
+    /// therefore a `thickness` of 0 draws a line exactly one voxel thick.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/Bresenham%27s_line_algorithm
+    pub fn line(&mut self, a: Voxel, b: Voxel, thickness: i32) {
+        for p in bresenham(a, b) {
+            self.sphere(p, thickness);
+        }
+    }
+
+    /// Add a line passing through all the given points.
+    ///
+    /// See `Scene::line` for the meaning of `thickness`.
+    pub fn polyline(&mut self, pts: &[Voxel], thickness: i32) {
+        if let [p] = pts {
+            self.sphere(*p, thickness);
+        }
+
+        for w in pts.windows(2) {
+            self.line(w[0], w[1], thickness);
+        }
+    }
+
+    /// Add a cubic [Bézier curve][0] starting at `a`, ending at `b` and with
+    /// `c1` and `c2` as control points.
+    ///
+    /// The curve is approximated by a polyline that's fine enough to not leave
+    /// gaps between voxels. See `Scene::line` for the meaning of `thickness`.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/B%C3%A9zier_curve
+    pub fn bezier(&mut self, a: Voxel, c1: Voxel, c2: Voxel, b: Voxel, thickness: i32) {
+        let to_v3 = |(x, y, z): Voxel| v3(x, y, z);
+        let (p0, p1, p2, p3) = (to_v3(a), to_v3(c1), to_v3(c2), to_v3(b));
+
+        // the length of the control polygon is always greater or equal than the
+        // length of the curve, therefore using it as the number of steps
+        // guarantees there are no holes
+        let steps = (p0.dist(p1) + p1.dist(p2) + p2.dist(p3)).ceil().max(1.0);
+
+        let mut pts: Vec<Voxel> = vec![];
+        for t in linspace(0.0, 1.0, steps as usize + 1) {
+            let u = 1.0 - t;
+            let p = p0 * u.powi(3)
+                + p1 * (3.0 * u.powi(2) * t)
+                + p2 * (3.0 * u * t.powi(2))
+                + p3 * t.powi(3);
+            let p = (p.x.round() as i32, p.y.round() as i32, p.z.round() as i32);
+
+            if pts.last() != Some(&p) {
+                pts.push(p);
+            }
+        }
+
+        if pts.last() != Some(&b) {
+            pts.push(b);
+        }
+
+        self.polyline(&pts, thickness);
+    }
+
     /// Add all the voxels that are contained in the given sdf by sampling the
-    /// sdf by the given step.
+    /// sdf at each voxel of its bounding box.
     pub fn sdf(&mut self, sdf: &Sdf) {
         let bbox = sdf.bbox();
-        let (tl, br) = (bbox.min(), bbox.max());
-
-        let tl = tl.floor();
-
-        // ray march each layer so that we can quickly jump over the outside pixels
-        for z in arange(tl.z, br.z, 1.0) {
-            for y in arange(tl.y, br.y, 1.0) {
-                let mut x = tl.x;
-                while x <= br.x {
-                    let d = sdf.dist(&v3(x, y, z));
-                    if d <= 0.1 {
-                        self.add(x.round() as i32, y.round() as i32, z.round() as i32);
+        let (tl, br) = (bbox.min().floor(), bbox.max());
+
+        // ray march all the rows of a layer at once so that we can quickly jump
+        // over the outside voxels while still taking advantage of the batched
+        // evaluation of the sdf
+        let mut rows = vec![];
+        let mut dists = vec![];
+        for z in arange_inclusive(tl.z, br.z, 1.0) {
+            rows.clear();
+            rows.extend(arange_inclusive(tl.y, br.y, 1.0).map(|y| v3(tl.x, y, z)));
+
+            while !rows.is_empty() {
+                dists.resize(rows.len(), 0.0);
+                sdf.dist_many(&rows, &mut dists);
+
+                for (p, d) in rows.iter_mut().zip(&dists) {
+                    if *d <= 0.1 {
+                        self.add(p.x.round() as i32, p.y.round() as i32, p.z.round() as i32);
                     }
 
-                    x += f64::max(d.round(), 1.0);
+                    p.x += f64::max(d.round(), 1.0);
                 }
+
+                rows.retain(|p| p.x <= br.x);
+            }
+        }
+    }
+
+    /// Add all the voxels that lie on the surface of the given triangle.
+    ///
+    /// Only the surface is voxelized, therefore a closed mesh is turned into a
+    /// hollow shell which is enough to render it.
+    pub fn triangle(&mut self, t: &Triangle) {
+        // sample the triangle finely enough to not skip any voxel, the last
+        // samples lie on the vertices and the edge between b and c
+        let n = (t.a.dist(t.b).max(t.b.dist(t.c)).max(t.c.dist(t.a)) * 2.0)
+            .ceil()
+            .max(1.0) as u32;
+
+        let (ab, ac) = ((t.b - t.a) / f64::from(n), (t.c - t.a) / f64::from(n));
+        for i in 0..=n {
+            for j in 0..=n - i {
+                let p = t.a + ab * f64::from(i) + ac * f64::from(j);
+                self.add(p.x.round() as i32, p.y.round() as i32, p.z.round() as i32);
             }
         }
     }
 }
 
+impl Clone for Scene {
+    /// Clone the voxels and the insertion mode of the Scene, but not its
+    /// changes since the copy is not the Scene of an `IncrementalRenderer`.
+    fn clone(&self) -> Self {
+        Self {
+            voxels: self.voxels.clone(),
+            add: self.add,
+            changes: None,
+        }
+    }
+}
+
 impl Default for Scene {
     fn default() -> Self {
         Self::new()
     }
 }
+
+/// Iterator over the voxels on the line from `a` to `b` (both included) as
+/// calculated by the 3D Bresenham's line algorithm.
+fn bresenham(a: Voxel, b: Voxel) -> impl Iterator<Item = Voxel> {
+    let d = [(b.0 - a.0).abs(), (b.1 - a.1).abs(), (b.2 - a.2).abs()];
+    let s = [
+        (b.0 - a.0).signum(),
+        (b.1 - a.1).signum(),
+        (b.2 - a.2).signum(),
+    ];
+
+    // always step along the driving axis, that is the one with the biggest
+    // change, and use the error terms to know when to step along the others
+    let driving = if d[0] >= d[1] && d[0] >= d[2] {
+        0
+    } else if d[1] >= d[2] {
+        1
+    } else {
+        2
+    };
+    let (o1, o2) = ((driving + 1) % 3, (driving + 2) % 3);
+
+    let mut p = [a.0, a.1, a.2];
+    let mut e1 = 2 * d[o1] - d[driving];
+    let mut e2 = 2 * d[o2] - d[driving];
+
+    (0..=d[driving]).map(move |_| {
+        let v = (p[0], p[1], p[2]);
+
+        if e1 >= 0 {
+            p[o1] += s[o1];
+            e1 -= 2 * d[driving];
+        }
+        if e2 >= 0 {
+            p[o2] += s[o2];
+            e2 -= 2 * d[driving];
+        }
+
+        e1 += 2 * d[o1];
+        e2 += 2 * d[o2];
+        p[driving] += s[driving];
+
+        v
+    })
+}
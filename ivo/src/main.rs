@@ -0,0 +1,303 @@
+use std::{path::Path, process};
+
+use geo::{mesh::load_mesh, Axis, Triangle};
+use sketch_utils::{
+    args::{self, Cli},
+    opener,
+    svg::parse_color,
+};
+
+use ivo::{
+    hatch_triangles, rasterize, render_hidden_outlines, render_outlines, render_silhouette,
+    render_triangles,
+    scene_file::{self, FillColors, FitMode, RenderMode, RenderOptions, SceneFile},
+    vox, Line, Orientation, RasterSettings, Scene,
+};
+
+struct Options {
+    input: String,
+    output: String,
+    open: bool,
+    resolution: f64,
+    scale: Option<f64>,
+    rotate: i32,
+    mirror: Option<Axis>,
+    style: Option<RenderMode>,
+    width: Option<f64>,
+    height: Option<f64>,
+    margin: Option<f64>,
+    fit: Option<FitMode>,
+    stroke_width: Option<f64>,
+    hatch_spacing: Option<f64>,
+}
+
+fn main() {
+    let cli = Cli::new(
+        "ivo",
+        "Render a scene file, a .vox model or a voxelized mesh to .svg or .png, or save it as .vox.",
+    )
+    .with_positional(
+        "INPUT",
+        "scene.toml, scene.json, scene.vox or a stl, obj, off, gltf or glb mesh",
+    )
+    .with_option("--resolution", "N", "voxels along the longest side of a mesh, 64 by default")
+    .with_option("--scale", "F", "voxels per mesh unit, overrides --resolution")
+    .with_option("--rotate", "N", "rotate the scene by N * 90 degrees around the z axis")
+    .with_option("--mirror", "AXIS", "mirror the scene along the x, y or z axis")
+    .with_option("--style", "STYLE", "outlines, triangles, hatching, silhouette or construction")
+    .with_option("--margin", "F", "empty space around the drawing")
+    .with_option("--fit", "FIT", "contain, cover or a fixed scale factor")
+    .with_option("--stroke-width", "F", "width of the lines")
+    .with_option("--hatch-spacing", "F", "distance between the hatching lines");
+
+    let args = parse_args(&cli.parse()).unwrap_or_else(|e| {
+        eprintln!("{e}\n\n{}", cli.usage());
+        process::exit(2);
+    });
+
+    let SceneFile {
+        mut scene,
+        mut render,
+    } = load(&args).unwrap_or_else(|e| {
+        eprintln!("cannot load {}: {e}", args.input);
+        process::exit(1);
+    });
+
+    if args.rotate != 0 {
+        scene = scene.rotated_z90(args.rotate);
+    }
+    if let Some(axis) = args.mirror {
+        scene = scene.mirrored(axis);
+    }
+
+    if let Some(style) = args.style {
+        render.mode = style;
+    }
+    if let Some(width) = args.width {
+        render.width = width;
+    }
+    if let Some(height) = args.height {
+        render.height = height;
+    }
+    if let Some(margin) = args.margin {
+        render.margin = margin;
+    }
+    if let Some(fit) = args.fit {
+        render.fit = fit;
+    }
+    if let Some(stroke_width) = args.stroke_width {
+        render.stroke_width = stroke_width;
+    }
+    if let Some(hatch_spacing) = args.hatch_spacing {
+        render.hatch_spacing = hatch_spacing;
+    }
+    if render.mode == RenderMode::Triangles && render.fill == FillColors::default() {
+        render.fill = FillColors {
+            top: Some("white".to_string()),
+            left: Some("gray".to_string()),
+            right: Some("black".to_string()),
+        };
+    }
+
+    let scene = SceneFile { scene, render };
+    let res = if args.output.ends_with(".png") {
+        save_png(&scene, &args.output)
+    } else if args.output.ends_with(".vox") {
+        vox::save(&args.output, &scene.scene)
+    } else {
+        scene.render_svg(&args.output)
+    };
+
+    if let Err(e) = res {
+        eprintln!("cannot save {}: {e}", args.output);
+        process::exit(1);
+    }
+
+    if args.open {
+        if let Err(e) = opener::open(&args.output) {
+            eprintln!("cannot open {}: {e}", args.output);
+            process::exit(1);
+        }
+    }
+}
+
+/// Parse the ivo specific options on top of the common ones, the output is
+/// saved next to the input as .svg by default.
+fn parse_args(args: &args::Args) -> Result<Options, String> {
+    let option = |name| args.option::<String>(name).map_err(|e| e.to_string());
+    let number = |name| args.option::<f64>(name).map_err(|e| e.to_string());
+
+    let input: String = args
+        .positional(0)
+        .map_err(|e| e.to_string())?
+        .ok_or("missing input file")?;
+    let output = args.output.clone().unwrap_or_else(|| {
+        Path::new(&input)
+            .with_extension("svg")
+            .to_string_lossy()
+            .into_owned()
+    });
+
+    let resolution = number("--resolution")?.unwrap_or(64.0);
+    let scale = number("--scale")?;
+    if resolution <= 0.0 || scale.is_some_and(|s| s <= 0.0) {
+        return Err("the resolution and the scale must be positive".to_string());
+    }
+
+    let mirror = match option("--mirror")?.as_deref() {
+        None => None,
+        Some("x") => Some(Axis::X),
+        Some("y") => Some(Axis::Y),
+        Some("z") => Some(Axis::Z),
+        Some(a) => return Err(format!("unknown axis {a}")),
+    };
+
+    let style = match option("--style")?.as_deref() {
+        None => None,
+        Some("outlines") => Some(RenderMode::Outlines),
+        Some("triangles") => Some(RenderMode::Triangles),
+        Some("hatching") => Some(RenderMode::Hatching),
+        Some("silhouette") => Some(RenderMode::Silhouette),
+        Some("construction") => Some(RenderMode::Construction),
+        Some(s) => return Err(format!("unknown style {s}")),
+    };
+
+    let fit = match option("--fit")?.as_deref() {
+        None => None,
+        Some("contain") => Some(FitMode::Contain),
+        Some("cover") => Some(FitMode::Cover),
+        Some(_) => number("--fit")?.map(FitMode::Scale),
+    };
+
+    Ok(Options {
+        input,
+        output,
+        open: args.open,
+        resolution,
+        scale,
+        rotate: args
+            .option("--rotate")
+            .map_err(|e| e.to_string())?
+            .unwrap_or(0),
+        mirror,
+        style,
+        width: args.width.map(f64::from),
+        height: args.height.map(f64::from),
+        margin: number("--margin")?,
+        fit,
+        stroke_width: number("--stroke-width")?,
+        hatch_spacing: number("--hatch-spacing")?,
+    })
+}
+
+/// Load the input either as a scene file or as a mesh to voxelize.
+fn load(args: &Options) -> Result<SceneFile, String> {
+    let ext = Path::new(&args.input)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("");
+
+    if ext == "vox" {
+        let scene = vox::load(&args.input).map_err(|e| e.to_string())?;
+        return Ok(SceneFile {
+            scene,
+            render: RenderOptions::default(),
+        });
+    }
+
+    if ext == "toml" || ext == "json" {
+        return scene_file::load(&args.input).map_err(|e| e.to_string());
+    }
+
+    let mesh = load_mesh(&args.input).map_err(|e| e.to_string())?;
+    let bbox = mesh.bbox().ok_or("empty mesh")?;
+
+    // all the triangles collapse to a single point, there's nothing to
+    // voxelize nor a size to derive the scale from
+    let d = bbox.dimensions();
+    let size = d.x.max(d.y).max(d.z);
+    if size <= 0.0 {
+        return Err("degenerate mesh, all its vertices are in the same point".to_string());
+    }
+    let scale = args.scale.unwrap_or(args.resolution / size);
+
+    let mut scene = Scene::new();
+    for t in mesh.triangles() {
+        let [a, b, c] = [t.a, t.b, t.c].map(|p| (p - bbox.min()) * scale);
+        scene.triangle(&Triangle::new(a, b, c));
+    }
+
+    Ok(SceneFile {
+        scene,
+        render: RenderOptions::default(),
+    })
+}
+
+fn save_png(scene: &SceneFile, path: &str) -> std::io::Result<()> {
+    let render = &scene.render;
+
+    let mut settings = RasterSettings::new(render.width as u32, render.height as u32)
+        .with_padding(render.padding)
+        .with_stroke(parse_color(&render.stroke).unwrap_or([0, 0, 0]));
+    if let Some(background) = render.background.as_deref().and_then(parse_color) {
+        settings = settings.with_background(background);
+    }
+
+    let (triangles, lines) = match render.mode {
+        RenderMode::Outlines => (vec![], render_outlines(&scene.scene)),
+        RenderMode::Triangles => {
+            for (orientation, fill) in [
+                (Orientation::Top, &render.fill.top),
+                (Orientation::Left, &render.fill.left),
+                (Orientation::Right, &render.fill.right),
+            ] {
+                if let Some(fill) = fill.as_deref().and_then(parse_color) {
+                    settings = settings.with_fill_color(orientation, fill);
+                }
+            }
+
+            (render_triangles(&scene.scene), vec![])
+        }
+        RenderMode::Hatching => {
+            let mut lines = render_outlines(&scene.scene);
+            lines.extend(hatch_triangles(
+                &render_triangles(&scene.scene),
+                render.hatching_spacing(),
+            ));
+            (vec![], lines)
+        }
+        RenderMode::Silhouette => (vec![], render_silhouette(&scene.scene)),
+        RenderMode::Construction => {
+            let mut lines = render_outlines(&scene.scene);
+            lines.extend(dashed(&render_hidden_outlines(&scene.scene), 0.25));
+            (vec![], lines)
+        }
+    };
+
+    rasterize(&triangles, &lines, &settings).save(path)
+}
+
+/// Split the given lines into dashes of length `dash` with gaps of the same
+/// length since the rasterizer can only draw solid lines.
+fn dashed(lines: &[Line], dash: f64) -> Vec<Line> {
+    let mut dashes = vec![];
+
+    for l in lines {
+        for w in l.windows(2) {
+            let ((x0, y0), (x1, y1)) = (w[0], w[1]);
+            let len = (x1 - x0).hypot(y1 - y0);
+            let lerp = |d: f64| {
+                let t = d.min(len) / len;
+                (x0 + (x1 - x0) * t, y0 + (y1 - y0) * t)
+            };
+
+            let mut d = 0.0;
+            while d < len {
+                dashes.push(vec![lerp(d), lerp(d + dash)]);
+                d += 2.0 * dash;
+            }
+        }
+    }
+
+    dashes
+}
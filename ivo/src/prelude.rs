@@ -0,0 +1,20 @@
+//! Glob import this module to get the voxel `Scene`, the functions rendering
+//! it to outlines or to shaded triangles and the ones saving those as SVG. The
+//! math types of `geo::prelude` come along since most scenes need a few of
+//! them, e.g. an `Sdf` to carve voxels out of.
+//!
+//! ```no_run
+//! use ivo::prelude::*;
+//!
+//! let mut scene = Scene::new();
+//! scene.sphere((0, 0, 0), 10);
+//! scene.zslab((0, 0, 10), (4, 4, 6));
+//!
+//! let lines = render_outlines(&scene);
+//! dump_outlines_svg("ball.svg", &lines, &SvgSettings::new(800.0, 800.0))?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+pub use geo::prelude::*;
+
+pub use crate::{renderer::*, Explode, Line, Scene, Voxel, IJ, XY};
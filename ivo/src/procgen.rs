@@ -0,0 +1,150 @@
+//! Helpers to procedurally generate scenes like landscapes and caves.
+//!
+//! In the same spirit of the rest of the crate, the noise implementation is
+//! written from scratch and it's a simple [Value noise][0] that is good enough
+//! for most generative voxel sketches.
+//!
+//! This module is only available with the `procgen` feature enabled.
+//!
+//! [0]: https://en.wikipedia.org/wiki/Value_noise
+
+use crate::{Scene, Voxel};
+
+/// A 3D [Value noise][0] generator with support for multiple octaves.
+///
+/// [0]: https://en.wikipedia.org/wiki/Value_noise
+#[derive(Debug, Clone)]
+pub struct ValueNoise {
+    seed: u64,
+    octaves: u32,
+}
+
+impl ValueNoise {
+    /// Create a new single octave `ValueNoise` from the given seed.
+    pub fn new(seed: u64) -> Self {
+        Self { seed, octaves: 1 }
+    }
+
+    /// Set how many octaves of noise to sum together. Each octave has double
+    /// the frequency and half the amplitude of the previous one.
+    pub fn with_octaves(mut self, octaves: u32) -> Self {
+        self.octaves = octaves.max(1);
+        self
+    }
+
+    /// Sample the noise at the given point.
+    ///
+    /// The returned value is always in [-1, 1].
+    pub fn get(&self, x: f64, y: f64, z: f64) -> f64 {
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        let mut max_amplitude = 0.0;
+
+        for o in 0..self.octaves {
+            total += self.octave(
+                x * frequency,
+                y * frequency,
+                z * frequency,
+                self.seed.wrapping_add(u64::from(o)),
+            ) * amplitude;
+
+            max_amplitude += amplitude;
+            amplitude /= 2.0;
+            frequency *= 2.0;
+        }
+
+        total / max_amplitude
+    }
+
+    fn octave(&self, x: f64, y: f64, z: f64, seed: u64) -> f64 {
+        let (x0, y0, z0) = (x.floor(), y.floor(), z.floor());
+        let (tx, ty, tz) = (smoothstep(x - x0), smoothstep(y - y0), smoothstep(z - z0));
+        let (x0, y0, z0) = (x0 as i64, y0 as i64, z0 as i64);
+
+        let lattice = |dx, dy, dz| lattice_value(x0 + dx, y0 + dy, z0 + dz, seed);
+
+        let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+
+        let c00 = lerp(lattice(0, 0, 0), lattice(1, 0, 0), tx);
+        let c10 = lerp(lattice(0, 1, 0), lattice(1, 1, 0), tx);
+        let c01 = lerp(lattice(0, 0, 1), lattice(1, 0, 1), tx);
+        let c11 = lerp(lattice(0, 1, 1), lattice(1, 1, 1), tx);
+
+        let c0 = lerp(c00, c10, ty);
+        let c1 = lerp(c01, c11, ty);
+
+        lerp(c0, c1, tz)
+    }
+}
+
+impl Scene {
+    /// Add a column of voxels for each cell of the given heightmap.
+    ///
+    /// The heightmap is a row major grid with `width` columns whose values are
+    /// multiplied by `scale` to obtain the height of the column. Each column
+    /// starts at z = 0 and cells with a negative height are skipped.
+    pub fn heightmap(&mut self, heights: &[f64], width: usize, scale: f64) {
+        assert!(width > 0);
+
+        for (i, h) in heights.iter().enumerate() {
+            let h = (h * scale).round();
+            if h < 0.0 {
+                continue;
+            }
+
+            let (x, y) = (i % width, i / width);
+            self.zslab((x as i32, y as i32, 0), (0, 0, h as i32));
+        }
+    }
+
+    /// Add all the voxels inside the bounding box from `min` to `max` where
+    /// the noise is greater than the given `threshold`.
+    ///
+    /// The voxel coordinates are multiplied by `frequency` before sampling the
+    /// noise, the lower the frequency the bigger the blobs.
+    pub fn noise_fill(
+        &mut self,
+        min: Voxel,
+        max: Voxel,
+        noise: &ValueNoise,
+        frequency: f64,
+        threshold: f64,
+    ) {
+        for z in min.2..=max.2 {
+            for y in min.1..=max.1 {
+                for x in min.0..=max.0 {
+                    let n = noise.get(
+                        f64::from(x) * frequency,
+                        f64::from(y) * frequency,
+                        f64::from(z) * frequency,
+                    );
+
+                    if n > threshold {
+                        self.add(x, y, z);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Return a pseudo random value in [-1, 1] for the given lattice point.
+fn lattice_value(x: i64, y: i64, z: i64, seed: u64) -> f64 {
+    // splitmix64 finalizer over a simple combination of the coordinates, it's
+    // not cryptographically secure, but it's more than enough for noise
+    let mut h = seed
+        ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+        ^ (z as u64).wrapping_mul(0x1656_67B1_9E37_79F9);
+
+    h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    h ^= h >> 31;
+
+    (h >> 11) as f64 / (1_u64 << 53) as f64 * 2.0 - 1.0
+}
+
+fn smoothstep(t: f64) -> f64 {
+    t * t * (3.0 - 2.0 * t)
+}
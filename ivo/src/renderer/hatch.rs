@@ -0,0 +1,67 @@
+use geo::{util::clip::ClipRegion, v2, Polyline2, Vec2};
+
+use crate::{IsoTriangle, Line, Orientation, XY};
+
+/// Fill the given triangles with parallel hatching lines spaced according to
+/// their `Orientation`.
+///
+/// Faces with a smaller spacing look darker and faces whose spacing is None
+/// are left empty. Each orientation is hatched in a different direction that
+/// follows the isometric grid and the lines of all the triangles lie on the
+/// same global grid, so that the lines of neighboring triangles line up.
+///
+/// The occluded triangles, as returned by `render_triangles_with_occlusion`,
+/// are hatched denser to darken the crevices: the spacing is halved for the
+/// fully occluded ones.
+pub fn hatch_triangles(triangles: &[IsoTriangle<XY>], spacing: [Option<f64>; 3]) -> Vec<Line> {
+    let mut lines = vec![];
+
+    for t in triangles {
+        let Some(spacing) = spacing[t.orientation as usize] else {
+            continue;
+        };
+        assert!(spacing > 0.0, "hatching spacing must be positive");
+        let spacing = spacing / (1.0 + f64::from(t.occlusion) / 8.0);
+
+        let dir = hatching_dir(t.orientation);
+        let normal = dir.perp();
+        let pts = t.pts.map(Vec2::from);
+
+        let (omin, omax) = min_max(pts.iter().map(|p| p.dot(normal)));
+        let (dmin, dmax) = min_max(pts.iter().map(|p| p.dot(dir)));
+
+        let triangle = ClipRegion::polygon(pts);
+        let mut o = (omin / spacing).ceil() * spacing;
+        while o <= omax {
+            let base = normal * o;
+            let hatch = Polyline2::from(vec![base + dir * (dmin - 1.0), base + dir * (dmax + 1.0)]);
+
+            lines.extend(
+                triangle
+                    .clip(&hatch)
+                    .into_iter()
+                    .map(|l| l.iter().map(XY::from).collect::<Line>()),
+            );
+
+            o += spacing;
+        }
+    }
+
+    lines
+}
+
+fn hatching_dir(orientation: Orientation) -> Vec2 {
+    let (s, c) = 30_f64.to_radians().sin_cos();
+
+    match orientation {
+        Orientation::Top => v2(1, 0),
+        Orientation::Left => v2(c, -s),
+        Orientation::Right => v2(0, 1),
+    }
+}
+
+fn min_max(it: impl Iterator<Item = f64>) -> (f64, f64) {
+    it.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+        (lo.min(v), hi.max(v))
+    })
+}
@@ -0,0 +1,195 @@
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{Line, Scene, Voxel, IJ, XY};
+
+use super::{
+    project_ij,
+    scene::{merge_segments, project_triangle, render_tile, render_tile_segments},
+    IsoTriangle,
+};
+
+/// A renderer that caches what it rendered so that after editing the Scene
+/// only the parts affected by the edits are rendered again.
+///
+/// The Scene is split in square tiles of `tile_size` cells in IJ space and the
+/// output of each tile is cached. The renderer keeps track of the cells whose
+/// voxels were added or removed since the last render and only the tiles close
+/// enough to them to be affected are rendered again, the others are taken from
+/// the cache.
+///
+/// Note that finding the voxels of the affected tiles still requires scanning
+/// all the voxels of the Scene, but that's way cheaper than triangulating them.
+#[derive(Debug)]
+pub struct IncrementalRenderer {
+    scene: Scene,
+    tile_size: i32,
+    triangles: TileCache<IsoTriangle<IJ>>,
+    segments: TileCache<(IJ, u8, IJ)>,
+}
+
+/// The output of each tile along with the cells that changed since the tiles
+/// were last rendered.
+///
+/// The tiles are only rendered on the first render, before that the cache is
+/// `None`.
+#[derive(Debug)]
+struct TileCache<T> {
+    tiles: Option<FxHashMap<IJ, Vec<T>>>,
+    changes: FxHashSet<IJ>,
+}
+
+impl IncrementalRenderer {
+    /// Create a renderer for the given Scene that caches the output in tiles
+    /// of `tile_size` cells.
+    pub fn new(mut scene: Scene, tile_size: i32) -> Self {
+        assert!(tile_size > 0);
+
+        scene.track_changes(true);
+
+        Self {
+            scene,
+            tile_size,
+            triangles: TileCache::new(),
+            segments: TileCache::new(),
+        }
+    }
+
+    /// The Scene being rendered.
+    pub fn scene(&self) -> &Scene {
+        &self.scene
+    }
+
+    /// The Scene being rendered, the edits made to it are picked up by the
+    /// next render.
+    ///
+    /// Replacing the whole Scene is supported too, but then the next render
+    /// renders everything again.
+    pub fn scene_mut(&mut self) -> &mut Scene {
+        &mut self.scene
+    }
+
+    /// Return the Scene being rendered dropping the cache.
+    pub fn into_scene(mut self) -> Scene {
+        self.scene.track_changes(false);
+        self.scene
+    }
+
+    /// Render the Scene into a set of IsoTriangle like `render_triangles`
+    /// does, but only render again the tiles affected by the edits made since
+    /// the last call.
+    ///
+    /// Note that the triangles are not sorted in any particular order.
+    pub fn render_triangles(&mut self) -> Vec<IsoTriangle<XY>> {
+        self.sync();
+
+        // the triangles of a tile depend on the voxels up to two cells away,
+        // see `render_tile`
+        self.triangles
+            .update(&self.scene, self.tile_size, 2, render_tile)
+            .cloned()
+            .map(project_triangle)
+            .collect()
+    }
+
+    /// Render the Scene into a set of visible lines like `render_outlines`
+    /// does, but only render again the tiles affected by the edits made since
+    /// the last call.
+    pub fn render_outlines(&mut self) -> Vec<Line> {
+        self.sync();
+
+        // the segments of a tile depend on the voxels up to three cells away,
+        // see `render_tile_segments`
+        let segments = self
+            .segments
+            .update(&self.scene, self.tile_size, 3, render_tile_segments)
+            .map(|&(start, mask, end)| ((start, mask), end))
+            .collect();
+
+        merge_segments(segments)
+    }
+
+    /// Hand the changes made to the Scene to both caches since they're
+    /// updated independently.
+    fn sync(&mut self) {
+        match self.scene.take_changes() {
+            Some(changes) => {
+                self.triangles.changes.extend(&changes);
+                self.segments.changes.extend(changes);
+            }
+            None => {
+                // the Scene was replaced through `scene_mut` with another one
+                // whose changes are not tracked, anything could have changed
+                self.scene.track_changes(true);
+                self.triangles = TileCache::new();
+                self.segments = TileCache::new();
+            }
+        }
+    }
+}
+
+impl<T> TileCache<T> {
+    fn new() -> Self {
+        Self {
+            tiles: None,
+            changes: FxHashSet::default(),
+        }
+    }
+
+    /// Render again the tiles whose output depends on the changed cells, that
+    /// is the tiles that are at most `margin` cells away from them, and return
+    /// the output of all the tiles.
+    fn update(
+        &mut self,
+        scene: &Scene,
+        tile_size: i32,
+        margin: i32,
+        render: impl Fn(&Scene, Vec<Voxel>, IJ, IJ) -> Vec<T>,
+    ) -> impl Iterator<Item = &T> {
+        // the tiles that are at most `margin` cells away from the given cell
+        let near = |(i, j): IJ| {
+            let tile = |(i, j): IJ| (i.div_euclid(tile_size), j.div_euclid(tile_size));
+            let (min, max) = (
+                tile((i - margin, j - margin)),
+                tile((i + margin, j + margin)),
+            );
+            (min.1..=max.1).flat_map(move |tj| (min.0..=max.0).map(move |ti| (ti, tj)))
+        };
+
+        // `None` means that all the tiles must be rendered
+        let dirty = self.tiles.as_mut().map(|tiles| {
+            let dirty = self
+                .changes
+                .iter()
+                .flat_map(|&c| near(c))
+                .collect::<FxHashSet<_>>();
+
+            // the tiles that end up without any voxel nearby are empty
+            for t in &dirty {
+                tiles.remove(t);
+            }
+
+            dirty
+        });
+        self.changes.clear();
+
+        let mut shards: FxHashMap<IJ, Vec<Voxel>> = FxHashMap::default();
+        if dirty.as_ref().is_none_or(|d| !d.is_empty()) {
+            for vox in scene.voxels() {
+                for t in near(project_ij(vox)) {
+                    if dirty.as_ref().is_none_or(|d| d.contains(&t)) {
+                        shards.entry(t).or_default().push(vox);
+                    }
+                }
+            }
+        }
+
+        let tiles = self.tiles.get_or_insert_with(FxHashMap::default);
+        for ((ti, tj), voxels) in shards {
+            let min = (ti * tile_size, tj * tile_size);
+            let max = (min.0 + tile_size - 1, min.1 + tile_size - 1);
+            tiles.insert((ti, tj), render(scene, voxels, min, max));
+        }
+
+        tiles.values().flatten()
+    }
+}
@@ -1,12 +1,29 @@
 use crate::{Voxel, IJ, XY};
 
+mod hatch;
+mod incremental;
 mod obj;
+mod raster;
 mod scene;
 mod svg;
 
+pub use hatch::hatch_triangles;
+pub use incremental::IncrementalRenderer;
 pub use obj::render_mesh;
-pub use scene::{render_outlines, render_triangles};
-pub use svg::{dump_outlines_svg, dump_triangles_svg, SvgSettings};
+pub use raster::{rasterize, RasterSettings};
+pub(crate) use scene::visible_voxels;
+#[cfg(feature = "parallel")]
+pub use scene::{par_render_outlines, par_render_triangles};
+pub use scene::{
+    render_hidden_outlines, render_outlines, render_outlines_tiled, render_outlines_with_nearness,
+    render_shadows, render_silhouette, render_tagged_outlines, render_triangles,
+    render_triangles_tiled, render_triangles_with_occlusion,
+};
+pub use svg::{
+    dump_animation_svg, dump_construction_svg, dump_nearness_outlines_svg, dump_outlines_svg,
+    dump_shadowed_outlines_svg, dump_tagged_outlines_svg, dump_triangles_svg, AnimationOutput,
+    DepthMapping, FillGradient, Fit, ShadowStyle, SvgSettings,
+};
 
 /// Enum over the possible orientations a Triangle can have.
 ///
@@ -23,13 +40,40 @@ pub use svg::{dump_outlines_svg, dump_triangles_svg, SvgSettings};
 ///           \ . /
 /// ```
 ///
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Orientation {
     Top = 0,
     Left,
     Right,
 }
 
+/// What a line returned by `render_tagged_outlines` is the edge of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EdgeTag {
+    /// The orientation of the face the edge belongs to. When the edge is
+    /// shared between two faces it's the one of the closest face.
+    pub orientation: Orientation,
+
+    /// What lies on either side of the edge.
+    pub kind: EdgeKind,
+}
+
+/// What an edge returned by `render_tagged_outlines` separates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    /// The edge lies on the silhouette of the Scene, that is between a face
+    /// and the empty background.
+    Silhouette = 0,
+
+    /// The edge lies between two faces with different orientations, that is
+    /// where the surface folds like the corners of a voxel.
+    Crease,
+
+    /// The edge lies between two faces with the same orientation, for example
+    /// where a face steps in front of another one further away.
+    Face,
+}
+
 /// Each Scene is rendered into a collection of Triangle to draw.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct IsoTriangle<T> {
@@ -43,10 +87,25 @@ pub struct IsoTriangle<T> {
     /// hide a segment because of its own rendering style. For example, it can
     /// hide an edge if it's shared between two neighboring voxels.
     pub visibility: [bool; 3],
+
+    /// How occluded the face this triangle belongs to is.
+    ///
+    /// It's the number of voxels, from 0 up to 8, that surround the face in
+    /// the layer right in front of it. Faces in crevices or corners will have
+    /// an higher value and can be shaded darker to give an ambient occlusion
+    /// effect.
+    ///
+    /// It's only calculated by `render_triangles_with_occlusion`, the other
+    /// renderers leave it to 0.
+    pub occlusion: u8,
+
+    /// How close the voxel this triangle belongs to is to the viewer, see
+    /// `nearness`. It's useful to fade out the farthest triangles.
+    pub nearness: i32,
 }
 
 /// Project the given Voxel in 3D space to the IJ coordinate space.
-fn project_ij((x, y, z): Voxel) -> IJ {
+pub(crate) fn project_ij((x, y, z): Voxel) -> IJ {
     (x - z, y - z)
 }
 
@@ -76,14 +135,32 @@ impl<T> IsoTriangle<T> {
             orientation,
             pts,
             visibility,
+            occlusion: 0,
+            nearness: 0,
         }
     }
 
+    /// Set how occluded the face of the triangle is, see
+    /// `IsoTriangle::occlusion`.
+    pub fn with_occlusion(mut self, occlusion: u8) -> Self {
+        self.occlusion = occlusion;
+        self
+    }
+
+    /// Set how close the voxel of the triangle is to the viewer, see
+    /// `IsoTriangle::nearness`.
+    pub fn with_nearness(mut self, nearness: i32) -> Self {
+        self.nearness = nearness;
+        self
+    }
+
     pub fn map<TT>(self, f: impl FnMut(T) -> TT) -> IsoTriangle<TT> {
         IsoTriangle {
             pts: self.pts.map(f),
             visibility: self.visibility,
             orientation: self.orientation,
+            occlusion: self.occlusion,
+            nearness: self.nearness,
         }
     }
 }
@@ -0,0 +1,137 @@
+use geo::{polyline2, util::image::Image, v2, Vec2};
+
+use crate::{IsoTriangle, Line, Orientation, XY};
+
+/// Settings to use when rasterizing the scene to an `Image`.
+pub struct RasterSettings {
+    width: u32,
+    height: u32,
+    padding: f64,
+    background: [u8; 3],
+    stroke: [u8; 3],
+    fill_colors: [Option<[u8; 3]>; 3],
+}
+
+/// Rasterize the given triangles and lines to a new RGB `Image`.
+///
+/// The triangles are filled according to their `Orientation` and then the
+/// lines are drawn on top of them. Like the SVG output, the drawing is scaled
+/// and centered to fit the image.
+pub fn rasterize(
+    triangles: &[IsoTriangle<XY>],
+    lines: &[Line],
+    settings: &RasterSettings,
+) -> Image<3> {
+    let mut img = Image::rgb(settings.width, settings.height);
+    for (_, _, px) in img.pixels_mut() {
+        px.copy_from_slice(&settings.background);
+    }
+
+    let pts = triangles
+        .iter()
+        .flat_map(|t| t.pts.iter())
+        .chain(lines.iter().flatten())
+        .copied()
+        .map(Vec2::from);
+    let Some((min, max)) = polyline2::bbox(pts) else {
+        return img;
+    };
+
+    let (min, max) = (min - settings.padding, max + settings.padding);
+    let size = max - min;
+    let (w, h) = (f64::from(settings.width), f64::from(settings.height));
+    let sf = f64::min(w / size.x, h / size.y);
+
+    let center = (min + max) / 2.0;
+    let to_image = |p: XY| (Vec2::from(p) - center) * sf + v2(w / 2.0, h / 2.0);
+
+    for t in triangles {
+        if let Some(fill) = settings.fill_colors[t.orientation as usize] {
+            fill_triangle(&mut img, t.pts.map(to_image), fill);
+        }
+    }
+
+    for l in lines {
+        for s in l.windows(2) {
+            draw_line(&mut img, to_image(s[0]), to_image(s[1]), settings.stroke);
+        }
+    }
+
+    img
+}
+
+fn fill_triangle(img: &mut Image<3>, [a, b, c]: [Vec2; 3], color: [u8; 3]) {
+    let area = (b - a).cross(c - a);
+    if area == 0.0 {
+        return;
+    }
+
+    let (min, max) = polyline2::bbox([a, b, c]).unwrap();
+    let (x0, y0) = (min.x.floor().max(0.0) as u32, min.y.floor().max(0.0) as u32);
+    let x1 = (max.x.ceil() as u32).min(img.width());
+    let y1 = (max.y.ceil() as u32).min(img.height());
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            // sample the center of the pixel and check it's on the same side
+            // of all the edges
+            let p = v2(f64::from(x) + 0.5, f64::from(y) + 0.5);
+            let inside = [(a, b), (b, c), (c, a)]
+                .into_iter()
+                .all(|(s, e)| (e - s).cross(p - s) * area.signum() >= 0.0);
+
+            if inside {
+                img.pixel_mut(x, y).copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+fn draw_line(img: &mut Image<3>, a: Vec2, b: Vec2, color: [u8; 3]) {
+    let steps = (b - a).x.abs().max((b - a).y.abs()).ceil().max(1.0);
+
+    for i in 0..=steps as u32 {
+        let p = a.lerp(b, f64::from(i) / steps);
+        if p.x < 0.0 || p.y < 0.0 {
+            continue;
+        }
+
+        let (x, y) = (p.x as u32, p.y as u32);
+        if x < img.width() && y < img.height() {
+            img.pixel_mut(x, y).copy_from_slice(&color);
+        }
+    }
+}
+
+impl RasterSettings {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            padding: 0.0,
+            background: [255, 255, 255],
+            stroke: [0, 0, 0],
+            fill_colors: [None; 3],
+        }
+    }
+
+    pub fn with_background(mut self, background: [u8; 3]) -> Self {
+        self.background = background;
+        self
+    }
+
+    pub fn with_stroke(mut self, stroke: [u8; 3]) -> Self {
+        self.stroke = stroke;
+        self
+    }
+
+    pub fn with_padding(mut self, padding: f64) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn with_fill_color(mut self, orientation: Orientation, fill: [u8; 3]) -> Self {
+        self.fill_colors[orientation as usize] = Some(fill);
+        self
+    }
+}
@@ -1,75 +1,279 @@
 use std::{cmp::Reverse, collections::hash_map::Entry};
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::{Line, Scene, Voxel, IJ, XY};
 
-use super::{nearness, project_ij, project_iso, IsoTriangle, Orientation};
+use super::{nearness, project_ij, project_iso, EdgeKind, EdgeTag, IsoTriangle, Orientation};
 
 /// Render the Scene in 3D space into a set of visible lines.
 ///
 /// Note that the lines are simplified and merged together when the endpoints
 /// between two segments match in order to reduce the amount of lines.
 pub fn render_outlines(scene: &Scene) -> Vec<Line> {
-    // store for each position the connectivity as a bitmask (1 vertical, 2
-    // u-parallel, 4 j-parallel) so that later we can use this connectivity
-    // graph to create straight lines without any duplicate segments.
-    let mut connectivity_graph: FxHashMap<IJ, u8> = FxHashMap::default();
     let mut drawn = FxHashSet::default();
+    let mut connectivity_graph = connectivity_graph(render(scene, scene.voxels(), &mut drawn));
 
-    for triangle in render(scene, &mut drawn) {
-        for i in 0..triangle.pts.len() {
-            let a = triangle.pts[i];
-            let b = triangle.pts[(i + 1) % triangle.pts.len()];
+    let mut res = vec![];
+
+    // generate the final paths by following the connections in the connectivity
+    // graph
+    for t in drawn {
+        for p in t {
+            for (mask, dir) in DIRECTIONS {
+                if let Some(end) = follow_path(&mut connectivity_graph, mask, p, dir) {
+                    res.push(project_line(p, end));
+                }
+            }
+        }
+    }
+
+    res
+}
 
+/// Render the Scene into a set of visible lines like `render_outlines` does,
+/// but also return the nearness of each line, see `IsoTriangle::nearness`.
+///
+/// Lines are only merged together if they have the same nearness, therefore
+/// there are usually more lines than the ones returned by `render_outlines`.
+pub fn render_outlines_with_nearness(scene: &Scene) -> Vec<(Line, i32)> {
+    let mut drawn = FxHashSet::default();
+
+    // the unit segments indexed by their starting point and direction along
+    // with the nearness of the closest triangle they're visible in
+    let mut segments: FxHashMap<(IJ, u8), (IJ, i32)> = FxHashMap::default();
+    for t in render(scene, scene.voxels(), &mut drawn) {
+        for i in 0..t.pts.len() {
+            if !t.visibility[i] {
+                continue;
+            }
+
+            let (a, b) = (t.pts[i], t.pts[(i + 1) % t.pts.len()]);
             let (a, b) = (a.min(b), a.max(b));
 
-            *connectivity_graph.entry(a).or_default() |= u8::from(triangle.visibility[i]) << i;
-            connectivity_graph.entry(b).or_default();
+            let (_, nearness) = segments.entry((a, 1 << i)).or_insert((b, t.nearness));
+            *nearness = (*nearness).max(t.nearness);
         }
     }
 
+    let ends = segments
+        .iter()
+        .map(|(&(_, mask), &(end, nearness))| (end, mask, nearness))
+        .collect::<FxHashSet<_>>();
+
     let mut res = vec![];
+    for (&(start, mask), &(end, nearness)) in &segments {
+        if ends.contains(&(start, mask, nearness)) {
+            continue;
+        }
 
-    let mut follow_path = |mask: u8, i, j, di, dj| {
-        let a = (i, j);
-        for d in 0.. {
-            // NOTE: *2 is because the triangles are in the
-            // "doubled-coordinates" space
-            let d = d * 2;
-
-            let b = (i + d * di, j + d * dj);
-            match connectivity_graph.get_mut(&b) {
-                Some(v) if *v & mask != 0 => {
-                    // straight line, follow along
-                    *v &= !mask;
-                }
-                _ if a == b => {
-                    // just a single point, skip line
-                    break;
-                }
-                _ => {
-                    // a termination point, break line
-                    let a = project_iso(a);
-                    let b = project_iso(b);
+        let mut end = end;
+        while let Some(&(next, n)) = segments.get(&(end, mask)) {
+            if n != nearness {
+                break;
+            }
+            end = next;
+        }
+
+        res.push((project_line(start, end), nearness));
+    }
 
-                    res.push(vec![(a.0 / 2.0, a.1 / 2.0), (b.0 / 2.0, b.1 / 2.0)]);
-                    break;
+    res
+}
+
+/// Render the Scene into a set of visible lines like `render_outlines` does,
+/// but also return what each line is the edge of, see `EdgeTag`.
+///
+/// Lines are only merged together if they have the same tag, therefore there
+/// are usually more lines than the ones returned by `render_outlines`.
+pub fn render_tagged_outlines(scene: &Scene) -> Vec<(Line, EdgeTag)> {
+    let mut drawn = FxHashSet::default();
+    let triangles = render(scene, scene.voxels(), &mut drawn).collect::<Vec<_>>();
+    let faces = edge_faces(&triangles);
+
+    // the unit segments indexed by their starting point and direction along
+    // with the tag of the closest triangle they're visible in
+    let mut segments: FxHashMap<(IJ, u8), (IJ, EdgeTag)> = FxHashMap::default();
+    for t in &triangles {
+        for (i, (a, b)) in edges(t) {
+            if !t.visibility[i] {
+                continue;
+            }
+
+            let kind = match faces[&(a, 1 << i)].1 {
+                [_, None] => EdgeKind::Silhouette,
+                [Some(o1), Some(o2)] if o1 != o2 => EdgeKind::Crease,
+                _ => EdgeKind::Face,
+            };
+
+            // triangles are rendered from the closest to the farthest
+            segments.entry((a, 1 << i)).or_insert((
+                b,
+                EdgeTag {
+                    orientation: t.orientation,
+                    kind,
+                },
+            ));
+        }
+    }
+
+    let ends = segments
+        .iter()
+        .map(|(&(_, mask), &(end, tag))| (end, mask, tag))
+        .collect::<FxHashSet<_>>();
+
+    let mut res = vec![];
+    for (&(start, mask), &(end, tag)) in &segments {
+        if ends.contains(&(start, mask, tag)) {
+            continue;
+        }
+
+        let mut end = end;
+        while let Some(&(next, t)) = segments.get(&(end, mask)) {
+            if t != tag {
+                break;
+            }
+            end = next;
+        }
+
+        res.push((project_line(start, end), tag));
+    }
+
+    res
+}
+
+/// Render only the outer silhouette of the Scene, that is the outline of the
+/// area covered by the voxels once projected, dropping all the interior
+/// edges. Holes in such area are outlined too.
+pub fn render_silhouette(scene: &Scene) -> Vec<Line> {
+    let mut drawn = FxHashSet::default();
+    let triangles = render(scene, scene.voxels(), &mut drawn).collect::<Vec<_>>();
+
+    let segments = edge_faces(&triangles)
+        .into_iter()
+        .filter(|(_, (_, faces))| faces[1].is_none())
+        .map(|(k, (end, _))| (k, end))
+        .collect();
+
+    merge_segments(segments)
+}
+
+/// Render the edges of the Scene that are hidden by the voxels in front of
+/// them, like the construction lines of technical drawings that are usually
+/// drawn dashed below the ones returned by `render_outlines`.
+///
+/// Only the edges of the top, left and right faces that are not glued to
+/// another voxel are considered. The parts of such edges that lie on a
+/// visible line are dropped since they'd be drawn over by it anyway.
+pub fn render_hidden_outlines(scene: &Scene) -> Vec<Line> {
+    let mut drawn = FxHashSet::default();
+    let mut visible_triangles = FxHashSet::default();
+    let mut visible_segments: FxHashSet<(IJ, u8)> = FxHashSet::default();
+    for (vox, t) in render_voxels(scene, scene.voxels(), &mut drawn) {
+        for (i, (a, _)) in edges(&t) {
+            if t.visibility[i] {
+                visible_segments.insert((a, 1 << i));
+            }
+        }
+        visible_triangles.insert((vox, t.pts));
+    }
+
+    // unlike `render`, which only knows about the closest voxels, the
+    // neighbors of the hidden faces can be hidden themselves
+    let voxels = scene.voxels().collect::<FxHashSet<_>>();
+
+    let mut segments = FxHashMap::default();
+    for vox @ (x, y, z) in scene.boundary_voxels() {
+        for t in triangulate(&vox, &voxels) {
+            let (dx, dy, dz) = match t.orientation {
+                Orientation::Top => (0, 0, 1),
+                Orientation::Left => (0, 1, 0),
+                Orientation::Right => (1, 0, 0),
+            };
+            if scene.is_set(x + dx, y + dy, z + dz) {
+                continue;
+            }
+
+            let t = t.map(project_ij);
+            if visible_triangles.contains(&(vox, t.pts)) {
+                continue;
+            }
+
+            for (i, (a, b)) in edges(&t) {
+                if t.visibility[i] && !visible_segments.contains(&(a, 1 << i)) {
+                    segments.insert((a, 1 << i), b);
                 }
             }
         }
-    };
+    }
 
-    // generate the final paths by following the connections in the connectivity
-    // graph
-    for t in drawn {
-        for (i, j) in t {
-            follow_path(1, i, j, 1, 1);
-            follow_path(2, i, j, 1, 0);
-            follow_path(4, i, j, 0, 1);
+    merge_segments(segments)
+}
+
+/// Render the Scene into a set of visible lines like `render_outlines` does,
+/// but process the scene in square tiles of `tile_size` cells in IJ space.
+///
+/// Only the data structures of a single tile are kept in memory at any given
+/// time which bounds the peak memory usage when rendering huge scenes, at the
+/// cost of scanning all the voxels of the scene once per tile. The lines
+/// crossing multiple tiles are merged back together at the end.
+pub fn render_outlines_tiled(scene: &Scene, tile_size: i32) -> Vec<Line> {
+    // segments in IJ space indexed by their starting point and direction
+    let mut segments: FxHashMap<(IJ, u8), IJ> = FxHashMap::default();
+
+    for (min, max) in tiles(scene, tile_size) {
+        for (start, mask, end) in render_tile_segments(scene, scene.voxels(), min, max) {
+            segments.insert((start, mask), end);
         }
     }
 
+    merge_segments(segments)
+}
+
+/// Render the Scene into a set of visible lines like `render_outlines` does,
+/// but split the work across multiple threads.
+///
+/// The scene is split in tiles in IJ space that are rendered in parallel and
+/// whose lines are then merged back together.
+#[cfg(feature = "parallel")]
+pub fn par_render_outlines(scene: &Scene) -> Vec<Line> {
+    // the segments of a tile depend on the voxels up to three cells away, see
+    // `render_tile_segments`
+    let segments = shards(scene, 3)
+        .into_par_iter()
+        .flat_map_iter(|((min, max), voxels)| render_tile_segments(scene, voxels, min, max))
+        .map(|(start, mask, end)| ((start, mask), end))
+        .collect::<Vec<_>>();
+
+    merge_segments(segments.into_iter().collect())
+}
+
+/// Merge the given collinear segments that share an endpoint, see
+/// `render_tile_segments`.
+pub(super) fn merge_segments(segments: FxHashMap<(IJ, u8), IJ>) -> Vec<Line> {
+    let ends = segments
+        .iter()
+        .map(|(&(_, mask), &end)| (end, mask))
+        .collect::<FxHashSet<_>>();
+
+    let mut res = vec![];
+    for (&(start, mask), &end) in &segments {
+        // only start from the first segment of each line and follow the
+        // segments that continue it
+        if ends.contains(&(start, mask)) {
+            continue;
+        }
+
+        let mut end = end;
+        while let Some(&next) = segments.get(&(end, mask)) {
+            end = next;
+        }
+
+        res.push(project_line(start, end));
+    }
+
     res
 }
 
@@ -81,17 +285,407 @@ pub fn render_outlines(scene: &Scene) -> Vec<Line> {
 /// check IsoTriangle::visibility to understand that.
 pub fn render_triangles(scene: &Scene) -> Vec<IsoTriangle<XY>> {
     let mut drawn = FxHashSet::default();
-    render(scene, &mut drawn)
-        .map(|t| {
-            t.map(|p| {
-                let (a, b) = project_iso(p);
-                (a / 2.0, b / 2.0)
-            })
+    render(scene, scene.voxels(), &mut drawn)
+        .map(project_triangle)
+        .collect()
+}
+
+/// Render the given Scene into a set of IsoTriangle like `render_triangles`
+/// does, but also calculate the occlusion of each triangle, see
+/// `IsoTriangle::occlusion`.
+///
+/// The occlusion requires looking up all the neighbors of each face, that's
+/// why `render_triangles` doesn't calculate it. It can be used to darken the
+/// crevices with `SvgSettings::with_occlusion_shading` or `hatch_triangles`.
+pub fn render_triangles_with_occlusion(scene: &Scene) -> Vec<IsoTriangle<XY>> {
+    let mut drawn = FxHashSet::default();
+    render_voxels(scene, scene.voxels(), &mut drawn)
+        .map(|(vox, t)| {
+            let occlusion = occlusion(scene, vox, t.orientation);
+            project_triangle(t.with_occlusion(occlusion))
         })
         .collect()
 }
 
-/// Low-level rendering of a given scene into a list of visible IsoTriangle.
+/// Render the given Scene into a set of IsoTriangle like `render_triangles`
+/// does, but process the scene in square tiles of `tile_size` cells in IJ
+/// space.
+///
+/// The triangles are lazily produced one tile at a time so that only the data
+/// structures of a single tile are kept in memory at any given time, at the
+/// cost of scanning all the voxels of the scene once per tile.
+pub fn render_triangles_tiled(
+    scene: &Scene,
+    tile_size: i32,
+) -> impl Iterator<Item = IsoTriangle<XY>> + '_ {
+    tiles(scene, tile_size)
+        .flat_map(move |(min, max)| render_tile(scene, scene.voxels(), min, max))
+        .map(project_triangle)
+}
+
+/// Render the given Scene into a set of IsoTriangle like `render_triangles`
+/// does, but split the work across multiple threads.
+///
+/// Note that the triangles are not sorted in any particular order.
+#[cfg(feature = "parallel")]
+pub fn par_render_triangles(scene: &Scene) -> Vec<IsoTriangle<XY>> {
+    // the triangles of a tile depend on the voxels up to two cells away, see
+    // `render_tile`
+    shards(scene, 2)
+        .into_par_iter()
+        .flat_map_iter(|((min, max), voxels)| render_tile(scene, voxels, min, max))
+        .map(project_triangle)
+        .collect()
+}
+
+/// Render the drop shadows cast by the voxels of the Scene when lit by a
+/// directional light going down along `(dx, dy, -1)`.
+///
+/// The shadows fall on the top faces of the voxels below and on the ground,
+/// that is the plane right below the lowest voxels. Only the visible parts of
+/// the shadows are returned, as `Orientation::Top` triangles to be filled or
+/// hatched below the outlines of the Scene.
+pub fn render_shadows(scene: &Scene, (dx, dy): (i32, i32)) -> Vec<IsoTriangle<XY>> {
+    let Some(ground) = scene.voxels().map(|(_, _, z)| z).min().map(|z| z - 1) else {
+        return vec![];
+    };
+
+    // follow the light from each voxel down to the first top face it hits,
+    // the voxels of the ground are below the scene
+    let mut receivers = FxHashSet::default();
+    for (x, y, z) in scene.boundary_voxels() {
+        for k in 1.. {
+            let (sx, sy, sz) = (x + dx * k, y + dy * k, z - k);
+
+            if sz == ground {
+                receivers.insert((sx, sy, sz));
+                break;
+            }
+
+            if scene.is_set(sx, sy, sz) {
+                if !scene.is_set(sx, sy, sz + 1) {
+                    receivers.insert((sx, sy, sz));
+                }
+                break;
+            }
+        }
+    }
+
+    // render the scene along with the ground in shadow to find out which
+    // parts of the shadows are not hidden by the voxels in front of them
+    let mut ground_voxels = Scene::new();
+    for &(x, y, z) in &receivers {
+        if z == ground {
+            ground_voxels.add(x, y, z);
+        }
+    }
+    let mut lit = scene.clone();
+    lit.union(&ground_voxels);
+
+    let mut drawn = FxHashSet::default();
+    render_voxels(&lit, lit.voxels(), &mut drawn)
+        .filter(|(v, t)| t.orientation == Orientation::Top && receivers.contains(v))
+        .map(|(_, t)| project_triangle(t))
+        .collect()
+}
+
+/// Return the voxels of the Scene that are at least partially visible once
+/// rendered, sorted from the closest to the farthest.
+pub(crate) fn visible_voxels(scene: &Scene) -> Vec<Voxel> {
+    let mut drawn = FxHashSet::default();
+
+    // the triangles of each voxel are rendered one after the other
+    let mut voxels = render_voxels(scene, scene.voxels(), &mut drawn)
+        .map(|(v, _)| v)
+        .collect::<Vec<_>>();
+    voxels.dedup();
+
+    voxels
+}
+
+/// The bitmask and direction of the lines in the connectivity graph, see
+/// `connectivity_graph`.
+const DIRECTIONS: [(u8, IJ); 3] = [(1, (1, 1)), (2, (1, 0)), (4, (0, 1))];
+
+/// Build the connectivity graph of the given triangles.
+///
+/// It stores for each position the connectivity as a bitmask (1 vertical, 2
+/// u-parallel, 4 j-parallel) so that later we can use this connectivity graph
+/// to create straight lines without any duplicate segments.
+fn connectivity_graph(triangles: impl IntoIterator<Item = IsoTriangle<IJ>>) -> FxHashMap<IJ, u8> {
+    let mut connectivity_graph: FxHashMap<IJ, u8> = FxHashMap::default();
+
+    for triangle in triangles {
+        for i in 0..triangle.pts.len() {
+            let a = triangle.pts[i];
+            let b = triangle.pts[(i + 1) % triangle.pts.len()];
+
+            let (a, b) = (a.min(b), a.max(b));
+
+            *connectivity_graph.entry(a).or_default() |= u8::from(triangle.visibility[i]) << i;
+            connectivity_graph.entry(b).or_default();
+        }
+    }
+
+    connectivity_graph
+}
+
+/// Follow the straight line starting at `(i, j)` in the given direction
+/// removing its segments from the connectivity graph.
+///
+/// Return the end point of the line, if any.
+fn follow_path(
+    connectivity_graph: &mut FxHashMap<IJ, u8>,
+    mask: u8,
+    (i, j): IJ,
+    (di, dj): IJ,
+) -> Option<IJ> {
+    let a = (i, j);
+    for d in 0.. {
+        // NOTE: *2 is because the triangles are in the
+        // "doubled-coordinates" space
+        let d = d * 2;
+
+        let b = (i + d * di, j + d * dj);
+        match connectivity_graph.get_mut(&b) {
+            Some(v) if *v & mask != 0 => {
+                // straight line, follow along
+                *v &= !mask;
+            }
+            _ if a == b => {
+                // just a single point, skip line
+                return None;
+            }
+            _ => {
+                // a termination point, break line
+                return Some(b);
+            }
+        }
+    }
+
+    unreachable!()
+}
+
+/// Return the unit edges of the given triangle along with their index, each
+/// edge is sorted so that it starts from its minimum point.
+fn edges(t: &IsoTriangle<IJ>) -> impl Iterator<Item = (usize, (IJ, IJ))> + '_ {
+    (0..t.pts.len()).map(move |i| {
+        let (a, b) = (t.pts[i], t.pts[(i + 1) % t.pts.len()]);
+        (i, (a.min(b), a.max(b)))
+    })
+}
+
+/// The unit edges indexed by their starting point and direction along with
+/// their end point and the orientations of the triangles sharing them.
+type EdgeFaces = FxHashMap<(IJ, u8), (IJ, [Option<Orientation>; 2])>;
+
+/// Return the orientations of the triangles sharing each unit edge, indexed
+/// by its starting point and direction like in `merge_segments`.
+///
+/// Since each spot in IJ space is covered by at most one triangle, an edge is
+/// shared by at most two triangles. An edge that belongs to a single triangle
+/// lies between a filled and an empty spot and therefore it's part of the
+/// silhouette.
+fn edge_faces(triangles: &[IsoTriangle<IJ>]) -> EdgeFaces {
+    let mut faces = EdgeFaces::default();
+
+    for t in triangles {
+        for (i, (a, b)) in edges(t) {
+            let (_, f) = faces.entry((a, 1 << i)).or_insert((b, [None; 2]));
+
+            if let Some(slot) = f.iter_mut().find(|o| o.is_none()) {
+                *slot = Some(t.orientation);
+            }
+        }
+    }
+
+    faces
+}
+
+/// Render the segments whose starting point is owned by a cell in the tile
+/// from `min` to `max` included, see `owner`.
+///
+/// Each segment is returned as its start point, its direction mask and its
+/// end point.
+pub(super) fn render_tile_segments(
+    scene: &Scene,
+    voxels: impl IntoIterator<Item = Voxel>,
+    min: IJ,
+    max: IJ,
+) -> Vec<(IJ, u8, IJ)> {
+    // the triangles that share a point owned by this tile can be owned by the
+    // neighboring tiles too
+    let triangles = render_tile(
+        scene,
+        voxels,
+        (min.0 - 1, min.1 - 1),
+        (max.0 + 1, max.1 + 1),
+    );
+
+    let mut connectivity_graph = connectivity_graph(triangles);
+    for (p, mask) in &mut connectivity_graph {
+        if !contains(min, max, owner(*p)) {
+            *mask = 0;
+        }
+    }
+
+    let mut pts = connectivity_graph
+        .iter()
+        .filter(|(_, mask)| **mask != 0)
+        .map(|(p, _)| *p)
+        .collect::<Vec<_>>();
+    pts.sort_unstable();
+
+    let mut res = vec![];
+    for p in pts {
+        for (mask, dir) in DIRECTIONS {
+            if let Some(end) = follow_path(&mut connectivity_graph, mask, p, dir) {
+                res.push((p, mask, end));
+            }
+        }
+    }
+
+    res
+}
+
+/// Render the triangles owned by a cell in the tile from `min` to `max`
+/// included, see `owner`.
+///
+/// `voxels` must contain at least all the voxels of the scene up to two cells
+/// away from the tile, the others are ignored.
+pub(super) fn render_tile(
+    scene: &Scene,
+    voxels: impl IntoIterator<Item = Voxel>,
+    min: IJ,
+    max: IJ,
+) -> Vec<IsoTriangle<IJ>> {
+    // each triangle is at most one cell away from the voxel that generated it
+    // and whether its edges are visible depends on the voxels one cell further
+    // away, hence consider all the voxels up to two cells away from the tile
+    let voxels = voxels.into_iter().filter(|v| {
+        let (i, j) = project_ij(*v);
+        contains((min.0 - 2, min.1 - 2), (max.0 + 2, max.1 + 2), (i, j))
+    });
+
+    let mut drawn = FxHashSet::default();
+    render(scene, voxels, &mut drawn)
+        .filter(|t| {
+            let i = t.pts.iter().map(|p| p.0).min().unwrap();
+            let j = t.pts.iter().map(|p| p.1).min().unwrap();
+            contains(min, max, owner((i, j)))
+        })
+        .collect()
+}
+
+/// Return the tiles of the given size that cover all the triangles of the
+/// Scene.
+fn tiles(scene: &Scene, tile_size: i32) -> impl Iterator<Item = (IJ, IJ)> {
+    assert!(tile_size > 0);
+
+    // an empty range of tiles if the scene is empty
+    let (min, max) = bounds(scene).unwrap_or(((0, 0), (-1, -1)));
+
+    (min.1..=max.1)
+        .step_by(tile_size as usize)
+        .flat_map(move |j| {
+            (min.0..=max.0).step_by(tile_size as usize).map(move |i| {
+                (
+                    (i, j),
+                    (
+                        i.saturating_add(tile_size - 1).min(max.0),
+                        j.saturating_add(tile_size - 1).min(max.1),
+                    ),
+                )
+            })
+        })
+}
+
+/// Split the voxels of the Scene into tiles so that each tile contains all the
+/// voxels up to `margin` cells away from it.
+///
+/// The size of the tiles is chosen so that there are enough tiles to keep all
+/// the threads busy.
+#[cfg(feature = "parallel")]
+fn shards(scene: &Scene, margin: i32) -> Vec<((IJ, IJ), Vec<Voxel>)> {
+    let Some((min, max)) = bounds(scene) else {
+        return vec![];
+    };
+
+    // a few tiles per thread to balance the work between them, but not too
+    // small otherwise the voxels in the margins are processed too many times
+    let tiles_count = rayon::current_num_threads() * 4;
+    let area = f64::from(max.0 - min.0 + 1) * f64::from(max.1 - min.1 + 1);
+    let tile_size = ((area / tiles_count as f64).sqrt().ceil() as i32).max(32);
+
+    let mut shards = tiles(scene, tile_size)
+        .map(|t| (t, vec![]))
+        .collect::<Vec<_>>();
+
+    // tiles are in row major order
+    let cols = (max.0 - min.0) / tile_size + 1;
+    let rows = (max.1 - min.1) / tile_size + 1;
+
+    for vox in scene.voxels() {
+        let (i, j) = project_ij(vox);
+
+        let tile_range = |c: i32, min: i32, n: i32| {
+            ((c - margin - min).div_euclid(tile_size).max(0))
+                ..=((c + margin - min).div_euclid(tile_size).min(n - 1))
+        };
+
+        for r in tile_range(j, min.1, rows) {
+            for c in tile_range(i, min.0, cols) {
+                shards[(r * cols + c) as usize].1.push(vox);
+            }
+        }
+    }
+
+    shards
+}
+
+/// Return the bounds in IJ space of the cells that can own a triangle of the
+/// Scene, if any.
+fn bounds(scene: &Scene) -> Option<(IJ, IJ)> {
+    let mut voxels = scene.voxels().map(project_ij);
+
+    let first = voxels.next()?;
+    let (min, max) = voxels.fold((first, first), |(min, max), (i, j)| {
+        ((min.0.min(i), min.1.min(j)), (max.0.max(i), max.1.max(j)))
+    });
+
+    // the triangles can be owned by the cells right next to the voxels
+    Some(((min.0 - 1, min.1 - 1), (max.0 + 1, max.1 + 1)))
+}
+
+/// Return the cell in IJ space that owns the given point in the
+/// "doubled-coordinates" space.
+///
+/// The owner of a triangle is the owner of the minimum of its coordinates and
+/// it's always at most one cell away from the voxel that generated it.
+fn owner((i, j): IJ) -> IJ {
+    (i.div_euclid(2), j.div_euclid(2))
+}
+
+fn contains(min: IJ, max: IJ, (i, j): IJ) -> bool {
+    (min.0..=max.0).contains(&i) && (min.1..=max.1).contains(&j)
+}
+
+fn project_line(a: IJ, b: IJ) -> Line {
+    let a = project_iso(a);
+    let b = project_iso(b);
+
+    vec![(a.0 / 2.0, a.1 / 2.0), (b.0 / 2.0, b.1 / 2.0)]
+}
+
+pub(super) fn project_triangle(t: IsoTriangle<IJ>) -> IsoTriangle<XY> {
+    t.map(|p| {
+        let (a, b) = project_iso(p);
+        (a / 2.0, b / 2.0)
+    })
+}
+
+/// Low-level rendering of the given voxels of a scene into a list of visible
+/// IsoTriangle.
 ///
 /// Note that the edges of such triangles are not always visible, be sure to
 /// check IsoTriangle::visibility to understand that.
@@ -103,13 +697,23 @@ pub fn render_triangles(scene: &Scene) -> Vec<IsoTriangle<XY>> {
 /// `drawn` contains the coordinates of the rendered triangles in IJ space.
 fn render<'a>(
     scene: &'a Scene,
+    voxels: impl IntoIterator<Item = Voxel>,
     drawn: &'a mut FxHashSet<[IJ; 3]>,
 ) -> impl Iterator<Item = IsoTriangle<IJ>> + 'a {
+    render_voxels(scene, voxels, drawn).map(|(_, t)| t)
+}
+
+/// Like `render`, but also return the voxel each triangle belongs to.
+fn render_voxels<'a>(
+    scene: &'a Scene,
+    voxels: impl IntoIterator<Item = Voxel>,
+    drawn: &'a mut FxHashSet<[IJ; 3]>,
+) -> impl Iterator<Item = (Voxel, IsoTriangle<IJ>)> + 'a {
     let mut faces = FxHashMap::default();
 
     // remove voxels that when projected end up in the same spot,
     // keep only the nearest one.
-    for vox in scene.voxels() {
+    for vox in voxels {
         match faces.entry(project_ij(vox)) {
             Entry::Vacant(v) => {
                 v.insert(vox);
@@ -133,9 +737,11 @@ fn render<'a>(
 
     voxels
         .into_iter()
-        .flat_map(move |vox| triangulate(&vox, &spatial_ix))
-        .map(|t| t.map(project_ij))
-        .filter(move |t| drawn.insert(t.pts))
+        .flat_map(move |vox| {
+            triangulate(&vox, &spatial_ix).map(|t| (vox, t.with_nearness(nearness(vox))))
+        })
+        .map(|(vox, t)| (vox, t.map(project_ij)))
+        .filter(move |(_, t)| drawn.insert(t.pts))
 }
 
 /// Triangulate the left, top and right quadrilateral faces of a given Voxel
@@ -227,3 +833,34 @@ fn triangulate(&(x, y, z): &Voxel, voxels: &FxHashSet<Voxel>) -> [IsoTriangle<Vo
         ),
     ]
 }
+
+/// Count how many voxels are set among the 8 neighbors of the face of the
+/// given voxel with the given orientation, that is in the layer right in front
+/// of it.
+///
+/// The occlusion is calculated against all the voxels in the Scene and not
+/// only the visible ones.
+fn occlusion(scene: &Scene, (x, y, z): Voxel, orientation: Orientation) -> u8 {
+    let neighbor = |a, b| match orientation {
+        Orientation::Top => (x + a, y + b, z + 1),
+        Orientation::Left => (x + a, y + 1, z + b),
+        Orientation::Right => (x + 1, y + a, z + b),
+    };
+
+    let mut n = 0;
+
+    for a in -1..=1 {
+        for b in -1..=1 {
+            if a == 0 && b == 0 {
+                continue;
+            }
+
+            let (x, y, z) = neighbor(a, b);
+            if scene.is_set(x, y, z) {
+                n += 1;
+            }
+        }
+    }
+
+    n
+}
@@ -1,9 +1,10 @@
-use std::{
-    fs::File,
-    io::{self, BufWriter, Write},
-};
+use std::{io, path::Path};
 
-use crate::{IsoTriangle, Line, Orientation, XY};
+use geo::{polyline2, Vec2};
+use sketch_utils::svg::{Style, SvgFile, SvgWriter};
+
+use super::hatch_triangles;
+use crate::{EdgeKind, EdgeTag, IsoTriangle, Line, Orientation, XY};
 
 /// Svg settings to use when serializing the scene in Svg.
 pub struct SvgSettings<'s> {
@@ -14,8 +15,85 @@ pub struct SvgSettings<'s> {
     stroke_width: f64,
     digits: usize,
     padding: f64,
+    margin: f64,
+    single_path: bool,
+    fit: Fit,
+    view_box: Option<(XY, XY)>,
+    depth_mapping: Option<DepthMapping>,
+    shadows: ShadowStyle<'s>,
+    hidden_dasharray: &'s str,
+    occlusion_shading: Option<f64>,
 
     fill_colors: [Option<&'s str>; 3],
+    fill_gradients: [Option<FillGradient<'s>>; 3],
+    orientation_strokes: [Option<(&'s str, f64)>; 3],
+    silhouette_stroke: Option<(&'s str, f64)>,
+    edge_widths: [Option<f64>; 3],
+}
+
+/// How the drawing is scaled to fit the SVG viewport.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fit {
+    /// Scale the drawing so that it's entirely visible, preserving its aspect
+    /// ratio.
+    Contain,
+
+    /// Scale the drawing so that it covers the whole viewport, preserving its
+    /// aspect ratio. Parts of the drawing might be cut.
+    Cover,
+
+    /// Scale the drawing by the given factor regardless of the viewport size.
+    Scale(f64),
+}
+
+/// How `dump_nearness_outlines_svg` styles the lines depending on how close
+/// they are to the viewer. The closest lines always use the plain style.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DepthMapping {
+    /// Thin the lines down to `min` times the stroke width for the farthest
+    /// ones.
+    StrokeWidth { min: f64 },
+
+    /// Fade the lines down to `min` opacity for the farthest ones.
+    Opacity { min: f64 },
+}
+
+/// How `dump_shadowed_outlines_svg` draws the shadows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShadowStyle<'s> {
+    /// Fill the shadows with the given color.
+    Fill(&'s str),
+
+    /// Hatch the shadows with lines `spacing` apart using the stroke color.
+    Hatch { spacing: f64 },
+}
+
+/// A linear gradient filling each triangle saved by `dump_triangles_svg` from
+/// the `from` color to the `to` color.
+///
+/// The gradient spans the bounding box of each triangle along the `angle`
+/// direction, in degrees clockwise from the +X axis of the SVG, e.g. 90 goes
+/// from the top of the triangle to its bottom.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FillGradient<'s> {
+    pub from: &'s str,
+    pub to: &'s str,
+    pub angle: f64,
+}
+
+/// Number of distinct styles used by `dump_nearness_outlines_svg`.
+const NEARNESS_LEVELS: usize = 16;
+
+/// How `dump_animation_svg` saves the frames of an animation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnimationOutput {
+    /// A single SVG showing each frame for `frame_duration` seconds in a loop
+    /// using SMIL.
+    Smil { frame_duration: f64 },
+
+    /// A SVG for each frame named after the given path with the index of the
+    /// frame appended to the file stem, e.g. `anim_0001.svg`.
+    Frames,
 }
 
 pub fn dump_outlines_svg(path: &str, lines: &[Line], settings: &SvgSettings) -> io::Result<()> {
@@ -23,27 +101,267 @@ pub fn dump_outlines_svg(path: &str, lines: &[Line], settings: &SvgSettings) ->
         path,
         settings,
         || lines.iter().flat_map(|l| l.iter().copied()),
+        |f, origin, sf| write_outlines(f, lines, origin, sf, settings),
+    )
+}
+
+/// Save the given frames, each made of a set of lines, as an animation.
+///
+/// All the frames are fitted in the viewport using the bounding box of all
+/// of them, instead of their own, so that the animation doesn't jitter.
+pub fn dump_animation_svg(
+    path: &str,
+    frames: &[Vec<Line>],
+    settings: &SvgSettings,
+    output: AnimationOutput,
+) -> io::Result<()> {
+    let pts = || frames.iter().flatten().flat_map(|l| l.iter().copied());
+
+    match output {
+        AnimationOutput::Smil { frame_duration } => {
+            svg_prelude(path, settings, pts, |f, origin, sf| {
+                let total = frame_duration * frames.len() as f64;
+
+                for (i, lines) in frames.iter().enumerate() {
+                    f.begin_frame(
+                        i as f64 * frame_duration,
+                        frame_duration,
+                        total,
+                        &Style::new(),
+                    )?;
+                    write_outlines(f, lines, origin, sf, settings)?;
+                    f.end_group()?;
+                }
+
+                Ok(())
+            })
+        }
+        AnimationOutput::Frames => {
+            let Some((origin, sf)) = fit(settings, pts()) else {
+                return Ok(());
+            };
+
+            for (i, lines) in frames.iter().enumerate() {
+                write_svg(&frame_path(path, i), settings, |f| {
+                    write_outlines(f, lines, origin, sf, settings)
+                })?;
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// Save the given lines, along with their nearness as returned by
+/// `render_outlines_with_nearness`, styling each line according to the
+/// `DepthMapping` of the settings so that the farthest lines fade out.
+///
+/// The lines are bucketed in a few levels of nearness that share the same
+/// style to keep the SVG small.
+pub fn dump_nearness_outlines_svg(
+    path: &str,
+    lines: &[(Line, i32)],
+    settings: &SvgSettings,
+) -> io::Result<()> {
+    svg_prelude(
+        path,
+        settings,
+        || lines.iter().flat_map(|(l, _)| l.iter().copied()),
         |f, origin, sf| {
-            // all the lines share the same attributes hence using a group allows to
-            // save a lot of space in the final SVG given that such attributes are not
-            // repeated.
-            writeln!(
-                f,
-                r#"<g stroke="{}" stroke-width="{}" fill="none">"#,
-                settings.stroke, settings.stroke_width,
-            )?;
-
-            for l in lines {
-                dump_polyline(f, origin, sf, l, settings.digits)?;
+            let (lo, hi) = lines.iter().fold((i32::MAX, i32::MIN), |(lo, hi), (_, n)| {
+                (lo.min(*n), hi.max(*n))
+            });
+
+            let mut levels = vec![vec![]; NEARNESS_LEVELS];
+            for (l, n) in lines {
+                let t = if hi > lo {
+                    f64::from(n - lo) / f64::from(hi - lo)
+                } else {
+                    1.0
+                };
+
+                levels[(t * (NEARNESS_LEVELS - 1) as f64).round() as usize].push(l);
             }
 
-            writeln!(f, "</g>")?;
+            for (i, lines) in levels.iter().enumerate() {
+                if lines.is_empty() {
+                    continue;
+                }
+
+                let t = i as f64 / (NEARNESS_LEVELS - 1) as f64;
+                let style = settings.outline_style();
+                let style = match settings.depth_mapping {
+                    None => style,
+                    Some(DepthMapping::StrokeWidth { min }) => {
+                        style.with_stroke_width(settings.stroke_width * (min + (1.0 - min) * t))
+                    }
+                    Some(DepthMapping::Opacity { min }) => {
+                        style.with_opacity(min + (1.0 - min) * t)
+                    }
+                };
+
+                write_lines(f, lines, origin, sf, settings, style)?;
+            }
 
             Ok(())
         },
     )
 }
 
+/// Save the given lines on top of the given shadows, as returned by
+/// `render_shadows`, in two separate layers so that they can be styled or
+/// plotted independently.
+pub fn dump_shadowed_outlines_svg(
+    path: &str,
+    lines: &[Line],
+    shadows: &[IsoTriangle<XY>],
+    settings: &SvgSettings,
+) -> io::Result<()> {
+    svg_prelude(
+        path,
+        settings,
+        || {
+            lines
+                .iter()
+                .flat_map(|l| l.iter().copied())
+                .chain(shadows.iter().flat_map(|t| t.pts.iter().copied()))
+        },
+        |f, origin, sf| {
+            f.begin_layer("shadows", &Style::new())?;
+            match settings.shadows {
+                ShadowStyle::Fill(fill) => {
+                    let style = settings.fill_style(fill);
+                    write_triangles(f, shadows.iter(), origin, sf, settings, &style)?;
+                }
+                ShadowStyle::Hatch { spacing } => {
+                    let hatches = hatch_triangles(shadows, [Some(spacing); 3]);
+                    write_outlines(f, &hatches, origin, sf, settings)?;
+                }
+            }
+            f.end_group()?;
+
+            f.begin_layer("outlines", &Style::new())?;
+            write_outlines(f, lines, origin, sf, settings)?;
+            f.end_group()
+        },
+    )
+}
+
+/// Save the given lines along with the hidden ones, as returned by
+/// `render_hidden_outlines`, drawing the latter dashed like the construction
+/// lines of technical drawings.
+///
+/// The hidden lines are saved in their own layer below the visible ones so
+/// that they can be plotted with a different pen.
+pub fn dump_construction_svg(
+    path: &str,
+    lines: &[Line],
+    hidden: &[Line],
+    settings: &SvgSettings,
+) -> io::Result<()> {
+    svg_prelude(
+        path,
+        settings,
+        || lines.iter().chain(hidden).flat_map(|l| l.iter().copied()),
+        |f, origin, sf| {
+            f.begin_layer("hidden", &Style::new())?;
+            let style = settings
+                .outline_style()
+                .with_dasharray(settings.hidden_dasharray);
+            write_lines(f, hidden, origin, sf, settings, style)?;
+            f.end_group()?;
+
+            f.begin_layer("outlines", &Style::new())?;
+            write_outlines(f, lines, origin, sf, settings)?;
+            f.end_group()
+        },
+    )
+}
+
+/// Save the given lines, along with their tags as returned by
+/// `render_tagged_outlines`, styling the silhouette and the edges of each
+/// `Orientation` with their own stroke, if any. The stroke width of each
+/// `EdgeKind`, if any, takes precedence.
+///
+/// The silhouette and the edges of each `Orientation` are saved in their own
+/// layer so that they can be plotted with a different pen.
+pub fn dump_tagged_outlines_svg(
+    path: &str,
+    lines: &[(Line, EdgeTag)],
+    settings: &SvgSettings,
+) -> io::Result<()> {
+    svg_prelude(
+        path,
+        settings,
+        || lines.iter().flat_map(|(l, _)| l.iter().copied()),
+        |f, origin, sf| {
+            let layers = [
+                ("silhouette", None),
+                ("top", Some(Orientation::Top)),
+                ("left", Some(Orientation::Left)),
+                ("right", Some(Orientation::Right)),
+            ];
+
+            for (name, orientation) in layers {
+                let kinds = match orientation {
+                    None => &[EdgeKind::Silhouette][..],
+                    Some(_) => &[EdgeKind::Crease, EdgeKind::Face][..],
+                };
+
+                let stroke = match orientation {
+                    None => settings.silhouette_stroke,
+                    Some(o) => settings.orientation_strokes[o as usize],
+                };
+                let style = match stroke {
+                    None => settings.outline_style(),
+                    Some((stroke, width)) => settings
+                        .outline_style()
+                        .with_stroke(stroke)
+                        .with_stroke_width(width),
+                };
+
+                let mut layer_open = false;
+                for &kind in kinds {
+                    let lines = lines
+                        .iter()
+                        .filter(|(_, tag)| {
+                            tag.kind == kind && orientation.is_none_or(|o| tag.orientation == o)
+                        })
+                        .map(|(l, _)| l)
+                        .collect::<Vec<_>>();
+
+                    if lines.is_empty() {
+                        continue;
+                    }
+
+                    if !layer_open {
+                        f.begin_layer(name, &Style::new())?;
+                        layer_open = true;
+                    }
+
+                    let style = match settings.edge_widths[kind as usize] {
+                        None => style.clone(),
+                        Some(w) => style.clone().with_stroke_width(w),
+                    };
+                    write_lines(f, &lines, origin, sf, settings, style)?;
+                }
+
+                if layer_open {
+                    f.end_group()?;
+                }
+            }
+
+            Ok(())
+        },
+    )
+}
+
+/// Save the given triangles filling them with the color or the gradient of
+/// their `Orientation`, the latter taking precedence.
+///
+/// If occlusion shading is enabled, see `SvgSettings::with_occlusion_shading`,
+/// the occluded triangles are darkened by a layer of black triangles on top
+/// of the fills.
 pub fn dump_triangles_svg(
     path: &str,
     triangles: &[IsoTriangle<XY>],
@@ -54,32 +372,57 @@ pub fn dump_triangles_svg(
         settings,
         || triangles.iter().flat_map(|l| l.pts.iter().copied()),
         |f, origin, sf| {
-            for orient in [Orientation::Top, Orientation::Left, Orientation::Right] {
-                let fill = settings.fill_colors[orient as usize];
-
-                writeln!(
-                    f,
-                    r#"<g stroke="{fill}" fill="{fill}" stroke-width="{}" >"#,
-                    settings.stroke_width,
-                    fill = fill.unwrap_or("none"),
+            let orientations = [
+                (Orientation::Top, "top"),
+                (Orientation::Left, "left"),
+                (Orientation::Right, "right"),
+            ];
+
+            for (orient, name) in orientations {
+                let Some(g) = settings.fill_gradients[orient as usize] else {
+                    continue;
+                };
+
+                let (dx, dy) = (g.angle.to_radians().cos(), g.angle.to_radians().sin());
+                f.linear_gradient(
+                    &format!("fill-{name}"),
+                    (0.5 - dx / 2.0, 0.5 - dy / 2.0),
+                    (0.5 + dx / 2.0, 0.5 + dy / 2.0),
+                    &[(0.0, g.from), (1.0, g.to)],
                 )?;
+            }
+
+            for (orient, name) in orientations {
+                let gradient = format!("url(#fill-{name})");
+                let fill = match settings.fill_gradients[orient as usize] {
+                    Some(_) => &gradient,
+                    None => settings.fill_colors[orient as usize].unwrap_or("none"),
+                };
+                let triangles = triangles.iter().filter(|t| t.orientation == orient);
+                let style = settings.fill_style(fill);
 
-                for t in triangles {
-                    if t.orientation != orient {
+                write_triangles(f, triangles, origin, sf, settings, &style)?;
+            }
+
+            if let Some(opacity) = settings.occlusion_shading {
+                f.begin_layer("occlusion", &Style::new())?;
+                for occlusion in 1..=8 {
+                    let mut triangles = triangles
+                        .iter()
+                        .filter(|t| t.occlusion == occlusion)
+                        .peekable();
+                    if triangles.peek().is_none() {
                         continue;
                     }
 
-                    // be sure to close the polyline otherwise glitches occur
-                    dump_polyline(
-                        f,
-                        origin,
-                        sf,
-                        &[t.pts[0], t.pts[1], t.pts[2], t.pts[0]],
-                        settings.digits,
-                    )?;
-                }
+                    let style = Style::new()
+                        .with_stroke("none")
+                        .with_fill("black")
+                        .with_opacity(opacity * f64::from(occlusion) / 8.0);
 
-                writeln!(f, "</g>")?;
+                    write_triangles(f, triangles, origin, sf, settings, &style)?;
+                }
+                f.end_group()?;
             }
 
             Ok(())
@@ -87,95 +430,167 @@ pub fn dump_triangles_svg(
     )
 }
 
+fn write_triangles<'t>(
+    f: &mut SvgWriter<SvgFile>,
+    triangles: impl Iterator<Item = &'t IsoTriangle<XY>>,
+    origin: XY,
+    sf: f64,
+    settings: &SvgSettings,
+    style: &Style,
+) -> io::Result<()> {
+    f.begin_group(style)?;
+
+    // be sure to close the polyline otherwise glitches occur
+    let outlines = triangles.map(|t| [t.pts[0], t.pts[1], t.pts[2], t.pts[0]]);
+
+    if settings.single_path {
+        f.polylines(
+            outlines.map(|pts| transform(origin, sf, &pts).collect::<Vec<_>>()),
+            &Style::new(),
+        )?;
+    } else {
+        for pts in outlines {
+            f.polyline(transform(origin, sf, &pts), &Style::new())?;
+        }
+    }
+
+    f.end_group()
+}
+
 fn svg_prelude<Pts>(
     path: &str,
     settings: &SvgSettings,
     get_pts: impl Fn() -> Pts,
-    render: impl Fn(&mut BufWriter<File>, XY, f64) -> io::Result<()>,
+    render: impl Fn(&mut SvgWriter<SvgFile>, XY, f64) -> io::Result<()>,
 ) -> io::Result<()>
 where
     Pts: IntoIterator<Item = XY>,
 {
-    let f = File::create(path)?;
-    let mut f = BufWriter::new(f);
-
-    let (mut minx, mut maxx) = (f64::INFINITY, f64::NEG_INFINITY);
-    let (mut miny, mut maxy) = (f64::INFINITY, f64::NEG_INFINITY);
-
-    for (x, y) in get_pts() {
-        minx = f64::min(minx, x);
-        maxx = f64::max(maxx, x);
-
-        miny = f64::min(miny, y);
-        maxy = f64::max(maxy, y);
-    }
+    let Some((origin, sf)) = fit(settings, get_pts()) else {
+        return SvgWriter::create(path, (0.0, 0.0, 0.0, 0.0))?
+            .finish()?
+            .close();
+    };
 
-    if minx > maxx || miny > maxy {
-        writeln!(
-            f,
-            r#"<?xml version="1.0" encoding="UTF-8"?>
-    <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 0 0">
-    </svg>"#
-        )?;
-        return Ok(());
-    }
-
-    minx -= settings.padding;
-    maxx += settings.padding;
-    miny -= settings.padding;
-    maxy += settings.padding;
-
-    let (width, height) = (maxx - minx, maxy - miny);
-
-    let sf = f64::min(settings.width / width, settings.height / height);
+    write_svg(path, settings, |f| render(f, origin, sf))
+}
 
-    writeln!(
-        f,
-        r#"<?xml version="1.0" encoding="UTF-8"?>
-<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
-        -settings.width / 2.0,
-        -settings.height / 2.0,
-        settings.width,
-        settings.height,
-    )?;
+/// Return the origin and the scale factor that fit the given points, or the
+/// view box if any, in the viewport.
+fn fit(settings: &SvgSettings, pts: impl IntoIterator<Item = XY>) -> Option<(XY, f64)> {
+    let (min, max) = match settings.view_box {
+        Some((min, max)) => (Vec2::from(min), Vec2::from(max)),
+        None => polyline2::bbox(pts.into_iter().map(Vec2::from))?,
+    };
+
+    let (min, max) = (min - settings.padding, max + settings.padding);
+    let Vec2 {
+        x: width,
+        y: height,
+    } = max - min;
+
+    let available_width = settings.width - 2.0 * settings.margin;
+    let available_height = settings.height - 2.0 * settings.margin;
+
+    // a flat drawing, e.g. a single line, can only be fitted along the other
+    // axis while a single point cannot be fitted at all
+    let sx = (width > 0.0).then(|| available_width / width);
+    let sy = (height > 0.0).then(|| available_height / height);
+
+    let sf = match (settings.fit, sx, sy) {
+        (Fit::Scale(sf), _, _) => sf,
+        (Fit::Contain, Some(sx), Some(sy)) => f64::min(sx, sy),
+        (Fit::Cover, Some(sx), Some(sy)) => f64::max(sx, sy),
+        (_, Some(sf), None) | (_, None, Some(sf)) => sf,
+        (_, None, None) => 1.0,
+    };
+
+    Some((((min + max) / 2.0).into(), sf))
+}
 
-    if let Some(background) = settings.background {
-        writeln!(
-            f,
-            r#"<rect x="{}" y="{}" width="{}" height="{}" stroke="none" fill="{background}"/>"#,
+fn write_svg(
+    path: &str,
+    settings: &SvgSettings,
+    render: impl FnOnce(&mut SvgWriter<SvgFile>) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut f = SvgWriter::create(
+        path,
+        (
             -settings.width / 2.0,
             -settings.height / 2.0,
             settings.width,
             settings.height,
+        ),
+    )?
+    .with_digits(settings.digits);
+
+    if let Some(background) = settings.background {
+        f.rect(
+            (-settings.width / 2.0, -settings.height / 2.0),
+            (settings.width, settings.height),
+            &Style::new().with_stroke("none").with_fill(background),
         )?;
     }
 
-    render(&mut f, ((minx + maxx) / 2.0, (miny + maxy) / 2.0), sf)?;
+    render(&mut f)?;
 
-    writeln!(f, "</svg>")?;
+    f.finish()?.close()
+}
 
-    Ok(())
+fn write_outlines(
+    f: &mut SvgWriter<SvgFile>,
+    lines: &[Line],
+    origin: XY,
+    sf: f64,
+    settings: &SvgSettings,
+) -> io::Result<()> {
+    write_lines(f, lines, origin, sf, settings, settings.outline_style())
 }
 
-fn dump_polyline(
-    f: &mut impl Write,
+fn write_lines<L: AsRef<[XY]>>(
+    f: &mut SvgWriter<SvgFile>,
+    lines: &[L],
     origin: XY,
     sf: f64,
-    pts: &[XY],
-    digits: usize,
+    settings: &SvgSettings,
+    style: Style,
 ) -> io::Result<()> {
-    write!(f, r#"<polyline points=""#)?;
-    for (x, y) in pts {
-        write!(
-            f,
-            "{:.digits$},{:.digits$} ",
-            (x - origin.0) * sf,
-            (y - origin.1) * sf,
-            digits = digits
+    // all the lines share the same attributes hence using a group allows to
+    // save a lot of space in the final SVG given that such attributes are not
+    // repeated.
+    f.begin_group(&style)?;
+
+    if settings.single_path {
+        f.polylines(
+            lines.iter().map(|l| transform(origin, sf, l.as_ref())),
+            &Style::new(),
         )?;
+    } else {
+        for l in lines {
+            f.polyline(transform(origin, sf, l.as_ref()), &Style::new())?;
+        }
     }
-    writeln!(f, r#"" />"#)?;
-    Ok(())
+
+    f.end_group()
+}
+
+/// Return the path of the i-th frame of an animation saved at `path`.
+fn frame_path(path: &str, i: usize) -> String {
+    let p = Path::new(path);
+    let stem = p.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+
+    let name = match p.extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!("{stem}_{i:04}.{ext}"),
+        None => format!("{stem}_{i:04}"),
+    };
+
+    p.with_file_name(name).to_string_lossy().into_owned()
+}
+
+fn transform<'a>(origin: XY, sf: f64, pts: &'a [XY]) -> impl Iterator<Item = XY> + 'a {
+    let origin = Vec2::from(origin);
+    pts.iter()
+        .map(move |&p| ((Vec2::from(p) - origin) * sf).into())
 }
 
 impl<'a> SvgSettings<'a> {
@@ -188,7 +603,19 @@ impl<'a> SvgSettings<'a> {
             stroke_width: 1.0,
             digits: 4,
             padding: 0.0,
+            margin: 0.0,
+            single_path: false,
+            fit: Fit::Contain,
+            view_box: None,
+            depth_mapping: None,
+            shadows: ShadowStyle::Fill("lightgray"),
+            hidden_dasharray: "4 4",
+            occlusion_shading: None,
             fill_colors: [None; 3],
+            fill_gradients: [None; 3],
+            orientation_strokes: [None; 3],
+            silhouette_stroke: None,
+            edge_widths: [None; 3],
         }
     }
 
@@ -212,8 +639,117 @@ impl<'a> SvgSettings<'a> {
         self
     }
 
+    /// Leave the given margin, in SVG units, on each side of the viewport.
+    pub fn with_margin(mut self, margin: f64) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    pub fn with_fit(mut self, fit: Fit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    /// Fit the given rectangle of the drawing in the viewport instead of the
+    /// bounding box of the drawing itself. Using the same view box for
+    /// multiple renders aligns them on the same canvas.
+    pub fn with_view_box(mut self, min: XY, max: XY) -> Self {
+        self.view_box = Some((min, max));
+        self
+    }
+
+    /// Write all the lines sharing the same style as a single path with
+    /// relative coordinates. This produces much smaller files for dense
+    /// scenes, but the lines cannot be edited individually anymore.
+    pub fn with_single_path(mut self, single_path: bool) -> Self {
+        self.single_path = single_path;
+        self
+    }
+
+    /// Style the lines saved by `dump_nearness_outlines_svg` according to
+    /// how close they are.
+    pub fn with_depth_mapping(mut self, mapping: DepthMapping) -> Self {
+        self.depth_mapping = Some(mapping);
+        self
+    }
+
+    /// Draw the shadows saved by `dump_shadowed_outlines_svg` with the given
+    /// style, by default they're filled in light gray.
+    pub fn with_shadows(mut self, shadows: ShadowStyle<'a>) -> Self {
+        self.shadows = shadows;
+        self
+    }
+
+    /// Dash the hidden lines saved by `dump_construction_svg` with the given
+    /// pattern of dash and gap lengths in SVG units, by default `"4 4"`.
+    pub fn with_hidden_dasharray(mut self, dasharray: &'a str) -> Self {
+        self.hidden_dasharray = dasharray;
+        self
+    }
+
     pub fn with_fill_color(mut self, orientation: Orientation, fill: &'a str) -> Self {
         self.fill_colors[orientation as usize] = Some(fill);
         self
     }
+
+    /// Fill the triangles with the given orientation saved by
+    /// `dump_triangles_svg` with a gradient instead of a flat color, e.g. to
+    /// make the top faces lighter at the back for a cheap lighting effect.
+    pub fn with_fill_gradient(
+        mut self,
+        orientation: Orientation,
+        gradient: FillGradient<'a>,
+    ) -> Self {
+        self.fill_gradients[orientation as usize] = Some(gradient);
+        self
+    }
+
+    /// Darken the triangles saved by `dump_triangles_svg` according to their
+    /// occlusion, see `render_triangles_with_occlusion`, up to the given
+    /// opacity of black for the fully occluded ones. Disabled by default.
+    pub fn with_occlusion_shading(mut self, opacity: f64) -> Self {
+        self.occlusion_shading = Some(opacity);
+        self
+    }
+
+    /// Draw the interior edges of the faces with the given orientation saved
+    /// by `dump_tagged_outlines_svg` with the given stroke and width.
+    pub fn with_orientation_stroke(
+        mut self,
+        orientation: Orientation,
+        stroke: &'a str,
+        width: f64,
+    ) -> Self {
+        self.orientation_strokes[orientation as usize] = Some((stroke, width));
+        self
+    }
+
+    /// Draw the silhouette saved by `dump_tagged_outlines_svg` with the given
+    /// stroke and width.
+    pub fn with_silhouette_stroke(mut self, stroke: &'a str, width: f64) -> Self {
+        self.silhouette_stroke = Some((stroke, width));
+        self
+    }
+
+    /// Draw the edges of the given kind saved by `dump_tagged_outlines_svg`
+    /// with the given stroke width, e.g. thick silhouettes, medium creases and
+    /// thin lines between faces like in technical illustrations.
+    pub fn with_edge_width(mut self, kind: EdgeKind, width: f64) -> Self {
+        self.edge_widths[kind as usize] = Some(width);
+        self
+    }
+
+    fn fill_style<'f>(&self, fill: &'f str) -> Style<'f> {
+        Style::new()
+            .with_stroke(fill)
+            .with_fill(fill)
+            .with_stroke_width(self.stroke_width)
+    }
+
+    fn outline_style(&self) -> Style<'a> {
+        Style::new()
+            .with_stroke(self.stroke)
+            .with_stroke_width(self.stroke_width)
+            .with_fill("none")
+    }
 }
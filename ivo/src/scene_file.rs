@@ -0,0 +1,459 @@
+//! Describe a voxel `Scene` and how to render it in a JSON or TOML file.
+//!
+//! A scene file is a list of operations applied in order to an initially empty
+//! `Scene` plus some optional rendering options.
+//!
+//! ```toml
+//! [render]
+//! mode = "triangles"
+//! width = 1920
+//! height = 1080
+//! margin = 20
+//! fit = "contain"
+//! fill = { top = "white", left = "gray", right = "black" }
+//! gradients = { top = { from = "white", to = "lightgray", angle = 90 } }
+//!
+//! [[ops]]
+//! op = "aabb"
+//! center = [0, 0, 0]
+//! half_size = [10, 10, 10]
+//!
+//! [[ops]]
+//! op = "invert"
+//!
+//! [[ops]]
+//! op = "sdf"
+//! sdf = { type = "sphere", radius = 12.0 }
+//! ```
+
+use std::{fmt, io, path::Path};
+
+use serde::Deserialize;
+use sketch_utils::loader;
+
+pub use sketch_utils::loader::Format;
+
+use geo::{sdf::SdfDesc, Axis};
+
+use crate::{
+    dump_construction_svg, dump_outlines_svg, dump_triangles_svg, hatch_triangles,
+    render_hidden_outlines, render_outlines, render_silhouette, render_triangles, FillGradient,
+    Fit, Orientation, Scene, SvgSettings, Voxel,
+};
+
+/// Result type returned by the scene file loaders.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Possible errors while loading a scene file.
+#[derive(Debug)]
+pub enum Error {
+    /// The scene file could not be read or it doesn't describe a scene.
+    Load(loader::Error),
+
+    /// An sdf has a boolean operation without any operand.
+    InvalidSdf,
+}
+
+/// A fully loaded scene file.
+#[derive(Debug)]
+pub struct SceneFile {
+    pub scene: Scene,
+    pub render: RenderOptions,
+}
+
+/// How to render a `Scene` to SVG.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RenderOptions {
+    pub mode: RenderMode,
+    pub width: f64,
+    pub height: f64,
+    pub background: Option<String>,
+    pub stroke: String,
+    pub stroke_width: f64,
+    pub padding: f64,
+
+    /// Empty space around the drawing in SVG units.
+    pub margin: f64,
+    pub fit: FitMode,
+
+    /// Rectangle of the drawing, as its min and max corners, to fit in the
+    /// viewport instead of the bounding box of the drawing.
+    pub view_box: Option<[[f64; 2]; 2]>,
+
+    pub single_path: bool,
+    pub fill: FillColors,
+
+    /// Gradients filling the triangles instead of the flat `fill` colors.
+    pub gradients: FillGradients,
+
+    /// Distance between the hatching lines of the darkest faces, only used
+    /// in `RenderMode::Hatching`.
+    pub hatch_spacing: f64,
+
+    /// Dash pattern of the hidden lines, e.g. "4 2", only used in
+    /// `RenderMode::Construction`.
+    pub hidden_dasharray: Option<String>,
+}
+
+/// Whether to render only the outlines of the `Scene`, its filled triangles,
+/// its outlines shaded with hatching lines, only its silhouette or its
+/// outlines along with the dashed hidden ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RenderMode {
+    Outlines,
+    Triangles,
+    Hatching,
+    Silhouette,
+    Construction,
+}
+
+/// How the drawing is scaled to fit the viewport, see `Fit`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FitMode {
+    Contain,
+    Cover,
+    Scale(f64),
+}
+
+/// Fill colors of the triangles by `Orientation`.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FillColors {
+    pub top: Option<String>,
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+/// Fill gradients of the triangles by `Orientation`, see `FillGradient`.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FillGradients {
+    pub top: Option<GradientDesc>,
+    pub left: Option<GradientDesc>,
+    pub right: Option<GradientDesc>,
+}
+
+/// A linear gradient between two colors, see `FillGradient`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GradientDesc {
+    pub from: String,
+    pub to: String,
+
+    /// Direction of the gradient in degrees, top to bottom by default.
+    #[serde(default = "default_gradient_angle")]
+    pub angle: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneDesc {
+    #[serde(default)]
+    render: RenderOptions,
+
+    #[serde(default)]
+    ops: Vec<Op>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case", deny_unknown_fields)]
+enum Op {
+    Aabb {
+        center: Voxel,
+        half_size: Voxel,
+    },
+    Xslab {
+        start: Voxel,
+        size: Voxel,
+    },
+    Yslab {
+        start: Voxel,
+        size: Voxel,
+    },
+    Zslab {
+        start: Voxel,
+        size: Voxel,
+    },
+    Sphere {
+        center: Voxel,
+        radius: i32,
+    },
+    Cylinder {
+        base: Voxel,
+        radius: i32,
+        height: i32,
+    },
+    Line {
+        from: Voxel,
+        to: Voxel,
+        #[serde(default)]
+        thickness: i32,
+    },
+    Polyline {
+        points: Vec<Voxel>,
+        #[serde(default)]
+        thickness: i32,
+    },
+    Bezier {
+        from: Voxel,
+        c1: Voxel,
+        c2: Voxel,
+        to: Voxel,
+        #[serde(default)]
+        thickness: i32,
+    },
+    Sdf {
+        sdf: SdfDesc,
+    },
+
+    /// switch between adding and removing voxels
+    Invert,
+
+    /// boolean operations between the current scene and the one built by
+    /// `ops`
+    Union {
+        ops: Vec<Op>,
+    },
+    Intersect {
+        ops: Vec<Op>,
+    },
+    Subtract {
+        ops: Vec<Op>,
+    },
+
+    Translate {
+        by: Voxel,
+    },
+    RotateZ90 {
+        n: i32,
+    },
+    Mirror {
+        axis: Axis,
+    },
+}
+
+/// Load the scene file at `path` guessing its format by the file extension.
+pub fn load(path: impl AsRef<Path>) -> Result<SceneFile> {
+    let desc: SceneDesc = loader::load(path)?;
+    desc.build()
+}
+
+/// Parse a scene from the given document written in the given `Format`.
+pub fn from_str(data: &str, format: Format) -> Result<SceneFile> {
+    let desc: SceneDesc = loader::from_str(data, format)?;
+    desc.build()
+}
+
+impl SceneFile {
+    /// Render the `Scene` to the SVG at `path` according to the
+    /// `RenderOptions`.
+    pub fn render_svg(&self, path: &str) -> io::Result<()> {
+        let settings = self.render.svg_settings();
+
+        match self.render.mode {
+            RenderMode::Outlines => {
+                dump_outlines_svg(path, &render_outlines(&self.scene), &settings)
+            }
+            RenderMode::Triangles => {
+                dump_triangles_svg(path, &render_triangles(&self.scene), &settings)
+            }
+            RenderMode::Hatching => {
+                let mut lines = render_outlines(&self.scene);
+                lines.extend(hatch_triangles(
+                    &render_triangles(&self.scene),
+                    self.render.hatching_spacing(),
+                ));
+
+                dump_outlines_svg(path, &lines, &settings)
+            }
+            RenderMode::Silhouette => {
+                dump_outlines_svg(path, &render_silhouette(&self.scene), &settings)
+            }
+            RenderMode::Construction => dump_construction_svg(
+                path,
+                &render_outlines(&self.scene),
+                &render_hidden_outlines(&self.scene),
+                &settings,
+            ),
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Return the `SvgSettings` corresponding to these options.
+    pub fn svg_settings(&self) -> SvgSettings<'_> {
+        let mut settings = SvgSettings::new(self.width, self.height)
+            .with_stroke(&self.stroke)
+            .with_stroke_width(self.stroke_width)
+            .with_padding(self.padding)
+            .with_margin(self.margin)
+            .with_fit(match self.fit {
+                FitMode::Contain => Fit::Contain,
+                FitMode::Cover => Fit::Cover,
+                FitMode::Scale(sf) => Fit::Scale(sf),
+            })
+            .with_single_path(self.single_path);
+
+        if let Some(background) = &self.background {
+            settings = settings.with_background(background);
+        }
+        if let Some([[x0, y0], [x1, y1]]) = self.view_box {
+            settings = settings.with_view_box((x0, y0), (x1, y1));
+        }
+        if let Some(dasharray) = &self.hidden_dasharray {
+            settings = settings.with_hidden_dasharray(dasharray);
+        }
+
+        for (orientation, fill) in [
+            (Orientation::Top, &self.fill.top),
+            (Orientation::Left, &self.fill.left),
+            (Orientation::Right, &self.fill.right),
+        ] {
+            if let Some(fill) = fill {
+                settings = settings.with_fill_color(orientation, fill);
+            }
+        }
+
+        for (orientation, gradient) in [
+            (Orientation::Top, &self.gradients.top),
+            (Orientation::Left, &self.gradients.left),
+            (Orientation::Right, &self.gradients.right),
+        ] {
+            if let Some(g) = gradient {
+                settings = settings.with_fill_gradient(
+                    orientation,
+                    FillGradient {
+                        from: &g.from,
+                        to: &g.to,
+                        angle: g.angle,
+                    },
+                );
+            }
+        }
+
+        settings
+    }
+
+    /// Return the spacing of the hatching lines by `Orientation`: the top
+    /// faces are left empty while the right faces are the darkest.
+    pub fn hatching_spacing(&self) -> [Option<f64>; 3] {
+        [
+            None,
+            Some(self.hatch_spacing * 2.0),
+            Some(self.hatch_spacing),
+        ]
+    }
+}
+
+impl SceneDesc {
+    fn build(self) -> Result<SceneFile> {
+        let mut scene = Scene::new();
+        apply(&mut scene, &self.ops)?;
+
+        Ok(SceneFile {
+            scene,
+            render: self.render,
+        })
+    }
+}
+
+fn apply(scene: &mut Scene, ops: &[Op]) -> Result<()> {
+    for op in ops {
+        match op {
+            Op::Aabb { center, half_size } => scene.aabb(*center, *half_size),
+            Op::Xslab { start, size } => scene.xslab(*start, *size),
+            Op::Yslab { start, size } => scene.yslab(*start, *size),
+            Op::Zslab { start, size } => scene.zslab(*start, *size),
+            Op::Sphere { center, radius } => scene.sphere(*center, *radius),
+            Op::Cylinder {
+                base,
+                radius,
+                height,
+            } => scene.cylinder(*base, *radius, *height),
+            Op::Line {
+                from,
+                to,
+                thickness,
+            } => scene.line(*from, *to, *thickness),
+            Op::Polyline { points, thickness } => scene.polyline(points, *thickness),
+            Op::Bezier {
+                from,
+                c1,
+                c2,
+                to,
+                thickness,
+            } => scene.bezier(*from, *c1, *c2, *to, *thickness),
+            Op::Sdf { sdf } => scene.sdf(&sdf.build().ok_or(Error::InvalidSdf)?),
+            Op::Invert => scene.invert(),
+
+            Op::Union { ops } => scene.union(&sub_scene(ops)?),
+            Op::Intersect { ops } => scene.intersect(&sub_scene(ops)?),
+            Op::Subtract { ops } => scene.subtract(&sub_scene(ops)?),
+
+            Op::Translate { by: (dx, dy, dz) } => *scene = scene.translated(*dx, *dy, *dz),
+            Op::RotateZ90 { n } => *scene = scene.rotated_z90(*n),
+            Op::Mirror { axis } => *scene = scene.mirrored(*axis),
+        }
+    }
+
+    Ok(())
+}
+
+fn sub_scene(ops: &[Op]) -> Result<Scene> {
+    let mut scene = Scene::new();
+    apply(&mut scene, ops)?;
+    Ok(scene)
+}
+
+fn default_gradient_angle() -> f64 {
+    90.0
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            mode: RenderMode::Outlines,
+            width: 1920.0,
+            height: 1080.0,
+            background: None,
+            stroke: "black".to_string(),
+            stroke_width: 1.0,
+            padding: 0.0,
+            margin: 0.0,
+            fit: FitMode::Contain,
+            view_box: None,
+            single_path: false,
+            fill: FillColors::default(),
+            gradients: FillGradients::default(),
+            hatch_spacing: 0.25,
+            hidden_dasharray: None,
+        }
+    }
+}
+
+impl From<loader::Error> for Error {
+    fn from(e: loader::Error) -> Self {
+        Error::Load(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Load(e) => write!(f, "{e}"),
+            Error::InvalidSdf => write!(f, "sdf boolean operations need at least one operand"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Load(e) => Some(e),
+            _ => None,
+        }
+    }
+}
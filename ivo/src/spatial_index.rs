@@ -21,7 +21,7 @@ use rustc_hash::FxHashSet;
 
 pub const MAX_RUNNING_VOXELS: usize = 1_000_000;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Index {
     grid: Option<Grid>,
     outside_grid: FxHashSet<Voxel>,
@@ -108,9 +108,68 @@ impl Index {
             }
         }
     }
+
+    pub fn union(&mut self, other: &Index) {
+        match (&mut self.grid, &other.grid) {
+            (Some(g), Some(og)) if g.same_bounds(og) => {
+                for (c, oc) in g.cells.iter_mut().zip(&og.cells) {
+                    *c |= oc;
+                }
+
+                for &(x, y, z) in &other.outside_grid {
+                    self.add(x, y, z);
+                }
+            }
+            _ => {
+                for (x, y, z) in other.iter() {
+                    self.add(x, y, z);
+                }
+            }
+        }
+    }
+
+    pub fn intersect(&mut self, other: &Index) {
+        match (&mut self.grid, &other.grid) {
+            (Some(g), Some(og)) if g.same_bounds(og) => {
+                for (c, oc) in g.cells.iter_mut().zip(&og.cells) {
+                    *c &= oc;
+                }
+
+                self.outside_grid.retain(|&(x, y, z)| other.is_set(x, y, z));
+            }
+            _ => {
+                let to_remove = self
+                    .iter()
+                    .filter(|&(x, y, z)| !other.is_set(x, y, z))
+                    .collect::<Vec<_>>();
+
+                for (x, y, z) in to_remove {
+                    self.remove(x, y, z);
+                }
+            }
+        }
+    }
+
+    pub fn subtract(&mut self, other: &Index) {
+        match (&mut self.grid, &other.grid) {
+            (Some(g), Some(og)) if g.same_bounds(og) => {
+                for (c, oc) in g.cells.iter_mut().zip(&og.cells) {
+                    *c &= !oc;
+                }
+
+                self.outside_grid
+                    .retain(|&(x, y, z)| !other.is_set(x, y, z));
+            }
+            _ => {
+                for (x, y, z) in other.iter() {
+                    self.remove(x, y, z);
+                }
+            }
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Grid {
     min: Voxel,
     max: Voxel,
@@ -159,6 +218,10 @@ impl Grid {
         self.cells[p] &= !(1 << bi);
     }
 
+    pub fn same_bounds(&self, other: &Grid) -> bool {
+        self.min == other.min && self.max == other.max
+    }
+
     fn index(&self, x: i32, y: i32, z: i32) -> (usize, usize) {
         let (x, y, z) = (x - self.min.0, y - self.min.1, z - self.min.2);
         let (x, y, z) = (x as usize, y as usize, z as usize);
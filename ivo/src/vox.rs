@@ -0,0 +1,535 @@
+//! Load and save `Scene`s in the [MagicaVoxel][0] `.vox` format.
+//!
+//! A `.vox` model cannot be larger than 256 voxels along any axis hence bigger
+//! scenes are split into chunks, each saved as its own model, that are placed
+//! in the world by a scene graph made of a transform node per model all
+//! grouped under the root.
+//!
+//! When loading, the scene graph is traversed so that the translations and
+//! rotations of the transform nodes are applied to the voxels of the models
+//! they refer to. Only the first frame of animated transforms is considered
+//! and the colors of the voxels are ignored, given that a `Scene` doesn't
+//! have any. When saving, all the voxels use the first color of an explicit
+//! palette so that they look the same in every viewer regardless of its
+//! default palette.
+//!
+//! [0]: https://github.com/ephtracy/voxel-model/blob/master/MagicaVoxel-file-format-vox.txt
+
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+};
+
+use rustc_hash::FxHashMap;
+
+use crate::{Scene, Voxel};
+
+/// Result type returned by the `.vox` loaders.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Possible errors while loading a `.vox` file.
+#[derive(Debug)]
+pub enum Error {
+    /// IO error.
+    IoError(io::Error),
+
+    /// The file is not a valid `.vox` file, with a description of what's
+    /// wrong.
+    Malformed(String),
+}
+
+/// The maximum size of a model along each axis.
+const MAX_MODEL_SIZE: i32 = 256;
+
+const VERSION: i32 = 150;
+
+/// The color of all the voxels, the first entry of the palette.
+const VOXEL_COLOR: [u8; 4] = [0xd9, 0xd9, 0xd9, 0xff];
+
+/// The number of colors in a palette.
+const PALETTE_SIZE: usize = 256;
+
+/// A 3x3 rotation matrix made of 0s and ±1s, stored by rows.
+type Rotation = [[i32; 3]; 3];
+
+const IDENTITY: Rotation = [[1, 0, 0], [0, 1, 0], [0, 0, 1]];
+
+#[derive(Debug)]
+enum Node {
+    Transform {
+        child: i32,
+        rotation: Rotation,
+        translation: Voxel,
+    },
+    Group {
+        children: Vec<i32>,
+    },
+    Shape {
+        models: Vec<i32>,
+    },
+}
+
+#[derive(Debug)]
+struct Model {
+    size: Voxel,
+    voxels: Vec<Voxel>,
+}
+
+/// Save the given `Scene` as a `.vox` file at `path`.
+pub fn save(path: &str, scene: &Scene) -> io::Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    write(&mut w, scene)?;
+    w.flush()
+}
+
+/// Write the given `Scene` in the `.vox` format.
+pub fn write(mut w: impl Write, scene: &Scene) -> io::Result<()> {
+    // split the scene into chunks aligned to the minimum corner of the scene
+    // so that small scenes fit in a single model
+    let min = scene
+        .voxels()
+        .reduce(|(ax, ay, az), (x, y, z)| (ax.min(x), ay.min(y), az.min(z)))
+        .unwrap_or((0, 0, 0));
+
+    let mut chunks = FxHashMap::<Voxel, Vec<Voxel>>::default();
+    for (x, y, z) in scene.voxels() {
+        let chunk = (
+            (x - min.0) / MAX_MODEL_SIZE,
+            (y - min.1) / MAX_MODEL_SIZE,
+            (z - min.2) / MAX_MODEL_SIZE,
+        );
+        chunks.entry(chunk).or_default().push((x, y, z));
+    }
+
+    let mut chunks = chunks.into_values().collect::<Vec<_>>();
+    for chunk in &mut chunks {
+        chunk.sort_unstable();
+    }
+    chunks.sort_unstable();
+
+    // an empty scene is saved as a single 1x1x1 empty model because all the
+    // readers expect at least one model
+    if chunks.is_empty() {
+        chunks.push(vec![]);
+    }
+
+    let mut content = vec![];
+    let mut placements = vec![];
+
+    for voxels in &chunks {
+        let cmin = voxels
+            .iter()
+            .copied()
+            .reduce(|(ax, ay, az), (x, y, z)| (ax.min(x), ay.min(y), az.min(z)))
+            .unwrap_or((0, 0, 0));
+        let cmax = voxels
+            .iter()
+            .copied()
+            .reduce(|(ax, ay, az), (x, y, z)| (ax.max(x), ay.max(y), az.max(z)))
+            .unwrap_or((0, 0, 0));
+        let size = (
+            cmax.0 - cmin.0 + 1,
+            cmax.1 - cmin.1 + 1,
+            cmax.2 - cmin.2 + 1,
+        );
+
+        let mut data = vec![];
+        push_i32(&mut data, size.0);
+        push_i32(&mut data, size.1);
+        push_i32(&mut data, size.2);
+        chunk(&mut content, b"SIZE", &data, &[]);
+
+        data.clear();
+        push_i32(&mut data, voxels.len() as i32);
+        for (x, y, z) in voxels {
+            // every voxel uses the first color of the palette
+            data.extend([x - cmin.0, y - cmin.1, z - cmin.2].map(|c| c as u8));
+            data.push(1);
+        }
+        chunk(&mut content, b"XYZI", &data, &[]);
+
+        // models are centered on their translation
+        placements.push((
+            cmin.0 + size.0 / 2,
+            cmin.1 + size.1 / 2,
+            cmin.2 + size.2 / 2,
+        ));
+    }
+
+    // the scene graph is a root transform pointing to a group containing a
+    // transform and a shape node per model
+    let mut data = vec![];
+    transform_node(&mut data, 0, 1, -1, None);
+    chunk(&mut content, b"nTRN", &data, &[]);
+
+    data.clear();
+    push_i32(&mut data, 1);
+    push_dict(&mut data, &[]);
+    push_i32(&mut data, placements.len() as i32);
+    for i in 0..placements.len() as i32 {
+        push_i32(&mut data, 2 + 2 * i);
+    }
+    chunk(&mut content, b"nGRP", &data, &[]);
+
+    for (i, t) in placements.into_iter().enumerate() {
+        let id = 2 + 2 * i as i32;
+
+        data.clear();
+        transform_node(&mut data, id, id + 1, 0, Some(t));
+        chunk(&mut content, b"nTRN", &data, &[]);
+
+        data.clear();
+        push_i32(&mut data, id + 1);
+        push_dict(&mut data, &[]);
+        push_i32(&mut data, 1);
+        push_i32(&mut data, i as i32);
+        push_dict(&mut data, &[]);
+        chunk(&mut content, b"nSHP", &data, &[]);
+    }
+
+    // the remaining colors are unused, just leave them black
+    data.clear();
+    data.extend_from_slice(&VOXEL_COLOR);
+    for _ in 1..PALETTE_SIZE {
+        data.extend_from_slice(&[0, 0, 0, 0xff]);
+    }
+    chunk(&mut content, b"RGBA", &data, &[]);
+
+    let mut out = b"VOX ".to_vec();
+    push_i32(&mut out, VERSION);
+    chunk(&mut out, b"MAIN", &[], &content);
+
+    w.write_all(&out)
+}
+
+/// Load the `Scene` saved in the `.vox` file at `path`.
+pub fn load(path: &str) -> Result<Scene> {
+    read(BufReader::new(File::open(path)?))
+}
+
+/// Read a `Scene` in the `.vox` format.
+pub fn read(mut r: impl Read) -> Result<Scene> {
+    let mut data = vec![];
+    r.read_to_end(&mut data)?;
+
+    let mut p = Parser {
+        data: &data,
+        pos: 0,
+    };
+    if p.bytes(4)? != b"VOX " {
+        return Err(Error::Malformed("missing VOX magic number".to_string()));
+    }
+    let _version = p.i32()?;
+
+    let (id, _, children) = p.chunk()?;
+    if id != b"MAIN" {
+        return Err(Error::Malformed("missing MAIN chunk".to_string()));
+    }
+
+    let mut models = vec![];
+    let mut size = None;
+    let mut nodes = FxHashMap::default();
+
+    let mut p = Parser {
+        data: children,
+        pos: 0,
+    };
+    while !p.is_empty() {
+        let (id, content, _) = p.chunk()?;
+        let mut c = Parser {
+            data: content,
+            pos: 0,
+        };
+
+        match id {
+            b"SIZE" => size = Some((c.i32()?, c.i32()?, c.i32()?)),
+            b"XYZI" => {
+                let size = size
+                    .take()
+                    .ok_or_else(|| Error::Malformed("XYZI chunk without SIZE".to_string()))?;
+
+                let n = c.i32()?;
+                let mut voxels = Vec::with_capacity((n.max(0) as usize).min(content.len() / 4));
+                for _ in 0..n {
+                    let v = c.bytes(4)?;
+                    voxels.push((i32::from(v[0]), i32::from(v[1]), i32::from(v[2])));
+                }
+
+                models.push(Model { size, voxels });
+            }
+            b"nTRN" => {
+                let id = c.i32()?;
+                let _attributes = c.dict()?;
+                let child = c.i32()?;
+                let _reserved = c.i32()?;
+                let _layer = c.i32()?;
+
+                let frames = c.i32()?;
+                let mut rotation = IDENTITY;
+                let mut translation = (0, 0, 0);
+                if frames > 0 {
+                    let frame = c.dict()?;
+                    for (k, v) in frame {
+                        match k.as_str() {
+                            "_t" => translation = parse_translation(&v)?,
+                            "_r" => rotation = parse_rotation(&v)?,
+                            _ => {}
+                        }
+                    }
+                }
+
+                nodes.insert(
+                    id,
+                    Node::Transform {
+                        child,
+                        rotation,
+                        translation,
+                    },
+                );
+            }
+            b"nGRP" => {
+                let id = c.i32()?;
+                let _attributes = c.dict()?;
+                let n = c.i32()?;
+                let children = (0..n).map(|_| c.i32()).collect::<Result<Vec<_>>>()?;
+
+                nodes.insert(id, Node::Group { children });
+            }
+            b"nSHP" => {
+                let id = c.i32()?;
+                let _attributes = c.dict()?;
+                let n = c.i32()?;
+                let mut shape_models = vec![];
+                for _ in 0..n {
+                    shape_models.push(c.i32()?);
+                    let _attributes = c.dict()?;
+                }
+
+                nodes.insert(
+                    id,
+                    Node::Shape {
+                        models: shape_models,
+                    },
+                );
+            }
+            b"RGBA" => {
+                if content.len() != PALETTE_SIZE * 4 {
+                    return Err(Error::Malformed("invalid palette".to_string()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut scene = Scene::new();
+
+    // files without a scene graph just have the models at the origin
+    if nodes.is_empty() {
+        for m in &models {
+            for &(x, y, z) in &m.voxels {
+                scene.add(x, y, z);
+            }
+        }
+        return Ok(scene);
+    }
+
+    let mut stack = vec![(0, IDENTITY, (0, 0, 0), 0)];
+    while let Some((id, rotation, translation, depth)) = stack.pop() {
+        // a path longer than the number of nodes means there's a cycle in the
+        // scene graph
+        if depth > nodes.len() {
+            return Err(Error::Malformed("cycle in the scene graph".to_string()));
+        }
+
+        let node = nodes
+            .get(&id)
+            .ok_or_else(|| Error::Malformed(format!("missing node {id}")))?;
+
+        match node {
+            Node::Transform {
+                child,
+                rotation: r,
+                translation: t,
+            } => {
+                let (tx, ty, tz) = rotate(&rotation, *t);
+                stack.push((
+                    *child,
+                    mul(&rotation, r),
+                    (translation.0 + tx, translation.1 + ty, translation.2 + tz),
+                    depth + 1,
+                ));
+            }
+            Node::Group { children } => {
+                stack.extend(
+                    children
+                        .iter()
+                        .map(|c| (*c, rotation, translation, depth + 1)),
+                );
+            }
+            Node::Shape { models: ids } => {
+                for &i in ids {
+                    let m = usize::try_from(i)
+                        .ok()
+                        .and_then(|i| models.get(i))
+                        .ok_or_else(|| Error::Malformed(format!("missing model {i}")))?;
+
+                    let (cx, cy, cz) = (m.size.0 / 2, m.size.1 / 2, m.size.2 / 2);
+                    for &(x, y, z) in &m.voxels {
+                        let (x, y, z) = rotate(&rotation, (x - cx, y - cy, z - cz));
+                        scene.add(x + translation.0, y + translation.1, z + translation.2);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(scene)
+}
+
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8]> {
+        let b = self
+            .data
+            .get(self.pos..self.pos.saturating_add(n))
+            .ok_or_else(|| Error::Malformed("unexpected end of file".to_string()))?;
+
+        self.pos += n;
+        Ok(b)
+    }
+
+    fn i32(&mut self) -> Result<i32> {
+        let b = self.bytes(4)?;
+        Ok(i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn len(&mut self) -> Result<usize> {
+        usize::try_from(self.i32()?).map_err(|_| Error::Malformed("negative length".to_string()))
+    }
+
+    /// Parse a chunk returning its id, content and children.
+    fn chunk(&mut self) -> Result<(&'a [u8], &'a [u8], &'a [u8])> {
+        let id = self.bytes(4)?;
+        let content_len = self.len()?;
+        let children_len = self.len()?;
+
+        Ok((id, self.bytes(content_len)?, self.bytes(children_len)?))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let n = self.len()?;
+        Ok(String::from_utf8_lossy(self.bytes(n)?).into_owned())
+    }
+
+    fn dict(&mut self) -> Result<Vec<(String, String)>> {
+        let n = self.i32()?;
+        (0..n)
+            .map(|_| Ok((self.string()?, self.string()?)))
+            .collect()
+    }
+}
+
+fn chunk(out: &mut Vec<u8>, id: &[u8; 4], content: &[u8], children: &[u8]) {
+    out.extend_from_slice(id);
+    push_i32(out, content.len() as i32);
+    push_i32(out, children.len() as i32);
+    out.extend_from_slice(content);
+    out.extend_from_slice(children);
+}
+
+fn transform_node(out: &mut Vec<u8>, id: i32, child: i32, layer: i32, t: Option<Voxel>) {
+    push_i32(out, id);
+    push_dict(out, &[]);
+    push_i32(out, child);
+    push_i32(out, -1);
+    push_i32(out, layer);
+    push_i32(out, 1);
+
+    match t {
+        None => push_dict(out, &[]),
+        Some((x, y, z)) => push_dict(out, &[("_t", &format!("{x} {y} {z}"))]),
+    }
+}
+
+fn push_i32(out: &mut Vec<u8>, v: i32) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+fn push_dict(out: &mut Vec<u8>, dict: &[(&str, &str)]) {
+    push_i32(out, dict.len() as i32);
+    for s in dict.iter().flat_map(|(k, v)| [k, v]) {
+        push_i32(out, s.len() as i32);
+        out.extend_from_slice(s.as_bytes());
+    }
+}
+
+fn parse_translation(t: &str) -> Result<Voxel> {
+    let invalid = || Error::Malformed(format!("invalid translation {t}"));
+
+    let mut it = t.split_whitespace().map(|c| c.parse::<i32>());
+    let mut next = || it.next().and_then(|c| c.ok()).ok_or_else(invalid);
+
+    Ok((next()?, next()?, next()?))
+}
+
+/// Parse a rotation packed in a byte where bits 0-1 and 2-3 are the columns of
+/// the non zero entries of the first and second row, the third row uses the
+/// remaining one, while bits 4, 5 and 6 are the signs of each row.
+fn parse_rotation(r: &str) -> Result<Rotation> {
+    let invalid = || Error::Malformed(format!("invalid rotation {r}"));
+
+    let r = r.trim().parse::<u8>().map_err(|_| invalid())?;
+    let (c0, c1) = (usize::from(r & 3), usize::from((r >> 2) & 3));
+    if c0 > 2 || c1 > 2 || c0 == c1 {
+        return Err(invalid());
+    }
+    let c2 = 3 - c0 - c1;
+
+    let mut m = [[0; 3]; 3];
+    for (row, col) in [c0, c1, c2].into_iter().enumerate() {
+        m[row][col] = if r & (1 << (4 + row)) == 0 { 1 } else { -1 };
+    }
+
+    Ok(m)
+}
+
+fn rotate(r: &Rotation, (x, y, z): Voxel) -> Voxel {
+    let row = |i: usize| r[i][0] * x + r[i][1] * y + r[i][2] * z;
+    (row(0), row(1), row(2))
+}
+
+fn mul(a: &Rotation, b: &Rotation) -> Rotation {
+    let mut m = [[0; 3]; 3];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, v) in row.iter_mut().enumerate() {
+            *v = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    m
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::IoError(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::IoError(e) => write!(f, "io error: {e}"),
+            Error::Malformed(e) => write!(f, "invalid vox file: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,117 @@
+use std::path::{Path, PathBuf};
+
+use ivo::*;
+use sketch_utils::golden::assert_svg_matches;
+
+fn scene() -> Scene {
+    let mut scene = Scene::new();
+    scene.aabb((0, 0, 0), (6, 6, 1));
+    scene.aabb((-3, -3, 3), (2, 2, 2));
+    scene.sphere((3, 3, 4), 3);
+    scene
+}
+
+fn paths(name: &str) -> (PathBuf, PathBuf) {
+    let output = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("ivo-{name}.svg"));
+    let golden = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{name}.svg"));
+
+    (output, golden)
+}
+
+#[test]
+fn test_golden_outlines() {
+    let (output, golden) = paths("outlines");
+
+    // the tiled renderer splits the lines in the same way regardless of the
+    // order the voxels are visited in
+    let lines = render_outlines_tiled(&scene(), 8);
+    dump_outlines_svg(
+        output.to_str().unwrap(),
+        &lines,
+        &SvgSettings::new(256.0, 256.0),
+    )
+    .expect("cannot save render");
+
+    assert_svg_matches(output, golden, 0.01);
+}
+
+#[test]
+fn test_golden_triangles() {
+    let (output, golden) = paths("triangles");
+
+    let settings = SvgSettings::new(256.0, 256.0)
+        .with_fill_color(Orientation::Top, "white")
+        .with_fill_color(Orientation::Left, "gray")
+        .with_fill_color(Orientation::Right, "black");
+    dump_triangles_svg(
+        output.to_str().unwrap(),
+        &render_triangles(&scene()),
+        &settings,
+    )
+    .expect("cannot save render");
+
+    assert_svg_matches(output, golden, 0.01);
+}
+
+#[test]
+fn test_golden_silhouette() {
+    let (output, golden) = paths("silhouette");
+
+    let lines = render_silhouette(&scene());
+    dump_outlines_svg(
+        output.to_str().unwrap(),
+        &lines,
+        &SvgSettings::new(256.0, 256.0),
+    )
+    .expect("cannot save render");
+
+    assert_svg_matches(output, golden, 0.01);
+}
+
+#[test]
+fn test_golden_incremental() {
+    let mut extra = Scene::new();
+    extra.aabb((4, -6, 6), (2, 2, 2));
+
+    let mut sphere = Scene::new();
+    sphere.sphere((3, 3, 4), 3);
+
+    // edit the scene back and forth between renders so that the cached tiles
+    // are invalidated both by added and removed voxels, the final render must
+    // match the one of the whole scene
+    let mut renderer = IncrementalRenderer::new(scene(), 8);
+    renderer.scene_mut().union(&extra);
+    renderer.render_outlines();
+    renderer.render_triangles();
+
+    renderer.scene_mut().subtract(&extra);
+    renderer.scene_mut().subtract(&sphere);
+    renderer.render_outlines();
+
+    renderer.scene_mut().union(&sphere);
+
+    let (output, golden) = (paths("incremental-outlines").0, paths("outlines").1);
+    dump_outlines_svg(
+        output.to_str().unwrap(),
+        &renderer.render_outlines(),
+        &SvgSettings::new(256.0, 256.0),
+    )
+    .expect("cannot save render");
+    assert_svg_matches(output, golden, 0.01);
+
+    let (output, golden) = (paths("incremental-triangles").0, paths("triangles").1);
+    let settings = SvgSettings::new(256.0, 256.0)
+        .with_fill_color(Orientation::Top, "white")
+        .with_fill_color(Orientation::Left, "gray")
+        .with_fill_color(Orientation::Right, "black");
+    dump_triangles_svg(
+        output.to_str().unwrap(),
+        &renderer.render_triangles(),
+        &settings,
+    )
+    .expect("cannot save render");
+    assert_svg_matches(output, golden, 0.01);
+}
@@ -0,0 +1,388 @@
+use ivo::*;
+
+/// A scene spanning multiple tiles with holes and overhangs.
+fn scene() -> Scene {
+    let mut scene = Scene::new();
+    scene.aabb((0, 0, 0), (30, 30, 1));
+    scene.sphere((5, 5, 10), 12);
+    scene.aabb((-20, -20, 14), (4, 4, 4));
+    scene.invert();
+    scene.aabb((5, 5, 10), (6, 30, 3));
+    scene
+}
+
+/// Round the given point so that it can be compared and sorted.
+fn key((x, y): XY) -> (i64, i64) {
+    ((x * 1e3).round() as i64, (y * 1e3).round() as i64)
+}
+
+/// Split the given lines in unit segments, so that renders that split the
+/// same lines differently compare equal.
+fn unit_segments(lines: &[Line]) -> Vec<[(i64, i64); 2]> {
+    let mut segments = vec![];
+    for l in lines {
+        for s in l.windows(2) {
+            let ((x0, y0), (x1, y1)) = (s[0], s[1]);
+            let n = f64::hypot(x1 - x0, y1 - y0).round() as i32;
+
+            for k in 0..n {
+                let (t0, t1) = (f64::from(k) / f64::from(n), f64::from(k + 1) / f64::from(n));
+                let a = key((x0 + (x1 - x0) * t0, y0 + (y1 - y0) * t0));
+                let b = key((x0 + (x1 - x0) * t1, y0 + (y1 - y0) * t1));
+                segments.push([a.min(b), a.max(b)]);
+            }
+        }
+    }
+
+    segments.sort_unstable();
+    segments
+}
+
+/// Sort the given triangles so that renders that return them in different
+/// orders compare equal.
+fn sorted_triangles(triangles: &[IsoTriangle<XY>]) -> Vec<IsoTriangle<(i64, i64)>> {
+    let mut triangles = triangles
+        .iter()
+        .map(|t| t.clone().map(key))
+        .collect::<Vec<_>>();
+
+    triangles.sort_unstable_by_key(|t| (t.pts, t.orientation as u8, t.visibility, t.nearness));
+    triangles
+}
+
+fn pixel(img: &geo::util::image::Image<3>, x: u32, y: u32) -> [u8; 3] {
+    let i = ((y * img.width() + x) * 3) as usize;
+    [img.data()[i], img.data()[i + 1], img.data()[i + 2]]
+}
+
+#[test]
+fn test_hatch_triangles() {
+    let t = IsoTriangle::new(
+        Orientation::Top,
+        [(0.0, 0.0), (4.0, 0.0), (0.0, 4.0)],
+        [true; 3],
+    );
+
+    // the top faces are hatched with horizontal lines inside the triangle
+    let lines = hatch_triangles(&[t.clone()], [Some(1.0), None, None]);
+    assert!(!lines.is_empty());
+    for l in &lines {
+        assert_eq!(l.len(), 2);
+        assert!((l[0].1 - l[1].1).abs() < 1e-9, "{l:?} is not horizontal");
+        for &(x, y) in l {
+            assert!(x >= -1e-9 && y >= -1e-9 && x + y <= 4.0 + 1e-9, "{l:?}");
+        }
+    }
+
+    // denser hatching for darker faces
+    let dense = hatch_triangles(&[t.clone()], [Some(0.25), None, None]);
+    assert!(dense.len() > lines.len());
+
+    // faces without spacing are left empty
+    assert!(hatch_triangles(&[t], [None, Some(1.0), Some(1.0)]).is_empty());
+}
+
+#[test]
+fn test_rasterize() {
+    let t = IsoTriangle::new(
+        Orientation::Left,
+        [(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)],
+        [true; 3],
+    );
+    let settings = RasterSettings::new(20, 20)
+        .with_background([0, 0, 255])
+        .with_stroke([0, 255, 0])
+        .with_fill_color(Orientation::Left, [255, 0, 0]);
+
+    // the triangle is scaled to cover half of the image
+    let img = rasterize(&[t.clone()], &[], &settings);
+    assert_eq!((img.width(), img.height()), (20, 20));
+    assert_eq!(pixel(&img, 2, 2), [255, 0, 0]);
+    assert_eq!(pixel(&img, 17, 17), [0, 0, 255]);
+
+    // the lines are drawn on top of the triangles
+    let img = rasterize(&[t], &[vec![(0.0, 0.0), (10.0, 10.0)]], &settings);
+    assert_eq!(pixel(&img, 5, 5), [0, 255, 0]);
+    assert_eq!(pixel(&img, 2, 8), [255, 0, 0]);
+    assert_eq!(pixel(&img, 17, 12), [0, 0, 255]);
+
+    // nothing to draw
+    let img = rasterize(&[], &[], &settings);
+    assert!(img.data().chunks(3).all(|px| px == [0, 0, 255]));
+}
+
+fn svg_path(name: &str) -> String {
+    std::path::Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join(format!("ivo-renderers-{name}.svg"))
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[test]
+fn test_occlusion() {
+    // a floor with a wall along its back side, the floor right next to the
+    // wall lies in a concave corner while the front of the floor is exposed
+    let mut scene = Scene::new();
+    scene.aabb((0, 0, 0), (4, 4, 0));
+    scene.aabb((0, 0, 1), (0, 4, 2));
+
+    let plain = render_triangles(&scene);
+    assert!(plain.iter().all(|t| t.occlusion == 0));
+
+    let occluded = render_triangles_with_occlusion(&scene);
+    assert_eq!(
+        occluded.iter().map(|t| t.pts).collect::<Vec<_>>(),
+        plain.iter().map(|t| t.pts).collect::<Vec<_>>()
+    );
+
+    let top = |t: &&IsoTriangle<XY>| t.orientation == Orientation::Top;
+    let corner = occluded
+        .iter()
+        .filter(top)
+        .find(|t| t.occlusion > 0)
+        .expect("no occluded top face in the corner")
+        .clone();
+    let exposed = occluded
+        .iter()
+        .filter(top)
+        .find(|t| t.occlusion == 0)
+        .expect("no exposed top face")
+        .clone();
+
+    // the corner is hatched denser than the exposed face
+    let spacing = [Some(0.1), None, None];
+    let hatch = |t: &IsoTriangle<XY>| {
+        let t = IsoTriangle::new(t.orientation, exposed.pts, t.visibility);
+        hatch_triangles(&[t.with_occlusion(t.occlusion)], spacing).len()
+    };
+    assert!(hatch(&corner) > hatch(&exposed));
+
+    // and darkened only when the occlusion shading is enabled
+    let svg = |name: &str, t: &IsoTriangle<XY>, settings: &SvgSettings| {
+        let path = svg_path(name);
+        dump_triangles_svg(&path, &[t.clone()], settings).expect("cannot save render");
+        std::fs::read_to_string(path).expect("cannot read render")
+    };
+
+    let settings = || SvgSettings::new(100.0, 100.0).with_fill_color(Orientation::Top, "white");
+    let shaded = settings().with_occlusion_shading(0.5);
+    let layer = r#"inkscape:label="occlusion""#;
+
+    let corner_svg = svg("occlusion-corner", &corner, &shaded);
+    let exposed_svg = svg("occlusion-exposed", &exposed, &shaded);
+    assert!(corner_svg.contains(layer) && corner_svg.contains("opacity"));
+    assert!(!exposed_svg.contains("opacity"));
+    assert!(!svg("occlusion-plain", &corner, &settings()).contains(layer));
+}
+
+#[test]
+fn test_tiled() {
+    let scene = scene();
+
+    let lines = unit_segments(&render_outlines(&scene));
+    assert!(!lines.is_empty());
+
+    let triangles = sorted_triangles(&render_triangles(&scene));
+    assert!(!triangles.is_empty());
+
+    // tiles smaller and bigger than the scene
+    for tile_size in [5, 16, 1000] {
+        assert_eq!(
+            unit_segments(&render_outlines_tiled(&scene, tile_size)),
+            lines
+        );
+
+        let tiled = render_triangles_tiled(&scene, tile_size).collect::<Vec<_>>();
+        assert_eq!(sorted_triangles(&tiled), triangles);
+    }
+
+    // nothing to render
+    assert!(render_outlines_tiled(&Scene::new(), 8).is_empty());
+    assert_eq!(render_triangles_tiled(&Scene::new(), 8).count(), 0);
+}
+
+#[test]
+#[cfg(feature = "parallel")]
+fn test_parallel() {
+    let scene = scene();
+
+    let lines = unit_segments(&render_outlines(&scene));
+    assert!(!lines.is_empty());
+    assert_eq!(unit_segments(&par_render_outlines(&scene)), lines);
+
+    let triangles = sorted_triangles(&render_triangles(&scene));
+    assert!(!triangles.is_empty());
+    assert_eq!(sorted_triangles(&par_render_triangles(&scene)), triangles);
+
+    // nothing to render
+    assert!(par_render_outlines(&Scene::new()).is_empty());
+    assert!(par_render_triangles(&Scene::new()).is_empty());
+}
+
+#[test]
+fn test_fit_degenerate() {
+    let svg = |name: &str, lines: &[Line], fit: Fit| {
+        let path = svg_path(name);
+        let settings = SvgSettings::new(100.0, 50.0).with_fit(fit);
+        dump_outlines_svg(&path, lines, &settings).expect("cannot save render");
+
+        let svg = std::fs::read_to_string(path).expect("cannot read render");
+        assert!(!svg.contains("inf") && !svg.contains("NaN"), "{svg}");
+        svg
+    };
+
+    // a flat drawing is fitted along its other axis
+    let line = vec![vec![(0.0, 0.0), (10.0, 0.0)]];
+    for fit in [Fit::Contain, Fit::Cover] {
+        let svg = svg("fit-line", &line, fit);
+        assert!(svg.contains(r#"points="-50.0000,0.0000 50.0000,0.0000 ""#));
+    }
+
+    // while a single point is left as is
+    let point = vec![vec![(3.0, 3.0), (3.0, 3.0)]];
+    for fit in [Fit::Contain, Fit::Cover] {
+        let svg = svg("fit-point", &point, fit);
+        assert!(svg.contains(r#"points="0.0000,0.0000 0.0000,0.0000 ""#));
+    }
+}
+
+#[test]
+fn test_incremental_replaced_scene() {
+    let mut renderer = IncrementalRenderer::new(scene(), 8);
+    renderer.render_outlines();
+    renderer.render_triangles();
+
+    let assert_full_render = |renderer: &mut IncrementalRenderer, scene: &Scene| {
+        assert_eq!(
+            unit_segments(&renderer.render_outlines()),
+            unit_segments(&render_outlines(scene))
+        );
+        assert_eq!(
+            sorted_triangles(&renderer.render_triangles()),
+            sorted_triangles(&render_triangles(scene))
+        );
+    };
+
+    // a brand new scene
+    let mut other = Scene::new();
+    other.sphere((0, 0, 0), 6);
+    *renderer.scene_mut() = other.clone();
+    assert_full_render(&mut renderer, &other);
+
+    // a copy of the scene edited outside of the renderer
+    let mut edited = renderer.scene().clone();
+    edited.aabb((0, 0, 8), (2, 2, 2));
+    edited.remove(0, 0, 6);
+    *renderer.scene_mut() = edited.clone();
+    assert_full_render(&mut renderer, &edited);
+
+    // the edits made afterwards are still tracked
+    renderer.scene_mut().add(20, 20, 20);
+    edited.add(20, 20, 20);
+    assert_full_render(&mut renderer, &edited);
+}
+
+#[test]
+fn test_primitives() {
+    let assert_same_render = |scene: &Scene, expected: &Scene| {
+        let mut voxels = scene.voxels().collect::<Vec<_>>();
+        let mut expected_voxels = expected.voxels().collect::<Vec<_>>();
+        voxels.sort_unstable();
+        expected_voxels.sort_unstable();
+        assert!(!voxels.is_empty());
+        assert_eq!(voxels, expected_voxels);
+
+        assert_eq!(
+            unit_segments(&render_outlines(scene)),
+            unit_segments(&render_outlines(expected))
+        );
+        assert_eq!(
+            sorted_triangles(&render_triangles(scene)),
+            sorted_triangles(&render_triangles(expected))
+        );
+    };
+
+    // axis aligned lines and straight curves are slabs
+    let mut slab = Scene::new();
+    slab.xslab((0, 0, 0), (9, 0, 0));
+
+    let mut line = Scene::new();
+    line.line((0, 0, 0), (9, 0, 0), 0);
+    assert_same_render(&line, &slab);
+
+    let mut bezier = Scene::new();
+    bezier.bezier((0, 0, 0), (3, 0, 0), (6, 0, 0), (9, 0, 0), 0);
+    assert_same_render(&bezier, &slab);
+
+    // diagonal lines step on all the axes at once
+    let mut line = Scene::new();
+    line.line((5, 5, 5), (0, 0, 0), 0);
+    let mut diagonal = Scene::new();
+    for i in 0..=5 {
+        diagonal.add(i, i, i);
+    }
+    assert_same_render(&line, &diagonal);
+
+    // polylines are made of lines
+    let pts = [(0, 0, 0), (6, 2, 0), (6, 8, 3), (-2, 8, 3)];
+    let mut polyline = Scene::new();
+    polyline.polyline(&pts, 1);
+    let mut lines = Scene::new();
+    for w in pts.windows(2) {
+        lines.line(w[0], w[1], 1);
+    }
+    assert_same_render(&polyline, &lines);
+
+    // spheres match the sdf of the same sphere
+    let mut sphere = Scene::new();
+    sphere.sphere((3, -2, 1), 4);
+    let mut expected = Scene::new();
+    expected.sdf(&(geo::sdf::sphere(4.0) + geo::v3(3, -2, 1)));
+    assert_same_render(&sphere, &expected);
+
+    // and thin cylinders are slabs too
+    let mut cylinder = Scene::new();
+    cylinder.cylinder((2, 2, 0), 0, 6);
+    let mut slab = Scene::new();
+    slab.zslab((2, 2, 0), (0, 0, 6));
+    assert_same_render(&cylinder, &slab);
+}
+
+#[test]
+fn test_exploded() {
+    // a small box on top of a bigger one, the center of the scene is at
+    // z = 27 * 6 / 102 ~ 1.6 so the parts are moved by 4 and -2 voxels
+    let mut scene = Scene::new();
+    scene.aabb((0, 0, 0), (2, 2, 1));
+    scene.aabb((0, 0, 6), (1, 1, 1));
+    let part = |(_, _, z): (i32, i32, i32)| z > 2;
+
+    let mut expected = Scene::new();
+    expected.aabb((0, 0, -2), (2, 2, 1));
+    expected.aabb((0, 0, 10), (1, 1, 1));
+
+    let exploded = scene.exploded(part, 1.0, Explode::Along(geo::Axis::Z));
+    let lines = unit_segments(&render_outlines(&exploded));
+    assert_eq!(lines, unit_segments(&render_outlines(&expected)));
+    let triangles = sorted_triangles(&render_triangles(&exploded));
+    assert_eq!(triangles, sorted_triangles(&render_triangles(&expected)));
+
+    // the parts are aligned on the other axes
+    let radial = scene.exploded(part, 1.0, Explode::Radial);
+    assert_eq!(unit_segments(&render_outlines(&radial)), lines);
+    let still = scene.exploded(part, 1.0, Explode::Along(geo::Axis::X));
+    assert_eq!(
+        unit_segments(&render_outlines(&still)),
+        unit_segments(&render_outlines(&scene))
+    );
+
+    // the parallel renderer draws the exploded view like the serial one
+    #[cfg(feature = "parallel")]
+    {
+        assert_eq!(unit_segments(&par_render_outlines(&exploded)), lines);
+        assert_eq!(
+            sorted_triangles(&par_render_triangles(&exploded)),
+            triangles
+        );
+    }
+}
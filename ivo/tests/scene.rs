@@ -0,0 +1,231 @@
+use geo::{sdf, v3, Axis, Triangle};
+
+use ivo::{render_shadows, render_triangles, Orientation, Scene};
+
+#[test]
+fn test_triangle_covers_vertices_and_edges() {
+    let (a, b, c) = (v3(0, 0, 0), v3(8, 0, 4), v3(0, 8, 4));
+
+    let mut scene = Scene::new();
+    scene.triangle(&Triangle::new(a, b, c));
+
+    // the edges pass through the voxels at the given steps from their start
+    for (start, end, steps) in [(a, b, 4), (b, c, 8), (c, a, 4)] {
+        for k in 0..=steps {
+            let p = start.lerp(end, f64::from(k) / f64::from(steps));
+            let (x, y, z) = (p.x.round() as i32, p.y.round() as i32, p.z.round() as i32);
+
+            assert!(
+                scene.is_set(x, y, z),
+                "voxel {:?} on the edge from {start:?} to {end:?} is not set",
+                (x, y, z)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_degenerate_triangle() {
+    let mut scene = Scene::new();
+    scene.triangle(&Triangle::new(v3(1, 2, 3), v3(1, 2, 3), v3(1, 2, 3)));
+
+    assert_eq!(scene.voxels().collect::<Vec<_>>(), vec![(1, 2, 3)]);
+}
+
+#[test]
+fn test_clone() {
+    // the bbox hint makes the voxels live in the grid, the others are outside
+    let mut scene = Scene::with_bbox_hint((0, 0, 0), (4, 4, 4));
+    scene.aabb((2, 2, 2), (1, 1, 1));
+    scene.add(10, 10, 10);
+
+    let mut copy = scene.clone();
+    copy.remove(2, 2, 2);
+    copy.remove(10, 10, 10);
+    copy.add(-1, 0, 0);
+
+    let mut voxels = scene.voxels().collect::<Vec<_>>();
+    voxels.sort_unstable();
+    assert_eq!(voxels.len(), 28);
+    assert_eq!(voxels.last(), Some(&(10, 10, 10)));
+    assert!(scene.is_set(2, 2, 2));
+    assert!(!scene.is_set(-1, 0, 0));
+
+    assert_eq!(copy.voxels().count(), 27);
+    assert!(!copy.is_set(2, 2, 2) && !copy.is_set(10, 10, 10));
+}
+
+#[test]
+fn test_sdf() {
+    // a sphere far from the origin so that most of its bbox is outside of it
+    let shape = sdf::sphere(6.0) + v3(20, -10, 5);
+
+    let mut scene = Scene::new();
+    scene.sdf(&shape);
+
+    // skipping the outside voxels finds the same voxels as sampling the whole
+    // bbox, all the axes included
+    let bbox = shape.bbox();
+    let (min, max) = (bbox.min().floor(), bbox.max());
+    let mut expected = vec![];
+    for z in min.z as i32..=max.z.floor() as i32 {
+        for y in min.y as i32..=max.y.floor() as i32 {
+            for x in min.x as i32..=max.x.floor() as i32 {
+                if shape.dist(&v3(x, y, z)) <= 0.1 {
+                    expected.push((x, y, z));
+                }
+            }
+        }
+    }
+
+    let mut voxels = scene.voxels().collect::<Vec<_>>();
+    voxels.sort_unstable();
+    expected.sort_unstable();
+    assert!(!expected.is_empty());
+    assert_eq!(voxels, expected);
+}
+
+#[test]
+fn test_boolean_ops_same_bounds() {
+    // scenes with the same bbox hint combine their grids directly while the
+    // others go voxel by voxel, both must give exactly the same voxels
+    let build = |same_bounds: bool, center: (i32, i32, i32)| {
+        let mut scene = if same_bounds {
+            Scene::with_bbox_hint((-8, -8, -8), (8, 8, 8))
+        } else {
+            Scene::new()
+        };
+
+        // part of the sphere pokes out of the grid
+        scene.sphere(center, 6);
+        scene.add(20, 0, -20);
+        scene
+    };
+
+    let ops: [fn(&mut Scene, &Scene); 3] = [Scene::union, Scene::intersect, Scene::subtract];
+    for op in ops {
+        let mut fast = build(true, (0, 0, 0));
+        op(&mut fast, &build(true, (4, 3, 2)));
+
+        let mut slow = build(false, (0, 0, 0));
+        op(&mut slow, &build(false, (4, 3, 2)));
+
+        let mut mixed = build(true, (0, 0, 0));
+        op(&mut mixed, &build(false, (4, 3, 2)));
+
+        assert_eq!(sorted_voxels(&fast), sorted_voxels(&slow));
+        assert_eq!(sorted_voxels(&mixed), sorted_voxels(&slow));
+    }
+}
+
+#[test]
+fn test_transforms_round_trip() {
+    // an asymmetric scene so that any rotation or mirroring moves its voxels
+    let mut scene = Scene::new();
+    scene.aabb((3, 1, 2), (2, 1, 0));
+    scene.add(-4, 7, -1);
+    scene.add(0, 0, 5);
+    let voxels = sorted_voxels(&scene);
+
+    assert_ne!(sorted_voxels(&scene.rotated_z90(1)), voxels);
+    assert_eq!(sorted_voxels(&scene.rotated_z90(0)), voxels);
+    assert_eq!(sorted_voxels(&scene.rotated_z90(4)), voxels);
+    assert_eq!(sorted_voxels(&scene.rotated_z90(-4)), voxels);
+
+    let mut rotated = scene.clone();
+    for _ in 0..4 {
+        rotated = rotated.rotated_z90(1);
+    }
+    assert_eq!(sorted_voxels(&rotated), voxels);
+
+    for n in -5..=5 {
+        assert_eq!(sorted_voxels(&scene.rotated_z90(n).rotated_z90(-n)), voxels);
+    }
+
+    for axis in [Axis::X, Axis::Y, Axis::Z] {
+        let mirrored = scene.mirrored(axis);
+        assert_ne!(sorted_voxels(&mirrored), voxels);
+        assert_eq!(sorted_voxels(&mirrored.mirrored(axis)), voxels);
+    }
+
+    assert_eq!(
+        sorted_voxels(&scene.translated(3, -2, 7).translated(-3, 2, -7)),
+        voxels
+    );
+}
+
+#[test]
+fn test_render_shadows() {
+    assert!(render_shadows(&Scene::new(), (1, 1)).is_empty());
+
+    // a pillar next to a voxel lit from the back, the shadow of the pillar
+    // falls on the ground, which is right below the lowest voxels, and on the
+    // top of the voxel which in turn casts its own shadow on the ground
+    let mut scene = Scene::new();
+    scene.aabb((0, 0, 2), (0, 0, 1));
+    scene.add(2, 2, 1);
+
+    // the shadows on the ground at (1, 1, 0) and (2, 2, 0) are hidden by the
+    // voxel
+    let mut receivers = Scene::new();
+    receivers.add(2, 2, 1);
+    receivers.add(3, 3, 0);
+
+    let shadows = render_shadows(&scene, (1, 1));
+    assert!(shadows.iter().all(|t| t.orientation == Orientation::Top));
+    assert_eq!(
+        top_faces(&shadows),
+        top_faces(&render_triangles(&receivers))
+    );
+    assert_eq!(shadows.len(), 4);
+}
+
+#[test]
+fn test_visible_voxels() {
+    assert!(Scene::new().visible_voxels().is_empty());
+
+    // a voxel right behind another one along the view direction is hidden
+    let mut scene = Scene::new();
+    scene.add(0, 0, 0);
+    scene.add(1, 1, 1);
+    assert_eq!(scene.visible_voxels(), vec![(1, 1, 1)]);
+
+    // only the front, right and top sides of a cube are visible
+    let mut scene = Scene::new();
+    scene.aabb((0, 0, 0), (1, 1, 1));
+
+    let visible = scene.visible_voxels();
+    assert_eq!(visible.first(), Some(&(1, 1, 1)));
+    assert!(visible
+        .windows(2)
+        .all(|w| w[0].0 + w[0].1 + w[0].2 >= w[1].0 + w[1].1 + w[1].2));
+
+    let mut visible = visible;
+    visible.sort_unstable();
+    let mut expected = scene
+        .voxels()
+        .filter(|&(x, y, z)| x == 1 || y == 1 || z == 1)
+        .collect::<Vec<_>>();
+    expected.sort_unstable();
+    assert_eq!(expected.len(), 19);
+    assert_eq!(visible, expected);
+}
+
+fn top_faces(triangles: &[ivo::IsoTriangle<ivo::XY>]) -> Vec<[(i64, i64); 3]> {
+    let mut faces = triangles
+        .iter()
+        .filter(|t| t.orientation == Orientation::Top)
+        .map(|t| {
+            t.pts
+                .map(|(x, y)| ((x * 1e3).round() as i64, (y * 1e3).round() as i64))
+        })
+        .collect::<Vec<_>>();
+    faces.sort_unstable();
+    faces
+}
+
+fn sorted_voxels(scene: &Scene) -> Vec<(i32, i32, i32)> {
+    let mut voxels = scene.voxels().collect::<Vec<_>>();
+    voxels.sort_unstable();
+    voxels
+}
@@ -0,0 +1,99 @@
+use ivo::scene_file::{self, Error, FitMode, Format, RenderMode};
+use sketch_utils::loader;
+
+const TOML: &str = r#"
+[render]
+mode = "hatching"
+width = 800
+height = 600
+fit = { scale = 2.0 }
+fill = { top = "white" }
+gradients = { left = { from = "white", to = "gray" } }
+
+[[ops]]
+op = "aabb"
+center = [0, 0, 0]
+half_size = [1, 1, 1]
+
+[[ops]]
+op = "subtract"
+ops = [{ op = "aabb", center = [1, 1, 1], half_size = [0, 0, 0] }]
+
+[[ops]]
+op = "translate"
+by = [10, 0, 0]
+"#;
+
+const JSON: &str = r#"{
+  "render": {
+    "mode": "hatching",
+    "width": 800,
+    "height": 600,
+    "fit": { "scale": 2.0 },
+    "fill": { "top": "white" },
+    "gradients": { "left": { "from": "white", "to": "gray" } }
+  },
+  "ops": [
+    { "op": "aabb", "center": [0, 0, 0], "half_size": [1, 1, 1] },
+    { "op": "subtract", "ops": [{ "op": "aabb", "center": [1, 1, 1], "half_size": [0, 0, 0] }] },
+    { "op": "translate", "by": [10, 0, 0] }
+  ]
+}"#;
+
+#[test]
+fn test_parse() {
+    for (data, format) in [(TOML, Format::Toml), (JSON, Format::Json)] {
+        let file = scene_file::from_str(data, format).unwrap();
+
+        assert_eq!(file.render.mode, RenderMode::Hatching);
+        assert_eq!((file.render.width, file.render.height), (800.0, 600.0));
+        assert_eq!(file.render.fit, FitMode::Scale(2.0));
+        assert_eq!(file.render.fill.top.as_deref(), Some("white"));
+        assert_eq!(file.render.fill.left, None);
+        assert_eq!(file.render.gradients.left.as_ref().unwrap().angle, 90.0);
+        assert_eq!(file.render.stroke, "black");
+
+        assert_eq!(file.scene.voxels().count(), 26);
+        assert!(file.scene.is_set(9, -1, -1));
+        assert!(!file.scene.is_set(11, 1, 1));
+        assert!(!file.scene.is_set(0, 0, 0));
+    }
+
+    let empty = scene_file::from_str("", Format::Toml).unwrap();
+    assert_eq!(empty.scene.voxels().count(), 0);
+    assert_eq!(empty.render.mode, RenderMode::Outlines);
+}
+
+#[test]
+fn test_errors() {
+    let err = scene_file::from_str(
+        "[[ops]]\nop = \"sdf\"\nsdf = { type = \"union\", of = [] }",
+        Format::Toml,
+    )
+    .unwrap_err();
+    assert!(matches!(err, Error::InvalidSdf));
+
+    let unknown_op = TOML.replace("op = \"translate\"", "op = \"teleport\"");
+    assert!(matches!(
+        scene_file::from_str(&unknown_op, Format::Toml),
+        Err(Error::Load(loader::Error::Toml(_)))
+    ));
+
+    let unknown_field = JSON.replace("\"width\"", "\"widht\"");
+    let err = scene_file::from_str(&unknown_field, Format::Json).unwrap_err();
+    assert!(matches!(err, Error::Load(loader::Error::Json(_))));
+    assert!(std::error::Error::source(&err).is_some());
+
+    assert!(matches!(
+        scene_file::from_str(TOML, Format::Json),
+        Err(Error::Load(loader::Error::Json(_)))
+    ));
+    assert!(matches!(
+        scene_file::load("scene.yml"),
+        Err(Error::Load(loader::Error::UnknownFormat(_)))
+    ));
+    assert!(matches!(
+        scene_file::load("missing-scene.toml"),
+        Err(Error::Load(loader::Error::IoError(_)))
+    ));
+}
@@ -0,0 +1,167 @@
+use ivo::{vox, Scene};
+
+fn chunk(id: &[u8; 4], content: &[u8], children: &[u8]) -> Vec<u8> {
+    let mut out = id.to_vec();
+    out.extend((content.len() as i32).to_le_bytes());
+    out.extend((children.len() as i32).to_le_bytes());
+    out.extend(content);
+    out.extend(children);
+    out
+}
+
+fn i32s(v: &[i32]) -> Vec<u8> {
+    v.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn dict(d: &[(&str, &str)]) -> Vec<u8> {
+    let mut out = i32s(&[d.len() as i32]);
+    for s in d.iter().flat_map(|(k, v)| [k, v]) {
+        out.extend(i32s(&[s.len() as i32]));
+        out.extend(s.as_bytes());
+    }
+    out
+}
+
+fn vox_file(children: &[u8]) -> Vec<u8> {
+    let mut out = b"VOX ".to_vec();
+    out.extend(i32s(&[150]));
+    out.extend(chunk(b"MAIN", &[], children));
+    out
+}
+
+/// Return the content of the first chunk with the given id.
+fn find_chunk<'a>(data: &'a [u8], id: &[u8; 4]) -> &'a [u8] {
+    let i = data.windows(4).position(|w| w == id).unwrap();
+    let len = i32::from_le_bytes(data[i + 4..i + 8].try_into().unwrap()) as usize;
+    &data[i + 12..i + 12 + len]
+}
+
+fn sorted_voxels(scene: &Scene) -> Vec<(i32, i32, i32)> {
+    let mut voxels = scene.voxels().collect::<Vec<_>>();
+    voxels.sort_unstable();
+    voxels
+}
+
+#[test]
+fn test_roundtrip() {
+    // spans more than a single model along every axis
+    let mut scene = Scene::new();
+    for i in -3..3 {
+        scene.add(i, i * 2, -i);
+    }
+    scene.add(300, 2, 1);
+    scene.add(10, 260, 3);
+    scene.add(0, 0, 600);
+    scene.add(-400, -1, 0);
+
+    let mut data = vec![];
+    vox::write(&mut data, &scene).unwrap();
+    let loaded = vox::read(&data[..]).unwrap();
+
+    assert_eq!(sorted_voxels(&loaded), sorted_voxels(&scene));
+
+    let mut data = vec![];
+    vox::write(&mut data, &Scene::new()).unwrap();
+    assert_eq!(vox::read(&data[..]).unwrap().voxels().count(), 0);
+}
+
+#[test]
+fn test_palette() {
+    let mut scene = Scene::new();
+    scene.add(0, 0, 0);
+    scene.add(1, 2, 3);
+
+    let mut data = vec![];
+    vox::write(&mut data, &scene).unwrap();
+
+    // all the voxels use the first color, which must be opaque
+    let xyzi = find_chunk(&data, b"XYZI");
+    assert_eq!(&xyzi[..4], &2_i32.to_le_bytes());
+    assert!(xyzi[4..].chunks_exact(4).all(|v| v[3] == 1));
+
+    let palette = find_chunk(&data, b"RGBA");
+    assert_eq!(palette.len(), 256 * 4);
+    assert_eq!(palette[3], 0xff);
+    assert_ne!(&palette[..3], &[0, 0, 0]);
+
+    assert_eq!(
+        sorted_voxels(&vox::read(&data[..]).unwrap()),
+        sorted_voxels(&scene)
+    );
+}
+
+#[test]
+fn test_truncated() {
+    let mut scene = Scene::new();
+    scene.add(1, 2, 3);
+    scene.add(4, 5, 6);
+
+    let mut data = vec![];
+    vox::write(&mut data, &scene).unwrap();
+
+    for len in 0..data.len() {
+        assert!(vox::read(&data[..len]).is_err(), "{len}");
+    }
+}
+
+#[test]
+fn test_malformed() {
+    let size = chunk(b"SIZE", &i32s(&[1, 1, 1]), &[]);
+    let xyzi = chunk(b"XYZI", &[1, 0, 0, 0, 0, 0, 0, 1], &[]);
+    let model = [size, xyzi.clone()].concat();
+
+    // sanity check, a single voxel without a scene graph
+    let scene = vox::read(&vox_file(&model)[..]).unwrap();
+    assert_eq!(sorted_voxels(&scene), vec![(0, 0, 0)]);
+
+    let mut bad_magic = vox_file(&model);
+    bad_magic[..4].copy_from_slice(b"XOV ");
+    assert!(vox::read(&bad_magic[..]).is_err());
+
+    let mut not_main = vox_file(&model);
+    not_main[8..12].copy_from_slice(b"PACK");
+    assert!(vox::read(&not_main[..]).is_err());
+
+    assert!(vox::read(&vox_file(&xyzi)[..]).is_err());
+
+    let mut negative = b"SIZE".to_vec();
+    negative.extend(i32s(&[-12, 0]));
+    assert!(vox::read(&vox_file(&negative)[..]).is_err());
+
+    let palette = chunk(b"RGBA", &[0; 12], &[]);
+    assert!(vox::read(&vox_file(&[model.clone(), palette].concat())[..]).is_err());
+
+    let transform = |id: i32, child: i32, frame: &[(&str, &str)]| {
+        let content = [
+            i32s(&[id]),
+            dict(&[]),
+            i32s(&[child, -1, -1, 1]),
+            dict(frame),
+        ]
+        .concat();
+        chunk(b"nTRN", &content, &[])
+    };
+    let shape = |id: i32, model: i32| {
+        let content = [i32s(&[id]), dict(&[]), i32s(&[1, model]), dict(&[])].concat();
+        chunk(b"nSHP", &content, &[])
+    };
+
+    let graph = [transform(0, 1, &[("_t", "1 2 3")]), shape(1, 0)].concat();
+    let scene = vox::read(&vox_file(&[model.clone(), graph].concat())[..]).unwrap();
+    assert_eq!(sorted_voxels(&scene), vec![(1, 2, 3)]);
+
+    let cycle = transform(0, 0, &[]);
+    assert!(vox::read(&vox_file(&[model.clone(), cycle].concat())[..]).is_err());
+
+    let missing_node = transform(0, 7, &[]);
+    assert!(vox::read(&vox_file(&[model.clone(), missing_node].concat())[..]).is_err());
+
+    let missing_model = [transform(0, 1, &[]), shape(1, 5)].concat();
+    assert!(vox::read(&vox_file(&[model.clone(), missing_model].concat())[..]).is_err());
+
+    let bad_rotation = [transform(0, 1, &[("_r", "3")]), shape(1, 0)].concat();
+    assert!(vox::read(&vox_file(&[model.clone(), bad_rotation].concat())[..]).is_err());
+
+    let bad_translation = [transform(0, 1, &[("_t", "1 x 3")]), shape(1, 0)].concat();
+    assert!(vox::read(&vox_file(&[model, bad_translation].concat())[..]).is_err());
+}
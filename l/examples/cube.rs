@@ -2,10 +2,9 @@ use std::sync::Arc;
 
 use rand::prelude::*;
 
-use geo::{v3, Aabb, Vec3};
 use sketch_utils::opener;
 
-use l::*;
+use l::prelude::*;
 
 pub fn main() -> opener::Result<()> {
     let mut objects = vec![];
@@ -29,14 +28,7 @@ pub fn main() -> opener::Result<()> {
     let camera = Camera::look_at(v3(-25.0, -25.0, -25.0), Vec3::zero(), v3(0, 1, 0))
         .with_perspective_projection(60.0, 1.0, 0.01, 100.0);
 
-    let paths = render(
-        &camera,
-        &scene,
-        &Settings {
-            chop_eps: 0.001,
-            simplify_eps: 0.001,
-        },
-    );
+    let paths = render(&camera, &scene, &Settings::new(0.001, 0.001));
     dump_svg("cube.svg", &paths, SvgSettings::new(2048.0, 2048.0)).expect("cannot save cube.svg");
 
     opener::open("cube.svg")
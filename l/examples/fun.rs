@@ -18,14 +18,7 @@ pub fn main() -> opener::Result<()> {
     let camera = Camera::look_at(v3(8, 8, 10), Vec3::zero(), v3(0, 0, 1))
         .with_perspective_projection(30.0, 1.0, 0.01, 100.0);
 
-    let paths = render(
-        &camera,
-        &scene,
-        &Settings {
-            chop_eps: 0.01,
-            simplify_eps: 0.001,
-        },
-    );
+    let paths = render(&camera, &scene, &Settings::new(0.01, 0.001));
     dump_svg("fun.svg", &paths, SvgSettings::new(1024.0, 1024.0)).expect("cannot save fun.svg");
 
     opener::open("fun.svg")
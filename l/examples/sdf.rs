@@ -26,14 +26,7 @@ pub fn glitch_sdf() {
     let camera = Camera::look_at(position, target, v3(0, 0, 1))
         .with_perspective_projection(45.0, 1.0, 0.001, 10000.0);
 
-    let paths = render(
-        &camera,
-        &scene,
-        &Settings {
-            chop_eps: 0.01,
-            simplify_eps: 0.001,
-        },
-    );
+    let paths = render(&camera, &scene, &Settings::new(0.01, 0.001));
     dump_svg(
         "glitch_sdf.svg",
         &paths,
@@ -42,8 +35,11 @@ pub fn glitch_sdf() {
             height: 2048.0,
             stroke_width: 3.0,
             stroke: "white",
+            pen_width: None,
             background: Some("black"),
             digits: 3,
+            single_path: false,
+            depth_layers: 1,
         },
     )
     .expect("cannot save glitch_sdf.svg");
@@ -73,14 +69,7 @@ pub fn poke_sdf() {
     let camera = Camera::look_at(position, target, v3(0, 0, 1))
         .with_perspective_projection(45.0, 1.0, 0.001, 10000.0);
 
-    let paths = render(
-        &camera,
-        &scene,
-        &Settings {
-            chop_eps: 0.01,
-            simplify_eps: 0.001,
-        },
-    );
+    let paths = render(&camera, &scene, &Settings::new(0.01, 0.001));
     dump_svg(
         "poke_sdf.svg",
         &paths,
@@ -89,8 +78,11 @@ pub fn poke_sdf() {
             height: 2048.0,
             stroke_width: 1.0,
             stroke: "black",
+            pen_width: None,
             background: None,
             digits: 3,
+            single_path: false,
+            depth_layers: 1,
         },
     )
     .expect("cannot save poke_sdf.svg");
@@ -164,14 +156,7 @@ pub fn main() -> opener::Result<()> {
     let camera = Camera::look_at(position, target, v3(0, 0, 1))
         .with_perspective_projection(45.0, 1.0, 0.001, 10000.0);
 
-    let paths = render(
-        &camera,
-        &scene,
-        &Settings {
-            chop_eps: 0.01,
-            simplify_eps: 0.001,
-        },
-    );
+    let paths = render(&camera, &scene, &Settings::new(0.01, 0.001));
     dump_svg(
         "sdf.svg",
         &paths,
@@ -182,8 +167,11 @@ pub fn main() -> opener::Result<()> {
             height: 2048.0,
             stroke_width: 3.0,
             stroke: "black",
+            pen_width: None,
             background: Some("white"),
             digits: 3,
+            single_path: false,
+            depth_layers: 1,
         },
     )
     .expect("cannot save sdf.svg");
@@ -106,16 +106,18 @@ pub fn main() -> opener::Result<()> {
     let camera = Camera::look_at(v3(5.0, -3.0, 20.0), Vec3::zero(), v3(0, 0, 1))
         .with_perspective_projection(45.0, 1.0, 0.01, 100.0);
 
-    let paths = render(
-        &camera,
-        &scene,
-        &Settings {
-            chop_eps: 0.001,
-            simplify_eps: 0.001,
+    let paths = render(&camera, &scene, &Settings::new(0.001, 0.001));
+    // split the buildings in the foreground from the ones in the background so
+    // that they can be plotted with different pens
+    dump_svg(
+        "skyscrapers.svg",
+        &paths,
+        SvgSettings {
+            depth_layers: 3,
+            ..SvgSettings::new(2048.0, 2048.0)
         },
-    );
-    dump_svg("skyscrapers.svg", &paths, SvgSettings::new(2048.0, 2048.0))
-        .expect("cannot save skyscrapers.svg");
+    )
+    .expect("cannot save skyscrapers.svg");
 
     opener::open("skyscrapers.svg")
 }
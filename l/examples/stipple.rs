@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use geo::{sdf::*, v3, Axis};
+
+use sketch_utils::opener;
+
+use l::*;
+
+pub fn main() -> opener::Result<()> {
+    let position = v3(0, -200, 100);
+    let target = v3(0, 0, 0);
+    let light_dir = v3(1, 1, -1).normalized();
+
+    let sdf = (sphere(60.0) - (sphere(40.0) + v3(0, -40, 30))).pad_bbox(30.0);
+
+    // light the slices from below so that the visible side keeps all of its
+    // paths and the shading comes only from the density of the stipples
+    let objects =
+        vec![Arc::new(SdfSlicer::new(sdf.clone(), 150, Axis::Z, v3(0, 0, 1))) as Arc<dyn Object>];
+    let scene = Scene::new(objects);
+
+    let camera = Camera::look_at(position, target, v3(0, 0, 1))
+        .with_perspective_projection(45.0, 1.0, 0.001, 10000.0);
+
+    let stipples = render_stipples(
+        &camera,
+        &scene,
+        &Settings::new(0.01, 0.001),
+        &StippleSettings::new(1.0).with_shape(StippleShape::Dash { length: 0.5 }),
+        directional_light(|p| sdf.normal_at(p), light_dir),
+    );
+
+    dump_stipple_svg(
+        "stipple.svg",
+        &stipples,
+        SvgSettings {
+            width: 2048.0,
+            height: 2048.0,
+            stroke_width: 3.0,
+            stroke: "black",
+            pen_width: None,
+            background: Some("white"),
+            digits: 3,
+            single_path: false,
+            depth_layers: 1,
+        },
+    )
+    .expect("cannot save stipple.svg");
+
+    opener::open("stipple.svg")
+}
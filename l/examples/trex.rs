@@ -33,14 +33,7 @@ pub fn main() -> opener::Result<()> {
     let camera = Camera::look_at(position, target, v3(0, 0, 1))
         .with_perspective_projection(60.0, 1.0, 0.01, 10000.0);
 
-    let paths = render(
-        &camera,
-        &scene,
-        &Settings {
-            chop_eps: 0.001,
-            simplify_eps: 0.01,
-        },
-    );
+    let paths = render(&camera, &scene, &Settings::new(0.001, 0.01));
     dump_svg("trex.svg", &paths, SvgSettings::new(2048.0, 2048.0)).expect("cannot save trex.svg");
 
     opener::open("trex.svg")
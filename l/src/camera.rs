@@ -1,6 +1,4 @@
-use std::f64::consts::PI;
-
-use geo::{mat4::Mat4, Vec3};
+use geo::{mat4::Mat4, v3, Vec3};
 
 /// A `Camera` is an object that allows to cast rays towards a 3D point in world
 /// space that is calculated from a 2D point in screen space.
@@ -8,7 +6,9 @@ use geo::{mat4::Mat4, Vec3};
 pub struct Camera {
     position: Vec3,
     camera_to_world: Mat4,
+    world_to_camera: Mat4,
     matrix: Mat4,
+    orthographic: bool,
 }
 
 impl Camera {
@@ -20,26 +20,59 @@ impl Camera {
     ///
     /// [0]: https://en.wikipedia.org/wiki/Viewing_frustum
     pub fn look_at(position: Vec3, target: Vec3, vup: Vec3) -> Self {
-        let f = (target - position).normalized();
-        let s = f.cross(vup).normalized();
-        let u = s.cross(f).normalized();
-
-        let camera_to_world = Mat4 {
-            data: [
-                [s.x, u.x, -f.x, position.x],
-                [s.y, u.y, -f.y, position.y],
-                [s.z, u.z, -f.z, position.z],
-                [0.0, 0.0, 0.0, 1.0],
-            ],
-        };
+        let world_to_camera = Mat4::look_at(position, target, vup);
+        let camera_to_world = world_to_camera.inverse();
 
         Self {
             position,
             matrix: camera_to_world.clone(),
             camera_to_world,
+            world_to_camera,
+            orthographic: false,
         }
     }
 
+    /// Create a `Camera` looking at `target` from `distance` away along the
+    /// direction given by `azimuth` and `elevation`, both in degrees.
+    ///
+    /// The `azimuth` is the rotation around the `vup` axis while the
+    /// `elevation` is the angle above the plane perpendicular to `vup`. Paired
+    /// with `Camera::with_orthographic_projection` it produces the
+    /// [axonometric projections][0] common in technical drawings.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/Axonometric_projection
+    pub fn axonometric(
+        target: Vec3,
+        azimuth: f64,
+        elevation: f64,
+        distance: f64,
+        vup: Vec3,
+    ) -> Self {
+        let w = vup.normalized();
+        let a = if w.x.abs() < 0.9 {
+            v3(1, 0, 0)
+        } else {
+            v3(0, 1, 0)
+        };
+        let e1 = (a - w * a.dot(w)).normalized();
+        let e2 = w.cross(e1);
+
+        let (az, el) = (azimuth.to_radians(), elevation.to_radians());
+        let dir = e1 * (el.cos() * az.cos()) + e2 * (el.cos() * az.sin()) + w * el.sin();
+
+        Self::look_at(target + dir * distance, target, vup)
+    }
+
+    /// Create a `Camera` looking at `target` from `distance` away in the
+    /// classic [isometric][0] view where the three axes are equally
+    /// foreshortened.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/Isometric_projection
+    pub fn isometric(target: Vec3, distance: f64, vup: Vec3) -> Self {
+        let elevation = (1.0 / 2.0_f64.sqrt()).atan().to_degrees();
+        Self::axonometric(target, 45.0, elevation, distance, vup)
+    }
+
     /// Set the `Camera` to use [Perspective projection][0] when projecting 3D
     /// points to 2D.
     ///
@@ -49,7 +82,6 @@ impl Camera {
     /// between of these planes are not projected.
     ///
     /// [0]: https://en.wikipedia.org/wiki/3D_projection#Perspective_projection
-    #[rustfmt::skip]
     pub fn with_perspective_projection(
         mut self,
         fovy: f64,
@@ -57,24 +89,33 @@ impl Camera {
         near: f64,
         far: f64,
     ) -> Self {
-        let ymax = near * (fovy * PI / 360.0).tan();
-        let xmax = ymax * aspect;
-
-        let t1 = 2.0 * near;
-        let t2 = 2.0 * xmax;
-        let t3 = 2.0 * ymax;
-        let t4 = far - near;
-
-        let projection = Mat4 {
-            data: [
-                [t1 / t2, 0.0,     0.0,                0.0],
-                [0.0,     t1 / t3, 0.0,                0.0],
-                [0.0,     0.0,     (-far - near) / t4, -t1 * far / t4],
-                [0.0,     0.0,     -1.0,               0.0],
-            ],
-        };
+        let projection = Mat4::perspective(fovy, aspect, near, far);
 
-        self.matrix = projection * &self.camera_to_world.inverse();
+        self.matrix = projection * &self.world_to_camera;
+        self.orthographic = false;
+        self
+    }
+
+    /// Set the `Camera` to use [Orthographic projection][0] when projecting 3D
+    /// points to 2D.
+    ///
+    /// The projected region is `width` x `height` wide in world units and
+    /// centered on the viewing direction. Points not between the near and far
+    /// planes are not projected.
+    ///
+    /// [0]: https://en.wikipedia.org/wiki/Orthographic_projection
+    pub fn with_orthographic_projection(
+        mut self,
+        width: f64,
+        height: f64,
+        near: f64,
+        far: f64,
+    ) -> Self {
+        let (w2, h2) = (width / 2.0, height / 2.0);
+        let projection = Mat4::orthographic(-w2, w2, -h2, h2, near, far);
+
+        self.matrix = projection * &self.world_to_camera;
+        self.orthographic = true;
         self
     }
 
@@ -83,12 +124,50 @@ impl Camera {
         self.position
     }
 
+    /// Return the direction the camera is looking at.
+    pub fn direction(&self) -> Vec3 {
+        let m = &self.camera_to_world.data;
+        -Vec3::new(m[0][2], m[1][2], m[2][2])
+    }
+
+    /// Return the direction pointing to the right of the image.
+    pub fn right(&self) -> Vec3 {
+        let m = &self.camera_to_world.data;
+        Vec3::new(m[0][0], m[1][0], m[2][0])
+    }
+
+    /// Return a copy of this `Camera`, with the same projection, moved by
+    /// `offset`.
+    pub fn translated(&self, offset: Vec3) -> Self {
+        Self {
+            position: self.position + offset,
+            camera_to_world: Mat4::translate(offset) * &self.camera_to_world,
+            world_to_camera: self.world_to_camera.clone() * &Mat4::translate(-offset),
+            matrix: self.matrix.clone() * &Mat4::translate(-offset),
+            orthographic: self.orthographic,
+        }
+    }
+
+    /// Return the depth of the given point, that is its distance from the
+    /// camera along the viewing direction.
+    pub fn depth(&self, p: Vec3) -> f64 {
+        (p - self.position).dot(self.direction())
+    }
+
+    /// Return the point the ray of sight reaching `p` starts from. It's the
+    /// position of the camera for perspective projections, while for
+    /// orthographic projections the rays are all parallel to the viewing
+    /// direction and start from the plane of the camera.
+    pub fn ray_origin(&self, p: Vec3) -> Vec3 {
+        if self.orthographic {
+            p - self.direction() * self.depth(p)
+        } else {
+            self.position
+        }
+    }
+
     /// Project the given point in 3D space to 2D as seen by this `Camera`.
     pub fn project(&self, v: Vec3) -> Vec3 {
-        let p = v * &self.matrix;
-
-        let m = &self.matrix.data;
-        let w = m[3][0] * v.x + m[3][1] * v.y + m[3][2] * v.z + m[3][3];
-        p / w
+        self.matrix.project_point(v)
     }
 }
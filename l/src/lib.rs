@@ -1,23 +1,35 @@
 pub mod camera;
 pub mod object;
+pub mod prelude;
 mod renderer;
+pub mod scene_file;
+pub mod sketch;
+pub mod stereo;
+pub mod stipple;
+pub mod svg;
 
 use std::sync::Arc;
 
 use geo::{
     primitive::polyline::Polyline,
     ray::Ray,
-    spatial_index::{Bvh, Intersection, Shape},
+    spatial_index::{Bvh, Shape},
 };
 
 pub use camera::Camera;
 pub use object::*;
 pub use renderer::*;
+pub use sketch::{dump_sketch_svg, Sketch};
+pub use stereo::{dump_stereo_svg, render_stereo, StereoOutput};
+pub use stipple::{
+    directional_light, dump_stipple_svg, render_stipples, StippleSettings, StippleShape,
+};
 
 /// A `Scene` is a collection of objects that can be rendered.
 #[derive(Debug)]
 pub struct Scene {
-    objects: Bvh<Arc<dyn Object>>,
+    objects: Vec<Arc<dyn Object>>,
+    occluders: Bvh<Arc<dyn Object>>,
 }
 
 /// An `Object` that can be rendered.
@@ -27,23 +39,63 @@ pub trait Object: Shape<Intersection = f64> + std::fmt::Debug + Send + Sync {
     /// Note that all paths are considered open if the last point doesn't
     /// exactly match the first one.
     fn paths(&self) -> Vec<Polyline>;
+
+    /// How the `Object` takes part in the hidden line removal, by default it
+    /// both hides and can be hidden by the other objects.
+    fn occlusion(&self) -> Occlusion {
+        Occlusion::Normal
+    }
+}
+
+/// How an `Object` takes part in the hidden line removal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Occlusion {
+    /// The `Object` hides the objects behind it and its paths are drawn only
+    /// where they're not hidden by other objects.
+    #[default]
+    Normal,
+
+    /// The `Object` hides the objects behind it, but its paths are never
+    /// drawn.
+    OccluderOnly,
+
+    /// The `Object` doesn't hide anything and its paths are always drawn even
+    /// if they're behind other objects.
+    NonOccluding,
 }
 
 impl Scene {
     /// Create a new `Scene` with the given objects.
     pub fn new(objects: impl IntoIterator<Item = Arc<dyn Object>>) -> Self {
-        Self {
-            objects: objects.into_iter().collect(),
-        }
+        let objects = objects.into_iter().collect::<Vec<_>>();
+        let occluders = objects
+            .iter()
+            .filter(|o| o.occlusion() != Occlusion::NonOccluding)
+            .cloned()
+            .collect();
+
+        Self { objects, occluders }
+    }
+
+    /// Return the paths to draw along with the `Occlusion` of the object they
+    /// belong to.
+    pub(crate) fn paths(&self) -> Vec<(Polyline, Occlusion)> {
+        self.objects
+            .iter()
+            .filter(|o| o.occlusion() != Occlusion::OccluderOnly)
+            .flat_map(|o| {
+                let occlusion = o.occlusion();
+                o.paths().into_iter().map(move |p| (p, occlusion))
+            })
+            .collect()
     }
 
     /// Calculate the intersection between a `Ray` and all the objects in the
-    /// scene returning the closest object (along with its intersection t
-    /// parameter) to the ray.
+    /// scene that can hide other objects returning the closest object (along
+    /// with its intersection t parameter) to the ray.
     pub fn intersection(&self, ray: &Ray) -> Option<(&dyn Object, f64)> {
-        self.objects
-            .intersections(ray)
-            .min_by(|(_, t0), (_, t1)| t0.t().total_cmp(&t1.t()))
+        self.occluders
+            .closest_intersection(ray)
             .map(|(s, t)| (s.as_ref(), t))
     }
 }
@@ -0,0 +1,26 @@
+use std::{env, path::Path, process};
+
+use l::scene_file;
+
+fn main() {
+    let args: Vec<_> = env::args().skip(1).collect();
+
+    let (input, output) = match args.as_slice() {
+        [input] => (input.clone(), Path::new(input).with_extension("svg")),
+        [input, output] => (input.clone(), output.into()),
+        _ => {
+            eprintln!("usage: l <scene.toml|scene.json> [output.svg]");
+            process::exit(1);
+        }
+    };
+
+    let scene = scene_file::load(&input).unwrap_or_else(|e| {
+        eprintln!("cannot load {input}: {e}");
+        process::exit(1);
+    });
+
+    if let Err(e) = scene.render_svg(&output.to_string_lossy()) {
+        eprintln!("cannot save {}: {e}", output.display());
+        process::exit(1);
+    }
+}
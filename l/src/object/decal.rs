@@ -0,0 +1,81 @@
+use std::path::Path;
+
+use geo::{
+    mat4::Mat4, polyline2, primitive::polyline::Polyline, ray::Ray, spatial_index::Shape, v2, v3,
+    Aabb, Polyline2, Triangle, Vec2, Vec3,
+};
+
+use crate::{svg, Object};
+
+/// Flat 2D paths, e.g. a logo or an hand drawn element, placed in the 3D
+/// scene.
+///
+/// The paths lie on the rectangle bounding them which hides the objects
+/// behind it like a sign would, while the paths are hidden by the other
+/// objects like any other path.
+#[derive(Debug)]
+pub struct Decal {
+    paths: Vec<Polyline>,
+    card: [Triangle; 2],
+}
+
+impl Decal {
+    /// Create a `Decal` with the given 2D paths lying on the XY plane and then
+    /// transformed by `transform`.
+    pub fn new(paths: &[Polyline2], transform: &Mat4) -> Self {
+        Self::mapped(paths, |p| v3(p.x, p.y, 0.0) * transform)
+    }
+
+    /// Create a `Decal` with the given 2D paths lying on the plane passing
+    /// through `origin` where the X and Y axes of the paths are mapped to `u`
+    /// and `v` respectively.
+    pub fn on_plane(paths: &[Polyline2], origin: Vec3, u: Vec3, v: Vec3) -> Self {
+        Self::mapped(paths, |p| origin + u * p.x + v * p.y)
+    }
+
+    /// Load the paths of the SVG at `path` and place them on the plane passing
+    /// through `origin` like `on_plane` does.
+    ///
+    /// The top left corner of the SVG is at `origin`, the X axis of the SVG
+    /// goes along `u` and `v` is the up direction of the drawing, see the
+    /// `svg` module for what's supported.
+    pub fn from_svg(path: impl AsRef<Path>, origin: Vec3, u: Vec3, v: Vec3) -> svg::Result<Self> {
+        let paths = svg::load_svg(path)?;
+
+        // the Y axis of SVGs points down
+        Ok(Self::on_plane(&paths, origin, u, -v))
+    }
+
+    fn mapped(paths: &[Polyline2], f: impl Fn(Vec2) -> Vec3) -> Self {
+        let (min, max) = polyline2::bbox(paths.iter().flat_map(Polyline2::iter))
+            .unwrap_or((Vec2::zero(), Vec2::zero()));
+
+        let [a, b, c, d] = [min, v2(max.x, min.y), max, v2(min.x, max.y)].map(&f);
+
+        Self {
+            paths: paths.iter().map(|p| p.iter().map(&f).collect()).collect(),
+            card: [Triangle::new(a, b, c), Triangle::new(a, c, d)],
+        }
+    }
+}
+
+impl Shape for Decal {
+    type Intersection = f64;
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        self.card
+            .iter()
+            .filter_map(|t| t.intersection(ray))
+            .min_by(f64::total_cmp)
+    }
+
+    fn bbox(&self) -> Aabb {
+        self.card[0].bbox().union(&self.card[1].bbox())
+    }
+}
+
+impl Object for Decal {
+    fn paths(&self) -> Vec<Polyline> {
+        self.paths.clone()
+    }
+}
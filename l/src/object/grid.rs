@@ -1,7 +1,7 @@
 use geo::{
     primitive::polyline::Polyline,
     ray::Ray,
-    spatial_index::{Bvh, Intersection, Shape},
+    spatial_index::{Bvh, Shape},
     v3, Aabb, Triangle, Vec3,
 };
 
@@ -69,10 +69,7 @@ impl Shape for Grid {
     type Intersection = f64;
 
     fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
-        self.bvh
-            .intersections(ray)
-            .map(|(_, t)| t)
-            .min_by(|t0, t1| t0.t().total_cmp(&t1.t()))
+        self.bvh.closest_intersection(ray).map(|(_, t)| t)
     }
 
     fn bbox(&self) -> Aabb {
@@ -0,0 +1,110 @@
+use geo::{primitive::polyline::Polyline, ray::Ray, spatial_index::Shape, v3, Aabb};
+
+use crate::Object;
+
+/// A terrain described by a `geo::Heightfield` whose heights are along the Z
+/// axis.
+#[derive(Debug)]
+pub struct Heightfield {
+    heightfield: geo::Heightfield,
+    style: HeightfieldStyle,
+}
+
+/// How the paths of a `Heightfield` are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeightfieldStyle {
+    /// Draw the rows and the columns of the samples.
+    Wireframe,
+
+    /// Draw the given number of contour lines evenly spaced between the
+    /// minimum and maximum height.
+    Contours(u16),
+}
+
+impl Heightfield {
+    pub fn new(heightfield: geo::Heightfield) -> Self {
+        Self {
+            heightfield,
+            style: HeightfieldStyle::Wireframe,
+        }
+    }
+
+    pub fn with_style(mut self, style: HeightfieldStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    fn wireframe(&self) -> Vec<Polyline> {
+        let (w, h) = self.heightfield.dimensions();
+
+        let rows = (0..h).map(|j| (0..w).map(|i| self.heightfield.vertex(i, j)).collect());
+        let cols = (0..w).map(|i| (0..h).map(|j| self.heightfield.vertex(i, j)).collect());
+
+        rows.chain(cols).collect()
+    }
+
+    fn contours(&self, levels: u16) -> Vec<Polyline> {
+        let bbox = self.bbox();
+        let (zmin, zmax) = (bbox.min().z, bbox.max().z);
+
+        let (w, h) = self.heightfield.dimensions();
+        let (first, last) = (
+            self.heightfield.vertex(0, 0),
+            self.heightfield.vertex(w - 1, h - 1),
+        );
+
+        (1..=levels)
+            .flat_map(|l| {
+                // skip the extremes that would only touch the samples
+                let z = zmin + (zmax - zmin) * f64::from(l) / f64::from(levels + 1);
+
+                marching_squares::march(&HeightfieldField(&self.heightfield), z)
+                    .into_iter()
+                    .map(move |c| {
+                        c.into_iter()
+                            .map(|(x, y)| {
+                                v3(
+                                    first.x + (last.x - first.x) * x / (w - 1) as f64,
+                                    first.y + (last.y - first.y) * y / (h - 1) as f64,
+                                    z,
+                                )
+                            })
+                            .collect()
+                    })
+            })
+            .collect()
+    }
+}
+
+impl Shape for Heightfield {
+    type Intersection = f64;
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        self.heightfield.intersection(ray)
+    }
+
+    fn bbox(&self) -> Aabb {
+        self.heightfield.bbox()
+    }
+}
+
+impl Object for Heightfield {
+    fn paths(&self) -> Vec<Polyline> {
+        match self.style {
+            HeightfieldStyle::Wireframe => self.wireframe(),
+            HeightfieldStyle::Contours(levels) => self.contours(levels),
+        }
+    }
+}
+
+struct HeightfieldField<'a>(&'a geo::Heightfield);
+
+impl marching_squares::Field for HeightfieldField<'_> {
+    fn dimensions(&self) -> (usize, usize) {
+        self.0.dimensions()
+    }
+
+    fn z_at(&self, x: usize, y: usize) -> f64 {
+        self.0.vertex(x, y).z
+    }
+}
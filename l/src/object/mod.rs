@@ -1,9 +1,15 @@
 mod cube;
+mod decal;
 mod facet;
 mod grid;
+mod heightfield;
+mod occlusion;
 mod sdf;
 
 pub use cube::Cube;
+pub use decal::Decal;
 pub use facet::Facet;
 pub use grid::Grid;
-pub use sdf::SdfSlicer;
+pub use heightfield::{Heightfield, HeightfieldStyle};
+pub use occlusion::WithOcclusion;
+pub use sdf::{sdf2_contours, SdfSlicer};
@@ -0,0 +1,55 @@
+use geo::{primitive::polyline::Polyline, ray::Ray, spatial_index::Shape, Aabb, Vec3};
+
+use crate::{Object, Occlusion};
+
+/// Wrap an `Object` to change how it takes part in the hidden line removal,
+/// e.g. to use it as guide geometry that only hides the other objects or as
+/// a ghosted overlay drawn on top of everything else.
+#[derive(Debug, Clone)]
+pub struct WithOcclusion<O> {
+    object: O,
+    occlusion: Occlusion,
+}
+
+impl<O> WithOcclusion<O> {
+    pub fn new(object: O, occlusion: Occlusion) -> Self {
+        Self { object, occlusion }
+    }
+
+    /// Wrap the given `Object` so that it hides the others, but it's not drawn.
+    pub fn occluder_only(object: O) -> Self {
+        Self::new(object, Occlusion::OccluderOnly)
+    }
+
+    /// Wrap the given `Object` so that it doesn't hide the others and it's
+    /// always drawn.
+    pub fn non_occluding(object: O) -> Self {
+        Self::new(object, Occlusion::NonOccluding)
+    }
+}
+
+impl<O: Object> Shape for WithOcclusion<O> {
+    type Intersection = f64;
+
+    fn intersection(&self, ray: &Ray) -> Option<Self::Intersection> {
+        self.object.intersection(ray)
+    }
+
+    fn bbox(&self) -> Aabb {
+        self.object.bbox()
+    }
+
+    fn bounding_sphere(&self) -> (Vec3, f64) {
+        self.object.bounding_sphere()
+    }
+}
+
+impl<O: Object> Object for WithOcclusion<O> {
+    fn paths(&self) -> Vec<Polyline> {
+        self.object.paths()
+    }
+
+    fn occlusion(&self) -> Occlusion {
+        self.occlusion
+    }
+}
@@ -1,5 +1,6 @@
 use geo::{
-    primitive::polyline::Polyline, ray::Ray, sdf::Sdf, spatial_index::Shape, v3, Aabb, Axis, Vec3,
+    primitive::polyline::Polyline, ray::Ray, sdf::Sdf, sdf2::Sdf2, spatial_index::Shape,
+    util::linspace, v2, v3, Aabb, Axis, Polyline2, Vec3,
 };
 
 use crate::Object;
@@ -54,13 +55,15 @@ impl Object for SdfSlicer {
     fn paths(&self) -> Vec<Polyline> {
         let bbox = self.bbox();
 
-        (0..self.divs)
+        let (min, max) = (bbox.min()[self.axis], bbox.max()[self.axis]);
+        let slices = linspace(min, max, usize::from(self.divs)).collect::<Vec<_>>();
+
+        slices
             .into_par_iter()
-            .flat_map(|t| {
+            .enumerate()
+            .flat_map(|(t, y)| {
                 let mut paths = vec![];
 
-                let y = bbox.min()[self.axis]
-                    + bbox.dimensions()[self.axis] * f64::from(t) / f64::from(self.divs - 1);
                 let f = SdfField::new(&self.sdf, y, self.axis);
 
                 let contours = marching_squares::march(&f, 0.0);
@@ -149,45 +152,53 @@ impl SdfField {
         // This means we can use ray-marching to quickly jump when we're outside
         // of the Sdf. However, when we reach the interior we have to check
         // pixel by pixel given that usually the interior of an SDF is broken.
+        // All the rows are marched at once to evaluate the Sdf in batches.
         //
-        // Note that we actually calculate all the distances on the "boundary"
-        // of the Sdf (both inside and outside) to have nicely interpolated
-        // paths. Otherwise, the paths are too jaggy and not that pretty.
-        for j in 0..h {
-            let mut i = 0.0;
-            while i < w as f64 {
-                let d = sdf.dist(&res.to_3d(i, j as f64));
-
+        // The border is left untouched so that the paths are always closed.
+        let mut rows = (1..h - 1).map(|j| (1, j)).collect::<Vec<_>>();
+        let mut pts = vec![];
+        let mut dists = vec![];
+        while !rows.is_empty() {
+            pts.clear();
+            pts.extend(rows.iter().map(|&(i, j)| res.to_3d(i as f64, j as f64)));
+            dists.resize(pts.len(), 0.0);
+            sdf.dist_many(&pts, &mut dists);
+
+            for ((i, j), &d) in rows.iter_mut().zip(&dists) {
                 if d <= EPS {
-                    res.data[j * w + i as usize] = d;
-
-                    // we're inside, populate the pixels to the left and above
-                    // if they're outside the Sdf (if they're inside we already
-                    // calculated their value).
-                    if i > 0.0 && res.data[j * w + i as usize - 1] > EPS {
-                        res.data[j * w + i as usize - 1] = sdf.dist(&res.to_3d(i - 1.0, j as f64));
-                    }
+                    res.data[*j * w + *i] = d;
+                    *i += 1;
+                } else {
+                    *i = i.saturating_add((d * Self::RESOLUTION).floor().max(1.0) as usize);
+                }
+            }
 
-                    if j > 0 && res.data[(j - 1) * w + i as usize] > EPS {
-                        res.data[(j - 1) * w + i as usize] =
-                            sdf.dist(&res.to_3d(i, j as f64 - 1.0));
-                    }
+            rows.retain(|&(i, _)| i < w - 1);
+        }
 
-                    i += 1.0;
-                    continue;
-                }
+        // Then calculate the distances of the outside pixels right next to the
+        // inside ones, that is on the "boundary" of the Sdf, to have nicely
+        // interpolated paths. Otherwise, the paths are too jaggy and not that
+        // pretty.
+        let inside = |i: usize, j: usize| res.data[j * w + i] <= EPS;
+        let boundary = (1..h - 1)
+            .flat_map(|j| (1..w - 1).map(move |i| (i, j)))
+            .filter(|&(i, j)| {
+                !inside(i, j)
+                    && (inside(i - 1, j)
+                        || inside(i + 1, j)
+                        || inside(i, j - 1)
+                        || inside(i, j + 1))
+            })
+            .collect::<Vec<_>>();
 
-                // we're outside, but the pixel to the left or above are inside
-                // the Sdf, calculate the distance to have nicely interpolated
-                // paths anyway
-                if (i > 0.0 && res.data[j * w + i as usize - 1] <= EPS)
-                    || j > 0 && res.data[(j - 1) * w + i as usize] <= EPS
-                {
-                    res.data[j * w + i as usize] = d;
-                }
+        pts.clear();
+        pts.extend(boundary.iter().map(|&(i, j)| res.to_3d(i as f64, j as f64)));
+        dists.resize(pts.len(), 0.0);
+        sdf.dist_many(&pts, &mut dists);
 
-                i += (d * Self::RESOLUTION).floor().max(1.0);
-            }
+        for ((i, j), d) in boundary.into_iter().zip(dists) {
+            res.data[j * w + i] = d;
         }
 
         res
@@ -209,6 +220,60 @@ impl SdfField {
     }
 }
 
+/// Contour the given `Sdf2` at each of the given distances from its boundary,
+/// 0 being the boundary itself and negative distances being inside the shape.
+///
+/// The returned paths are in the same space of the `Sdf2` and they're closed.
+/// `resolution` is how many cells per unit to sample the `Sdf2` at.
+pub fn sdf2_contours(sdf: &Sdf2, levels: &[f64], resolution: f64) -> Vec<Polyline2> {
+    // make room for the outermost contour plus a cell so that it's closed
+    let outer = levels.iter().copied().fold(0.0, f64::max);
+    let (min, max) = sdf.bbox();
+    let min = min - (outer + 1.0 / resolution);
+    let max = max + (outer + 1.0 / resolution);
+
+    let w = ((max.x - min.x) * resolution).ceil() as usize + 1;
+    let h = ((max.y - min.y) * resolution).ceil() as usize + 1;
+    let to_2d = move |i: f64, j: f64| min + v2(i, j) / resolution;
+
+    let data = (0..h)
+        .into_par_iter()
+        .flat_map_iter(|j| (0..w).map(move |i| sdf.dist(&to_2d(i as f64, j as f64))))
+        .collect();
+    let field = Sdf2Field {
+        data,
+        width: w,
+        height: h,
+    };
+
+    levels
+        .par_iter()
+        .flat_map_iter(|&l| {
+            marching_squares::march(&field, l).into_iter().map(|c| {
+                c.into_iter()
+                    .map(|(i, j)| to_2d(i, j))
+                    .collect::<Polyline2>()
+            })
+        })
+        .collect()
+}
+
+struct Sdf2Field {
+    data: Vec<f64>,
+    width: usize,
+    height: usize,
+}
+
+impl marching_squares::Field for Sdf2Field {
+    fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn z_at(&self, x: usize, y: usize) -> f64 {
+        self.data[y * self.width + x]
+    }
+}
+
 impl marching_squares::Field for SdfField {
     fn dimensions(&self) -> (usize, usize) {
         (self.width, self.height)
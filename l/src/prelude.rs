@@ -0,0 +1,34 @@
+//! Glob import this module to describe a 3D `Scene` made of `Object`s, look
+//! at it through a `Camera` and draw its visible lines to SVG, also as
+//! stipples or stereo pairs. It re-exports `geo::prelude` as well for the
+//! vectors, matrices and sdfs the objects are built from.
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//!
+//! use l::prelude::*;
+//!
+//! let cube = Cube::new(Aabb::cuboid(Vec3::zero(), 2.0));
+//! let scene = Scene::new(vec![Arc::new(cube) as Arc<dyn Object>]);
+//!
+//! let camera = Camera::look_at(v3(-5, -5, -5), Vec3::zero(), v3(0, 1, 0))
+//!     .with_perspective_projection(60.0, 1.0, 0.01, 100.0);
+//!
+//! let paths = render(&camera, &scene, &Settings::new(0.001, 0.001));
+//! dump_svg("cube.svg", &paths, SvgSettings::new(800.0, 800.0))?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+pub use geo::prelude::*;
+
+pub use crate::{
+    camera::Camera,
+    object::*,
+    renderer::*,
+    sketch::{dump_sketch_svg, Sketch},
+    stereo::{dump_stereo_svg, render_stereo, StereoOutput},
+    stipple::{
+        directional_light, dump_stipple_svg, render_stipples, StippleSettings, StippleShape,
+    },
+    Object, Occlusion, Scene,
+};
@@ -1,13 +1,14 @@
-use std::{
-    fs::File,
-    io::{self, BufWriter, Write},
-};
+use std::io::{self, Write};
 
 use rayon::prelude::*;
 
-use geo::{ray::Ray, spatial_index::Intersection, Aabb, Vec3};
+use geo::{
+    polyline2::LineJoin, ray::Ray, spatial_index::Intersection, v2, v3, Aabb, Plane, Polyline2,
+    Vec2, Vec3,
+};
+use sketch_utils::svg::{Style, SvgWriter};
 
-use crate::{Camera, Polyline, Scene};
+use crate::{Camera, Occlusion, Polyline, Scene};
 
 /// Simple struct to hold the rendering params together.
 #[derive(Debug, PartialEq, Clone)]
@@ -19,6 +20,69 @@ pub struct Settings {
     /// the epsilon used to simplify the lines after having checked for point
     /// visibility.
     pub simplify_eps: f64,
+
+    /// how much the simplification epsilon grows with the distance from the
+    /// camera. The epsilon used for a point at distance `d` is
+    /// `simplify_eps * (1 + simplify_depth_scale * d)` so that far away
+    /// details, which are barely visible, are simplified more aggressively
+    /// than the foreground. 0 uses the same epsilon everywhere.
+    pub simplify_depth_scale: f64,
+
+    /// points closer than `near` to the camera, along its viewing direction,
+    /// are not drawn.
+    pub near: f64,
+
+    /// points farther than `far` from the camera, along its viewing
+    /// direction, are not drawn.
+    pub far: f64,
+
+    /// additional planes that cut away the parts of the scene that lie behind
+    /// them to allow partial cutaway drawings.
+    pub clip_planes: Vec<ClipPlane>,
+
+    /// optional region of interest in projected coordinates, from (-1, -1) to
+    /// (1, 1), given as min and max corners. Only the lines inside it are
+    /// drawn and they're scaled so that the region fills the whole output.
+    pub crop: Option<(Vec2, Vec2)>,
+}
+
+/// An infinite plane that clips away all the points behind it, that is the
+/// points on the opposite side its normal points to.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ClipPlane {
+    pub plane: Plane,
+}
+
+impl Settings {
+    /// Create new `Settings` with the given sampling and simplification
+    /// epsilons that don't clip anything besides what's outside the camera
+    /// frustum.
+    pub fn new(chop_eps: f64, simplify_eps: f64) -> Self {
+        Self {
+            chop_eps,
+            simplify_eps,
+            simplify_depth_scale: 0.0,
+            near: 0.0,
+            far: f64::INFINITY,
+            clip_planes: vec![],
+            crop: None,
+        }
+    }
+}
+
+impl ClipPlane {
+    /// Create a new `ClipPlane` passing through `origin` that keeps the
+    /// points on the side `normal` points to.
+    pub fn new(origin: Vec3, normal: Vec3) -> Self {
+        Self {
+            plane: Plane::new(origin, normal),
+        }
+    }
+
+    /// Check whether the given point is kept by the `ClipPlane`.
+    pub fn contains(&self, p: Vec3) -> bool {
+        self.plane.signed_distance(p) >= 0.0
+    }
 }
 
 /// The settings to render a set of `Polyline`s as returned by `render` to a
@@ -41,55 +105,82 @@ pub struct SvgSettings<'s> {
     /// stroke color of all the lines
     pub stroke: &'s str,
 
+    /// optional width of the plotter pen, if set each line is expanded into
+    /// parallel passes of this width that together are `stroke_width` wide
+    pub pen_width: Option<f64>,
+
     /// optional background color of the SVG
     pub background: Option<&'s str>,
 
     /// how many digits to keep in the floating point numbers dumped to the SVG
     pub digits: usize,
+
+    /// whether to write all the lines as a single path with relative
+    /// coordinates which is a lot smaller, but harder to edit
+    pub single_path: bool,
+
+    /// how many layers, from the foreground to the background, to split the
+    /// lines into according to their average projected depth, see
+    /// `depth_layers`. Each layer can then be plotted with a different pen.
+    /// 1 writes all the lines in a single group.
+    pub depth_layers: usize,
 }
 
 /// Render the given `Scene` using the given `Camera` and `Settings`.
 pub fn render(camera: &Camera, scene: &Scene, settings: &Settings) -> Vec<Polyline> {
-    // the projection matrix returns points from (-1,-1,-1) to (1,1,1), points
-    // outside this area are outside of the clipping region
-    let clip_box = Aabb::cuboid(Vec3::zero(), 2.0);
-
-    let is_visible = |p: Vec3| {
-        // NOTE: here we fire the ray from the camera to the point because doing
-        // the other way around wouldn't actually work since intersections,
-        // subtractions and unions don't always produce valid SDFs especially in
-        // the interior of the shape.
-        let d = p - camera.position();
-        let ray = Ray::new(camera.position(), d.normalized());
+    render_with_depth(camera, scene, settings)
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect()
+}
 
-        match scene.intersection(&ray) {
-            None => true,
-            Some((_, t)) => t.t() + settings.chop_eps >= d.norm(),
-        }
+/// Render the given `Scene` like `render`, but return each `Polyline` along
+/// with the average depth of its points in camera space, that is their
+/// distance from the camera along its viewing direction.
+pub fn render_with_depth(
+    camera: &Camera,
+    scene: &Scene,
+    settings: &Settings,
+) -> Vec<(Polyline, f64)> {
+    let project = visible_projector(camera, scene, settings);
+
+    let simplify = |path: Polyline, dists: &[f64], depth: f64| {
+        let path = path.simplified_with(|i, _| {
+            settings.simplify_eps * (1.0 + settings.simplify_depth_scale * dists[i])
+        });
+
+        (path, depth / dists.len() as f64)
     };
 
-    let paths: Vec<_> = scene.objects.iter().flat_map(|o| o.paths()).collect();
+    let paths = scene.paths();
 
     paths
         .par_iter()
-        .filter(|p| !p.is_empty())
-        .flat_map(|path: &Polyline| {
+        .filter(|(p, _)| !p.is_empty())
+        .flat_map(|(path, occlusion)| {
             let mut out = vec![];
 
+            // distance from the camera of each point in cur and the sum of
+            // their depths
+            let mut dists = vec![];
+            let mut depth = 0.0;
+
             let mut cur = Polyline::new();
             for p in path.chop(settings.chop_eps).iter() {
-                let projected = camera.project(p);
-
-                if clip_box.contains(&projected) && is_visible(p) {
+                if let Some(projected) = project(p, *occlusion) {
                     cur.push(projected);
+                    dists.push(p.dist(camera.ray_origin(p)));
+                    depth += camera.depth(p);
                 } else if !cur.is_empty() {
-                    out.push(cur.simplified(settings.simplify_eps));
+                    out.push(simplify(cur, &dists, depth));
                     cur = Polyline::new();
+                    dists.clear();
+                    depth = 0.0;
                 }
             }
 
             if !cur.is_empty() {
-                out.push(cur.simplified(settings.simplify_eps));
+                out.push(simplify(cur, &dists, depth));
             }
 
             out
@@ -97,88 +188,232 @@ pub fn render(camera: &Camera, scene: &Scene, settings: &Settings) -> Vec<Polyli
         .collect()
 }
 
+/// Return a function that projects a point of the `Scene`, belonging to an
+/// object with the given `Occlusion`, to the output space of `render` if it's
+/// visible, that is if it's inside the camera frustum and the crop region,
+/// it's not clipped and it's not hidden by other objects.
+pub(crate) fn visible_projector<'a>(
+    camera: &'a Camera,
+    scene: &'a Scene,
+    settings: &'a Settings,
+) -> impl Fn(Vec3, Occlusion) -> Option<Vec3> + Sync + 'a {
+    // the projection matrix returns points from (-1,-1,-1) to (1,1,1), points
+    // outside this area are outside of the clipping region
+    let (crop_min, crop_max) = settings.crop.unwrap_or((v2(-1, -1), v2(1, 1)));
+    let clip_box =
+        Aabb::new(v3(crop_min.x, crop_min.y, -1.0)).expanded(v3(crop_max.x, crop_max.y, 1.0));
+
+    // map the crop region to the whole projection area
+    let to_crop = move |p: Vec3| {
+        let Vec2 { x, y } = (v2(p.x, p.y) - crop_min) / (crop_max - crop_min) * 2.0 - 1.0;
+        v3(x, y, p.z)
+    };
+
+    let is_clipped = move |p: Vec3| {
+        let depth = camera.depth(p);
+
+        depth < settings.near
+            || depth > settings.far
+            || settings.clip_planes.iter().any(|plane| !plane.contains(p))
+    };
+
+    let is_visible = move |p: Vec3| {
+        // NOTE: here we fire the ray from the camera to the point because doing
+        // the other way around wouldn't actually work since intersections,
+        // subtractions and unions don't always produce valid SDFs especially in
+        // the interior of the shape.
+        let origin = camera.ray_origin(p);
+        let d = p - origin;
+        let ray = Ray::new(origin, d.normalized());
+
+        match scene.intersection(&ray) {
+            None => true,
+            Some((_, t)) => t.t() + settings.chop_eps >= d.norm(),
+        }
+    };
+
+    move |p: Vec3, occlusion: Occlusion| {
+        let projected = camera.project(p);
+
+        if clip_box.contains(&projected)
+            && !is_clipped(p)
+            && (occlusion == Occlusion::NonOccluding || is_visible(p))
+        {
+            Some(to_crop(projected))
+        } else {
+            None
+        }
+    }
+}
+
 /// Dump to `path` the given `Polyline`s with the given settings.
 ///
 /// Note: The input `Polyline`s must be in [-1, 1].
 pub fn dump_svg(path: &str, poylines: &[Polyline], settings: SvgSettings) -> io::Result<()> {
-    let f = File::create(path)?;
-    let mut f = BufWriter::new(f);
-
-    writeln!(
-        f,
-        r#"<?xml version="1.0" encoding="UTF-8"?>
-<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {} {}">"#,
-        settings.width, settings.height
-    )?;
+    let mut f = SvgWriter::create(path, (0.0, 0.0, settings.width, settings.height))?
+        .with_digits(settings.digits);
 
     if poylines.is_empty() {
-        return writeln!(f, "</svg>");
+        return f.finish()?.close();
     }
 
     if let Some(background) = settings.background {
-        writeln!(
-            f,
-            r#"<rect x="0" y="0" width="{:.digits$}" height="{:.digits$}" stroke="none" fill="{}"/>"#,
-            settings.width,
-            settings.height,
-            background,
-            digits = settings.digits
+        f.rect(
+            (0.0, 0.0),
+            (settings.width, settings.height),
+            &Style::new().with_stroke("none").with_fill(background),
         )?;
     }
 
+    if settings.depth_layers > 1 {
+        // the projected z grows with the depth so it can be used to sort the
+        // lines without knowing the camera
+        let paths = poylines
+            .iter()
+            .filter(|path| !path.is_empty())
+            .map(|path| {
+                let z = path.iter().map(|p| p.z).sum::<f64>() / path.len() as f64;
+                (path.clone(), z)
+            })
+            .collect::<Vec<_>>();
+
+        for (i, layer) in depth_layers(&paths, settings.depth_layers)
+            .iter()
+            .enumerate()
+        {
+            f.begin_layer(
+                &format!("depth-{i}"),
+                &settings.group_style(settings.stroke),
+            )?;
+            write_polylines(&mut f, layer, &settings, (0.0, 0.0))?;
+            f.end_group()?;
+        }
+
+        return f.finish()?.close();
+    }
+
     // all the lines share the same attributes hence using a group allows to
     // save a lot of space in the final SVG given that such attributes are not
     // repeated.
-    writeln!(
-        f,
-        r#"<g stroke="{}" stroke-width="{}" fill="none" >"#,
-        settings.stroke, settings.stroke_width
-    )?;
+    f.begin_group(&settings.group_style(settings.stroke))?;
+    write_polylines(&mut f, poylines, &settings, (0.0, 0.0))?;
 
-    // subtract the stroke width from the available dimensions so that the
-    // rendered lines are all inside the requested dimensions
-    let w2 = (settings.width - settings.stroke_width) / 2.0;
-    let h2 = (settings.height - settings.stroke_width) / 2.0;
+    f.finish()?.close()
+}
 
-    for path in poylines {
-        if path.is_empty() {
-            continue;
-        }
+/// Split the given `Polyline`s, along with their depth as returned by
+/// `render_with_depth`, into `n` layers of equal depth range sorted from the
+/// foreground to the background. Some layers might be empty.
+pub fn depth_layers(paths: &[(Polyline, f64)], n: usize) -> Vec<Vec<Polyline>> {
+    let n = n.max(1);
+    let mut layers = vec![vec![]; n];
+
+    let (min, max) = paths
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), (_, d)| {
+            (min.min(*d), max.max(*d))
+        });
+    let range = max - min;
+
+    for (path, d) in paths {
+        let i = if range > 0.0 {
+            (((d - min) / range * n as f64) as usize).min(n - 1)
+        } else {
+            0
+        };
+
+        layers[i].push(path.clone());
+    }
 
-        write!(f, r#"<polyline points=""#)?;
-        for mut p in path.iter() {
-            // invert y coordinate because in world space (0, 0) lies at the
-            // center and the y axis grows upwards while in image space (0, 0)
-            // is at the top left and y grows downwards.
-            p.y = -p.y;
-
-            // ignore z value as it is meaningless at this point given that the
-            // 3d point has already been projected to a 2d point.
-            write!(
-                f,
-                "{:.digits$},{:.digits$} ",
-                (p.x + 1.0) * w2,
-                (p.y + 1.0) * h2,
-                digits = settings.digits
-            )?;
+    layers
+}
+
+/// Write the given `Polyline`s, which must be in [-1, 1], scaled to fill the
+/// `width` x `height` area at `offset` of the SVG.
+pub(crate) fn write_polylines(
+    f: &mut SvgWriter<impl Write>,
+    poylines: &[Polyline],
+    settings: &SvgSettings,
+    offset: (f64, f64),
+) -> io::Result<()> {
+    let to_image = image_transform(settings, offset);
+
+    let paths = poylines
+        .iter()
+        .filter(|path| !path.is_empty())
+        .map(|path| path.iter().map(&to_image).collect::<Polyline2>());
+
+    write_image_polylines(f, paths, settings)
+}
+
+/// Write the given `Polyline2`s, which are already in image space, expanding
+/// them into multiple passes if the `pen_width` is set.
+pub(crate) fn write_image_polylines(
+    f: &mut SvgWriter<impl Write>,
+    paths: impl IntoIterator<Item = Polyline2>,
+    settings: &SvgSettings,
+) -> io::Result<()> {
+    let paths = paths
+        .into_iter()
+        .flat_map(|path| match settings.pen_width {
+            None => vec![path],
+            Some(pen_width) => {
+                path.stroke_passes(settings.stroke_width, pen_width, LineJoin::Round)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if settings.single_path {
+        f.polylines(
+            paths.iter().map(|path| path.iter().map(Into::into)),
+            &Style::new(),
+        )
+    } else {
+        for path in &paths {
+            f.polyline(path.iter().map(Into::into), &Style::new())?;
         }
-        writeln!(f, r#"" />"#)?;
+        Ok(())
     }
+}
 
-    writeln!(f, "</g>\n</svg>")?;
-
-    Ok(())
+/// Return the function mapping a point in [-1, 1] to the `width` x `height`
+/// area at `offset` of the SVG.
+pub(crate) fn image_transform(settings: &SvgSettings, offset: (f64, f64)) -> impl Fn(Vec3) -> Vec2 {
+    // subtract the stroke width from the available dimensions so that the
+    // rendered lines are all inside the requested dimensions
+    let w2 = (settings.width - settings.stroke_width) / 2.0;
+    let h2 = (settings.height - settings.stroke_width) / 2.0;
+    let offset = v2(offset.0, offset.1);
+
+    // invert y coordinate because in world space (0, 0) lies at the center
+    // and the y axis grows upwards while in image space (0, 0) is at the top
+    // left and y grows downwards.
+    //
+    // ignore z value as it is meaningless at this point given that the 3d
+    // point has already been projected to a 2d point.
+    move |p: Vec3| v2((p.x + 1.0) * w2, (1.0 - p.y) * h2) + offset
 }
 
-impl SvgSettings<'_> {
+impl<'s> SvgSettings<'s> {
     pub fn new(width: f64, height: f64) -> Self {
         Self {
             width,
             height,
             stroke_width: 1.0,
             stroke: "black",
+            pen_width: None,
             background: Some("white"),
             digits: 3,
+            single_path: false,
+            depth_layers: 1,
         }
     }
+
+    /// The style of the group containing the lines drawn with `stroke`.
+    pub(crate) fn group_style(&self, stroke: &'s str) -> Style<'s> {
+        Style::new()
+            .with_stroke(stroke)
+            .with_stroke_width(self.pen_width.unwrap_or(self.stroke_width))
+            .with_fill("none")
+    }
 }
@@ -0,0 +1,378 @@
+//! Describe a `Scene`, the `Camera` looking at it and how to render it in a
+//! JSON or TOML file.
+//!
+//! ```toml
+//! [camera]
+//! position = [-25.0, -25.0, -25.0]
+//! target = [0.0, 0.0, 0.0]
+//! up = [0.0, 1.0, 0.0]
+//! fovy = 60.0
+//! # use an orthographic projection this tall instead of a perspective one
+//! # ortho_height = 40.0
+//!
+//! [settings]
+//! chop_eps = 0.001
+//! simplify_eps = 0.001
+//! clip_planes = [{ origin = [0.0, 0.0, 0.0], normal = [0.0, 0.0, 1.0] }]
+//!
+//! [[objects]]
+//! type = "cube"
+//! min = [-5.0, -5.0, -5.0]
+//! max = [5.0, 5.0, 5.0]
+//!
+//! [[objects]]
+//! type = "sdf"
+//! sdf = { type = "torus", r1 = 20.0, r2 = 5.0 }
+//! divs = 100
+//! axis = "z"
+//! light_dir = [-1.0, 1.0, 1.0]
+//!
+//! [[objects]]
+//! type = "svg"
+//! path = "logo.svg"
+//! origin = [-5.0, -5.0, 10.0]
+//! u = [0.1, 0.0, 0.0]
+//! v = [0.0, 0.1, 0.0]
+//! ```
+//!
+//! Paths to mesh and SVG files are relative to the scene file.
+
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::Deserialize;
+use sketch_utils::loader;
+
+pub use sketch_utils::loader::Format;
+
+use geo::{mesh::load_mesh, sdf::SdfDesc, v2, Aabb, Axis};
+
+use crate::{
+    dump_svg, render, svg, Camera, ClipPlane, Cube, Decal, Facet, Object, Scene, SdfSlicer,
+    Settings, SvgSettings,
+};
+
+/// Result type returned by the scene file loaders.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Possible errors while loading a scene file.
+#[derive(Debug)]
+pub enum Error {
+    /// The scene file could not be read or it doesn't describe a scene.
+    Load(loader::Error),
+
+    /// An sdf has a boolean operation without any operand.
+    InvalidSdf,
+
+    /// A mesh referenced by the scene could not be loaded.
+    Mesh(PathBuf, geo::mesh::Error),
+
+    /// An SVG referenced by the scene could not be loaded.
+    Svg(PathBuf, svg::Error),
+}
+
+/// A fully loaded scene file, ready to be rendered.
+#[derive(Debug)]
+pub struct SceneFile {
+    pub camera: Camera,
+    pub scene: Scene,
+    pub settings: Settings,
+    pub svg: SvgOptions,
+}
+
+/// How to save the rendered paths to SVG, see `SvgSettings`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SvgOptions {
+    pub width: f64,
+    pub height: f64,
+    pub stroke_width: f64,
+    pub stroke: String,
+    pub pen_width: Option<f64>,
+    pub background: Option<String>,
+    pub digits: usize,
+    pub single_path: bool,
+    pub depth_layers: usize,
+}
+
+type V3 = [f64; 3];
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneDesc {
+    camera: CameraDesc,
+
+    #[serde(default)]
+    settings: SettingsDesc,
+
+    #[serde(default)]
+    svg: SvgOptions,
+
+    #[serde(default)]
+    objects: Vec<ObjectDesc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CameraDesc {
+    position: V3,
+    target: V3,
+    #[serde(default = "default_up")]
+    up: V3,
+    #[serde(default = "default_fovy")]
+    fovy: f64,
+
+    /// height of the region projected with an orthographic projection, if
+    /// unset a perspective projection with `fovy` is used instead
+    ortho_height: Option<f64>,
+
+    /// defaults to the aspect ratio of the SVG
+    aspect: Option<f64>,
+    #[serde(default = "default_near")]
+    near: f64,
+    #[serde(default = "default_far")]
+    far: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SettingsDesc {
+    chop_eps: f64,
+    simplify_eps: f64,
+    simplify_depth_scale: f64,
+    near: f64,
+    far: f64,
+    clip_planes: Vec<ClipPlaneDesc>,
+    crop: Option<CropDesc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ClipPlaneDesc {
+    origin: V3,
+    normal: V3,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CropDesc {
+    min: [f64; 2],
+    max: [f64; 2],
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum ObjectDesc {
+    Cube {
+        min: V3,
+        max: V3,
+    },
+    Mesh {
+        path: PathBuf,
+        #[serde(default)]
+        hatching_lines: u16,
+    },
+    Sdf {
+        sdf: SdfDesc,
+        divs: u16,
+        axis: Axis,
+        light_dir: V3,
+        #[serde(default)]
+        pad: f64,
+    },
+    Svg {
+        path: PathBuf,
+        origin: V3,
+        u: V3,
+        v: V3,
+    },
+}
+
+/// Load the scene file at `path` guessing its format by the file extension.
+///
+/// Relative mesh and SVG paths are resolved against the directory containing the
+/// scene file.
+pub fn load(path: impl AsRef<Path>) -> Result<SceneFile> {
+    let path = path.as_ref();
+    let desc: SceneDesc = loader::load(path)?;
+    desc.build(loader::base_dir(path))
+}
+
+/// Parse a scene from the given document written in the given `Format`.
+/// Relative mesh and SVG paths are resolved against `base_dir`.
+pub fn from_str(data: &str, format: Format, base_dir: impl AsRef<Path>) -> Result<SceneFile> {
+    let desc: SceneDesc = loader::from_str(data, format)?;
+    desc.build(base_dir.as_ref())
+}
+
+impl SceneFile {
+    /// Render the `Scene` and save the paths to the SVG at `path`.
+    pub fn render_svg(&self, path: &str) -> io::Result<()> {
+        let paths = render(&self.camera, &self.scene, &self.settings);
+
+        let svg = &self.svg;
+        let mut settings = SvgSettings::new(svg.width, svg.height);
+        settings.stroke_width = svg.stroke_width;
+        settings.stroke = &svg.stroke;
+        settings.pen_width = svg.pen_width;
+        settings.background = svg.background.as_deref();
+        settings.digits = svg.digits;
+        settings.single_path = svg.single_path;
+        settings.depth_layers = svg.depth_layers;
+
+        dump_svg(path, &paths, settings)
+    }
+}
+
+impl SceneDesc {
+    fn build(self, base_dir: &Path) -> Result<SceneFile> {
+        let cam = self.camera;
+        let aspect = cam.aspect.unwrap_or(self.svg.width / self.svg.height);
+        let camera = Camera::look_at(cam.position.into(), cam.target.into(), cam.up.into());
+        let camera = match cam.ortho_height {
+            Some(h) => camera.with_orthographic_projection(h * aspect, h, cam.near, cam.far),
+            None => camera.with_perspective_projection(cam.fovy, aspect, cam.near, cam.far),
+        };
+
+        let mut objects: Vec<Arc<dyn Object>> = vec![];
+        for obj in self.objects {
+            match obj {
+                ObjectDesc::Cube { min, max } => {
+                    objects.push(Arc::new(Cube::new(
+                        Aabb::new(min.into()).expanded(max.into()),
+                    )));
+                }
+                ObjectDesc::Mesh {
+                    path,
+                    hatching_lines,
+                } => {
+                    let path = base_dir.join(path);
+                    let mesh = load_mesh(&path).map_err(|e| Error::Mesh(path, e))?;
+
+                    for t in mesh.triangles() {
+                        objects.push(Arc::new(Facet::new(t).with_hatching_lines(hatching_lines)));
+                    }
+                }
+                ObjectDesc::Sdf {
+                    sdf,
+                    divs,
+                    axis,
+                    light_dir,
+                    pad,
+                } => {
+                    let sdf = sdf.build().ok_or(Error::InvalidSdf)?.pad_bbox(pad);
+                    objects.push(Arc::new(SdfSlicer::new(sdf, divs, axis, light_dir.into())));
+                }
+                ObjectDesc::Svg { path, origin, u, v } => {
+                    let path = base_dir.join(path);
+                    let decal = Decal::from_svg(&path, origin.into(), u.into(), v.into())
+                        .map_err(|e| Error::Svg(path, e))?;
+
+                    objects.push(Arc::new(decal));
+                }
+            }
+        }
+
+        let s = self.settings;
+        let mut settings = Settings::new(s.chop_eps, s.simplify_eps);
+        settings.simplify_depth_scale = s.simplify_depth_scale;
+        settings.near = s.near;
+        settings.far = s.far;
+        settings.clip_planes = s
+            .clip_planes
+            .into_iter()
+            .map(|p| ClipPlane::new(p.origin.into(), p.normal.into()))
+            .collect();
+        settings.crop = s
+            .crop
+            .map(|c| (v2(c.min[0], c.min[1]), v2(c.max[0], c.max[1])));
+
+        Ok(SceneFile {
+            camera,
+            scene: Scene::new(objects),
+            settings,
+            svg: self.svg,
+        })
+    }
+}
+
+fn default_up() -> V3 {
+    [0.0, 1.0, 0.0]
+}
+
+fn default_fovy() -> f64 {
+    60.0
+}
+
+fn default_near() -> f64 {
+    0.01
+}
+
+fn default_far() -> f64 {
+    1000.0
+}
+
+impl Default for SettingsDesc {
+    fn default() -> Self {
+        let settings = Settings::new(0.001, 0.001);
+
+        Self {
+            chop_eps: settings.chop_eps,
+            simplify_eps: settings.simplify_eps,
+            simplify_depth_scale: settings.simplify_depth_scale,
+            near: settings.near,
+            far: settings.far,
+            clip_planes: vec![],
+            crop: None,
+        }
+    }
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        let settings = SvgSettings::new(2048.0, 2048.0);
+
+        Self {
+            width: settings.width,
+            height: settings.height,
+            stroke_width: settings.stroke_width,
+            stroke: settings.stroke.to_string(),
+            pen_width: settings.pen_width,
+            background: settings.background.map(str::to_string),
+            digits: settings.digits,
+            single_path: settings.single_path,
+            depth_layers: settings.depth_layers,
+        }
+    }
+}
+
+impl From<loader::Error> for Error {
+    fn from(e: loader::Error) -> Self {
+        Error::Load(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Load(e) => write!(f, "{e}"),
+            Error::InvalidSdf => write!(f, "sdf boolean operations need at least one operand"),
+            Error::Mesh(path, e) => write!(f, "cannot load mesh {}: {e}", path.display()),
+            Error::Svg(path, e) => write!(f, "cannot load svg {}: {e}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Load(e) => Some(e),
+            Error::Mesh(_, e) => Some(e),
+            Error::Svg(_, e) => Some(e),
+            Error::InvalidSdf => None,
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Draw 2D sketches, that is polylines that are already in world space and
+//! don't need to be projected by a `Camera`, like the contours of an `Sdf2`.
+//!
+//! The sketch is fit into the SVG preserving its aspect ratio and it's saved
+//! with the same `SvgSettings` of the 3D renderer.
+
+use std::io;
+
+use geo::{polyline2, v2, Polyline2, Vec2};
+use sketch_utils::svg::{Style, SvgWriter};
+
+use crate::{write_image_polylines, SvgSettings};
+
+/// A 2D drawing made of polylines grouped in named layers, each drawn with its
+/// own stroke so that it can be plotted with a different pen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sketch<'s> {
+    layers: Vec<Layer<'s>>,
+    margin: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Layer<'s> {
+    name: &'s str,
+    stroke: &'s str,
+    paths: Vec<Polyline2>,
+}
+
+impl<'s> Sketch<'s> {
+    /// Create an empty `Sketch` without margins.
+    pub fn new() -> Self {
+        Self {
+            layers: vec![],
+            margin: 0.0,
+        }
+    }
+
+    /// Leave `margin` empty space, in SVG units, between the sketch and the
+    /// borders of the SVG.
+    pub fn with_margin(mut self, margin: f64) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Add the given paths to the layer called `name`, creating it with the
+    /// given `stroke` if it doesn't exist yet. Layers are drawn in the order
+    /// they're created.
+    pub fn with_layer(
+        mut self,
+        name: &'s str,
+        stroke: &'s str,
+        paths: impl IntoIterator<Item = Polyline2>,
+    ) -> Self {
+        let paths = paths.into_iter().filter(|p| !p.is_empty());
+
+        match self.layers.iter_mut().find(|l| l.name == name) {
+            Some(layer) => layer.paths.extend(paths),
+            None => self.layers.push(Layer {
+                name,
+                stroke,
+                paths: paths.collect(),
+            }),
+        }
+
+        self
+    }
+
+    /// Return the bounding box of all the paths in the `Sketch`, if any.
+    pub fn bbox(&self) -> Option<(Vec2, Vec2)> {
+        polyline2::bbox(
+            self.layers
+                .iter()
+                .flat_map(|l| l.paths.iter().flat_map(Polyline2::iter)),
+        )
+    }
+
+    /// Return the function mapping a point of the `Sketch` to the SVG so that
+    /// the whole sketch is centered and as big as possible within the margins.
+    fn image_transform(&self, settings: &SvgSettings) -> impl Fn(Vec2) -> Vec2 {
+        let (min, max) = self.bbox().unwrap_or((Vec2::zero(), Vec2::zero()));
+        let center = (min + max) / 2.0;
+        let size = max - min;
+
+        // like in `dump_svg` the stroke must fit in the available space too
+        let avail =
+            v2(settings.width, settings.height) - (2.0 * self.margin + settings.stroke_width);
+        let scale = match (size.x > 0.0, size.y > 0.0) {
+            (true, true) => f64::min(avail.x / size.x, avail.y / size.y),
+            (true, false) => avail.x / size.x,
+            (false, true) => avail.y / size.y,
+            (false, false) => 1.0,
+        };
+
+        let image_center = v2(settings.width, settings.height) / 2.0;
+
+        // y grows upwards in world space, but downwards in the SVG
+        move |p| image_center + v2(p.x - center.x, center.y - p.y) * scale
+    }
+}
+
+impl Default for Sketch<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Dump to `path` the given `Sketch` with the given settings. Each layer of the
+/// sketch is saved as an SVG layer, the `stroke` and `depth_layers` of the
+/// settings are ignored.
+pub fn dump_sketch_svg(path: &str, sketch: &Sketch, settings: SvgSettings) -> io::Result<()> {
+    let mut f = SvgWriter::create(path, (0.0, 0.0, settings.width, settings.height))?
+        .with_digits(settings.digits);
+
+    if let Some(background) = settings.background {
+        f.rect(
+            (0.0, 0.0),
+            (settings.width, settings.height),
+            &Style::new().with_stroke("none").with_fill(background),
+        )?;
+    }
+
+    let to_image = sketch.image_transform(&settings);
+
+    for layer in &sketch.layers {
+        f.begin_layer(layer.name, &settings.group_style(layer.stroke))?;
+        write_image_polylines(
+            &mut f,
+            layer
+                .paths
+                .iter()
+                .map(|p| p.iter().map(&to_image).collect()),
+            &settings,
+        )?;
+        f.end_group()?;
+    }
+
+    f.finish()?.close()
+}
@@ -0,0 +1,85 @@
+//! Render [stereo pairs][0] of a `Scene` to be plotted as stereograms.
+//!
+//! The `Scene` is rendered from two cameras slightly offset horizontally, one
+//! for each eye, and the two views are either placed side by side or overlaid
+//! in two different colors to be looked at through red/cyan glasses.
+//!
+//! [0]: https://en.wikipedia.org/wiki/Stereoscopy
+
+use std::io;
+
+use sketch_utils::svg::{Style, SvgWriter};
+
+use crate::{render, write_polylines, Camera, Polyline, Scene, Settings, SvgSettings};
+
+/// How to combine the views of the two eyes in a single SVG.
+#[derive(Debug, PartialEq, Clone)]
+pub enum StereoOutput<'s> {
+    /// Place the view of the left eye on the left and the one of the right
+    /// eye on the right with `gap` space in between, for parallel viewing.
+    SideBySide { gap: f64 },
+
+    /// Overlay the two views as two layers with the given colors, usually red
+    /// for the left eye and cyan for the right one.
+    Anaglyph { left: &'s str, right: &'s str },
+}
+
+/// Render the `Scene` as seen by the left and right eye of a viewer at
+/// `camera` whose eyes are `eye_separation` apart.
+pub fn render_stereo(
+    camera: &Camera,
+    scene: &Scene,
+    settings: &Settings,
+    eye_separation: f64,
+) -> (Vec<Polyline>, Vec<Polyline>) {
+    let offset = camera.right() * (eye_separation / 2.0);
+
+    let left = render(&camera.translated(-offset), scene, settings);
+    let right = render(&camera.translated(offset), scene, settings);
+
+    (left, right)
+}
+
+/// Dump to `path` the views of the left and right eye, as returned by
+/// `render_stereo`, combined according to `output`. The `width` and `height`
+/// of the `SvgSettings` are the dimensions of a single view.
+pub fn dump_stereo_svg(
+    path: &str,
+    (left, right): (&[Polyline], &[Polyline]),
+    settings: SvgSettings,
+    output: StereoOutput,
+) -> io::Result<()> {
+    let (w, h) = (settings.width, settings.height);
+
+    let (total_width, right_x) = match output {
+        StereoOutput::SideBySide { gap } => (2.0 * w + gap, w + gap),
+        StereoOutput::Anaglyph { .. } => (w, 0.0),
+    };
+
+    let mut f = SvgWriter::create(path, (0.0, 0.0, total_width, h))?.with_digits(settings.digits);
+
+    if let Some(background) = settings.background {
+        f.rect(
+            (0.0, 0.0),
+            (total_width, h),
+            &Style::new().with_stroke("none").with_fill(background),
+        )?;
+    }
+
+    let (left_stroke, right_stroke) = match output {
+        StereoOutput::SideBySide { .. } => (settings.stroke, settings.stroke),
+        StereoOutput::Anaglyph { left, right } => (left, right),
+    };
+
+    // a layer per eye so that each one can be plotted with its own pen
+    for (name, paths, stroke, x) in [
+        ("left", left, left_stroke, 0.0),
+        ("right", right, right_stroke, right_x),
+    ] {
+        f.begin_layer(name, &settings.group_style(stroke))?;
+        write_polylines(&mut f, paths, &settings, (x, 0.0))?;
+        f.end_group()?;
+    }
+
+    f.finish()?.close()
+}
@@ -0,0 +1,179 @@
+//! Render a `Scene` as [stipples][0] to produce dotwork style drawings.
+//!
+//! Instead of drawing the visible parts of the paths as continuous lines, the
+//! paths are sampled at regular intervals and a dot, or a short dash, is
+//! placed on a sample with a probability given by how dark the surface is at
+//! that point. Dark areas end up covered by many dots while the lit ones fade
+//! out.
+//!
+//! [0]: https://en.wikipedia.org/wiki/Stippling
+
+use std::io;
+
+use geo::Vec3;
+use rayon::prelude::*;
+use sketch_utils::svg::{Style, SvgWriter};
+
+use crate::{
+    image_transform, visible_projector, write_polylines, Camera, Polyline, Scene, Settings,
+    SvgSettings,
+};
+
+/// The mark placed for each stipple.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StippleShape {
+    /// A dot with a diameter equal to the stroke width.
+    Dot,
+
+    /// A short dash `length` long, in world units, following the direction of
+    /// the path.
+    Dash { length: f64 },
+}
+
+/// Settings of the stippling.
+#[derive(Debug, PartialEq, Clone)]
+pub struct StippleSettings {
+    /// distance between two consecutive samples of a path, the closer the
+    /// samples the denser the darkest areas
+    pub spacing: f64,
+
+    /// the shape of the marks
+    pub shape: StippleShape,
+}
+
+impl StippleSettings {
+    pub fn new(spacing: f64) -> Self {
+        Self {
+            spacing,
+            shape: StippleShape::Dot,
+        }
+    }
+
+    pub fn with_shape(mut self, shape: StippleShape) -> Self {
+        self.shape = shape;
+        self
+    }
+}
+
+/// Render the given `Scene` as stipples where `darkness` returns, for a point
+/// on a path, a value in [0, 1] that is the density of the marks around it.
+///
+/// Each returned `Polyline` is either a single point for a dot or a segment
+/// for a dash.
+pub fn render_stipples(
+    camera: &Camera,
+    scene: &Scene,
+    settings: &Settings,
+    stipple: &StippleSettings,
+    darkness: impl Fn(Vec3) -> f64 + Sync,
+) -> Vec<Polyline> {
+    let project = visible_projector(camera, scene, settings);
+
+    let paths = scene.paths();
+
+    paths
+        .par_iter()
+        .filter(|(p, _)| !p.is_empty())
+        .flat_map(|(path, occlusion)| {
+            let samples = path.resampled(stipple.spacing).points;
+            let mut out = vec![];
+
+            // diffuse the error along the path so that the density of the
+            // marks follows the darkness without any randomness
+            let mut acc = 0.5;
+
+            for (i, &p) in samples.iter().enumerate() {
+                acc += darkness(p).clamp(0.0, 1.0);
+                if acc < 1.0 {
+                    continue;
+                }
+                acc -= 1.0;
+
+                let Some(start) = project(p, *occlusion) else {
+                    continue;
+                };
+
+                let mut mark = Polyline::new();
+                mark.push(start);
+
+                if let StippleShape::Dash { length } = stipple.shape {
+                    let dir = match (samples.get(i + 1), i.checked_sub(1)) {
+                        (Some(&next), _) => next - p,
+                        (None, Some(prev)) => p - samples[prev],
+                        (None, None) => Vec3::zero(),
+                    };
+
+                    // fallback to a dot when the path is degenerate or the end
+                    // of the dash isn't visible
+                    let d = dir.norm();
+                    if let Some(end) = (d > 0.0)
+                        .then(|| project(p + dir / d * length, *occlusion))
+                        .flatten()
+                    {
+                        mark.push(end);
+                    }
+                }
+
+                out.push(mark);
+            }
+
+            out
+        })
+        .collect()
+}
+
+/// Return a `darkness` function for `render_stipples` that shades surfaces
+/// whose normal is given by `normal_at` with a directional light coming from
+/// `light_dir`.
+pub fn directional_light(
+    normal_at: impl Fn(Vec3) -> Vec3 + Sync,
+    light_dir: Vec3,
+) -> impl Fn(Vec3) -> f64 + Sync {
+    let light_dir = light_dir.normalized();
+
+    move |p| 1.0 - normal_at(p).dot(-light_dir).max(0.0)
+}
+
+/// Dump to `path` the stipples returned by `render_stipples` drawing dots as
+/// filled circles and dashes as lines.
+pub fn dump_stipple_svg(
+    path: &str,
+    stipples: &[Polyline],
+    settings: SvgSettings,
+) -> io::Result<()> {
+    let mut f = SvgWriter::create(path, (0.0, 0.0, settings.width, settings.height))?
+        .with_digits(settings.digits);
+
+    if stipples.is_empty() {
+        return f.finish()?.close();
+    }
+
+    if let Some(background) = settings.background {
+        f.rect(
+            (0.0, 0.0),
+            (settings.width, settings.height),
+            &Style::new().with_stroke("none").with_fill(background),
+        )?;
+    }
+
+    let (dots, dashes): (Vec<_>, Vec<_>) = stipples
+        .iter()
+        .filter(|s| !s.is_empty())
+        .cloned()
+        .partition(|s| s.len() == 1);
+
+    let to_image = image_transform(&settings, (0.0, 0.0));
+
+    f.begin_group(&Style::new().with_stroke("none").with_fill(settings.stroke))?;
+    for dot in &dots {
+        let c = to_image(dot.points[0]);
+        f.circle((c.x, c.y), settings.stroke_width / 2.0, &Style::new())?;
+    }
+    f.end_group()?;
+
+    f.begin_group(&settings.group_style(settings.stroke))?;
+    write_polylines(&mut f, &dashes, &settings, (0.0, 0.0))?;
+    f.end_group()?;
+
+    f.finish()?.close()
+}
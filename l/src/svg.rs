@@ -0,0 +1,345 @@
+//! Read the 2D paths of simple SVG files, e.g. logos or hand drawn elements,
+//! so that they can be placed in a `Scene` as a `Decal`.
+//!
+//! Only the geometry of the `path`, `polyline`, `polygon` and `line` elements
+//! is read, everything else including styles, groups and `transform`
+//! attributes is ignored. Curves are flattened into line segments while
+//! elliptical arcs are not supported.
+//!
+//! The points are in SVG user units, that is with the Y axis pointing down.
+
+use std::{fmt, fs, io, path::Path};
+
+use geo::{v2, Polyline2, Vec2};
+
+/// Number of segments each Bézier curve is flattened into.
+const CURVE_SEGMENTS: u16 = 16;
+
+/// Result type returned by the SVG loaders.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Possible errors while loading an SVG.
+#[derive(Debug)]
+pub enum Error {
+    /// IO error.
+    IoError(io::Error),
+
+    /// The geometry of an element is malformed or uses an unsupported
+    /// command, e.g. an elliptical arc.
+    BadPath(String),
+}
+
+/// Load the paths of the SVG file at `path`, see `parse_svg`.
+pub fn load_svg(path: impl AsRef<Path>) -> Result<Vec<Polyline2>> {
+    parse_svg(&fs::read_to_string(path)?)
+}
+
+/// Parse the paths of the given SVG document. Closed shapes end with their
+/// first point.
+pub fn parse_svg(data: &str) -> Result<Vec<Polyline2>> {
+    let mut paths = vec![];
+
+    let mut rest = data;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+
+        if let Some(comment) = rest.strip_prefix("!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+
+        let end = rest.find('>').ok_or_else(|| bad("unterminated element"))?;
+        let element = rest[..end].trim_end_matches('/');
+        let (tag, attrs) = element
+            .split_once(char::is_whitespace)
+            .unwrap_or((element, ""));
+        rest = &rest[end + 1..];
+
+        let attr =
+            |name: &str| attribute(attrs, name).ok_or_else(|| bad(format!("{tag} without {name}")));
+        match tag {
+            "path" => paths.extend(parse_path_data(attr("d")?)?),
+            "polyline" | "polygon" => {
+                let coords = parse_numbers(attr("points")?)?;
+                if coords.len() % 2 != 0 {
+                    return Err(bad(format!("odd number of coordinates in {tag}")));
+                }
+
+                let mut points = coords.chunks(2).map(|c| v2(c[0], c[1])).collect::<Vec<_>>();
+                if tag == "polygon" && !points.is_empty() {
+                    points.push(points[0]);
+                }
+                paths.push(Polyline2::from(points));
+            }
+            "line" => {
+                let coord = |name: &str| -> Result<f64> {
+                    let n = parse_numbers(attr(name)?)?;
+                    n.first()
+                        .copied()
+                        .ok_or_else(|| bad(format!("invalid {name}")))
+                };
+                paths.push(Polyline2::from(vec![
+                    v2(coord("x1")?, coord("y1")?),
+                    v2(coord("x2")?, coord("y2")?),
+                ]));
+            }
+            _ => {}
+        }
+    }
+
+    paths.retain(|p| p.len() > 1);
+    Ok(paths)
+}
+
+/// Parse the given [path data][0], that is the `d` attribute of a `path`
+/// element, into one polyline for each subpath.
+///
+/// [0]: https://www.w3.org/TR/SVG2/paths.html#PathData
+pub fn parse_path_data(d: &str) -> Result<Vec<Polyline2>> {
+    let mut paths: Vec<Polyline2> = vec![];
+    let mut current: Vec<Vec2> = vec![];
+
+    let mut pos = Vec2::zero();
+    let mut start = Vec2::zero();
+
+    // the last control point of the previous curve, if any, used by the smooth
+    // curve commands along with the command that set it
+    let mut last_ctrl: Option<(char, Vec2)> = None;
+
+    let mut tokens = Tokenizer::new(d);
+    let mut cmd = None;
+    while let Some(tok) = tokens.next_command(cmd)? {
+        cmd = Some(tok);
+
+        let relative = tok.is_ascii_lowercase();
+        let origin = if relative { pos } else { Vec2::zero() };
+        let point = |tokens: &mut Tokenizer| -> Result<Vec2> {
+            Ok(origin + v2(tokens.number()?, tokens.number()?))
+        };
+
+        let mut ctrl = None;
+        match tok.to_ascii_uppercase() {
+            'M' => {
+                pos = point(&mut tokens)?;
+                start = pos;
+                if current.len() > 1 {
+                    paths.push(Polyline2::from(current));
+                }
+                current = vec![pos];
+
+                // the coordinates following a move are implicit lines
+                cmd = Some(if relative { 'l' } else { 'L' });
+            }
+            'L' => {
+                pos = point(&mut tokens)?;
+                current.push(pos);
+            }
+            'H' => {
+                pos.x = origin.x + tokens.number()?;
+                current.push(pos);
+            }
+            'V' => {
+                pos.y = origin.y + tokens.number()?;
+                current.push(pos);
+            }
+            'C' | 'S' => {
+                let c1 = if tok.eq_ignore_ascii_case(&'C') {
+                    point(&mut tokens)?
+                } else {
+                    reflected(pos, last_ctrl, 'C')
+                };
+                let c2 = point(&mut tokens)?;
+                let end = point(&mut tokens)?;
+
+                current.extend((1..=CURVE_SEGMENTS).map(|i| {
+                    let t = f64::from(i) / f64::from(CURVE_SEGMENTS);
+                    let s = 1.0 - t;
+                    pos * s.powi(3)
+                        + c1 * (3.0 * s * s * t)
+                        + c2 * (3.0 * s * t * t)
+                        + end * t.powi(3)
+                }));
+
+                pos = end;
+                ctrl = Some(('C', c2));
+            }
+            'Q' | 'T' => {
+                let c = if tok.eq_ignore_ascii_case(&'Q') {
+                    point(&mut tokens)?
+                } else {
+                    reflected(pos, last_ctrl, 'Q')
+                };
+                let end = point(&mut tokens)?;
+
+                current.extend((1..=CURVE_SEGMENTS).map(|i| {
+                    let t = f64::from(i) / f64::from(CURVE_SEGMENTS);
+                    let s = 1.0 - t;
+                    pos * (s * s) + c * (2.0 * s * t) + end * (t * t)
+                }));
+
+                pos = end;
+                ctrl = Some(('Q', c));
+            }
+            'Z' => {
+                if current.len() > 1 {
+                    current.push(start);
+                    paths.push(Polyline2::from(current));
+                }
+                pos = start;
+                current = vec![pos];
+
+                // a close path doesn't take any argument
+                cmd = None;
+            }
+            _ => return Err(bad(format!("unsupported path command {tok}"))),
+        }
+        last_ctrl = ctrl;
+    }
+
+    if current.len() > 1 {
+        paths.push(Polyline2::from(current));
+    }
+
+    Ok(paths)
+}
+
+/// The first control point of a smooth curve, that is the reflection of the
+/// last control point of the previous curve if it has the same `kind`,
+/// otherwise the current point.
+fn reflected(pos: Vec2, last_ctrl: Option<(char, Vec2)>, kind: char) -> Vec2 {
+    match last_ctrl {
+        Some((k, c)) if k == kind => pos * 2.0 - c,
+        _ => pos,
+    }
+}
+
+/// Return the value of the attribute `name` among the given attributes of an
+/// element.
+fn attribute<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = attrs;
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].split_whitespace().last().unwrap_or("");
+        let value = rest[eq + 1..].trim_start();
+
+        let quote = value.chars().next()?;
+        if quote != '"' && quote != '\'' {
+            return None;
+        }
+        let end = value[1..].find(quote)?;
+        if key == name {
+            return Some(&value[1..=end]);
+        }
+
+        rest = &value[end + 2..];
+    }
+
+    None
+}
+
+fn parse_numbers(s: &str) -> Result<Vec<f64>> {
+    let mut tokens = Tokenizer::new(s);
+    let mut numbers = vec![];
+    while !tokens.is_empty() {
+        numbers.push(tokens.number()?);
+    }
+    Ok(numbers)
+}
+
+/// Split path data into commands and numbers, which can be separated by
+/// whitespace, commas or nothing at all as in `M.5-1.5.5`.
+struct Tokenizer<'a> {
+    data: &'a str,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn new(data: &'a str) -> Self {
+        Self { data }
+    }
+
+    fn skip_separators(&mut self) {
+        self.data = self
+            .data
+            .trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+
+    fn is_empty(&mut self) -> bool {
+        self.skip_separators();
+        self.data.is_empty()
+    }
+
+    /// Return the next command, or `implicit` if the data continues with the
+    /// arguments of another command of the same kind.
+    fn next_command(&mut self, implicit: Option<char>) -> Result<Option<char>> {
+        self.skip_separators();
+
+        match self.data.chars().next() {
+            None => Ok(None),
+            Some(c) if c.is_ascii_alphabetic() => {
+                self.data = &self.data[1..];
+                Ok(Some(c))
+            }
+            Some(c) => match implicit {
+                Some(cmd) => Ok(Some(cmd)),
+                None => Err(bad(format!("unexpected {c} in path data"))),
+            },
+        }
+    }
+
+    fn number(&mut self) -> Result<f64> {
+        self.skip_separators();
+
+        let b = self.data.as_bytes();
+        let mut end = 0;
+        if end < b.len() && (b[end] == b'-' || b[end] == b'+') {
+            end += 1;
+        }
+        let mut dot = false;
+        while end < b.len() && (b[end].is_ascii_digit() || (b[end] == b'.' && !dot)) {
+            dot |= b[end] == b'.';
+            end += 1;
+        }
+        if end < b.len() && (b[end] == b'e' || b[end] == b'E') {
+            end += 1;
+            if end < b.len() && (b[end] == b'-' || b[end] == b'+') {
+                end += 1;
+            }
+            while end < b.len() && b[end].is_ascii_digit() {
+                end += 1;
+            }
+        }
+
+        let n = self.data[..end]
+            .parse()
+            .map_err(|_| bad(format!("expected a number at {:?}", self.data)))?;
+        self.data = &self.data[end..];
+        Ok(n)
+    }
+}
+
+fn bad(msg: impl Into<String>) -> Error {
+    Error::BadPath(msg.into())
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::IoError(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::IoError(e) => write!(f, "io error: {e}"),
+            Error::BadPath(msg) => write!(f, "invalid svg path: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::IoError(e) => Some(e),
+            Error::BadPath(_) => None,
+        }
+    }
+}
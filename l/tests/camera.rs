@@ -0,0 +1,41 @@
+use geo::{v3, Vec3};
+
+use l::Camera;
+
+fn assert_close(a: Vec3, b: Vec3) {
+    assert!(a.dist(b) < 1e-9, "{a:?} != {b:?}");
+}
+
+#[test]
+fn test_look_at() {
+    let camera = Camera::look_at(v3(0, -10, 0), Vec3::zero(), v3(0, 0, 1))
+        .with_perspective_projection(60.0, 1.0, 0.1, 100.0);
+
+    assert_eq!(camera.position(), v3(0, -10, 0));
+    assert_close(camera.direction(), v3(0, 1, 0));
+    assert_close(camera.right(), v3(1, 0, 0));
+    assert!((camera.depth(v3(3, 5, 7)) - 15.0).abs() < 1e-9);
+
+    // the target is at the center of the image, up is up and right is right
+    let center = camera.project(Vec3::zero());
+    assert!(center.x.abs() < 1e-9 && center.y.abs() < 1e-9);
+    assert!(camera.project(v3(1, 0, 0)).x > 0.0);
+    assert!(camera.project(v3(0, 0, 1)).y > 0.0);
+
+    // points farther away are deeper in the clip volume
+    assert!(camera.project(v3(0, 10, 0)).z > center.z);
+}
+
+#[test]
+fn test_translated() {
+    let camera = Camera::look_at(v3(-4, -4, 2), v3(0, 0, 1), v3(0, 0, 1))
+        .with_orthographic_projection(4.0, 4.0, 0.1, 100.0);
+    let moved = camera.translated(v3(1, 2, 3));
+
+    assert_eq!(moved.position(), v3(-3, -2, 5));
+    assert_close(moved.direction(), camera.direction());
+    assert_close(moved.right(), camera.right());
+
+    let p = v3(0.5, -0.5, 1.5);
+    assert_close(moved.project(p + v3(1, 2, 3)), camera.project(p));
+}
@@ -0,0 +1,72 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use geo::{v3, Aabb, Vec3};
+use sketch_utils::golden::assert_svg_matches;
+
+use l::*;
+
+fn scene() -> (Scene, Camera) {
+    let objects = [
+        Aabb::cuboid(v3(0, 0, 0), 2.0),
+        Aabb::cuboid(v3(2, 1, 0), 1.0),
+        Aabb::cuboid(v3(-1, 2, 1), 1.5),
+    ]
+    .into_iter()
+    .map(|bbox| Arc::new(Cube::new(bbox)) as Arc<dyn Object>);
+
+    let camera = Camera::look_at(v3(-6, -5, 4), Vec3::zero(), v3(0, 0, 1))
+        .with_perspective_projection(45.0, 1.0, 0.01, 100.0);
+
+    (Scene::new(objects), camera)
+}
+
+fn paths(name: &str) -> (PathBuf, PathBuf) {
+    let output = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("l-{name}.svg"));
+    let golden = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{name}.svg"));
+
+    (output, golden)
+}
+
+#[test]
+fn test_golden_cubes() {
+    let (scene, camera) = scene();
+    let (output, golden) = paths("cubes");
+
+    let lines = render(&camera, &scene, &Settings::new(0.01, 0.001));
+    dump_svg(
+        output.to_str().unwrap(),
+        &lines,
+        SvgSettings::new(256.0, 256.0),
+    )
+    .expect("cannot save render");
+
+    assert_svg_matches(output, golden, 0.01);
+}
+
+#[test]
+fn test_golden_stipple() {
+    let (scene, camera) = scene();
+    let (output, golden) = paths("stipple");
+
+    let stipples = render_stipples(
+        &camera,
+        &scene,
+        &Settings::new(0.01, 0.001),
+        &StippleSettings::new(0.1),
+        |p: Vec3| (p.z + 1.0) / 3.0,
+    );
+    dump_stipple_svg(
+        output.to_str().unwrap(),
+        &stipples,
+        SvgSettings::new(256.0, 256.0),
+    )
+    .expect("cannot save render");
+
+    assert_svg_matches(output, golden, 0.01);
+}
@@ -0,0 +1,140 @@
+use std::path::Path;
+
+use geo::{ray::Ray, v2, v3};
+use sketch_utils::loader;
+
+use l::scene_file::{self, Error, Format};
+
+const TOML: &str = r#"
+[camera]
+position = [-10.0, 0.0, 0.0]
+target = [0.0, 0.0, 0.0]
+ortho_height = 4.0
+
+[settings]
+chop_eps = 0.01
+clip_planes = [{ origin = [0.0, 0.0, 0.0], normal = [0.0, 0.0, 1.0] }]
+crop = { min = [0.25, 0.25], max = [0.75, 0.75] }
+
+[svg]
+width = 400
+height = 200
+stroke = "red"
+
+[[objects]]
+type = "cube"
+min = [-1.0, -1.0, -1.0]
+max = [1.0, 1.0, 1.0]
+
+[[objects]]
+type = "sdf"
+sdf = { type = "sphere", radius = 1.0 }
+divs = 10
+axis = "z"
+light_dir = [0.0, 0.0, 1.0]
+"#;
+
+const JSON: &str = r#"{
+  "camera": { "position": [-10, 0, 0], "target": [0, 0, 0], "ortho_height": 4 },
+  "settings": {
+    "chop_eps": 0.01,
+    "clip_planes": [{ "origin": [0, 0, 0], "normal": [0, 0, 1] }],
+    "crop": { "min": [0.25, 0.25], "max": [0.75, 0.75] }
+  },
+  "svg": { "width": 400, "height": 200, "stroke": "red" },
+  "objects": [
+    { "type": "cube", "min": [-1, -1, -1], "max": [1, 1, 1] },
+    {
+      "type": "sdf", "sdf": { "type": "sphere", "radius": 1 },
+      "divs": 10, "axis": "z", "light_dir": [0, 0, 1]
+    }
+  ]
+}"#;
+
+#[test]
+fn test_parse() {
+    for (data, format) in [(TOML, Format::Toml), (JSON, Format::Json)] {
+        let file = scene_file::from_str(data, format, "").unwrap();
+
+        assert_eq!(file.camera.position(), v3(-10, 0, 0));
+        assert_eq!(file.settings.chop_eps, 0.01);
+        assert_eq!(file.settings.simplify_eps, 0.001);
+        assert_eq!(file.settings.clip_planes.len(), 1);
+        assert_eq!(file.settings.crop, Some((v2(0.25, 0.25), v2(0.75, 0.75))));
+        assert_eq!((file.svg.width, file.svg.height), (400.0, 200.0));
+        assert_eq!(file.svg.stroke, "red");
+
+        let (_, t) = file
+            .scene
+            .intersection(&Ray::new(v3(-10, 0, 0), v3(1, 0, 0)))
+            .unwrap();
+        assert!((t - 9.0).abs() < 1e-6, "{t}");
+    }
+}
+
+#[test]
+fn test_svg_object() {
+    let dir = std::env::temp_dir().join(format!("l-scene-file-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("logo.svg"), r#"<svg><path d="M0 0 L1 0"/></svg>"#).unwrap();
+
+    let scene = r#"
+[camera]
+position = [0.0, -10.0, 0.0]
+target = [0.0, 0.0, 0.0]
+
+[[objects]]
+type = "svg"
+path = "logo.svg"
+origin = [0.0, 0.0, 0.0]
+u = [1.0, 0.0, 0.0]
+v = [0.0, 0.0, 1.0]
+"#;
+    let file = scene_file::from_str(scene, Format::Toml, &dir);
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(file.is_ok());
+
+    let err = scene_file::from_str(scene, Format::Toml, &dir).unwrap_err();
+    assert!(matches!(&err, Error::Svg(path, _) if path == &dir.join("logo.svg")));
+    assert!(err.to_string().starts_with("cannot load svg "));
+    assert!(std::error::Error::source(&err).is_some());
+}
+
+#[test]
+fn test_errors() {
+    let mesh = TOML.replace(
+        "type = \"cube\"\nmin = [-1.0, -1.0, -1.0]\nmax = [1.0, 1.0, 1.0]",
+        "type = \"mesh\"\npath = \"missing.stl\"",
+    );
+    let err = scene_file::from_str(&mesh, Format::Toml, "meshes").unwrap_err();
+    assert!(matches!(
+        &err,
+        Error::Mesh(path, geo::mesh::Error::IoError(_)) if path == &Path::new("meshes").join("missing.stl")
+    ));
+    assert!(err.to_string().starts_with("cannot load mesh meshes"));
+    assert!(!err.to_string().contains("IoError"));
+    assert!(std::error::Error::source(&err).is_some());
+
+    let invalid_sdf = TOML.replace(
+        "{ type = \"sphere\", radius = 1.0 }",
+        "{ type = \"intersection\", of = [] }",
+    );
+    assert!(matches!(
+        scene_file::from_str(&invalid_sdf, Format::Toml, ""),
+        Err(Error::InvalidSdf)
+    ));
+
+    let missing_camera = JSON.replace("\"camera\"", "\"kamera\"");
+    let err = scene_file::from_str(&missing_camera, Format::Json, "").unwrap_err();
+    assert!(matches!(err, Error::Load(loader::Error::Json(_))));
+    assert!(std::error::Error::source(&err).is_some());
+
+    assert!(matches!(
+        scene_file::from_str(&TOML.replace("divs = 10", "divs = -10"), Format::Toml, ""),
+        Err(Error::Load(loader::Error::Toml(_)))
+    ));
+    assert!(matches!(
+        scene_file::load("scene.svg"),
+        Err(Error::Load(loader::Error::UnknownFormat(_)))
+    ));
+}
@@ -0,0 +1,204 @@
+use geo::{ray::Ray, spatial_index::Shape, v2, v3, Polyline2, Vec2};
+
+use l::{
+    svg::{self, parse_path_data, parse_svg},
+    Decal, Object,
+};
+
+fn points(paths: &[Polyline2]) -> Vec<Vec<Vec2>> {
+    paths.iter().map(|p| p.iter().collect()).collect()
+}
+
+fn assert_close(a: Vec2, b: Vec2) {
+    assert!((a - b).norm() < 1e-9, "{a:?} != {b:?}");
+}
+
+#[test]
+fn test_absolute_and_relative_commands() {
+    let expected = vec![vec![
+        v2(10, 20),
+        v2(30, 40),
+        v2(50, 40),
+        v2(50, 60),
+        v2(10, 20),
+    ]];
+
+    let absolute = parse_path_data("M 10 20 L 30 40 H 50 V 60 Z").unwrap();
+    assert_eq!(points(&absolute), expected);
+
+    let relative = parse_path_data("m 10 20 l 20 20 h 20 v 20 z").unwrap();
+    assert_eq!(points(&relative), expected);
+
+    // relative moves after a close path start from the start of the subpath
+    let subpaths = parse_path_data("M1 1 L2 1 L2 2 Z m 1 1 l 1 0").unwrap();
+    assert_eq!(
+        points(&subpaths),
+        vec![
+            vec![v2(1, 1), v2(2, 1), v2(2, 2), v2(1, 1)],
+            vec![v2(2, 2), v2(3, 2)]
+        ]
+    );
+
+    let moves = parse_path_data("M0 0 L1 0 M5 5 L6 5").unwrap();
+    assert_eq!(
+        points(&moves),
+        vec![vec![v2(0, 0), v2(1, 0)], vec![v2(5, 5), v2(6, 5)]]
+    );
+}
+
+#[test]
+fn test_implicit_commands() {
+    // the coordinates after a move are lines
+    let absolute = parse_path_data("M0 0 1 0 1 1").unwrap();
+    assert_eq!(points(&absolute), vec![vec![v2(0, 0), v2(1, 0), v2(1, 1)]]);
+
+    let relative = parse_path_data("m0 0 1 0 0 1").unwrap();
+    assert_eq!(points(&relative), vec![vec![v2(0, 0), v2(1, 0), v2(1, 1)]]);
+
+    let repeated = parse_path_data("M0 0 h1 2 v3,4").unwrap();
+    assert_eq!(
+        points(&repeated),
+        vec![vec![v2(0, 0), v2(1, 0), v2(3, 0), v2(3, 3), v2(3, 7)]]
+    );
+
+    // numbers can be separated just by their sign or their dot
+    let compact = parse_path_data("M.5-1.5.5.5l-1e1+2").unwrap();
+    assert_eq!(
+        points(&compact),
+        vec![vec![v2(0.5, -1.5), v2(0.5, 0.5), v2(-9.5, 2.5)]]
+    );
+}
+
+#[test]
+fn test_curves() {
+    let cubic = parse_path_data("M0 0 C0 1 1 1 1 0").unwrap();
+    assert_eq!(cubic.len(), 1);
+    assert_eq!(cubic[0].len(), 17);
+    assert_close(cubic[0].iter().nth(8).unwrap(), v2(0.5, 0.75));
+    assert_close(cubic[0].iter().last().unwrap(), v2(1, 0));
+
+    // the smooth curve mirrors the last control point, making an S
+    let smooth = parse_path_data("M0 0 C0 1 1 1 1 0 s1 -1 1 0").unwrap();
+    assert_eq!(smooth[0].len(), 33);
+    assert_close(smooth[0].iter().nth(24).unwrap(), v2(1.5, -0.75));
+    assert_close(smooth[0].iter().last().unwrap(), v2(2, 0));
+
+    let quadratic = parse_path_data("M0 0 Q1 2 2 0 T4 0").unwrap();
+    assert_eq!(quadratic[0].len(), 33);
+    assert_close(quadratic[0].iter().nth(8).unwrap(), v2(1, 1));
+    assert_close(quadratic[0].iter().nth(24).unwrap(), v2(3, -1));
+}
+
+#[test]
+fn test_arcs_are_unsupported() {
+    assert!(matches!(
+        parse_path_data("M0 0 A 1 1 0 0 1 2 0"),
+        Err(svg::Error::BadPath(_))
+    ));
+    assert!(parse_path_data("M0 0 a 1 1 0 0 1 2 0").is_err());
+}
+
+#[test]
+fn test_malformed_path_data() {
+    for d in [
+        "10 20",
+        "M 0",
+        "M 0 0 L x 1",
+        "M 0 0 L 1",
+        "M 0 0 Z 1 2",
+        "M 0 0 L 1 1 B 2 2",
+        "M 0 0 C 1 1 2 2",
+        "M - 1",
+    ] {
+        assert!(
+            matches!(parse_path_data(d), Err(svg::Error::BadPath(_))),
+            "{d}"
+        );
+    }
+
+    assert!(parse_path_data("").unwrap().is_empty());
+    assert!(parse_path_data("M 1 1").unwrap().is_empty());
+}
+
+#[test]
+fn test_parse_svg() {
+    let paths = parse_svg(
+        r#"<?xml version="1.0"?>
+<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+  <!-- <path d="M 0 0 L 9 9"/> -->
+  <g transform="translate(100 100)">
+    <path fill="none" d="M 0 0 L 1 1" />
+    <polyline points="0,0 1,0 1,1"/>
+    <polygon points='0 0 2 0 2 2'/>
+    <line x1="1" y1="2" x2="3" y2="4"/>
+    <circle cx="5" cy="5" r="1"/>
+    <path d="M 7 7"/>
+  </g>
+</svg>"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        points(&paths),
+        vec![
+            vec![v2(0, 0), v2(1, 1)],
+            vec![v2(0, 0), v2(1, 0), v2(1, 1)],
+            vec![v2(0, 0), v2(2, 0), v2(2, 2), v2(0, 0)],
+            vec![v2(1, 2), v2(3, 4)],
+        ]
+    );
+
+    for data in [
+        r#"<path d="M 0 0 L 1 1""#,
+        r#"<path stroke="red"/>"#,
+        r#"<path d=M0,0L1,1 />"#,
+        r#"<polyline points="0 0 1"/>"#,
+        r#"<line x1="0" y1="0" x2="1"/>"#,
+        r#"<line x1="a" y1="0" x2="1" y2="1"/>"#,
+    ] {
+        assert!(parse_svg(data).is_err(), "{data}");
+    }
+}
+
+#[test]
+fn test_decal() {
+    let paths = vec![Polyline2::from(vec![v2(0, 0), v2(2, 0), v2(2, 1)])];
+    let decal = Decal::on_plane(&paths, v3(1, 1, 1), v3(0, 1, 0), v3(0, 0, 1));
+
+    assert_eq!(
+        decal.paths()[0].iter().collect::<Vec<_>>(),
+        vec![v3(1, 1, 1), v3(1, 3, 1), v3(1, 3, 2)]
+    );
+
+    let bbox = decal.bbox();
+    assert_eq!((bbox.min(), bbox.max()), (v3(1, 1, 1), v3(1, 3, 2)));
+
+    // the card hides what's behind it only within the bounds of the paths
+    let t = decal
+        .intersection(&Ray::new(v3(-1.0, 2.5, 1.2), v3(1, 0, 0)))
+        .unwrap();
+    assert!((t - 2.0).abs() < 1e-9, "{t}");
+    assert!(decal
+        .intersection(&Ray::new(v3(-1, 4, 1.5), v3(1, 0, 0)))
+        .is_none());
+}
+
+#[test]
+fn test_decal_from_svg() {
+    let path = std::env::temp_dir().join(format!("l-decal-{}.svg", std::process::id()));
+    std::fs::write(&path, r#"<svg><path d="M 0 0 L 2 0 L 2 1"/></svg>"#).unwrap();
+
+    let decal = Decal::from_svg(&path, v3(0, 0, 0), v3(1, 0, 0), v3(0, 0, 1)).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // the Y axis of the SVG points down, i.e. against v
+    assert_eq!(
+        decal.paths()[0].iter().collect::<Vec<_>>(),
+        vec![v3(0, 0, 0), v3(2, 0, 0), v3(2, 0, -1)]
+    );
+
+    assert!(matches!(
+        Decal::from_svg(&path, v3(0, 0, 0), v3(1, 0, 0), v3(0, 0, 1)),
+        Err(svg::Error::IoError(_))
+    ));
+}
@@ -0,0 +1,404 @@
+//! Parse the command line arguments of a sketch.
+//!
+//! All the sketches accept the same set of common options so that they behave
+//! consistently:
+//!
+//! - `--seed N` the seed of the random number generator, a random one is
+//!   picked when missing;
+//! - `-o`, `--output PATH` where to save the result;
+//! - `--width N` and `--height N` the dimensions of the result;
+//! - `--samples N` the number of samples per pixel for the path traced
+//!   sketches;
+//! - `--open`/`--no-open` whether to open the result once done, it's opened by
+//!   default;
+//! - `-h`, `--help` print the usage and exit.
+//!
+//! On top of these, a sketch can declare its own flags, options with a value
+//! and positional arguments that are also listed in the usage.
+//!
+//! ```no_run
+//! use sketch_utils::args::Cli;
+//!
+//! let args = Cli::new("tower", "Render a random tower.")
+//!     .with_positional("N", "number of slabs, 10 by default")
+//!     .with_flag("--fill", "fill the faces instead of drawing their outlines")
+//!     .with_option("--spacing", "F", "distance between the slabs")
+//!     .parse();
+//!
+//! let n: u32 = args.positional(0).expect("invalid N").unwrap_or(10);
+//! let fill = args.flag("--fill");
+//! let spacing: f64 = args.option("--spacing").expect("invalid spacing").unwrap_or(1.0);
+//! let output = args.output_or("tower.svg");
+//! ```
+
+use std::{
+    env, fmt, process,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub type Result<T> = std::result::Result<T, ArgsError>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgsError {
+    /// The usage was requested via `-h` or `--help`.
+    Help,
+
+    /// The option was given without its value.
+    MissingValue(String),
+
+    /// The value of the option couldn't be parsed.
+    InvalidValue { option: String, value: String },
+
+    /// The option is neither a common one nor one declared by the sketch.
+    UnknownOption(String),
+
+    /// More positional arguments than the declared ones were given.
+    UnexpectedArgument(String),
+}
+
+/// The description of the command line of a sketch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cli {
+    name: &'static str,
+    about: &'static str,
+    flags: Vec<(&'static str, &'static str)>,
+    options: Vec<(&'static str, &'static str, &'static str)>,
+    positionals: Vec<(&'static str, &'static str)>,
+}
+
+/// The parsed command line arguments of a sketch.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Args {
+    pub seed: Option<u64>,
+    pub output: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub samples: Option<u32>,
+    pub open: bool,
+
+    flags: Vec<String>,
+    options: Vec<(String, String)>,
+    positionals: Vec<(&'static str, String)>,
+}
+
+impl Cli {
+    /// Create a new `Cli` for the sketch called `name` that only accepts the
+    /// common options.
+    pub fn new(name: &'static str, about: &'static str) -> Self {
+        Self {
+            name,
+            about,
+            flags: vec![],
+            options: vec![],
+            positionals: vec![],
+        }
+    }
+
+    /// Accept the given boolean flag, e.g. `--fill`.
+    pub fn with_flag(mut self, flag: &'static str, help: &'static str) -> Self {
+        self.flags.push((flag, help));
+        self
+    }
+
+    /// Accept the given option followed by its value, e.g. `--spacing 0.5`.
+    /// `value` is the name of the value shown in the usage.
+    pub fn with_option(
+        mut self,
+        option: &'static str,
+        value: &'static str,
+        help: &'static str,
+    ) -> Self {
+        self.options.push((option, value, help));
+        self
+    }
+
+    /// Accept another optional positional argument after the declared ones.
+    pub fn with_positional(mut self, name: &'static str, help: &'static str) -> Self {
+        self.positionals.push((name, help));
+        self
+    }
+
+    /// Parse the arguments of the current process, printing the usage and
+    /// exiting if requested or if they're not valid.
+    pub fn parse(&self) -> Args {
+        match self.try_parse_from(env::args().skip(1)) {
+            Ok(args) => args,
+            Err(ArgsError::Help) => {
+                println!("{}", self.usage());
+                process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("{e}\n\n{}", self.usage());
+                process::exit(2);
+            }
+        }
+    }
+
+    /// Parse the given arguments, not including the program name.
+    pub fn try_parse_from(&self, args: impl IntoIterator<Item = String>) -> Result<Args> {
+        let mut args = args.into_iter();
+        let mut parsed = Args {
+            open: true,
+            ..Args::default()
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| ArgsError::MissingValue(arg.clone()))
+            };
+
+            match arg.as_str() {
+                "--seed" => parsed.seed = Some(parse(&arg, value()?)?),
+                "-o" | "--output" => parsed.output = Some(value()?),
+                "--width" => parsed.width = Some(parse(&arg, value()?)?),
+                "--height" => parsed.height = Some(parse(&arg, value()?)?),
+                "--samples" => parsed.samples = Some(parse(&arg, value()?)?),
+                "--open" => parsed.open = true,
+                "--no-open" => parsed.open = false,
+                "-h" | "--help" => return Err(ArgsError::Help),
+                _ if self.flags.iter().any(|(f, _)| *f == arg) => parsed.flags.push(arg),
+                _ if self.options.iter().any(|(o, _, _)| *o == arg) => {
+                    let value = value()?;
+                    parsed.options.push((arg, value));
+                }
+                _ if arg.starts_with('-') && arg.parse::<f64>().is_err() => {
+                    return Err(ArgsError::UnknownOption(arg))
+                }
+                _ if parsed.positionals.len() < self.positionals.len() => {
+                    let (name, _) = self.positionals[parsed.positionals.len()];
+                    parsed.positionals.push((name, arg))
+                }
+                _ => return Err(ArgsError::UnexpectedArgument(arg)),
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// The usage message listing all the accepted arguments.
+    pub fn usage(&self) -> String {
+        let mut usage = format!("{}\n\nUsage: {} [OPTIONS]", self.about, self.name);
+        for (name, _) in &self.positionals {
+            usage += &format!(" [{name}]");
+        }
+
+        let options = self
+            .options
+            .iter()
+            .map(|(o, v, h)| (format!("{o} <{v}>"), *h))
+            .collect::<Vec<_>>();
+
+        let mut lines = vec![];
+        if !self.positionals.is_empty() {
+            lines.push("\nArguments:".to_string());
+            lines.extend(self.positionals.iter().map(|(n, h)| row(n, h)));
+        }
+
+        lines.push("\nOptions:".to_string());
+        lines.extend(
+            [
+                ("--seed <N>", "seed of the random number generator"),
+                ("-o, --output <PATH>", "where to save the result"),
+                ("--width <N>", "width of the result"),
+                ("--height <N>", "height of the result"),
+                ("--samples <N>", "number of samples per pixel"),
+                ("--open", "open the result once done, the default"),
+                ("--no-open", "do not open the result once done"),
+            ]
+            .iter()
+            .chain(&self.flags)
+            .map(|(n, h)| row(n, h))
+            .chain(options.iter().map(|(n, h)| row(n, h))),
+        );
+        lines.push(row("-h, --help", "print this message and exit"));
+
+        usage + "\n" + &lines.join("\n")
+    }
+}
+
+impl Args {
+    /// Whether the given sketch specific flag was passed.
+    pub fn flag(&self, flag: &str) -> bool {
+        self.flags.iter().any(|f| f == flag)
+    }
+
+    /// The value of the given sketch specific option, if passed, parsed as a
+    /// `T`. If the option is passed multiple times the last value wins.
+    ///
+    /// Returns `ArgsError::InvalidValue` if the value cannot be parsed.
+    pub fn option<T: FromStr>(&self, option: &str) -> Result<Option<T>> {
+        match self.options.iter().rev().find(|(o, _)| *o == option) {
+            None => Ok(None),
+            Some((option, value)) => parse(option, value.clone()).map(Some),
+        }
+    }
+
+    /// The `i`th positional argument, if given, parsed as a `T`.
+    ///
+    /// Returns `ArgsError::InvalidValue` if the argument cannot be parsed.
+    pub fn positional<T: FromStr>(&self, i: usize) -> Result<Option<T>> {
+        match self.positionals.get(i) {
+            None => Ok(None),
+            Some((name, arg)) => parse(name, arg.clone()).map(Some),
+        }
+    }
+
+    /// The seed passed via `--seed` or a random one based on the current time.
+    pub fn seed(&self) -> u64 {
+        self.seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos() as u64
+        })
+    }
+
+    /// The output path or the given default.
+    pub fn output_or(&self, default: &str) -> String {
+        self.output.clone().unwrap_or_else(|| default.to_string())
+    }
+
+    /// The dimensions of the result falling back to the given defaults.
+    pub fn dimensions_or(&self, width: u32, height: u32) -> (u32, u32) {
+        (self.width.unwrap_or(width), self.height.unwrap_or(height))
+    }
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgsError::Help => write!(f, "help requested"),
+            ArgsError::MissingValue(o) => write!(f, "missing value for {o}"),
+            ArgsError::InvalidValue { option, value } => {
+                write!(f, "invalid value for {option}: {value}")
+            }
+            ArgsError::UnknownOption(o) => write!(f, "unknown option {o}"),
+            ArgsError::UnexpectedArgument(a) => write!(f, "unexpected argument {a}"),
+        }
+    }
+}
+
+impl std::error::Error for ArgsError {}
+
+fn parse<T: FromStr>(option: &str, value: String) -> Result<T> {
+    value.parse().map_err(|_| ArgsError::InvalidValue {
+        option: option.to_string(),
+        value,
+    })
+}
+
+fn row(name: &str, help: &str) -> String {
+    format!("  {name:<24}{help}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cli() -> Cli {
+        Cli::new("tower", "Render a random tower.")
+            .with_positional("N", "number of slabs")
+            .with_positional("SCALE", "scale of the slabs")
+            .with_flag("--fill", "fill the faces")
+            .with_option("--spacing", "F", "distance between the slabs")
+    }
+
+    fn parse_args(args: &[&str]) -> Result<Args> {
+        cli().try_parse_from(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn test_parse() {
+        let args = parse_args(&[]).unwrap();
+        assert_eq!((args.seed, args.width, args.samples), (None, None, None));
+        assert!(args.open);
+        assert!(!args.flag("--fill"));
+        assert_eq!(args.positional::<u32>(0), Ok(None));
+        assert_eq!(args.option::<f64>("--spacing"), Ok(None));
+        assert_eq!(args.output_or("tower.svg"), "tower.svg");
+        assert_eq!(args.dimensions_or(800, 600), (800, 600));
+
+        let args = parse_args(&[
+            "--seed",
+            "42",
+            "12",
+            "-o",
+            "out.png",
+            "--width",
+            "100",
+            "--no-open",
+            "--fill",
+            "-0.5",
+            "--samples",
+            "8",
+            "--spacing",
+            "2",
+            "--spacing",
+            "-1.5",
+        ])
+        .unwrap();
+        assert_eq!(args.seed(), 42);
+        assert_eq!(args.output_or("tower.svg"), "out.png");
+        assert_eq!(args.dimensions_or(800, 600), (100, 600));
+        assert_eq!(args.samples, Some(8));
+        assert!(!args.open);
+        assert!(args.flag("--fill"));
+        assert_eq!(args.positional(0), Ok(Some(12_u32)));
+        assert_eq!(args.positional(1), Ok(Some(-0.5)));
+        assert_eq!(args.positional::<u32>(2), Ok(None));
+        assert_eq!(args.option("--spacing"), Ok(Some(-1.5)));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse_args(&["--width", "10", "-h"]), Err(ArgsError::Help));
+        assert_eq!(
+            parse_args(&["--seed"]),
+            Err(ArgsError::MissingValue("--seed".to_string()))
+        );
+        assert_eq!(
+            parse_args(&["--height", "tall"]),
+            Err(ArgsError::InvalidValue {
+                option: "--height".to_string(),
+                value: "tall".to_string()
+            })
+        );
+        assert_eq!(
+            parse_args(&["--open", "--outline"]),
+            Err(ArgsError::UnknownOption("--outline".to_string()))
+        );
+        assert_eq!(
+            parse_args(&["--spacing"]),
+            Err(ArgsError::MissingValue("--spacing".to_string()))
+        );
+        assert_eq!(
+            parse_args(&["1", "2", "3"]),
+            Err(ArgsError::UnexpectedArgument("3".to_string()))
+        );
+
+        let args = parse_args(&["ten", "2"]).unwrap();
+        assert_eq!(
+            args.positional::<u32>(0),
+            Err(ArgsError::InvalidValue {
+                option: "N".to_string(),
+                value: "ten".to_string()
+            })
+        );
+        assert_eq!(args.positional::<u8>(1), Ok(Some(2)));
+        assert_eq!(
+            parse_args(&["--spacing", "wide"])
+                .unwrap()
+                .option::<f64>("--spacing"),
+            Err(ArgsError::InvalidValue {
+                option: "--spacing".to_string(),
+                value: "wide".to_string()
+            })
+        );
+        assert_eq!(
+            args.positional::<bool>(1).map_err(|e| e.to_string()),
+            Err("invalid value for SCALE: 2".to_string())
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! Compare the outputs of the renderers against golden files stored in the
+//! repository to catch changes that silently alter the rendered images.
+//!
+//! The comparisons are tolerant on purpose so that small numerical
+//! differences, or different streams of random numbers, don't break the
+//! tests while real regressions do:
+//!
+//! - PPM images are compared after averaging the pixels in square blocks so
+//!   that the sampling noise cancels out;
+//! - SVGs are compared element by element regardless of their order with all
+//!   the numbers compared up to a given tolerance.
+//!
+//! Run the tests with the `UPDATE_GOLDEN` environment variable set to
+//! overwrite the golden files with the current outputs instead.
+
+use std::{env, fs, io, path::Path};
+
+/// Assert that the PPM image at `actual` matches the `golden` one.
+///
+/// Both images are split in `block` x `block` tiles and the average color of
+/// each tile must not differ by more than `tolerance`, in [0, 1], on any
+/// channel.
+pub fn assert_ppm_matches(
+    actual: impl AsRef<Path>,
+    golden: impl AsRef<Path>,
+    block: usize,
+    tolerance: f64,
+) {
+    let (actual, golden) = (actual.as_ref(), golden.as_ref());
+    if update(actual, golden) {
+        return;
+    }
+
+    let (aw, ah, a) = read_ppm(actual).expect("cannot read rendered PPM");
+    let (gw, gh, g) = read_ppm(golden).expect("cannot read golden PPM");
+    assert_eq!(
+        (aw, ah),
+        (gw, gh),
+        "{} has different dimensions than {}",
+        actual.display(),
+        golden.display()
+    );
+
+    let block = block.max(1);
+    for by in (0..ah).step_by(block) {
+        for bx in (0..aw).step_by(block) {
+            let (mut sa, mut sg, mut n) = ([0.0; 3], [0.0; 3], 0.0);
+
+            for y in by..(by + block).min(ah) {
+                for x in bx..(bx + block).min(aw) {
+                    let i = (y * aw + x) * 3;
+                    for c in 0..3 {
+                        sa[c] += f64::from(a[i + c]) / 255.0;
+                        sg[c] += f64::from(g[i + c]) / 255.0;
+                    }
+                    n += 1.0;
+                }
+            }
+
+            for c in 0..3 {
+                let d = (sa[c] - sg[c]).abs() / n;
+                assert!(
+                    d <= tolerance,
+                    "{} differs from {} by {d} in the block at ({bx}, {by})",
+                    actual.display(),
+                    golden.display()
+                );
+            }
+        }
+    }
+}
+
+/// Assert that the SVG at `actual` matches the `golden` one.
+///
+/// The SVGs must have the same elements, in any order, where the numbers in
+/// each element cannot differ by more than `tolerance`.
+pub fn assert_svg_matches(actual: impl AsRef<Path>, golden: impl AsRef<Path>, tolerance: f64) {
+    let (actual, golden) = (actual.as_ref(), golden.as_ref());
+    if update(actual, golden) {
+        return;
+    }
+
+    let a = svg_elements(&fs::read_to_string(actual).expect("cannot read rendered SVG"));
+    let g = svg_elements(&fs::read_to_string(golden).expect("cannot read golden SVG"));
+    assert_eq!(
+        a.len(),
+        g.len(),
+        "{} has a different number of elements than {}",
+        actual.display(),
+        golden.display()
+    );
+
+    for (ea, eg) in a.iter().zip(&g) {
+        let matches = ea.text == eg.text
+            && ea
+                .numbers
+                .iter()
+                .zip(&eg.numbers)
+                .all(|(na, ng)| (na - ng).abs() <= tolerance);
+
+        assert!(
+            matches,
+            "{} differs from {}: {ea:?} vs {eg:?}",
+            actual.display(),
+            golden.display()
+        );
+    }
+}
+
+/// An SVG element split into its text, where each number is replaced by a
+/// placeholder, and its numbers.
+#[derive(Debug)]
+struct Element {
+    text: String,
+    numbers: Vec<f64>,
+}
+
+/// Split the given SVG into its elements sorted so that equal elements of two
+/// SVGs end up in the same position.
+fn svg_elements(svg: &str) -> Vec<Element> {
+    let mut elements = svg
+        .split('<')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(|e| {
+            let mut text = String::new();
+            let mut numbers = vec![];
+
+            let mut chars = e.char_indices().peekable();
+            while let Some((start, c)) = chars.next() {
+                let is_number_start = c.is_ascii_digit()
+                    || ((c == '-' || c == '.')
+                        && chars.peek().is_some_and(|(_, n)| n.is_ascii_digit()));
+
+                if !is_number_start {
+                    text.push(c);
+                    continue;
+                }
+
+                let mut end = start + c.len_utf8();
+                while let Some(&(i, n)) = chars.peek() {
+                    if !(n.is_ascii_digit() || n == '.') {
+                        break;
+                    }
+                    end = i + n.len_utf8();
+                    chars.next();
+                }
+
+                match e[start..end].parse() {
+                    Ok(n) => {
+                        numbers.push(n);
+                        text.push('#');
+                    }
+                    Err(_) => text.push_str(&e[start..end]),
+                }
+            }
+
+            Element { text, numbers }
+        })
+        .collect::<Vec<_>>();
+
+    elements.sort_by(|a, b| {
+        a.text.cmp(&b.text).then_with(|| {
+            a.numbers
+                .iter()
+                .zip(&b.numbers)
+                .map(|(x, y)| x.total_cmp(y))
+                .find(|o| o.is_ne())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+    });
+
+    elements
+}
+
+/// Overwrite the `golden` file with `actual` if requested, returning whether
+/// it did so.
+fn update(actual: &Path, golden: &Path) -> bool {
+    if env::var_os("UPDATE_GOLDEN").is_none() {
+        return false;
+    }
+
+    if let Some(dir) = golden.parent() {
+        fs::create_dir_all(dir).expect("cannot create golden directory");
+    }
+    fs::copy(actual, golden).expect("cannot update golden file");
+
+    true
+}
+
+/// Read a binary PPM returning its dimensions and RGB data.
+fn read_ppm(path: &Path) -> io::Result<(usize, usize, Vec<u8>)> {
+    let data = fs::read(path)?;
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "invalid PPM");
+
+    // the header is made of the magic number, the dimensions and the maximum
+    // value separated by whitespace
+    let mut fields = vec![];
+    let mut i = 0;
+    while fields.len() < 4 {
+        while data.get(i).ok_or_else(invalid)?.is_ascii_whitespace() {
+            i += 1;
+        }
+        let start = i;
+        while !data.get(i).ok_or_else(invalid)?.is_ascii_whitespace() {
+            i += 1;
+        }
+        fields.push(std::str::from_utf8(&data[start..i]).map_err(|_| invalid())?);
+    }
+
+    let dim = |s: &str| s.parse::<usize>().map_err(|_| invalid());
+    if fields[0] != "P6" || fields[3] != "255" {
+        return Err(invalid());
+    }
+    let (w, h) = (dim(fields[1])?, dim(fields[2])?);
+
+    // a single whitespace separates the header from the pixels
+    let pixels = data.get(i + 1..).ok_or_else(invalid)?;
+    if pixels.len() != w * h * 3 {
+        return Err(invalid());
+    }
+
+    Ok((w, h, pixels.to_vec()))
+}
@@ -4,7 +4,14 @@ use std::{
     time::{SystemTime, UNIX_EPOCH},
 };
 
+pub mod args;
+pub mod golden;
+pub mod loader;
 pub mod opener;
+pub mod output;
+pub mod svg;
+
+pub use output::OutputSession;
 
 #[macro_export]
 macro_rules! chrono {
@@ -0,0 +1,196 @@
+//! Read scene description files written either in JSON or in TOML.
+//!
+//! This is the part shared by the `scene_file` modules of the renderers: they
+//! only define what a scene looks like and how to build it, while reading the
+//! file and picking the format by its extension is done here.
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::de::DeserializeOwned;
+
+/// Result type returned by the loaders.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Possible errors while loading a scene file.
+#[derive(Debug)]
+pub enum Error {
+    /// IO error.
+    IoError(io::Error),
+
+    /// The scene file is not valid JSON or it doesn't describe a scene.
+    Json(serde_json::Error),
+
+    /// The scene file is not valid TOML or it doesn't describe a scene.
+    Toml(toml::de::Error),
+
+    /// The scene file extension is neither json nor toml.
+    UnknownFormat(PathBuf),
+}
+
+/// The formats a scene file can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Toml,
+}
+
+impl Format {
+    /// Guess the format of the file at `path` by its extension.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Ok(Format::Json),
+            Some("toml") => Ok(Format::Toml),
+            _ => Err(Error::UnknownFormat(path.to_path_buf())),
+        }
+    }
+}
+
+/// Load the file at `path` guessing its format by the file extension.
+pub fn load<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T> {
+    let path = path.as_ref();
+    let format = Format::from_path(path)?;
+
+    from_str(&fs::read_to_string(path)?, format)
+}
+
+/// Parse the given document written in the given `Format`.
+pub fn from_str<T: DeserializeOwned>(data: &str, format: Format) -> Result<T> {
+    let desc = match format {
+        Format::Json => serde_json::from_str(data)?,
+        Format::Toml => toml::from_str(data)?,
+    };
+
+    Ok(desc)
+}
+
+/// The directory the relative paths in the scene file at `path` are resolved
+/// against, that is the one containing the scene file.
+pub fn base_dir(path: &Path) -> &Path {
+    path.parent().unwrap_or_else(|| Path::new(""))
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::IoError(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Error::Toml(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::IoError(e) => write!(f, "io error: {e}"),
+            Error::Json(e) => write!(f, "invalid json scene: {e}"),
+            Error::Toml(e) => write!(f, "invalid toml scene: {e}"),
+            Error::UnknownFormat(path) => write!(
+                f,
+                "scene files must be either .json or .toml, got {}",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::IoError(e) => Some(e),
+            Error::Json(e) => Some(e),
+            Error::Toml(e) => Some(e),
+            Error::UnknownFormat(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Desc {
+        name: String,
+        size: [f64; 2],
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(Format::from_path("a/b.json").unwrap(), Format::Json);
+        assert_eq!(Format::from_path("b.toml").unwrap(), Format::Toml);
+        assert!(matches!(
+            Format::from_path("b.yaml"),
+            Err(Error::UnknownFormat(_))
+        ));
+        assert!(matches!(
+            Format::from_path("toml"),
+            Err(Error::UnknownFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_str() {
+        let expected = Desc {
+            name: "box".to_string(),
+            size: [1.0, 2.5],
+        };
+
+        let json = r#"{ "name": "box", "size": [1, 2.5] }"#;
+        assert_eq!(from_str::<Desc>(json, Format::Json).unwrap(), expected);
+
+        let toml = "name = \"box\"\nsize = [1.0, 2.5]";
+        assert_eq!(from_str::<Desc>(toml, Format::Toml).unwrap(), expected);
+
+        assert!(matches!(
+            from_str::<Desc>(toml, Format::Json),
+            Err(Error::Json(_))
+        ));
+        assert!(matches!(
+            from_str::<Desc>(json, Format::Toml),
+            Err(Error::Toml(_))
+        ));
+        assert!(matches!(
+            from_str::<Desc>(r#"{ "name": "box" }"#, Format::Json),
+            Err(Error::Json(_))
+        ));
+        assert!(matches!(
+            from_str::<Desc>("name = \"box\"\nsize = [1.0, 2.5]\ncolor = 1", Format::Toml),
+            Err(Error::Toml(_))
+        ));
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("sketch-utils-loader-{}.toml", std::process::id()));
+        fs::write(&path, "name = \"box\"\nsize = [1.0, 2.5]").unwrap();
+
+        let desc = load::<Desc>(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(desc.unwrap().size, [1.0, 2.5]);
+        assert_eq!(base_dir(&path), dir);
+
+        assert!(matches!(load::<Desc>(&path), Err(Error::IoError(_))));
+
+        let err = load::<Desc>(dir.join("scene.txt")).unwrap_err();
+        assert!(matches!(err, Error::UnknownFormat(_)));
+        assert!(std::error::Error::source(&err).is_none());
+    }
+}
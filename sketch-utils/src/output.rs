@@ -0,0 +1,215 @@
+//! Keep track of how the outputs of the sketches were generated.
+//!
+//! An `OutputSession` picks the path of the output like `sketch_output_path`
+//! and, once the sketch is done, writes a JSON sidecar next to it, i.e.
+//! `helix-123.svg.json` for `helix-123.svg`, with the seed, the parameters,
+//! the git commit and the time it took to render so that any output can be
+//! reproduced after the fact.
+
+use std::{
+    fmt, fs, io,
+    path::Path,
+    process::Command,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Serialize, Serializer};
+
+use crate::sketch_output_path;
+
+/// The output of a single run of a sketch.
+#[derive(Debug, Clone)]
+pub struct OutputSession {
+    path: String,
+    seed: Option<u64>,
+    params: Vec<(String, String)>,
+    start: Instant,
+}
+
+/// The content of the metadata sidecar.
+#[derive(Debug, Serialize)]
+struct Sidecar<'a> {
+    output: &'a str,
+    seed: Option<u64>,
+    #[serde(serialize_with = "ordered_params")]
+    params: &'a [(String, String)],
+    git_commit: Option<String>,
+    git_dirty: Option<bool>,
+    timestamp: u64,
+    duration_ms: u64,
+}
+
+impl OutputSession {
+    /// Start a new session for the given sketch saving the output in a new
+    /// file as returned by `sketch_output_path`.
+    pub fn new(sketch: &str) -> io::Result<Self> {
+        Ok(Self::at(sketch_output_path(sketch)?))
+    }
+
+    /// Start a new session saving the output at the given path.
+    pub fn at(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            seed: None,
+            params: vec![],
+            start: Instant::now(),
+        }
+    }
+
+    /// Record the seed of the random number generator.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Record a parameter of the sketch, later parameters with the same name
+    /// replace the previous ones.
+    pub fn with_param(mut self, name: &str, value: impl fmt::Display) -> Self {
+        self.set_param(name, value);
+        self
+    }
+
+    /// Record a parameter of the sketch that is known only after the session
+    /// started, e.g. a randomly picked one.
+    pub fn set_param(&mut self, name: &str, value: impl fmt::Display) {
+        let value = value.to_string();
+
+        match self.params.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = value,
+            None => self.params.push((name.to_string(), value)),
+        }
+    }
+
+    /// The path where the sketch must save its output.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The path of the metadata sidecar.
+    pub fn sidecar_path(&self) -> String {
+        format!("{}.json", self.path)
+    }
+
+    /// Finish the session writing the metadata sidecar and returning the path
+    /// of the output.
+    pub fn finish(self) -> io::Result<String> {
+        let duration = self.start.elapsed();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let (git_commit, git_dirty) = git_commit().unzip();
+        let sidecar = Sidecar {
+            output: &self.path,
+            seed: self.seed,
+            params: &self.params,
+            git_commit,
+            git_dirty,
+            timestamp,
+            duration_ms: duration.as_millis() as u64,
+        };
+
+        let mut json = serde_json::to_string_pretty(&sidecar).map_err(io::Error::other)?;
+        json.push('\n');
+
+        fs::write(self.sidecar_path(), json)?;
+
+        Ok(self.path)
+    }
+}
+
+/// The current git commit of the repository and whether there are
+/// uncommitted changes, if git is available.
+fn git_commit() -> Option<(String, bool)> {
+    let repo = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let git = |args: &[&str]| {
+        let out = Command::new("git")
+            .args(args)
+            .current_dir(&repo)
+            .output()
+            .ok()?;
+
+        out.status
+            .success()
+            .then(|| String::from_utf8_lossy(&out.stdout).trim().to_string())
+    };
+
+    let commit = git(&["rev-parse", "HEAD"])?;
+    let dirty = !git(&["status", "--porcelain"])?.is_empty();
+
+    Some((commit, dirty))
+}
+
+/// Serialize the parameters as a JSON object keeping them in the order they
+/// were recorded.
+fn ordered_params<S: Serializer>(
+    params: &[(String, String)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(params.iter().map(|(name, value)| (name, value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    #[test]
+    fn test_sidecar() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("sketch-utils-output-{}.svg", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let mut session = OutputSession::at(path)
+            .with_seed(42)
+            .with_param("n", 10)
+            .with_param("title", "a \"quoted\"\nname")
+            .with_param("fill", false);
+        session.set_param("n", 12);
+        session.set_param("angle", 0.5);
+        assert_eq!(session.path(), path);
+        assert_eq!(session.sidecar_path(), format!("{path}.json"));
+
+        let sidecar_path = session.sidecar_path();
+        assert_eq!(session.finish().unwrap(), path);
+
+        let data = fs::read_to_string(&sidecar_path).unwrap();
+        fs::remove_file(&sidecar_path).unwrap();
+
+        let sidecar: Value = serde_json::from_str(&data).unwrap();
+        assert_eq!(sidecar["output"], path);
+        assert_eq!(sidecar["seed"], 42);
+        assert_eq!(
+            sidecar["params"],
+            json!({"n": "12", "title": "a \"quoted\"\nname", "fill": "false", "angle": "0.5"})
+        );
+        assert!(sidecar["git_commit"].is_string() || sidecar["git_commit"].is_null());
+        assert!(sidecar["timestamp"].as_u64().unwrap() > 0);
+        assert!(sidecar["duration_ms"].is_u64());
+
+        // the parameters are listed in the order they were first recorded
+        let names = ["\"n\"", "\"title\"", "\"fill\"", "\"angle\""].map(|n| data.find(n).unwrap());
+        assert!(names.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_sidecar_without_seed() {
+        let path = std::env::temp_dir().join(format!(
+            "sketch-utils-output-empty-{}.png",
+            std::process::id()
+        ));
+
+        let session = OutputSession::at(path.to_str().unwrap());
+        let sidecar_path = session.sidecar_path();
+        session.finish().unwrap();
+
+        let data = fs::read_to_string(&sidecar_path).unwrap();
+        fs::remove_file(&sidecar_path).unwrap();
+
+        let sidecar: Value = serde_json::from_str(&data).unwrap();
+        assert!(sidecar["seed"].is_null());
+        assert_eq!(sidecar["params"], json!({}));
+    }
+}
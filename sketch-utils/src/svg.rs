@@ -0,0 +1,904 @@
+//! Minimal streaming [SVG][0] writer shared by the renderers.
+//!
+//! Elements are written as soon as they're added so that even huge drawings
+//! don't need to be kept in memory. Files with the `.svgz` extension are
+//! transparently gzip compressed.
+//!
+//! [0]: https://developer.mozilla.org/en-US/docs/Web/SVG
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use flate2::{write::GzEncoder, Compression};
+
+/// A point in the SVG coordinate space.
+pub type XY = (f64, f64);
+
+/// A SVG writer that writes elements to the underlying writer as they are
+/// added.
+///
+/// The document is only complete after `SvgWriter::finish` is called.
+#[derive(Debug)]
+pub struct SvgWriter<W: Write> {
+    out: W,
+    digits: usize,
+    open_groups: usize,
+}
+
+/// The presentation attributes of an element or a group of elements.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Style<'s> {
+    id: Option<&'s str>,
+    stroke: Option<&'s str>,
+    stroke_width: Option<f64>,
+    fill: Option<&'s str>,
+    opacity: Option<f64>,
+    dasharray: Option<&'s str>,
+}
+
+/// A SVG file on disk as created by `SvgWriter::create`.
+///
+/// The content is gzip compressed if the file has the `.svgz` extension.
+#[derive(Debug)]
+pub struct SvgFile {
+    out: SvgFileOut,
+}
+
+#[derive(Debug)]
+enum SvgFileOut {
+    Plain(BufWriter<File>),
+    Compressed(GzEncoder<BufWriter<File>>),
+}
+
+/// A single command of a SVG path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCommand {
+    /// Start a new sub-path at the given point.
+    MoveTo(XY),
+
+    /// Draw a straight line to the given point.
+    LineTo(XY),
+
+    /// Draw a quadratic Bézier curve with the given control point to the
+    /// given point.
+    QuadTo(XY, XY),
+
+    /// Draw a cubic Bézier curve with the given control points to the given
+    /// point.
+    CubicTo(XY, XY, XY),
+
+    /// Close the current sub-path.
+    Close,
+}
+
+impl SvgWriter<SvgFile> {
+    /// Create the file at `path` and start a new SVG document in it with the
+    /// given viewbox, see `SvgWriter::new`.
+    ///
+    /// If `path` has the `.svgz` extension then the document is gzip
+    /// compressed, remember to call `SvgFile::close` on the file returned by
+    /// `SvgWriter::finish` to write the end of the compressed stream.
+    pub fn create(path: &str, viewbox: (f64, f64, f64, f64)) -> io::Result<Self> {
+        Self::new(SvgFile::create(path)?, viewbox)
+    }
+}
+
+impl SvgFile {
+    /// Create the file at the given path, compressing it if it has the
+    /// `.svgz` extension.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let f = BufWriter::new(File::create(path)?);
+
+        let compressed = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("svgz"));
+
+        let out = if compressed {
+            SvgFileOut::Compressed(GzEncoder::new(f, Compression::default()))
+        } else {
+            SvgFileOut::Plain(f)
+        };
+
+        Ok(Self { out })
+    }
+
+    /// Write any pending data and close the file.
+    pub fn close(self) -> io::Result<()> {
+        match self.out {
+            SvgFileOut::Plain(mut f) => f.flush(),
+            SvgFileOut::Compressed(f) => f.finish()?.flush(),
+        }
+    }
+}
+
+impl Write for SvgFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.out {
+            SvgFileOut::Plain(f) => f.write(buf),
+            SvgFileOut::Compressed(f) => f.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.out {
+            SvgFileOut::Plain(f) => f.flush(),
+            SvgFileOut::Compressed(f) => f.flush(),
+        }
+    }
+}
+
+impl<W: Write> SvgWriter<W> {
+    /// Start a new SVG document with the given viewbox in the form (min_x,
+    /// min_y, width, height).
+    pub fn new(mut out: W, (x, y, w, h): (f64, f64, f64, f64)) -> io::Result<Self> {
+        writeln!(
+            out,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" viewBox="{x} {y} {w} {h}">"#,
+        )?;
+
+        Ok(Self {
+            out,
+            digits: 4,
+            open_groups: 0,
+        })
+    }
+
+    /// Set how many digits to keep in the coordinates of the elements.
+    pub fn with_digits(mut self, digits: usize) -> Self {
+        self.digits = digits;
+        self
+    }
+
+    /// Add a rectangle with the top left corner at the given point.
+    pub fn rect(&mut self, (x, y): XY, (w, h): XY, style: &Style) -> io::Result<()> {
+        let d = self.digits;
+        write!(
+            self.out,
+            r#"<rect x="{x:.d$}" y="{y:.d$}" width="{w:.d$}" height="{h:.d$}""#
+        )?;
+        style.write(&mut self.out)?;
+        writeln!(self.out, "/>")
+    }
+
+    /// Add a circle centered at the given point.
+    pub fn circle(&mut self, (x, y): XY, r: f64, style: &Style) -> io::Result<()> {
+        let d = self.digits;
+        write!(self.out, r#"<circle cx="{x:.d$}" cy="{y:.d$}" r="{r:.d$}""#)?;
+        style.write(&mut self.out)?;
+        writeln!(self.out, "/>")
+    }
+
+    /// Add a polyline passing through all the given points.
+    pub fn polyline(&mut self, pts: impl IntoIterator<Item = XY>, style: &Style) -> io::Result<()> {
+        let d = self.digits;
+        write!(self.out, r#"<polyline points=""#)?;
+        for (x, y) in pts {
+            write!(self.out, "{x:.d$},{y:.d$} ")?;
+        }
+        write!(self.out, r#"""#)?;
+        style.write(&mut self.out)?;
+        writeln!(self.out, " />")
+    }
+
+    /// Add all the given polylines as a single path.
+    ///
+    /// The path uses relative coordinates which are usually much shorter than
+    /// the absolute ones and it's written once for all the lines instead of
+    /// once per line, making the final file a lot smaller than adding each
+    /// line with `SvgWriter::polyline`.
+    pub fn polylines<L>(
+        &mut self,
+        lines: impl IntoIterator<Item = L>,
+        style: &Style,
+    ) -> io::Result<()>
+    where
+        L: IntoIterator<Item = XY>,
+    {
+        // work on the coordinates rounded to the requested digits so that the
+        // rounding errors don't accumulate when summing up the relative
+        // offsets
+        let d = self.digits;
+        let scale = 10_f64.powi(d as i32);
+        let quantize = |(x, y): XY| ((x * scale).round() as i64, (y * scale).round() as i64);
+
+        write!(self.out, r#"<path d=""#)?;
+
+        let mut cur = None;
+        for l in lines {
+            let mut pts = l.into_iter().map(quantize);
+
+            let Some(start) = pts.next() else {
+                continue;
+            };
+
+            match cur {
+                None => {
+                    write!(self.out, "M")?;
+                    write_fixed(&mut self.out, start, d)?;
+                }
+                Some((cx, cy)) => {
+                    write!(self.out, "m")?;
+                    write_fixed(&mut self.out, (start.0 - cx, start.1 - cy), d)?;
+                }
+            }
+            cur = Some(start);
+
+            let mut cmd = "l";
+            for p in pts {
+                let (cx, cy) = cur.unwrap();
+
+                write!(self.out, "{cmd}")?;
+                write_fixed(&mut self.out, (p.0 - cx, p.1 - cy), d)?;
+
+                cur = Some(p);
+                cmd = " ";
+            }
+        }
+
+        write!(self.out, r#"""#)?;
+        style.write(&mut self.out)?;
+        writeln!(self.out, " />")
+    }
+
+    /// Add a path made of the given commands.
+    pub fn path(
+        &mut self,
+        cmds: impl IntoIterator<Item = PathCommand>,
+        style: &Style,
+    ) -> io::Result<()> {
+        let d = self.digits;
+        write!(self.out, r#"<path d=""#)?;
+        for cmd in cmds {
+            match cmd {
+                PathCommand::MoveTo((x, y)) => write!(self.out, "M{x:.d$},{y:.d$}")?,
+                PathCommand::LineTo((x, y)) => write!(self.out, "L{x:.d$},{y:.d$}")?,
+                PathCommand::QuadTo((cx, cy), (x, y)) => {
+                    write!(self.out, "Q{cx:.d$},{cy:.d$} {x:.d$},{y:.d$}")?
+                }
+                PathCommand::CubicTo((c1x, c1y), (c2x, c2y), (x, y)) => write!(
+                    self.out,
+                    "C{c1x:.d$},{c1y:.d$} {c2x:.d$},{c2y:.d$} {x:.d$},{y:.d$}"
+                )?,
+                PathCommand::Close => write!(self.out, "Z")?,
+            }
+        }
+        write!(self.out, r#"""#)?;
+        style.write(&mut self.out)?;
+        writeln!(self.out, " />")
+    }
+
+    /// Add a text label whose baseline starts at the given point.
+    pub fn text(&mut self, (x, y): XY, size: f64, text: &str, style: &Style) -> io::Result<()> {
+        let d = self.digits;
+        write!(
+            self.out,
+            r#"<text x="{x:.d$}" y="{y:.d$}" font-size="{size}""#
+        )?;
+        style.write(&mut self.out)?;
+        write!(self.out, ">")?;
+        write_escaped(&mut self.out, text)?;
+        writeln!(self.out, "</text>")
+    }
+
+    /// Define a linear gradient with the given `id` that can be referenced as
+    /// `url(#id)` in a fill or a stroke.
+    ///
+    /// The gradient goes from `start` to `end`, both in the coordinate system
+    /// of the bounding box of the element it's applied to, i.e. (0, 0) is its
+    /// top left corner while (1, 1) is its bottom right one. Each stop is an
+    /// offset in [0, 1] along the gradient and the color there.
+    pub fn linear_gradient(
+        &mut self,
+        id: &str,
+        (x1, y1): XY,
+        (x2, y2): XY,
+        stops: &[(f64, &str)],
+    ) -> io::Result<()> {
+        let d = self.digits;
+        write!(self.out, r#"<defs><linearGradient id=""#)?;
+        write_escaped(&mut self.out, id)?;
+        writeln!(
+            self.out,
+            r#"" x1="{x1:.d$}" y1="{y1:.d$}" x2="{x2:.d$}" y2="{y2:.d$}">"#
+        )?;
+
+        for (offset, color) in stops {
+            write!(self.out, r#"<stop offset="{offset}""#)?;
+            write_attr(&mut self.out, "stop-color", color)?;
+            writeln!(self.out, " />")?;
+        }
+
+        writeln!(self.out, "</linearGradient></defs>")
+    }
+
+    /// Open a new group, all the elements added until the matching
+    /// `SvgWriter::end_group` are part of it and inherit its style.
+    pub fn begin_group(&mut self, style: &Style) -> io::Result<()> {
+        write!(self.out, "<g")?;
+        style.write(&mut self.out)?;
+        writeln!(self.out, ">")?;
+
+        self.open_groups += 1;
+        Ok(())
+    }
+
+    /// Open a new group that is only visible for `duration` seconds starting
+    /// at `start` of a [SMIL][0] animation `total` seconds long that repeats
+    /// forever. Showing a group per frame one after the other plays the
+    /// frames as an animation.
+    ///
+    /// [0]: https://developer.mozilla.org/en-US/docs/Web/SVG/SVG_animation_with_SMIL
+    pub fn begin_frame(
+        &mut self,
+        start: f64,
+        duration: f64,
+        total: f64,
+        style: &Style,
+    ) -> io::Result<()> {
+        assert!(total > 0.0 && start >= 0.0 && duration > 0.0 && start + duration <= total);
+
+        let (on, off) = (start / total, (start + duration) / total);
+
+        // discrete animations require the first key time to be 0 while the
+        // others are optional
+        let mut keys = vec![];
+        if on > 0.0 {
+            keys.push(("hidden", 0.0));
+        }
+        keys.push(("visible", on));
+        if off < 1.0 {
+            keys.push(("hidden", off));
+        }
+
+        let values = keys.iter().map(|(v, _)| *v).collect::<Vec<_>>().join(";");
+        let times = keys
+            .iter()
+            .map(|(_, t)| t.to_string())
+            .collect::<Vec<_>>()
+            .join(";");
+
+        write!(self.out, r#"<g visibility="hidden""#)?;
+        style.write(&mut self.out)?;
+        writeln!(self.out, ">")?;
+        writeln!(
+            self.out,
+            r#"<animate attributeName="visibility" values="{values}" keyTimes="{times}" dur="{total}s" calcMode="discrete" repeatCount="indefinite" />"#
+        )?;
+
+        self.open_groups += 1;
+        Ok(())
+    }
+
+    /// Open a new group that Inkscape recognizes as a layer with the given
+    /// name. This is particularly handy when plotting since each layer can be
+    /// plotted with a different pen.
+    pub fn begin_layer(&mut self, name: &str, style: &Style) -> io::Result<()> {
+        write!(
+            self.out,
+            r#"<g inkscape:groupmode="layer" inkscape:label=""#
+        )?;
+        write_escaped(&mut self.out, name)?;
+        write!(self.out, r#"""#)?;
+        style.write(&mut self.out)?;
+        writeln!(self.out, ">")?;
+
+        self.open_groups += 1;
+        Ok(())
+    }
+
+    /// Close the last opened group or layer.
+    pub fn end_group(&mut self) -> io::Result<()> {
+        assert!(self.open_groups > 0, "no group to close");
+
+        self.open_groups -= 1;
+        writeln!(self.out, "</g>")
+    }
+
+    /// Close any open group and terminate the document returning the
+    /// underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        while self.open_groups > 0 {
+            self.end_group()?;
+        }
+
+        writeln!(self.out, "</svg>")?;
+        self.out.flush()?;
+
+        Ok(self.out)
+    }
+}
+
+impl<'s> Style<'s> {
+    /// Create an empty style that inherits all the attributes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_id(mut self, id: &'s str) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn with_stroke(mut self, stroke: &'s str) -> Self {
+        self.stroke = Some(stroke);
+        self
+    }
+
+    pub fn with_stroke_width(mut self, w: f64) -> Self {
+        self.stroke_width = Some(w);
+        self
+    }
+
+    pub fn with_fill(mut self, fill: &'s str) -> Self {
+        self.fill = Some(fill);
+        self
+    }
+
+    pub fn with_opacity(mut self, opacity: f64) -> Self {
+        self.opacity = Some(opacity);
+        self
+    }
+
+    /// Dash the stroke with the given pattern of dash and gap lengths, e.g.
+    /// `"4 2"`.
+    pub fn with_dasharray(mut self, dasharray: &'s str) -> Self {
+        self.dasharray = Some(dasharray);
+        self
+    }
+
+    fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        if let Some(id) = self.id {
+            write_attr(out, "id", id)?;
+        }
+        if let Some(stroke) = self.stroke {
+            write_attr(out, "stroke", stroke)?;
+        }
+        if let Some(w) = self.stroke_width {
+            write!(out, r#" stroke-width="{w}""#)?;
+        }
+        if let Some(fill) = self.fill {
+            write_attr(out, "fill", fill)?;
+        }
+        if let Some(opacity) = self.opacity {
+            write!(out, r#" opacity="{opacity}""#)?;
+        }
+        if let Some(dasharray) = self.dasharray {
+            write_attr(out, "stroke-dasharray", dasharray)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Write the given pair of fixed point numbers with `digits` decimal digits
+/// dropping all the trailing zeros.
+fn write_fixed(out: &mut impl Write, (x, y): (i64, i64), digits: usize) -> io::Result<()> {
+    let write_num = |out: &mut dyn Write, n: i64| -> io::Result<()> {
+        let scale = 10_u64.pow(digits as u32);
+
+        if n < 0 {
+            write!(out, "-")?;
+        }
+
+        let n = n.unsigned_abs();
+        write!(out, "{}", n / scale)?;
+
+        let (mut frac, mut digits) = (n % scale, digits);
+        if frac == 0 {
+            return Ok(());
+        }
+
+        while frac % 10 == 0 {
+            frac /= 10;
+            digits -= 1;
+        }
+
+        write!(out, ".{frac:0digits$}")
+    };
+
+    write_num(out, x)?;
+    write!(out, ",")?;
+    write_num(out, y)
+}
+
+/// Write the attribute with the given name and value, escaping the value.
+fn write_attr(out: &mut impl Write, name: &str, value: &str) -> io::Result<()> {
+    write!(out, r#" {name}=""#)?;
+    write_escaped(out, value)?;
+    write!(out, "\"")
+}
+
+fn write_escaped(out: &mut impl Write, s: &str) -> io::Result<()> {
+    for c in s.chars() {
+        match c {
+            '&' => write!(out, "&amp;")?,
+            '<' => write!(out, "&lt;")?,
+            '>' => write!(out, "&gt;")?,
+            '"' => write!(out, "&quot;")?,
+            c => write!(out, "{c}")?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse a SVG color into its RGB channels.
+///
+/// The color can be given as one of the [named colors][0], in the `#rgb` or
+/// `#rrggbb` hexadecimal notation or in the functional `rgb(r, g, b)` one.
+/// Returns None for the colors that can't be parsed, like `none`.
+///
+/// [0]: https://developer.mozilla.org/en-US/docs/Web/CSS/named-color
+pub fn parse_color(c: &str) -> Option<[u8; 3]> {
+    let c = c.trim().to_ascii_lowercase();
+
+    if let Some(hex) = c.strip_prefix('#') {
+        let channel = |i: usize, len: usize| {
+            let v = u8::from_str_radix(hex.get(i * len..(i + 1) * len)?, 16).ok()?;
+            Some(if len == 1 { v * 17 } else { v })
+        };
+
+        return match hex.len() {
+            3 => Some([channel(0, 1)?, channel(1, 1)?, channel(2, 1)?]),
+            6 => Some([channel(0, 2)?, channel(1, 2)?, channel(2, 2)?]),
+            _ => None,
+        };
+    }
+
+    if let Some(rgb) = c.strip_prefix("rgb(").and_then(|c| c.strip_suffix(')')) {
+        let mut channels = rgb.split(',').map(|c| c.trim().parse::<u8>().ok());
+        let rgb = [channels.next()??, channels.next()??, channels.next()??];
+        return channels.next().is_none().then_some(rgb);
+    }
+
+    NAMED_COLORS
+        .binary_search_by_key(&c.as_str(), |&(n, _)| n)
+        .ok()
+        .map(|i| NAMED_COLORS[i].1)
+}
+
+/// The named colors supported by SVG sorted by name.
+const NAMED_COLORS: [(&str, [u8; 3]); 148] = [
+    ("aliceblue", [240, 248, 255]),
+    ("antiquewhite", [250, 235, 215]),
+    ("aqua", [0, 255, 255]),
+    ("aquamarine", [127, 255, 212]),
+    ("azure", [240, 255, 255]),
+    ("beige", [245, 245, 220]),
+    ("bisque", [255, 228, 196]),
+    ("black", [0, 0, 0]),
+    ("blanchedalmond", [255, 235, 205]),
+    ("blue", [0, 0, 255]),
+    ("blueviolet", [138, 43, 226]),
+    ("brown", [165, 42, 42]),
+    ("burlywood", [222, 184, 135]),
+    ("cadetblue", [95, 158, 160]),
+    ("chartreuse", [127, 255, 0]),
+    ("chocolate", [210, 105, 30]),
+    ("coral", [255, 127, 80]),
+    ("cornflowerblue", [100, 149, 237]),
+    ("cornsilk", [255, 248, 220]),
+    ("crimson", [220, 20, 60]),
+    ("cyan", [0, 255, 255]),
+    ("darkblue", [0, 0, 139]),
+    ("darkcyan", [0, 139, 139]),
+    ("darkgoldenrod", [184, 134, 11]),
+    ("darkgray", [169, 169, 169]),
+    ("darkgreen", [0, 100, 0]),
+    ("darkgrey", [169, 169, 169]),
+    ("darkkhaki", [189, 183, 107]),
+    ("darkmagenta", [139, 0, 139]),
+    ("darkolivegreen", [85, 107, 47]),
+    ("darkorange", [255, 140, 0]),
+    ("darkorchid", [153, 50, 204]),
+    ("darkred", [139, 0, 0]),
+    ("darksalmon", [233, 150, 122]),
+    ("darkseagreen", [143, 188, 143]),
+    ("darkslateblue", [72, 61, 139]),
+    ("darkslategray", [47, 79, 79]),
+    ("darkslategrey", [47, 79, 79]),
+    ("darkturquoise", [0, 206, 209]),
+    ("darkviolet", [148, 0, 211]),
+    ("deeppink", [255, 20, 147]),
+    ("deepskyblue", [0, 191, 255]),
+    ("dimgray", [105, 105, 105]),
+    ("dimgrey", [105, 105, 105]),
+    ("dodgerblue", [30, 144, 255]),
+    ("firebrick", [178, 34, 34]),
+    ("floralwhite", [255, 250, 240]),
+    ("forestgreen", [34, 139, 34]),
+    ("fuchsia", [255, 0, 255]),
+    ("gainsboro", [220, 220, 220]),
+    ("ghostwhite", [248, 248, 255]),
+    ("gold", [255, 215, 0]),
+    ("goldenrod", [218, 165, 32]),
+    ("gray", [128, 128, 128]),
+    ("green", [0, 128, 0]),
+    ("greenyellow", [173, 255, 47]),
+    ("grey", [128, 128, 128]),
+    ("honeydew", [240, 255, 240]),
+    ("hotpink", [255, 105, 180]),
+    ("indianred", [205, 92, 92]),
+    ("indigo", [75, 0, 130]),
+    ("ivory", [255, 255, 240]),
+    ("khaki", [240, 230, 140]),
+    ("lavender", [230, 230, 250]),
+    ("lavenderblush", [255, 240, 245]),
+    ("lawngreen", [124, 252, 0]),
+    ("lemonchiffon", [255, 250, 205]),
+    ("lightblue", [173, 216, 230]),
+    ("lightcoral", [240, 128, 128]),
+    ("lightcyan", [224, 255, 255]),
+    ("lightgoldenrodyellow", [250, 250, 210]),
+    ("lightgray", [211, 211, 211]),
+    ("lightgreen", [144, 238, 144]),
+    ("lightgrey", [211, 211, 211]),
+    ("lightpink", [255, 182, 193]),
+    ("lightsalmon", [255, 160, 122]),
+    ("lightseagreen", [32, 178, 170]),
+    ("lightskyblue", [135, 206, 250]),
+    ("lightslategray", [119, 136, 153]),
+    ("lightslategrey", [119, 136, 153]),
+    ("lightsteelblue", [176, 196, 222]),
+    ("lightyellow", [255, 255, 224]),
+    ("lime", [0, 255, 0]),
+    ("limegreen", [50, 205, 50]),
+    ("linen", [250, 240, 230]),
+    ("magenta", [255, 0, 255]),
+    ("maroon", [128, 0, 0]),
+    ("mediumaquamarine", [102, 205, 170]),
+    ("mediumblue", [0, 0, 205]),
+    ("mediumorchid", [186, 85, 211]),
+    ("mediumpurple", [147, 112, 219]),
+    ("mediumseagreen", [60, 179, 113]),
+    ("mediumslateblue", [123, 104, 238]),
+    ("mediumspringgreen", [0, 250, 154]),
+    ("mediumturquoise", [72, 209, 204]),
+    ("mediumvioletred", [199, 21, 133]),
+    ("midnightblue", [25, 25, 112]),
+    ("mintcream", [245, 255, 250]),
+    ("mistyrose", [255, 228, 225]),
+    ("moccasin", [255, 228, 181]),
+    ("navajowhite", [255, 222, 173]),
+    ("navy", [0, 0, 128]),
+    ("oldlace", [253, 245, 230]),
+    ("olive", [128, 128, 0]),
+    ("olivedrab", [107, 142, 35]),
+    ("orange", [255, 165, 0]),
+    ("orangered", [255, 69, 0]),
+    ("orchid", [218, 112, 214]),
+    ("palegoldenrod", [238, 232, 170]),
+    ("palegreen", [152, 251, 152]),
+    ("paleturquoise", [175, 238, 238]),
+    ("palevioletred", [219, 112, 147]),
+    ("papayawhip", [255, 239, 213]),
+    ("peachpuff", [255, 218, 185]),
+    ("peru", [205, 133, 63]),
+    ("pink", [255, 192, 203]),
+    ("plum", [221, 160, 221]),
+    ("powderblue", [176, 224, 230]),
+    ("purple", [128, 0, 128]),
+    ("rebeccapurple", [102, 51, 153]),
+    ("red", [255, 0, 0]),
+    ("rosybrown", [188, 143, 143]),
+    ("royalblue", [65, 105, 225]),
+    ("saddlebrown", [139, 69, 19]),
+    ("salmon", [250, 128, 114]),
+    ("sandybrown", [244, 164, 96]),
+    ("seagreen", [46, 139, 87]),
+    ("seashell", [255, 245, 238]),
+    ("sienna", [160, 82, 45]),
+    ("silver", [192, 192, 192]),
+    ("skyblue", [135, 206, 235]),
+    ("slateblue", [106, 90, 205]),
+    ("slategray", [112, 128, 144]),
+    ("slategrey", [112, 128, 144]),
+    ("snow", [255, 250, 250]),
+    ("springgreen", [0, 255, 127]),
+    ("steelblue", [70, 130, 180]),
+    ("tan", [210, 180, 140]),
+    ("teal", [0, 128, 128]),
+    ("thistle", [216, 191, 216]),
+    ("tomato", [255, 99, 71]),
+    ("turquoise", [64, 224, 208]),
+    ("violet", [238, 130, 238]),
+    ("wheat", [245, 222, 179]),
+    ("white", [255, 255, 255]),
+    ("whitesmoke", [245, 245, 245]),
+    ("yellow", [255, 255, 0]),
+    ("yellowgreen", [154, 205, 50]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document() {
+        let mut svg = SvgWriter::new(vec![], (0.0, 0.0, 10.0, 10.0))
+            .unwrap()
+            .with_digits(1);
+
+        svg.begin_layer("pen <1>", &Style::new().with_stroke("black"))
+            .unwrap();
+        svg.polyline(vec![(0.0, 0.0), (1.0, 2.0)], &Style::new())
+            .unwrap();
+        svg.path(
+            vec![
+                PathCommand::MoveTo((0.0, 0.0)),
+                PathCommand::CubicTo((1.0, 0.0), (1.0, 1.0), (0.0, 1.0)),
+                PathCommand::Close,
+            ],
+            &Style::new().with_fill("none"),
+        )
+        .unwrap();
+        svg.circle((5.0, 5.0), 1.0, &Style::new().with_id("c"))
+            .unwrap();
+        svg.text((1.0, 9.0), 2.0, "a & b", &Style::new()).unwrap();
+
+        let out = String::from_utf8(svg.finish().unwrap()).unwrap();
+        let body = out.lines().skip(2).collect::<Vec<_>>();
+
+        assert_eq!(
+            body,
+            vec![
+                r#"<g inkscape:groupmode="layer" inkscape:label="pen &lt;1&gt;" stroke="black">"#,
+                r#"<polyline points="0.0,0.0 1.0,2.0 " />"#,
+                r#"<path d="M0.0,0.0C1.0,0.0 1.0,1.0 0.0,1.0Z" fill="none" />"#,
+                r#"<circle cx="5.0" cy="5.0" r="1.0" id="c"/>"#,
+                r#"<text x="1.0" y="9.0" font-size="2">a &amp; b</text>"#,
+                "</g>",
+                "</svg>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escaped_style() {
+        let mut svg = SvgWriter::new(vec![], (0.0, 0.0, 10.0, 10.0))
+            .unwrap()
+            .with_digits(1);
+
+        let style = Style::new()
+            .with_id("a\"b")
+            .with_stroke("<red>")
+            .with_stroke_width(0.5)
+            .with_fill("url(#a&b)")
+            .with_dasharray("1 \"2\"");
+        svg.rect((0.0, 0.0), (1.0, 1.0), &style).unwrap();
+        svg.linear_gradient("g", (0.0, 0.0), (1.0, 0.0), &[(0.0, "\"/><x")])
+            .unwrap();
+
+        let out = String::from_utf8(svg.finish().unwrap()).unwrap();
+        let body = out.lines().skip(2).collect::<Vec<_>>();
+
+        assert_eq!(
+            body[..3],
+            [
+                r#"<rect x="0.0" y="0.0" width="1.0" height="1.0" id="a&quot;b" stroke="&lt;red&gt;" stroke-width="0.5" fill="url(#a&amp;b)" stroke-dasharray="1 &quot;2&quot;"/>"#,
+                r#"<defs><linearGradient id="g" x1="0.0" y1="0.0" x2="1.0" y2="0.0">"#,
+                r#"<stop offset="0" stop-color="&quot;/&gt;&lt;x" />"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_linear_gradient() {
+        let mut svg = SvgWriter::new(vec![], (0.0, 0.0, 10.0, 10.0))
+            .unwrap()
+            .with_digits(1);
+
+        svg.linear_gradient(
+            "g",
+            (0.0, 0.0),
+            (0.0, 1.0),
+            &[(0.0, "white"), (1.0, "gray")],
+        )
+        .unwrap();
+        svg.rect((0.0, 0.0), (1.0, 1.0), &Style::new().with_fill("url(#g)"))
+            .unwrap();
+
+        let out = String::from_utf8(svg.finish().unwrap()).unwrap();
+        let body = out.lines().skip(2).collect::<Vec<_>>();
+
+        assert_eq!(
+            body[..4],
+            [
+                r#"<defs><linearGradient id="g" x1="0.0" y1="0.0" x2="0.0" y2="1.0">"#,
+                r#"<stop offset="0" stop-color="white" />"#,
+                r#"<stop offset="1" stop-color="gray" />"#,
+                "</linearGradient></defs>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_frames() {
+        let mut svg = SvgWriter::new(vec![], (0.0, 0.0, 10.0, 10.0)).unwrap();
+
+        for i in 0..2 {
+            svg.begin_frame(f64::from(i) * 0.5, 0.5, 1.0, &Style::new())
+                .unwrap();
+            svg.end_group().unwrap();
+        }
+
+        let out = String::from_utf8(svg.finish().unwrap()).unwrap();
+        let body = out.lines().skip(2).collect::<Vec<_>>();
+
+        assert_eq!(
+            body,
+            vec![
+                r#"<g visibility="hidden">"#,
+                r#"<animate attributeName="visibility" values="visible;hidden" keyTimes="0;0.5" dur="1s" calcMode="discrete" repeatCount="indefinite" />"#,
+                "</g>",
+                r#"<g visibility="hidden">"#,
+                r#"<animate attributeName="visibility" values="hidden;visible" keyTimes="0;0.5" dur="1s" calcMode="discrete" repeatCount="indefinite" />"#,
+                "</g>",
+                "</svg>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_polylines() {
+        let mut svg = SvgWriter::new(vec![], (0.0, 0.0, 10.0, 10.0))
+            .unwrap()
+            .with_digits(2);
+
+        svg.polylines(
+            vec![
+                vec![(0.0, 0.0), (1.26, 0.5), (1.0, -0.05)],
+                vec![],
+                vec![(2.0, 2.0), (2.0, 3.1)],
+            ],
+            &Style::new().with_stroke("black"),
+        )
+        .unwrap();
+
+        let out = String::from_utf8(svg.finish().unwrap()).unwrap();
+        let body = out.lines().skip(2).collect::<Vec<_>>();
+
+        assert_eq!(
+            body,
+            vec![
+                r#"<path d="M0,0l1.26,0.5 -0.26,-0.55m1,2.05l0,1.1" stroke="black" />"#,
+                "</svg>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("black"), Some([0, 0, 0]));
+        assert_eq!(parse_color("White"), Some([255, 255, 255]));
+        assert_eq!(parse_color("grey"), parse_color("gray"));
+        assert_eq!(parse_color("lightgray"), Some([211, 211, 211]));
+        assert_eq!(parse_color("rebeccapurple"), Some([102, 51, 153]));
+        assert_eq!(parse_color("yellowgreen"), Some([154, 205, 50]));
+        assert_eq!(parse_color("aliceblue"), Some([240, 248, 255]));
+
+        assert_eq!(parse_color("#ff8000"), Some([255, 128, 0]));
+        assert_eq!(parse_color("#F80"), Some([255, 136, 0]));
+        assert_eq!(parse_color(" rgb(1, 2,3) "), Some([1, 2, 3]));
+
+        for invalid in [
+            "none",
+            "",
+            "#",
+            "#12345",
+            "#gg0000",
+            "rgb(1, 2)",
+            "rgb(1, 2, 3, 4)",
+            "rgb(256, 0, 0)",
+        ] {
+            assert_eq!(parse_color(invalid), None, "{invalid}");
+        }
+
+        assert!(NAMED_COLORS.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+}